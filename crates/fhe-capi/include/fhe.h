@@ -0,0 +1,141 @@
+/*
+ * C API for the BFV encryption scheme of the fhe.rs library.
+ *
+ * Every fallible function returns an FheStatus, equal to FHE_STATUS_OK on
+ * success; outputs are only written on success.
+ *
+ * Ownership rules:
+ * - Handles are returned through an `out` pointer and owned by the caller,
+ *   which must release each of them exactly once with the matching `*_free`
+ *   function. Freeing a NULL handle is a no-op.
+ * - Input handles and arrays are only borrowed during the call, and handles
+ *   never reference each other.
+ * - Serialized bytes are returned in an FheBuffer owned by the caller, to be
+ *   released with fhe_buffer_free.
+ */
+
+#ifndef FHE_H
+#define FHE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum FheStatus {
+  FHE_STATUS_OK = 0,
+  FHE_STATUS_NULL_POINTER = 1,
+  FHE_STATUS_INVALID_ARGUMENT = 2,
+  FHE_STATUS_PARAMETERS_ERROR = 3,
+  FHE_STATUS_SERIALIZATION_ERROR = 4,
+  FHE_STATUS_ENTROPY_ERROR = 5,
+  FHE_STATUS_ERROR = 6,
+  FHE_STATUS_PANIC = 7,
+} FheStatus;
+
+typedef enum FheEncoding {
+  FHE_ENCODING_POLY = 0,
+  FHE_ENCODING_SIMD = 1,
+} FheEncoding;
+
+typedef struct FheBuffer {
+  uint8_t *data;
+  size_t len;
+} FheBuffer;
+
+typedef struct FheParameters FheParameters;
+typedef struct FheSecretKey FheSecretKey;
+typedef struct FhePublicKey FhePublicKey;
+typedef struct FheRelinearizationKey FheRelinearizationKey;
+typedef struct FheEvaluationKey FheEvaluationKey;
+typedef struct FheCiphertext FheCiphertext;
+
+/* Parameters */
+FheStatus fhe_parameters_new(size_t degree, uint64_t plaintext_modulus,
+                             const size_t *moduli_sizes, size_t num_moduli,
+                             FheParameters **out);
+FheStatus fhe_parameters_new_insecure(size_t degree, uint64_t plaintext_modulus,
+                                      const size_t *moduli_sizes,
+                                      size_t num_moduli, FheParameters **out);
+size_t fhe_parameters_degree(const FheParameters *par);
+void fhe_parameters_free(FheParameters *par);
+
+/* Keys */
+FheStatus fhe_secret_key_generate(const FheParameters *par,
+                                  FheSecretKey **out);
+void fhe_secret_key_free(FheSecretKey *sk);
+FheStatus fhe_public_key_generate(const FheSecretKey *sk, FhePublicKey **out);
+void fhe_public_key_free(FhePublicKey *pk);
+FheStatus fhe_relinearization_key_generate(const FheSecretKey *sk,
+                                           FheRelinearizationKey **out);
+void fhe_relinearization_key_free(FheRelinearizationKey *rk);
+FheStatus fhe_evaluation_key_generate(const FheSecretKey *sk,
+                                      bool row_rotation,
+                                      const size_t *column_rotations,
+                                      size_t num_column_rotations,
+                                      FheEvaluationKey **out);
+void fhe_evaluation_key_free(FheEvaluationKey *ek);
+
+/* Encryption and decryption; `len` must equal the degree for decryption. */
+FheStatus fhe_public_key_encrypt(const FhePublicKey *pk, const uint64_t *values,
+                                 size_t len, FheEncoding encoding,
+                                 FheCiphertext **out);
+FheStatus fhe_secret_key_encrypt(const FheSecretKey *sk, const uint64_t *values,
+                                 size_t len, FheEncoding encoding,
+                                 FheCiphertext **out);
+FheStatus fhe_secret_key_decrypt(const FheSecretKey *sk,
+                                 const FheCiphertext *ct, FheEncoding encoding,
+                                 uint64_t *out, size_t len);
+
+/* Homomorphic operations */
+FheStatus fhe_ciphertext_add(const FheCiphertext *a, const FheCiphertext *b,
+                             FheCiphertext **out);
+FheStatus fhe_ciphertext_sub(const FheCiphertext *a, const FheCiphertext *b,
+                             FheCiphertext **out);
+FheStatus fhe_ciphertext_mul(const FheRelinearizationKey *rk,
+                             const FheCiphertext *a, const FheCiphertext *b,
+                             FheCiphertext **out);
+FheStatus fhe_ciphertext_rotate_rows(const FheEvaluationKey *ek,
+                                     const FheCiphertext *ct,
+                                     FheCiphertext **out);
+FheStatus fhe_ciphertext_rotate_columns(const FheEvaluationKey *ek,
+                                        const FheCiphertext *ct, size_t steps,
+                                        FheCiphertext **out);
+void fhe_ciphertext_free(FheCiphertext *ct);
+
+/* Serialization */
+FheStatus fhe_parameters_serialize(const FheParameters *par, FheBuffer *out);
+FheStatus fhe_parameters_deserialize(const uint8_t *data, size_t len,
+                                     FheParameters **out);
+FheStatus fhe_secret_key_serialize(const FheSecretKey *sk, FheBuffer *out);
+FheStatus fhe_secret_key_deserialize(const FheParameters *par,
+                                     const uint8_t *data, size_t len,
+                                     FheSecretKey **out);
+FheStatus fhe_public_key_serialize(const FhePublicKey *pk, FheBuffer *out);
+FheStatus fhe_public_key_deserialize(const FheParameters *par,
+                                     const uint8_t *data, size_t len,
+                                     FhePublicKey **out);
+FheStatus fhe_relinearization_key_serialize(const FheRelinearizationKey *rk,
+                                            FheBuffer *out);
+FheStatus fhe_relinearization_key_deserialize(const FheParameters *par,
+                                              const uint8_t *data, size_t len,
+                                              FheRelinearizationKey **out);
+FheStatus fhe_evaluation_key_serialize(const FheEvaluationKey *ek,
+                                       FheBuffer *out);
+FheStatus fhe_evaluation_key_deserialize(const FheParameters *par,
+                                         const uint8_t *data, size_t len,
+                                         FheEvaluationKey **out);
+FheStatus fhe_ciphertext_serialize(const FheCiphertext *ct, FheBuffer *out);
+FheStatus fhe_ciphertext_deserialize(const FheParameters *par,
+                                     const uint8_t *data, size_t len,
+                                     FheCiphertext **out);
+void fhe_buffer_free(FheBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FHE_H */
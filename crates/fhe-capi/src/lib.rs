@@ -0,0 +1,984 @@
+#![crate_name = "fhe_capi"]
+#![warn(missing_docs, unused_imports)]
+
+//! C API for the BFV encryption scheme of the `fhe` crate.
+//!
+//! The library is built as a `cdylib` and a `staticlib`, and the functions are
+//! declared in `include/fhe.h`.
+//!
+//! # Error codes
+//!
+//! Every fallible function returns an [`FheStatus`], equal to
+//! [`FheStatus::Ok`] on success. Outputs are only written on success. A panic
+//! is caught at the boundary and reported as [`FheStatus::Panic`].
+//!
+//! # Ownership
+//!
+//! - Objects (parameters, keys and ciphertexts) are opaque handles created by
+//!   the library and returned through an `out` pointer. The caller owns each
+//!   handle and must release it exactly once with the matching `*_free`
+//!   function. Freeing a null handle is a no-op.
+//! - Input handles are only borrowed during the call, and handles never
+//!   reference each other: a handle can be freed while handles created from
+//!   it are still in use.
+//! - Serialized bytes are returned in an [`FheBuffer`] owned by the caller,
+//!   which must be released with [`fhe_buffer_free`]. Input byte and value
+//!   arrays are only read during the call.
+//!
+//! # Safety
+//!
+//! All the pointer arguments must either be null (which is reported as
+//! [`FheStatus::NullPointer`]) or valid for the duration of the call: handles
+//! must have been returned by this library and not freed yet, and arrays must
+//! contain at least the given number of elements. A handle must not be freed
+//! by one thread while it is used by another thread.
+
+use fhe::bfv::{
+    BfvParameters, BfvParametersBuilder, Ciphertext, Encoding, EvaluationKey, EvaluationKeyBuilder,
+    Multiplicator, Plaintext, PublicKey, RelinearizationKey, SecretKey,
+};
+use fhe::entropy::{default_rng, EntropyRng};
+use fhe::Error;
+use fhe_traits::{
+    Deserialize, DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter,
+    Serialize,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Status code returned by the functions of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FheStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was invalid, e.g. a number of values or incompatible
+    /// objects.
+    InvalidArgument = 2,
+    /// The parameters could not be created.
+    ParametersError = 3,
+    /// The bytes could not be deserialized.
+    SerializationError = 4,
+    /// The operating system random number generator failed.
+    EntropyError = 5,
+    /// Another error occurred in the library.
+    Error = 6,
+    /// The library panicked.
+    Panic = 7,
+}
+
+impl From<Error> for FheStatus {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::TooManyValues(..)
+            | Error::TooFewValues(..)
+            | Error::UnspecifiedInput(_)
+            | Error::EncodingMismatch(..)
+            | Error::EncodingNotSupported(_) => FheStatus::InvalidArgument,
+            Error::ParametersError(_) => FheStatus::ParametersError,
+            Error::SerializationError => FheStatus::SerializationError,
+            Error::EntropyError(_) => FheStatus::EntropyError,
+            _ => FheStatus::Error,
+        }
+    }
+}
+
+/// Encoding of the plaintext values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FheEncoding {
+    /// The values are the coefficients of the plaintext polynomial.
+    Poly = 0,
+    /// The values are encoded in SIMD slots.
+    Simd = 1,
+}
+
+impl From<FheEncoding> for Encoding {
+    fn from(encoding: FheEncoding) -> Self {
+        match encoding {
+            FheEncoding::Poly => Encoding::poly(),
+            FheEncoding::Simd => Encoding::simd(),
+        }
+    }
+}
+
+/// A byte buffer owned by the caller, to be released with [`fhe_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct FheBuffer {
+    /// Pointer to the bytes.
+    pub data: *mut u8,
+    /// Number of bytes.
+    pub len: usize,
+}
+
+/// Opaque handle to BFV parameters.
+pub struct FheParameters(Arc<BfvParameters>);
+
+/// An object together with the parameters it was created for.
+pub struct Handle<T> {
+    par: Arc<BfvParameters>,
+    value: T,
+}
+
+/// Opaque handle to a secret key.
+pub type FheSecretKey = Handle<SecretKey>;
+
+/// Opaque handle to a public key.
+pub type FhePublicKey = Handle<PublicKey>;
+
+/// Opaque handle to a relinearization key, together with the multiplication
+/// strategy using it.
+pub type FheRelinearizationKey = Handle<(RelinearizationKey, Multiplicator)>;
+
+/// Opaque handle to an evaluation key, used for rotations.
+pub type FheEvaluationKey = Handle<EvaluationKey>;
+
+/// Opaque handle to a ciphertext.
+pub type FheCiphertext = Handle<Ciphertext>;
+
+impl<T> Handle<T> {
+    fn new(par: &Arc<BfvParameters>, value: T) -> Self {
+        Self {
+            par: par.clone(),
+            value,
+        }
+    }
+
+    /// Returns an error if the objects were created for different parameters.
+    fn check_parameters<U>(&self, other: &Handle<U>) -> Result<(), FheStatus> {
+        if self.par != other.par {
+            Err(FheStatus::InvalidArgument)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns the handle of a relinearization key and its multiplication
+/// strategy.
+fn relinearization_handle(
+    par: &Arc<BfvParameters>,
+    rk: RelinearizationKey,
+) -> Result<FheRelinearizationKey, Error> {
+    let multiplicator = Multiplicator::default(&rk)?;
+    Ok(Handle::new(par, (rk, multiplicator)))
+}
+
+/// Runs `f`, converting panics into [`FheStatus::Panic`].
+fn guard<F: FnOnce() -> Result<(), FheStatus>>(f: F) -> FheStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => FheStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => FheStatus::Panic,
+    }
+}
+
+fn rng() -> Result<EntropyRng, FheStatus> {
+    default_rng().map_err(FheStatus::from)
+}
+
+/// Dereferences a handle.
+unsafe fn borrow<'a, T>(p: *const T) -> Result<&'a T, FheStatus> {
+    p.as_ref().ok_or(FheStatus::NullPointer)
+}
+
+/// Returns a slice of `len` elements, where `p` may only be null if `len` is 0.
+unsafe fn slice<'a, T>(p: *const T, len: usize) -> Result<&'a [T], FheStatus> {
+    if len == 0 {
+        Ok(&[])
+    } else if p.is_null() {
+        Err(FheStatus::NullPointer)
+    } else {
+        Ok(std::slice::from_raw_parts(p, len))
+    }
+}
+
+/// Checks that an output pointer is not null.
+fn check_out<T>(out: *mut T) -> Result<(), FheStatus> {
+    if out.is_null() {
+        Err(FheStatus::NullPointer)
+    } else {
+        Ok(())
+    }
+}
+
+/// Moves `value` into a new handle written to `out`, which must not be null.
+unsafe fn write_handle<T>(out: *mut *mut T, value: T) -> Result<(), FheStatus> {
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+/// Releases a handle created by [`write_handle`].
+unsafe fn free_handle<T>(p: *mut T) {
+    if !p.is_null() {
+        drop(Box::from_raw(p))
+    }
+}
+
+/// Moves `bytes` into a buffer written to `out`, which must not be null.
+unsafe fn write_buffer(out: *mut FheBuffer, bytes: Vec<u8>) -> Result<(), FheStatus> {
+    let len = bytes.len();
+    let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+    *out = FheBuffer { data, len };
+    Ok(())
+}
+
+/// Create BFV parameters with a polynomial `degree`, a `plaintext_modulus`,
+/// and `num_moduli` ciphertext moduli of the given bit sizes.
+///
+/// Returns `ParametersError` if the parameters do not provide 128 bits of
+/// security.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_new(
+    degree: usize,
+    plaintext_modulus: u64,
+    moduli_sizes: *const usize,
+    num_moduli: usize,
+    out: *mut *mut FheParameters,
+) -> FheStatus {
+    guard(|| {
+        parameters_new(
+            degree,
+            plaintext_modulus,
+            moduli_sizes,
+            num_moduli,
+            false,
+            out,
+        )
+    })
+}
+
+/// Create BFV parameters as [`fhe_parameters_new`], without checking their
+/// security, e.g. for tests with small degrees.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_new_insecure(
+    degree: usize,
+    plaintext_modulus: u64,
+    moduli_sizes: *const usize,
+    num_moduli: usize,
+    out: *mut *mut FheParameters,
+) -> FheStatus {
+    guard(|| {
+        parameters_new(
+            degree,
+            plaintext_modulus,
+            moduli_sizes,
+            num_moduli,
+            true,
+            out,
+        )
+    })
+}
+
+/// Creates the parameters of [`fhe_parameters_new`], without checking their
+/// security if `insecure`.
+unsafe fn parameters_new(
+    degree: usize,
+    plaintext_modulus: u64,
+    moduli_sizes: *const usize,
+    num_moduli: usize,
+    insecure: bool,
+    out: *mut *mut FheParameters,
+) -> Result<(), FheStatus> {
+    let sizes = slice(moduli_sizes, num_moduli)?;
+    check_out(out)?;
+    let mut builder = BfvParametersBuilder::new();
+    builder
+        .set_degree(degree)
+        .set_plaintext_modulus(plaintext_modulus)
+        .set_moduli_sizes(sizes);
+    if insecure {
+        builder.allow_insecure();
+    }
+    write_handle(out, FheParameters(builder.build_arc()?))
+}
+
+/// Returns the polynomial degree of the parameters, i.e. the number of values
+/// in a plaintext, or 0 if `par` is null.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_degree(par: *const FheParameters) -> usize {
+    par.as_ref().map_or(0, |par| par.0.degree())
+}
+
+/// Release parameters.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_free(par: *mut FheParameters) {
+    free_handle(par)
+}
+
+/// Generate a secret key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_secret_key_generate(
+    par: *const FheParameters,
+    out: *mut *mut FheSecretKey,
+) -> FheStatus {
+    guard(|| {
+        let par = borrow(par)?;
+        check_out(out)?;
+        let sk = SecretKey::random(&par.0, &mut rng()?);
+        write_handle(out, Handle::new(&par.0, sk))
+    })
+}
+
+/// Release a secret key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_secret_key_free(sk: *mut FheSecretKey) {
+    free_handle(sk)
+}
+
+/// Generate a public key from a secret key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_public_key_generate(
+    sk: *const FheSecretKey,
+    out: *mut *mut FhePublicKey,
+) -> FheStatus {
+    guard(|| {
+        let sk = borrow(sk)?;
+        check_out(out)?;
+        let pk = PublicKey::new(&sk.value, &mut rng()?);
+        write_handle(out, Handle::new(&sk.par, pk))
+    })
+}
+
+/// Release a public key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_public_key_free(pk: *mut FhePublicKey) {
+    free_handle(pk)
+}
+
+/// Generate a relinearization key from a secret key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_relinearization_key_generate(
+    sk: *const FheSecretKey,
+    out: *mut *mut FheRelinearizationKey,
+) -> FheStatus {
+    guard(|| {
+        let sk = borrow(sk)?;
+        check_out(out)?;
+        let rk = RelinearizationKey::new(&sk.value, &mut rng()?)?;
+        write_handle(out, relinearization_handle(&sk.par, rk)?)
+    })
+}
+
+/// Release a relinearization key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_relinearization_key_free(rk: *mut FheRelinearizationKey) {
+    free_handle(rk)
+}
+
+/// Generate an evaluation key from a secret key, supporting the rotation of
+/// the rows of SIMD slots if `row_rotation` is true, and the rotations of the
+/// columns by each of the `num_column_rotations` values of
+/// `column_rotations`.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_evaluation_key_generate(
+    sk: *const FheSecretKey,
+    row_rotation: bool,
+    column_rotations: *const usize,
+    num_column_rotations: usize,
+    out: *mut *mut FheEvaluationKey,
+) -> FheStatus {
+    guard(|| {
+        let sk = borrow(sk)?;
+        let column_rotations = slice(column_rotations, num_column_rotations)?;
+        check_out(out)?;
+        let mut builder = EvaluationKeyBuilder::new(&sk.value)?;
+        if row_rotation {
+            builder.enable_row_rotation()?;
+        }
+        for i in column_rotations {
+            builder.enable_column_rotation(*i)?;
+        }
+        let ek = builder.build(&mut rng()?)?;
+        write_handle(out, Handle::new(&sk.par, ek))
+    })
+}
+
+/// Release an evaluation key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_evaluation_key_free(ek: *mut FheEvaluationKey) {
+    free_handle(ek)
+}
+
+/// Encrypt at most `degree` values with a public key. The values must be
+/// reduced modulo the plaintext modulus.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_public_key_encrypt(
+    pk: *const FhePublicKey,
+    values: *const u64,
+    len: usize,
+    encoding: FheEncoding,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let pk = borrow(pk)?;
+        let values = slice(values, len)?;
+        check_out(out)?;
+        let pt = Plaintext::try_encode(values, encoding.into(), &pk.par)?;
+        let ct = pk.value.try_encrypt(&pt, &mut rng()?)?;
+        write_handle(out, Handle::new(&pk.par, ct))
+    })
+}
+
+/// Encrypt at most `degree` values with a secret key. The values must be
+/// reduced modulo the plaintext modulus.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_secret_key_encrypt(
+    sk: *const FheSecretKey,
+    values: *const u64,
+    len: usize,
+    encoding: FheEncoding,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let sk = borrow(sk)?;
+        let values = slice(values, len)?;
+        check_out(out)?;
+        let pt = Plaintext::try_encode(values, encoding.into(), &sk.par)?;
+        let ct = sk.value.try_encrypt(&pt, &mut rng()?)?;
+        write_handle(out, Handle::new(&sk.par, ct))
+    })
+}
+
+/// Decrypt a ciphertext into `len` values, where `len` must be the degree of
+/// the parameters.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_secret_key_decrypt(
+    sk: *const FheSecretKey,
+    ct: *const FheCiphertext,
+    encoding: FheEncoding,
+    out: *mut u64,
+    len: usize,
+) -> FheStatus {
+    guard(|| {
+        let sk = borrow(sk)?;
+        let ct = borrow(ct)?;
+        check_out(out)?;
+        sk.check_parameters(ct)?;
+        if len != sk.par.degree() {
+            return Err(FheStatus::InvalidArgument);
+        }
+        let pt = sk.value.try_decrypt(&ct.value)?;
+        let values = Vec::<u64>::try_decode(&pt, Encoding::from(encoding))?;
+        std::slice::from_raw_parts_mut(out, len).copy_from_slice(&values);
+        Ok(())
+    })
+}
+
+/// Returns an error if two ciphertexts cannot be combined.
+fn check_compatible(a: &FheCiphertext, b: &FheCiphertext) -> Result<(), FheStatus> {
+    a.check_parameters(b)?;
+    if a.value.level() != b.value.level() {
+        Err(FheStatus::InvalidArgument)
+    } else {
+        Ok(())
+    }
+}
+
+/// Add two ciphertexts.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_add(
+    a: *const FheCiphertext,
+    b: *const FheCiphertext,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let (a, b) = (borrow(a)?, borrow(b)?);
+        check_out(out)?;
+        check_compatible(a, b)?;
+        write_handle(out, Handle::new(&a.par, &a.value + &b.value))
+    })
+}
+
+/// Subtract the ciphertext `b` from the ciphertext `a`.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_sub(
+    a: *const FheCiphertext,
+    b: *const FheCiphertext,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let (a, b) = (borrow(a)?, borrow(b)?);
+        check_out(out)?;
+        check_compatible(a, b)?;
+        write_handle(out, Handle::new(&a.par, &a.value - &b.value))
+    })
+}
+
+/// Multiply two ciphertexts and relinearize the product.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_mul(
+    rk: *const FheRelinearizationKey,
+    a: *const FheCiphertext,
+    b: *const FheCiphertext,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let rk = borrow(rk)?;
+        let (a, b) = (borrow(a)?, borrow(b)?);
+        check_out(out)?;
+        check_compatible(a, b)?;
+        rk.check_parameters(a)?;
+        let product = rk.value.1.multiply(&a.value, &b.value)?;
+        write_handle(out, Handle::new(&a.par, product))
+    })
+}
+
+/// Rotate the two rows of SIMD slots of a ciphertext.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_rotate_rows(
+    ek: *const FheEvaluationKey,
+    ct: *const FheCiphertext,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let (ek, ct) = (borrow(ek)?, borrow(ct)?);
+        check_out(out)?;
+        ek.check_parameters(ct)?;
+        let rotated = ek.value.rotates_rows(&ct.value)?;
+        write_handle(out, Handle::new(&ct.par, rotated))
+    })
+}
+
+/// Rotate the columns of SIMD slots of a ciphertext by `steps`.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_rotate_columns(
+    ek: *const FheEvaluationKey,
+    ct: *const FheCiphertext,
+    steps: usize,
+    out: *mut *mut FheCiphertext,
+) -> FheStatus {
+    guard(|| {
+        let (ek, ct) = (borrow(ek)?, borrow(ct)?);
+        check_out(out)?;
+        ek.check_parameters(ct)?;
+        let rotated = ek.value.rotates_columns_by(&ct.value, steps)?;
+        write_handle(out, Handle::new(&ct.par, rotated))
+    })
+}
+
+/// Release a ciphertext.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_ciphertext_free(ct: *mut FheCiphertext) {
+    free_handle(ct)
+}
+
+/// Serialize parameters.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_serialize(
+    par: *const FheParameters,
+    out: *mut FheBuffer,
+) -> FheStatus {
+    guard(|| {
+        let par = borrow(par)?;
+        check_out(out)?;
+        write_buffer(out, par.0.to_bytes())
+    })
+}
+
+/// Deserialize parameters.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_parameters_deserialize(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut FheParameters,
+) -> FheStatus {
+    guard(|| {
+        let bytes = slice(data, len)?;
+        check_out(out)?;
+        let par = BfvParameters::try_deserialize(bytes)?;
+        write_handle(out, FheParameters(Arc::new(par)))
+    })
+}
+
+/// Defines the serialization functions of a handle wrapping a type
+/// implementing `Serialize` and `DeserializeParametrized`.
+macro_rules! serialization {
+    ($handle:ident, $inner:ty, $what:literal, $serialize:ident, $deserialize:ident) => {
+        #[doc = concat!("Serialize ", $what, ".")]
+        ///
+        /// # Safety
+        ///
+        /// See the [crate documentation](crate#safety).
+        #[no_mangle]
+        pub unsafe extern "C" fn $serialize(
+            value: *const $handle,
+            out: *mut FheBuffer,
+        ) -> FheStatus {
+            guard(|| {
+                let value = borrow(value)?;
+                check_out(out)?;
+                write_buffer(out, value.value.to_bytes())
+            })
+        }
+
+        #[doc = concat!("Deserialize ", $what, " for the given parameters.")]
+        ///
+        /// # Safety
+        ///
+        /// See the [crate documentation](crate#safety).
+        #[no_mangle]
+        pub unsafe extern "C" fn $deserialize(
+            par: *const FheParameters,
+            data: *const u8,
+            len: usize,
+            out: *mut *mut $handle,
+        ) -> FheStatus {
+            guard(|| {
+                let par = borrow(par)?;
+                let bytes = slice(data, len)?;
+                check_out(out)?;
+                let value = <$inner>::from_bytes(bytes, &par.0)?;
+                write_handle(out, Handle::new(&par.0, value))
+            })
+        }
+    };
+}
+
+serialization!(
+    FheSecretKey,
+    SecretKey,
+    "a secret key",
+    fhe_secret_key_serialize,
+    fhe_secret_key_deserialize
+);
+serialization!(
+    FhePublicKey,
+    PublicKey,
+    "a public key",
+    fhe_public_key_serialize,
+    fhe_public_key_deserialize
+);
+serialization!(
+    FheEvaluationKey,
+    EvaluationKey,
+    "an evaluation key",
+    fhe_evaluation_key_serialize,
+    fhe_evaluation_key_deserialize
+);
+serialization!(
+    FheCiphertext,
+    Ciphertext,
+    "a ciphertext",
+    fhe_ciphertext_serialize,
+    fhe_ciphertext_deserialize
+);
+
+/// Serialize a relinearization key.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_relinearization_key_serialize(
+    rk: *const FheRelinearizationKey,
+    out: *mut FheBuffer,
+) -> FheStatus {
+    guard(|| {
+        let rk = borrow(rk)?;
+        check_out(out)?;
+        write_buffer(out, rk.value.0.to_bytes())
+    })
+}
+
+/// Deserialize a relinearization key for the given parameters.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_relinearization_key_deserialize(
+    par: *const FheParameters,
+    data: *const u8,
+    len: usize,
+    out: *mut *mut FheRelinearizationKey,
+) -> FheStatus {
+    guard(|| {
+        let par = borrow(par)?;
+        let bytes = slice(data, len)?;
+        check_out(out)?;
+        let rk = RelinearizationKey::from_bytes(bytes, &par.0)?;
+        write_handle(out, relinearization_handle(&par.0, rk)?)
+    })
+}
+
+/// Release a buffer returned by a serialization function.
+///
+/// # Safety
+///
+/// See the [crate documentation](crate#safety).
+#[no_mangle]
+pub unsafe extern "C" fn fhe_buffer_free(buffer: FheBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::{null, null_mut};
+
+    #[test]
+    fn round_trip() {
+        unsafe {
+            let mut par = null_mut();
+            let sizes = [62usize, 62, 62];
+            assert_eq!(
+                fhe_parameters_new_insecure(16, 1153, sizes.as_ptr(), sizes.len(), &mut par),
+                FheStatus::Ok
+            );
+            let degree = fhe_parameters_degree(par);
+            assert_eq!(degree, 16);
+
+            let (mut sk, mut pk, mut rk, mut ek) = (null_mut(), null_mut(), null_mut(), null_mut());
+            assert_eq!(fhe_secret_key_generate(par, &mut sk), FheStatus::Ok);
+            assert_eq!(fhe_public_key_generate(sk, &mut pk), FheStatus::Ok);
+            assert_eq!(fhe_relinearization_key_generate(sk, &mut rk), FheStatus::Ok);
+            let rotations = [1usize];
+            assert_eq!(
+                fhe_evaluation_key_generate(sk, true, rotations.as_ptr(), 1, &mut ek),
+                FheStatus::Ok
+            );
+
+            let a = (0..degree as u64).collect::<Vec<_>>();
+            let b = vec![3u64; degree];
+            let (mut ct_a, mut ct_b) = (null_mut(), null_mut());
+            assert_eq!(
+                fhe_public_key_encrypt(pk, a.as_ptr(), degree, FheEncoding::Simd, &mut ct_a),
+                FheStatus::Ok
+            );
+            assert_eq!(
+                fhe_secret_key_encrypt(sk, b.as_ptr(), degree, FheEncoding::Simd, &mut ct_b),
+                FheStatus::Ok
+            );
+
+            let (mut sum, mut product, mut rotated) = (null_mut(), null_mut(), null_mut());
+            assert_eq!(fhe_ciphertext_add(ct_a, ct_b, &mut sum), FheStatus::Ok);
+            assert_eq!(
+                fhe_ciphertext_mul(rk, sum, ct_b, &mut product),
+                FheStatus::Ok
+            );
+            assert_eq!(
+                fhe_ciphertext_rotate_columns(ek, product, 1, &mut rotated),
+                FheStatus::Ok
+            );
+
+            // Serialize the ciphertext and deserialize it with deserialized
+            // parameters.
+            let mut buffer = FheBuffer {
+                data: null_mut(),
+                len: 0,
+            };
+            assert_eq!(fhe_parameters_serialize(par, &mut buffer), FheStatus::Ok);
+            let mut par2 = null_mut();
+            assert_eq!(
+                fhe_parameters_deserialize(buffer.data, buffer.len, &mut par2),
+                FheStatus::Ok
+            );
+            fhe_buffer_free(buffer);
+            let mut buffer = FheBuffer {
+                data: null_mut(),
+                len: 0,
+            };
+            assert_eq!(
+                fhe_ciphertext_serialize(rotated, &mut buffer),
+                FheStatus::Ok
+            );
+            let mut rotated2 = null_mut();
+            assert_eq!(
+                fhe_ciphertext_deserialize(par2, buffer.data, buffer.len, &mut rotated2),
+                FheStatus::Ok
+            );
+            fhe_buffer_free(buffer);
+
+            let mut out = vec![0u64; degree];
+            assert_eq!(
+                fhe_secret_key_decrypt(sk, rotated2, FheEncoding::Simd, out.as_mut_ptr(), degree),
+                FheStatus::Ok
+            );
+            let row_size = degree / 2;
+            let expected = (0..degree)
+                .map(|i| {
+                    let row = i / row_size;
+                    let j = row * row_size + (i + 1) % row_size;
+                    (a[j] + 3) * 3 % 1153
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(out, expected);
+
+            for ct in [ct_a, ct_b, sum, product, rotated, rotated2] {
+                fhe_ciphertext_free(ct);
+            }
+            fhe_evaluation_key_free(ek);
+            fhe_relinearization_key_free(rk);
+            fhe_public_key_free(pk);
+            fhe_secret_key_free(sk);
+            fhe_parameters_free(par2);
+            fhe_parameters_free(par);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        unsafe {
+            let mut par = null_mut();
+            assert_eq!(
+                fhe_parameters_new(16, 1153, null(), 1, &mut par),
+                FheStatus::NullPointer
+            );
+            let sizes = [62usize];
+            assert_eq!(
+                fhe_parameters_new(15, 1153, sizes.as_ptr(), 1, &mut par),
+                FheStatus::ParametersError
+            );
+            assert!(par.is_null());
+            assert_eq!(
+                fhe_parameters_new(16, 1153, sizes.as_ptr(), 1, &mut par),
+                FheStatus::ParametersError
+            );
+            assert!(par.is_null());
+            assert_eq!(
+                fhe_parameters_new_insecure(16, 1153, sizes.as_ptr(), 1, &mut par),
+                FheStatus::Ok
+            );
+
+            let mut sk = null_mut();
+            assert_eq!(
+                fhe_secret_key_generate(null(), &mut sk),
+                FheStatus::NullPointer
+            );
+            assert_eq!(
+                fhe_secret_key_generate(par, null_mut()),
+                FheStatus::NullPointer
+            );
+            assert_eq!(fhe_secret_key_generate(par, &mut sk), FheStatus::Ok);
+
+            let values = [1u64; 17];
+            let mut ct = null_mut();
+            assert_eq!(
+                fhe_secret_key_encrypt(sk, values.as_ptr(), 17, FheEncoding::Poly, &mut ct),
+                FheStatus::InvalidArgument
+            );
+            assert_eq!(
+                fhe_secret_key_encrypt(sk, values.as_ptr(), 16, FheEncoding::Poly, &mut ct),
+                FheStatus::Ok
+            );
+            let mut out = vec![0u64; 16];
+            assert_eq!(
+                fhe_secret_key_decrypt(sk, ct, FheEncoding::Poly, out.as_mut_ptr(), 8),
+                FheStatus::InvalidArgument
+            );
+
+            let bytes = [1u8, 2, 3];
+            let mut ct2 = null_mut();
+            assert_ne!(
+                fhe_ciphertext_deserialize(par, bytes.as_ptr(), bytes.len(), &mut ct2),
+                FheStatus::Ok
+            );
+
+            fhe_ciphertext_free(ct);
+            fhe_ciphertext_free(null_mut());
+            fhe_secret_key_free(sk);
+            fhe_parameters_free(par);
+        }
+    }
+}
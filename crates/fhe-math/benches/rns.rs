@@ -1,5 +1,6 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use fhe_math::rns::{RnsContext, RnsScaler, ScalingFactor};
+use ndarray::Array2;
 use num_bigint::BigUint;
 use rand::{thread_rng, RngCore};
 extern crate alloc;
@@ -52,6 +53,32 @@ pub fn rns_benchmark(c: &mut Criterion) {
         },
     );
 
+    // Conversion of a block of coefficients, column by column or fused.
+    let xs = Array2::from_shape_fn((q.len(), 512), |(i, _)| rng.next_u64() % q[i]);
+    let mut ys = Array2::zeros((p.len(), 512));
+
+    group.bench_function(
+        BenchmarkId::new(
+            "scaler_as_converter_block",
+            format!("{}->{}", q.len(), p.len()),
+        ),
+        |b| {
+            b.iter(|| {
+                xs.columns()
+                    .into_iter()
+                    .zip(ys.columns_mut())
+                    .for_each(|(x, y)| scaler_as_converter.scale(x, y, 0))
+            });
+        },
+    );
+
+    group.bench_function(
+        BenchmarkId::new("converter_block", format!("{}->{}", q.len(), p.len())),
+        |b| {
+            b.iter(|| scaler_as_converter.convert(xs.view(), ys.view_mut(), 0));
+        },
+    );
+
     group.finish();
 }
 
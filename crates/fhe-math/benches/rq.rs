@@ -1,7 +1,9 @@
 use criterion::measurement::WallTime;
 use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion};
-use fhe_math::rq::*;
+use fhe_math::rns::ScalingFactor;
+use fhe_math::rq::{scaler::Scaler, *};
 use itertools::{izip, Itertools};
+use num_bigint::BigUint;
 use rand::thread_rng;
 use std::{
     ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
@@ -18,7 +20,7 @@ static MODULI: &[u64; 4] = &[
 
 static DEGREE: &[usize] = &[1024, 2048, 4096, 8192];
 
-fn create_group(c: &mut Criterion, name: String) -> BenchmarkGroup<WallTime> {
+fn create_group(c: &mut Criterion, name: String) -> BenchmarkGroup<'_, WallTime> {
     let mut group = c.benchmark_group(name);
     group.warm_up_time(Duration::from_millis(100));
     group.measurement_time(Duration::from_secs(1));
@@ -287,5 +289,57 @@ pub fn rq_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(rq, rq_op_benchmark, rq_dot_product, rq_benchmark);
+pub fn rq_scaler(c: &mut Criterion) {
+    let mut group = create_group(c, "rq_scaler".to_string());
+    let mut rng = thread_rng();
+
+    for degree in DEGREE {
+        let from = Arc::new(Context::new(&MODULI[1..3], *degree).unwrap());
+        let to = Arc::new(Context::new(&MODULI[1..], *degree).unwrap());
+        let p = Poly::random(&from, Representation::Ntt, &mut rng);
+
+        for (name, factor) in [
+            (
+                "scale",
+                ScalingFactor::new(&BigUint::from(3u64), &BigUint::from(7u64)),
+            ),
+            ("convert", ScalingFactor::one()),
+        ] {
+            let mut scaler = Scaler::new(&from, &to, factor).unwrap();
+            for block_size in [64, 512, *degree] {
+                scaler.set_block_size(block_size).unwrap();
+                group.bench_function(
+                    BenchmarkId::new(
+                        name,
+                        format!("{}/{}/{}", degree, to.modulus().bits(), block_size),
+                    ),
+                    |b| {
+                        b.iter(|| p.scale(&scaler));
+                    },
+                );
+            }
+        }
+
+        // Scaling between single moduli, as in the decryption.
+        let from = Arc::new(Context::new(&MODULI[1..2], *degree).unwrap());
+        let to = Arc::new(Context::new(&MODULI[2..3], *degree).unwrap());
+        let p = Poly::random(&from, Representation::PowerBasis, &mut rng);
+        let scaler = Scaler::new(
+            &from,
+            &to,
+            ScalingFactor::new(&BigUint::from(MODULI[2]), &BigUint::from(MODULI[1])),
+        )
+        .unwrap();
+        group.bench_function(
+            BenchmarkId::new("scale_single_modulus", format!("{}", degree)),
+            |b| {
+                b.iter(|| p.scale(&scaler));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(rq, rq_op_benchmark, rq_dot_product, rq_benchmark, rq_scaler);
 criterion_main!(rq);
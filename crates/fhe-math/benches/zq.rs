@@ -40,6 +40,17 @@ pub fn zq_benchmark(c: &mut Criterion) {
             b.iter(|| q.mul_vec_vt(&mut a, &c));
         });
 
+        group.bench_function(BenchmarkId::new("mul_add_vec", vector_size), |b| {
+            b.iter(|| q.mul_add_vec(&mut a, &c, &c));
+        });
+
+        group.bench_function(
+            BenchmarkId::new("mul_add_vec_vt", vector_size),
+            |b| unsafe {
+                b.iter(|| q.mul_add_vec_vt(&mut a, &c, &c));
+            },
+        );
+
         group.bench_function(BenchmarkId::new("mul_shoup_vec", vector_size), |b| {
             b.iter(|| q.mul_shoup_vec(&mut a, &c, &c_shoup));
         });
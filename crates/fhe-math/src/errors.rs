@@ -34,6 +34,10 @@ pub enum Error {
     #[error("Invalid seed: got {0} bytes, expected {1} bytes.")]
     InvalidSeedSize(usize, usize),
 
+    /// Indicates that an allocation exceeds the cap of the context.
+    #[error("Allocation of {0} bytes exceeds the cap of {1} bytes.")]
+    AllocationCapExceeded(usize, usize),
+
     /// Indicates a default error
     /// TODO: To delete when transition is over
     #[error("{0}")]
@@ -70,5 +74,9 @@ mod tests {
             Error::InvalidSeedSize(0, 1).to_string(),
             "Invalid seed: got 0 bytes, expected 1 bytes."
         );
+        assert_eq!(
+            Error::AllocationCapExceeded(2, 1).to_string(),
+            "Allocation of 2 bytes exceeds the cap of 1 bytes."
+        );
     }
 }
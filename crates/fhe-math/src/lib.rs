@@ -4,6 +4,16 @@
 #![warn(missing_docs, unused_imports)]
 
 //! Mathematical utilities for the fhe.rs library.
+//!
+//! The crate only depends on `core` and `alloc`, so that [`zq::Modulus`],
+//! [`rq::Poly`], [`ntt::NttOperator`] and [`rns::RnsContext`] compile for
+//! bare-metal targets with a global allocator, such as
+//! `thumbv7em-none-eabihf`. The `std` feature, disabled by default, enables
+//! the standard library in the dependencies, e.g. for the runtime detection
+//! of SIMD instructions; it is implied by the `rayon` feature.
+
+#[cfg(feature = "std")]
+extern crate std;
 
 mod errors;
 mod proto;
@@ -11,6 +21,8 @@ mod proto;
 pub mod ntt;
 pub mod rns;
 pub mod rq;
+pub mod rq_static;
+pub mod stream;
 pub mod zq;
 
 pub use errors::{Error, Result};
@@ -1,5 +1,6 @@
 use concrete_ntt::prime64::Plan;
 
+use crate::proto::rq::NttTables;
 use crate::zq::Modulus;
 
 use super::native;
@@ -34,6 +35,22 @@ impl NttOperator {
         })
     }
 
+    /// Returns the precomputed tables of the native operator.
+    pub(crate) fn tables(&self) -> NttTables {
+        self.native_operator.tables()
+    }
+
+    /// Creates an NTT operator from the precomputed tables of the native
+    /// operator.
+    pub(crate) fn from_tables(tables: &NttTables) -> Option<Self> {
+        let native_operator = native::NttOperator::from_tables(tables)?;
+        let concrete_operator = Plan::try_new(tables.degree as usize, tables.modulus);
+        Some(Self {
+            concrete_operator,
+            native_operator,
+        })
+    }
+
     /// Compute the forward NTT in place.
     /// Aborts if a is not of the size handled by the operator.
     pub fn forward(&self, a: &mut [u64]) {
@@ -65,7 +82,7 @@ impl NttOperator {
     /// about the value being reduced.
     pub(crate) unsafe fn forward_vt_lazy(&self, a_ptr: *mut u64) {
         if let Some(ref concrete_operator) = self.concrete_operator {
-            let a = std::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
+            let a = core::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
             concrete_operator.fwd(a);
         } else {
             self.native_operator.forward_vt_lazy(a_ptr);
@@ -80,7 +97,7 @@ impl NttOperator {
     /// about the value being reduced.
     pub unsafe fn forward_vt(&self, a_ptr: *mut u64) {
         if let Some(ref concrete_operator) = self.concrete_operator {
-            let a = std::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
+            let a = core::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
             concrete_operator.fwd(a);
         } else {
             self.native_operator.forward_vt(a_ptr);
@@ -95,7 +112,7 @@ impl NttOperator {
     /// about the value being reduced.
     pub unsafe fn backward_vt(&self, a_ptr: *mut u64) {
         if let Some(ref concrete_operator) = self.concrete_operator {
-            let a = std::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
+            let a = core::slice::from_raw_parts_mut(a_ptr, concrete_operator.ntt_size());
             concrete_operator.inv(a);
             concrete_operator.normalize(a);
         } else {
@@ -12,12 +12,30 @@ pub use concrete::NttOperator;
 #[cfg(not(any(feature = "concrete-ntt", feature = "concrete-ntt-nightly")))]
 pub use native::NttOperator;
 
+/// Minimum size of the Number-Theoretic Transform.
+///
+/// With the `small-degree` feature (and in the tests of this crate), the
+/// native implementation also supports transforms of size 4, which makes
+/// tests of higher-level protocols faster.
+#[cfg(all(
+    any(test, feature = "small-degree"),
+    not(any(feature = "concrete-ntt", feature = "concrete-ntt-nightly"))
+))]
+pub const MIN_SIZE: usize = 4;
+
+/// Minimum size of the Number-Theoretic Transform.
+#[cfg(not(all(
+    any(test, feature = "small-degree"),
+    not(any(feature = "concrete-ntt", feature = "concrete-ntt-nightly"))
+)))]
+pub const MIN_SIZE: usize = 8;
+
 /// Returns whether a modulus p is prime and supports the Number Theoretic
 /// Transform of size n.
 ///
-/// Aborts if n is not a power of 2 that is >= 8.
+/// Aborts if n is not a power of 2 that is >= [`MIN_SIZE`].
 pub(crate) fn supports_ntt(p: u64, n: usize) -> bool {
-    assert!(n >= 8 && n.is_power_of_two());
+    assert!(n >= MIN_SIZE && n.is_power_of_two());
 
     p % ((n as u64) << 1) == 1 && is_prime(p)
 }
@@ -52,7 +70,7 @@ mod tests {
         let ntests = 100;
         let mut rng = thread_rng();
 
-        for size in [32, 1024] {
+        for size in [super::MIN_SIZE, 32, 1024] {
             for p in [1153, 4611686018326724609] {
                 let q = Modulus::new(p).unwrap();
 
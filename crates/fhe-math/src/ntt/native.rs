@@ -1,8 +1,7 @@
-use crate::zq::Modulus;
+use crate::proto::rq::NttTables;
+use crate::zq::{roots::primitive_root_of_unity, Modulus};
 use core::iter::successors;
 use itertools::Itertools;
-use rand::{Rng, SeedableRng};
-use rand_chacha::ChaCha8Rng;
 extern crate alloc;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -24,7 +23,8 @@ pub struct NttOperator {
 impl NttOperator {
     /// Create an NTT operator given a modulus for a specific size.
     ///
-    /// Aborts if the size is not a power of 2 that is >= 8 in debug mode.
+    /// Aborts if the size is not a power of 2 that is >= [`super::MIN_SIZE`] in
+    /// debug mode.
     /// Returns None if the modulus does not support the NTT for this specific
     /// size.
     pub fn new(p: &Modulus, size: usize) -> Option<Self> {
@@ -33,7 +33,7 @@ impl NttOperator {
         } else {
             let size_inv = p.inv(size as u64)?;
 
-            let omega = Self::primitive_root(size, p);
+            let omega = primitive_root_of_unity(p, 2 * size)?;
             let omega_inv = p.inv(omega)?;
 
             let powers = successors(Some(1u64), |n| Some(p.mul(*n, omega)))
@@ -68,6 +68,69 @@ impl NttOperator {
         }
     }
 
+    /// Returns the precomputed tables of the operator.
+    pub(crate) fn tables(&self) -> NttTables {
+        NttTables {
+            modulus: self.p.p,
+            degree: self.size as u32,
+            omegas: self.omegas.to_vec(),
+            omegas_shoup: self.omegas_shoup.to_vec(),
+            zetas_inv: self.zetas_inv.to_vec(),
+            zetas_inv_shoup: self.zetas_inv_shoup.to_vec(),
+            size_inv: self.size_inv,
+            size_inv_shoup: self.size_inv_shoup,
+        }
+    }
+
+    /// Creates an NTT operator from precomputed tables, without searching
+    /// for a root of unity.
+    ///
+    /// Returns None if the modulus does not support the NTT of the size of
+    /// the tables, or if the tables are malformed. The tables are only checked
+    /// to be those of a primitive root of unity, the Shoup representations are
+    /// trusted.
+    pub(crate) fn from_tables(tables: &NttTables) -> Option<Self> {
+        let size = tables.degree as usize;
+        let p = Modulus::new(tables.modulus).ok()?;
+        if !size.is_power_of_two() || size < super::MIN_SIZE || !super::supports_ntt(p.p, size) {
+            return None;
+        }
+        let vectors = [
+            &tables.omegas,
+            &tables.omegas_shoup,
+            &tables.zetas_inv,
+            &tables.zetas_inv_shoup,
+        ];
+        if vectors.iter().any(|v| v.len() != size)
+            || tables.omegas.iter().any(|w| *w >= p.p)
+            || tables.zetas_inv.iter().any(|z| *z >= p.p)
+        {
+            return None;
+        }
+
+        // The bit-reversed index of 1 is size / 2, so that omegas[size / 2] is
+        // the primitive 2 * size-th root of unity and zetas_inv[0] its inverse.
+        let omega = tables.omegas[size >> 1];
+        if p.pow(omega, size as u64) != p.p - 1
+            || p.mul(omega, tables.zetas_inv[0]) != 1
+            || p.mul(tables.size_inv, p.reduce(size as u64)) != 1
+        {
+            return None;
+        }
+
+        Some(Self {
+            p: p.clone(),
+            p_twice: p.p * 2,
+            size,
+            omegas: tables.omegas.clone().into_boxed_slice(),
+            omegas_shoup: tables.omegas_shoup.clone().into_boxed_slice(),
+            zetas_inv: tables.zetas_inv.clone().into_boxed_slice(),
+            zetas_inv_shoup: tables.zetas_inv_shoup.clone().into_boxed_slice(),
+            size_inv: tables.size_inv,
+            size_inv_shoup: tables.size_inv_shoup,
+        })
+    }
+
     /// Compute the forward NTT in place.
     /// Aborts if a is not of the size handled by the operator.
     pub fn forward(&self, a: &mut [u64]) {
@@ -348,37 +411,4 @@ impl NttOperator {
         debug_assert!(*x < self.p_twice);
         debug_assert!(*y < self.p_twice);
     }
-
-    /// Returns a 2n-th primitive root modulo p.
-    ///
-    /// Aborts if p is not prime or n is not a power of 2 that is >= 8.
-    fn primitive_root(n: usize, p: &Modulus) -> u64 {
-        debug_assert!(super::supports_ntt(p.p, n));
-
-        let lambda = (p.p - 1) / (2 * n as u64);
-
-        let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(0);
-        for _ in 0..100 {
-            let mut root = rng.gen_range(0..p.p);
-            root = p.pow(root, lambda);
-            if Self::is_primitive_root(root, 2 * n, p) {
-                return root;
-            }
-        }
-
-        debug_assert!(false, "Couldn't find primitive root");
-        0
-    }
-
-    /// Returns whether a is a n-th primitive root of unity.
-    ///
-    /// Aborts if a >= p in debug mode.
-    fn is_primitive_root(a: u64, n: usize, p: &Modulus) -> bool {
-        debug_assert!(a < p.p);
-        debug_assert!(super::supports_ntt(p.p, n >> 1)); // TODO: This is not exactly the right condition here.
-
-        // A primitive root of unity is such that x^n = 1 mod p, and x^(n/p) != 1 mod p
-        // for all prime p dividing n.
-        (p.pow(a, n as u64) == 1) && (p.pow(a, (n / 2) as u64) != 1)
-    }
 }
@@ -11,6 +11,36 @@ pub struct Rq {
     pub coefficients: ::prost::alloc::vec::Vec<u8>,
     #[prost(bool, tag = "4")]
     pub allow_variable_time: bool,
+    #[prost(bytes = "vec", tag = "5")]
+    pub context_digest: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NttTables {
+    #[prost(uint64, tag = "1")]
+    pub modulus: u64,
+    #[prost(uint32, tag = "2")]
+    pub degree: u32,
+    #[prost(uint64, repeated, tag = "3")]
+    pub omegas: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub omegas_shoup: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, repeated, tag = "5")]
+    pub zetas_inv: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, repeated, tag = "6")]
+    pub zetas_inv_shoup: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, tag = "7")]
+    pub size_inv: u64,
+    #[prost(uint64, tag = "8")]
+    pub size_inv_shoup: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrecomputationCache {
+    #[prost(message, repeated, tag = "1")]
+    pub tables: ::prost::alloc::vec::Vec<NttTables>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub digest: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -4,11 +4,10 @@
 
 use crate::{zq::Modulus, Error, Result};
 use alloc::fmt::Debug;
-use core::cmp::Ordering;
+use fhe_util::is_prime;
 use itertools::izip;
 use ndarray::ArrayView1;
 use num_bigint::BigUint;
-use num_bigint_dig::{BigInt as BigIntDig, BigUint as BigUintDig, ExtendedGcd, ModInverse};
 use num_traits::{cast::ToPrimitive, One, Zero};
 extern crate alloc;
 use alloc::borrow::ToOwned;
@@ -19,11 +18,46 @@ mod scaler;
 
 pub use scaler::{RnsScaler, ScalingFactor};
 
+/// Returns the greatest common divisor of `a` and `b`.
+///
+/// # Security
+/// This function is not constant time and its timing may reveal information
+/// about `a` and `b`: it is meant for public values such as the moduli.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns the inverse of `a` modulo `m`, or `None` if `a` is not invertible
+/// modulo `m` or if `m` is zero.
+///
+/// # Security
+/// This function is not constant time and its timing may reveal information
+/// about `a`: use [`RnsContext::inverse`] with prime moduli to invert secret
+/// values.
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+    // Extended Euclidean algorithm, where t_i * a = r_i modulo m.
+    let (mut r0, mut r1) = (m as i128, (a % m) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    (r0 == 1).then(|| t0.rem_euclid(m as i128) as u64)
+}
+
 /// Context for a Residue Number System.
 #[derive(Default, Clone, PartialEq, Eq)]
 pub struct RnsContext {
     moduli_u64: Vec<u64>,
     moduli: Vec<Modulus>,
+    prime: Vec<bool>,
     q_tilde: Vec<u64>,
     q_tilde_shoup: Vec<u64>,
     q_star: Vec<BigUint>,
@@ -54,25 +88,20 @@ impl RnsContext {
             Err(Error::Default("The list of moduli is empty".to_string()))
         } else {
             let mut product = BigUint::one();
-            let mut product_dig = BigUintDig::one();
 
             for i in 0..moduli_u64.len() {
                 // Return an error if the moduli are not coprime.
                 for j in 0..moduli_u64.len() {
-                    if i != j {
-                        let (d, _, _) = BigUintDig::from(moduli_u64[i])
-                            .extended_gcd(&BigUintDig::from(moduli_u64[j]));
-                        if d.cmp(&BigIntDig::from(1)) != Ordering::Equal {
-                            return Err(Error::Default("The moduli are not coprime".to_string()));
-                        }
+                    if i != j && gcd(moduli_u64[i], moduli_u64[j]) != 1 {
+                        return Err(Error::Default("The moduli are not coprime".to_string()));
                     }
                 }
 
                 product *= &BigUint::from(moduli_u64[i]);
-                product_dig *= &BigUintDig::from(moduli_u64[i]);
             }
 
             let mut moduli = Vec::with_capacity(moduli_u64.len());
+            let mut prime = Vec::with_capacity(moduli_u64.len());
             let mut q_tilde = Vec::with_capacity(moduli_u64.len());
             let mut q_tilde_shoup = Vec::with_capacity(moduli_u64.len());
             let mut q_star = Vec::with_capacity(moduli_u64.len());
@@ -80,14 +109,12 @@ impl RnsContext {
 
             for modulus in moduli_u64 {
                 moduli.push(Modulus::new(*modulus)?);
+                prime.push(is_prime(*modulus));
                 // q* = product / modulus
                 let q_star_i = &product / modulus;
                 // q~ = (product / modulus) ^ (-1) % modulus
-                let q_tilde_i = (&product_dig / modulus)
-                    .mod_inverse(&BigUintDig::from(*modulus))
-                    .unwrap()
-                    .to_u64()
-                    .unwrap();
+                let q_tilde_i =
+                    mod_inverse((&q_star_i % modulus).to_u64().unwrap(), *modulus).unwrap();
                 // garner = (q*) * (q~)
                 let garner_i = &q_star_i * q_tilde_i;
                 q_tilde.push(q_tilde_i);
@@ -103,6 +130,7 @@ impl RnsContext {
             Ok(Self {
                 moduli_u64: moduli_u64.to_owned(),
                 moduli,
+                prime,
                 q_tilde,
                 q_tilde_shoup,
                 q_star,
@@ -137,6 +165,33 @@ impl RnsContext {
         result % &self.product
     }
 
+    /// Returns the rests of the inverse modulo the product of the moduli of
+    /// the value with the given `rests`, as computed by
+    /// [`RnsContext::project`], or `None` if the value is not invertible.
+    ///
+    /// Aborts if the number of rests is different than the number of moduli in
+    /// debug mode.
+    ///
+    /// # Security
+    /// The rests modulo prime moduli are inverted in constant time, by
+    /// exponentiation to the public exponent `modulus - 2`; the others are
+    /// inverted with [`mod_inverse`], in variable time. In both cases, the
+    /// result reveals whether the value is invertible.
+    pub fn inverse(&self, rests: &[u64]) -> Option<Vec<u64>> {
+        debug_assert_eq!(rests.len(), self.moduli.len());
+        izip!(rests, &self.moduli, &self.prime)
+            .map(|(r_i, q_i, prime_i)| {
+                let r_i = q_i.reduce(*r_i);
+                if *prime_i {
+                    let inv = q_i.pow(r_i, **q_i - 2);
+                    (r_i != 0).then_some(inv)
+                } else {
+                    mod_inverse(r_i, **q_i)
+                }
+            })
+            .collect()
+    }
+
     /// Getter for the i-th garner coefficient.
     pub fn get_garner(&self, i: usize) -> Option<&BigUint> {
         self.garner.get(i)
@@ -148,17 +203,13 @@ mod tests {
 
     use crate::Error;
 
-    use super::RnsContext;
+    use super::{gcd, mod_inverse, RnsContext};
     use ndarray::ArrayView1;
     use num_bigint::BigUint;
     use rand::RngCore;
     extern crate alloc;
-    
-    
+
     use alloc::string::ToString;
-    
-    
-    
 
     #[test]
     fn constructor() {
@@ -177,6 +228,35 @@ mod tests {
         assert_eq!(e.unwrap_err().to_string(), "The moduli are not coprime");
     }
 
+    #[test]
+    fn gcd_inverse() -> Result<(), Error> {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(1153, 4), 1);
+
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(10, 7), Some(5));
+        assert_eq!(mod_inverse(2, 4), None);
+        assert_eq!(mod_inverse(2, 0), None);
+        assert_eq!(
+            mod_inverse(2, 4611686018326724609),
+            Some(2305843009163362305)
+        );
+
+        // The moduli 4 and 15 are not prime, the moduli 1153 and
+        // 4611686018326724609 are.
+        for moduli in [&[4u64, 15, 1153] as &[u64], &[1153, 4611686018326724609]] {
+            let rns = RnsContext::new(moduli)?;
+            let a = BigUint::from(1234567u64);
+            let a_inv = rns.lift(ArrayView1::from(&rns.inverse(&rns.project(&a)).unwrap()));
+            assert_eq!((a * a_inv) % rns.modulus(), 1u64.into());
+            assert_eq!(rns.inverse(&rns.project(&BigUint::from(1153u64))), None);
+        }
+        let rns = RnsContext::new(&[4, 15, 1153])?;
+        assert_eq!(rns.inverse(&rns.project(&BigUint::from(6u64))), None);
+        Ok(())
+    }
+
     #[test]
     fn garner() -> Result<(), Error> {
         let rns = RnsContext::new(&[4, 15, 1153])?;
@@ -11,15 +11,15 @@ use alloc::vec;
 use alloc::vec::Vec;
 use ethnum::{u256, U256};
 use itertools::{izip, Itertools};
-use ndarray::{ArrayView1, ArrayViewMut1};
+use ndarray::{ArrayView1, ArrayView2, ArrayViewMut1, ArrayViewMut2, Axis};
 use num_bigint::BigUint;
 use num_traits::{One, ToPrimitive, Zero};
 
 /// Scaling factor when performing a RNS scaling.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct ScalingFactor {
-    numerator: BigUint,
-    denominator: BigUint,
+    pub(crate) numerator: BigUint,
+    pub(crate) denominator: BigUint,
     pub(crate) is_one: bool,
 }
 
@@ -222,6 +222,25 @@ impl RnsScaler {
         (projected, theta_lo, theta_hi, theta_sign)
     }
 
+    /// Returns the rounding of the inner product of the rests with
+    /// theta_garner, i.e. the number of times the product of the input moduli
+    /// is subtracted when lifting the rests with the garner coefficients.
+    fn overflow(&self, rests: ArrayView1<u64>) -> u128 {
+        let mut sum_theta_garner = u256::ZERO;
+        for (thetag_lo, thetag_hi, ri) in izip!(
+            self.theta_garner_lo.iter(),
+            self.theta_garner_hi.iter(),
+            rests
+        ) {
+            sum_theta_garner = sum_theta_garner.wrapping_add(
+                U256::from(*ri) * U256::from((*thetag_lo as u128) | ((*thetag_hi as u128) << 64)),
+            );
+        }
+        // Let's compute v = round(sum_theta_garner / 2^theta_garner_shift)
+        sum_theta_garner >>= self.theta_garner_shift - 1;
+        sum_theta_garner.as_u128().div_ceil(2)
+    }
+
     /// Output the RNS representation of the rests scaled by numerator *
     /// denominator, and either rounded or floored.
     ///
@@ -249,20 +268,8 @@ impl RnsScaler {
         debug_assert!(!out.is_empty());
         debug_assert!(starting_index + out.len() <= self.to.moduli_u64.len());
 
-        // First, let's compute the inner product of the rests with theta_omega.
-        let mut sum_theta_garner = u256::ZERO;
-        for (thetag_lo, thetag_hi, ri) in izip!(
-            self.theta_garner_lo.iter(),
-            self.theta_garner_hi.iter(),
-            rests
-        ) {
-            sum_theta_garner = sum_theta_garner.wrapping_add(
-                U256::from(*ri) * U256::from((*thetag_lo as u128) | ((*thetag_hi as u128) << 64)),
-            );
-        }
-        // Let's compute v = round(sum_theta_garner / 2^theta_garner_shift)
-        sum_theta_garner >>= self.theta_garner_shift - 1;
-        let v = sum_theta_garner.as_u128().div_ceil(2);
+        // First, let's compute the number of overflows of the rests.
+        let v = self.overflow(rests);
 
         // If the scaling factor is not 1, compute the inner product with the
         // theta_omega
@@ -343,6 +350,57 @@ impl RnsScaler {
             }
         }
     }
+
+    /// Compute the RNS representation of the columns of `rests` when the
+    /// scaling factor is one, i.e. convert them to the output moduli starting
+    /// at `starting_index`, and store the results in the columns of `out`.
+    ///
+    /// This outputs the same values as [`RnsScaler::scale`] on each column,
+    /// but first computes the overflows of all the columns, and then the
+    /// output one modulus at a time, so that the rows of `rests` and `out` are
+    /// read and written contiguously.
+    ///
+    /// Aborts if the scaling factor is not one, if the number of rows of
+    /// `rests` is different than the number of moduli, or if the shapes of
+    /// `rests` and `out` are not compatible in debug mode.
+    pub fn convert(
+        &self,
+        rests: ArrayView2<u64>,
+        mut out: ArrayViewMut2<u64>,
+        starting_index: usize,
+    ) {
+        debug_assert!(self.scaling_factor.is_one);
+        debug_assert_eq!(rests.nrows(), self.from.moduli_u64.len());
+        debug_assert_eq!(rests.ncols(), out.ncols());
+        debug_assert!(starting_index + out.nrows() <= self.to.moduli_u64.len());
+
+        let v = rests
+            .axis_iter(Axis(1))
+            .map(|column| self.overflow(column))
+            .collect_vec();
+        let mut y = vec![0u128; v.len()];
+        for (i, out_i) in out.outer_iter_mut().enumerate() {
+            let qi = &self.to.moduli[starting_index + i];
+            let (gamma_i, gamma_shoup_i) = (
+                self.gamma[starting_index + i],
+                self.gamma_shoup[starting_index + i],
+            );
+            izip!(&mut y, &v).for_each(|(yj, vj)| {
+                *yj = (**qi * 2 - qi.lazy_mul_shoup(qi.reduce_u128(*vj), gamma_i, gamma_shoup_i))
+                    as u128
+            });
+            for (rests_j, omega_ij, omega_shoup_ij) in izip!(
+                rests.outer_iter(),
+                self.omega[starting_index + i].iter(),
+                self.omega_shoup[starting_index + i].iter()
+            ) {
+                izip!(&mut y, rests_j).for_each(|(yk, rk)| {
+                    *yk += qi.lazy_mul_shoup(*rk, *omega_ij, *omega_shoup_ij) as u128
+                });
+            }
+            izip!(out_i, &y).for_each(|(o, yj)| *o = qi.reduce_u128(*yj));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -354,7 +412,7 @@ mod tests {
     use super::RnsScaler;
     use crate::rns::{scaler::ScalingFactor, RnsContext};
     use alloc::vec;
-    use ndarray::ArrayView1;
+    use ndarray::{Array2, ArrayView1};
     use num_bigint::BigUint;
     use num_traits::ToPrimitive;
     use rand::{thread_rng, RngCore};
@@ -467,4 +525,29 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn convert() -> Result<(), Error> {
+        let q = Arc::new(RnsContext::new(&[4u64, 4611686018326724609, 1153])?);
+        let r = Arc::new(RnsContext::new(&[
+            4u64,
+            4611686018309947393,
+            4611686018282684417,
+            4611686018257518593,
+        ])?);
+        let mut rng = thread_rng();
+        let scaler = RnsScaler::new(&q, &r, ScalingFactor::one());
+
+        let x = Array2::from_shape_fn((3, 100), |(i, _)| rng.next_u64() % q.moduli_u64[i]);
+        for starting_index in 0..r.moduli.len() {
+            let mut y = Array2::zeros((r.moduli.len() - starting_index, x.ncols()));
+            scaler.convert(x.view(), y.view_mut(), starting_index);
+            for (x_column, y_column) in x.columns().into_iter().zip(y.columns()) {
+                let mut expected = vec![0; y.nrows()];
+                scaler.scale(x_column, (&mut expected).into(), starting_index);
+                assert_eq!(y_column.to_vec(), expected);
+            }
+        }
+        Ok(())
+    }
 }
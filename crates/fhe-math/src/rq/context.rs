@@ -1,17 +1,38 @@
 use alloc::{fmt::Debug, sync::Arc};
 use itertools::Itertools;
 use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
 extern crate alloc;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
+use alloc::format;
 use alloc::string::ToString;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use crate::{ntt::NttOperator, rns::RnsContext, zq::Modulus, Error, Result};
+use crate::{
+    ntt::{NttOperator, MIN_SIZE},
+    rns::RnsContext,
+    rq::PrecomputationCache,
+    zq::Modulus,
+    Error, Result,
+};
 
 /// Struct that holds the context associated with elements in rq.
-#[derive(Default, Clone, PartialEq, Eq)]
+///
+/// A context is defined by a polynomial degree and a list of moduli, and holds
+/// the chain of its sub-contexts, each with one less modulus than the previous
+/// one, down to a single modulus.
+///
+/// # Stability
+///
+/// The introspection methods [`Context::moduli`], [`Context::degree`],
+/// [`Context::modulus`], [`Context::next_context`] and
+/// [`Context::sub_contexts`] are covered by semantic versioning: their
+/// signatures and behavior will only change with a new major version, or a new
+/// minor version before `1.0.0`, so that downstream crates can inspect a
+/// context without the parameters that created it.
+#[derive(Default, Clone)]
 pub struct Context {
     pub(crate) moduli: Box<[u64]>,
     pub(crate) q: Box<[Modulus]>,
@@ -22,8 +43,27 @@ pub struct Context {
     pub(crate) inv_last_qi_mod_qj: Box<[u64]>,
     pub(crate) inv_last_qi_mod_qj_shoup: Box<[u64]>,
     pub(crate) next_context: Option<Arc<Context>>,
+    pub(crate) allocation_cap: Option<usize>,
 }
 
+/// The allocation cap is not compared, so that polynomials in a capped
+/// context and in an uncapped context with the same moduli are compatible.
+impl PartialEq for Context {
+    fn eq(&self, other: &Self) -> bool {
+        self.moduli == other.moduli
+            && self.q == other.q
+            && self.rns == other.rns
+            && self.ops == other.ops
+            && self.degree == other.degree
+            && self.bitrev == other.bitrev
+            && self.inv_last_qi_mod_qj == other.inv_last_qi_mod_qj
+            && self.inv_last_qi_mod_qj_shoup == other.inv_last_qi_mod_qj_shoup
+            && self.next_context == other.next_context
+    }
+}
+
+impl Eq for Context {}
+
 impl Debug for Context {
     fn fmt(&self, f: &mut alloc::fmt::Formatter<'_>) -> alloc::fmt::Result {
         f.debug_struct("Context")
@@ -46,17 +86,59 @@ impl Context {
     /// Returns an error if the moduli are not primes less than 62 bits which
     /// supports the NTT of size `degree`.
     pub fn new(moduli: &[u64], degree: usize) -> Result<Self> {
-        if !degree.is_power_of_two() || degree < 8 {
-            Err(Error::Default(
-                "The degree is not a power of two larger or equal to 8".to_string(),
-            ))
+        Self::new_internal(moduli, degree, None, None)
+    }
+
+    /// Creates a context as [`Context::new`], where the NTT tables are taken
+    /// from `cache` when available, and added to `cache` otherwise.
+    pub fn new_with_cache(
+        moduli: &[u64],
+        degree: usize,
+        cache: &mut PrecomputationCache,
+    ) -> Result<Self> {
+        Self::new_internal(moduli, degree, None, Some(cache))
+    }
+
+    /// Creates a context from a list of moduli and a polynomial degree, where
+    /// the polynomials and temporary buffers allocated by operations returning
+    /// a `Result` are limited to `allocation_cap` bytes each.
+    ///
+    /// This allows to deploy the library in memory-constrained environments,
+    /// such as wasm or SGX enclaves, where exceeding a memory quota aborts the
+    /// program: the capped operations return
+    /// [`Error::AllocationCapExceeded`] instead. Returns an error if a single
+    /// polynomial does not fit within the cap.
+    pub fn new_with_allocation_cap(
+        moduli: &[u64],
+        degree: usize,
+        allocation_cap: usize,
+    ) -> Result<Self> {
+        let ctx = Self::new_internal(moduli, degree, Some(allocation_cap), None)?;
+        ctx.check_allocation(ctx.poly_size_in_bytes())?;
+        Ok(ctx)
+    }
+
+    fn new_internal(
+        moduli: &[u64],
+        degree: usize,
+        allocation_cap: Option<usize>,
+        mut cache: Option<&mut PrecomputationCache>,
+    ) -> Result<Self> {
+        if !degree.is_power_of_two() || degree < MIN_SIZE {
+            Err(Error::Default(format!(
+                "The degree is not a power of two larger or equal to {MIN_SIZE}"
+            )))
         } else {
             let mut q = Vec::with_capacity(moduli.len());
             let rns = Arc::new(RnsContext::new(moduli)?);
             let mut ops = Vec::with_capacity(moduli.len());
             for modulus in moduli {
                 let qi = Modulus::new(*modulus)?;
-                if let Some(op) = NttOperator::new(&qi, degree) {
+                let op = match cache.as_deref_mut() {
+                    Some(cache) => cache.ntt_operator(&qi, degree),
+                    None => NttOperator::new(&qi, degree),
+                };
+                if let Some(op) = op {
                     q.push(qi);
                     ops.push(op);
                 } else {
@@ -79,7 +161,12 @@ impl Context {
             }
 
             let next_context = if moduli.len() >= 2 {
-                Some(Arc::new(Context::new(&moduli[..moduli.len() - 1], degree)?))
+                Some(Arc::new(Context::new_internal(
+                    &moduli[..moduli.len() - 1],
+                    degree,
+                    allocation_cap,
+                    cache,
+                )?))
             } else {
                 None
             };
@@ -94,6 +181,7 @@ impl Context {
                 inv_last_qi_mod_qj: inv_last_qi_mod_qj.into_boxed_slice(),
                 inv_last_qi_mod_qj_shoup: inv_last_qi_mod_qj_shoup.into_boxed_slice(),
                 next_context,
+                allocation_cap,
             })
         }
     }
@@ -103,7 +191,26 @@ impl Context {
         Self::new(moduli, degree).map(Arc::new)
     }
 
-    /// Returns the modulus as a BigUint.
+    /// Returns the maximum size in bytes of the allocations of this context, if
+    /// any.
+    pub fn allocation_cap(&self) -> Option<usize> {
+        self.allocation_cap
+    }
+
+    /// Returns the size in bytes of the coefficients of a polynomial.
+    pub(crate) fn poly_size_in_bytes(&self) -> usize {
+        self.q.len() * self.degree * core::mem::size_of::<u64>()
+    }
+
+    /// Returns an error if an allocation of `bytes` bytes exceeds the cap.
+    pub(crate) fn check_allocation(&self, bytes: usize) -> Result<()> {
+        match self.allocation_cap {
+            Some(cap) if bytes > cap => Err(Error::AllocationCapExceeded(bytes, cap)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the modulus as a BigUint, i.e., the product of the moduli.
     pub fn modulus(&self) -> &BigUint {
         self.rns.modulus()
     }
@@ -113,6 +220,41 @@ impl Context {
         &self.moduli
     }
 
+    /// Returns the degree of the polynomials in this context.
+    pub const fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Returns a digest identifying the context, i.e. the SHA-256 hash of its
+    /// degree and of its moduli.
+    ///
+    /// The digest is stored in the serialization of the polynomials, so that a
+    /// polynomial cannot be deserialized in another context.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update((self.degree as u64).to_le_bytes());
+        self.moduli
+            .iter()
+            .for_each(|qi| hasher.update(qi.to_le_bytes()));
+        hasher.finalize().into()
+    }
+
+    /// Returns the context with the last modulus dropped, if this context has
+    /// more than one modulus.
+    pub fn next_context(&self) -> Option<&Arc<Context>> {
+        self.next_context.as_ref()
+    }
+
+    /// Returns an iterator over the sub-contexts, starting from the context
+    /// with the last modulus dropped, and ending with the context with only
+    /// the first modulus.
+    ///
+    /// The `i`-th item is the context returned by
+    /// [`Context::context_at_level`] for level `i + 1`, without the clones.
+    pub fn sub_contexts(&self) -> impl Iterator<Item = &Arc<Context>> {
+        core::iter::successors(self.next_context.as_ref(), |ctx| ctx.next_context.as_ref())
+    }
+
     /// Returns a reference to the moduli as Modulus in this context.
     pub fn moduli_operators(&self) -> &[Modulus] {
         &self.q
@@ -166,7 +308,7 @@ mod tests {
     use alloc::sync::Arc;
 
     use crate::ntt::supports_ntt;
-    use crate::rq::Context;
+    use crate::rq::{dot_product, traits::TryConvertFrom, Context, Poly, Representation};
 
     const MODULI: &[u64; 5] = &[
         1153,
@@ -196,6 +338,41 @@ mod tests {
         assert!(Context::new(MODULI, 128).is_err());
     }
 
+    #[test]
+    fn allocation_cap() -> Result<(), Error> {
+        let poly_size = MODULI.len() * 16 * 8;
+        assert_eq!(
+            Context::new_with_allocation_cap(MODULI, 16, poly_size - 1),
+            Err(Error::AllocationCapExceeded(poly_size, poly_size - 1))
+        );
+
+        let ctx = Arc::new(Context::new_with_allocation_cap(MODULI, 16, poly_size)?);
+        assert_eq!(ctx.allocation_cap(), Some(poly_size));
+        assert_eq!(
+            ctx.next_context.as_ref().unwrap().allocation_cap(),
+            Some(poly_size)
+        );
+        assert_eq!(Context::new(MODULI, 16)?.allocation_cap(), None);
+        assert_eq!(*ctx, Context::new(MODULI, 16)?);
+
+        let p = Poly::try_zero(&ctx, Representation::Ntt)?;
+        let uncapped = Arc::new(Context::new(MODULI, 16)?);
+        assert_eq!(&p + &Poly::zero(&uncapped, Representation::Ntt), p);
+        assert_eq!(
+            Poly::try_zero(&ctx, Representation::NttShoup),
+            Err(Error::AllocationCapExceeded(2 * poly_size, poly_size))
+        );
+        assert!(
+            Poly::try_convert_from(&[1u64, 2], &ctx, false, Representation::PowerBasis).is_ok()
+        );
+        // The dot product accumulates in u128 coefficients.
+        assert_eq!(
+            dot_product([&p].into_iter(), [&p].into_iter()),
+            Err(Error::AllocationCapExceeded(2 * poly_size, poly_size))
+        );
+        Ok(())
+    }
+
     #[test]
     fn next_context() -> Result<(), Error> {
         // A context should have a children pointing to a context with one less modulus.
@@ -217,6 +394,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn introspection() -> Result<(), Error> {
+        let context = Context::new(MODULI, 16)?;
+        assert_eq!(context.moduli(), MODULI);
+        assert_eq!(context.degree(), 16);
+        assert_eq!(
+            context.modulus(),
+            &MODULI
+                .iter()
+                .map(|m| num_bigint::BigUint::from(*m))
+                .product()
+        );
+
+        let sub_contexts = context.sub_contexts().collect::<alloc::vec::Vec<_>>();
+        assert_eq!(sub_contexts.len(), MODULI.len() - 1);
+        assert_eq!(context.next_context(), sub_contexts.first().copied());
+        for (i, sub_context) in sub_contexts.iter().enumerate() {
+            assert_eq!(sub_context.moduli(), &MODULI[..MODULI.len() - 1 - i]);
+            assert_eq!(sub_context.degree(), 16);
+            assert_eq!(*sub_context, &context.context_at_level(i + 1)?);
+        }
+        assert!(sub_contexts.last().unwrap().next_context().is_none());
+        assert_eq!(sub_contexts.last().unwrap().sub_contexts().count(), 0);
+
+        assert_eq!(context.digest(), Context::new(MODULI, 16)?.digest());
+        assert_ne!(context.digest(), sub_contexts[0].digest());
+        assert_ne!(context.digest(), Context::new(MODULI, 32)?.digest());
+        Ok(())
+    }
+
     #[test]
     fn niterations_to() -> Result<(), Error> {
         // A context should have a children pointing to a context with one less modulus.
@@ -2,12 +2,14 @@
 
 use super::{traits::TryConvertFrom, Context, Poly, Representation};
 use crate::{
+    ntt::MIN_SIZE,
     proto::rq::{Representation as RepresentationProto, Rq},
     Error, Result,
 };
 use itertools::{izip, Itertools};
 use ndarray::{Array2, ArrayView, Axis};
 use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 extern crate alloc;
 use alloc::string::ToString;
 use alloc::sync::Arc;
@@ -47,6 +49,7 @@ impl From<&Poly> for Rq {
         proto.coefficients = serialization;
         proto.degree = p.ctx.degree as u32;
         proto.allow_variable_time = p.allow_variable_time_computations;
+        proto.context_digest = p.ctx.digest().to_vec();
         proto
     }
 }
@@ -111,7 +114,7 @@ impl TryConvertFrom<Vec<u64>> for Poly {
                         has_lazy_coefficients: false,
                     })
                 } else if v.len() <= ctx.degree {
-                    let mut out = Self::zero(ctx, repr.unwrap());
+                    let mut out = Self::try_zero(ctx, repr.unwrap())?;
                     if variable_time {
                         unsafe {
                             izip!(out.coefficients.outer_iter_mut(), ctx.q.iter()).for_each(
@@ -176,10 +179,15 @@ impl TryConvertFrom<&Rq> for Poly {
         }
 
         let degree = value.degree as usize;
-        if degree % 8 != 0 || degree < 8 {
+        if !degree.is_power_of_two() || degree < MIN_SIZE || degree != ctx.degree {
             return Err(Error::Default("Invalid degree".to_string()));
         }
 
+        // Serializations predating the context digest do not contain it.
+        if !value.context_digest.is_empty() && value.context_digest != ctx.digest() {
+            return Err(Error::InvalidContext);
+        }
+
         let mut expected_nbytes = 0;
         ctx.q
             .iter()
@@ -194,6 +202,10 @@ impl TryConvertFrom<&Rq> for Poly {
             let qi = &ctx.q[i];
             let size = qi.serialization_length(degree);
             let mut v = qi.deserialize_vec(&value.coefficients[index..index + size]);
+            v.truncate(degree);
+            if v.iter().any(|vi| *vi >= **qi) {
+                return Err(Error::Default("Invalid coefficients".to_string()));
+            }
             power_basis_coefficients.append(&mut v);
             index += size;
         }
@@ -256,6 +268,10 @@ impl<'a> TryConvertFrom<&'a [u64]> for Poly {
     }
 }
 
+/// Signed integers are interpreted as centered representatives modulo the
+/// modulus `q` of the context, and are reduced modulo each of its moduli.
+/// Returns an error if a value has a magnitude larger than `(q - 1) / 2`, since
+/// it would not be the centered representative of its residue class.
 impl<'a> TryConvertFrom<&'a [i64]> for Poly {
     fn try_convert_from<R>(
         v: &'a [i64],
@@ -272,7 +288,18 @@ impl<'a> TryConvertFrom<&'a [i64]> for Poly {
                     .to_string(),
             ))
         } else if v.len() <= ctx.degree {
-            let mut out = Self::zero(ctx, Representation::PowerBasis);
+            if let Some(bound) = (ctx.modulus() >> 1u32).to_u64() {
+                // Scan all the values to avoid leaking the position of the
+                // first out-of-range value.
+                let max_magnitude = v.iter().fold(0, |m, vi| m.max(vi.unsigned_abs()));
+                if max_magnitude > bound {
+                    return Err(Error::Default(
+                        "A signed coefficient exceeds the centered range of the modulus"
+                            .to_string(),
+                    ));
+                }
+            }
+            let mut out = Self::try_zero(ctx, Representation::PowerBasis)?;
             if variable_time {
                 unsafe { out.allow_variable_time_computations() }
             }
@@ -323,6 +350,7 @@ impl<'a> TryConvertFrom<&'a [BigUint]> for Poly {
                     .to_string(),
             ))
         } else if repr.is_some() {
+            ctx.check_allocation(ctx.poly_size_in_bytes())?;
             let mut coefficients = Array2::zeros((ctx.q.len(), ctx.degree));
 
             izip!(coefficients.axis_iter_mut(Axis(1)), v).for_each(|(mut c, vi)| {
@@ -490,9 +518,86 @@ mod tests {
         assert_eq!(
             Poly::try_convert_from(&proto, &ctx, false, None)
                 .expect_err("Should fail because of incorrect context"),
+            CrateError::InvalidContext
+        );
+        let mut proto_without_digest = proto.clone();
+        proto_without_digest.context_digest.clear();
+        assert_eq!(
+            Poly::try_convert_from(&proto_without_digest, &ctx, false, None)
+                .expect_err("Should fail because of incorrect context"),
             CrateError::Default("Invalid coefficients".to_string())
         );
+        let ctx_permuted = Arc::new(Context::new(&[MODULI[1], MODULI[0], MODULI[2]], 16)?);
+        assert_eq!(
+            Poly::try_convert_from(&proto, &ctx_permuted, false, None)
+                .expect_err("Should fail because of incorrect context"),
+            CrateError::InvalidContext
+        );
 
+        let ctx = Arc::new(Context::new(&MODULI[1..], 16)?);
+        let p = Poly::random(&ctx, Representation::PowerBasis, &mut rng);
+        let mut proto = Rq::from(&p);
+        let ctx_32 = Arc::new(Context::new(&MODULI[1..], 32)?);
+        assert_eq!(
+            Poly::try_convert_from(&proto, &ctx_32, false, None)
+                .expect_err("Should fail because of incorrect degree"),
+            CrateError::Default("Invalid degree".to_string())
+        );
+        proto.coefficients[..8].fill(0xff);
+        assert_eq!(
+            Poly::try_convert_from(&proto, &ctx, false, None)
+                .expect_err("Should fail because of unreduced coefficients"),
+            CrateError::Default("Invalid coefficients".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_convert_from_i64_centered() -> Result<(), Error> {
+        for modulus in MODULI {
+            let ctx = Arc::new(Context::new(&[*modulus], 16)?);
+            let bound = (*modulus >> 1) as i64;
+            let v = [bound, -bound, 0, 1, -1];
+            for variable_time in [false, true] {
+                let p =
+                    Poly::try_convert_from(&v, &ctx, variable_time, Representation::PowerBasis)?;
+                assert_eq!(
+                    &Vec::<u64>::from(&p)[..5],
+                    &[bound as u64, *modulus - bound as u64, 0, 1, *modulus - 1]
+                );
+                for out_of_range in [bound + 1, -bound - 1] {
+                    assert_eq!(
+                        Poly::try_convert_from(
+                            &[0, out_of_range],
+                            &ctx,
+                            variable_time,
+                            Representation::PowerBasis
+                        )
+                        .unwrap_err(),
+                        CrateError::Default(
+                            "A signed coefficient exceeds the centered range of the modulus"
+                                .to_string()
+                        )
+                    );
+                }
+            }
+        }
+
+        // With a modulus larger than 64 bits, every i64 is in range.
+        let ctx = Arc::new(Context::new(MODULI, 16)?);
+        let v = [i64::MIN, i64::MAX];
+        let p = Poly::try_convert_from(&v, &ctx, false, Representation::PowerBasis)?;
+        let q = Poly::try_convert_from(
+            &[
+                ctx.modulus() - BigUint::from(1u64 << 63),
+                BigUint::from(i64::MAX as u64),
+            ],
+            &ctx,
+            false,
+            Representation::PowerBasis,
+        )?;
+        assert_eq!(p, q);
         Ok(())
     }
 
@@ -6,6 +6,7 @@
 mod context;
 mod convert;
 mod ops;
+mod precomputation;
 mod serialize;
 
 pub mod scaler;
@@ -17,10 +18,12 @@ pub use context::Context;
 use fhe_util::sample_vec_cbd;
 use itertools::{izip, Itertools};
 use ndarray::{s, Array2, ArrayView2, Axis};
-pub use ops::dot_product;
+pub use ops::{dot_product, LazyAccumulator};
+pub use precomputation::PrecomputationCache;
 use rand::{CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use sha2::{Digest, Sha256};
+use subtle::{ConditionallySelectable, ConstantTimeEq};
 extern crate alloc;
 use alloc::string::ToString;
 use alloc::sync::Arc;
@@ -128,6 +131,18 @@ impl Poly {
         }
     }
 
+    /// Creates a polynomial holding the constant 0, or returns an error if the
+    /// polynomial exceeds the allocation cap of the context.
+    pub fn try_zero(ctx: &Arc<Context>, representation: Representation) -> Result<Self> {
+        let copies = if representation == Representation::NttShoup {
+            2
+        } else {
+            1
+        };
+        ctx.check_allocation(copies * ctx.poly_size_in_bytes())?;
+        Ok(Self::zero(ctx, representation))
+    }
+
     /// Enable variable time computations when this polynomial is involved.
     ///
     /// # Safety
@@ -143,6 +158,12 @@ impl Poly {
         self.allow_variable_time_computations = false
     }
 
+    /// Reports whether variable time computations are enabled when this
+    /// polynomial is involved.
+    pub const fn allows_variable_time_computations(&self) -> bool {
+        self.allow_variable_time_computations
+    }
+
     /// Current representation of the polynomial.
     pub const fn representation(&self) -> &Representation {
         &self.representation
@@ -308,7 +329,7 @@ impl Poly {
     }
 
     /// Access the polynomial coefficients in RNS representation.
-    pub fn coefficients(&self) -> ArrayView2<u64> {
+    pub fn coefficients(&self) -> ArrayView2<'_, u64> {
         self.coefficients.view()
     }
 
@@ -339,7 +360,7 @@ impl Poly {
     /// multiple of 2 * degree. In Ntt and NttShoup representation, i can be any
     /// odd integer that is not a multiple of 2 * degree.
     pub fn substitute(&self, i: &SubstitutionExponent) -> Result<Poly> {
-        let mut q = Poly::zero(&self.ctx, self.representation.clone());
+        let mut q = Poly::try_zero(&self.ctx, self.representation.clone())?;
         if self.allow_variable_time_computations {
             unsafe { q.allow_variable_time_computations() }
         }
@@ -429,6 +450,46 @@ impl Poly {
         }
     }
 
+    /// Create a polynomial as
+    /// [`Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time`],
+    /// where the coefficients modulo the `index`-th modulus are copied from
+    /// `ntt_coefficients` instead of being computed. These coefficients must be
+    /// those of `power_basis_coefficients` modulo this modulus, in NTT
+    /// representation, which saves one NTT.
+    ///
+    /// # Safety
+    /// This operation also creates a polynomial that allows variable time
+    /// operations.
+    pub unsafe fn create_constant_ntt_polynomial_with_lazy_coefficients_and_ntt_row(
+        power_basis_coefficients: &[u64],
+        index: usize,
+        ntt_coefficients: &[u64],
+        ctx: &Arc<Context>,
+    ) -> Self {
+        let mut coefficients = Array2::zeros((ctx.q.len(), ctx.degree));
+        izip!(coefficients.outer_iter_mut(), ctx.q.iter(), ctx.ops.iter())
+            .enumerate()
+            .for_each(|(i, (mut p, qi, op))| {
+                if i == index {
+                    p.as_slice_mut().unwrap().clone_from_slice(ntt_coefficients);
+                } else {
+                    p.as_slice_mut()
+                        .unwrap()
+                        .clone_from_slice(power_basis_coefficients);
+                    qi.lazy_reduce_vec(p.as_slice_mut().unwrap());
+                    op.forward_vt_lazy(p.as_mut_ptr());
+                }
+            });
+        Self {
+            ctx: ctx.clone(),
+            representation: Representation::Ntt,
+            allow_variable_time_computations: true,
+            coefficients,
+            coefficients_shoup: None,
+            has_lazy_coefficients: true,
+        }
+    }
+
     /// Modulus switch down the polynomial by dividing and rounding each
     /// coefficient by the last modulus in the chain, then drops the last
     /// modulus, as described in Algorithm 2 of <https://eprint.iacr.org/2018/931.pdf>.
@@ -579,6 +640,67 @@ impl Poly {
         });
         Ok(())
     }
+
+    /// Multiplies the polynomial by a scalar, in any representation.
+    pub fn multiply_scalar(&mut self, scalar: u64) {
+        let variable_time = self.allow_variable_time_computations;
+        izip!(self.coefficients.outer_iter_mut(), self.ctx.q.iter()).for_each(|(mut v, qi)| {
+            let s = qi.reduce(scalar);
+            if variable_time {
+                unsafe { qi.scalar_mul_vec_vt(v.as_slice_mut().unwrap(), s) }
+            } else {
+                qi.scalar_mul_vec(v.as_slice_mut().unwrap(), s)
+            }
+        });
+        if self.coefficients_shoup.is_some() {
+            self.zeroize_shoup();
+            self.compute_coefficients_shoup();
+        }
+    }
+
+    /// Returns a copy of `polys[secret_index]` in constant time, or the zero
+    /// polynomial if the index is out of range.
+    ///
+    /// Every coefficient of every polynomial is accessed, so that neither the
+    /// memory access pattern nor the timing depend on the secret index. Returns
+    /// an error if `polys` is empty, or if the polynomials do not share the
+    /// same context and representation.
+    pub fn ct_select(polys: &[Poly], secret_index: usize) -> Result<Poly> {
+        let first = polys
+            .first()
+            .ok_or_else(|| Error::Default("No polynomial to select from".to_string()))?;
+        if polys.iter().any(|p| p.ctx != first.ctx) {
+            return Err(Error::InvalidContext);
+        }
+        if let Some(p) = polys
+            .iter()
+            .find(|p| p.representation != first.representation)
+        {
+            return Err(Error::IncorrectRepresentation(
+                p.representation.clone(),
+                first.representation.clone(),
+            ));
+        }
+
+        let mut out = Poly::try_zero(&first.ctx, first.representation.clone())?;
+        out.allow_variable_time_computations =
+            polys.iter().all(|p| p.allow_variable_time_computations);
+        out.has_lazy_coefficients = polys.iter().any(|p| p.has_lazy_coefficients);
+        let secret_index = secret_index as u64;
+        for (i, p) in polys.iter().enumerate() {
+            let choice = (i as u64).ct_eq(&secret_index);
+            izip!(out.coefficients.iter_mut(), p.coefficients.iter())
+                .for_each(|(o, c)| o.conditional_assign(c, choice));
+            if let (Some(out_shoup), Some(p_shoup)) = (
+                out.coefficients_shoup.as_mut(),
+                p.coefficients_shoup.as_ref(),
+            ) {
+                izip!(out_shoup.iter_mut(), p_shoup.iter())
+                    .for_each(|(o, c)| o.conditional_assign(c, choice));
+            }
+        }
+        Ok(out)
+    }
 }
 
 #[cfg(test)]
@@ -994,6 +1116,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn constant_ntt_polynomial_with_ntt_row() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let ctx = Arc::new(Context::new(MODULI, 16)?);
+        let p = Poly::random(&ctx, Representation::PowerBasis, &mut rng);
+        let mut p_ntt = p.clone();
+        p_ntt.change_representation(Representation::Ntt);
+        let q = Poly::random(&ctx, Representation::NttShoup, &mut rng);
+
+        for index in 0..MODULI.len() {
+            let coefficients = p.coefficients();
+            let coefficients = coefficients.row(index);
+            let ntt_coefficients = p_ntt.coefficients();
+            let ntt_coefficients = ntt_coefficients.row(index);
+            let expected = unsafe {
+                Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
+                    coefficients.as_slice().unwrap(),
+                    &ctx,
+                )
+            };
+            let r = unsafe {
+                Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_ntt_row(
+                    coefficients.as_slice().unwrap(),
+                    index,
+                    ntt_coefficients.as_slice().unwrap(),
+                    &ctx,
+                )
+            };
+            assert_eq!(&r * &q, &expected * &q);
+        }
+        Ok(())
+    }
+
     #[test]
     fn mod_switch_down_next() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -1125,4 +1280,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn multiply_scalar() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for modulus in MODULI {
+            let ctx = Arc::new(Context::new(&[*modulus], 16)?);
+            let scalar = rng.gen::<u64>();
+            let p = Poly::random(&ctx, Representation::PowerBasis, &mut rng);
+            let expected = Vec::<BigUint>::from(&p)
+                .iter()
+                .map(|c| (c * scalar) % ctx.modulus())
+                .collect_vec();
+
+            let mut q = p.clone();
+            q.multiply_scalar(scalar);
+            assert_eq!(Vec::<BigUint>::from(&q), expected);
+
+            let mut q = p.clone();
+            q.change_representation(Representation::NttShoup);
+            q.multiply_scalar(scalar);
+            let mut r = Poly::random(&ctx, Representation::Ntt, &mut rng);
+            let mut s = r.clone();
+            r *= &q;
+            let mut q_ntt = q.clone();
+            q_ntt.change_representation(Representation::Ntt);
+            s *= &q_ntt;
+            assert_eq!(r, s);
+            q.change_representation(Representation::PowerBasis);
+            assert_eq!(Vec::<BigUint>::from(&q), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ct_select() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let ctx = Arc::new(Context::new(MODULI, 16)?);
+        for representation in [
+            Representation::PowerBasis,
+            Representation::Ntt,
+            Representation::NttShoup,
+        ] {
+            let polys = (0..5)
+                .map(|_| Poly::random(&ctx, representation.clone(), &mut rng))
+                .collect_vec();
+            for (i, p) in polys.iter().enumerate() {
+                assert_eq!(&Poly::ct_select(&polys, i)?, p);
+            }
+            assert_eq!(
+                Poly::ct_select(&polys, polys.len())?,
+                Poly::zero(&ctx, representation)
+            );
+        }
+
+        assert!(Poly::ct_select(&[], 0).is_err());
+        let polys = [
+            Poly::random(&ctx, Representation::Ntt, &mut rng),
+            Poly::random(&ctx, Representation::PowerBasis, &mut rng),
+        ];
+        assert!(Poly::ct_select(&polys, 0).is_err());
+        let other_ctx = Arc::new(Context::new(&MODULI[..1], 16)?);
+        let polys = [
+            Poly::random(&ctx, Representation::Ntt, &mut rng),
+            Poly::random(&other_ctx, Representation::Ntt, &mut rng),
+        ];
+        assert!(Poly::ct_select(&polys, 0).is_err());
+        Ok(())
+    }
 }
@@ -1,17 +1,15 @@
 //! Implementation of operations over polynomials.
 
-use super::{traits::TryConvertFrom, Poly, Representation};
-use crate::{Error, Result};
-use core::{
-    cmp::min,
-    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
-};
-use itertools::{izip, Itertools};
+use super::{traits::TryConvertFrom, Context, Poly, Representation};
+use crate::{zq::Modulus, Error, Result};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use itertools::izip;
 use ndarray::Array2;
 use num_bigint::BigUint;
 use zeroize::Zeroize;
 extern crate alloc;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 
@@ -349,6 +347,129 @@ unsafe fn fma(out: &mut [u128], x: &[u64], y: &[u64]) {
     }
 }
 
+/// An accumulator of products of polynomials in Ntt representation, which
+/// defers the modular reductions to the end of the accumulation.
+///
+/// The products are accumulated in 128-bit coefficients, and an upper bound on
+/// the coefficients is maintained for every modulus: the coefficients modulo a
+/// modulus are only reduced when the next product could overflow. The
+/// polynomials may have lazy coefficients, i.e. less than four times the
+/// moduli, such as those created by
+/// [`Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time`].
+/// For moduli of less than 62 bits, at least sixteen products of reduced
+/// polynomials, or four products of a lazy and a reduced polynomial, are
+/// accumulated between two reductions, and many more for smaller moduli; the
+/// product of two lazy polynomials may however be reduced after every product,
+/// since it can be as large as `16 * q^2`.
+#[derive(Debug, Clone)]
+pub struct LazyAccumulator {
+    ctx: Arc<Context>,
+    acc: Array2<u128>,
+    /// Upper bound on the coefficients of the accumulator, for every modulus.
+    bounds: Vec<u128>,
+    allow_variable_time_computations: bool,
+}
+
+impl LazyAccumulator {
+    /// Creates an accumulator of polynomials of context `ctx`, initialized to
+    /// zero.
+    ///
+    /// Returns an error if the accumulator exceeds the allocation limit of the
+    /// context.
+    pub fn new(ctx: &Arc<Context>) -> Result<Self> {
+        // The accumulator holds u128 coefficients.
+        ctx.check_allocation(2 * ctx.poly_size_in_bytes())?;
+        Ok(Self {
+            ctx: ctx.clone(),
+            acc: Array2::zeros((ctx.q.len(), ctx.degree)),
+            bounds: vec![0; ctx.q.len()],
+            allow_variable_time_computations: false,
+        })
+    }
+
+    /// Adds the product `p * q` to the accumulator.
+    ///
+    /// Returns an error if the polynomials do not have the context of the
+    /// accumulator, or if any of them is in PowerBasis representation.
+    pub fn add_product(&mut self, p: &Poly, q: &Poly) -> Result<()> {
+        if p.ctx != self.ctx || q.ctx != self.ctx {
+            return Err(Error::Default("Incompatible contexts".to_string()));
+        }
+        if p.representation == Representation::PowerBasis
+            || q.representation == Representation::PowerBasis
+        {
+            return Err(Error::Default(
+                "The polynomials must be in Ntt or NttShoup representation".to_string(),
+            ));
+        }
+        self.allow_variable_time_computations |=
+            p.allow_variable_time_computations || q.allow_variable_time_computations;
+
+        for (mut acc_j, p_j, q_j, qj, bound) in izip!(
+            self.acc.outer_iter_mut(),
+            p.coefficients.outer_iter(),
+            q.coefficients.outer_iter(),
+            self.ctx.q.iter(),
+            self.bounds.iter_mut()
+        ) {
+            let max_coefficient = |lazy: bool| (if lazy { 4 * **qj } else { **qj } - 1) as u128;
+            // Since the moduli have at most 62 bits, the product of two lazy
+            // coefficients plus a reduced coefficient fits in 128 bits.
+            let max_product =
+                max_coefficient(p.has_lazy_coefficients) * max_coefficient(q.has_lazy_coefficients);
+            let acc_j = acc_j.as_slice_mut().unwrap();
+            if bound.checked_add(max_product).is_none() {
+                Self::reduce(acc_j, qj, self.allow_variable_time_computations);
+                *bound = (**qj - 1) as u128;
+            }
+            unsafe { fma(acc_j, p_j.as_slice().unwrap(), q_j.as_slice().unwrap()) }
+            *bound += max_product;
+        }
+        Ok(())
+    }
+
+    /// Reduces the accumulated coefficients modulo `qj`.
+    fn reduce(acc_j: &mut [u128], qj: &Modulus, variable_time: bool) {
+        if variable_time {
+            acc_j
+                .iter_mut()
+                .for_each(|c| *c = unsafe { qj.reduce_u128_vt(*c) } as u128);
+        } else {
+            acc_j
+                .iter_mut()
+                .for_each(|c| *c = qj.reduce_u128(*c) as u128);
+        }
+    }
+
+    /// Returns the accumulated polynomial, in Ntt representation, which allows
+    /// variable time computations if any of the accumulated polynomials does.
+    pub fn finish(self) -> Poly {
+        let mut coefficients: Array2<u64> = Array2::zeros((self.ctx.q.len(), self.ctx.degree));
+        izip!(
+            coefficients.outer_iter_mut(),
+            self.acc.outer_iter(),
+            self.ctx.q.iter()
+        )
+        .for_each(|(mut c_j, acc_j, qj)| {
+            if self.allow_variable_time_computations {
+                izip!(c_j.iter_mut(), acc_j.iter())
+                    .for_each(|(c, acc)| *c = unsafe { qj.reduce_u128_vt(*acc) });
+            } else {
+                izip!(c_j.iter_mut(), acc_j.iter()).for_each(|(c, acc)| *c = qj.reduce_u128(*acc));
+            }
+        });
+
+        Poly {
+            ctx: self.ctx,
+            representation: Representation::Ntt,
+            allow_variable_time_computations: self.allow_variable_time_computations,
+            coefficients,
+            coefficients_shoup: None,
+            has_lazy_coefficients: false,
+        }
+    }
+}
+
 /// Compute the dot product between two iterators of polynomials.
 /// Returna an error if the iterator counts are 0, or if any of the polynomial
 /// is not in Ntt or NttShoup representation.
@@ -357,106 +478,18 @@ where
     I: Iterator<Item = &'a Poly> + Clone,
     J: Iterator<Item = &'b Poly> + Clone,
 {
-    debug_assert!(!p
-        .clone()
-        .any(|pi| pi.representation == Representation::PowerBasis));
-    debug_assert!(!q
-        .clone()
-        .any(|qi| qi.representation == Representation::PowerBasis));
-
-    let count = min(p.clone().count(), q.clone().count());
-    if count == 0 {
+    let Some(p_first) = p.clone().next() else {
+        return Err(Error::Default("At least one iterator is empty".to_string()));
+    };
+    if q.clone().next().is_none() {
         return Err(Error::Default("At least one iterator is empty".to_string()));
     }
 
-    let p_first = p.clone().next().unwrap();
-
-    // Initialize the accumulator
-    let mut acc: Array2<u128> = Array2::zeros((p_first.ctx.q.len(), p_first.ctx.degree));
-    let acc_ptr = acc.as_mut_ptr();
-
-    // Current number of products accumulated
-    let mut num_acc = vec![1u128; p_first.ctx.q.len()];
-    let num_acc_ptr = num_acc.as_mut_ptr();
-
-    // Maximum number of products that can be accumulated
-    let max_acc = p_first
-        .ctx
-        .q
-        .iter()
-        .map(|qi| 1u128 << (2 * (*qi).leading_zeros()))
-        .collect_vec();
-    let max_acc_ptr = max_acc.as_ptr();
-
-    let q_ptr = p_first.ctx.q.as_ptr();
-    let degree = p_first.ctx.degree as isize;
-
-    let min_of_max = max_acc.iter().min().unwrap();
-
-    let out_slice = acc.as_slice_mut().unwrap();
-    if count as u128 > *min_of_max {
-        for (pi, qi) in izip!(p, q) {
-            let pij = pi.coefficients();
-            let qij = qi.coefficients();
-            let pi_slice = pij.as_slice().unwrap();
-            let qi_slice = qij.as_slice().unwrap();
-            unsafe {
-                fma(out_slice, pi_slice, qi_slice);
-
-                for j in 0..p_first.ctx.q.len() as isize {
-                    let qj = &*q_ptr.offset(j);
-                    *num_acc_ptr.offset(j) += 1;
-                    if *num_acc_ptr.offset(j) == *max_acc_ptr.offset(j) {
-                        if p_first.allow_variable_time_computations {
-                            for i in j * degree..(j + 1) * degree {
-                                *acc_ptr.offset(i) = qj.reduce_u128_vt(*acc_ptr.offset(i)) as u128;
-                            }
-                        } else {
-                            for i in j * degree..(j + 1) * degree {
-                                *acc_ptr.offset(i) = qj.reduce_u128(*acc_ptr.offset(i)) as u128;
-                            }
-                        }
-                        *num_acc_ptr.offset(j) = 1;
-                    }
-                }
-            }
-        }
-    } else {
-        // We don't need to check the condition on the max, it should shave off a few
-        // cycles.
-        for (pi, qi) in izip!(p, q) {
-            let pij = pi.coefficients();
-            let qij = qi.coefficients();
-            let pi_slice = pij.as_slice().unwrap();
-            let qi_slice = qij.as_slice().unwrap();
-            unsafe { fma(out_slice, pi_slice, qi_slice) }
-        }
+    let mut acc = LazyAccumulator::new(&p_first.ctx)?;
+    for (pi, qi) in izip!(p, q) {
+        acc.add_product(pi, qi)?;
     }
-    // Last reduction to create the coefficients
-    let mut coeffs: Array2<u64> = Array2::zeros((p_first.ctx.q.len(), p_first.ctx.degree));
-    izip!(
-        coeffs.outer_iter_mut(),
-        acc.outer_iter(),
-        p_first.ctx.q.iter()
-    )
-    .for_each(|(mut coeffsj, accj, m)| {
-        if p_first.allow_variable_time_computations {
-            izip!(coeffsj.iter_mut(), accj.iter())
-                .for_each(|(cj, accjk)| *cj = unsafe { m.reduce_u128_vt(*accjk) });
-        } else {
-            izip!(coeffsj.iter_mut(), accj.iter())
-                .for_each(|(cj, accjk)| *cj = m.reduce_u128(*accjk));
-        }
-    });
-
-    Ok(Poly {
-        ctx: p_first.ctx.clone(),
-        representation: Representation::Ntt,
-        allow_variable_time_computations: p_first.allow_variable_time_computations,
-        coefficients: coeffs,
-        coefficients_shoup: None,
-        has_lazy_coefficients: false,
-    })
+    Ok(acc.finish())
 }
 
 #[cfg(test)]
@@ -469,7 +502,8 @@ mod tests {
     use itertools::{izip, Itertools};
     use rand::thread_rng;
 
-    use super::dot_product;
+    use super::{dot_product, LazyAccumulator};
+    use crate::rq::traits::TryConvertFrom;
     use crate::{
         rq::{Context, Poly, Representation},
         zq::Modulus,
@@ -706,4 +740,75 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn lazy_accumulator() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for moduli in [&MODULI[..1], &MODULI[1..], MODULI] {
+            let ctx = Arc::new(Context::new(moduli, 16)?);
+            let mut acc = LazyAccumulator::new(&ctx)?;
+            let mut expected = Poly::zero(&ctx, Representation::Ntt);
+            for i in 0..10 {
+                let p = Poly::random(&ctx, Representation::PowerBasis, &mut rng);
+                let mut q = Poly::random(&ctx, Representation::Ntt, &mut rng);
+                if i % 2 == 0 {
+                    q.change_representation(Representation::NttShoup);
+                }
+                // A polynomial with lazy coefficients, i.e. up to 4 times the
+                // moduli, for each row of the coefficients of p.
+                for row in p.coefficients().outer_iter() {
+                    let lazy = unsafe {
+                        Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
+                            row.as_slice().unwrap(),
+                            &ctx,
+                        )
+                    };
+                    acc.add_product(&lazy, &q)?;
+                    let mut reduced = Poly::try_convert_from(
+                        row.as_slice().unwrap(),
+                        &ctx,
+                        false,
+                        Representation::PowerBasis,
+                    )?;
+                    reduced.change_representation(Representation::Ntt);
+                    expected += &(&reduced * &q);
+
+                    // The coefficients of the accumulator are within the bounds.
+                    for (acc_j, bound) in izip!(acc.acc.outer_iter(), &acc.bounds) {
+                        assert!(acc_j.iter().all(|c| c <= bound));
+                    }
+                }
+                let mut p_ntt = p.clone();
+                p_ntt.change_representation(Representation::Ntt);
+                acc.add_product(&p_ntt, &q)?;
+                expected += &(&p_ntt * &q);
+
+                // The product of two polynomials with lazy coefficients.
+                let row = p.coefficients().row(0).to_vec();
+                let lazy = unsafe {
+                    Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
+                        &row, &ctx,
+                    )
+                };
+                acc.add_product(&lazy, &lazy)?;
+                let mut reduced =
+                    Poly::try_convert_from(&row, &ctx, false, Representation::PowerBasis)?;
+                reduced.change_representation(Representation::Ntt);
+                expected += &(&reduced * &reduced);
+                for (acc_j, bound) in izip!(acc.acc.outer_iter(), &acc.bounds) {
+                    assert!(acc_j.iter().all(|c| c <= bound));
+                }
+            }
+            let r = acc.clone().finish();
+            assert_eq!(r.coefficients(), expected.coefficients());
+            assert!(r.allows_variable_time_computations());
+
+            let p = Poly::random(&ctx, Representation::PowerBasis, &mut rng);
+            assert!(acc.add_product(&p, &p).is_err());
+            let other = Arc::new(Context::new(&MODULI[..1], 32)?);
+            let q = Poly::random(&other, Representation::Ntt, &mut rng);
+            assert!(acc.add_product(&q, &q).is_err());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,194 @@
+//! Caches of the precomputed tables of the contexts.
+
+use crate::ntt::NttOperator;
+use crate::proto::rq::PrecomputationCache as PrecomputationCacheProto;
+use crate::rq::Context;
+use crate::zq::Modulus;
+use crate::{Error, Result};
+use prost::Message;
+use sha2::{Digest, Sha256};
+extern crate alloc;
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// A cache of the NTT tables of the contexts, i.e. the powers of a root of
+/// unity and their Shoup representations, indexed by modulus and degree.
+///
+/// Computing these tables searches for a root of unity and computes `4 *
+/// degree` values per modulus, for each of the contexts of a parameter set; a
+/// service can instead build its contexts with [`Context::new_with_cache`]
+/// once, save the cache with [`PrecomputationCache::to_bytes`] (or
+/// `PrecomputationCache::save` with the `std` feature), and load it at the
+/// next start.
+///
+/// The serialization contains a SHA-256 digest of the tables, which detects
+/// corrupted caches, and the roots of unity are checked when loading; the
+/// Shoup representations are however trusted, so that a cache must only be
+/// loaded from a trusted location.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecomputationCache {
+    operators: BTreeMap<(u64, usize), NttOperator>,
+}
+
+impl PrecomputationCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the NTT tables of a context and of its sub-contexts to the cache.
+    pub fn insert(&mut self, ctx: &Context) {
+        for (qi, op) in ctx.q.iter().zip(ctx.ops.iter()) {
+            self.operators
+                .entry((qi.p, ctx.degree))
+                .or_insert_with(|| op.clone());
+        }
+    }
+
+    /// Returns the number of NTT tables in the cache.
+    pub fn len(&self) -> usize {
+        self.operators.len()
+    }
+
+    /// Reports whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.operators.is_empty()
+    }
+
+    /// Returns the NTT operator of `modulus` for the size `degree`, which is
+    /// computed and added to the cache if needed.
+    pub(crate) fn ntt_operator(&mut self, modulus: &Modulus, degree: usize) -> Option<NttOperator> {
+        if let Some(op) = self.operators.get(&(modulus.p, degree)) {
+            return Some(op.clone());
+        }
+        let op = NttOperator::new(modulus, degree)?;
+        self.operators.insert((modulus.p, degree), op.clone());
+        Some(op)
+    }
+
+    /// Serializes the cache.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let tables = self.operators.values().map(|op| op.tables()).collect();
+        let mut proto = PrecomputationCacheProto {
+            tables,
+            digest: Vec::new(),
+        };
+        proto.digest = digest(&proto).to_vec();
+        proto.encode_to_vec()
+    }
+
+    /// Deserializes a cache.
+    ///
+    /// Returns an error if the digest does not match the tables, or if a
+    /// table is not that of a root of unity for its modulus and degree.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let proto = PrecomputationCacheProto::decode(bytes)
+            .map_err(|_| Error::Serialization("Invalid precomputation cache".to_string()))?;
+        if proto.digest != digest(&proto) {
+            return Err(Error::Serialization(
+                "The digest of the precomputation cache does not match".to_string(),
+            ));
+        }
+        let mut operators = BTreeMap::new();
+        for tables in &proto.tables {
+            let op = NttOperator::from_tables(tables).ok_or_else(|| {
+                Error::Serialization("Invalid NTT tables in the precomputation cache".to_string())
+            })?;
+            operators.insert((tables.modulus, tables.degree as usize), op);
+        }
+        Ok(Self { operators })
+    }
+
+    /// Saves the cache to the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| Error::Default(e.to_string()))
+    }
+
+    /// Loads a cache from the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| Error::Default(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Returns the digest of the tables of a serialized cache.
+fn digest(proto: &PrecomputationCacheProto) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    proto
+        .tables
+        .iter()
+        .for_each(|tables| hasher.update(tables.encode_to_vec()));
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digest, PrecomputationCache};
+    use crate::proto::rq::PrecomputationCache as PrecomputationCacheProto;
+    use crate::rq::{Context, Poly, Representation};
+    use crate::Error;
+    use prost::Message;
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::sync::Arc;
+
+    const MODULI: &[u64; 3] = &[
+        4611686018326724609,
+        4611686018309947393,
+        4611686018282684417,
+    ];
+
+    #[test]
+    fn cache() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let mut cache = PrecomputationCache::new();
+        assert!(cache.is_empty());
+        let ctx = Arc::new(Context::new_with_cache(MODULI, 16, &mut cache)?);
+        assert_eq!(ctx.as_ref(), &Context::new(MODULI, 16)?);
+        assert_eq!(cache.len(), 3);
+        cache.insert(&Context::new(&MODULI[..1], 32)?);
+        assert_eq!(cache.len(), 4);
+
+        let bytes = cache.to_bytes();
+        let mut loaded = PrecomputationCache::from_bytes(&bytes)?;
+        assert_eq!(loaded, cache);
+        let loaded_ctx = Arc::new(Context::new_with_cache(MODULI, 16, &mut loaded)?);
+        assert_eq!(loaded_ctx, ctx);
+        assert_eq!(loaded.len(), 4);
+
+        let mut p = Poly::random(&loaded_ctx, Representation::PowerBasis, &mut rng);
+        let q = p.clone();
+        p.change_representation(Representation::Ntt);
+        p.change_representation(Representation::PowerBasis);
+        assert_eq!(p, q);
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 40;
+        corrupted[last] ^= 1;
+        assert!(PrecomputationCache::from_bytes(&corrupted).is_err());
+        assert!(PrecomputationCache::from_bytes(&bytes[1..]).is_err());
+
+        // Tables of a wrong root of unity are rejected despite a valid digest.
+        let mut proto = PrecomputationCacheProto::decode(bytes.as_slice()).unwrap();
+        proto.tables[0].omegas.swap(1, 8);
+        proto.digest = digest(&proto).to_vec();
+        assert!(PrecomputationCache::from_bytes(&proto.encode_to_vec()).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn save_load() -> Result<(), Error> {
+        let mut cache = PrecomputationCache::new();
+        Context::new_with_cache(MODULI, 16, &mut cache)?;
+        let path = std::env::temp_dir().join("fhe-math-precomputation-cache.bin");
+        cache.save(&path)?;
+        assert_eq!(PrecomputationCache::load(&path)?, cache);
+        std::fs::remove_file(&path).unwrap();
+        assert!(PrecomputationCache::load(&path).is_err());
+        Ok(())
+    }
+}
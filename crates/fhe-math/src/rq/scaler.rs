@@ -6,13 +6,21 @@ extern crate alloc;
 
 use super::{Context, Poly, Representation};
 use crate::{
+    ntt::NttOperator,
     rns::{RnsScaler, ScalingFactor},
+    zq::Modulus,
     Error, Result,
 };
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use itertools::izip;
-use ndarray::{s, Array2, Axis};
+#[cfg(feature = "rayon")]
+use ndarray::parallel::prelude::*;
+use ndarray::{s, Array2, ArrayView2, ArrayViewMut1, ArrayViewMut2, Axis};
+use num_traits::ToPrimitive;
+
+/// Default number of coefficients scaled by each task.
+const DEFAULT_BLOCK_SIZE: usize = 512;
 
 /// Context extender.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -21,6 +29,74 @@ pub struct Scaler {
     to: Arc<Context>,
     number_common_moduli: usize,
     scaler: RnsScaler,
+    single_modulus: Option<SingleModulusScaler>,
+    /// Whether the scaling factor is one, in which case the blocks of
+    /// coefficients are converted with the fused [`RnsScaler::convert`].
+    is_conversion: bool,
+    block_size: usize,
+}
+
+/// Scaler between two contexts with a single modulus, by a factor whose
+/// numerator and odd denominator fit on 64 bits, e.g. the scaling by t / q of
+/// the decryption. It only uses arithmetic on u128, and outputs the same
+/// values as the RNS scaler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SingleModulusScaler {
+    from: u64,
+    to: Modulus,
+    numerator: u64,
+    denominator: Modulus,
+    /// The inverse of the denominator modulo 2^128.
+    denominator_inv: u128,
+}
+
+impl SingleModulusScaler {
+    fn new(from: &Context, to: &Context, factor: &ScalingFactor) -> Option<Self> {
+        if from.q.len() != 1 || to.q.len() != 1 || factor.is_one {
+            return None;
+        }
+        let numerator = factor.numerator.to_u64()?;
+        let denominator = factor.denominator.to_u64()?;
+        if denominator & 1 == 0 {
+            return None;
+        }
+        let denominator = Modulus::new(denominator).ok()?;
+
+        // Newton iteration, which doubles the number of correct low bits, starting
+        // from the 3 bits of d * d = 1 mod 8.
+        let d = *denominator as u128;
+        let mut denominator_inv = d;
+        for _ in 0..6 {
+            denominator_inv =
+                denominator_inv.wrapping_mul(2u128.wrapping_sub(d.wrapping_mul(denominator_inv)));
+        }
+        debug_assert_eq!(d.wrapping_mul(denominator_inv), 1);
+
+        Some(Self {
+            from: *from.q[0],
+            to: to.q[0].clone(),
+            numerator,
+            denominator,
+            denominator_inv,
+        })
+    }
+
+    /// Returns the rounding of `numerator * x / denominator` modulo the output
+    /// modulus, where `x` is taken as its centered representative, in constant
+    /// time.
+    fn scale(&self, x: u64) -> u64 {
+        let mask = ((x > self.from >> 1) as u64).wrapping_neg();
+        let magnitude = (x & !mask) | ((self.from - x) & mask);
+
+        // The division is exact once the remainder is subtracted.
+        let n = magnitude as u128 * self.numerator as u128 + (*self.denominator >> 1) as u128;
+        let r = self.denominator.reduce_u128(n) as u128;
+        let y = self
+            .to
+            .reduce_u128((n - r).wrapping_mul(self.denominator_inv));
+
+        (y & !mask) | (self.to.neg(y) & mask)
+    }
 }
 
 impl Scaler {
@@ -41,6 +117,8 @@ impl Scaler {
             }
         }
 
+        let single_modulus = SingleModulusScaler::new(from, to, &factor);
+        let is_conversion = factor.is_one;
         let scaler = RnsScaler::new(&from.rns, &to.rns, factor);
 
         Ok(Self {
@@ -48,9 +126,63 @@ impl Scaler {
             to: to.clone(),
             number_common_moduli,
             scaler,
+            single_modulus,
+            is_conversion,
+            block_size: DEFAULT_BLOCK_SIZE,
         })
     }
 
+    /// Sets the number of coefficients scaled by each task.
+    ///
+    /// When the `rayon` feature is enabled, the blocks of coefficients are
+    /// scaled in parallel, as well as the NTTs modulo each modulus. Returns an
+    /// error if the block size is 0.
+    pub fn set_block_size(&mut self, block_size: usize) -> Result<&mut Self> {
+        if block_size == 0 {
+            return Err(Error::Default(
+                "The block size must be positive".to_string(),
+            ));
+        }
+        self.block_size = block_size;
+        Ok(self)
+    }
+
+    /// Scale the columns of `input` into the columns of `output`, one block of
+    /// coefficients at a time.
+    fn scale_coefficients(&self, input: ArrayView2<u64>, mut output: ArrayViewMut2<u64>) {
+        if let Some(scaler) = self.single_modulus.as_ref() {
+            izip!(output.row_mut(0), input.row(0)).for_each(|(y, x)| *y = scaler.scale(*x));
+            return;
+        }
+
+        let scale_block = |(mut output, input): (ArrayViewMut2<u64>, ArrayView2<u64>)| {
+            if self.is_conversion {
+                self.scaler
+                    .convert(input, output, self.number_common_moduli)
+            } else {
+                izip!(output.axis_iter_mut(Axis(1)), input.axis_iter(Axis(1))).for_each(
+                    |(new_column, column)| {
+                        self.scaler
+                            .scale(column, new_column, self.number_common_moduli)
+                    },
+                )
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        output
+            .axis_chunks_iter_mut(Axis(1), self.block_size)
+            .into_par_iter()
+            .zip(input.axis_chunks_iter(Axis(1), self.block_size))
+            .for_each(scale_block);
+        #[cfg(not(feature = "rayon"))]
+        izip!(
+            output.axis_chunks_iter_mut(Axis(1), self.block_size),
+            input.axis_chunks_iter(Axis(1), self.block_size)
+        )
+        .for_each(scale_block);
+    }
+
     /// Scale a polynomial
     pub(crate) fn scale(&self, p: &Poly) -> Result<Poly> {
         if p.ctx.as_ref() != self.from.as_ref() {
@@ -63,6 +195,7 @@ impl Scaler {
                 representation = Representation::Ntt;
             }
 
+            self.to.check_allocation(self.to.poly_size_in_bytes())?;
             let mut new_coefficients = Array2::<u64>::zeros((self.to.q.len(), self.to.degree));
 
             if self.number_common_moduli > 0 {
@@ -73,55 +206,59 @@ impl Scaler {
 
             if self.number_common_moduli < self.to.q.len() {
                 if p.representation == Representation::PowerBasis {
-                    izip!(
-                        new_coefficients
-                            .slice_mut(s![self.number_common_moduli.., ..])
-                            .axis_iter_mut(Axis(1)),
-                        p.coefficients.axis_iter(Axis(1))
-                    )
-                    .for_each(|(new_column, column)| {
-                        self.scaler
-                            .scale(column, new_column, self.number_common_moduli)
-                    });
-                } else if self.number_common_moduli < self.to.q.len() {
+                    self.scale_coefficients(
+                        p.coefficients.view(),
+                        new_coefficients.slice_mut(s![self.number_common_moduli.., ..]),
+                    );
+                } else {
                     let mut p_coefficients_powerbasis = p.coefficients.clone();
                     // Backward NTT
-                    if p.allow_variable_time_computations {
-                        izip!(p_coefficients_powerbasis.outer_iter_mut(), p.ctx.ops.iter())
-                            .for_each(|(mut v, op)| unsafe { op.backward_vt(v.as_mut_ptr()) });
-                    } else {
-                        izip!(p_coefficients_powerbasis.outer_iter_mut(), p.ctx.ops.iter())
-                            .for_each(|(mut v, op)| op.backward(v.as_slice_mut().unwrap()));
-                    }
+                    let backward = |(mut v, op): (ArrayViewMut1<u64>, &NttOperator)| {
+                        if p.allow_variable_time_computations {
+                            unsafe { op.backward_vt(v.as_mut_ptr()) }
+                        } else {
+                            op.backward(v.as_slice_mut().unwrap())
+                        }
+                    };
+                    #[cfg(feature = "rayon")]
+                    p_coefficients_powerbasis
+                        .outer_iter_mut()
+                        .into_par_iter()
+                        .zip(p.ctx.ops.par_iter())
+                        .for_each(backward);
+                    #[cfg(not(feature = "rayon"))]
+                    izip!(p_coefficients_powerbasis.outer_iter_mut(), p.ctx.ops.iter())
+                        .for_each(backward);
+
                     // Conversion
+                    self.scale_coefficients(
+                        p_coefficients_powerbasis.view(),
+                        new_coefficients.slice_mut(s![self.number_common_moduli.., ..]),
+                    );
+
+                    // Forward NTT on the second half
+                    let forward = |(mut v, op): (ArrayViewMut1<u64>, &NttOperator)| {
+                        if p.allow_variable_time_computations {
+                            unsafe { op.forward_vt(v.as_mut_ptr()) }
+                        } else {
+                            op.forward(v.as_slice_mut().unwrap())
+                        }
+                    };
+                    #[cfg(feature = "rayon")]
+                    new_coefficients
+                        .slice_mut(s![self.number_common_moduli.., ..])
+                        .outer_iter_mut()
+                        .into_par_iter()
+                        .zip(self.to.ops[self.number_common_moduli..].par_iter())
+                        .for_each(forward);
+                    #[cfg(not(feature = "rayon"))]
                     izip!(
                         new_coefficients
                             .slice_mut(s![self.number_common_moduli.., ..])
-                            .axis_iter_mut(Axis(1)),
-                        p_coefficients_powerbasis.axis_iter(Axis(1))
+                            .outer_iter_mut(),
+                        &self.to.ops[self.number_common_moduli..]
                     )
-                    .for_each(|(new_column, column)| {
-                        self.scaler
-                            .scale(column, new_column, self.number_common_moduli)
-                    });
-                    // Forward NTT on the second half
-                    if p.allow_variable_time_computations {
-                        izip!(
-                            new_coefficients
-                                .slice_mut(s![self.number_common_moduli.., ..])
-                                .outer_iter_mut(),
-                            &self.to.ops[self.number_common_moduli..]
-                        )
-                        .for_each(|(mut v, op)| unsafe { op.forward_vt(v.as_mut_ptr()) });
-                    } else {
-                        izip!(
-                            new_coefficients
-                                .slice_mut(s![self.number_common_moduli.., ..])
-                                .outer_iter_mut(),
-                            &self.to.ops[self.number_common_moduli..]
-                        )
-                        .for_each(|(mut v, op)| op.forward(v.as_slice_mut().unwrap()));
-                    }
+                    .for_each(forward);
                 }
             }
 
@@ -162,6 +299,64 @@ mod tests {
         4611686018257518593,
     ];
 
+    #[test]
+    fn block_size() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let from = Context::new_arc(Q, 16)?;
+        let to = Context::new_arc(P, 16)?;
+
+        for factor in [
+            ScalingFactor::one(),
+            ScalingFactor::new(&BigUint::from(3u64), &BigUint::from(7u64)),
+        ] {
+            let mut scaler = Scaler::new(&from, &to, factor)?;
+            assert!(scaler.set_block_size(0).is_err());
+
+            for representation in [Representation::PowerBasis, Representation::Ntt] {
+                let poly = Poly::random(&from, representation, &mut rng);
+                let expected = scaler.scale(&poly)?;
+                for block_size in [1, 3, 16, 100] {
+                    scaler.set_block_size(block_size)?;
+                    assert_eq!(scaler.scale(&poly)?, expected);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn single_modulus() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let from = Context::new_arc(&Q[..1], 16)?;
+        for to in [
+            Context::new_arc(&[1153], 16)?,
+            Context::new_arc(&P[1..2], 16)?,
+        ] {
+            for (numerator, denominator) in [
+                (*to.moduli().first().unwrap(), Q[0]),
+                (3, 7),
+                (4611686018326724610, 1001),
+                (1000, 1000),
+            ] {
+                let factor =
+                    ScalingFactor::new(&BigUint::from(numerator), &BigUint::from(denominator));
+                let scaler = Scaler::new(&from, &to, factor)?;
+                assert_eq!(scaler.single_modulus.is_some(), denominator & 1 == 1);
+                let mut rns_scaler = scaler.clone();
+                rns_scaler.single_modulus = None;
+
+                let mut poly = Poly::random(&from, Representation::PowerBasis, &mut rng);
+                let half = Q[0] >> 1;
+                poly.coefficients
+                    .row_mut(0)
+                    .slice_mut(ndarray::s![..5])
+                    .assign(&ndarray::arr1(&[0, half - 1, half, half + 1, Q[0] - 1]));
+                assert_eq!(scaler.scale(&poly)?, rns_scaler.scale(&poly)?);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn scaler() -> Result<(), Error> {
         let mut rng = thread_rng();
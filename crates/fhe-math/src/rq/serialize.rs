@@ -4,8 +4,10 @@ extern crate alloc;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 
-use super::{traits::TryConvertFrom, Context, Poly};
-use crate::{proto::rq::Rq, Error};
+use super::{traits::TryConvertFrom, Context, Poly, Representation};
+use crate::stream::{Reader, Writer};
+use crate::{proto::rq::Rq, Error, Result};
+use alloc::vec;
 use alloc::vec::Vec;
 use fhe_traits::{DeserializeWithContext, Serialize};
 use prost::Message;
@@ -20,12 +22,85 @@ impl DeserializeWithContext for Poly {
     type Error = Error;
     type Context = Context;
 
-    fn from_bytes(bytes: &[u8], ctx: &Arc<Context>) -> Result<Self, Self::Error> {
+    fn from_bytes(bytes: &[u8], ctx: &Arc<Context>) -> Result<Self> {
         let rq: Rq = Message::decode(bytes).map_err(|e| Error::Serialization(e.to_string()))?;
         Poly::try_convert_from(&rq, ctx, false, None)
     }
 }
 
+impl Poly {
+    /// Writes the polynomial to `w` in the streaming format, one modulus at a
+    /// time, without building the whole serialization in memory.
+    ///
+    /// The format is the representation, the variable time flag, the digest of
+    /// the context, and the coefficients for each modulus, packed as in
+    /// [`Serialize::to_bytes`]; the coefficients are written in their current
+    /// representation, so that no conversion is needed. Returns an error if
+    /// the polynomial has lazy coefficients.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        if self.has_lazy_coefficients {
+            return Err(Error::Serialization(
+                "Cannot serialize lazy coefficients".to_string(),
+            ));
+        }
+        let representation = match self.representation {
+            Representation::PowerBasis => 0,
+            Representation::Ntt => 1,
+            Representation::NttShoup => 2,
+        };
+        w.write_all(&[representation, self.allow_variable_time_computations as u8])?;
+        w.write_all(&self.ctx.digest())?;
+        for (row, qi) in self.coefficients.outer_iter().zip(self.ctx.q.iter()) {
+            w.write_all(&qi.serialize_vec(row.as_slice().unwrap()))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a polynomial in the context `ctx` from `r`, in the streaming
+    /// format of [`Poly::write_to`].
+    ///
+    /// Returns an error if the serialization was produced in another context,
+    /// or if a coefficient is not reduced modulo its modulus.
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, ctx: &Arc<Context>) -> Result<Self> {
+        let mut header = [0u8; 2];
+        r.read_exact(&mut header)?;
+        let representation = match header[0] {
+            0 => Representation::PowerBasis,
+            1 => Representation::Ntt,
+            2 => Representation::NttShoup,
+            _ => return Err(Error::Serialization("Invalid representation".to_string())),
+        };
+        if header[1] > 1 {
+            return Err(Error::Serialization("Invalid flag".to_string()));
+        }
+        let mut digest = [0u8; 32];
+        r.read_exact(&mut digest)?;
+        if digest != ctx.digest() {
+            return Err(Error::InvalidContext);
+        }
+
+        let mut coefficients = Vec::with_capacity(ctx.q.len() * ctx.degree);
+        for qi in ctx.q.iter() {
+            let mut bytes = vec![0u8; qi.serialization_length(ctx.degree)];
+            r.read_exact(&mut bytes)?;
+            let mut v = qi.deserialize_vec(&bytes);
+            v.truncate(ctx.degree);
+            if v.iter().any(|vi| *vi >= **qi) {
+                return Err(Error::Serialization("Invalid coefficients".to_string()));
+            }
+            coefficients.append(&mut v);
+        }
+        let stored = if representation == Representation::PowerBasis {
+            Representation::PowerBasis
+        } else {
+            Representation::Ntt
+        };
+        let mut p = Poly::try_convert_from(coefficients, ctx, header[1] == 1, stored)?;
+        p.change_representation(representation);
+        Ok(p)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Error;
@@ -38,6 +113,7 @@ mod tests {
     use rand::thread_rng;
 
     use crate::rq::{Context, Poly, Representation};
+    use alloc::vec::Vec;
 
     const Q: &[u64; 3] = &[
         4611686018282684417,
@@ -69,4 +145,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let ctx = Arc::new(Context::new(Q, 16)?);
+        for representation in [
+            Representation::PowerBasis,
+            Representation::Ntt,
+            Representation::NttShoup,
+        ] {
+            let mut p = Poly::random(&ctx, representation, &mut rng);
+            unsafe { p.allow_variable_time_computations() }
+            let mut bytes = Vec::new();
+            p.write_to(&mut bytes)?;
+            let mut r = &bytes[..];
+            assert_eq!(Poly::read_from(&mut r, &ctx)?, p);
+            assert!(r.is_empty());
+
+            // A buffer one byte too small.
+            let mut buf = alloc::vec![0u8; bytes.len() - 1];
+            assert!(p.write_to(&mut &mut buf[..]).is_err());
+            assert!(Poly::read_from(&mut &bytes[..bytes.len() - 1], &ctx).is_err());
+        }
+
+        let p = Poly::random(&ctx, Representation::Ntt, &mut rng);
+        let mut bytes = Vec::new();
+        p.write_to(&mut bytes)?;
+        let other = Arc::new(Context::new(&Q[..2], 16)?);
+        assert_eq!(
+            Poly::read_from(&mut &bytes[..], &other),
+            Err(Error::InvalidContext)
+        );
+        bytes[0] = 3;
+        assert!(Poly::read_from(&mut &bytes[..], &ctx).is_err());
+        Ok(())
+    }
 }
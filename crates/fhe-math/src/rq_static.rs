@@ -0,0 +1,310 @@
+//! Experimental polynomials of a fixed degree with array storage.
+//!
+//! A [`Poly<N>`] is a polynomial of the ring `Z_p[X] / (X^N + 1)` for a single
+//! modulus `p`, whose coefficients are stored in an array, so that the
+//! arithmetic does not allocate on the heap. The multiplication is the
+//! schoolbook negacyclic product, in `O(N^2)` operations, which is suited to
+//! the tiny degrees usable on microcontrollers; the [`rq`](crate::rq) module
+//! should be used otherwise.
+//!
+//! # Stability
+//!
+//! This API is experimental, and may change without a major version bump.
+
+use crate::rq::{traits::TryConvertFrom, Context, Poly as RqPoly, Representation};
+use crate::zq::Modulus;
+use crate::{Error, Result};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use rand::{CryptoRng, RngCore};
+use subtle::{Choice, ConditionallySelectable};
+use zeroize::Zeroize;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+
+/// A polynomial of degree less than `N` modulo `X^N + 1` and a single
+/// modulus, in power basis representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly<const N: usize> {
+    modulus: Modulus,
+    coefficients: [u64; N],
+}
+
+impl<const N: usize> Poly<N> {
+    /// Creates a polynomial holding the constant 0.
+    pub fn zero(modulus: &Modulus) -> Self {
+        Self {
+            modulus: modulus.clone(),
+            coefficients: [0; N],
+        }
+    }
+
+    /// Creates a polynomial from its coefficients, which are reduced modulo
+    /// the modulus.
+    pub fn from_coefficients(coefficients: [u64; N], modulus: &Modulus) -> Self {
+        Self {
+            modulus: modulus.clone(),
+            coefficients: coefficients.map(|c| modulus.reduce(c)),
+        }
+    }
+
+    /// Creates a polynomial from signed coefficients, which are reduced
+    /// modulo the modulus in constant time.
+    pub fn from_i64(coefficients: &[i64; N], modulus: &Modulus) -> Self {
+        let mut out = Self::zero(modulus);
+        for (o, c) in out.coefficients.iter_mut().zip(coefficients) {
+            let r = modulus.reduce(c.unsigned_abs());
+            *o = u64::conditional_select(&r, &modulus.neg(r), Choice::from((*c < 0) as u8));
+        }
+        out
+    }
+
+    /// Generates a polynomial with coefficients sampled uniformly modulo the
+    /// modulus.
+    pub fn random<R: RngCore + CryptoRng>(modulus: &Modulus, rng: &mut R) -> Self {
+        let mask = u64::MAX >> modulus.leading_zeros();
+        let mut out = Self::zero(modulus);
+        for o in out.coefficients.iter_mut() {
+            // Rejection sampling, which leaks the number of rejections but not
+            // the accepted values.
+            *o = loop {
+                let v = rng.next_u64() & mask;
+                if v < **modulus {
+                    break v;
+                }
+            };
+        }
+        out
+    }
+
+    /// Generates a small polynomial, whose coefficients are sampled from a
+    /// centered binomial distribution of variance `variance`, between 1 and
+    /// 16.
+    pub fn small<R: RngCore>(modulus: &Modulus, variance: usize, rng: &mut R) -> Result<Self> {
+        if !(1..=16).contains(&variance) {
+            return Err(Error::Default(
+                "The variance should be between 1 and 16".to_string(),
+            ));
+        }
+        let k = 2 * variance;
+        let mask = u64::MAX >> (64 - k);
+        let mut coefficients = [0i64; N];
+        for c in coefficients.iter_mut() {
+            let bits = rng.next_u64();
+            *c = (bits & mask).count_ones() as i64 - ((bits >> k) & mask).count_ones() as i64;
+        }
+        let out = Self::from_i64(&coefficients, modulus);
+        coefficients.zeroize();
+        Ok(out)
+    }
+
+    /// Returns the modulus of the polynomial.
+    pub fn modulus(&self) -> &Modulus {
+        &self.modulus
+    }
+
+    /// Returns the coefficients of the polynomial.
+    pub fn coefficients(&self) -> &[u64; N] {
+        &self.coefficients
+    }
+
+    /// Converts the polynomial into a [`rq::Poly`](crate::rq::Poly) in power
+    /// basis representation, in a context of degree `N` with the same single
+    /// modulus.
+    pub fn to_poly(&self, ctx: &Arc<Context>) -> Result<RqPoly> {
+        check_context::<N>(ctx, &self.modulus)?;
+        RqPoly::try_convert_from(
+            self.coefficients.as_slice(),
+            ctx,
+            false,
+            Representation::PowerBasis,
+        )
+    }
+
+    /// Converts a [`rq::Poly`](crate::rq::Poly) of degree `N` with a single
+    /// modulus into a polynomial with array storage.
+    pub fn try_from_poly(p: &RqPoly) -> Result<Self> {
+        let modulus = Modulus::new(p.ctx().moduli()[0])?;
+        check_context::<N>(p.ctx(), &modulus)?;
+        let mut p = p.clone();
+        p.change_representation(Representation::PowerBasis);
+        let mut out = Self::zero(&modulus);
+        for (o, c) in out.coefficients.iter_mut().zip(p.coefficients().row(0)) {
+            *o = *c;
+        }
+        p.zeroize();
+        Ok(out)
+    }
+}
+
+/// Checks that the context has the degree `N` and the single modulus
+/// `modulus`.
+fn check_context<const N: usize>(ctx: &Context, modulus: &Modulus) -> Result<()> {
+    if ctx.degree() != N || ctx.moduli() != [**modulus] {
+        Err(Error::InvalidContext)
+    } else {
+        Ok(())
+    }
+}
+
+impl<const N: usize> Zeroize for Poly<N> {
+    fn zeroize(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl<const N: usize> AddAssign<&Poly<N>> for Poly<N> {
+    fn add_assign(&mut self, p: &Poly<N>) {
+        assert_eq!(self.modulus, p.modulus, "Incompatible moduli");
+        self.modulus
+            .add_vec(&mut self.coefficients, &p.coefficients);
+    }
+}
+
+impl<const N: usize> Add<&Poly<N>> for &Poly<N> {
+    type Output = Poly<N>;
+    fn add(self, p: &Poly<N>) -> Poly<N> {
+        let mut q = self.clone();
+        q += p;
+        q
+    }
+}
+
+impl<const N: usize> SubAssign<&Poly<N>> for Poly<N> {
+    fn sub_assign(&mut self, p: &Poly<N>) {
+        assert_eq!(self.modulus, p.modulus, "Incompatible moduli");
+        self.modulus
+            .sub_vec(&mut self.coefficients, &p.coefficients);
+    }
+}
+
+impl<const N: usize> Sub<&Poly<N>> for &Poly<N> {
+    type Output = Poly<N>;
+    fn sub(self, p: &Poly<N>) -> Poly<N> {
+        let mut q = self.clone();
+        q -= p;
+        q
+    }
+}
+
+impl<const N: usize> MulAssign<&Poly<N>> for Poly<N> {
+    fn mul_assign(&mut self, p: &Poly<N>) {
+        assert_eq!(self.modulus, p.modulus, "Incompatible moduli");
+        let m = &self.modulus;
+        let mut out = [0u64; N];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            let minus_a = m.neg(*a);
+            for (j, b) in p.coefficients.iter().enumerate() {
+                // X^N = -1, so the terms of degree at least N are subtracted.
+                if i + j < N {
+                    out[i + j] = m.mul_add(*a, *b, out[i + j]);
+                } else {
+                    out[i + j - N] = m.mul_add(minus_a, *b, out[i + j - N]);
+                }
+            }
+        }
+        self.coefficients = out;
+        out.zeroize();
+    }
+}
+
+impl<const N: usize> Mul<&Poly<N>> for &Poly<N> {
+    type Output = Poly<N>;
+    fn mul(self, p: &Poly<N>) -> Poly<N> {
+        let mut q = self.clone();
+        q *= p;
+        q
+    }
+}
+
+impl<const N: usize> Neg for &Poly<N> {
+    type Output = Poly<N>;
+    fn neg(self) -> Poly<N> {
+        let mut q = self.clone();
+        q.modulus.neg_vec(&mut q.coefficients);
+        q
+    }
+}
+
+impl<const N: usize> Neg for Poly<N> {
+    type Output = Poly<N>;
+    fn neg(mut self) -> Poly<N> {
+        self.modulus.neg_vec(&mut self.coefficients);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Poly;
+    use crate::rq::{Context, Representation};
+    use crate::zq::Modulus;
+    use crate::Error;
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::sync::Arc;
+
+    const N: usize = 16;
+
+    #[test]
+    fn arithmetic() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for p in [4611686018326724609, 1153] {
+            let modulus = Modulus::new(p)?;
+            let ctx = Arc::new(Context::new(&[p], N)?);
+            let a = Poly::<N>::random(&modulus, &mut rng);
+            let b = Poly::<N>::random(&modulus, &mut rng);
+            assert!(a.coefficients().iter().all(|c| *c < p));
+            let (pa, pb) = (a.to_poly(&ctx)?, b.to_poly(&ctx)?);
+            assert_eq!(Poly::<N>::try_from_poly(&pa)?, a);
+
+            assert_eq!(Poly::try_from_poly(&(&pa + &pb))?, &a + &b);
+            assert_eq!(Poly::try_from_poly(&(&pa - &pb))?, &a - &b);
+            assert_eq!(Poly::try_from_poly(&(-&pa))?, -&a);
+            assert_eq!(Poly::try_from_poly(&(-&pa))?, -a.clone());
+            let mut pa_ntt = pa.clone();
+            let mut pb_ntt = pb.clone();
+            pa_ntt.change_representation(Representation::Ntt);
+            pb_ntt.change_representation(Representation::Ntt);
+            assert_eq!(Poly::try_from_poly(&(&pa_ntt * &pb_ntt))?, &a * &b);
+
+            assert_eq!(&a + &Poly::zero(&modulus), a);
+            assert_eq!(&a * &Poly::zero(&modulus), Poly::zero(&modulus));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn conversions() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let modulus = Modulus::new(1153)?;
+        let mut coefficients = [0i64; N];
+        coefficients[0] = -1;
+        coefficients[1] = 1154;
+        let p = Poly::<N>::from_i64(&coefficients, &modulus);
+        assert_eq!(p.coefficients()[..2], [1152, 1]);
+        let mut coefficients = [0u64; N];
+        coefficients[0] = 1154;
+        assert_eq!(
+            Poly::<N>::from_coefficients(coefficients, &modulus).coefficients()[0],
+            1
+        );
+
+        let small = Poly::<N>::small(&modulus, 2, &mut rng)?;
+        assert!(small
+            .coefficients()
+            .iter()
+            .all(|c| *c <= 4 || *c >= 1153 - 4));
+        assert!(Poly::<N>::small(&modulus, 17, &mut rng).is_err());
+
+        let ctx = Arc::new(Context::new(&[1153, 4611686018326724609], N)?);
+        assert!(p.to_poly(&ctx).is_err());
+        let ctx = Arc::new(Context::new(&[4611686018326724609], N)?);
+        assert!(p.to_poly(&ctx).is_err());
+        assert!(Poly::<8>::try_from_poly(
+            &Poly::<N>::zero(&Modulus::new(4611686018326724609)?).to_poly(&ctx)?
+        )
+        .is_err());
+        Ok(())
+    }
+}
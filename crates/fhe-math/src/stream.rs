@@ -0,0 +1,138 @@
+//! Streaming serialization.
+//!
+//! The `to_bytes` serializations build a protobuf message holding the whole
+//! serialization before encoding it, which doubles the memory needed for large
+//! objects such as evaluation keys. The `write_to` and `read_from` methods
+//! instead stream the objects polynomial by polynomial, through a [`Writer`]
+//! and a [`Reader`]. With the `std` feature, these traits are implemented by
+//! all the types implementing `std::io::Write` and `std::io::Read`, e.g. files
+//! and sockets; otherwise, they are implemented by byte slices, which are
+//! advanced past the bytes written or read, and by vectors of bytes, which are
+//! appended to. The streaming format is distinct from the protobuf format.
+
+use crate::{Error, Result};
+extern crate alloc;
+use alloc::string::ToString;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// A destination of bytes for the streaming serialization.
+pub trait Writer {
+    /// Writes all the bytes of `buf`, or returns an error.
+    fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+}
+
+/// A source of bytes for the streaming deserialization.
+pub trait Reader {
+    /// Reads exactly enough bytes to fill `buf`, or returns an error.
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> Writer for W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        std::io::Write::write_all(self, buf).map_err(|e| Error::Serialization(e.to_string()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + ?Sized> Reader for R {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        std::io::Read::read_exact(self, buf).map_err(|e| Error::Serialization(e.to_string()))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Writer for &mut [u8] {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if buf.len() > self.len() {
+            return Err(Error::Serialization("The buffer is too small".to_string()));
+        }
+        let (head, tail) = core::mem::take(self).split_at_mut(buf.len());
+        head.copy_from_slice(buf);
+        *self = tail;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Writer for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Reader for &[u8] {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        if buf.len() > self.len() {
+            return Err(Error::Serialization("Unexpected end of input".to_string()));
+        }
+        let (head, tail) = self.split_at(buf.len());
+        buf.copy_from_slice(head);
+        *self = tail;
+        Ok(())
+    }
+}
+
+/// Writes a `u32` in little-endian order.
+pub fn write_u32<W: Writer + ?Sized>(w: &mut W, value: u32) -> Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+/// Reads a `u32` in little-endian order.
+pub fn read_u32<R: Reader + ?Sized>(r: &mut R) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Writes a `u64` in little-endian order.
+pub fn write_u64<W: Writer + ?Sized>(w: &mut W, value: u64) -> Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+/// Reads a `u64` in little-endian order.
+pub fn read_u64<R: Reader + ?Sized>(r: &mut R) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_u32, read_u64, write_u32, write_u64, Reader, Writer};
+    use crate::Error;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn slices_and_vectors() -> Result<(), Error> {
+        let mut v = Vec::new();
+        write_u32(&mut v, 0x01020304)?;
+        v.write_all(&[5, 6])?;
+        assert_eq!(v, [4, 3, 2, 1, 5, 6]);
+
+        let mut buf = [0u8; 5];
+        let mut w = &mut buf[..];
+        write_u32(&mut w, 7)?;
+        assert_eq!(w.len(), 1);
+        assert!(w.write_all(&[1, 2]).is_err());
+        assert_eq!(buf[..4], [7, 0, 0, 0]);
+
+        let mut r = &v[..];
+        assert_eq!(read_u32(&mut r)?, 0x01020304);
+        let mut rest = [0u8; 2];
+        r.read_exact(&mut rest)?;
+        assert_eq!(rest, [5, 6]);
+        assert!(read_u32(&mut r).is_err());
+
+        let mut v = Vec::new();
+        write_u64(&mut v, u64::MAX - 1)?;
+        assert_eq!(v.len(), 8);
+        assert_eq!(read_u64(&mut &v[..])?, u64::MAX - 1);
+        assert!(read_u64(&mut &v[1..]).is_err());
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 //! Ring operations for moduli up to 62 bits.
 
 pub mod primes;
+pub mod roots;
 
 use core::ops::Deref;
 
@@ -158,6 +159,48 @@ impl Modulus {
         self.reduce_opt_u128_vt((a as u128) * (b as u128))
     }
 
+    /// Performs the modular multiplication and addition a * b + c in constant
+    /// time, with a single reduction of the 128-bit result.
+    /// Aborts if a >= p, b >= p, or c >= p in debug mode.
+    pub const fn mul_add(&self, a: u64, b: u64, c: u64) -> u64 {
+        debug_assert!(a < self.p && b < self.p && c < self.p);
+        self.reduce_u128((a as u128) * (b as u128) + (c as u128))
+    }
+
+    /// Performs the modular multiplication and addition a * b + c in variable
+    /// time, with a single reduction of the 128-bit result.
+    /// Aborts if a >= p, b >= p, or c >= p in debug mode.
+    ///
+    /// # Safety
+    /// This function is not constant time and its timing may reveal information
+    /// about the values being multiplied.
+    const unsafe fn mul_add_vt(&self, a: u64, b: u64, c: u64) -> u64 {
+        debug_assert!(a < self.p && b < self.p && c < self.p);
+        self.reduce_u128_vt((a as u128) * (b as u128) + (c as u128))
+    }
+
+    /// Optimized modular multiplication and addition a * b + c in constant
+    /// time; the result is reduced once since a * b + c < p^2.
+    /// Aborts if a >= p, b >= p, or c >= p in debug mode.
+    const fn mul_add_opt(&self, a: u64, b: u64, c: u64) -> u64 {
+        debug_assert!(self.supports_opt);
+        debug_assert!(a < self.p && b < self.p && c < self.p);
+        self.reduce_opt_u128((a as u128) * (b as u128) + (c as u128))
+    }
+
+    /// Optimized modular multiplication and addition a * b + c in variable
+    /// time.
+    /// Aborts if a >= p, b >= p, or c >= p in debug mode.
+    ///
+    /// # Safety
+    /// This function is not constant time and its timing may reveal information
+    /// about the values being multiplied.
+    const unsafe fn mul_add_opt_vt(&self, a: u64, b: u64, c: u64) -> u64 {
+        debug_assert!(self.supports_opt);
+        debug_assert!(a < self.p && b < self.p && c < self.p);
+        self.reduce_opt_u128_vt((a as u128) * (b as u128) + (c as u128))
+    }
+
     /// Modular negation in constant time.
     ///
     /// Aborts if a >= p in debug mode.
@@ -399,6 +442,54 @@ impl Modulus {
         }
     }
 
+    /// Modular multiplication and addition of vectors in place in constant
+    /// time, i.e. a = a * b + c, with a single reduction per coefficient.
+    ///
+    /// Aborts if a, b and c differ in size, and if any of their values is >= p
+    /// in debug mode.
+    pub fn mul_add_vec(&self, a: &mut [u64], b: &[u64], c: &[u64]) {
+        debug_assert_eq!(a.len(), b.len());
+        debug_assert_eq!(a.len(), c.len());
+
+        if self.supports_opt {
+            self.arch.dispatch(|| {
+                izip!(a.iter_mut(), b.iter(), c.iter())
+                    .for_each(|(ai, bi, ci)| *ai = self.mul_add_opt(*ai, *bi, *ci))
+            })
+        } else {
+            self.arch.dispatch(|| {
+                izip!(a.iter_mut(), b.iter(), c.iter())
+                    .for_each(|(ai, bi, ci)| *ai = self.mul_add(*ai, *bi, *ci))
+            })
+        }
+    }
+
+    /// Modular multiplication and addition of vectors in place in variable
+    /// time, i.e. a = a * b + c.
+    ///
+    /// Aborts if a, b and c differ in size, and if any of their values is >= p
+    /// in debug mode.
+    ///
+    /// # Safety
+    /// This function is not constant time and its timing may reveal information
+    /// about the values being multiplied.
+    pub unsafe fn mul_add_vec_vt(&self, a: &mut [u64], b: &[u64], c: &[u64]) {
+        debug_assert_eq!(a.len(), b.len());
+        debug_assert_eq!(a.len(), c.len());
+
+        if self.supports_opt {
+            self.arch.dispatch(|| {
+                izip!(a.iter_mut(), b.iter(), c.iter())
+                    .for_each(|(ai, bi, ci)| *ai = self.mul_add_opt_vt(*ai, *bi, *ci))
+            })
+        } else {
+            self.arch.dispatch(|| {
+                izip!(a.iter_mut(), b.iter(), c.iter())
+                    .for_each(|(ai, bi, ci)| *ai = self.mul_add_vt(*ai, *bi, *ci))
+            })
+        }
+    }
+
     /// Compute the Shoup representation of a vector.
     ///
     /// Aborts if any of the values of the vector is >= p in debug mode.
@@ -757,23 +848,23 @@ impl Modulus {
     }
 
     /// Length of the serialization of a vector of size `size`.
-    ///
-    /// Panics if the size is not a multiple of 8.
     pub const fn serialization_length(&self, size: usize) -> usize {
-        assert!(size % 8 == 0);
         let p_nbits = 64 - (self.p - 1).leading_zeros() as usize;
-        p_nbits * size / 8
+        (p_nbits * size).div_ceil(8)
     }
 
-    /// Serialize a vector of elements of length a multiple of 8.
+    /// Serialize a vector of elements.
     ///
-    /// Panics if the length of the vector is not a multiple of 8.
+    /// The last byte is padded with zeros when the total number of bits is not
+    /// a multiple of 8.
     pub fn serialize_vec(&self, a: &[u64]) -> Vec<u8> {
         let p_nbits = 64 - (self.p - 1).leading_zeros() as usize;
         transcode_to_bytes(a, p_nbits)
     }
 
     /// Deserialize a vector of bytes into a vector of elements mod p.
+    ///
+    /// The output may contain an extra element when the last byte is padded.
     pub fn deserialize_vec(&self, b: &[u8]) -> Vec<u64> {
         let p_nbits = 64 - (self.p - 1).leading_zeros() as usize;
         transcode_from_bytes(b, p_nbits)
@@ -788,12 +879,9 @@ mod tests {
     use proptest::prelude::{any, BoxedStrategy, Just, Strategy};
     use rand::{thread_rng, RngCore};
     extern crate alloc;
-    
-    
+
     use alloc::format;
-    
-    
-    
+
     use alloc::vec::Vec;
 
     // Utility functions for the proptests.
@@ -890,6 +978,22 @@ mod tests {
             //}
         }
 
+        #[test]
+        fn mul_add(p in valid_moduli(), mut a: u64, mut b: u64, mut c: u64) {
+            a = p.reduce(a);
+            b = p.reduce(b);
+            c = p.reduce(c);
+            let expected = ((a as u128) * (b as u128) + (c as u128)) % (*p as u128);
+            prop_assert_eq!(p.mul_add(a, b, c) as u128, expected);
+            unsafe { prop_assert_eq!(p.mul_add_vt(a, b, c) as u128, expected) }
+            if p.supports_opt {
+                prop_assert_eq!(p.mul_add_opt(a, b, c) as u128, expected);
+                unsafe { prop_assert_eq!(p.mul_add_opt_vt(a, b, c) as u128, expected) }
+            }
+            let m = *p - 1;
+            prop_assert_eq!(p.mul_add(m, m, m), p.add(p.mul(m, m), m));
+        }
+
         #[test]
         fn mul_shoup(p in valid_moduli(), mut a: u64, mut b: u64) {
             a = p.reduce(a);
@@ -988,6 +1092,20 @@ mod tests {
             prop_assert_eq!(a, izip!(b.iter(), c.iter()).map(|(bi, ci)| p.mul(*ci, *bi)).collect_vec());
         }
 
+        #[test]
+        fn mul_add_vec(p in valid_moduli(), (mut a, mut b) in vecs()) {
+            p.reduce_vec(&mut a);
+            p.reduce_vec(&mut b);
+            let c = a.iter().rev().copied().collect_vec();
+            let d = a.clone();
+            let expected = izip!(b.iter(), c.iter(), d.iter()).map(|(bi, ci, di)| p.add(p.mul(*di, *bi), *ci)).collect_vec();
+            p.mul_add_vec(&mut a, &b, &c);
+            prop_assert_eq!(&a, &expected);
+            a.clone_from(&d);
+            unsafe { p.mul_add_vec_vt(&mut a, &b, &c); }
+            prop_assert_eq!(a, expected);
+        }
+
         #[test]
         fn scalar_mul_vec(p in valid_moduli(), mut a: Vec<u64>, mut b: u64) {
             p.reduce_vec(&mut a);
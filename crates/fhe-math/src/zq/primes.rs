@@ -1,7 +1,9 @@
 //! Optimized primes generated as in the NFLlib library.
 
-use fhe_util::is_prime;
+use fhe_util::{inverse, is_prime};
 use num_bigint::BigUint;
+extern crate alloc;
+use alloc::vec::Vec;
 
 /// Returns whether the modulus supports optimized multiplication and reduction.
 /// These optimized operations are possible when the modulus verifies
@@ -56,16 +58,93 @@ pub fn generate_prime(num_bits: usize, modulo: u64, upper_bound: u64) -> Option<
     }
 }
 
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Maximum number of residues tried per prime by [`generate_optimized_moduli`].
+const MAX_RESIDUES: u64 = 1 << 16;
+
+/// Maximum number of candidates tried per residue by
+/// [`generate_optimized_moduli`].
+const MAX_CANDIDATES: u64 = 1 << 12;
+
+/// Generate distinct primes of `sizes` bits, congruent to 1 modulo
+/// `2 * degree`, whose residues modulo `plaintext` are as close to 0 as
+/// possible.
+///
+/// The noise added by a BFV multiplication grows with the residue of the
+/// ciphertext modulus modulo the plaintext modulus, taken in
+/// `(-plaintext / 2, plaintext / 2]`. The residues 1, -1, 2, -2, ... are tried
+/// in this order for each prime, so that the primes are congruent to 1 or -1
+/// modulo `plaintext` whenever such primes exist, and so is their product at
+/// every level. For each size, the largest prime with the first residue for
+/// which a prime exists is selected. Returns None if a size does not belong to
+/// (10..=62), or if no prime is found.
+pub fn generate_optimized_moduli(
+    sizes: &[usize],
+    degree: usize,
+    plaintext: u64,
+) -> Option<Vec<u64>> {
+    let m = 2 * degree as u64;
+    if plaintext < 2 || m == 0 {
+        return None;
+    }
+    let g = gcd(m, plaintext);
+    let t_g = plaintext / g;
+    // The residues modulo plaintext are in (-plaintext / 2, plaintext / 2].
+    let max_residue = (plaintext / 2).min(MAX_RESIDUES);
+
+    let mut moduli: Vec<u64> = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        if !(10..=62).contains(size) {
+            return None;
+        }
+        let (lower, upper) = (1u128 << (size - 1), 1u128 << size);
+
+        let prime = (1..=max_residue)
+            .flat_map(|r| [r, plaintext - r])
+            .find_map(|residue| {
+                // Solve q = 1 mod m and q = residue mod plaintext, which requires
+                // residue = 1 mod gcd(m, plaintext). Then q = 1 + m * k, where
+                // (m / g) * k = (residue - 1) / g mod (plaintext / g).
+                if (residue - 1) % g != 0 {
+                    return None;
+                }
+                let k = if t_g == 1 {
+                    0
+                } else {
+                    let rhs = ((residue - 1) / g) % t_g;
+                    let inv = inverse((m / g) % t_g, t_g)?;
+                    ((rhs as u128 * inv as u128) % t_g as u128) as u64
+                };
+                let lcm = m as u128 * t_g as u128;
+                let q0 = (1 + m as u128 * k as u128) % lcm;
+                if q0 >= upper {
+                    return None;
+                }
+                let top = q0 + (upper - 1 - q0) / lcm * lcm;
+                (0..MAX_CANDIDATES as u128)
+                    .map(|i| top.checked_sub(i * lcm))
+                    .take_while(|q| q.is_some_and(|q| q >= lower))
+                    .flatten()
+                    .map(|q| q as u64)
+                    .find(|q| *q != plaintext && !moduli.contains(q) && is_prime(*q))
+            })?;
+        moduli.push(prime);
+    }
+    Some(moduli)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::generate_prime;
+    use super::{generate_optimized_moduli, generate_prime};
     extern crate alloc;
-    
-    
-    
-    
+
     use alloc::vec;
-    
 
     // Verifies that the same moduli as in the NFLlib library are generated.
     // <https://github.com/quarkslab/NFLlib/blob/master/include/nfl/params.hpp>
@@ -125,4 +204,46 @@ mod tests {
         // smaller one should fail.
         assert!(generate_prime(11, 16, 1033).is_none());
     }
+
+    #[test]
+    fn optimized_moduli() {
+        // When 2 * degree * plaintext is larger than the primes, few primes have
+        // a given residue, and the residues are not all 1 or -1.
+        for (sizes, degree, plaintext, unit_residues) in [
+            (vec![62, 62, 62, 50, 30], 16, 1153, true),
+            (vec![62; 5], 2048, 65537, true),
+            (vec![60, 40], 1024, 1 << 20, true),
+            (vec![54, 54, 54], 4096, 0x3fffffff000001, false),
+        ] {
+            let moduli = generate_optimized_moduli(&sizes, degree, plaintext).unwrap();
+            assert_eq!(moduli.len(), sizes.len());
+            for (i, (q, size)) in moduli.iter().zip(&sizes).enumerate() {
+                assert_eq!(64 - q.leading_zeros() as usize, *size);
+                assert_eq!(q % (2 * degree as u64), 1);
+                assert!(fhe_util::is_prime(*q));
+                assert!(!moduli[..i].contains(q));
+            }
+
+            // The residues are at least as small as those of the primes of the
+            // same sizes generated without optimization.
+            let residue = |q: u64| {
+                let r = q % plaintext;
+                r.min(plaintext - r)
+            };
+            for (q, size) in moduli.iter().zip(&sizes) {
+                let baseline = generate_prime(*size, 2 * degree as u64, 1 << size).unwrap();
+                assert!(residue(*q) <= residue(baseline));
+            }
+            let product = moduli.iter().fold(1, |acc, q| {
+                ((acc as u128 * *q as u128) % plaintext as u128) as u64
+            });
+            if unit_residues {
+                assert_eq!(residue(product), 1);
+            }
+        }
+
+        assert!(generate_optimized_moduli(&[63], 16, 1153).is_none());
+        assert!(generate_optimized_moduli(&[9], 16, 1153).is_none());
+        assert!(generate_optimized_moduli(&[62], 16, 1).is_none());
+    }
 }
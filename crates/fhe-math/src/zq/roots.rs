@@ -0,0 +1,100 @@
+//! Primitive roots of unity, as used by negacyclic Number-Theoretic
+//! Transforms.
+
+use super::Modulus;
+use fhe_util::is_prime;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Returns whether `a` is a primitive `order`-th root of unity modulo p.
+///
+/// The order must be a power of 2; returns false otherwise.
+pub fn is_primitive_root_of_unity(p: &Modulus, a: u64, order: usize) -> bool {
+    if a >= p.p || order < 2 || !order.is_power_of_two() || order as u64 >= p.p {
+        return false;
+    }
+
+    // Since the order is a power of 2, a is a primitive root of unity when
+    // a^order = 1 mod p and a^(order / 2) != 1 mod p.
+    (p.pow(a, order as u64) == 1) && (p.pow(a, (order / 2) as u64) != 1)
+}
+
+/// Returns a primitive `order`-th root of unity modulo p, e.g. a `2n`-th
+/// primitive root for a negacyclic transform of size n.
+///
+/// The root is the one used by the Number-Theoretic Transforms of this crate,
+/// and is deterministic. Returns None if p is not prime, or if the order is
+/// not a power of 2 dividing p - 1.
+pub fn primitive_root_of_unity(p: &Modulus, order: usize) -> Option<u64> {
+    if order < 2 || !order.is_power_of_two() || (p.p - 1) % order as u64 != 0 || !is_prime(p.p) {
+        return None;
+    }
+
+    let lambda = (p.p - 1) / order as u64;
+
+    let mut rng: ChaCha8Rng = SeedableRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut root = rng.gen_range(0..p.p);
+        root = p.pow(root, lambda);
+        if is_primitive_root_of_unity(p, root, order) {
+            return Some(root);
+        }
+    }
+
+    None
+}
+
+/// Returns the smallest primitive `order`-th root of unity modulo p.
+///
+/// The primitive roots are the odd powers of any primitive root, so this
+/// requires `order / 2` multiplications. Returns None under the same
+/// conditions as [`primitive_root_of_unity`].
+pub fn minimal_primitive_root(p: &Modulus, order: usize) -> Option<u64> {
+    let root = primitive_root_of_unity(p, order)?;
+    let square = p.mul(root, root);
+
+    let mut minimal = root;
+    let mut current = root;
+    for _ in 1..order / 2 {
+        current = p.mul(current, square);
+        minimal = minimal.min(current);
+    }
+    Some(minimal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_primitive_root_of_unity, minimal_primitive_root, primitive_root_of_unity};
+    use crate::zq::Modulus;
+
+    #[test]
+    fn primitive_roots() {
+        for p in [1153u64, 4611686018326724609] {
+            let q = Modulus::new(p).unwrap();
+            for order in [2, 4, 16, 128] {
+                let root = primitive_root_of_unity(&q, order).unwrap();
+                assert!(is_primitive_root_of_unity(&q, root, order));
+
+                let minimal = minimal_primitive_root(&q, order).unwrap();
+                assert!(is_primitive_root_of_unity(&q, minimal, order));
+                assert!(minimal <= root);
+                if p == 1153 {
+                    assert!((1..minimal).all(|a| !is_primitive_root_of_unity(&q, a, order)));
+                }
+            }
+            assert!(primitive_root_of_unity(&q, 6).is_none());
+            assert!(!is_primitive_root_of_unity(&q, 1, 16));
+            assert!(!is_primitive_root_of_unity(&q, p - 1, 4));
+            assert!(is_primitive_root_of_unity(&q, p - 1, 2));
+        }
+
+        // 1153 - 1 = 2^7 * 9, so 256-th roots of unity do not exist.
+        let q = Modulus::new(1153).unwrap();
+        assert!(primitive_root_of_unity(&q, 256).is_none());
+        assert!(minimal_primitive_root(&q, 256).is_none());
+
+        // The modulus must be prime.
+        let q = Modulus::new(1153 * 1153).unwrap();
+        assert!(primitive_root_of_unity(&q, 4).is_none());
+    }
+}
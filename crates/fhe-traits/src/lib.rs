@@ -61,7 +61,11 @@ where
     ) -> Result<Self, Self::Error>;
 }
 
-/// Encode a value using a specified encoding.
+/// Encode a value using a specified encoding, in variable time.
+///
+/// This is significantly faster than [`FheEncoder`], and is meant for public
+/// values only, e.g., when preprocessing a large public database for private
+/// information retrieval.
 pub trait FheEncoderVariableTime<V>
 where
     Self: FhePlaintext,
@@ -72,7 +76,9 @@ where
     /// Attempt to encode a value using a specified encoding.
     /// # Safety
     /// This encoding runs in variable time and may leak information about the
-    /// value.
+    /// value. It is safe to use only when the value is public, and the
+    /// resulting plaintext may enable variable time computations on the
+    /// ciphertexts it is combined with.
     unsafe fn try_encode_vt(
         value: V,
         encoding: Self::Encoding,
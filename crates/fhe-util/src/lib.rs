@@ -12,6 +12,7 @@ use rand::RngCore;
 
 use num_bigint_dig::{prime::probably_prime, BigUint, ModInverse};
 use num_traits::{cast::ToPrimitive, PrimInt};
+use subtle::{ConditionallySelectable, ConstantTimeEq};
 extern crate alloc;
 use alloc::vec::Vec;
 
@@ -163,6 +164,159 @@ pub fn transcode_bidirectional(a: &[u64], input_nbits: usize, output_nbits: usiz
     out
 }
 
+/// The order in which the bits of packed numbers are laid out in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The least significant bits come first, as in [`transcode_to_bytes`].
+    Little,
+    /// The most significant bits come first: the numbers are written from
+    /// their most significant bit, and the bytes are filled from theirs.
+    Big,
+}
+
+/// Transcodes a vector of u64 of `nbits`-bit numbers into a vector of bytes,
+/// in the given endianness.
+pub fn transcode_to_bytes_with_endianness(
+    a: &[u64],
+    nbits: usize,
+    endianness: Endianness,
+) -> Vec<u8> {
+    match endianness {
+        Endianness::Little => transcode_to_bytes(a, nbits),
+        Endianness::Big => {
+            // Reversing the bits of the numbers and of the bytes turns the
+            // little-endian layout into the big-endian one.
+            let reversed = a
+                .iter()
+                .map(|ai| ai.reverse_bits() >> (64 - nbits))
+                .collect::<Vec<_>>();
+            let mut out = transcode_to_bytes(&reversed, nbits);
+            out.iter_mut().for_each(|b| *b = b.reverse_bits());
+            out
+        }
+    }
+}
+
+/// Transcodes a vector of bytes into a vector of u64 of `nbits`-bit numbers,
+/// in the given endianness.
+pub fn transcode_from_bytes_with_endianness(
+    b: &[u8],
+    nbits: usize,
+    endianness: Endianness,
+) -> Vec<u64> {
+    match endianness {
+        Endianness::Little => transcode_from_bytes(b, nbits),
+        Endianness::Big => {
+            let reversed = b.iter().map(|bi| bi.reverse_bits()).collect::<Vec<_>>();
+            let mut out = transcode_from_bytes(&reversed, nbits);
+            out.iter_mut()
+                .for_each(|o| *o = o.reverse_bits() >> (64 - nbits));
+            out
+        }
+    }
+}
+
+/// The version of the header written by [`transcode_to_bytes_with_header`].
+pub const TRANSCODE_HEADER_VERSION: u8 = 1;
+
+/// The size in bytes of the header written by
+/// [`transcode_to_bytes_with_header`].
+pub const TRANSCODE_HEADER_SIZE: usize = 15;
+
+/// Transcodes a vector of u64 of `nbits`-bit numbers into a vector of bytes,
+/// preceded by a header describing the packing, for storage or exchange
+/// across platforms.
+///
+/// The header consists of the version [`TRANSCODE_HEADER_VERSION`], the
+/// endianness (0 for little, 1 for big), the width `nbits`, the number of
+/// values as a little-endian u64, and the [`crc32`] of these fields followed
+/// by the packed values, as a little-endian u32.
+pub fn transcode_to_bytes_with_header(a: &[u64], nbits: usize, endianness: Endianness) -> Vec<u8> {
+    let payload = transcode_to_bytes_with_endianness(a, nbits, endianness);
+    let mut out = Vec::with_capacity(TRANSCODE_HEADER_SIZE + payload.len());
+    out.push(TRANSCODE_HEADER_VERSION);
+    out.push(match endianness {
+        Endianness::Little => 0,
+        Endianness::Big => 1,
+    });
+    out.push(nbits as u8);
+    out.extend_from_slice(&(a.len() as u64).to_le_bytes());
+    let checksum = !crc32_update(crc32_update(u32::MAX, &out), &payload);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Transcodes a vector of bytes written by [`transcode_to_bytes_with_header`]
+/// into the vector of numbers, and returns it with their width.
+///
+/// Returns an error if the header is malformed or of another version, if the
+/// length does not match the header, or if the checksum does not match.
+pub fn transcode_from_bytes_with_header(b: &[u8]) -> Result<(Vec<u64>, usize), &'static str> {
+    if b.len() < TRANSCODE_HEADER_SIZE {
+        return Err("The header is truncated");
+    }
+    if b[0] != TRANSCODE_HEADER_VERSION {
+        return Err("Unsupported header version");
+    }
+    let endianness = match b[1] {
+        0 => Endianness::Little,
+        1 => Endianness::Big,
+        _ => return Err("Invalid endianness"),
+    };
+    let nbits = b[2] as usize;
+    if !(1..=64).contains(&nbits) {
+        return Err("Invalid width");
+    }
+    let count = u64::from_le_bytes(b[3..11].try_into().unwrap());
+    let checksum = u32::from_le_bytes(b[11..15].try_into().unwrap());
+    let payload = &b[TRANSCODE_HEADER_SIZE..];
+    let expected_len = (count as u128 * nbits as u128).div_ceil(8);
+    if payload.len() as u128 != expected_len {
+        return Err("The length does not match the header");
+    }
+    if !crc32_update(crc32_update(u32::MAX, &b[..11]), payload) != checksum {
+        return Err("The checksum does not match");
+    }
+    let mut out = transcode_from_bytes_with_endianness(payload, nbits, endianness);
+    out.truncate(count as usize);
+    Ok((out, nbits))
+}
+
+/// The table of the CRC-32 of the bytes, for the reflected polynomial
+/// 0xEDB88320.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xEDB88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !crc32_update(u32::MAX, bytes)
+}
+
+/// Updates the non-inverted CRC-32 `crc` with bytes.
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, b| {
+        CRC32_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
 /// Computes the modular multiplicative inverse of `a` modulo `p`. Returns
 /// `None` if `a` is not invertible modulo `p`.
 pub fn inverse(a: u64, p: u64) -> Option<u64> {
@@ -171,6 +325,18 @@ pub fn inverse(a: u64, p: u64) -> Option<u64> {
     a.mod_inverse(p)?.to_u64()
 }
 
+/// Returns `table[secret_index]` in constant time, or 0 if the index is out of
+/// range.
+///
+/// The whole table is scanned and every entry is accessed, so that neither the
+/// memory access pattern nor the timing depend on the secret index.
+pub fn ct_select(table: &[u64], secret_index: usize) -> u64 {
+    let secret_index = secret_index as u64;
+    table.iter().enumerate().fold(0, |acc, (i, v)| {
+        u64::conditional_select(&acc, v, (i as u64).ct_eq(&secret_index))
+    })
+}
+
 /// Compute the sample variance of a list of values.
 /// Panics if the length of value is < 2.
 pub fn variance<T: PrimInt>(values: &[T]) -> f64 {
@@ -183,14 +349,17 @@ pub fn variance<T: PrimInt>(values: &[T]) -> f64 {
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
     use itertools::Itertools;
     use rand::{thread_rng, RngCore};
 
     use crate::variance;
 
     use super::{
-        inverse, is_prime, sample_vec_cbd, transcode_bidirectional, transcode_from_bytes,
-        transcode_to_bytes,
+        crc32, ct_select, inverse, is_prime, sample_vec_cbd, transcode_bidirectional,
+        transcode_from_bytes, transcode_from_bytes_with_endianness,
+        transcode_from_bytes_with_header, transcode_to_bytes, transcode_to_bytes_with_endianness,
+        transcode_to_bytes_with_header, Endianness, TRANSCODE_HEADER_SIZE,
     };
 
     #[test]
@@ -266,6 +435,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transcode_endianness() {
+        let mut rng = thread_rng();
+        assert_eq!(
+            transcode_to_bytes_with_endianness(&[0b101, 0b011, 0b111], 3, Endianness::Big),
+            [0b10101111, 0b10000000]
+        );
+        assert_eq!(
+            transcode_to_bytes_with_endianness(&[0b101, 0b011, 0b111], 3, Endianness::Little),
+            transcode_to_bytes(&[0b101, 0b011, 0b111], 3)
+        );
+        for nbits in 1..=64 {
+            let input = (0..37)
+                .map(|_| rng.next_u64() & (u64::MAX >> (64 - nbits)))
+                .collect_vec();
+            for endianness in [Endianness::Little, Endianness::Big] {
+                let bytes = transcode_to_bytes_with_endianness(&input, nbits, endianness);
+                let output = transcode_from_bytes_with_endianness(&bytes, nbits, endianness);
+                assert_eq!(output[..input.len()], input);
+
+                let bytes = transcode_to_bytes_with_header(&input, nbits, endianness);
+                assert_eq!(
+                    transcode_from_bytes_with_header(&bytes),
+                    Ok((input.clone(), nbits))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn transcode_header() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+
+        let bytes = transcode_to_bytes_with_header(&[1, 2, 3, 4, 5], 7, Endianness::Big);
+        assert_eq!(bytes.len(), TRANSCODE_HEADER_SIZE + 5);
+        for i in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 1;
+            assert!(transcode_from_bytes_with_header(&corrupted).is_err());
+        }
+        assert!(transcode_from_bytes_with_header(&bytes[..bytes.len() - 1]).is_err());
+        assert!(transcode_from_bytes_with_header(&bytes[..3]).is_err());
+        assert_eq!(
+            transcode_from_bytes_with_header(&transcode_to_bytes_with_header(
+                &[],
+                12,
+                Endianness::Little
+            )),
+            Ok((Vec::new(), 12))
+        );
+    }
+
+    #[test]
+    fn constant_time_select() {
+        let mut rng = thread_rng();
+        let table = (0..17).map(|_| rng.next_u64()).collect_vec();
+        for (i, v) in table.iter().enumerate() {
+            assert_eq!(ct_select(&table, i), *v);
+        }
+        assert_eq!(ct_select(&table, table.len()), 0);
+        assert_eq!(ct_select(&[], 0), 0);
+    }
+
     #[test]
     fn inv_kats() {
         // KATs for inversion generated in Sage using the following code.
@@ -0,0 +1,155 @@
+//! Compares homomorphic operations to the same operations on plaintext
+//! vectors, and outputs a JSON report of the slowdown factors, of the
+//! ciphertext expansion, and of the key sizes for each parameter set.
+//!
+//! Run with `cargo bench -p fhe --bench baseline`. The report is written to
+//! the standard output, and to the file `$BASELINE_REPORT` when this
+//! environment variable is set. The inputs and keys are derived from a fixed
+//! seed, so that only the timings vary between runs.
+
+use fhe::bfv::{
+    BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext, PublicKey,
+    RelinearizationKey, SecretKey,
+};
+use fhe_traits::{FheEncoder, FheEncrypter, Serialize};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Number of timed iterations of each operation.
+const ITERATIONS: usize = 21;
+
+/// Returns the median time of `f` in nanoseconds.
+fn median_ns<T, F: FnMut() -> T>(mut f: F) -> f64 {
+    black_box(f());
+    let mut times = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed().as_nanos() as f64
+        })
+        .collect::<Vec<_>>();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times[ITERATIONS / 2]
+}
+
+/// Times a homomorphic operation and its plaintext baseline.
+fn workload<T, U, F, G>(name: &str, fhe: F, plain: G) -> Value
+where
+    F: FnMut() -> T,
+    G: FnMut() -> U,
+{
+    let fhe_ns = median_ns(fhe);
+    let plain_ns = median_ns(plain).max(1.0);
+    json!({
+        "name": name,
+        "fhe_ns": fhe_ns,
+        "plain_ns": plain_ns,
+        "slowdown": fhe_ns / plain_ns,
+    })
+}
+
+fn report(par: &std::sync::Arc<BfvParameters>, rng: &mut ChaCha8Rng) -> Value {
+    let t = par.plaintext();
+    let n = par.degree();
+    let row_size = n >> 1;
+    let a = (0..n).map(|_| rng.gen_range(0..t)).collect::<Vec<_>>();
+    let b = (0..n).map(|_| rng.gen_range(0..t)).collect::<Vec<_>>();
+
+    let sk = SecretKey::random(par, rng);
+    let pk = PublicKey::new(&sk, rng);
+    let pt_a = Plaintext::try_encode(&a, Encoding::simd(), par).unwrap();
+    let pt_b = Plaintext::try_encode(&b, Encoding::simd(), par).unwrap();
+    let ct_a: Ciphertext = pk.try_encrypt(&pt_a, rng).unwrap();
+    let ct_b: Ciphertext = pk.try_encrypt(&pt_b, rng).unwrap();
+    let ct_seeded: Ciphertext = sk.try_encrypt(&pt_a, rng).unwrap();
+
+    let add_mod = |x: &[u64], y: &[u64]| {
+        x.iter()
+            .zip(y)
+            .map(|(xi, yi)| (xi + yi) % t)
+            .collect::<Vec<_>>()
+    };
+    let mul_mod = |x: &[u64], y: &[u64]| {
+        x.iter()
+            .zip(y)
+            .map(|(xi, yi)| ((*xi as u128 * *yi as u128) % t as u128) as u64)
+            .collect::<Vec<_>>()
+    };
+
+    let mut workloads = vec![
+        workload("add", || &ct_a + &ct_b, || add_mod(&a, &b)),
+        workload("mul_plain", || &ct_a * &pt_b, || mul_mod(&a, &b)),
+    ];
+
+    // Ciphertext multiplications and rotations need more than one modulus.
+    let (mut rk_bytes, mut ek_bytes) = (Value::Null, Value::Null);
+    if par.moduli().len() > 1 {
+        let rk = RelinearizationKey::new(&sk, rng).unwrap();
+        let ek = EvaluationKeyBuilder::new(&sk)
+            .unwrap()
+            .enable_inner_sum()
+            .unwrap()
+            .build(rng)
+            .unwrap();
+        rk_bytes = json!(rk.to_bytes().len());
+        ek_bytes = json!(ek.to_bytes().len());
+
+        workloads.push(workload(
+            "mul",
+            || {
+                let mut ct = &ct_a * &ct_b;
+                rk.relinearizes(&mut ct).unwrap();
+                ct
+            },
+            || mul_mod(&a, &b),
+        ));
+        workloads.push(workload(
+            "rotate_columns",
+            || ek.rotates_columns_by(&ct_a, 1).unwrap(),
+            || {
+                let mut rotated = a.clone();
+                rotated[..row_size].rotate_left(1);
+                rotated[row_size..].rotate_left(1);
+                rotated
+            },
+        ));
+        workloads.push(workload(
+            "inner_sum",
+            || ek.computes_inner_sum(&ct_a).unwrap(),
+            || a.iter().fold(0, |sum, ai| (sum + ai) % t),
+        ));
+    }
+
+    let plaintext_bytes = (n * (64 - (t - 1).leading_zeros() as usize)).div_ceil(8);
+    let ciphertext_bytes = ct_a.to_bytes_expanded().len();
+    json!({
+        "degree": n,
+        "log_q": par.moduli_sizes().iter().sum::<usize>(),
+        "plaintext_modulus": t,
+        "plaintext_bytes": plaintext_bytes,
+        "ciphertext_bytes": ciphertext_bytes,
+        "ciphertext_seeded_bytes": ct_seeded.to_bytes().len(),
+        "ciphertext_expansion": ciphertext_bytes as f64 / plaintext_bytes as f64,
+        "public_key_bytes": pk.to_bytes().len(),
+        "relinearization_key_bytes": rk_bytes,
+        "evaluation_key_bytes": ek_bytes,
+        "workloads": workloads,
+    })
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let reports = BfvParameters::default_parameters_128(20)
+        .iter()
+        .map(|par| report(par, &mut rng))
+        .collect::<Vec<_>>();
+    let report = serde_json::to_string_pretty(&json!({ "parameters": reports })).unwrap();
+
+    println!("{report}");
+    if let Ok(path) = std::env::var("BASELINE_REPORT") {
+        std::fs::write(&path, &report).unwrap();
+    }
+}
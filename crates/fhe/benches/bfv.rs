@@ -5,10 +5,10 @@ use fhe::bfv::{
 };
 use fhe_math::rns::{RnsContext, ScalingFactor};
 use fhe_math::zq::primes::generate_prime;
-use fhe_traits::{FheEncoder, FheEncrypter};
+use fhe_traits::{FheEncoder, FheEncoderVariableTime, FheEncrypter};
 use itertools::Itertools;
 use num_bigint::BigUint;
-use rand::{rngs::OsRng, thread_rng};
+use rand::{rngs::OsRng, thread_rng, Rng};
 use std::time::Duration;
 
 pub fn bfv_benchmark(c: &mut Criterion) {
@@ -85,6 +85,24 @@ pub fn bfv_benchmark(c: &mut Criterion) {
             },
         );
 
+        let rows = (0..16)
+            .map(|_| {
+                (0..par.degree())
+                    .map(|_| rng.gen_range(0..par.plaintext()))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let rows = rows.iter().map(|row| row.as_slice()).collect_vec();
+        group.bench_function(
+            BenchmarkId::new(
+                "encode_simd_rows_16",
+                format!("n={}/log(q)={}", par.degree(), q),
+            ),
+            |b| {
+                b.iter(|| Plaintext::try_encode_rows(&rows, Encoding::simd(), &par));
+            },
+        );
+
         group.bench_function(
             BenchmarkId::new("encrypt_sk", format!("n={}/log(q)={}", par.degree(), q)),
             |b| {
@@ -157,6 +175,36 @@ pub fn bfv_benchmark(c: &mut Criterion) {
             },
         );
 
+        // Masks are public, and encoded in variable time so that their structure
+        // is detected.
+        let masks = [
+            (
+                "mul_pt_mask_dense",
+                (0..par.degree() as u64).map(|i| i & 1).collect_vec(),
+            ),
+            ("mul_pt_mask_constant", vec![3u64; par.degree()]),
+            ("mul_pt_mask_zero", vec![0u64; par.degree()]),
+        ];
+        for (name, mask) in &masks {
+            let pt = unsafe { Plaintext::try_encode_vt(mask, Encoding::simd(), &par).unwrap() };
+            group.bench_function(
+                BenchmarkId::new(*name, format!("n={}/log(q)={}", par.degree(), q)),
+                |b| {
+                    b.iter(|| c1 = &c2 * &pt);
+                },
+            );
+        }
+
+        group.bench_function(
+            BenchmarkId::new(
+                "encode_vt_mask_constant",
+                format!("n={}/log(q)={}", par.degree(), q),
+            ),
+            |b| {
+                b.iter(|| unsafe { Plaintext::try_encode_vt(&masks[1].1, Encoding::simd(), &par) });
+            },
+        );
+
         let mut c3 = &c1 * &c1;
         let c3_clone = c3.clone();
         if let Some(rk) = rk.as_ref() {
@@ -193,6 +241,16 @@ pub fn bfv_benchmark(c: &mut Criterion) {
                 },
             );
 
+            group.bench_function(
+                BenchmarkId::new(
+                    "inner_sum_trace",
+                    format!("n={}/log(q)={}", par.degree(), q),
+                ),
+                |b| {
+                    b.iter(|| c1 = ek.computes_inner_sum_by_trace(&c1).unwrap());
+                },
+            );
+
             for i in 1..=par.degree().ilog2() {
                 if par.degree() > 2048 && i > 4 {
                     continue; // Skip slow benchmarks
@@ -1,8 +1,11 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use fhe::bfv::{dot_product_scalar, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+use fhe::bfv::{
+    dot_product_scalar, BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext,
+    SecretKey,
+};
 use fhe_traits::{FheEncoder, FheEncrypter};
 use itertools::{izip, Itertools};
-use rand::{rngs::OsRng, thread_rng};
+use rand::{rngs::OsRng, thread_rng, Rng};
 use std::time::Duration;
 
 pub fn bfv_benchmark(c: &mut Criterion) {
@@ -65,6 +68,73 @@ pub fn bfv_benchmark(c: &mut Criterion) {
         }
     }
 
+    // Product of a matrix with 128 non-zero diagonals by a vector, with 16 baby
+    // steps and 8 giant steps.
+    let (baby_steps, giant_steps) = (16, 8);
+    for par in &BfvParameters::default_parameters_128(20)[2..4] {
+        let sk = SecretKey::random(par, &mut OsRng);
+        let mut builder = EvaluationKeyBuilder::new(&sk).unwrap();
+        for j in 1..baby_steps {
+            builder.enable_column_rotation(j).unwrap();
+        }
+        for k in 1..giant_steps {
+            builder.enable_column_rotation(k * baby_steps).unwrap();
+        }
+        let ek = builder.build(&mut rng).unwrap();
+
+        let v = (0..par.degree())
+            .map(|_| rng.gen_range(0..par.plaintext()))
+            .collect_vec();
+        let ct: Ciphertext = sk
+            .try_encrypt(
+                &Plaintext::try_encode(&v, Encoding::simd(), par).unwrap(),
+                &mut rng,
+            )
+            .unwrap();
+        let diagonals = (0..baby_steps * giant_steps)
+            .map(|_| {
+                let d = (0..par.degree())
+                    .map(|_| rng.gen_range(0..par.plaintext()))
+                    .collect_vec();
+                Plaintext::try_encode(&d, Encoding::simd(), par).unwrap()
+            })
+            .collect_vec();
+
+        let matvec = |fused: bool| {
+            let mut out = Ciphertext::zero(par);
+            for k in 0..giant_steps {
+                let mut inner = &ct * &diagonals[k * baby_steps];
+                for j in 1..baby_steps {
+                    let pt = &diagonals[k * baby_steps + j];
+                    if fused {
+                        ek.rotate_mul_acc(&ct, j, pt, &mut inner).unwrap();
+                    } else {
+                        inner += &(&ek.rotates_columns_by(&ct, j).unwrap() * pt);
+                    }
+                }
+                if k > 0 {
+                    inner = ek.rotates_columns_by(&inner, k * baby_steps).unwrap();
+                }
+                out += &inner;
+            }
+            out
+        };
+
+        for (name, fused) in [("matvec_128/naive", false), ("matvec_128/fused", true)] {
+            group.bench_function(
+                BenchmarkId::new(
+                    name,
+                    format!(
+                        "degree={}/logq={}",
+                        par.degree(),
+                        par.moduli_sizes().iter().sum::<usize>()
+                    ),
+                ),
+                |b| b.iter(|| matvec(fused)),
+            );
+        }
+    }
+
     group.finish();
 }
 
@@ -0,0 +1,104 @@
+//! Multithreaded benchmarks with keys shared between worker threads.
+//!
+//! Each worker thread performs the same number of multiplications and
+//! rotations with the same evaluation and relinearization keys, shared behind
+//! an `Arc`. Without contention, the time per iteration is independent of the
+//! number of threads, as long as it does not exceed the number of cores; a
+//! time growing with the number of threads reveals a serialization point.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fhe::bfv::{
+    BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Multiplicator, Plaintext,
+    RelinearizationKey, SecretKey,
+};
+use fhe_traits::{FheEncoder, FheEncrypter};
+use itertools::Itertools;
+use rand::{rngs::OsRng, thread_rng};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The number of operations of each worker thread per iteration.
+const OPERATIONS: usize = 4;
+
+pub fn bfv_threads_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bfv_threads");
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_secs(1));
+    group.measurement_time(Duration::from_secs(3));
+
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let threads = [1, 2, 4, 8, 16]
+        .into_iter()
+        .filter(|n| *n <= cores)
+        .collect_vec();
+
+    for par in &BfvParameters::default_parameters_128(20)[2..] {
+        let mut rng = thread_rng();
+        let sk = SecretKey::random(par, &mut OsRng);
+        let ek = Arc::new(
+            EvaluationKeyBuilder::new(&sk)
+                .unwrap()
+                .enable_column_rotation(1)
+                .unwrap()
+                .build(&mut rng)
+                .unwrap(),
+        );
+        let rk = Arc::new(RelinearizationKey::new(&sk, &mut rng).unwrap());
+        let multiplicator = Multiplicator::default_shared(&rk).unwrap();
+        let pt = Plaintext::try_encode(&(1..16u64).collect_vec(), Encoding::simd(), par).unwrap();
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng).unwrap();
+        let q = par.moduli_sizes().iter().sum::<usize>();
+
+        for n in &threads {
+            group.bench_function(
+                BenchmarkId::new(
+                    "mul_relin",
+                    format!("threads={}/n={}/log(q)={}", n, par.degree(), q),
+                ),
+                |b| {
+                    b.iter(|| {
+                        thread::scope(|scope| {
+                            for _ in 0..*n {
+                                let multiplicator = multiplicator.clone();
+                                let ct = &ct;
+                                scope.spawn(move || {
+                                    for _ in 0..OPERATIONS {
+                                        multiplicator.multiply(ct, ct).unwrap();
+                                    }
+                                });
+                            }
+                        })
+                    });
+                },
+            );
+
+            group.bench_function(
+                BenchmarkId::new(
+                    "rotate_columns",
+                    format!("threads={}/n={}/log(q)={}", n, par.degree(), q),
+                ),
+                |b| {
+                    b.iter(|| {
+                        thread::scope(|scope| {
+                            for _ in 0..*n {
+                                let ek = ek.clone();
+                                let ct = &ct;
+                                scope.spawn(move || {
+                                    for _ in 0..OPERATIONS {
+                                        ek.rotates_columns_by(ct, 1).unwrap();
+                                    }
+                                });
+                            }
+                        })
+                    });
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(bfv_threads, bfv_threads_benchmark);
+criterion_main!(bfv_threads);
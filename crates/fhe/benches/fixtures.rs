@@ -0,0 +1,33 @@
+//! Times the standard workloads on the canonical parameter sets, with keys
+//! and inputs derived from a fixed seed, so that the results can be compared
+//! across versions of this crate and with other libraries.
+//!
+//! Run with `cargo bench -p fhe --features bench-fixtures --bench fixtures`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fhe::bfv::fixtures::{Fixture, ParameterSet, Workload};
+use std::time::Duration;
+
+pub fn fixtures_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fixtures");
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_millis(600));
+    group.measurement_time(Duration::from_millis(1000));
+
+    for set in ParameterSet::ALL {
+        let fixture = Fixture::new(set).unwrap();
+        fixture.check().unwrap();
+        let mut rng = fixture.rng();
+        for workload in Workload::ALL {
+            group.bench_function(
+                BenchmarkId::new(workload.name(), format!("n={}", set.degree())),
+                |b| b.iter(|| fixture.run(workload, &mut rng).unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(fixtures, fixtures_benchmark);
+criterion_main!(fixtures);
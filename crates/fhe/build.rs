@@ -2,6 +2,6 @@ use core::result::Result;
 
 fn main() -> Result<(), &'static str> {
     // Generate the proto files.
-    // prost_build::compile_protos(&["src/proto/bfv.proto"], &["src/proto"])?;
+    // prost_build::compile_protos(&["src/proto/bfv.proto", "src/proto/bgv.proto", "src/proto/ckks.proto"], &["src/proto"])?;
     Ok(())
 }
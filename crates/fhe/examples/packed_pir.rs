@@ -0,0 +1,105 @@
+// Private information retrieval with the PIR toolkit of the `fhe` crate, in the
+// style of SealPIR <https://eprint.iacr.org/2017/1142>: the server packs a
+// database of byte strings in plaintexts arranged in two dimensions, the client
+// sends a single ciphertext selecting one row and one column, and the server
+// obliviously expands it and folds the database into an encryption of the
+// plaintext containing the element.
+
+use std::sync::Arc;
+
+use fhe::{
+    bfv::{
+        answer_query, elements_per_plaintext, pack_database, query_plaintext, unpack_element,
+        BfvParameters, BfvParametersBuilder, Encoding, EvaluationKeyBuilder, Multiplicator,
+        Plaintext, RelinearizationKey, SecretKey,
+    },
+    Error,
+};
+use fhe_traits::{FheDecrypter, FheEncrypter};
+use rand::{thread_rng, RngCore};
+
+/// Returns a database of `size` random elements of `element_size` bytes.
+fn random_database(size: usize, element_size: usize) -> Vec<Vec<u8>> {
+    let mut rng = thread_rng();
+    (0..size)
+        .map(|_| {
+            let mut element = vec![0u8; element_size];
+            rng.fill_bytes(&mut element);
+            element
+        })
+        .collect()
+}
+
+/// Retrieves `database[index]` privately.
+fn run(params: &Arc<BfvParameters>, database: &[Vec<u8>], index: usize) -> Result<Vec<u8>, Error> {
+    let mut rng = thread_rng();
+    let element_size = database[0].len();
+
+    // The server packs the database, and completes it with zero plaintexts to a
+    // `dim1 × dim2` rectangle.
+    let mut packed = pack_database(database, params)?;
+    let dim1 = (packed.len() as f64).sqrt().ceil() as usize;
+    let dim2 = packed.len().div_ceil(dim1);
+    packed.resize(dim1 * dim2, Plaintext::zero(Encoding::poly(), params)?);
+    let dimensions = [dim1, dim2];
+
+    // The client generates the keys for the expansion of the query to
+    // `dim1 + dim2` selectors, and for the folding of the second dimension.
+    let sk = SecretKey::random(params, &mut rng);
+    let level = (dim1 + dim2).next_power_of_two().ilog2() as usize;
+    let ek = EvaluationKeyBuilder::new(&sk)?
+        .enable_expansion(level)?
+        .build(&mut rng)?;
+    let rk = RelinearizationKey::new(&sk, &mut rng)?;
+
+    // The client encrypts the query for the plaintext containing the element.
+    let per_plaintext = elements_per_plaintext(params, element_size);
+    let pt = query_plaintext(index / per_plaintext, &dimensions, params)?;
+    let query = sk.try_encrypt(&pt, &mut rng)?;
+
+    // The server computes the answer.
+    let multiplicator = Multiplicator::default(&rk)?;
+    let answer = answer_query(&packed, &dimensions, &query, &ek, Some(&multiplicator))?;
+
+    // The client decrypts the answer and extracts the element.
+    let pt = sk.try_decrypt(&answer)?;
+    unpack_element(&pt, index, element_size)
+}
+
+fn main() -> Result<(), Error> {
+    // We use the parameters of MulPIR, reported in Table 1 of
+    // <https://eprint.iacr.org/2019/1483.pdf>.
+    let params = BfvParametersBuilder::new()
+        .set_degree(8192)
+        .set_plaintext_modulus((1 << 20) + (1 << 19) + (1 << 17) + (1 << 16) + (1 << 14) + 1)
+        .set_moduli_sizes(&[50, 55, 55])
+        .build_arc()?;
+    let database = random_database(1 << 14, 288);
+    let index = thread_rng().next_u32() as usize % database.len();
+
+    let element = run(&params, &database, index)?;
+    println!("database[{index}] starts with {:02x?}", &element[..8]);
+    assert_eq!(element, database[index]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_database, run};
+    use fhe::{bfv::BfvParametersBuilder, Error};
+
+    #[test]
+    fn packed_pir() -> Result<(), Error> {
+        let params = BfvParametersBuilder::new()
+            .set_degree(8192)
+            .set_plaintext_modulus((1 << 20) + (1 << 19) + (1 << 17) + (1 << 16) + (1 << 14) + 1)
+            .set_moduli_sizes(&[50, 55, 55])
+            .build_arc()?;
+        // Two elements of 8 KiB fit in a plaintext, which gives 3 × 2 plaintexts.
+        let database = random_database(9, 8192);
+        for index in [0, 5, database.len() - 1] {
+            assert_eq!(run(&params, &database, index)?, database[index]);
+        }
+        Ok(())
+    }
+}
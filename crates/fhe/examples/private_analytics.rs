@@ -0,0 +1,97 @@
+// Private analytics with the `fhe` crate: clients encrypt a numerical value and
+// a category, and the analyst computes the sum of the values and a histogram of
+// the categories over the encrypted data, only decrypting the aggregates.
+
+use std::sync::Arc;
+
+use fhe::{
+    bfv::{
+        BfvParameters, Ciphertext, CollisionPolicy, CountingEncoding, Encoding,
+        EvaluationKeyBuilder, Plaintext, PublicKey, SecretKey,
+    },
+    Error,
+};
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+/// The categories reported by the clients.
+const CATEGORIES: [&str; 4] = ["android", "ios", "linux", "windows"];
+
+/// Aggregates the `(value, category)` reports of the clients, and returns the
+/// sum of the values and the number of reports per category.
+fn run(params: &Arc<BfvParameters>, reports: &[(u64, &str)]) -> Result<(u64, Vec<u64>), Error> {
+    let mut rng = thread_rng();
+    let sk = SecretKey::random(params, &mut rng);
+    let pk = PublicKey::new(&sk, &mut rng);
+    let ek = EvaluationKeyBuilder::new(&sk)?
+        .enable_inner_sum()?
+        .build(&mut rng)?;
+    let counting = CountingEncoding::new(params, CollisionPolicy::CountMin(2))?;
+
+    // Each client encrypts its value in the first slot, and its category with
+    // the counting encoding.
+    let mut values = Vec::with_capacity(reports.len());
+    let mut categories = Vec::with_capacity(reports.len());
+    for (value, category) in reports {
+        let pt = Plaintext::try_encode(&[*value], Encoding::simd(), params)?;
+        values.push(pk.try_encrypt(&pt, &mut rng)?);
+        categories.push(pk.try_encrypt(&counting.encode([category])?, &mut rng)?);
+    }
+
+    // The values are added in a single ciphertext, whose slots are then summed,
+    // so that the analyst could for example add a mask before decryption.
+    let mut sum = Ciphertext::zero(params);
+    for ct in &values {
+        sum += ct;
+    }
+    let sum = ek.computes_inner_sum(&sum)?;
+
+    // The encoded categories are added to form a histogram.
+    let mut histogram = Ciphertext::zero(params);
+    for ct in &categories {
+        histogram += ct;
+    }
+
+    let sum = Vec::<u64>::try_decode(&sk.try_decrypt(&sum)?, Encoding::simd())?[0];
+    let histogram = counting.decode(&sk.try_decrypt(&histogram)?, &CATEGORIES)?;
+    Ok((sum, histogram))
+}
+
+fn main() -> Result<(), Error> {
+    let params = BfvParameters::default_parameters_128(20)[2].clone();
+    let mut rng = thread_rng();
+    let reports = (0..100)
+        .map(|_| {
+            (
+                rng.gen_range(0..1000),
+                *CATEGORIES.choose(&mut rng).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (sum, histogram) = run(&params, &reports)?;
+    println!("Average value: {}", sum as f64 / reports.len() as f64);
+    for (category, count) in CATEGORIES.iter().zip(histogram) {
+        println!("{category}: at most {count} reports");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use fhe::{bfv::BfvParameters, Error};
+
+    #[test]
+    fn private_analytics() -> Result<(), Error> {
+        let params = BfvParameters::default_parameters_128(20)[2].clone();
+        let reports = [(10, "linux"), (20, "ios"), (30, "linux"), (40, "android")];
+        let (sum, histogram) = run(&params, &reports)?;
+        assert_eq!(sum, 100);
+        // Count-min sketches never underestimate the counts.
+        for (count, expected) in histogram.iter().zip([1, 1, 2, 0]) {
+            assert!(*count >= expected);
+        }
+        Ok(())
+    }
+}
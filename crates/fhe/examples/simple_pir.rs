@@ -0,0 +1,77 @@
+// Private information retrieval with the `fhe` crate: the database is packed in
+// SIMD plaintexts, the client sends an encrypted selection vector, and the
+// server answers with the dot product of the query and the database, without
+// learning which element was retrieved.
+
+use std::sync::Arc;
+
+use fhe::{
+    bfv::{dot_product_scalar, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey},
+    Error,
+};
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+use rand::{thread_rng, Rng};
+
+/// Returns a database of `size` random elements modulo the plaintext modulus.
+fn random_database(params: &BfvParameters, size: usize) -> Vec<u64> {
+    let mut rng = thread_rng();
+    (0..size)
+        .map(|_| rng.gen_range(0..params.plaintext()))
+        .collect()
+}
+
+/// Retrieves `database[index]` privately.
+fn run(params: &Arc<BfvParameters>, database: &[u64], index: usize) -> Result<u64, Error> {
+    let mut rng = thread_rng();
+    let slots = params.degree();
+
+    // The server packs the database in plaintexts of `degree` elements.
+    let packed = database
+        .chunks(slots)
+        .map(|chunk| Plaintext::try_encode(chunk, Encoding::simd(), params))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // The client encrypts a selection vector over the plaintexts.
+    let sk = SecretKey::random(params, &mut rng);
+    let query = (0..packed.len())
+        .map(|i| {
+            let selector = vec![(i == index / slots) as u64; slots];
+            let pt = Plaintext::try_encode(&selector, Encoding::simd(), params)?;
+            sk.try_encrypt(&pt, &mut rng)
+        })
+        .collect::<Result<Vec<Ciphertext>, Error>>()?;
+
+    // The server computes the answer.
+    let answer = dot_product_scalar(query.iter(), packed.iter())?;
+
+    // The client decrypts the answer and reads the slot of the element.
+    let pt = sk.try_decrypt(&answer)?;
+    Ok(Vec::<u64>::try_decode(&pt, Encoding::simd())?[index % slots])
+}
+
+fn main() -> Result<(), Error> {
+    let params = BfvParameters::default_parameters_128(20)[2].clone();
+    let database = random_database(&params, 4 * params.degree());
+    let index = thread_rng().gen_range(0..database.len());
+
+    let element = run(&params, &database, index)?;
+    println!("database[{index}] = {element}");
+    assert_eq!(element, database[index]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_database, run};
+    use fhe::{bfv::BfvParameters, Error};
+
+    #[test]
+    fn simple_pir() -> Result<(), Error> {
+        let params = BfvParameters::default_parameters_128(20)[2].clone();
+        let database = random_database(&params, 3 * params.degree() - 5);
+        for index in [0, params.degree() + 7, database.len() - 1] {
+            assert_eq!(run(&params, &database, index)?, database[index]);
+        }
+        Ok(())
+    }
+}
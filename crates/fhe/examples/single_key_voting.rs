@@ -0,0 +1,77 @@
+// Single-key voting with the `fhe` crate: each voter encrypts a one-hot ballot
+// under the election authority public key, anyone can add the ballots, and the
+// authority decrypts the tally of every candidate at once.
+
+use std::sync::Arc;
+
+use fhe::{
+    bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, PublicKey, SecretKey},
+    Error,
+};
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+use rand::{thread_rng, Rng};
+
+/// Runs an election between `num_candidates` candidates, where each of the
+/// `votes` is the index of a candidate, and returns the decrypted tally.
+fn run(
+    params: &Arc<BfvParameters>,
+    num_candidates: usize,
+    votes: &[usize],
+) -> Result<Vec<u64>, Error> {
+    let mut rng = thread_rng();
+
+    // The election authority generates the keys and publishes the public key.
+    let sk = SecretKey::random(params, &mut rng);
+    let pk = PublicKey::new(&sk, &mut rng);
+
+    // Each voter encrypts a ballot with a 1 in the slot of their candidate.
+    let ballots = votes
+        .iter()
+        .map(|vote| {
+            let mut ballot = vec![0u64; num_candidates];
+            ballot[*vote] = 1;
+            let pt = Plaintext::try_encode(&ballot, Encoding::simd(), params)?;
+            pk.try_encrypt(&pt, &mut rng)
+        })
+        .collect::<Result<Vec<Ciphertext>, Error>>()?;
+
+    // The ballots are added homomorphically.
+    let mut tally = Ciphertext::zero(params);
+    for ballot in &ballots {
+        tally += ballot;
+    }
+
+    // The authority decrypts the tally.
+    let pt = sk.try_decrypt(&tally)?;
+    let mut tally = Vec::<u64>::try_decode(&pt, Encoding::simd())?;
+    tally.truncate(num_candidates);
+    Ok(tally)
+}
+
+fn main() -> Result<(), Error> {
+    let params = BfvParameters::default_parameters_128(20)[2].clone();
+    let num_candidates = 5;
+    let votes = (0..1000)
+        .map(|_| thread_rng().gen_range(0..num_candidates))
+        .collect::<Vec<_>>();
+
+    let tally = run(&params, num_candidates, &votes)?;
+    for (candidate, count) in tally.iter().enumerate() {
+        println!("Candidate {candidate}: {count} votes");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use fhe::{bfv::BfvParameters, Error};
+
+    #[test]
+    fn single_key_voting() -> Result<(), Error> {
+        let params = BfvParameters::default_parameters_128(20)[2].clone();
+        let votes = [0, 2, 2, 1, 2, 0, 3];
+        assert_eq!(run(&params, 4, &votes)?, [2, 1, 3, 1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+//! Cooperative cancellation of long-running operations.
+
+use crate::{Error, Result};
+use core::sync::atomic::{AtomicBool, Ordering};
+extern crate alloc;
+use alloc::sync::Arc;
+
+/// A token to cancel long-running operations, such as the oblivious expansion
+/// with [`EvaluationKey::expands_with_cancellation`](crate::bfv::EvaluationKey::expands_with_cancellation)
+/// or [`dot_product_scalar_with_cancellation`](crate::bfv::dot_product_scalar_with_cancellation).
+///
+/// The clones of a token share its state, so that a token can be cancelled
+/// from another thread than the one running the operation. The operations
+/// check the token between coarse-grained steps, e.g. between key switchings,
+/// and return [`Error::Cancelled`] once it is cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the operations checking this token or one of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    /// Reports whether this token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns [`Error::Cancelled`] if the token is cancelled.
+    pub(crate) fn check(token: Option<&Self>) -> Result<()> {
+        if token.is_some_and(|token| token.is_cancelled()) {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CancellationToken;
+    use crate::bfv::{
+        dot_product_scalar, dot_product_scalar_with_cancellation, BfvParameters, Ciphertext,
+        Encoding, EvaluationKeyBuilder, Plaintext, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+
+    #[test]
+    fn cancellation() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_expansion(3)?
+            .build(&mut rng)?;
+        let pt = Plaintext::try_encode(&[1u64], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let cts = (0..4).map(|_| ct.clone()).collect::<Vec<_>>();
+        let pts = (0..4).map(|_| pt.clone()).collect::<Vec<_>>();
+
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+        assert_eq!(
+            ek.expands_with_cancellation(&ct, 8, &token)?,
+            ek.expands(&ct, 8)?
+        );
+        assert_eq!(
+            dot_product_scalar_with_cancellation(cts.iter(), pts.iter(), &token)?,
+            dot_product_scalar(cts.iter(), pts.iter())?
+        );
+
+        // Cancelling a clone cancels the token.
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(
+            ek.expands_with_cancellation(&ct, 8, &token),
+            Err(Error::Cancelled)
+        );
+        assert_eq!(
+            dot_product_scalar_with_cancellation(cts.iter(), pts.iter(), &token),
+            Err(Error::Cancelled)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cancellation_between_chunks() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        // With 62-bit moduli, the products are accumulated by chunks of 16.
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let cts = (0..40).map(|_| ct.clone()).collect::<Vec<_>>();
+        let pts = (0..40).map(|_| pt.clone()).collect::<Vec<_>>();
+
+        let token = CancellationToken::new();
+        assert_eq!(
+            dot_product_scalar_with_cancellation(cts.iter(), pts.iter(), &token)?,
+            dot_product_scalar(cts.iter(), pts.iter())?
+        );
+
+        // Cancel the token when the first plaintext of the second chunk is
+        // used in a product; the plaintexts are iterated twice beforehand to
+        // validate the inputs.
+        let seen = Cell::new(0);
+        let cancelling = pts.iter().enumerate().map(|(i, pti)| {
+            if i == 16 {
+                seen.set(seen.get() + 1);
+                if seen.get() == 3 {
+                    token.cancel();
+                }
+            }
+            pti
+        });
+        assert_eq!(
+            dot_product_scalar_with_cancellation(cts.iter(), cancelling, &token),
+            Err(Error::Cancelled)
+        );
+        assert!(token.is_cancelled());
+        Ok(())
+    }
+}
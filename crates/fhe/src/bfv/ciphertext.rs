@@ -2,23 +2,86 @@
 
 extern crate alloc;
 
-use crate::bfv::{parameters::BfvParameters, traits::TryConvertFrom};
+use crate::bfv::{
+    parameters::BfvParameters, traits::TryConvertFrom, DeserializationConfig, Encoding, Plaintext,
+    PublicKey,
+};
 use crate::proto::bfv::Ciphertext as CiphertextProto;
 use crate::{Error, Result};
+use alloc::format;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
 use core::ops::{Deref, DerefMut};
-use fhe_math::rq::{Poly, Representation};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use fhe_math::rq::{traits::TryConvertFrom as TryConvertFromPoly, Context, Poly, Representation};
+use fhe_math::stream::{read_u32, write_u32, Reader, Writer};
 use fhe_traits::{
-    DeserializeParametrized, DeserializeWithContext, FheCiphertext, FheParametrized, Serialize,
+    DeserializeParametrized, DeserializeWithContext, FheCiphertext, FheEncrypter, FheParametrized,
+    Serialize,
 };
+use itertools::izip;
 use prost::Message;
-use rand::SeedableRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use subtle::{Choice, ConstantTimeEq};
+
+/// Whether a ciphertext is serialized with its seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionState {
+    /// The ciphertext is a fresh encryption with a secret key, and
+    /// [`Serialize::to_bytes`] replaces its last polynomial by its seed.
+    Seeded,
+    /// All the polynomials of the ciphertext are serialized.
+    Expanded,
+}
+
+/// The operations through which a ciphertext loses its seed, as reported to
+/// the hook set with [`set_seed_loss_hook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedLoss {
+    /// Addition or subtraction of a ciphertext or a plaintext.
+    Addition,
+    /// Multiplication by a plaintext.
+    Multiplication,
+    /// Negation.
+    Negation,
+    /// Modulus switching to a lower level.
+    ModSwitch,
+    /// Conversion to the canonical form with [`Ciphertext::canonicalize`].
+    Canonicalization,
+    /// Accumulation of key-switched ciphertexts.
+    KeySwitching,
+}
+
+/// The hook called when a ciphertext loses its seed, as a function pointer, or
+/// null.
+static SEED_LOSS_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets the hook called with the operation every time a ciphertext loses its
+/// seed, or removes it with `None`.
+///
+/// The serialization of a ciphertext which lost its seed is about twice as
+/// large as that of a fresh one, so the hook allows to find which operation of
+/// a deployed protocol expands ciphertexts which were expected to be sent
+/// compressed. The hook is global, and is called from the thread performing
+/// the operation; by default, no hook is set.
+pub fn set_seed_loss_hook(hook: Option<fn(SeedLoss)>) {
+    SEED_LOSS_HOOK.store(
+        hook.map_or(ptr::null_mut(), |hook| hook as *mut ()),
+        Ordering::Release,
+    );
+}
 
 /// A ciphertext encrypting a plaintext.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Two ciphertexts are equal, and have the same hash, when they have the same
+/// canonical form (see [`Ciphertext::canonicalize`]), e.g. a fresh ciphertext
+/// compressed with its seed and the same ciphertext deserialized from its
+/// expanded form.
+#[derive(Debug, Clone, Eq)]
 pub struct Ciphertext {
     /// The parameters of the underlying BFV encryption scheme.
     pub(crate) par: Arc<BfvParameters>,
@@ -48,11 +111,75 @@ impl DerefMut for Ciphertext {
 }
 
 impl Ciphertext {
+    /// Returns the level of this ciphertext.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns whether the ciphertext is serialized with its seed.
+    pub fn compression_state(&self) -> CompressionState {
+        if self.seed.is_some() {
+            CompressionState::Seeded
+        } else {
+            CompressionState::Expanded
+        }
+    }
+
+    /// Drops the seed of the ciphertext, and reports the loss to the hook set
+    /// with [`set_seed_loss_hook`] if the ciphertext had a seed.
+    pub(crate) fn drop_seed(&mut self, operation: SeedLoss) {
+        if self.seed.take().is_some() {
+            report_seed_loss(operation)
+        }
+    }
+
+    /// Generate a fresh encryption under `pk` of a random plaintext, encoded
+    /// in SIMD slots if the parameters support it and as polynomial
+    /// coefficients otherwise, e.g. for tests.
+    pub fn random_fresh<R: RngCore + CryptoRng>(pk: &PublicKey, rng: &mut R) -> Result<Self> {
+        let encoding = if pk.par.op.is_some() {
+            Encoding::simd()
+        } else {
+            Encoding::poly()
+        };
+        let pt = Plaintext::random(encoding, &pk.par, rng)?;
+        pk.try_encrypt(&pt, rng)
+    }
+
+    /// Returns an iterator over the polynomials `c0, c1, ...` of this
+    /// ciphertext, e.g. to analyze them without the secret key.
+    pub fn components(&self) -> impl Iterator<Item = &Poly> {
+        self.c.iter()
+    }
+
+    /// Returns an iterator over the representations of the polynomials of
+    /// this ciphertext, in the order of [`Ciphertext::components`].
+    pub fn representations(&self) -> impl Iterator<Item = &Representation> {
+        self.c.iter().map(|ci| ci.representation())
+    }
+
+    /// Modulo switch the ciphertext down to the given level.
+    ///
+    /// Returns an error if the level is lower than the current level of the
+    /// ciphertext or larger than the maximum level of the parameters.
+    pub fn mod_switch_to_level(&mut self, level: usize) -> Result<()> {
+        if level < self.level || level > self.par.max_level() {
+            return Err(Error::DefaultError(format!(
+                "Cannot switch from level {} to level {}",
+                self.level, level
+            )));
+        }
+        while self.level < level {
+            self.mod_switch_to_next_level()?
+        }
+        Ok(())
+    }
+
     /// Modulo switch the ciphertext to the last level.
     pub fn mod_switch_to_last_level(&mut self) -> Result<()> {
+        self.drop_seed(SeedLoss::ModSwitch);
         self.level = self.par.max_level();
         let last_ctx = self.par.ctx_at_level(self.level)?;
-        self.seed = None;
         for ci in self.c.iter_mut() {
             if ci.ctx() != last_ctx {
                 ci.change_representation(Representation::PowerBasis);
@@ -63,6 +190,22 @@ impl Ciphertext {
         Ok(())
     }
 
+    /// Converts the ciphertext to its canonical form, without changing the
+    /// plaintext it encrypts: the seed of a fresh ciphertext is dropped, so
+    /// that it serializes in expanded form, and the polynomials are in NTT
+    /// representation and disallow variable time computations.
+    ///
+    /// The equality and the hash of ciphertexts are those of their canonical
+    /// forms, so that canonicalizing a ciphertext is only needed before
+    /// storing its serialization, e.g. in a content-addressed store.
+    pub fn canonicalize(&mut self) {
+        self.drop_seed(SeedLoss::Canonicalization);
+        for ci in self.c.iter_mut() {
+            ci.change_representation(Representation::Ntt);
+            ci.disallow_variable_time_computations();
+        }
+    }
+
     /// Truncate the underlying vector of polynomials.
     pub(crate) fn truncate(&mut self, len: usize) {
         self.c.truncate(len)
@@ -71,7 +214,7 @@ impl Ciphertext {
     /// Modulo switch the ciphertext to the next level.
     pub fn mod_switch_to_next_level(&mut self) -> Result<()> {
         if self.level < self.par.max_level() {
-            self.seed = None;
+            self.drop_seed(SeedLoss::ModSwitch);
             for ci in self.c.iter_mut() {
                 ci.change_representation(Representation::PowerBasis);
                 ci.mod_switch_down_next()?;
@@ -113,6 +256,155 @@ impl Ciphertext {
             level,
         })
     }
+
+    /// Export the ciphertext as raw RNS coefficients, e.g. to exchange it
+    /// through a foreign function interface without protobuf.
+    ///
+    /// The output contains one vector per polynomial of the ciphertext, in
+    /// order `c0, c1, ...`. Each vector contains `num_moduli * degree`
+    /// coefficients ordered by residue: first the `degree` coefficients modulo
+    /// the first modulus of the ciphertext level, then the `degree`
+    /// coefficients modulo the second modulus, and so on. The coefficients are
+    /// in the NTT representation of this library, i.e. in bit-reversed order,
+    /// and each coefficient is reduced modulo its modulus.
+    pub fn to_raw_parts(&self) -> Vec<Vec<u64>> {
+        self.c
+            .iter()
+            .map(|ci| ci.coefficients().iter().copied().collect())
+            .collect()
+    }
+
+    /// Serialize the ciphertext without compressing it with its seed.
+    ///
+    /// [`Serialize::to_bytes`] replaces the last polynomial by its seed when
+    /// the ciphertext is a fresh encryption with a secret key, so the length of
+    /// the serialization reveals the history of the ciphertext. The output of
+    /// this function always contains all the polynomials, so its length only
+    /// depends on the parameters, the level, and the number of polynomials of
+    /// the ciphertext. It is deserialized with [`Ciphertext::from_bytes`].
+    pub fn to_bytes_expanded(&self) -> Vec<u8> {
+        CiphertextProto {
+            c: self.c.iter().map(|ci| ci.to_bytes()).collect(),
+            seed: Vec::new(),
+            level: self.level as u32,
+        }
+        .encode_to_vec()
+    }
+
+    /// Import a ciphertext at the given level from raw RNS coefficients, laid
+    /// out as by [`Ciphertext::to_raw_parts`].
+    ///
+    /// Returns an error if there are fewer than two polynomials, if a
+    /// polynomial does not have `num_moduli * degree` coefficients for the
+    /// moduli of the level, or if a coefficient is not reduced modulo its
+    /// modulus.
+    pub fn from_raw_parts(
+        parts: Vec<Vec<u64>>,
+        level: usize,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        if parts.len() < 2 {
+            return Err(Error::TooFewValues(parts.len(), 2));
+        }
+        let ctx = par.ctx_at_level(level)?;
+        let c = parts
+            .into_iter()
+            .map(|part| {
+                if part.len() != ctx.moduli().len() * par.degree() {
+                    return Err(Error::DefaultError(format!(
+                        "Expected {} coefficients per polynomial, found {}",
+                        ctx.moduli().len() * par.degree(),
+                        part.len()
+                    )));
+                }
+                let reduced = izip!(part.chunks(par.degree()), ctx.moduli())
+                    .all(|(residues, qi)| residues.iter().all(|c| c < qi));
+                if !reduced {
+                    return Err(Error::DefaultError(
+                        "A coefficient is not reduced modulo its modulus".to_string(),
+                    ));
+                }
+                let mut ci = Poly::try_convert_from(part, ctx, false, Representation::Ntt)?;
+                unsafe { ci.allow_variable_time_computations() }
+                Ok(ci)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            par: par.clone(),
+            seed: None,
+            c,
+            level,
+        })
+    }
+}
+
+/// Calls the hook set with [`set_seed_loss_hook`], if any.
+pub(crate) fn report_seed_loss(operation: SeedLoss) {
+    let hook = SEED_LOSS_HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // The pointer was stored from a `fn(SeedLoss)` by `set_seed_loss_hook`.
+        let hook = unsafe { core::mem::transmute::<*mut (), fn(SeedLoss)>(hook) };
+        hook(operation)
+    }
+}
+
+impl From<&Ciphertext> for Vec<Poly> {
+    /// Returns copies of the polynomials of the ciphertext.
+    fn from(ct: &Ciphertext) -> Self {
+        ct.c.clone()
+    }
+}
+
+impl PartialEq for Ciphertext {
+    /// Compares the canonical forms of two ciphertexts.
+    fn eq(&self, other: &Self) -> bool {
+        if self.par != other.par || self.level != other.level || self.len() != other.len() {
+            return false;
+        }
+        izip!(self.iter(), other.iter()).all(|(c1i, c2i)| {
+            if c1i.representation() == c2i.representation() {
+                c1i.coefficients() == c2i.coefficients()
+            } else {
+                let mut c1i = c1i.clone();
+                let mut c2i = c2i.clone();
+                c1i.change_representation(Representation::Ntt);
+                c2i.change_representation(Representation::Ntt);
+                c1i.coefficients() == c2i.coefficients()
+            }
+        })
+    }
+}
+
+impl Hash for Ciphertext {
+    /// Hashes the expanded serialization of the canonical form of the
+    /// ciphertext, which requires a copy of the ciphertext.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut canonical = self.clone();
+        canonical.canonicalize();
+        canonical.to_bytes_expanded().hash(state)
+    }
+}
+
+impl ConstantTimeEq for Ciphertext {
+    /// Compares the coefficients of two ciphertexts in constant time.
+    ///
+    /// Only the parameters, the level and the number of polynomials, which are
+    /// public, are compared in variable time.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        if self.par != other.par || self.level != other.level || self.len() != other.len() {
+            return Choice::from(0);
+        }
+        let mut eq = Choice::from(1);
+        for (c1i, c2i) in izip!(self.iter(), other.iter()) {
+            if c1i.representation() != c2i.representation() {
+                return Choice::from(0);
+            }
+            for (a, b) in izip!(c1i.coefficients().iter(), c2i.coefficients().iter()) {
+                eq &= a.ct_eq(b);
+            }
+        }
+        eq
+    }
 }
 
 impl FheCiphertext for Ciphertext {}
@@ -128,18 +420,29 @@ impl Serialize for Ciphertext {
 }
 
 impl DeserializeParametrized for Ciphertext {
+    /// Deserializes a ciphertext with the default [`DeserializationConfig`].
     fn from_bytes(bytes: &[u8], par: &Arc<BfvParameters>) -> Result<Self> {
-        if let Ok(ctp) = Message::decode(bytes) {
-            Ciphertext::try_convert_from(&ctp, par)
-        } else {
-            Err(Error::SerializationError)
-        }
+        Ciphertext::from_bytes_with_config(bytes, par, &DeserializationConfig::default())
     }
 
     type Error = Error;
 }
 
 impl Ciphertext {
+    /// Deserializes a ciphertext, with the limits and options of `config`.
+    pub fn from_bytes_with_config(
+        bytes: &[u8],
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        config.check_bytes(bytes.len())?;
+        if let Ok(ctp) = Message::decode(bytes) {
+            Ciphertext::try_convert_from_with_config(&ctp, par, config)
+        } else {
+            Err(Error::SerializationError)
+        }
+    }
+
     /// Generate the zero ciphertext.
     pub fn zero(par: &Arc<BfvParameters>) -> Self {
         Self {
@@ -151,6 +454,89 @@ impl Ciphertext {
     }
 }
 
+impl Ciphertext {
+    /// Writes the ciphertext to `w` in the streaming format (see
+    /// [`fhe_math::stream`]), one polynomial at a time. As in
+    /// [`Serialize::to_bytes`], the last polynomial is replaced by its seed
+    /// when the ciphertext has one.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        if self.c.len() < 2 {
+            return Err(Error::DefaultError("Not enough polynomials".to_string()));
+        }
+        write_u32(w, self.level as u32)?;
+        write_u32(w, self.c.len() as u32)?;
+        let polys = match self.seed.as_ref() {
+            Some(seed) => {
+                w.write_all(&[1])?;
+                w.write_all(seed)?;
+                &self.c[..self.c.len() - 1]
+            }
+            None => {
+                w.write_all(&[0])?;
+                &self.c[..]
+            }
+        };
+        for p in polys {
+            p.write_to(w)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a ciphertext from `r`, in the streaming format of
+    /// [`Ciphertext::write_to`], with the default [`DeserializationConfig`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        Self::read_from_with_config(r, par, &DeserializationConfig::default())
+    }
+
+    /// Reads a ciphertext from `r`, in the streaming format of
+    /// [`Ciphertext::write_to`], with the options of `config` and its limit on
+    /// the number of polynomials; the limit on the number of bytes only
+    /// applies to [`Ciphertext::from_bytes_with_config`].
+    pub fn read_from_with_config<R: Reader + ?Sized>(
+        r: &mut R,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        let level = read_u32(r)? as usize;
+        let size = read_u32(r)? as usize;
+        if size < 2 {
+            return Err(Error::DefaultError("Not enough polynomials".to_string()));
+        }
+        config.check_polys(size)?;
+        let ctx = par.ctx_at_level(level)?;
+
+        let mut flag = [0u8];
+        r.read_exact(&mut flag)?;
+        let seed = match flag[0] {
+            0 => None,
+            1 => {
+                let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+                r.read_exact(&mut seed)?;
+                Some(seed)
+            }
+            _ => return Err(Error::DefaultError("Invalid seed flag".to_string())),
+        };
+
+        let mut c = Vec::with_capacity(size);
+        for _ in 0..size - seed.is_some() as usize {
+            let mut p = Poly::read_from(r, ctx)?;
+            p.change_representation(Representation::Ntt);
+            config.apply_vt(&mut p);
+            c.push(p)
+        }
+        if let Some(seed) = seed {
+            c.push(Self::expand_seed(ctx, seed, config))
+        }
+
+        Ok(Ciphertext {
+            par: par.clone(),
+            seed,
+            c,
+            level,
+        })
+    }
+}
+
 /// Conversions from and to protobuf.
 impl From<&Ciphertext> for CiphertextProto {
     fn from(ct: &Ciphertext) -> Self {
@@ -170,9 +556,22 @@ impl From<&Ciphertext> for CiphertextProto {
 
 impl TryConvertFrom<&CiphertextProto> for Ciphertext {
     fn try_convert_from(value: &CiphertextProto, par: &Arc<BfvParameters>) -> Result<Self> {
+        Self::try_convert_from_with_config(value, par, &DeserializationConfig::default())
+    }
+}
+
+impl Ciphertext {
+    /// Converts a protobuf ciphertext, with the limits and options of
+    /// `config`.
+    pub(crate) fn try_convert_from_with_config(
+        value: &CiphertextProto,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
         if value.c.is_empty() || (value.c.len() == 1 && value.seed.is_empty()) {
             return Err(Error::DefaultError("Not enough polynomials".to_string()));
         }
+        config.check_polys(value.c.len() + !value.seed.is_empty() as usize)?;
 
         if value.level as usize > par.max_level() {
             return Err(Error::DefaultError("Invalid level".to_string()));
@@ -182,7 +581,9 @@ impl TryConvertFrom<&CiphertextProto> for Ciphertext {
 
         let mut c = Vec::with_capacity(value.c.len() + 1);
         for cip in &value.c {
-            c.push(Poly::from_bytes(cip, ctx)?)
+            let mut ci = Poly::from_bytes(cip, ctx)?;
+            config.apply_vt(&mut ci);
+            c.push(ci)
         }
 
         let mut seed = None;
@@ -195,9 +596,7 @@ impl TryConvertFrom<&CiphertextProto> for Ciphertext {
                     ))
                 })?;
             seed = Some(try_seed);
-            let mut c1 = Poly::random_from_seed(ctx, Representation::Ntt, try_seed);
-            unsafe { c1.allow_variable_time_computations() }
-            c.push(c1)
+            c.push(Self::expand_seed(ctx, try_seed, config))
         }
 
         Ok(Ciphertext {
@@ -207,20 +606,40 @@ impl TryConvertFrom<&CiphertextProto> for Ciphertext {
             level: value.level as usize,
         })
     }
+
+    /// Expands the last polynomial of a compressed ciphertext from its seed,
+    /// which uses variable-time computations if `config` allows them.
+    fn expand_seed(
+        ctx: &Arc<Context>,
+        seed: <ChaCha8Rng as SeedableRng>::Seed,
+        config: &DeserializationConfig,
+    ) -> Poly {
+        let mut c1 = Poly::random_from_seed(ctx, Representation::Ntt, seed);
+        if config.allow_vt {
+            unsafe { c1.allow_variable_time_computations() }
+        }
+        c1
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::bfv::{
-        traits::TryConvertFrom, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey,
+        set_seed_loss_hook, traits::TryConvertFrom, BfvParameters, BfvParametersBuilder,
+        Ciphertext, CompressionState, DeserializationConfig, Encoding, Plaintext, PublicKey,
+        SecretKey, SeedLoss,
     };
     use crate::proto::bfv::Ciphertext as CiphertextProto;
     use crate::Error;
-    use fhe_traits::FheDecrypter;
     use fhe_traits::{DeserializeParametrized, FheEncoder, FheEncrypter, Serialize};
+    use fhe_traits::{FheDecoder, FheDecrypter};
+    use hashbrown::HashSet;
     use rand::thread_rng;
+    use subtle::ConstantTimeEq;
     extern crate alloc;
     use alloc::vec;
+    use alloc::vec::Vec;
+    use fhe_math::rq::{Poly, Representation};
 
     #[test]
     fn proto_conversion() -> Result<(), Error> {
@@ -230,8 +649,7 @@ mod tests {
             BfvParameters::default_arc(6, 16),
         ] {
             let sk = SecretKey::random(&params, &mut rng);
-            let v = params.plaintext.random_vec(params.degree(), &mut rng);
-            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
             let ct = sk.try_encrypt(&pt, &mut rng)?;
             let ct_proto = CiphertextProto::from(&ct);
             assert_eq!(ct, Ciphertext::try_convert_from(&ct_proto, &params)?);
@@ -251,8 +669,7 @@ mod tests {
             BfvParameters::default_arc(6, 16),
         ] {
             let sk = SecretKey::random(&params, &mut rng);
-            let v = params.plaintext.random_vec(params.degree(), &mut rng);
-            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
             let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
             let ct_bytes = ct.to_bytes();
             assert_eq!(ct, Ciphertext::from_bytes(&ct_bytes, &params)?);
@@ -260,6 +677,159 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn random_fresh() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParametersBuilder::new()
+                .set_degree(16)
+                .set_plaintext_modulus(2)
+                .set_moduli(&[4611686018326724609])
+                .allow_insecure()
+                .build_arc()?,
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            let ct = Ciphertext::random_fresh(&pk, &mut rng)?;
+            assert_eq!(ct.len(), 2);
+            assert_eq!(ct.level(), 0);
+            let pt = sk.try_decrypt(&ct)?;
+            let encoding = if params.op.is_some() {
+                Encoding::simd()
+            } else {
+                Encoding::poly()
+            };
+            assert_eq!(pt.encoding, None);
+            let values = Vec::<u64>::try_decode(&pt, encoding)?;
+            assert!(values.iter().all(|v| *v < params.plaintext()));
+            assert_ne!(ct, Ciphertext::random_fresh(&pk, &mut rng)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn compression_state() -> Result<(), Error> {
+        extern crate std;
+        use std::cell::RefCell;
+
+        std::thread_local! {
+            static LOSSES: RefCell<Vec<SeedLoss>> = const { RefCell::new(Vec::new()) };
+        }
+        fn record(operation: SeedLoss) {
+            LOSSES.with(|losses| losses.borrow_mut().push(operation))
+        }
+        let take = || LOSSES.with(|losses| losses.take());
+
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct_sk: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let ct_pk: Ciphertext = pk.try_encrypt(&pt, &mut rng)?;
+        assert_eq!(ct_sk.compression_state(), CompressionState::Seeded);
+        assert_eq!(ct_pk.compression_state(), CompressionState::Expanded);
+
+        set_seed_loss_hook(Some(record));
+        let sum = &ct_sk + &ct_pk;
+        assert_eq!(sum.compression_state(), CompressionState::Expanded);
+        assert_eq!(take(), [SeedLoss::Addition]);
+
+        // Only the ciphertexts which had a seed are reported.
+        let _ = &(&ct_pk * &pt) - &ct_pk;
+        let _ = -&ct_pk;
+        assert!(take().is_empty());
+
+        let _ = &ct_sk * &pt;
+        let _ = -&ct_sk;
+        let mut ct = ct_sk.clone();
+        ct.mod_switch_to_next_level()?;
+        let mut ct = ct_sk.clone();
+        ct.canonicalize();
+        assert_eq!(
+            take(),
+            [
+                SeedLoss::Multiplication,
+                SeedLoss::Negation,
+                SeedLoss::ModSwitch,
+                SeedLoss::Canonicalization
+            ]
+        );
+
+        set_seed_loss_hook(None);
+        let _ = &ct_sk + &ct_pk;
+        assert!(take().is_empty());
+        assert_eq!(ct_sk.compression_state(), CompressionState::Seeded);
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_expanded() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+            let ct_sk: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let ct_pk: Ciphertext = pk.try_encrypt(&pt, &mut rng)?;
+            assert!(ct_sk.seed.is_some() && ct_pk.seed.is_none());
+            assert_ne!(ct_sk.to_bytes().len(), ct_pk.to_bytes().len());
+
+            let ct_bytes = ct_sk.to_bytes_expanded();
+            assert_eq!(ct_bytes.len(), ct_pk.to_bytes_expanded().len());
+            assert_eq!(ct_pk.to_bytes_expanded(), ct_pk.to_bytes());
+            // Keep the variable-time flags of the serialization, to compare the
+            // polynomials exactly.
+            let config = DeserializationConfig {
+                allow_vt: true,
+                ..Default::default()
+            };
+            let ct = Ciphertext::from_bytes_with_config(&ct_bytes, &params, &config)?;
+            assert!(ct.seed.is_none());
+            assert_eq!(ct.c, ct_sk.c);
+            assert_eq!(ct.level, ct_sk.level);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+            let ct_sk: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let ct_pk: Ciphertext = pk.try_encrypt(&pt, &mut rng)?;
+            let mut ct3 = &ct_pk * &ct_pk;
+            ct3.mod_switch_to_last_level()?;
+
+            for ct in [ct_sk, ct_pk, ct3] {
+                let mut bytes = Vec::new();
+                ct.write_to(&mut bytes)?;
+                let mut r = &bytes[..];
+                let ct2 = Ciphertext::read_from(&mut r, &params)?;
+                assert!(r.is_empty());
+                assert_eq!(ct2, ct);
+                assert_eq!(ct2.seed, ct.seed);
+                assert_eq!(ct2.level, ct.level);
+                assert_eq!(sk.try_decrypt(&ct2)?, sk.try_decrypt(&ct)?);
+
+                let mut buffer = vec![0u8; bytes.len() - 1];
+                assert!(ct.write_to(&mut &mut buffer[..]).is_err());
+                assert!(Ciphertext::read_from(&mut &bytes[..bytes.len() - 1], &params).is_err());
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn new() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -268,8 +838,7 @@ mod tests {
             BfvParameters::default_arc(6, 16),
         ] {
             let sk = SecretKey::random(&params, &mut rng);
-            let v = params.plaintext.random_vec(params.degree(), &mut rng);
-            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
             let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
             let mut ct3 = &ct * &ct;
 
@@ -306,8 +875,7 @@ mod tests {
             BfvParameters::default_arc(6, 16),
         ] {
             let sk = SecretKey::random(&params, &mut rng);
-            let v = params.plaintext.random_vec(params.degree(), &mut rng);
-            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
             let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
 
             assert_eq!(ct.level, 0);
@@ -320,4 +888,183 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn decode_after_mod_switch() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let b = params.plaintext.random_vec(params.degree(), &mut rng);
+        let mut sum = a.clone();
+        params.plaintext.add_vec(&mut sum, &b);
+        let mut product = a.clone();
+        params.plaintext.mul_vec(&mut product, &b);
+
+        for encoding in [Encoding::simd(), Encoding::poly()] {
+            let pt = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+            let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let expected = Vec::<u64>::try_decode(&sk.try_decrypt(&ct)?, encoding.clone())?;
+            assert_eq!(expected, a);
+            for level in 1..=params.max_level() {
+                // Q mod t differs at each level, which the decryption and the
+                // plaintexts encoded at this level must account for.
+                ct.mod_switch_to_next_level()?;
+                assert_eq!(ct.level, level);
+                let decrypted = sk.try_decrypt(&ct)?;
+                assert_eq!(decrypted.level(), level);
+                assert_eq!(
+                    Vec::<u64>::try_decode(&decrypted, encoding.clone())?,
+                    expected
+                );
+
+                if encoding == Encoding::simd() {
+                    let pt_b = Plaintext::try_encode(&b, Encoding::simd_at_level(level), &params)?;
+                    let decrypted = sk.try_decrypt(&(&ct + &pt_b))?;
+                    assert_eq!(Vec::<u64>::try_decode(&decrypted, Encoding::simd())?, sum);
+                    let decrypted = sk.try_decrypt(&(&ct * &pt_b))?;
+                    assert_eq!(
+                        Vec::<u64>::try_decode(&decrypted, Encoding::simd())?,
+                        product
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ct_eq() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+        let ct1: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let ct2: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        assert!(bool::from(ct1.ct_eq(&ct1.clone())));
+        assert!(!bool::from(ct1.ct_eq(&ct2)));
+
+        let mut ct3 = ct1.clone();
+        ct3.mod_switch_to_next_level()?;
+        assert!(!bool::from(ct1.ct_eq(&ct3)));
+
+        let mut ct4 = ct1.clone();
+        ct4.truncate(1);
+        assert!(!bool::from(ct1.ct_eq(&ct4)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_parts() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+            let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            ct.mod_switch_to_last_level()?;
+
+            let parts = ct.to_raw_parts();
+            assert_eq!(parts.len(), 2);
+            assert_eq!(parts[0].len(), params.degree());
+            let ct2 = Ciphertext::from_raw_parts(parts.clone(), ct.level(), &params)?;
+            assert_eq!(ct2.to_raw_parts(), parts);
+            assert_eq!(sk.try_decrypt(&ct2)?, sk.try_decrypt(&ct)?);
+
+            assert!(Ciphertext::from_raw_parts(parts[..1].to_vec(), ct.level(), &params).is_err());
+            let mut truncated = parts.clone();
+            truncated[0].pop();
+            assert!(Ciphertext::from_raw_parts(truncated, ct.level(), &params).is_err());
+            let mut unreduced = parts.clone();
+            unreduced[1][0] = params.moduli()[0];
+            assert!(Ciphertext::from_raw_parts(unreduced, ct.level(), &params).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn components() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        assert_eq!(ct.components().count(), 2);
+        assert!(ct.components().eq(ct.iter()));
+        assert!(ct.representations().all(|r| r == &Representation::Ntt));
+        assert_eq!(Vec::<Poly>::from(&ct), ct.to_vec());
+        assert_eq!(
+            Ciphertext::new(Vec::<Poly>::from(&ct), &params)?.to_raw_parts(),
+            ct.to_raw_parts()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mod_switch_to_level() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        ct.mod_switch_to_level(3)?;
+        assert_eq!(ct.level(), 3);
+        assert!(ct.mod_switch_to_level(2).is_err());
+        assert!(ct.mod_switch_to_level(params.max_level() + 1).is_err());
+        assert_eq!(ct.level(), 3);
+
+        let decrypted = sk.try_decrypt(&ct)?;
+        assert_eq!(decrypted.value, pt.value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_form() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        // The seed-compressed and the expanded forms are equal.
+        let expanded = Ciphertext::from_bytes(&ct.to_bytes_expanded(), &params)?;
+        assert!(ct.seed.is_some() && expanded.seed.is_none());
+        assert_eq!(ct, expanded);
+
+        let mut canonical = ct.clone();
+        canonical.canonicalize();
+        assert!(canonical.seed.is_none());
+        // Both forms have the same canonical serialization.
+        let mut canonical_expanded = expanded.clone();
+        canonical_expanded.canonicalize();
+        assert_eq!(canonical.to_bytes(), canonical_expanded.to_bytes());
+        assert_eq!(canonical.to_bytes(), canonical.to_bytes_expanded());
+        assert_eq!(canonical, ct);
+
+        // The representation and the flags of the polynomials are ignored.
+        let mut other = ct.clone();
+        other[0].change_representation(Representation::PowerBasis);
+        unsafe { other[1].allow_variable_time_computations() }
+        assert_eq!(other, ct);
+
+        let ct2: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert_ne!(ct, ct2);
+        let mut ct3 = ct.clone();
+        ct3.mod_switch_to_next_level()?;
+        assert_ne!(ct, ct3);
+
+        // Equal ciphertexts have the same hash, and are deduplicated.
+        let set: HashSet<Ciphertext> = [ct, expanded, canonical, other, ct2, ct3]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 3);
+        Ok(())
+    }
 }
@@ -0,0 +1,316 @@
+//! Matrices of ciphertexts, as used by multi-dimensional PIR queries.
+
+extern crate alloc;
+
+use crate::bfv::{dot_product_scalar, Ciphertext, EvaluationKey, Multiplicator, Plaintext};
+use crate::{Error, Result};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// A `rows` × `cols` matrix of ciphertexts, stored in row-major order.
+///
+/// The combination helpers map onto the structure of multi-dimensional PIR: a
+/// database arranged as a plaintext matrix is multiplied by an encrypted query
+/// with [`CiphertextMatrix::mul_plaintext_matrix`], and the intermediate
+/// results are then folded dimension by dimension with
+/// [`CiphertextMatrix::fold_rows`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiphertextMatrix {
+    rows: usize,
+    cols: usize,
+    cts: Vec<Ciphertext>,
+}
+
+impl CiphertextMatrix {
+    /// Create a matrix from ciphertexts in row-major order.
+    ///
+    /// Returns an error if the number of ciphertexts is not `rows * cols`, if
+    /// the matrix is empty, or if the ciphertexts do not share the same
+    /// parameters.
+    pub fn new(cts: Vec<Ciphertext>, rows: usize, cols: usize) -> Result<Self> {
+        if rows == 0 || cols == 0 {
+            return Err(Error::DefaultError("Empty ciphertext matrix".to_string()));
+        }
+        if cts.len() != rows * cols {
+            return Err(Error::DefaultError(
+                "The number of ciphertexts does not match the dimensions".to_string(),
+            ));
+        }
+        if cts.iter().any(|ct| ct.par != cts[0].par) {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        Ok(Self { rows, cols, cts })
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the ciphertext at row `i` and column `j`, if any.
+    pub fn get(&self, i: usize, j: usize) -> Option<&Ciphertext> {
+        if i < self.rows && j < self.cols {
+            self.cts.get(i * self.cols + j)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the ciphertexts of row `i`, if any.
+    pub fn row(&self, i: usize) -> Option<impl Iterator<Item = &Ciphertext> + Clone> {
+        (i < self.rows).then(|| self.row_iter(i))
+    }
+
+    /// Returns an iterator over the ciphertexts of column `j`, if any.
+    pub fn column(&self, j: usize) -> Option<impl Iterator<Item = &Ciphertext> + Clone> {
+        (j < self.cols).then(|| self.column_iter(j))
+    }
+
+    fn row_iter(&self, i: usize) -> impl Iterator<Item = &Ciphertext> + Clone {
+        self.cts[i * self.cols..(i + 1) * self.cols].iter()
+    }
+
+    fn column_iter(&self, j: usize) -> impl Iterator<Item = &Ciphertext> + Clone {
+        self.cts.iter().skip(j).step_by(self.cols)
+    }
+
+    /// Returns the ciphertexts in row-major order.
+    pub fn ciphertexts(&self) -> &[Ciphertext] {
+        &self.cts
+    }
+
+    /// Returns the transposed matrix.
+    pub fn transpose(&self) -> Self {
+        let cts = (0..self.cols)
+            .flat_map(|j| self.column_iter(j).cloned())
+            .collect();
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            cts,
+        }
+    }
+
+    /// Rotate the rows of SIMD slots of every ciphertext of the matrix.
+    pub fn rotates_rows(&self, ek: &EvaluationKey) -> Result<Self> {
+        let cts = self
+            .cts
+            .iter()
+            .map(|ct| ek.rotates_rows(ct))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols,
+            cts,
+        })
+    }
+
+    /// Combine the rows with plaintext weights, i.e. compute the vector of
+    /// `cols` ciphertexts `sum_i weights[i] * self[i][j]`.
+    pub fn combine_rows(&self, weights: &[Plaintext]) -> Result<Vec<Ciphertext>> {
+        if weights.len() != self.rows {
+            return Err(Error::DefaultError(
+                "The number of weights does not match the number of rows".to_string(),
+            ));
+        }
+        (0..self.cols)
+            .map(|j| dot_product_scalar(self.column_iter(j), weights.iter()))
+            .collect()
+    }
+
+    /// Combine the columns with plaintext weights, i.e. compute the vector of
+    /// `rows` ciphertexts `sum_j weights[j] * self[i][j]`.
+    pub fn combine_columns(&self, weights: &[Plaintext]) -> Result<Vec<Ciphertext>> {
+        if weights.len() != self.cols {
+            return Err(Error::DefaultError(
+                "The number of weights does not match the number of columns".to_string(),
+            ));
+        }
+        (0..self.rows)
+            .map(|i| dot_product_scalar(self.row_iter(i), weights.iter()))
+            .collect()
+    }
+
+    /// Multiply the matrix by a `cols` × `k` plaintext matrix given in
+    /// row-major order, and return the resulting `rows` × `k` matrix.
+    pub fn mul_plaintext_matrix(&self, pts: &[Plaintext], k: usize) -> Result<Self> {
+        if k == 0 || pts.len() != self.cols * k {
+            return Err(Error::DefaultError(
+                "The plaintext matrix dimensions do not match".to_string(),
+            ));
+        }
+        let mut cts = Vec::with_capacity(self.rows * k);
+        for i in 0..self.rows {
+            for j in 0..k {
+                cts.push(dot_product_scalar(
+                    self.row_iter(i),
+                    pts.iter().skip(j).step_by(k),
+                )?);
+            }
+        }
+        Ok(Self {
+            rows: self.rows,
+            cols: k,
+            cts,
+        })
+    }
+
+    /// Fold the rows using encrypted selectors, i.e. compute the vector of
+    /// `cols` ciphertexts `sum_i selectors[i] * self[i][j]`.
+    ///
+    /// With a one-hot encrypted selector, this selects one row of the matrix
+    /// obliviously, which corresponds to one step of a recursive PIR query.
+    pub fn fold_rows(
+        &self,
+        selectors: &[Ciphertext],
+        multiplicator: &Multiplicator,
+    ) -> Result<Vec<Ciphertext>> {
+        if selectors.len() != self.rows {
+            return Err(Error::DefaultError(
+                "The number of selectors does not match the number of rows".to_string(),
+            ));
+        }
+        (0..self.cols)
+            .map(|j| {
+                let mut acc: Option<Ciphertext> = None;
+                for (selector, ct) in selectors.iter().zip(self.column_iter(j)) {
+                    let product = multiplicator.multiply(selector, ct)?;
+                    match acc.as_mut() {
+                        Some(acc) => *acc += &product,
+                        None => acc = Some(product),
+                    }
+                }
+                acc.ok_or_else(|| Error::DefaultError("Empty ciphertext matrix".to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CiphertextMatrix;
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, Multiplicator, Plaintext, RelinearizationKey,
+        SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn combine() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let (rows, cols, k) = (2, 3, 2);
+        let t = params.plaintext();
+
+        let values = (0..rows * cols)
+            .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+        let cts = values
+            .iter()
+            .map(|v| {
+                let pt = Plaintext::try_encode(v, Encoding::simd(), &params)?;
+                sk.try_encrypt(&pt, &mut rng)
+            })
+            .collect::<Result<Vec<Ciphertext>, Error>>()?;
+        let matrix = CiphertextMatrix::new(cts, rows, cols)?;
+        assert!(CiphertextMatrix::new(matrix.ciphertexts().to_vec(), cols, cols).is_err());
+        assert_eq!(matrix.row(1).unwrap().count(), cols);
+        assert_eq!(matrix.column(2).unwrap().count(), rows);
+        assert_eq!(matrix.column(2).unwrap().nth(1), matrix.get(1, 2));
+        assert!(matrix.row(rows).is_none());
+        assert!(matrix.column(cols).is_none());
+        assert!(matrix.get(0, cols).is_none());
+
+        let weights = (0..cols * k)
+            .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+        let pts = weights
+            .iter()
+            .map(|w| Plaintext::try_encode(w, Encoding::simd(), &params))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let decrypt = |ct: &Ciphertext| -> Result<Vec<u64>, Error> {
+            Vec::<u64>::try_decode(&sk.try_decrypt(ct)?, Encoding::simd())
+        };
+        let dot = |a: &[&Vec<u64>], b: &[&Vec<u64>]| -> Vec<u64> {
+            (0..params.degree())
+                .map(|s| {
+                    a.iter()
+                        .zip(b)
+                        .fold(0, |acc, (x, y)| (acc + x[s] * y[s] % t) % t)
+                })
+                .collect()
+        };
+
+        let product = matrix.mul_plaintext_matrix(&pts, k)?;
+        assert_eq!((product.rows(), product.cols()), (rows, k));
+        for i in 0..rows {
+            for j in 0..k {
+                let a = (0..cols).map(|l| &values[i * cols + l]).collect::<Vec<_>>();
+                let b = (0..cols).map(|l| &weights[l * k + j]).collect::<Vec<_>>();
+                assert_eq!(decrypt(product.get(i, j).unwrap())?, dot(&a, &b));
+            }
+        }
+
+        let combined = matrix.combine_columns(&pts[..cols])?;
+        let transposed = matrix.transpose();
+        assert_eq!(transposed.combine_rows(&pts[..cols])?, combined);
+        for (i, ct) in combined.iter().enumerate() {
+            let a = (0..cols).map(|l| &values[i * cols + l]).collect::<Vec<_>>();
+            let b = weights[..cols].iter().collect::<Vec<_>>();
+            assert_eq!(decrypt(ct)?, dot(&a, &b));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fold_rows() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+        let (rows, cols) = (3, 2);
+
+        let values = (0..rows * cols)
+            .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+        let mut cts = Vec::new();
+        for v in &values {
+            let pt = Plaintext::try_encode(v, Encoding::simd(), &params)?;
+            cts.push(sk.try_encrypt(&pt, &mut rng)?);
+        }
+        let matrix = CiphertextMatrix::new(cts, rows, cols)?;
+
+        let index = 1;
+        let mut selectors = Vec::new();
+        for i in 0..rows {
+            let selector = vec![(i == index) as u64; params.degree()];
+            let pt = Plaintext::try_encode(&selector, Encoding::simd(), &params)?;
+            selectors.push(sk.try_encrypt(&pt, &mut rng)?);
+        }
+        let folded = matrix.fold_rows(&selectors, &multiplicator)?;
+        assert_eq!(folded.len(), cols);
+        for (j, ct) in folded.iter().enumerate() {
+            let pt = sk.try_decrypt(ct)?;
+            assert_eq!(
+                Vec::<u64>::try_decode(&pt, Encoding::simd())?,
+                values[index * cols + j]
+            );
+        }
+        Ok(())
+    }
+}
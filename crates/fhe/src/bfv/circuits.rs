@@ -0,0 +1,187 @@
+//! Equality and comparison circuits over the slots of ciphertexts.
+//!
+//! For a prime plaintext modulus `t`, Fermat's little theorem gives
+//! `x^(t - 1) = 1` for `x != 0` and `0` for `x = 0`, so that `1 - (x - y)^(t -
+//! 1)` is the 0/1 indicator of `x = y`. More generally, any function of the
+//! slots is a polynomial of degree less than `t`: the comparison with a
+//! constant evaluates the polynomial interpolating it at the points `0..t`.
+//!
+//! The slots of the inputs are read in the order of a SIMD encoding, and each
+//! slot of the output encrypts 0 or 1. The ciphertexts must be at the level of
+//! the `multiplicator`, which must relinearize and must not switch modulus.
+
+use crate::bfv::ops::{constant, evaluate_polynomial, interpolate, pow};
+use crate::bfv::{Ciphertext, Multiplicator};
+use crate::{Error, Result};
+use fhe_util::is_prime;
+extern crate alloc;
+use alloc::string::ToString;
+
+/// Returns an error if the plaintext modulus of `ct` is not prime.
+fn check_prime_plaintext(ct: &Ciphertext) -> Result<()> {
+    if !is_prime(ct.par.plaintext()) {
+        return Err(Error::DefaultError(
+            "The plaintext modulus must be prime".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Compares the slots of `ct` with the constant `k`, reduced modulo the
+/// plaintext modulus `t`: each slot of the output encrypts 1 if the slot of
+/// `ct` is equal to `k`, and 0 otherwise.
+///
+/// The plaintext modulus must be prime. The comparison raises the difference
+/// to the power `t - 1`, with a multiplicative depth of `ceil(log2(t - 1))`.
+pub fn eq_const(ct: &Ciphertext, k: u64, multiplicator: &Multiplicator) -> Result<Ciphertext> {
+    check_prime_plaintext(ct)?;
+    let k = ct.par.plaintext.reduce(k);
+    let difference = ct - &constant(ct, k)?;
+    is_zero(&difference, multiplicator)
+}
+
+/// Compares the slots of `ct` with the constant `k`, as integers in `0..t`
+/// for the plaintext modulus `t`: each slot of the output encrypts 1 if the
+/// slot of `ct` is smaller than `k`, and 0 otherwise.
+///
+/// The plaintext modulus must be prime. The comparison evaluates a polynomial
+/// of degree `t - 1`, with about `2 sqrt(t)` ciphertext multiplications and
+/// a multiplicative depth of `ceil(log2(t))`, followed by a product by a
+/// constant. Returns an error if `0 < k < t` and `t` is larger than
+/// [`MAX_INTERPOLATION_BOUND`](crate::bfv::MAX_INTERPOLATION_BOUND).
+pub fn lt_const(ct: &Ciphertext, k: u64, multiplicator: &Multiplicator) -> Result<Ciphertext> {
+    check_prime_plaintext(ct)?;
+    let t = ct.par.plaintext();
+    if k == 0 || k >= t {
+        // The comparison is constant.
+        let zero = ct * &constant(ct, 0)?;
+        return if k == 0 {
+            Ok(zero)
+        } else {
+            Ok(&zero + &constant(ct, 1)?)
+        };
+    }
+    let coefficients = interpolate(t, &ct.par.plaintext, |x| (x < k) as u64)?;
+    evaluate_polynomial(ct, &coefficients, multiplicator)
+}
+
+/// Compares the slots of `lhs` and `rhs`: each slot of the output encrypts 1
+/// if the slots are equal, and 0 otherwise.
+///
+/// The ciphertexts must have the same parameters and level, and the plaintext
+/// modulus must be prime. The comparison raises the difference to the power
+/// `t - 1`, with a multiplicative depth of `ceil(log2(t - 1))`.
+pub fn eq(lhs: &Ciphertext, rhs: &Ciphertext, multiplicator: &Multiplicator) -> Result<Ciphertext> {
+    if lhs.par != rhs.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    if lhs.level != rhs.level {
+        return Err(Error::DefaultError(
+            "Ciphertexts are not at the same level".to_string(),
+        ));
+    }
+    check_prime_plaintext(lhs)?;
+    is_zero(&(lhs - rhs), multiplicator)
+}
+
+/// Returns `1 - ct^(t - 1)`, the indicator of the zero slots of `ct`.
+fn is_zero(ct: &Ciphertext, multiplicator: &Multiplicator) -> Result<Ciphertext> {
+    let nonzero = pow(ct, ct.par.plaintext() - 1, multiplicator)?;
+    Ok(&constant(ct, 1)? - &nonzero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq, eq_const, lt_const};
+    use crate::bfv::{
+        BfvParametersBuilder, Ciphertext, Encoding, Multiplicator, Plaintext, RelinearizationKey,
+        SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn comparisons() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(97)
+            .set_moduli_sizes(&[62; 8])
+            .allow_insecure()
+            .build_arc()?;
+        let t = params.plaintext();
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+
+        // Small values, so that some slots are equal.
+        let a = (0..params.degree())
+            .map(|_| rng.gen_range(0..8))
+            .collect::<Vec<u64>>();
+        let mut b = a.clone();
+        b.iter_mut().step_by(3).for_each(|v| *v = t - 1 - *v);
+        let encrypt = |values: &[u64]| -> Result<Ciphertext, Error> {
+            let pt = Plaintext::try_encode(values, Encoding::simd(), &params)?;
+            sk.try_encrypt(&pt, &mut thread_rng())
+        };
+        let decrypt = |ct: &Ciphertext| -> Result<Vec<u64>, Error> {
+            Vec::<u64>::try_decode(&sk.try_decrypt(ct)?, Encoding::simd())
+        };
+        let ct_a = encrypt(&a)?;
+        let ct_b = encrypt(&b)?;
+
+        for k in [0, 3, 7, t - 1, t + 3] {
+            let expected = a.iter().map(|v| (*v == k % t) as u64).collect::<Vec<_>>();
+            assert_eq!(decrypt(&eq_const(&ct_a, k, &multiplicator)?)?, expected);
+        }
+        for k in [0, 1, 4, 8, t - 1, t, t + 3] {
+            let expected = a.iter().map(|v| (*v < k) as u64).collect::<Vec<_>>();
+            assert_eq!(decrypt(&lt_const(&ct_a, k, &multiplicator)?)?, expected);
+        }
+        let expected = a
+            .iter()
+            .zip(&b)
+            .map(|(x, y)| (x == y) as u64)
+            .collect::<Vec<_>>();
+        assert_eq!(decrypt(&eq(&ct_a, &ct_b, &multiplicator)?)?, expected);
+
+        let mut lower = ct_b.clone();
+        lower.mod_switch_to_next_level()?;
+        assert!(eq(&ct_a, &lower, &multiplicator).is_err());
+
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1024)
+            .set_moduli_sizes(&[62; 2])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let multiplicator = Multiplicator::default(&RelinearizationKey::new(&sk, &mut rng)?)?;
+        let pt = Plaintext::try_encode(&[1u64], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(eq_const(&ct, 1, &multiplicator).is_err());
+        assert!(lt_const(&ct, 1, &multiplicator).is_err());
+        assert!(eq(&ct, &ct, &multiplicator).is_err());
+
+        // The interpolation is rejected for large plaintext moduli, while the
+        // equality only raises to a power.
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(65537)
+            .set_moduli_sizes(&[62; 2])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let multiplicator = Multiplicator::default(&RelinearizationKey::new(&sk, &mut rng)?)?;
+        let pt = Plaintext::try_encode(&[1u64], Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(lt_const(&ct, 2, &multiplicator).is_err());
+        assert!(lt_const(&ct, 0, &multiplicator).is_ok());
+        Ok(())
+    }
+}
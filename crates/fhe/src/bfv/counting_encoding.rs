@@ -0,0 +1,207 @@
+//! Orderless encoding of multisets into counters.
+
+extern crate alloc;
+
+use crate::bfv::{BfvParameters, Encoding, Plaintext};
+use crate::{Error, Result};
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use fhe_traits::{FheDecoder, FheEncoder};
+use hashbrown::HashMap;
+use sha2::{Digest, Sha256};
+
+/// Domain separation tag for the hash mapping items to counters.
+const DOMAIN: &[u8] = b"fhe.rs counting encoding";
+
+/// How the counting encoding handles distinct items mapped to the same counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Colliding items share their counter, so that decoded counts may
+    /// overestimate the actual counts.
+    Share,
+
+    /// Encoding fails if two distinct items are mapped to the same counter.
+    Reject,
+
+    /// Each item increments `k` counters, and its decoded count is the minimum
+    /// of these counters, as in a count-min sketch.
+    CountMin(usize),
+}
+
+/// An encoding of multisets of items into counters, one per coefficient (or
+/// SIMD slot) of a plaintext.
+///
+/// Items are mapped to counters by hashing, so the encoding does not depend on
+/// the order of the items. Adding ciphertexts encrypting encoded multisets
+/// yields an encryption of the encoded union of the multisets, which enables
+/// private frequency counts and heavy-hitter aggregation. The counters are
+/// computed modulo the plaintext modulus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountingEncoding {
+    par: Arc<BfvParameters>,
+    policy: CollisionPolicy,
+    encoding: Encoding,
+}
+
+impl CountingEncoding {
+    /// Create a counting encoding with the given collision policy.
+    ///
+    /// The counters are stored in SIMD slots if the parameters support it, and
+    /// in polynomial coefficients otherwise; in both cases, homomorphic
+    /// additions add the counters component-wise.
+    pub fn new(par: &Arc<BfvParameters>, policy: CollisionPolicy) -> Result<Self> {
+        if let CollisionPolicy::CountMin(k) = policy {
+            if k == 0 || k > par.degree() {
+                return Err(Error::UnspecifiedInput(
+                    "The number of counters per item must be between 1 and the degree".to_string(),
+                ));
+            }
+        }
+        let encoding = if par.op.is_some() {
+            Encoding::simd()
+        } else {
+            Encoding::poly()
+        };
+        Ok(Self {
+            par: par.clone(),
+            policy,
+            encoding,
+        })
+    }
+
+    /// Returns the collision policy.
+    pub fn policy(&self) -> CollisionPolicy {
+        self.policy
+    }
+
+    /// Returns the indices of the counters associated with an item.
+    pub fn counters(&self, item: &[u8]) -> Vec<usize> {
+        let k = match self.policy {
+            CollisionPolicy::CountMin(k) => k,
+            _ => 1,
+        };
+        (0..k as u32)
+            .map(|i| {
+                let mut hasher = Sha256::new();
+                hasher.update(DOMAIN);
+                hasher.update(i.to_le_bytes());
+                hasher.update(item);
+                let digest = hasher.finalize();
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&digest[..8]);
+                (u64::from_le_bytes(bytes) % self.par.degree() as u64) as usize
+            })
+            .collect()
+    }
+
+    /// Encode a multiset of items into a plaintext of counters.
+    pub fn encode<I, T>(&self, items: I) -> Result<Plaintext>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let t = self.par.plaintext();
+        let mut counters = vec![0u64; self.par.degree()];
+        let mut owners: HashMap<usize, Vec<u8>> = HashMap::new();
+        for item in items {
+            let item = item.as_ref();
+            for index in self.counters(item) {
+                if self.policy == CollisionPolicy::Reject {
+                    let owner = owners.entry(index).or_insert_with(|| item.to_vec());
+                    if owner.as_slice() != item {
+                        return Err(Error::UnspecifiedInput(
+                            "Two distinct items are mapped to the same counter".to_string(),
+                        ));
+                    }
+                }
+                counters[index] = (counters[index] + 1) % t;
+            }
+        }
+        Plaintext::try_encode(&counters, self.encoding.clone(), &self.par)
+    }
+
+    /// Decode the counts of the given items from a plaintext of counters.
+    pub fn decode<T: AsRef<[u8]>>(&self, pt: &Plaintext, items: &[T]) -> Result<Vec<u64>> {
+        if pt.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        let counters = Vec::<u64>::try_decode(pt, self.encoding.clone())?;
+        Ok(items
+            .iter()
+            .map(|item| {
+                self.counters(item.as_ref())
+                    .into_iter()
+                    .map(|index| counters[index])
+                    .min()
+                    .unwrap_or(0)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollisionPolicy, CountingEncoding};
+    use crate::bfv::{BfvParameters, Ciphertext, PublicKey, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecrypter, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn encode_decode() -> Result<(), Error> {
+        let params = BfvParameters::default_arc(1, 16);
+        for policy in [
+            CollisionPolicy::Share,
+            CollisionPolicy::Reject,
+            CollisionPolicy::CountMin(3),
+        ] {
+            let encoding = CountingEncoding::new(&params, policy)?;
+            assert_eq!(encoding.policy(), policy);
+            let items: [&[u8]; 6] = [b"a", b"b", b"a", b"a", b"b", b"c"];
+            let pt = encoding.encode(items)?;
+            let reversed = encoding.encode(items.iter().rev())?;
+            assert_eq!(pt, reversed);
+
+            let counts = encoding.decode(&pt, &[b"a", b"b", b"c"])?;
+            if policy == CollisionPolicy::Reject {
+                assert_eq!(counts, [3, 2, 1]);
+            } else {
+                assert!(counts[0] >= 3 && counts[1] >= 2 && counts[2] >= 1);
+            }
+        }
+
+        assert!(CountingEncoding::new(&params, CollisionPolicy::CountMin(0)).is_err());
+        assert!(CountingEncoding::new(&params, CollisionPolicy::CountMin(17)).is_err());
+
+        // With 16 counters, 17 distinct items must collide.
+        let encoding = CountingEncoding::new(&params, CollisionPolicy::Reject)?;
+        let items = (0..17u8).map(|i| [i]).collect::<Vec<_>>();
+        assert!(encoding.encode(&items).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(1, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let encoding = CountingEncoding::new(&params, CollisionPolicy::CountMin(2))?;
+
+        let clients: [&[&[u8]]; 3] = [&[b"x", b"y"], &[b"x"], &[b"x", b"z", b"z"]];
+        let mut sum = Ciphertext::zero(&params);
+        for items in clients {
+            let ct: Ciphertext = pk.try_encrypt(&encoding.encode(items)?, &mut rng)?;
+            sum = &sum + &ct;
+        }
+        let counts = encoding.decode(&sk.try_decrypt(&sum)?, &[b"x", b"y", b"z"])?;
+        assert!(counts[0] >= 3 && counts[1] >= 1 && counts[2] >= 2);
+        Ok(())
+    }
+}
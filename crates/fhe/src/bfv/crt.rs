@@ -0,0 +1,398 @@
+//! Plaintext moduli larger than 62 bits, by the Chinese remainder theorem.
+//!
+//! The plaintext modulus of [`BfvParameters`] fits in 62 bits. For a larger
+//! plaintext modulus `t = t_1 * ... * t_k` with coprime factors `t_i`, the
+//! values modulo `t` are represented by their residues modulo each `t_i`, and
+//! each residue is processed by an independent BFV instance with the plaintext
+//! modulus `t_i`. The homomorphic operations are applied component-wise, and
+//! the values modulo `t` are recombined upon decoding.
+
+use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+use crate::{Error, Result};
+use core::ops::{Add, Mul, Neg, Sub};
+use fhe_math::rns::RnsContext;
+use fhe_traits::{
+    FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, FheParameters, FheParametrized,
+    FhePlaintext,
+};
+use itertools::{izip, Itertools};
+use ndarray::ArrayView1;
+use num_bigint::BigUint;
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Parameters with a composite plaintext modulus, the product of the
+/// coprime plaintext moduli of several BFV parameters.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrtParameters {
+    /// The BFV parameters of the components.
+    par: Vec<Arc<BfvParameters>>,
+
+    /// The RNS context of the plaintext moduli of the components.
+    rns: RnsContext,
+}
+
+impl CrtParameters {
+    /// Creates parameters whose plaintext modulus is the product of the
+    /// plaintext moduli of `par`.
+    ///
+    /// Returns an error if `par` is empty, if the parameters do not have the
+    /// same degree, or if their plaintext moduli are not pairwise coprime.
+    pub fn new(par: &[Arc<BfvParameters>]) -> Result<Arc<Self>> {
+        if par.is_empty() || par.iter().any(|p| p.degree() != par[0].degree()) {
+            return Err(Error::DefaultError(
+                "The parameters must be non-empty and have the same degree".to_string(),
+            ));
+        }
+        let plaintexts = par.iter().map(|p| p.plaintext()).collect_vec();
+        let rns = RnsContext::new(&plaintexts)?;
+        Ok(Arc::new(Self {
+            par: par.to_vec(),
+            rns,
+        }))
+    }
+
+    /// Returns the composite plaintext modulus.
+    pub fn plaintext(&self) -> &BigUint {
+        self.rns.modulus()
+    }
+
+    /// Returns the BFV parameters of the components.
+    pub fn parameters(&self) -> &[Arc<BfvParameters>] {
+        &self.par
+    }
+
+    /// Returns the degree of the polynomials.
+    pub fn degree(&self) -> usize {
+        self.par[0].degree()
+    }
+}
+
+impl FheParameters for CrtParameters {}
+
+/// A plaintext modulo the composite plaintext modulus of [`CrtParameters`],
+/// stored as one plaintext per component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrtPlaintext {
+    par: Arc<CrtParameters>,
+    pts: Vec<Plaintext>,
+}
+
+impl CrtPlaintext {
+    /// Returns the plaintexts of the components.
+    pub fn plaintexts(&self) -> &[Plaintext] {
+        &self.pts
+    }
+}
+
+impl FheParametrized for CrtPlaintext {
+    type Parameters = CrtParameters;
+}
+
+impl FhePlaintext for CrtPlaintext {
+    type Encoding = Encoding;
+}
+
+impl<'a> FheEncoder<&'a [BigUint]> for CrtPlaintext {
+    type Error = Error;
+
+    /// Encodes values modulo the composite plaintext modulus; the values are
+    /// reduced modulo the plaintext modulus.
+    fn try_encode(
+        value: &'a [BigUint],
+        encoding: Encoding,
+        par: &Arc<CrtParameters>,
+    ) -> Result<Self> {
+        if value.len() > par.degree() {
+            return Err(Error::TooManyValues(value.len(), par.degree()));
+        }
+        let mut residues = vec![vec![0u64; value.len()]; par.par.len()];
+        for (j, v) in value.iter().enumerate() {
+            for (residue, r) in izip!(residues.iter_mut(), par.rns.project(v)) {
+                residue[j] = r;
+            }
+        }
+        let pts = izip!(&residues, &par.par)
+            .map(|(residue, p)| Plaintext::try_encode(residue, encoding.clone(), p))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            par: par.clone(),
+            pts,
+        })
+    }
+}
+
+impl<'a> FheEncoder<&'a Vec<BigUint>> for CrtPlaintext {
+    type Error = Error;
+
+    fn try_encode(
+        value: &'a Vec<BigUint>,
+        encoding: Encoding,
+        par: &Arc<CrtParameters>,
+    ) -> Result<Self> {
+        CrtPlaintext::try_encode(value.as_slice(), encoding, par)
+    }
+}
+
+impl FheDecoder<CrtPlaintext> for Vec<BigUint> {
+    type Error = Error;
+
+    fn try_decode<O>(pt: &CrtPlaintext, encoding: O) -> Result<Vec<BigUint>>
+    where
+        O: Into<Option<Encoding>>,
+    {
+        let encoding = encoding.into();
+        let residues = pt
+            .pts
+            .iter()
+            .map(|pti| Vec::<u64>::try_decode(pti, encoding.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((0..pt.par.degree())
+            .map(|j| {
+                let column = residues.iter().map(|residue| residue[j]).collect_vec();
+                pt.par.rns.lift(ArrayView1::from(&column))
+            })
+            .collect())
+    }
+}
+
+/// A ciphertext encrypting a [`CrtPlaintext`], stored as one ciphertext per
+/// component.
+///
+/// The arithmetic operators apply to each component; as for [`Ciphertext`],
+/// the product of two ciphertexts is not relinearized, which can be done on
+/// each component with the relinearization key of its parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrtCiphertext {
+    par: Arc<CrtParameters>,
+    cts: Vec<Ciphertext>,
+}
+
+impl CrtCiphertext {
+    /// Creates a ciphertext from the ciphertexts of the components, which must
+    /// be defined with the parameters of the components of `par`.
+    pub fn from_ciphertexts(par: &Arc<CrtParameters>, cts: Vec<Ciphertext>) -> Result<Self> {
+        if cts.len() != par.par.len() || izip!(&cts, &par.par).any(|(ct, p)| &ct.par != p) {
+            return Err(Error::DefaultError(
+                "The ciphertexts do not match the parameters".to_string(),
+            ));
+        }
+        Ok(Self {
+            par: par.clone(),
+            cts,
+        })
+    }
+
+    /// Returns the ciphertexts of the components.
+    pub fn ciphertexts(&self) -> &[Ciphertext] {
+        &self.cts
+    }
+
+    /// Returns mutable references to the ciphertexts of the components, e.g.
+    /// to relinearize them.
+    pub fn ciphertexts_mut(&mut self) -> &mut [Ciphertext] {
+        &mut self.cts
+    }
+}
+
+impl FheParametrized for CrtCiphertext {
+    type Parameters = CrtParameters;
+}
+
+macro_rules! impl_componentwise {
+    ($trait:ident, $method:ident, $rhs:ty, $field:ident) => {
+        impl $trait<&$rhs> for &CrtCiphertext {
+            type Output = CrtCiphertext;
+
+            fn $method(self, rhs: &$rhs) -> CrtCiphertext {
+                assert_eq!(self.par, rhs.par);
+                CrtCiphertext {
+                    par: self.par.clone(),
+                    cts: izip!(&self.cts, &rhs.$field)
+                        .map(|(a, b)| a.$method(b))
+                        .collect(),
+                }
+            }
+        }
+    };
+}
+
+impl_componentwise!(Add, add, CrtCiphertext, cts);
+impl_componentwise!(Sub, sub, CrtCiphertext, cts);
+impl_componentwise!(Mul, mul, CrtCiphertext, cts);
+impl_componentwise!(Add, add, CrtPlaintext, pts);
+impl_componentwise!(Sub, sub, CrtPlaintext, pts);
+impl_componentwise!(Mul, mul, CrtPlaintext, pts);
+
+impl Neg for &CrtCiphertext {
+    type Output = CrtCiphertext;
+
+    fn neg(self) -> CrtCiphertext {
+        CrtCiphertext {
+            par: self.par.clone(),
+            cts: self.cts.iter().map(|ct| -ct).collect(),
+        }
+    }
+}
+
+/// A secret key for [`CrtParameters`], made of one secret key per component.
+#[derive(Debug, PartialEq)]
+pub struct CrtSecretKey {
+    par: Arc<CrtParameters>,
+    sk: Vec<SecretKey>,
+}
+
+impl FheParametrized for CrtSecretKey {
+    type Parameters = CrtParameters;
+}
+
+impl CrtSecretKey {
+    /// Generates a random secret key.
+    pub fn random<R: RngCore + CryptoRng>(par: &Arc<CrtParameters>, rng: &mut R) -> Self {
+        Self {
+            par: par.clone(),
+            sk: par.par.iter().map(|p| SecretKey::random(p, rng)).collect(),
+        }
+    }
+
+    /// Returns the secret keys of the components, e.g. to generate their
+    /// relinearization or evaluation keys.
+    pub fn secret_keys(&self) -> &[SecretKey] {
+        &self.sk
+    }
+
+    /// Encrypts a plaintext, component-wise.
+    pub fn try_encrypt<R: RngCore + CryptoRng>(
+        &self,
+        pt: &CrtPlaintext,
+        rng: &mut R,
+    ) -> Result<CrtCiphertext> {
+        if pt.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible CRT parameters".to_string(),
+            ));
+        }
+        let cts = izip!(&self.sk, &pt.pts)
+            .map(|(sk, pt)| FheEncrypter::<Plaintext, Ciphertext>::try_encrypt(sk, pt, rng))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(CrtCiphertext {
+            par: self.par.clone(),
+            cts,
+        })
+    }
+
+    /// Decrypts a ciphertext, component-wise.
+    pub fn try_decrypt(&self, ct: &CrtCiphertext) -> Result<CrtPlaintext> {
+        if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible CRT parameters".to_string(),
+            ));
+        }
+        let pts = izip!(&self.sk, &ct.cts)
+            .map(|(sk, ct)| sk.try_decrypt(ct))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(CrtPlaintext {
+            par: self.par.clone(),
+            pts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrtCiphertext, CrtParameters, CrtPlaintext, CrtSecretKey};
+    use crate::bfv::{BfvParameters, BfvParametersBuilder, Encoding, RelinearizationKey};
+    use crate::Error;
+    use fhe_math::zq::primes::generate_prime;
+    use fhe_traits::{FheDecoder, FheEncoder};
+    use num_bigint::BigUint;
+    use num_traits::One;
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn crt_plaintext_modulus() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let p1 = generate_prime(50, 32, 1 << 50).unwrap();
+        let p2 = generate_prime(50, 32, p1).unwrap();
+        let par = [p1, p2]
+            .iter()
+            .map(|t| {
+                BfvParametersBuilder::new()
+                    .set_degree(16)
+                    .set_plaintext_modulus(*t)
+                    .set_moduli_sizes(&[62; 5])
+                    .allow_insecure()
+                    .build_arc()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let crt = CrtParameters::new(&par)?;
+        let t = crt.plaintext().clone();
+        assert!(t.bits() > 62);
+
+        let random = |rng: &mut rand::rngs::ThreadRng| {
+            (0..crt.degree())
+                .map(|_| BigUint::from(rng.gen::<u128>()) % &t)
+                .collect::<Vec<_>>()
+        };
+        let a = random(&mut rng);
+        let b = random(&mut rng);
+
+        let sk = CrtSecretKey::random(&crt, &mut rng);
+        for encoding in [Encoding::poly(), Encoding::simd()] {
+            let pt_a = CrtPlaintext::try_encode(&a, encoding.clone(), &crt)?;
+            let pt_b = CrtPlaintext::try_encode(&b, encoding.clone(), &crt)?;
+            assert_eq!(Vec::<BigUint>::try_decode(&pt_a, encoding.clone())?, a);
+
+            let ct_a = sk.try_encrypt(&pt_a, &mut rng)?;
+            let ct_b = sk.try_encrypt(&pt_b, &mut rng)?;
+            let decrypt = |ct: &CrtCiphertext| -> Result<Vec<BigUint>, Error> {
+                Vec::<BigUint>::try_decode(&sk.try_decrypt(ct)?, encoding.clone())
+            };
+            assert_eq!(decrypt(&ct_a)?, a);
+            let sum = a
+                .iter()
+                .zip(&b)
+                .map(|(x, y)| (x + y) % &t)
+                .collect::<Vec<_>>();
+            assert_eq!(decrypt(&(&ct_a + &ct_b))?, sum);
+            assert_eq!(decrypt(&(&ct_a + &pt_b))?, sum);
+            let difference = a
+                .iter()
+                .zip(&b)
+                .map(|(x, y)| (x + &t - y) % &t)
+                .collect::<Vec<_>>();
+            assert_eq!(decrypt(&(&ct_a - &ct_b))?, difference);
+            assert_eq!(decrypt(&(&ct_a - &pt_b))?, difference);
+            let negation = a.iter().map(|x| (&t - x) % &t).collect::<Vec<_>>();
+            assert_eq!(decrypt(&-&ct_a)?, negation);
+
+            if encoding == Encoding::simd() {
+                let product = a
+                    .iter()
+                    .zip(&b)
+                    .map(|(x, y)| x * y % &t)
+                    .collect::<Vec<_>>();
+                assert_eq!(decrypt(&(&ct_a * &pt_b))?, product);
+                let mut ct = &ct_a * &ct_b;
+                for (ct, sk) in ct.ciphertexts_mut().iter_mut().zip(sk.secret_keys()) {
+                    RelinearizationKey::new(sk, &mut rng)?.relinearizes(ct)?;
+                }
+                assert_eq!(decrypt(&ct)?, product);
+            }
+        }
+
+        let one = CrtPlaintext::try_encode(&[&t + BigUint::one()][..], Encoding::poly(), &crt)?;
+        assert!(Vec::<BigUint>::try_decode(&one, Encoding::poly())?[0].is_one());
+
+        assert!(CrtParameters::new(&[]).is_err());
+        assert!(CrtParameters::new(&[par[0].clone(), par[0].clone()]).is_err());
+        assert!(CrtParameters::new(&[par[0].clone(), BfvParameters::default_arc(1, 32)]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+//! Limits applied when deserializing untrusted objects.
+
+use crate::{Error, Result};
+use fhe_math::rq::Poly;
+
+/// Limits and options for the deserialization of ciphertexts and keys, which
+/// may come from an untrusted party.
+///
+/// The default configuration accepts serializations of at most 1 GiB with at
+/// most 4096 polynomials, and makes all the deserialized polynomials use
+/// constant-time computations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializationConfig {
+    /// The maximum number of polynomials of the object, including the
+    /// polynomials expanded from a seed.
+    pub max_polys: usize,
+    /// The maximum length of the serialization, in bytes.
+    pub max_bytes: usize,
+    /// Whether the deserialized polynomials may use variable-time
+    /// computations, as recorded in the serialization and, for the
+    /// polynomials expanded from a seed, as when they are generated.
+    /// Otherwise, all the polynomials use constant-time computations.
+    pub allow_vt: bool,
+}
+
+impl Default for DeserializationConfig {
+    fn default() -> Self {
+        Self {
+            max_polys: 1 << 12,
+            max_bytes: 1 << 30,
+            allow_vt: false,
+        }
+    }
+}
+
+impl DeserializationConfig {
+    /// The configuration used by the deserialization of key switching keys
+    /// without an explicit configuration: their polynomials are public and
+    /// generated with variable-time computations, which is kept.
+    pub(crate) fn keys() -> Self {
+        Self {
+            allow_vt: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns an error if a serialization of `len` bytes exceeds the limit.
+    pub(crate) fn check_bytes(&self, len: usize) -> Result<()> {
+        if len > self.max_bytes {
+            return Err(Error::TooManyValues(len, self.max_bytes));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if an object of `count` polynomials exceeds the limit.
+    pub(crate) fn check_polys(&self, count: usize) -> Result<()> {
+        if count > self.max_polys {
+            return Err(Error::TooManyValues(count, self.max_polys));
+        }
+        Ok(())
+    }
+
+    /// Makes the deserialized polynomial use constant-time computations,
+    /// unless variable-time computations are allowed.
+    pub(crate) fn apply_vt(&self, p: &mut Poly) {
+        if !self.allow_vt {
+            p.disallow_variable_time_computations()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeserializationConfig;
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKey, EvaluationKeyBuilder, Plaintext,
+        PublicKey, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{DeserializeParametrized, FheEncrypter, Serialize};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn limits() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ct: Ciphertext =
+            sk.try_encrypt(&Plaintext::zero(Encoding::poly(), &params)?, &mut rng)?;
+        let bytes = ct.to_bytes();
+        let product = &ct * &ct;
+
+        // By default, the polynomials of a deserialized ciphertext use
+        // constant-time computations, including the one expanded from a seed.
+        let default = DeserializationConfig::default();
+        let parsed = Ciphertext::from_bytes(&bytes, &params)?;
+        assert_eq!(parsed, ct);
+        assert!(parsed
+            .iter()
+            .all(|p| !p.allows_variable_time_computations()));
+        let vt = DeserializationConfig {
+            allow_vt: true,
+            ..default
+        };
+        let parsed = Ciphertext::from_bytes_with_config(&bytes, &params, &vt)?;
+        assert!(parsed[1].allows_variable_time_computations());
+
+        let two_polys = DeserializationConfig {
+            max_polys: 2,
+            ..default
+        };
+        assert!(Ciphertext::from_bytes_with_config(&bytes, &params, &two_polys).is_ok());
+        assert!(
+            Ciphertext::from_bytes_with_config(&product.to_bytes(), &params, &two_polys).is_err()
+        );
+        let mut stream = Vec::new();
+        product.write_to(&mut stream)?;
+        assert!(Ciphertext::read_from_with_config(&mut &stream[..], &params, &two_polys).is_err());
+        let few_bytes = DeserializationConfig {
+            max_bytes: bytes.len() - 1,
+            ..default
+        };
+        assert!(Ciphertext::from_bytes_with_config(&bytes, &params, &few_bytes).is_err());
+
+        let pk = PublicKey::new(&sk, &mut rng);
+        assert_eq!(
+            PublicKey::from_bytes_with_config(&pk.to_bytes(), &params, &default)?,
+            pk
+        );
+        assert!(PublicKey::from_bytes_with_config(&pk.to_bytes(), &params, &few_bytes).is_err());
+
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        assert_eq!(RelinearizationKey::from_bytes(&rk.to_bytes(), &params)?, rk);
+        let parsed = RelinearizationKey::from_bytes_with_config(&rk.to_bytes(), &params, &default)?;
+        assert!(parsed
+            .ksk
+            .c0
+            .iter()
+            .chain(parsed.ksk.c1.iter())
+            .all(|p| !p.allows_variable_time_computations()));
+        assert!(
+            RelinearizationKey::from_bytes_with_config(&rk.to_bytes(), &params, &two_polys)
+                .is_err()
+        );
+
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(1)?
+            .enable_row_rotation()?
+            .build(&mut rng)?;
+        assert_eq!(EvaluationKey::from_bytes(&ek.to_bytes(), &params)?, ek);
+        // The limit applies to the Galois keys together.
+        let one_key = DeserializationConfig {
+            max_polys: 2 * rk.ksk.c0.len(),
+            ..default
+        };
+        assert!(EvaluationKey::from_bytes_with_config(&ek.to_bytes(), &params, &one_key).is_err());
+        Ok(())
+    }
+}
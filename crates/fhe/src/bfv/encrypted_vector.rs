@@ -0,0 +1,157 @@
+//! Encryption of vectors of arbitrary length across several ciphertexts.
+
+extern crate alloc;
+
+use crate::bfv::{Ciphertext, Encoding, Plaintext, PublicKey, SecretKey};
+use crate::{Error, Result};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncrypter};
+use rand::{CryptoRng, RngCore};
+
+/// A vector of values of arbitrary length encrypted across as many ciphertexts
+/// as needed.
+///
+/// The values are split in chunks of `degree` values, the last chunk being
+/// padded with zeros; the length of the original vector is recorded so that
+/// the padding can be removed upon decryption.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedVector {
+    /// The ciphertexts, each encrypting a chunk of the vector.
+    ciphertexts: Vec<Ciphertext>,
+
+    /// The number of values in the original vector.
+    len: usize,
+
+    /// The encoding of the chunks.
+    encoding: Encoding,
+}
+
+impl EncryptedVector {
+    /// Returns the number of values in the encrypted vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the encrypted vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of padding values in the last ciphertext.
+    pub fn padding(&self) -> usize {
+        self.ciphertexts.len() * self.ciphertexts[0].par.degree() - self.len
+    }
+
+    /// Returns the encoding of the chunks.
+    pub fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+
+    /// Returns the ciphertexts encrypting the chunks of the vector.
+    pub fn ciphertexts(&self) -> &[Ciphertext] {
+        &self.ciphertexts
+    }
+}
+
+/// Encrypt a vector of arbitrary length under a public key.
+///
+/// The values are encoded in SIMD slots if the parameters support it, and as
+/// polynomial coefficients otherwise. An empty vector is encrypted as a single
+/// ciphertext of zero.
+pub fn encrypt_vector<R: RngCore + CryptoRng>(
+    pk: &PublicKey,
+    values: &[u64],
+    rng: &mut R,
+) -> Result<EncryptedVector> {
+    encrypt_vector_from_iter(pk, values.iter().copied(), rng)
+}
+
+/// Encrypt the values produced by an iterator under a public key, as
+/// [`encrypt_vector`] does, without collecting them first.
+///
+/// The values are encoded and encrypted `degree` at a time, so that only one
+/// chunk of values is in memory at once, e.g. when encrypting values read from
+/// a stream.
+pub fn encrypt_vector_from_iter<I, R>(
+    pk: &PublicKey,
+    mut values: I,
+    rng: &mut R,
+) -> Result<EncryptedVector>
+where
+    I: ExactSizeIterator<Item = u64>,
+    R: RngCore + CryptoRng,
+{
+    let par = &pk.par;
+    let encoding = if par.op.is_some() {
+        Encoding::simd()
+    } else {
+        Encoding::poly()
+    };
+    let len = values.len();
+    let ciphertexts = (0..len.div_ceil(par.degree()).max(1))
+        .map(|_| {
+            let pt = Plaintext::try_encode_from_iter(
+                values.by_ref().take(par.degree()),
+                encoding.clone(),
+                par,
+            )?;
+            pk.try_encrypt(&pt, rng)
+        })
+        .collect::<Result<Vec<Ciphertext>>>()?;
+    Ok(EncryptedVector {
+        ciphertexts,
+        len,
+        encoding,
+    })
+}
+
+/// Decrypt a vector encrypted with [`encrypt_vector`], returning exactly the
+/// original values.
+pub fn decrypt_vector(sk: &SecretKey, ev: &EncryptedVector) -> Result<Vec<u64>> {
+    let degree = sk.par.degree();
+    if ev.ciphertexts.is_empty() || ev.len > ev.ciphertexts.len() * degree {
+        return Err(Error::UnspecifiedInput(
+            "Invalid encrypted vector length".to_string(),
+        ));
+    }
+    let mut values = Vec::with_capacity(ev.ciphertexts.len() * degree);
+    for ct in &ev.ciphertexts {
+        let pt = sk.try_decrypt(ct)?;
+        values.extend(Vec::<u64>::try_decode(&pt, ev.encoding.clone())?);
+    }
+    values.truncate(ev.len);
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_vector, encrypt_vector, encrypt_vector_from_iter};
+    use crate::bfv::{BfvParameters, PublicKey, SecretKey};
+    use crate::Error;
+    use rand::thread_rng;
+
+    #[test]
+    fn encrypt_decrypt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(1, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+
+        for len in [0, 1, 15, 16, 17, 32, 33, 100] {
+            let values = params.plaintext.random_vec(len, &mut rng);
+            let ev = encrypt_vector(&pk, &values, &mut rng)?;
+            assert_eq!(ev.len(), len);
+            assert_eq!(ev.is_empty(), len == 0);
+            assert_eq!(ev.ciphertexts().len(), len.div_ceil(params.degree()).max(1));
+            assert_eq!(ev.padding(), ev.ciphertexts().len() * params.degree() - len);
+            assert_eq!(decrypt_vector(&sk, &ev)?, values);
+
+            let ev = encrypt_vector_from_iter(&pk, values.iter().copied(), &mut rng)?;
+            assert_eq!(ev.len(), len);
+            assert_eq!(ev.ciphertexts().len(), len.div_ceil(params.degree()).max(1));
+            assert_eq!(decrypt_vector(&sk, &ev)?, values);
+        }
+        Ok(())
+    }
+}
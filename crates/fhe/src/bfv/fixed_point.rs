@@ -0,0 +1,344 @@
+//! Fixed-point encoding of rational numbers.
+//!
+//! A rational `x` is encoded with the scale `f` as the integer closest to
+//! `x * 2^f`, modulo the plaintext modulus `t`, and the scale is tracked
+//! alongside the plaintexts and ciphertexts. The sum of two values encoded
+//! with the same scale has the same scale, and their product has the sum of
+//! the scales; a value is decoded by centering it modulo `t` and dividing it
+//! by `2^f`. The encoded integers must remain smaller than `t / 2` in absolute
+//! value, which is not checked on ciphertexts: the plaintext modulus bounds
+//! the precision and the number of products before a rescaling.
+//!
+//! For an odd plaintext modulus, [`FixedPointCiphertext::rescale_by_plain`]
+//! divides the encoded integers by `2^k` by multiplying them by the inverse of
+//! `2^k` modulo `t`. This division is exact, and therefore correct, only when
+//! the encoded integers are multiples of `2^k`, e.g. when an operand of a
+//! product encodes values with at least `k` trailing zero bits; there is no
+//! homomorphic rounding as in CKKS.
+
+use crate::bfv::{BfvParameters, Ciphertext, Encoding, Multiplicator, Plaintext, SecretKey};
+use crate::{Error, Result};
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, FheParametrized};
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// A plaintext encoding rationals with a fixed-point scale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedPointPlaintext {
+    /// The plaintext of the encoded integers.
+    pt: Plaintext,
+
+    /// The number of fractional bits of the encoded integers.
+    scale: u32,
+
+    /// The encoding of the integers in the plaintext.
+    encoding: Encoding,
+}
+
+impl FixedPointPlaintext {
+    /// Encodes the rationals `values` with `scale` fractional bits.
+    ///
+    /// Returns an error if a value is not finite, or if its encoding is not
+    /// smaller than half the plaintext modulus in absolute value.
+    pub fn try_encode(
+        values: &[f64],
+        scale: u32,
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        if scale >= 63 {
+            return Err(Error::UnspecifiedInput(
+                "The scale must be smaller than 63".to_string(),
+            ));
+        }
+        let bound = (par.plaintext() / 2) as i64;
+        let factor = (1u64 << scale) as f64;
+        let integers = values
+            .iter()
+            .map(|x| {
+                let y = x * factor;
+                // Round to the nearest integer, as `f64::round` requires `std`.
+                let y = if y < 0.0 { y - 0.5 } else { y + 0.5 };
+                if !y.is_finite() || y <= -(bound as f64) - 1.0 || y >= bound as f64 + 1.0 {
+                    return Err(Error::UnspecifiedInput(
+                        "The value is too large for the plaintext modulus".to_string(),
+                    ));
+                }
+                Ok(y as i64)
+            })
+            .collect::<Result<Vec<i64>>>()?;
+        let pt = Plaintext::try_encode(&integers, encoding.clone(), par)?;
+        Ok(Self {
+            pt,
+            scale,
+            encoding,
+        })
+    }
+
+    /// Decodes the rationals encoded in the plaintext.
+    pub fn try_decode(&self) -> Result<Vec<f64>> {
+        decode(&self.pt, self.scale, &self.encoding)
+    }
+
+    /// Returns the number of fractional bits of the encoded integers.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the plaintext of the encoded integers.
+    pub fn plaintext(&self) -> &Plaintext {
+        &self.pt
+    }
+}
+
+/// A ciphertext encrypting rationals with a fixed-point scale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedPointCiphertext {
+    /// The ciphertext of the encoded integers.
+    ct: Ciphertext,
+
+    /// The number of fractional bits of the encoded integers.
+    scale: u32,
+
+    /// The encoding of the integers in the plaintext.
+    encoding: Encoding,
+}
+
+impl FixedPointCiphertext {
+    /// Encrypts a fixed-point plaintext under a secret or a public key.
+    pub fn try_encrypt<K, R>(pt: &FixedPointPlaintext, key: &K, rng: &mut R) -> Result<Self>
+    where
+        K: FheParametrized<Parameters = BfvParameters>
+            + FheEncrypter<Plaintext, Ciphertext, Error = Error>,
+        R: RngCore + CryptoRng,
+    {
+        Ok(Self {
+            ct: key.try_encrypt(&pt.pt, rng)?,
+            scale: pt.scale,
+            encoding: pt.encoding.clone(),
+        })
+    }
+
+    /// Decrypts the ciphertext and decodes the rationals.
+    pub fn try_decrypt(&self, sk: &SecretKey) -> Result<Vec<f64>> {
+        decode(&sk.try_decrypt(&self.ct)?, self.scale, &self.encoding)
+    }
+
+    /// Returns the number of fractional bits of the encrypted integers.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the ciphertext of the encoded integers.
+    pub fn ciphertext(&self) -> &Ciphertext {
+        &self.ct
+    }
+
+    /// Adds two fixed-point ciphertexts. The operand with the smaller scale
+    /// is first multiplied by a power of two, so that the sum has the larger
+    /// scale.
+    pub fn add(&self, rhs: &FixedPointCiphertext) -> Result<FixedPointCiphertext> {
+        if self.ct.par != rhs.ct.par || self.encoding.encoding != rhs.encoding.encoding {
+            return Err(Error::DefaultError(
+                "Mismatched parameters or encodings".to_string(),
+            ));
+        }
+        let scale = self.scale.max(rhs.scale);
+        Ok(Self {
+            ct: &self.upscale(scale)? + &rhs.upscale(scale)?,
+            scale,
+            encoding: self.encoding.clone(),
+        })
+    }
+
+    /// Multiplies two fixed-point ciphertexts with a `multiplicator`, which
+    /// operates at the level of the ciphertexts. The scale of the product is
+    /// the sum of the scales, and must be smaller than 63.
+    pub fn mul(
+        &self,
+        rhs: &FixedPointCiphertext,
+        multiplicator: &Multiplicator,
+    ) -> Result<FixedPointCiphertext> {
+        if self.encoding.encoding != rhs.encoding.encoding {
+            return Err(Error::DefaultError("Mismatched encodings".to_string()));
+        }
+        let scale = product_scale(self.scale, rhs.scale)?;
+        Ok(Self {
+            ct: multiplicator.multiply(&self.ct, &rhs.ct)?,
+            scale,
+            encoding: self.encoding.clone(),
+        })
+    }
+
+    /// Multiplies a fixed-point ciphertext by a fixed-point plaintext at the
+    /// same level. The scale of the product is the sum of the scales, and must
+    /// be smaller than 63.
+    pub fn mul_plain(&self, rhs: &FixedPointPlaintext) -> Result<FixedPointCiphertext> {
+        if self.ct.par != rhs.pt.par || self.ct.level != rhs.pt.level {
+            return Err(Error::DefaultError(
+                "Mismatched parameters or levels".to_string(),
+            ));
+        }
+        if self.encoding.encoding != rhs.encoding.encoding {
+            return Err(Error::DefaultError("Mismatched encodings".to_string()));
+        }
+        let scale = product_scale(self.scale, rhs.scale)?;
+        Ok(Self {
+            ct: &self.ct * &rhs.pt,
+            scale,
+            encoding: self.encoding.clone(),
+        })
+    }
+
+    /// Divides the encrypted integers by `2^k`, which reduces the scale by
+    /// `k` without changing the encrypted rationals.
+    ///
+    /// The plaintext modulus must be odd and `k` at most the scale. The
+    /// division multiplies by the inverse of `2^k` modulo the plaintext
+    /// modulus, and is only correct if the encrypted integers are multiples of
+    /// `2^k`; otherwise, the result decrypts to unrelated values.
+    pub fn rescale_by_plain(&self, k: u32) -> Result<FixedPointCiphertext> {
+        if k > self.scale {
+            return Err(Error::UnspecifiedInput(
+                "The rescaling exceeds the scale".to_string(),
+            ));
+        }
+        let t = &self.ct.par.plaintext;
+        let inverse = t
+            .inv(t.pow(2, k as u64))
+            .ok_or_else(|| Error::DefaultError("The plaintext modulus must be odd".to_string()))?;
+        Ok(Self {
+            ct: &self.ct * &constant(&self.ct, inverse)?,
+            scale: self.scale - k,
+            encoding: self.encoding.clone(),
+        })
+    }
+
+    /// Returns the ciphertext of the encrypted integers with the larger
+    /// `scale`.
+    fn upscale(&self, scale: u32) -> Result<Ciphertext> {
+        if scale == self.scale {
+            return Ok(self.ct.clone());
+        }
+        let t = &self.ct.par.plaintext;
+        let factor = t.pow(2, (scale - self.scale) as u64);
+        Ok(&self.ct * &constant(&self.ct, factor)?)
+    }
+}
+
+/// Returns the scale of a product, i.e. the sum of the scales of its operands,
+/// or an error if it is not smaller than 63 as in
+/// [`FixedPointPlaintext::try_encode`].
+fn product_scale(lhs: u32, rhs: u32) -> Result<u32> {
+    match lhs.checked_add(rhs) {
+        Some(scale) if scale < 63 => Ok(scale),
+        _ => Err(Error::UnspecifiedInput(
+            "The scale of the product must be smaller than 63".to_string(),
+        )),
+    }
+}
+
+/// Returns the plaintext encoding the constant `c` at the level of `ct`.
+fn constant(ct: &Ciphertext, c: u64) -> Result<Plaintext> {
+    Plaintext::try_encode(&[c], Encoding::poly_at_level(ct.level), &ct.par)
+}
+
+/// Decodes the rationals encoded with `scale` fractional bits in `pt`.
+fn decode(pt: &Plaintext, scale: u32, encoding: &Encoding) -> Result<Vec<f64>> {
+    let integers = Vec::<i64>::try_decode(pt, encoding.clone())?;
+    Ok(integers
+        .iter()
+        .map(|v| libm::ldexp(*v as f64, -(scale as i32)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedPointCiphertext, FixedPointPlaintext};
+    use crate::bfv::{
+        BfvParametersBuilder, Encoding, Multiplicator, PublicKey, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use rand::thread_rng;
+
+    #[test]
+    fn fixed_point() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1032193)
+            .set_moduli_sizes(&[62; 4])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let multiplicator = Multiplicator::default(&RelinearizationKey::new(&sk, &mut rng)?)?;
+
+        let x = [1.5, -2.25, 0.125, 10.0];
+        let y = [0.5, 3.0, -4.0, -0.5];
+        let pt_x = FixedPointPlaintext::try_encode(&x, 4, Encoding::simd(), &params)?;
+        assert_eq!(pt_x.scale(), 4);
+        assert_eq!(pt_x.try_decode()?[..4], x);
+        // The values are rounded to the scale.
+        let rounded = FixedPointPlaintext::try_encode(&[0.3, -0.3], 2, Encoding::poly(), &params)?;
+        assert_eq!(rounded.try_decode()?[..2], [0.25, -0.25]);
+
+        let ct_x = FixedPointCiphertext::try_encrypt(&pt_x, &sk, &mut rng)?;
+        let pt_y = FixedPointPlaintext::try_encode(&y, 2, Encoding::simd(), &params)?;
+        let ct_y = FixedPointCiphertext::try_encrypt(&pt_y, &pk, &mut rng)?;
+        assert_eq!(ct_x.try_decrypt(&sk)?[..4], x);
+
+        let sum = ct_x.add(&ct_y)?;
+        assert_eq!(sum.scale(), 4);
+        assert_eq!(sum.try_decrypt(&sk)?[..4], [2.0, 0.75, -3.875, 9.5]);
+
+        let product = ct_x.mul(&ct_y, &multiplicator)?;
+        assert_eq!(product.scale(), 6);
+        let expected = [0.75, -6.75, -0.5, -5.0];
+        assert_eq!(product.try_decrypt(&sk)?[..4], expected);
+        assert_eq!(ct_x.mul_plain(&pt_y)?.try_decrypt(&sk)?[..4], expected);
+
+        // The integers of y are multiples of 2^1, and so are those of the
+        // product: the rescaling by 2^1 is exact.
+        let rescaled = product.rescale_by_plain(1)?;
+        assert_eq!(rescaled.scale(), 5);
+        assert_eq!(rescaled.try_decrypt(&sk)?[..4], expected);
+        assert!(product.rescale_by_plain(7).is_err());
+
+        assert!(FixedPointPlaintext::try_encode(&[1e6], 0, Encoding::simd(), &params).is_err());
+        assert!(
+            FixedPointPlaintext::try_encode(&[f64::NAN], 0, Encoding::simd(), &params).is_err()
+        );
+        let pt_poly = FixedPointPlaintext::try_encode(&x, 4, Encoding::poly(), &params)?;
+        assert!(ct_x.mul_plain(&pt_poly).is_err());
+
+        // The scale of a product must remain smaller than 63.
+        let high = [libm::ldexp(1.0, -20), libm::ldexp(-3.0, -18)];
+        let pt_high = FixedPointPlaintext::try_encode(&high, 32, Encoding::simd(), &params)?;
+        let ct_high = FixedPointCiphertext::try_encrypt(&pt_high, &sk, &mut rng)?;
+        assert_eq!(ct_high.try_decrypt(&sk)?[..2], high);
+        assert!(ct_high.mul(&ct_high, &multiplicator).is_err());
+        assert!(ct_high.mul_plain(&pt_high).is_err());
+        let pt_max = FixedPointPlaintext::try_encode(&[0.0], 30, Encoding::simd(), &params)?;
+        let ct_max = FixedPointCiphertext::try_encrypt(&pt_max, &sk, &mut rng)?;
+        let product = ct_high.mul(&ct_max, &multiplicator)?;
+        assert_eq!(product.scale(), 62);
+        assert_eq!(product.try_decrypt(&sk)?[..2], [0.0, 0.0]);
+        assert!(FixedPointPlaintext::try_encode(&[0.0], 63, Encoding::simd(), &params).is_err());
+
+        let even = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1024)
+            .set_moduli_sizes(&[62; 2])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&even, &mut rng);
+        let pt = FixedPointPlaintext::try_encode(&[1.0; 16], 2, Encoding::poly(), &even)?;
+        let ct = FixedPointCiphertext::try_encrypt(&pt, &sk, &mut rng)?;
+        assert!(ct.rescale_by_plain(1).is_err());
+        Ok(())
+    }
+}
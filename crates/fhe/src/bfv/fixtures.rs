@@ -0,0 +1,292 @@
+//! Canonical parameter sets, keys and workloads for benchmarking.
+//!
+//! The parameter sets have the degrees 4096, 8192 and 16384 and the default
+//! ciphertext moduli of Microsoft SEAL, which provide 128 bits of security
+//! according to the <https://homomorphicencryption.org> standard, with the
+//! largest 20-bit plaintext modulus supporting the SIMD encoding, as selected
+//! by `PlainModulus::Batching(n, 20)`. The keys and the inputs are derived from
+//! the fixed [`SEED`], so that the fixtures are identical across runs and
+//! versions of this crate, and the [`Workload`]s are the operations reported
+//! by the benchmarks of other libraries.
+//!
+//! This module requires the `bench-fixtures` feature.
+
+use crate::bfv::{
+    BfvParameters, Ciphertext, Encoding, EvaluationKey, EvaluationKeyBuilder, Plaintext, PublicKey,
+    RelinearizationKey, SecretKey,
+};
+use crate::{Error, Result};
+use core::hint::black_box;
+use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// The seed from which the keys and the inputs of the fixtures are derived.
+pub const SEED: [u8; 32] = *b"fhe.rs canonical benchmark seed!";
+
+/// The number of bits of the plaintext modulus of the parameter sets.
+pub const PLAINTEXT_NBITS: usize = 20;
+
+/// A canonical parameter set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterSet {
+    /// Degree 4096 and a 109-bit ciphertext modulus.
+    N4096,
+    /// Degree 8192 and a 218-bit ciphertext modulus.
+    N8192,
+    /// Degree 16384 and a 438-bit ciphertext modulus.
+    N16384,
+}
+
+impl ParameterSet {
+    /// All the parameter sets, by increasing degree.
+    pub const ALL: [ParameterSet; 3] = [Self::N4096, Self::N8192, Self::N16384];
+
+    /// Returns the degree of the parameter set.
+    pub const fn degree(&self) -> usize {
+        match self {
+            Self::N4096 => 4096,
+            Self::N8192 => 8192,
+            Self::N16384 => 16384,
+        }
+    }
+
+    /// Returns the BFV parameters of this set.
+    pub fn parameters(&self) -> Arc<BfvParameters> {
+        BfvParameters::default_parameters_128(PLAINTEXT_NBITS)
+            .into_iter()
+            .find(|par| par.degree() == self.degree())
+            .unwrap()
+    }
+}
+
+/// A standard workload, i.e. an operation timed by the benchmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Encode a vector with the SIMD encoding.
+    EncodeSimd,
+    /// Encrypt a plaintext with the secret key.
+    EncryptSecretKey,
+    /// Encrypt a plaintext with the public key.
+    EncryptPublicKey,
+    /// Decrypt a ciphertext.
+    Decrypt,
+    /// Add two ciphertexts.
+    Add,
+    /// Multiply a ciphertext by a plaintext.
+    MultiplyPlain,
+    /// Multiply two ciphertexts, without relinearization.
+    Multiply,
+    /// Relinearize the product of two ciphertexts.
+    Relinearize,
+    /// Rotate the columns by one step.
+    RotateColumns,
+    /// Rotate the rows.
+    RotateRows,
+    /// Switch a ciphertext to the next level.
+    ModSwitch,
+}
+
+impl Workload {
+    /// All the workloads.
+    pub const ALL: [Workload; 11] = [
+        Self::EncodeSimd,
+        Self::EncryptSecretKey,
+        Self::EncryptPublicKey,
+        Self::Decrypt,
+        Self::Add,
+        Self::MultiplyPlain,
+        Self::Multiply,
+        Self::Relinearize,
+        Self::RotateColumns,
+        Self::RotateRows,
+        Self::ModSwitch,
+    ];
+
+    /// Returns the name of the workload, which is stable across versions.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::EncodeSimd => "encode_simd",
+            Self::EncryptSecretKey => "encrypt_sk",
+            Self::EncryptPublicKey => "encrypt_pk",
+            Self::Decrypt => "decrypt",
+            Self::Add => "add",
+            Self::MultiplyPlain => "multiply_plain",
+            Self::Multiply => "multiply",
+            Self::Relinearize => "relinearize",
+            Self::RotateColumns => "rotate_columns",
+            Self::RotateRows => "rotate_rows",
+            Self::ModSwitch => "mod_switch",
+        }
+    }
+}
+
+/// The parameters, keys and inputs of a parameter set, derived from the
+/// [`SEED`].
+#[derive(Debug)]
+pub struct Fixture {
+    /// The parameter set.
+    pub set: ParameterSet,
+    /// The BFV parameters.
+    pub par: Arc<BfvParameters>,
+    /// The secret key.
+    pub sk: SecretKey,
+    /// The public key.
+    pub pk: PublicKey,
+    /// The relinearization key.
+    pub rk: RelinearizationKey,
+    /// The evaluation key, rotating the columns by one step and the rows.
+    pub ek: EvaluationKey,
+    /// The vectors encoded in the plaintexts.
+    pub values: [Vec<u64>; 2],
+    /// Two plaintexts with the SIMD encoding.
+    pub pt: [Plaintext; 2],
+    /// The encryptions of the plaintexts under the secret key.
+    pub ct: [Ciphertext; 2],
+    /// The product of the ciphertexts, before relinearization.
+    pub product: Ciphertext,
+}
+
+impl Fixture {
+    /// Generates the fixture of a parameter set.
+    pub fn new(set: ParameterSet) -> Result<Self> {
+        let mut rng = ChaCha8Rng::from_seed(SEED);
+        let par = set.parameters();
+        let sk = SecretKey::random(&par, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(1)?
+            .enable_row_rotation()?
+            .build(&mut rng)?;
+        let values = [
+            par.plaintext.random_vec(par.degree(), &mut rng),
+            par.plaintext.random_vec(par.degree(), &mut rng),
+        ];
+        let pt = [
+            Plaintext::try_encode(&values[0], Encoding::simd(), &par)?,
+            Plaintext::try_encode(&values[1], Encoding::simd(), &par)?,
+        ];
+        let ct: [Ciphertext; 2] = [
+            sk.try_encrypt(&pt[0], &mut rng)?,
+            sk.try_encrypt(&pt[1], &mut rng)?,
+        ];
+        let product = &ct[0] * &ct[1];
+        Ok(Self {
+            set,
+            par,
+            sk,
+            pk,
+            rk,
+            ek,
+            values,
+            pt,
+            ct,
+            product,
+        })
+    }
+
+    /// Returns a random number generator derived from the [`SEED`], distinct
+    /// from the one generating the fixture, for the encryption workloads.
+    pub fn rng(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::from_seed(SEED);
+        rng.set_stream(1);
+        rng
+    }
+
+    /// Runs a workload once on the inputs of the fixture.
+    pub fn run<R: RngCore + CryptoRng>(&self, workload: Workload, rng: &mut R) -> Result<()> {
+        match workload {
+            Workload::EncodeSimd => {
+                black_box(Plaintext::try_encode(
+                    &self.values[0],
+                    Encoding::simd(),
+                    &self.par,
+                )?);
+            }
+            Workload::EncryptSecretKey => {
+                black_box::<Ciphertext>(self.sk.try_encrypt(&self.pt[0], rng)?);
+            }
+            Workload::EncryptPublicKey => {
+                black_box(self.pk.try_encrypt(&self.pt[0], rng)?);
+            }
+            Workload::Decrypt => {
+                black_box(self.sk.try_decrypt(&self.ct[0])?);
+            }
+            Workload::Add => {
+                black_box(&self.ct[0] + &self.ct[1]);
+            }
+            Workload::MultiplyPlain => {
+                black_box(&self.ct[0] * &self.pt[1]);
+            }
+            Workload::Multiply => {
+                black_box(&self.ct[0] * &self.ct[1]);
+            }
+            Workload::Relinearize => {
+                let mut product = self.product.clone();
+                self.rk.relinearizes(&mut product)?;
+                black_box(product);
+            }
+            Workload::RotateColumns => {
+                black_box(self.ek.rotates_columns_by(&self.ct[0], 1)?);
+            }
+            Workload::RotateRows => {
+                black_box(self.ek.rotates_rows(&self.ct[0])?);
+            }
+            Workload::ModSwitch => {
+                let mut ct = self.ct[0].clone();
+                ct.mod_switch_to_next_level()?;
+                black_box(ct);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the workloads compute the expected results on the inputs
+    /// of the fixture, e.g. before timing them.
+    pub fn check(&self) -> Result<()> {
+        let t = &self.par.plaintext;
+        let mut expected = self.values[0].clone();
+        t.mul_vec(&mut expected, &self.values[1]);
+        let mut product = self.product.clone();
+        self.rk.relinearizes(&mut product)?;
+        product.mod_switch_to_next_level()?;
+        let decrypted = self.sk.try_decrypt(&product)?;
+        if Vec::<u64>::try_decode(&decrypted, Encoding::simd())? != expected {
+            return Err(Error::DefaultError(
+                "The fixture does not decrypt correctly".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fixture, ParameterSet, Workload};
+    use crate::Error;
+
+    #[test]
+    fn fixtures() -> Result<(), Error> {
+        let fixture = Fixture::new(ParameterSet::N4096)?;
+        assert_eq!(fixture.par.degree(), 4096);
+        assert_eq!(fixture.par.moduli_sizes().iter().sum::<usize>(), 109);
+        assert_eq!(fixture.par.plaintext() >> 19, 1);
+        fixture.check()?;
+
+        let other = Fixture::new(ParameterSet::N4096)?;
+        assert_eq!(fixture.sk, other.sk);
+        assert_eq!(fixture.pk, other.pk);
+        assert_eq!(fixture.ct, other.ct);
+
+        let mut rng = fixture.rng();
+        for workload in Workload::ALL {
+            fixture.run(workload, &mut rng)?;
+        }
+        Ok(())
+    }
+}
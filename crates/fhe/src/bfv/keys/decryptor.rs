@@ -0,0 +1,122 @@
+//! Batched decryption for the BFV encryption scheme
+
+use super::secret_key::decrypt_with_secret_poly;
+use crate::bfv::{BfvParameters, Ciphertext, Plaintext, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
+use fhe_traits::{FheDecrypter, FheParametrized};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use zeroize::Zeroizing;
+
+/// A decryptor for servers holding a [`SecretKey`] and decrypting many
+/// ciphertexts.
+///
+/// The secret polynomial is converted to NTT representation once for every
+/// level, instead of once per decryption as in [`SecretKey::try_decrypt`],
+/// and the ciphertexts of a batch are decrypted in parallel when the `rayon`
+/// feature is enabled. The precomputed polynomials are zeroized on drop.
+#[derive(Debug)]
+pub struct Decryptor {
+    par: Arc<BfvParameters>,
+    /// The secret polynomial in NTT representation, at every level.
+    s: Vec<Zeroizing<Poly>>,
+}
+
+impl Decryptor {
+    /// Creates a decryptor for the secret key `sk`.
+    pub fn new(sk: &SecretKey) -> Result<Self> {
+        let s = (0..=sk.par.max_level())
+            .map(|level| {
+                let mut s = Zeroizing::new(Poly::try_convert_from(
+                    sk.coeffs.as_ref(),
+                    sk.par.ctx_at_level(level)?,
+                    false,
+                    Representation::PowerBasis,
+                )?);
+                s.change_representation(Representation::Ntt);
+                Ok(s)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            par: sk.par.clone(),
+            s,
+        })
+    }
+
+    /// Decrypts a batch of ciphertexts, in parallel with the `rayon` feature.
+    ///
+    /// Returns an error if any of the ciphertexts cannot be decrypted.
+    pub fn decrypt_batch(&self, cts: &[Ciphertext]) -> Result<Vec<Plaintext>> {
+        #[cfg(feature = "rayon")]
+        let plaintexts = cts.par_iter().map(|ct| self.try_decrypt(ct)).collect();
+        #[cfg(not(feature = "rayon"))]
+        let plaintexts = cts.iter().map(|ct| self.try_decrypt(ct)).collect();
+        plaintexts
+    }
+}
+
+impl FheParametrized for Decryptor {
+    type Parameters = BfvParameters;
+}
+
+impl FheDecrypter<Plaintext, Ciphertext> for Decryptor {
+    type Error = Error;
+
+    fn try_decrypt(&self, ct: &Ciphertext) -> Result<Plaintext> {
+        if self.par != ct.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        decrypt_with_secret_poly(&self.s[ct.level], ct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decryptor;
+    use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn decrypt_batch() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let decryptor = Decryptor::new(&sk)?;
+
+        let mut cts = Vec::new();
+        let mut pts = Vec::new();
+        for level in 0..=params.max_level() {
+            let pt = Plaintext::random(Encoding::simd_at_level(level), &params, &mut rng)?;
+            let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            pts.push(pt);
+            cts.push(ct);
+        }
+        // A product of ciphertexts, before relinearization.
+        cts.push(&cts[0] * &cts[0]);
+        pts.push(sk.try_decrypt(&cts[cts.len() - 1])?);
+
+        assert_eq!(decryptor.decrypt_batch(&cts)?, pts);
+        assert_eq!(decryptor.try_decrypt(&cts[1])?, pts[1]);
+        assert!(decryptor.decrypt_batch(&[]).is_ok_and(|pts| pts.is_empty()));
+
+        let other = BfvParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&other, &mut rng);
+        let ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&[1u64], Encoding::poly(), &other)?,
+            &mut rng,
+        )?;
+        assert!(decryptor.decrypt_batch(&[ct]).is_err());
+        Ok(())
+    }
+}
@@ -1,17 +1,25 @@
 //! Leveled evaluation keys for the BFV encryption scheme.
 
-use crate::bfv::{keys::GaloisKey, traits::TryConvertFrom, BfvParameters, Ciphertext, SecretKey};
+use crate::bfv::{
+    keys::GaloisKey, traits::TryConvertFrom, BfvParameters, CancellationToken, Ciphertext,
+    DeserializationConfig, Plaintext, SecretKey,
+};
 use crate::proto::bfv::{EvaluationKey as EvaluationKeyProto, GaloisKey as GaloisKeyProto};
 use crate::{Error, Result};
 use alloc::vec;
 use alloc::vec::Vec;
 use fhe_math::rq::{traits::TryConvertFrom as TryConvertFromPoly, Poly, Representation};
+use fhe_math::stream::{read_u32, write_u32, Reader, Writer};
 use fhe_math::zq::Modulus;
 use fhe_traits::{DeserializeParametrized, FheParametrized, Serialize};
 use hashbrown::HashMap;
 use hashbrown::HashSet;
+use itertools::Itertools;
 use prost::Message;
-use rand::{CryptoRng, RngCore};
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 extern crate alloc;
 use alloc::string::ToString;
 use alloc::sync::Arc;
@@ -26,7 +34,7 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 /// - inner sum
 #[derive(Debug, PartialEq, Eq)]
 pub struct EvaluationKey {
-    par: Arc<BfvParameters>,
+    pub(crate) par: Arc<BfvParameters>,
 
     ciphertext_level: usize,
     evaluation_key_level: usize,
@@ -39,12 +47,21 @@ pub struct EvaluationKey {
 
     /// Monomials used in expansion
     monomials: Vec<Poly>,
+
+    /// The (optional) seed from which the seeds of the Galois keys are derived
+    seed: Option<<ChaCha8Rng as SeedableRng>::Seed>,
 }
 
 impl EvaluationKey {
     /// Reports whether the evaluation key enables to compute an homomorphic
-    /// inner sums.
+    /// inner sums, either with rotations or with the trace.
     pub fn supports_inner_sum(&self) -> bool {
+        self.supports_inner_sum_by_rotations() || self.supports_inner_sum_by_trace()
+    }
+
+    /// Reports whether the evaluation key contains the row rotation key and
+    /// the column rotation keys by powers of two.
+    fn supports_inner_sum_by_rotations(&self) -> bool {
         let mut ret = self.gk.contains_key(&(self.par.degree() * 2 - 1));
         let mut i = 1;
         while i < self.par.degree() / 2 {
@@ -56,13 +73,22 @@ impl EvaluationKey {
         ret
     }
 
+    /// Reports whether the evaluation key enables to compute an homomorphic
+    /// inner sum using the trace, i.e. whether it contains the keys for the
+    /// automorphisms `x -> x^(degree / 2^l + 1)` for all `l < log2(degree)`.
+    /// These are the keys enabling expansion at level `log2(degree)`.
+    pub fn supports_inner_sum_by_trace(&self) -> bool {
+        (0..self.par.degree().ilog2())
+            .all(|l| self.gk.contains_key(&((self.par.degree() >> l) + 1)))
+    }
+
     /// Computes the homomorphic inner sum.
+    ///
+    /// The sum is computed by rotating and adding the slots when the key
+    /// contains the required rotation keys, and using the trace otherwise (see
+    /// [`EvaluationKey::computes_inner_sum_by_trace`]).
     pub fn computes_inner_sum(&self, ct: &Ciphertext) -> Result<Ciphertext> {
-        if !self.supports_inner_sum() {
-            Err(Error::DefaultError(
-                "This key does not support the inner sum functionality".to_string(),
-            ))
-        } else {
+        if self.supports_inner_sum_by_rotations() {
             let mut out = ct.clone();
 
             let mut i = 1;
@@ -79,9 +105,37 @@ impl EvaluationKey {
             out += &gk.relinearize(&out)?;
 
             Ok(out)
+        } else if self.supports_inner_sum_by_trace() {
+            self.computes_inner_sum_by_trace(ct)
+        } else {
+            Err(Error::DefaultError(
+                "This key does not support the inner sum functionality".to_string(),
+            ))
         }
     }
 
+    /// Computes the homomorphic inner sum using the trace of the plaintext.
+    ///
+    /// The sum of the automorphisms of a plaintext polynomial `m` is the
+    /// constant `degree * m_0`, which is also the sum of its SIMD slots. The
+    /// trace is computed with `log2(degree)` key switches, as the rotate-and-add
+    /// algorithm, but only requires the keys used for oblivious expansion, so
+    /// that a single evaluation key supports both operations without the
+    /// rotation keys.
+    pub fn computes_inner_sum_by_trace(&self, ct: &Ciphertext) -> Result<Ciphertext> {
+        if !self.supports_inner_sum_by_trace() {
+            return Err(Error::DefaultError(
+                "This key does not support the inner sum by trace".to_string(),
+            ));
+        }
+        let mut out = ct.clone();
+        for l in 0..self.par.degree().ilog2() {
+            let gk = self.gk.get(&((self.par.degree() >> l) + 1)).unwrap();
+            out += &gk.relinearize(&out)?;
+        }
+        Ok(out)
+    }
+
     /// Reports whether the evaluation key enables to rotate the rows of the
     /// plaintext.
     pub fn supports_row_rotation(&self) -> bool {
@@ -125,6 +179,175 @@ impl EvaluationKey {
         }
     }
 
+    /// Returns the shortest sequence of column rotations supported by this key
+    /// whose composition rotates the columns by `i`.
+    pub(crate) fn column_rotation_path(&self, i: usize) -> Result<Vec<usize>> {
+        let row_size = self.par.degree() >> 1;
+        let steps = self
+            .rot_to_gk_exponent
+            .iter()
+            .filter(|(_, exp)| self.gk.contains_key(*exp))
+            .map(|(step, _)| *step)
+            .sorted()
+            .collect_vec();
+
+        // Breadth-first search over the rotations, where each rotation records
+        // the previous rotation and the step it was rotated by.
+        let mut previous: HashMap<usize, Option<(usize, usize)>> = HashMap::new();
+        previous.insert(0, None);
+        let mut frontier = vec![0];
+        while !frontier.is_empty() && !previous.contains_key(&i) {
+            let mut next = vec![];
+            for rotation in frontier {
+                for step in &steps {
+                    let new_rotation = (rotation + step) % row_size;
+                    if !previous.contains_key(&new_rotation) {
+                        previous.insert(new_rotation, Some((rotation, *step)));
+                        next.push(new_rotation);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let mut path = vec![];
+        let mut current = i;
+        loop {
+            match previous.get(&current) {
+                None => {
+                    return Err(Error::DefaultError(
+                        "The Galois keys do not generate this rotation".to_string(),
+                    ))
+                }
+                Some(None) => break,
+                Some(Some((rotation, step))) => {
+                    path.push(*step);
+                    current = *rotation;
+                }
+            }
+        }
+        Ok(path)
+    }
+
+    /// Rotates the columns of `ct` by `i`, multiplies the result by `pt`, and
+    /// adds it to `acc`.
+    ///
+    /// This fuses the steps of the baby-step giant-step matrix-vector
+    /// products: the rotated ciphertext is never materialized, and its
+    /// polynomials are multiplied and accumulated in NTT representation. An
+    /// empty accumulator, e.g. [`Ciphertext::zero`], is replaced by the
+    /// product.
+    pub fn rotate_mul_acc(
+        &self,
+        ct: &Ciphertext,
+        i: usize,
+        pt: &Plaintext,
+        acc: &mut Ciphertext,
+    ) -> Result<()> {
+        if !self.supports_column_rotation_by(i) {
+            return Err(Error::DefaultError(
+                "This key does not support rotating the columns by this index".to_string(),
+            ));
+        }
+        let gk = &self.gk[&self.rot_to_gk_exponent[&i]];
+        gk.relinearize_mul_acc(ct, pt, acc)
+    }
+
+    /// Enables rotating the columns by `i` without the secret key, by composing
+    /// the Galois keys of this evaluation key.
+    ///
+    /// This lets the holder of the secret key delegate the generation of
+    /// rotation keys: an evaluation key supporting e.g. the inner sum, whose
+    /// Galois keys generate all the column rotations, enables the server to
+    /// derive any column rotation later on. The derivation uses the fewest
+    /// compositions of Galois keys, and each composition adds the noise of a
+    /// key switching to the derived key, so that rotating with a derived key
+    /// adds more noise than with a key generated from the secret key.
+    ///
+    /// Returns an error if the Galois keys do not generate this rotation, or
+    /// if the ciphertext level and the evaluation key level differ.
+    pub fn derive_column_rotation(&mut self, i: usize) -> Result<()> {
+        let exponent = self.column_rotation_exponent(i)?;
+        if !self.gk.contains_key(&exponent) {
+            let gk = self.derive_galois_key(exponent)?;
+            self.gk.insert(exponent, gk);
+        }
+        Ok(())
+    }
+
+    /// Derives the Galois key rotating the columns by `i`, as in
+    /// [`EvaluationKey::derive_column_rotation`], but returns it instead of
+    /// adding it to this evaluation key, e.g. when the evaluation key is shared
+    /// between threads behind an `Arc`. The columns are then rotated with
+    /// [`GaloisKey::relinearize`].
+    ///
+    /// Returns an error if the evaluation key already contains this Galois
+    /// key, or if the Galois keys do not generate this rotation.
+    pub fn derive_column_rotation_key(&self, i: usize) -> Result<GaloisKey> {
+        self.derive_galois_key(self.column_rotation_exponent(i)?)
+    }
+
+    /// Returns the exponent of the Galois key rotating the columns by `i`.
+    fn column_rotation_exponent(&self, i: usize) -> Result<usize> {
+        self.rot_to_gk_exponent
+            .get(&i)
+            .copied()
+            .ok_or_else(|| Error::DefaultError("Invalid column index".to_string()))
+    }
+
+    /// Derives the Galois key of exponent `exponent` from the shortest product
+    /// of exponents of the Galois keys of this evaluation key.
+    fn derive_galois_key(&self, exponent: usize) -> Result<GaloisKey> {
+        let modulus = 2 * self.par.degree();
+        let exponents = self.gk.keys().copied().sorted().collect_vec();
+
+        // Breadth-first search, where each product records the previous product
+        // and the exponent it was multiplied by.
+        let mut previous: HashMap<usize, Option<(usize, usize)>> =
+            exponents.iter().map(|e| (*e, None)).collect();
+        let mut frontier = exponents.clone();
+        while !frontier.is_empty() && !previous.contains_key(&exponent) {
+            let mut next = vec![];
+            for product in frontier {
+                for e in &exponents {
+                    let new_product = (product * e) % modulus;
+                    if !previous.contains_key(&new_product) {
+                        previous.insert(new_product, Some((product, *e)));
+                        next.push(new_product);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let mut path = vec![];
+        let mut current = exponent;
+        loop {
+            match previous.get(&current) {
+                None => {
+                    return Err(Error::DefaultError(
+                        "The Galois keys do not generate this rotation".to_string(),
+                    ))
+                }
+                Some(None) => break,
+                Some(Some((product, e))) => {
+                    path.push(*e);
+                    current = *product;
+                }
+            }
+        }
+
+        let mut path = path.iter().rev();
+        let first = path
+            .next()
+            .ok_or_else(|| Error::DefaultError("The Galois key already exists".to_string()))?;
+        let mut gk = self.gk[&current].compose(&self.gk[first])?;
+        for e in path {
+            gk = gk.compose(&self.gk[e])?;
+        }
+        Ok(gk)
+    }
+
     /// Reports whether the evaluation key supports oblivious expansion.
     pub fn supports_expansion(&self, level: usize) -> bool {
         if level == 0 {
@@ -145,6 +368,27 @@ impl EvaluationKey {
     /// ciphertext does not have size 2. The output is a vector of `size`
     /// ciphertexts.
     pub fn expands(&self, ct: &Ciphertext, size: usize) -> Result<Vec<Ciphertext>> {
+        self.expands_inner(ct, size, None)
+    }
+
+    /// Obliviously expands the ciphertext as [`EvaluationKey::expands`],
+    /// checking `token` between the key switchings. Returns
+    /// [`Error::Cancelled`] if the token is cancelled.
+    pub fn expands_with_cancellation(
+        &self,
+        ct: &Ciphertext,
+        size: usize,
+        token: &CancellationToken,
+    ) -> Result<Vec<Ciphertext>> {
+        self.expands_inner(ct, size, Some(token))
+    }
+
+    fn expands_inner(
+        &self,
+        ct: &Ciphertext,
+        size: usize,
+        token: Option<&CancellationToken>,
+    ) -> Result<Vec<Ciphertext>> {
         let level = size.next_power_of_two().ilog2() as usize;
         if ct.len() != 2 {
             Err(Error::DefaultError(
@@ -162,6 +406,7 @@ impl EvaluationKey {
                 let monomial = &self.monomials[l];
                 let gk = self.gk.get(&((self.par.degree() >> l) + 1)).unwrap();
                 for i in 0..(1 << l) {
+                    CancellationToken::check(token)?;
                     let sub = gk.relinearize(&out[i])?;
                     if (1 << l) | i < size {
                         out[(1 << l) | i] = &out[i] - &sub;
@@ -189,6 +434,19 @@ impl EvaluationKey {
         }
         m
     }
+
+    /// Derives the seed of the Galois key of exponent `exponent` from the
+    /// shared seed of the evaluation key.
+    fn derive_seed(
+        seed: <ChaCha8Rng as SeedableRng>::Seed,
+        exponent: usize,
+    ) -> <ChaCha8Rng as SeedableRng>::Seed {
+        let mut rng = ChaCha8Rng::from_seed(seed);
+        rng.set_stream(exponent as u64);
+        let mut derived = <ChaCha8Rng as SeedableRng>::Seed::default();
+        rng.fill(&mut derived);
+        derived
+    }
 }
 
 impl FheParametrized for EvaluationKey {
@@ -204,10 +462,27 @@ impl Serialize for EvaluationKey {
 impl DeserializeParametrized for EvaluationKey {
     type Error = Error;
 
+    /// Deserializes an evaluation key with the limits of the default
+    /// [`DeserializationConfig`], keeping the variable-time computations of
+    /// its public polynomials.
     fn from_bytes(bytes: &[u8], par: &Arc<Self::Parameters>) -> Result<Self> {
+        Self::from_bytes_with_config(bytes, par, &DeserializationConfig::keys())
+    }
+}
+
+impl EvaluationKey {
+    /// Deserializes an evaluation key, with the limits and options of
+    /// `config`; the limit on the number of polynomials applies to all the
+    /// Galois keys together.
+    pub fn from_bytes_with_config(
+        bytes: &[u8],
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        config.check_bytes(bytes.len())?;
         let gkp = Message::decode(bytes);
         if let Ok(gkp) = gkp {
-            EvaluationKey::try_convert_from(&gkp, par)
+            EvaluationKey::try_convert_from_with_config(&gkp, par, config)
         } else {
             Err(Error::DefaultError("Invalid serialization".to_string()))
         }
@@ -224,6 +499,7 @@ pub struct EvaluationKeyBuilder {
     row_rotation: bool,
     expansion_level: usize,
     column_rotation: HashSet<usize>,
+    shared_seed: bool,
     rot_to_gk_exponent: HashMap<usize, usize>,
 }
 
@@ -246,6 +522,7 @@ impl EvaluationKeyBuilder {
             row_rotation: false,
             expansion_level: 0,
             column_rotation: HashSet::new(),
+            shared_seed: false,
             rot_to_gk_exponent: EvaluationKey::construct_rot_to_gk_exponent(&sk.par),
         })
     }
@@ -272,6 +549,7 @@ impl EvaluationKeyBuilder {
             row_rotation: false,
             expansion_level: 0,
             column_rotation: HashSet::new(),
+            shared_seed: false,
             rot_to_gk_exponent: EvaluationKey::construct_rot_to_gk_exponent(&sk.par),
         })
     }
@@ -313,8 +591,75 @@ impl EvaluationKeyBuilder {
         }
     }
 
+    /// Allow this evaluation key to homomorphically rotate the plaintext
+    /// columns in both directions, i.e. by `i` and by `degree / 2 - i`, for
+    /// every `i` in `steps`.
+    ///
+    /// The seeds of all the Galois keys of the evaluation key are then derived
+    /// from a single seed, so that the serialization contains one seed instead
+    /// of one per Galois key.
+    #[allow(unused_must_use)]
+    pub fn enable_column_rotations<I>(&mut self, steps: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let row_size = self.sk.par.degree() >> 1;
+        for i in steps {
+            if i == 0 || i >= row_size {
+                return Err(Error::DefaultError("Invalid column index".to_string()));
+            }
+            self.enable_column_rotation(i)?;
+            self.enable_column_rotation(row_size - i)?;
+        }
+        self.shared_seed = true;
+        Ok(self)
+    }
+
+    /// Allow this evaluation key to rotate the columns by any number of steps
+    /// with [`Ciphertext::rotate`], by enabling the baby steps `1, ...,
+    /// baby_steps - 1` and the giant steps multiple of `baby_steps`.
+    ///
+    /// Any rotation is then the composition of at most one baby step and one
+    /// giant step, and the number of Galois keys is minimal when `baby_steps`
+    /// is about the square root of `degree / 2`.
+    #[allow(unused_must_use)]
+    pub fn enable_baby_step_giant_step_rotations(
+        &mut self,
+        baby_steps: usize,
+    ) -> Result<&mut Self> {
+        let row_size = self.sk.par.degree() >> 1;
+        if baby_steps == 0 || baby_steps >= row_size {
+            return Err(Error::DefaultError(
+                "Invalid number of baby steps".to_string(),
+            ));
+        }
+        for i in (1..baby_steps).chain((baby_steps..row_size).step_by(baby_steps)) {
+            self.enable_column_rotation(i)?;
+        }
+        Ok(self)
+    }
+
     /// Build an [`EvaluationKey`] with the specified attributes.
     pub fn build<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Result<EvaluationKey> {
+        self.build_with_progress(rng, |_, _| {})
+    }
+
+    /// Build an [`EvaluationKey`] with the specified attributes, calling
+    /// `progress(generated, total)` after each batch of Galois keys is
+    /// generated.
+    ///
+    /// When the `rayon` feature is enabled, the Galois keys of a batch are
+    /// generated in parallel. The keys are derived from seeds sampled from
+    /// `rng`, so the output does not depend on the number of threads.
+    pub fn build_with_progress<R, F>(
+        &mut self,
+        rng: &mut R,
+        mut progress: F,
+    ) -> Result<EvaluationKey>
+    where
+        R: RngCore + CryptoRng,
+        F: FnMut(usize, usize),
+    {
         let mut ek = EvaluationKey {
             gk: HashMap::default(),
             par: self.sk.par.clone(),
@@ -322,6 +667,7 @@ impl EvaluationKeyBuilder {
             monomials: Vec::with_capacity(self.sk.par.degree().ilog2() as usize),
             ciphertext_level: self.ciphertext_level,
             evaluation_key_level: self.evaluation_key_level,
+            seed: None,
         };
 
         let mut indices = self.column_rotation.clone();
@@ -359,17 +705,63 @@ impl EvaluationKeyBuilder {
             ek.monomials.push(monomial);
         }
 
-        for index in indices {
-            ek.gk.insert(
-                index,
-                GaloisKey::new(
-                    &self.sk,
-                    index,
-                    self.ciphertext_level,
-                    self.evaluation_key_level,
-                    rng,
-                )?,
-            );
+        if self.shared_seed {
+            let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+            rng.fill(&mut seed);
+            ek.seed = Some(seed);
+        }
+
+        // Sample one seed per Galois key, in a deterministic order.
+        let indices = indices
+            .into_iter()
+            .sorted()
+            .map(|index| {
+                let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+                rng.fill(&mut seed);
+                (index, seed)
+            })
+            .collect_vec();
+
+        // The expanded forms of the secret key are shared by all the Galois keys.
+        let material = self.sk.public_key_material();
+        let s = material.s(self.ciphertext_level)?;
+        let s_ntt = material.s_ntt(self.evaluation_key_level)?;
+        let shared_seed = ek.seed;
+        let generate = |(index, seed): &(usize, <ChaCha8Rng as SeedableRng>::Seed)| {
+            let gk = GaloisKey::new_from_secret_poly(
+                &self.sk,
+                s,
+                s_ntt,
+                *index,
+                self.ciphertext_level,
+                self.evaluation_key_level,
+                shared_seed.map(|seed| EvaluationKey::derive_seed(seed, *index)),
+                &mut ChaCha8Rng::from_seed(*seed),
+            )?;
+            Ok((*index, gk))
+        };
+
+        #[cfg(feature = "rayon")]
+        let batch_size = rayon::current_num_threads();
+        #[cfg(not(feature = "rayon"))]
+        let batch_size = 1;
+
+        let mut generated = 0;
+        for batch in indices.chunks(batch_size) {
+            #[cfg(feature = "rayon")]
+            let gks = batch
+                .par_iter()
+                .map(generate)
+                .collect::<Result<Vec<(usize, GaloisKey)>>>()?;
+            #[cfg(not(feature = "rayon"))]
+            let gks = batch
+                .iter()
+                .map(generate)
+                .collect::<Result<Vec<(usize, GaloisKey)>>>()?;
+
+            generated += gks.len();
+            ek.gk.extend(gks);
+            progress(generated, indices.len());
         }
 
         Ok(ek)
@@ -378,35 +770,173 @@ impl EvaluationKeyBuilder {
 
 impl From<&EvaluationKey> for EvaluationKeyProto {
     fn from(ek: &EvaluationKey) -> Self {
-        let mut proto = EvaluationKeyProto::default();
-        for (_, gk) in ek.gk.iter() {
-            proto.gk.push(GaloisKeyProto::from(gk))
+        let mut proto = ek.header_proto();
+        for gk in ek.gk.values() {
+            proto.gk.push(ek.galois_key_proto(gk))
         }
-        proto.ciphertext_level = ek.ciphertext_level as u32;
-        proto.evaluation_key_level = ek.evaluation_key_level as u32;
         proto
     }
 }
 
+impl EvaluationKey {
+    /// Returns the protobuf evaluation key without its Galois keys.
+    fn header_proto(&self) -> EvaluationKeyProto {
+        EvaluationKeyProto {
+            seed: self.seed.map(|seed| seed.to_vec()).unwrap_or_default(),
+            ciphertext_level: self.ciphertext_level as u32,
+            evaluation_key_level: self.evaluation_key_level as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the protobuf Galois key of `gk`, without its seed when it is
+    /// derived from the shared seed of the evaluation key.
+    fn galois_key_proto(&self, gk: &GaloisKey) -> GaloisKeyProto {
+        let mut gkp = GaloisKeyProto::from(gk);
+        if self.seed.is_some() {
+            if let Some(ksk) = gkp.ksk.as_mut() {
+                ksk.seed.clear()
+            }
+        }
+        gkp
+    }
+
+    /// Converts a protobuf Galois key of an evaluation key with the shared
+    /// `seed`, if any.
+    fn galois_key_from_proto(
+        gkp: &GaloisKeyProto,
+        seed: Option<<ChaCha8Rng as SeedableRng>::Seed>,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<GaloisKey> {
+        if let Some(seed) = seed {
+            let mut gkp = gkp.clone();
+            if let Some(ksk) = gkp.ksk.as_mut() {
+                // Derived Galois keys store their polynomials c1.
+                if ksk.seed.is_empty() && ksk.c1.is_empty() {
+                    ksk.seed = EvaluationKey::derive_seed(seed, gkp.exponent as usize).to_vec();
+                }
+            }
+            GaloisKey::try_convert_from_with_config(&gkp, par, config)
+        } else {
+            GaloisKey::try_convert_from_with_config(gkp, par, config)
+        }
+    }
+}
+
 impl TryConvertFrom<&EvaluationKeyProto> for EvaluationKey {
     fn try_convert_from(value: &EvaluationKeyProto, par: &Arc<BfvParameters>) -> Result<Self> {
-        let mut gk = HashMap::new();
-        for gkp in &value.gk {
-            let key = GaloisKey::try_convert_from(gkp, par)?;
-            if key.ksk.ciphertext_level != value.ciphertext_level as usize {
-                return Err(Error::DefaultError(
-                    "Galois key has incorrect ciphertext level".to_string(),
-                ));
+        Self::try_convert_from_with_config(value, par, &DeserializationConfig::keys())
+    }
+}
+
+impl EvaluationKey {
+    /// Converts a protobuf evaluation key, with the limits and options of
+    /// `config`.
+    fn try_convert_from_with_config(
+        value: &EvaluationKeyProto,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        // Each key switching key has as many polynomials c1 as c0, which may be
+        // expanded from a seed.
+        config.check_polys(
+            value
+                .gk
+                .iter()
+                .filter_map(|gkp| gkp.ksk.as_ref())
+                .map(|ksk| 2 * ksk.c0.len())
+                .sum(),
+        )?;
+        let seed = if value.seed.is_empty() {
+            None
+        } else {
+            Some(
+                <ChaCha8Rng as SeedableRng>::Seed::try_from(value.seed.clone())
+                    .map_err(|_| Error::DefaultError("Invalid seed".to_string()))?,
+            )
+        };
+
+        let gk = value
+            .gk
+            .iter()
+            .map(|gkp| EvaluationKey::galois_key_from_proto(gkp, seed, par, config))
+            .collect::<Result<Vec<_>>>()?;
+
+        EvaluationKey::from_galois_keys(
+            par,
+            value.ciphertext_level as usize,
+            value.evaluation_key_level as usize,
+            gk,
+            seed,
+        )
+    }
+}
+
+impl EvaluationKey {
+    /// Writes the evaluation key to `w` in the streaming format (see
+    /// [`fhe_math::stream`]): its seed, its levels, and its Galois keys by
+    /// increasing exponent, one polynomial at a time.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        match self.seed.as_ref() {
+            Some(seed) => {
+                w.write_all(&[1])?;
+                w.write_all(seed)?;
             }
-            if key.ksk.ksk_level != value.evaluation_key_level as usize {
-                return Err(Error::DefaultError(
-                    "Galois key has incorrect evaluation key level".to_string(),
-                ));
+            None => w.write_all(&[0])?,
+        }
+        write_u32(w, self.ciphertext_level as u32)?;
+        write_u32(w, self.evaluation_key_level as u32)?;
+        write_u32(w, self.gk.len() as u32)?;
+        for exponent in self.gk.keys().sorted() {
+            self.gk[exponent].write_to(w)?;
+        }
+        Ok(())
+    }
+
+    /// Reads an evaluation key from `r`, in the streaming format of
+    /// [`EvaluationKey::write_to`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        let mut flag = [0u8];
+        r.read_exact(&mut flag)?;
+        let seed = match flag[0] {
+            0 => None,
+            1 => {
+                let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+                r.read_exact(&mut seed)?;
+                Some(seed)
             }
+            _ => return Err(Error::DefaultError("Invalid seed flag".to_string())),
+        };
+        let ciphertext_level = read_u32(r)? as usize;
+        let evaluation_key_level = read_u32(r)? as usize;
+        let size = read_u32(r)? as usize;
+        // The number of Galois keys is bounded by the number of exponents.
+        if size > par.degree() {
+            return Err(Error::DefaultError("Too many Galois keys".to_string()));
+        }
+        let gk = (0..size)
+            .map(|_| GaloisKey::read_from(r, par))
+            .collect::<Result<Vec<_>>>()?;
+        EvaluationKey::from_galois_keys(par, ciphertext_level, evaluation_key_level, gk, seed)
+    }
+
+    /// Assembles an evaluation key from deserialized Galois keys, after
+    /// checking their levels.
+    fn from_galois_keys(
+        par: &Arc<BfvParameters>,
+        ciphertext_level: usize,
+        evaluation_key_level: usize,
+        keys: Vec<GaloisKey>,
+        seed: Option<<ChaCha8Rng as SeedableRng>::Seed>,
+    ) -> Result<Self> {
+        let mut gk = HashMap::new();
+        for key in keys {
+            check_galois_key_levels(&key, ciphertext_level, evaluation_key_level)?;
             gk.insert(key.element.exponent, key);
         }
 
-        let ciphertext_ctx = par.ctx_at_level(value.ciphertext_level as usize)?;
+        let ciphertext_ctx = par.ctx_at_level(ciphertext_level)?;
         let mut monomials = Vec::with_capacity(par.degree().ilog2() as usize);
         for l in 0..par.degree().ilog2() {
             let mut monomial = vec![0i64; par.degree()];
@@ -427,16 +957,101 @@ impl TryConvertFrom<&EvaluationKeyProto> for EvaluationKey {
             par: par.clone(),
             rot_to_gk_exponent: EvaluationKey::construct_rot_to_gk_exponent(par),
             monomials,
-            ciphertext_level: value.ciphertext_level as usize,
-            evaluation_key_level: value.evaluation_key_level as usize,
+            ciphertext_level,
+            evaluation_key_level,
+            seed,
         })
     }
 }
 
+impl EvaluationKey {
+    /// Serializes the header of the evaluation key, i.e. its seed and levels,
+    /// for the chunked serialization of its Galois keys with
+    /// [`EvaluationKey::galois_key_chunks`]. The header is an evaluation key
+    /// message without Galois key, which [`EvaluationKey::from_bytes`]
+    /// deserializes into an evaluation key to which the chunks are loaded with
+    /// [`EvaluationKey::load_galois_key`].
+    pub fn header_to_bytes(&self) -> Vec<u8> {
+        self.header_proto().encode_to_vec()
+    }
+
+    /// Serializes the Galois keys one at a time, by increasing exponent, as
+    /// pairs of the exponent of the Galois element and of a Galois key message.
+    ///
+    /// The chunks are serialized lazily, so that a large evaluation key can be
+    /// streamed without holding a copy of its whole serialization, and a
+    /// server can store the chunks separately, e.g. in a memory-mapped file
+    /// indexed by exponent, and only load the Galois keys it needs.
+    pub fn galois_key_chunks(&self) -> impl Iterator<Item = (usize, Vec<u8>)> + '_ {
+        self.gk.keys().sorted().map(|exponent| {
+            (
+                *exponent,
+                self.galois_key_proto(&self.gk[exponent]).encode_to_vec(),
+            )
+        })
+    }
+
+    /// Deserializes a chunk of [`EvaluationKey::galois_key_chunks`] and adds
+    /// its Galois key to this evaluation key, replacing the key of the same
+    /// exponent if any. Returns the exponent of the Galois element.
+    ///
+    /// Returns an error if the chunk is invalid, or if the levels of the
+    /// Galois key differ from those of this evaluation key.
+    pub fn load_galois_key(&mut self, chunk: &[u8]) -> Result<usize> {
+        let config = DeserializationConfig::keys();
+        config.check_bytes(chunk.len())?;
+        let gkp = GaloisKeyProto::decode(chunk)
+            .map_err(|_| Error::DefaultError("Invalid serialization".to_string()))?;
+        if let Some(ksk) = gkp.ksk.as_ref() {
+            config.check_polys(2 * ksk.c0.len())?;
+        }
+        let key = EvaluationKey::galois_key_from_proto(&gkp, self.seed, &self.par, &config)?;
+        check_galois_key_levels(&key, self.ciphertext_level, self.evaluation_key_level)?;
+        let exponent = key.element.exponent;
+        self.gk.insert(exponent, key);
+        Ok(exponent)
+    }
+
+    /// Removes the Galois key of the element of exponent `exponent`, e.g. to
+    /// release its memory once it is not needed anymore. Returns whether the
+    /// key was loaded.
+    pub fn unload_galois_key(&mut self, exponent: usize) -> bool {
+        self.gk.remove(&exponent).is_some()
+    }
+
+    /// Returns the exponents of the Galois elements of the loaded Galois keys,
+    /// in increasing order.
+    pub fn galois_exponents(&self) -> Vec<usize> {
+        self.gk.keys().copied().sorted().collect()
+    }
+}
+
+/// Returns an error if the levels of a Galois key differ from those of the
+/// evaluation key.
+fn check_galois_key_levels(
+    key: &GaloisKey,
+    ciphertext_level: usize,
+    evaluation_key_level: usize,
+) -> Result<()> {
+    if key.ksk.ciphertext_level != ciphertext_level {
+        return Err(Error::DefaultError(
+            "Galois key has incorrect ciphertext level".to_string(),
+        ));
+    }
+    if key.ksk.ksk_level != evaluation_key_level {
+        return Err(Error::DefaultError(
+            "Galois key has incorrect evaluation key level".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{EvaluationKey, EvaluationKeyBuilder};
-    use crate::bfv::{traits::TryConvertFrom, BfvParameters, Encoding, Plaintext, SecretKey};
+    use crate::bfv::{
+        traits::TryConvertFrom, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey,
+    };
     use crate::proto::bfv::EvaluationKey as LeveledEvaluationKeyProto;
     use crate::Error;
     extern crate alloc;
@@ -447,7 +1062,7 @@ mod tests {
     use fhe_traits::{
         DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, Serialize,
     };
-    use itertools::izip;
+    use itertools::{izip, Itertools};
     use rand::thread_rng;
 
     #[test]
@@ -523,6 +1138,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn build_with_progress() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+
+        let mut steps = vec![];
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build_with_progress(&mut rng, |generated, total| steps.push((generated, total)))?;
+        assert!(ek.supports_inner_sum());
+
+        let total = ek.gk.len();
+        assert!(!steps.is_empty());
+        assert!(steps.iter().all(|(_, t)| *t == total));
+        assert!(steps.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(steps.last(), Some(&(total, total)));
+
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let expected = params
+            .plaintext
+            .reduce_u128(v.iter().map(|vi| *vi as u128).sum());
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        let ct2 = ek.computes_inner_sum(&ct)?;
+        let pt = sk.try_decrypt(&ct2)?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&pt, Encoding::simd())?,
+            vec![expected; params.degree()]
+        );
+        Ok(())
+    }
+
     #[test]
     fn inner_sum() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -567,6 +1215,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn inner_sum_by_trace() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_expansion(params.degree().ilog2() as usize)?
+            .build(&mut rng)?;
+        assert!(ek.supports_inner_sum());
+        assert!(ek.supports_inner_sum_by_trace());
+
+        let ek_rotations = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        assert!(!ek_rotations.supports_inner_sum_by_trace());
+
+        for _ in 0..10 {
+            let v = params.plaintext.random_vec(params.degree(), &mut rng);
+            let expected = params
+                .plaintext
+                .reduce_u128(v.iter().map(|vi| *vi as u128).sum());
+            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+            for ct2 in [
+                ek.computes_inner_sum(&ct)?,
+                ek.computes_inner_sum_by_trace(&ct)?,
+            ] {
+                let pt = sk.try_decrypt(&ct2)?;
+                assert_eq!(
+                    Vec::<u64>::try_decode(&pt, Encoding::simd())?,
+                    vec![expected; params.degree()]
+                );
+            }
+            assert!(ek_rotations.computes_inner_sum_by_trace(&ct).is_err());
+        }
+        Ok(())
+    }
+
     #[test]
     fn row_rotation() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -667,6 +1354,218 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn column_rotations_shared_seed() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotations(1..=2)?
+            .build(&mut rng)?;
+        for i in 1..row_size {
+            assert_eq!(
+                ek.supports_column_rotation_by(i),
+                [1, 2, row_size - 2, row_size - 1].contains(&i)
+            );
+        }
+        assert!(EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotations(0..2)
+            .is_err());
+        assert!(EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotations([row_size])
+            .is_err());
+
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        let ct2 = ek.rotates_columns_by(&ct, row_size - 1)?;
+        let ct2 = ek.rotates_columns_by(&ct2, 1)?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&sk.try_decrypt(&ct2)?, Encoding::simd())?,
+            v
+        );
+
+        // The Galois keys share a single seed in the serialization.
+        let bytes = ek.to_bytes();
+        assert_eq!(ek, EvaluationKey::from_bytes(&bytes, &params)?);
+        let mut builder = EvaluationKeyBuilder::new(&sk)?;
+        for i in [1, 2, row_size - 2, row_size - 1] {
+            builder.enable_column_rotation(i)?;
+        }
+        assert_eq!(
+            bytes.len() + 3 * 34,
+            builder.build(&mut rng)?.to_bytes().len()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rotate_mul_acc() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotations(1..=3)?
+            .build(&mut rng)?;
+
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let ct = sk.try_encrypt(
+            &Plaintext::try_encode(&v, Encoding::simd(), &params)?,
+            &mut rng,
+        )?;
+        let w = (0..4)
+            .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+
+        let mut acc = Ciphertext::zero(&params);
+        let mut expected = Ciphertext::zero(&params);
+        let mut values = vec![0u64; params.degree()];
+        for (i, wi) in izip!([1, 2, 3, row_size - 1], &w) {
+            let pt = Plaintext::try_encode(wi, Encoding::simd(), &params)?;
+            ek.rotate_mul_acc(&ct, i, &pt, &mut acc)?;
+            expected += &(&ek.rotates_columns_by(&ct, i)? * &pt);
+            for j in 0..params.degree() {
+                let rotated = v[(j / row_size) * row_size + (j + i) % row_size];
+                values[j] = params
+                    .plaintext
+                    .add(values[j], params.plaintext.mul(rotated, wi[j]));
+            }
+        }
+        assert_eq!(acc, expected);
+        assert_eq!(
+            Vec::<u64>::try_decode(&sk.try_decrypt(&acc)?, Encoding::simd())?,
+            values
+        );
+
+        // A zero plaintext leaves the accumulator unchanged.
+        ek.rotate_mul_acc(
+            &ct,
+            1,
+            &Plaintext::zero(Encoding::simd(), &params)?,
+            &mut acc,
+        )?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&sk.try_decrypt(&acc)?, Encoding::simd())?,
+            values
+        );
+
+        let pt = Plaintext::try_encode(&w[0], Encoding::simd(), &params)?;
+        assert!(ek.rotate_mul_acc(&ct, 4, &pt, &mut acc).is_err());
+        let pt = Plaintext::try_encode(&w[0], Encoding::simd_at_level(1), &params)?;
+        assert!(ek.rotate_mul_acc(&ct, 1, &pt, &mut acc).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn shared_across_threads() -> Result<(), Error> {
+        extern crate std;
+        use crate::bfv::{Multiplicator, RelinearizationKey};
+        use alloc::sync::Arc;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<EvaluationKey>();
+        assert_send_sync::<RelinearizationKey>();
+        assert_send_sync::<Multiplicator>();
+
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = Arc::new(
+            EvaluationKeyBuilder::new(&sk)?
+                .enable_column_rotation(1)?
+                .build(&mut rng)?,
+        );
+        let rk = Arc::new(RelinearizationKey::new(&sk, &mut rng)?);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        let mut expected = v.clone();
+        params.plaintext.mul_vec(&mut expected, &v);
+        let expected = (0..params.degree())
+            .map(|j| expected[(j / row_size) * row_size + (j + 1) % row_size])
+            .collect_vec();
+
+        let results = std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| {
+                    let (ek, rk, ct) = (ek.clone(), rk.clone(), &ct);
+                    scope.spawn(move || {
+                        let multiplicator = Multiplicator::default_shared(&rk)?;
+                        let product = multiplicator.multiply(ct, ct)?;
+                        ek.rotates_columns_by(&product, 1)
+                    })
+                })
+                .collect_vec();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Result<Vec<_>, Error>>()
+        })?;
+        for ct in results {
+            let w = Vec::<u64>::try_decode(&sk.try_decrypt(&ct)?, Encoding::simd())?;
+            assert_eq!(w, expected);
+        }
+
+        // A derived Galois key is returned without modifying the shared key.
+        let gk = ek.derive_column_rotation_key(2)?;
+        assert!(!ek.supports_column_rotation_by(2));
+        let w = Vec::<u64>::try_decode(&sk.try_decrypt(&gk.relinearize(&ct)?)?, Encoding::simd())?;
+        for j in 0..row_size {
+            assert_eq!(w[j], v[(j + 2) % row_size]);
+        }
+        assert!(ek.derive_column_rotation_key(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn derive_column_rotation() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+        // The Galois keys of the inner sum generate all the column rotations.
+        let mut ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        for i in [3, 5, 7] {
+            assert!(!ek.supports_column_rotation_by(i));
+            ek.derive_column_rotation(i)?;
+            assert!(ek.supports_column_rotation_by(i));
+
+            let ct2 = ek.rotates_columns_by(&ct, i)?;
+            let w = Vec::<u64>::try_decode(&sk.try_decrypt(&ct2)?, Encoding::simd())?;
+            for j in 0..row_size {
+                assert_eq!(w[j], v[(j + i) % row_size]);
+                assert_eq!(w[row_size + j], v[row_size + (j + i) % row_size]);
+            }
+        }
+        assert!(ek.derive_column_rotation(row_size).is_err());
+        assert_eq!(ek, EvaluationKey::from_bytes(&ek.to_bytes(), &params)?);
+
+        // Derived keys are serialized along with Galois keys sharing a seed.
+        let mut ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotations([2])?
+            .build(&mut rng)?;
+        ek.derive_column_rotation(4)?;
+        assert!(ek.derive_column_rotation(1).is_err());
+        assert_eq!(ek, EvaluationKey::from_bytes(&ek.to_bytes(), &params)?);
+
+        // Composing Galois keys requires the key level to be the ciphertext level.
+        let mut ek = EvaluationKeyBuilder::new_leveled(&sk, 1, 0)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        assert!(ek.derive_column_rotation(3).is_err());
+        Ok(())
+    }
+
     #[test]
     fn expansion() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -807,4 +1706,90 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            for ek in [
+                EvaluationKeyBuilder::new_leveled(&sk, 0, 0)?.build(&mut rng)?,
+                EvaluationKeyBuilder::new_leveled(&sk, 0, 0)?
+                    .enable_inner_sum()?
+                    .enable_expansion(params.degree().ilog2() as usize)?
+                    .build(&mut rng)?,
+            ] {
+                let mut bytes = Vec::new();
+                ek.write_to(&mut bytes)?;
+                let mut r = &bytes[..];
+                assert_eq!(ek, EvaluationKey::read_from(&mut r, &params)?);
+                assert!(r.is_empty());
+                assert!(EvaluationKey::read_from(&mut &bytes[..bytes.len() - 1], &params).is_err());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn chunks() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        for mut builder in [
+            EvaluationKeyBuilder::new(&sk)?,
+            EvaluationKeyBuilder::new_leveled(&sk, 1, 0)?,
+        ] {
+            builder
+                .enable_column_rotations([1, 2])?
+                .enable_row_rotation()?
+                .enable_expansion(2)?;
+            let ek = builder.build(&mut rng)?;
+            let chunks = ek.galois_key_chunks().collect::<Vec<_>>();
+            assert_eq!(
+                chunks
+                    .iter()
+                    .map(|(exponent, _)| *exponent)
+                    .collect::<Vec<_>>(),
+                ek.galois_exponents()
+            );
+
+            let mut loaded = EvaluationKey::from_bytes(&ek.header_to_bytes(), &params)?;
+            assert!(loaded.galois_exponents().is_empty());
+            for (exponent, chunk) in &chunks {
+                assert_eq!(loaded.load_galois_key(chunk)?, *exponent);
+            }
+            assert_eq!(loaded, ek);
+
+            // The row rotation only needs one Galois key.
+            let row = 2 * params.degree() - 1;
+            let mut partial = EvaluationKey::from_bytes(&ek.header_to_bytes(), &params)?;
+            let (_, chunk) = chunks
+                .iter()
+                .find(|(exponent, _)| *exponent == row)
+                .unwrap();
+            partial.load_galois_key(chunk)?;
+            let v = params.plaintext.random_vec(params.degree(), &mut rng);
+            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let ct = sk.try_encrypt_at_level(&pt, ek.ciphertext_level, &mut rng)?;
+            assert_eq!(
+                sk.try_decrypt(&partial.rotates_rows(&ct)?)?,
+                sk.try_decrypt(&ek.rotates_rows(&ct)?)?
+            );
+            assert!(!partial.supports_column_rotation_by(1));
+            assert!(partial.unload_galois_key(row));
+            assert!(!partial.unload_galois_key(row));
+            assert!(partial.rotates_rows(&ct).is_err());
+
+            let other = EvaluationKeyBuilder::new_leveled(&sk, 2, 0)?
+                .enable_row_rotation()?
+                .build(&mut rng)?;
+            let (_, chunk) = other.galois_key_chunks().next().unwrap();
+            assert!(partial.load_galois_key(&chunk).is_err());
+            assert!(partial.load_galois_key(&[1, 2, 3]).is_err());
+        }
+        Ok(())
+    }
 }
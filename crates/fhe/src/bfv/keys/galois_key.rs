@@ -1,18 +1,23 @@
 //! Galois keys for the BFV encryption scheme
 
 use super::key_switching_key::KeySwitchingKey;
-use crate::bfv::{traits::TryConvertFrom, BfvParameters, Ciphertext, SecretKey};
+use super::secret_key::SecretKeyExpanded;
+use crate::bfv::{
+    traits::TryConvertFrom, BfvParameters, Ciphertext, DeserializationConfig, Plaintext, SecretKey,
+    SeedLoss, Sparsity,
+};
 use crate::proto::bfv::{GaloisKey as GaloisKeyProto, KeySwitchingKey as KeySwitchingKeyProto};
 use crate::{Error, Result};
 extern crate alloc;
-use alloc::sync::Arc;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use alloc::vec;
-use fhe_math::rq::{
-    switcher::Switcher, traits::TryConvertFrom as TryConvertFromPoly, Poly, Representation,
-    SubstitutionExponent,
-};
-use rand::{CryptoRng, RngCore};
+use alloc::vec::Vec;
+use fhe_math::rq::{switcher::Switcher, Poly, Representation, SubstitutionExponent};
+use fhe_math::stream::{read_u32, write_u32, Reader, Writer};
+use itertools::izip;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use zeroize::Zeroizing;
 
 /// Galois key for the BFV encryption scheme.
@@ -32,6 +37,53 @@ impl GaloisKey {
         ciphertext_level: usize,
         galois_key_level: usize,
         rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_with_material(
+            &sk.public_key_material(),
+            exponent,
+            ciphertext_level,
+            galois_key_level,
+            rng,
+        )
+    }
+
+    /// Generate a [`GaloisKey`] from the expanded forms of a [`SecretKey`], so
+    /// that the conversions of the secret key can be shared across several
+    /// Galois keys.
+    pub(crate) fn new_with_material<R: RngCore + CryptoRng>(
+        material: &SecretKeyExpanded,
+        exponent: usize,
+        ciphertext_level: usize,
+        galois_key_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_from_secret_poly(
+            material.secret_key(),
+            material.s(ciphertext_level)?,
+            material.s_ntt(galois_key_level)?,
+            exponent,
+            ciphertext_level,
+            galois_key_level,
+            None,
+            rng,
+        )
+    }
+
+    /// Generate a [`GaloisKey`] from a [`SecretKey`], its polynomial `s` in
+    /// power basis in the context of the ciphertext level, and its polynomial
+    /// `s_ntt` in NTT representation in the context of the Galois key level.
+    /// The polynomials c1 of the key switching key are generated from
+    /// `c1_seed` when specified, and from a seed sampled from `rng` otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_from_secret_poly<R: RngCore + CryptoRng>(
+        sk: &SecretKey,
+        s: &Poly,
+        s_ntt: &Poly,
+        exponent: usize,
+        ciphertext_level: usize,
+        galois_key_level: usize,
+        c1_seed: Option<<ChaCha8Rng as SeedableRng>::Seed>,
+        rng: &mut R,
     ) -> Result<Self> {
         let ctx_galois_key = sk.par.ctx_at_level(galois_key_level)?;
         let ctx_ciphertext = sk.par.ctx_at_level(ciphertext_level)?;
@@ -40,23 +92,30 @@ impl GaloisKey {
             SubstitutionExponent::new(ctx_ciphertext, exponent).map_err(Error::MathError)?;
 
         let switcher_up = Switcher::new(ctx_ciphertext, ctx_galois_key)?;
-        let s = Zeroizing::new(Poly::try_convert_from(
-            sk.coeffs.as_ref(),
-            ctx_ciphertext,
-            false,
-            Representation::PowerBasis,
-        )?);
         let s_sub = Zeroizing::new(s.substitute(&ciphertext_exponent)?);
         let mut s_sub_switched_up = Zeroizing::new(s_sub.mod_switch_to(&switcher_up)?);
         s_sub_switched_up.change_representation(Representation::PowerBasis);
 
-        let ksk = KeySwitchingKey::new(
-            sk,
-            &s_sub_switched_up,
-            ciphertext_level,
-            galois_key_level,
-            rng,
-        )?;
+        let ksk = if let Some(seed) = c1_seed {
+            KeySwitchingKey::new_from_secret_poly_with_seed(
+                sk,
+                s_ntt,
+                &s_sub_switched_up,
+                ciphertext_level,
+                galois_key_level,
+                seed,
+                rng,
+            )?
+        } else {
+            KeySwitchingKey::new_from_secret_poly(
+                sk,
+                s_ntt,
+                &s_sub_switched_up,
+                ciphertext_level,
+                galois_key_level,
+                rng,
+            )?
+        };
 
         Ok(Self {
             element: ciphertext_exponent,
@@ -64,14 +123,139 @@ impl GaloisKey {
         })
     }
 
+    /// Returns the Galois key of exponent `self.exponent * other.exponent`,
+    /// computed without the secret key: the automorphism of `other` is applied
+    /// to the key switching key of `self`, which is then switched back to the
+    /// secret key with `other`. The error of the output is the error of `self`
+    /// plus the error of a key switching with `other`.
+    ///
+    /// The two keys must have the same levels, and their ciphertext and key
+    /// levels must be equal.
+    pub(crate) fn compose(&self, other: &GaloisKey) -> Result<GaloisKey> {
+        let (ksk, other_ksk) = (&self.ksk, &other.ksk);
+        if ksk.ciphertext_level != ksk.ksk_level
+            || other_ksk.ciphertext_level != ksk.ciphertext_level
+            || other_ksk.ksk_level != ksk.ksk_level
+        {
+            return Err(Error::DefaultError(
+                "Galois keys can only be composed when all their levels are equal".to_string(),
+            ));
+        }
+
+        let element = SubstitutionExponent::new(
+            &ksk.ctx_ciphertext,
+            self.element.exponent * other.element.exponent,
+        )
+        .map_err(Error::MathError)?;
+
+        let mut c0 = Vec::with_capacity(ksk.c0.len());
+        let mut c1 = Vec::with_capacity(ksk.c1.len());
+        for (c0_i, c1_i) in izip!(ksk.c0.iter(), ksk.c1.iter()) {
+            // (c0_i, c1_i) encrypts g_i * s(x^(k1 k2)) under s(x^k2) after the
+            // substitution, and c1_i * s(x^k2) is switched to s.
+            let mut c1_i = c1_i.substitute(&other.element)?;
+            c1_i.change_representation(Representation::PowerBasis);
+            let (d0, mut d1) = other_ksk.key_switch(&c1_i)?;
+
+            let mut c0_i = c0_i.substitute(&other.element)?;
+            c0_i.change_representation(Representation::Ntt);
+            c0_i += &d0;
+
+            unsafe {
+                c0_i.allow_variable_time_computations();
+                d1.allow_variable_time_computations();
+            }
+            c0_i.change_representation(Representation::NttShoup);
+            d1.change_representation(Representation::NttShoup);
+            c0.push(c0_i);
+            c1.push(d1);
+        }
+
+        Ok(Self {
+            element,
+            ksk: KeySwitchingKey {
+                par: ksk.par.clone(),
+                seed: None,
+                c0: c0.into_boxed_slice(),
+                c1: c1.into_boxed_slice(),
+                ciphertext_level: ksk.ciphertext_level,
+                ctx_ciphertext: ksk.ctx_ciphertext.clone(),
+                ksk_level: ksk.ksk_level,
+                ctx_ksk: ksk.ctx_ksk.clone(),
+                log_base: ksk.log_base,
+            },
+        })
+    }
+
     /// Relinearize a [`Ciphertext`] using the [`GaloisKey`]
     pub fn relinearize(&self, ct: &Ciphertext) -> Result<Ciphertext> {
         // assert_eq!(ct.par, self.ksk.par);
         assert_eq!(ct.len(), 2);
 
-        let mut c2 = ct[1].substitute(&self.element)?;
-        c2.change_representation(Representation::PowerBasis);
-        let (mut c0, mut c1) = self.ksk.key_switch(&c2)?;
+        let (c0, c1) = self.switch(ct)?;
+        Ok(Ciphertext {
+            par: ct.par.clone(),
+            seed: None,
+            c: vec![c0, c1],
+            level: self.ksk.ciphertext_level,
+        })
+    }
+
+    /// Computes `acc += relinearize(ct) * pt`, where the polynomials of the
+    /// relinearized ciphertext are multiplied by the plaintext and added to
+    /// the accumulator in NTT representation, without allocating the
+    /// intermediate ciphertexts. An empty accumulator is replaced by the
+    /// product.
+    pub(crate) fn relinearize_mul_acc(
+        &self,
+        ct: &Ciphertext,
+        pt: &Plaintext,
+        acc: &mut Ciphertext,
+    ) -> Result<()> {
+        if ct.len() != 2 {
+            return Err(Error::DefaultError(
+                "The ciphertext must have two parts".to_string(),
+            ));
+        }
+        if ct.par != pt.par || ct.level != pt.level {
+            return Err(Error::DefaultError(
+                "The plaintext must have the parameters and the level of the ciphertext"
+                    .to_string(),
+            ));
+        }
+        if !acc.is_empty() && (acc.par != ct.par || acc.level != ct.level || acc.len() != 2) {
+            return Err(Error::DefaultError(
+                "The accumulator must have two parts at the level of the ciphertext".to_string(),
+            ));
+        }
+        if pt.sparsity != Sparsity::Dense {
+            *acc += &(&self.relinearize(ct)? * pt);
+            return Ok(());
+        }
+
+        let (mut c0, mut c1) = self.switch(ct)?;
+        c0 *= &pt.poly_ntt;
+        c1 *= &pt.poly_ntt;
+        if acc.is_empty() {
+            *acc = Ciphertext {
+                par: ct.par.clone(),
+                seed: None,
+                c: vec![c0, c1],
+                level: ct.level,
+            };
+        } else {
+            acc[0] += &c0;
+            acc[1] += &c1;
+            acc.drop_seed(SeedLoss::KeySwitching);
+        }
+        Ok(())
+    }
+
+    /// Returns the polynomials of the relinearized ciphertext, in NTT
+    /// representation.
+    fn switch(&self, ct: &Ciphertext) -> Result<(Poly, Poly)> {
+        let c2 = ct[1].substitute(&self.element)?;
+        let (mut c0, mut c1) = self.ksk.key_switch_ntt(&c2)?;
 
         if c0.ctx() != ct[0].ctx() {
             c0.change_representation(Representation::PowerBasis);
@@ -83,13 +267,26 @@ impl GaloisKey {
         }
 
         c0 += &ct[0].substitute(&self.element)?;
+        Ok((c0, c1))
+    }
+}
 
-        Ok(Ciphertext {
-            par: ct.par.clone(),
-            seed: None,
-            c: vec![c0, c1],
-            level: self.ksk.ciphertext_level,
-        })
+impl GaloisKey {
+    /// Writes the Galois key to `w` in the streaming format (see
+    /// [`fhe_math::stream`]): its exponent, followed by its key switching key.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        write_u32(w, self.element.exponent as u32)?;
+        self.ksk.write_to(w)
+    }
+
+    /// Reads a Galois key from `r`, in the streaming format of
+    /// [`GaloisKey::write_to`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        let exponent = read_u32(r)? as usize;
+        let ksk = KeySwitchingKey::read_from(r, par)?;
+        let ctx = par.ctx_at_level(ksk.ciphertext_level)?;
+        let element = SubstitutionExponent::new(ctx, exponent).map_err(Error::MathError)?;
+        Ok(GaloisKey { element, ksk })
     }
 }
 
@@ -104,8 +301,20 @@ impl From<&GaloisKey> for GaloisKeyProto {
 
 impl TryConvertFrom<&GaloisKeyProto> for GaloisKey {
     fn try_convert_from(value: &GaloisKeyProto, par: &Arc<BfvParameters>) -> Result<Self> {
-        if value.ksk.is_some() {
-            let ksk = KeySwitchingKey::try_convert_from(value.ksk.as_ref().unwrap(), par)?;
+        Self::try_convert_from_with_config(value, par, &DeserializationConfig::keys())
+    }
+}
+
+impl GaloisKey {
+    /// Converts a protobuf Galois key, with the limits and options of
+    /// `config`.
+    pub(crate) fn try_convert_from_with_config(
+        value: &GaloisKeyProto,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        if let Some(ksk) = value.ksk.as_ref() {
+            let ksk = KeySwitchingKey::try_convert_from_with_config(ksk, par, config)?;
 
             let ctx = par.ctx_at_level(ksk.ciphertext_level)?;
             let element = SubstitutionExponent::new(ctx, value.exponent as usize)
@@ -194,4 +403,25 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(6, 16),
+            BfvParameters::default_arc(4, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            for gk in [
+                GaloisKey::new(&sk, 9, 0, 0, &mut rng)?,
+                GaloisKey::new(&sk, 3, 1, 2, &mut rng)?,
+            ] {
+                let mut bytes = Vec::new();
+                gk.write_to(&mut bytes)?;
+                assert_eq!(gk, GaloisKey::read_from(&mut &bytes[..], &params)?);
+                assert!(GaloisKey::read_from(&mut &bytes[..bytes.len() - 1], &params).is_err());
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,18 +1,23 @@
 //! Key-switching keys for the BFV encryption scheme
 
-use crate::bfv::{traits::TryConvertFrom as BfvTryConvertFrom, BfvParameters, SecretKey};
+use crate::bfv::keys::secret_key::SecretKeyExpanded;
+use crate::bfv::{
+    traits::TryConvertFrom as BfvTryConvertFrom, BfvParameters, Ciphertext, DeserializationConfig,
+    SecretKey,
+};
 use crate::proto::bfv::KeySwitchingKey as KeySwitchingKeyProto;
 use crate::{Error, Result};
-use fhe_math::rq::traits::TryConvertFrom;
-use fhe_math::rq::Context;
+use fhe_math::rq::{switcher::Switcher, Context};
 use fhe_math::{
     rns::RnsContext,
-    rq::{Poly, Representation},
+    rq::{LazyAccumulator, Poly, Representation},
+    stream::{read_u32, write_u32, Reader, Writer},
 };
-use fhe_traits::{DeserializeWithContext, Serialize};
+use fhe_traits::{DeserializeParametrized, DeserializeWithContext, FheParametrized, Serialize};
 use itertools::{izip, Itertools};
 use num_bigint::BigUint;
-use rand::{Rng, RngCore, SeedableRng};
+use prost::Message;
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 extern crate alloc;
 use alloc::boxed::Box;
@@ -58,6 +63,95 @@ impl KeySwitchingKey {
         ciphertext_level: usize,
         ksk_level: usize,
         rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_with_material(
+            &sk.public_key_material(),
+            from,
+            ciphertext_level,
+            ksk_level,
+            rng,
+        )
+    }
+
+    /// Generate a [`KeySwitchingKey`] from the secret key `from` to the secret
+    /// key `to`, with which [`Ciphertext::switch_key`] turns a ciphertext
+    /// encrypted under `from` into an encryption of the same plaintext under
+    /// `to`, e.g. for proxy re-encryption.
+    pub fn new_from_keys<R: RngCore + CryptoRng>(
+        from: &SecretKey,
+        to: &SecretKey,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_from_keys_leveled(from, to, 0, 0, rng)
+    }
+
+    /// Generate a [`KeySwitchingKey`] as in [`Self::new_from_keys`], switching
+    /// the ciphertexts at `ciphertext_level` with a key at `ksk_level`.
+    pub fn new_from_keys_leveled<R: RngCore + CryptoRng>(
+        from: &SecretKey,
+        to: &SecretKey,
+        ciphertext_level: usize,
+        ksk_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        if from.par != to.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        let ctx_ksk = from.par.ctx_at_level(ksk_level)?;
+        let ctx_ciphertext = from.par.ctx_at_level(ciphertext_level)?;
+        let material = from.public_key_material();
+        let switcher_up = Switcher::new(ctx_ciphertext, ctx_ksk)?;
+        let s_switched_up =
+            Zeroizing::new(material.s(ciphertext_level)?.mod_switch_to(&switcher_up)?);
+        Self::new(to, &s_switched_up, ciphertext_level, ksk_level, rng)
+    }
+
+    /// Generate a [`KeySwitchingKey`] from the expanded forms of a
+    /// [`SecretKey`], so that they can be shared across several keys.
+    pub(crate) fn new_with_material<R: RngCore>(
+        material: &SecretKeyExpanded,
+        from: &Poly,
+        ciphertext_level: usize,
+        ksk_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_from_secret_poly(
+            material.secret_key(),
+            material.s_ntt(ksk_level)?,
+            from,
+            ciphertext_level,
+            ksk_level,
+            rng,
+        )
+    }
+
+    /// Generate a [`KeySwitchingKey`] from a [`SecretKey`] and its polynomial
+    /// `s` in NTT representation in the context of the key switching key.
+    pub(crate) fn new_from_secret_poly<R: RngCore>(
+        sk: &SecretKey,
+        s: &Poly,
+        from: &Poly,
+        ciphertext_level: usize,
+        ksk_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+        rng.fill(&mut seed);
+        Self::new_from_secret_poly_with_seed(sk, s, from, ciphertext_level, ksk_level, seed, rng)
+    }
+
+    /// Generate a [`KeySwitchingKey`] as in [`Self::new_from_secret_poly`],
+    /// with the polynomials c1 generated from `seed`.
+    pub(crate) fn new_from_secret_poly_with_seed<R: RngCore>(
+        sk: &SecretKey,
+        s: &Poly,
+        from: &Poly,
+        ciphertext_level: usize,
+        ksk_level: usize,
+        seed: <ChaCha8Rng as SeedableRng>::Seed,
+        rng: &mut R,
     ) -> Result<Self> {
         let ctx_ksk = sk.par.ctx_at_level(ksk_level)?;
         let ctx_ciphertext = sk.par.ctx_at_level(ciphertext_level)?;
@@ -68,16 +162,13 @@ impl KeySwitchingKey {
             ));
         }
 
-        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
-        rng.fill(&mut seed);
-
         if ctx_ksk.moduli().len() == 1 {
             let modulus = ctx_ksk.moduli().first().unwrap();
             let log_modulus = modulus.next_power_of_two().ilog2() as usize;
             let log_base = log_modulus / 2;
 
-            let c1 = Self::generate_c1(ctx_ksk, seed, (log_modulus + log_base - 1) / log_base);
-            let c0 = Self::generate_c0_decomposition(sk, from, &c1, rng, log_base)?;
+            let c1 = Self::generate_c1(ctx_ksk, seed, log_modulus.div_ceil(log_base));
+            let c0 = Self::generate_c0_decomposition(sk, s, from, &c1, rng, log_base)?;
 
             Ok(Self {
                 par: sk.par.clone(),
@@ -92,7 +183,7 @@ impl KeySwitchingKey {
             })
         } else {
             let c1 = Self::generate_c1(ctx_ksk, seed, ctx_ciphertext.moduli().len());
-            let c0 = Self::generate_c0(sk, from, &c1, rng)?;
+            let c0 = Self::generate_c0(sk, s, from, &c1, rng)?;
 
             Ok(Self {
                 par: sk.par.clone(),
@@ -126,9 +217,101 @@ impl KeySwitchingKey {
         c1
     }
 
+    /// Writes the key switching key to `w` in the streaming format (see
+    /// [`fhe_math::stream`]), one polynomial at a time. The polynomials c1 are
+    /// replaced by their seed when the key has one.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        write_u32(w, self.ciphertext_level as u32)?;
+        write_u32(w, self.ksk_level as u32)?;
+        write_u32(w, self.log_base as u32)?;
+        match self.seed.as_ref() {
+            Some(seed) => {
+                w.write_all(&[1])?;
+                w.write_all(seed)?;
+            }
+            None => w.write_all(&[0])?,
+        }
+        for c0 in self.c0.iter() {
+            c0.write_to(w)?;
+        }
+        if self.seed.is_none() {
+            for c1 in self.c1.iter() {
+                c1.write_to(w)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a key switching key from `r`, in the streaming format of
+    /// [`KeySwitchingKey::write_to`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        let ciphertext_level = read_u32(r)? as usize;
+        let ksk_level = read_u32(r)? as usize;
+        let log_base = read_u32(r)? as usize;
+        let ctx_ksk = par.ctx_at_level(ksk_level)?;
+        let ctx_ciphertext = par.ctx_at_level(ciphertext_level)?;
+        let c0_size = Self::c0_size(par, ciphertext_level, ksk_level, log_base)?;
+
+        let mut flag = [0u8];
+        r.read_exact(&mut flag)?;
+        let seed = match flag[0] {
+            0 => None,
+            1 => {
+                let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+                r.read_exact(&mut seed)?;
+                Some(seed)
+            }
+            _ => return Err(Error::DefaultError("Invalid seed flag".to_string())),
+        };
+
+        let c0 = (0..c0_size)
+            .map(|_| Ok(Poly::read_from(r, ctx_ksk)?))
+            .collect::<Result<Vec<Poly>>>()?;
+        let c1 = match seed {
+            Some(seed) => Self::generate_c1(ctx_ksk, seed, c0_size),
+            None => (0..c0_size)
+                .map(|_| Ok(Poly::read_from(r, ctx_ksk)?))
+                .collect::<Result<Vec<Poly>>>()?,
+        };
+
+        Ok(Self {
+            par: par.clone(),
+            seed,
+            c0: c0.into_boxed_slice(),
+            c1: c1.into_boxed_slice(),
+            ciphertext_level,
+            ctx_ciphertext: ctx_ciphertext.clone(),
+            ksk_level,
+            ctx_ksk: ctx_ksk.clone(),
+            log_base,
+        })
+    }
+
+    /// Returns the number of polynomials c0 of a key switching key with the
+    /// given levels and decomposition size, or an error if the decomposition
+    /// size is specified but the levels are not maximal.
+    fn c0_size(
+        par: &BfvParameters,
+        ciphertext_level: usize,
+        ksk_level: usize,
+        log_base: usize,
+    ) -> Result<usize> {
+        if log_base == 0 {
+            Ok(par.ctx_at_level(ciphertext_level)?.moduli().len())
+        } else if ksk_level != par.max_level() || ciphertext_level != par.max_level() {
+            Err(Error::DefaultError(
+                "A decomposition size is specified but the levels are not maximal".to_string(),
+            ))
+        } else {
+            let log_modulus = par.moduli().first().unwrap().next_power_of_two().ilog2() as usize;
+            Ok(log_modulus.div_ceil(log_base))
+        }
+    }
+
     /// Generate the c0's from the c1's and the secret key
     fn generate_c0<R: RngCore>(
         sk: &SecretKey,
+        s: &Poly,
         from: &Poly,
         c1: &[Poly],
         rng: &mut R,
@@ -144,14 +327,6 @@ impl KeySwitchingKey {
 
         let size = c1.len();
 
-        let mut s = Zeroizing::new(Poly::try_convert_from(
-            sk.coeffs.as_ref(),
-            c1[0].ctx(),
-            false,
-            Representation::PowerBasis,
-        )?);
-        s.change_representation(Representation::Ntt);
-
         let rns = RnsContext::new(&sk.par.moduli[..size])?;
         let c0 = c1
             .iter()
@@ -160,7 +335,7 @@ impl KeySwitchingKey {
                 let mut a_s = Zeroizing::new(c1i.clone());
                 a_s.disallow_variable_time_computations();
                 a_s.change_representation(Representation::Ntt);
-                *a_s.as_mut() *= s.as_ref();
+                *a_s.as_mut() *= s;
                 a_s.change_representation(Representation::PowerBasis);
 
                 let mut b =
@@ -184,6 +359,7 @@ impl KeySwitchingKey {
     /// Generate the c0's from the c1's and the secret key
     fn generate_c0_decomposition<R: RngCore>(
         sk: &SecretKey,
+        s: &Poly,
         from: &Poly,
         c1: &[Poly],
         rng: &mut R,
@@ -199,14 +375,6 @@ impl KeySwitchingKey {
             ));
         }
 
-        let mut s = Zeroizing::new(Poly::try_convert_from(
-            sk.coeffs.as_ref(),
-            c1[0].ctx(),
-            false,
-            Representation::PowerBasis,
-        )?);
-        s.change_representation(Representation::Ntt);
-
         let c0 = c1
             .iter()
             .enumerate()
@@ -214,7 +382,7 @@ impl KeySwitchingKey {
                 let mut a_s = Zeroizing::new(c1i.clone());
                 a_s.disallow_variable_time_computations();
                 a_s.change_representation(Representation::Ntt);
-                *a_s.as_mut() *= s.as_ref();
+                *a_s.as_mut() *= s;
                 a_s.change_representation(Representation::PowerBasis);
 
                 let mut b =
@@ -249,24 +417,69 @@ impl KeySwitchingKey {
             return Err(Error::DefaultError("Incorrect representation".to_string()));
         }
 
-        let mut c0 = Poly::zero(&self.ctx_ksk, Representation::Ntt);
-        let mut c1 = Poly::zero(&self.ctx_ksk, Representation::Ntt);
+        let mut c0 = LazyAccumulator::new(&self.ctx_ksk)?;
+        let mut c1 = LazyAccumulator::new(&self.ctx_ksk)?;
         for (c2_i_coefficients, c0_i, c1_i) in izip!(
             p.coefficients().outer_iter(),
             self.c0.iter(),
             self.c1.iter()
         ) {
-            let mut c2_i = unsafe {
+            let c2_i = unsafe {
                 Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
                     c2_i_coefficients.as_slice().unwrap(),
                     &self.ctx_ksk,
                 )
             };
-            c0 += &(&c2_i * c0_i);
-            c2_i *= c1_i;
-            c1 += &c2_i;
+            c0.add_product(&c2_i, c0_i)?;
+            c1.add_product(&c2_i, c1_i)?;
         }
-        Ok((c0, c1))
+        Ok((c0.finish(), c1.finish()))
+    }
+
+    /// Key switch a polynomial in any representation.
+    ///
+    /// When the polynomial is in NTT representation and the key switching key
+    /// has the context of the polynomial, the NTT of the `i`-th RNS component
+    /// of the polynomial modulo the `i`-th modulus is the `i`-th row of the
+    /// polynomial, so that one NTT per modulus is saved compared to
+    /// [`KeySwitchingKey::key_switch`].
+    pub(crate) fn key_switch_ntt(&self, p: &Poly) -> Result<(Poly, Poly)> {
+        let mut p_power_basis = p.clone();
+        p_power_basis.change_representation(Representation::PowerBasis);
+        if p.representation() != &Representation::Ntt
+            || self.log_base != 0
+            || self.ctx_ksk.as_ref() != self.ctx_ciphertext.as_ref()
+        {
+            return self.key_switch(&p_power_basis);
+        }
+        if p.ctx().as_ref() != self.ctx_ciphertext.as_ref() {
+            return Err(Error::DefaultError(
+                "The input polynomial does not have the correct context.".to_string(),
+            ));
+        }
+
+        let mut c0 = LazyAccumulator::new(&self.ctx_ksk)?;
+        let mut c1 = LazyAccumulator::new(&self.ctx_ksk)?;
+        for (i, (c2_i_coefficients, c2_i_ntt, c0_i, c1_i)) in izip!(
+            p_power_basis.coefficients().outer_iter(),
+            p.coefficients().outer_iter(),
+            self.c0.iter(),
+            self.c1.iter()
+        )
+        .enumerate()
+        {
+            let c2_i = unsafe {
+                Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_ntt_row(
+                    c2_i_coefficients.as_slice().unwrap(),
+                    i,
+                    c2_i_ntt.as_slice().unwrap(),
+                    &self.ctx_ksk,
+                )
+            };
+            c0.add_product(&c2_i, c0_i)?;
+            c1.add_product(&c2_i, c1_i)?;
+        }
+        Ok((c0.finish(), c1.finish()))
     }
 
     /// Key switch a polynomial.
@@ -291,25 +504,64 @@ impl KeySwitchingKey {
         let mut coefficients = p.coefficients().to_slice().unwrap().to_vec();
         let mut c2i = vec![];
         let mask = (1u64 << self.log_base) - 1;
-        (0..(log_modulus + self.log_base - 1) / self.log_base).for_each(|_| {
+        (0..log_modulus.div_ceil(self.log_base)).for_each(|_| {
             c2i.push(coefficients.iter().map(|c| c & mask).collect_vec());
             coefficients.iter_mut().for_each(|c| *c >>= self.log_base);
         });
 
-        let mut c0 = Poly::zero(&self.ctx_ksk, Representation::Ntt);
-        let mut c1 = Poly::zero(&self.ctx_ksk, Representation::Ntt);
+        let mut c0 = LazyAccumulator::new(&self.ctx_ksk)?;
+        let mut c1 = LazyAccumulator::new(&self.ctx_ksk)?;
         for (c2_i_coefficients, c0_i, c1_i) in izip!(c2i.iter(), self.c0.iter(), self.c1.iter()) {
-            let mut c2_i = unsafe {
+            let c2_i = unsafe {
                 Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
                     c2_i_coefficients.as_slice(),
                     &self.ctx_ksk,
                 )
             };
-            c0 += &(&c2_i * c0_i);
-            c2_i *= c1_i;
-            c1 += &c2_i;
+            c0.add_product(&c2_i, c0_i)?;
+            c1.add_product(&c2_i, c1_i)?;
+        }
+        Ok((c0.finish(), c1.finish()))
+    }
+}
+
+impl Ciphertext {
+    /// Switches the secret key under which the ciphertext is encrypted, using
+    /// a key switching key generated by [`KeySwitchingKey::new_from_keys`].
+    ///
+    /// The ciphertext must have two parts and be at the ciphertext level of
+    /// the key switching key. The output encrypts the same plaintext under
+    /// the secret key `to`, with the additional noise of a key switching.
+    pub fn switch_key(&self, ksk: &KeySwitchingKey) -> Result<Ciphertext> {
+        if self.par != ksk.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        if self.len() != 2 {
+            return Err(Error::DefaultError(
+                "Only supports key switching of ciphertexts with 2 parts".to_string(),
+            ));
+        }
+        if self.level != ksk.ciphertext_level {
+            return Err(Error::DefaultError(
+                "Ciphertext has incorrect level".to_string(),
+            ));
         }
-        Ok((c0, c1))
+
+        let mut c1 = self[1].clone();
+        c1.change_representation(Representation::PowerBasis);
+        let (mut c0, mut c1) = ksk.key_switch(&c1)?;
+        if c0.ctx() != self[0].ctx() {
+            c0.change_representation(Representation::PowerBasis);
+            c1.change_representation(Representation::PowerBasis);
+            c0.mod_switch_down_to(self[0].ctx())?;
+            c1.mod_switch_down_to(self[1].ctx())?;
+            c0.change_representation(Representation::Ntt);
+            c1.change_representation(Representation::Ntt);
+        }
+        c0 += &self[0];
+        Ciphertext::new(vec![c0, c1], &self.par)
     }
 }
 
@@ -337,32 +589,31 @@ impl From<&KeySwitchingKey> for KeySwitchingKeyProto {
 
 impl BfvTryConvertFrom<&KeySwitchingKeyProto> for KeySwitchingKey {
     fn try_convert_from(value: &KeySwitchingKeyProto, par: &Arc<BfvParameters>) -> Result<Self> {
+        Self::try_convert_from_with_config(value, par, &DeserializationConfig::keys())
+    }
+}
+
+impl KeySwitchingKey {
+    /// Converts a protobuf key switching key, with the limits and options of
+    /// `config`.
+    pub(crate) fn try_convert_from_with_config(
+        value: &KeySwitchingKeyProto,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
         let ciphertext_level = value.ciphertext_level as usize;
         let ksk_level = value.ksk_level as usize;
         let ctx_ksk = par.ctx_at_level(ksk_level)?;
         let ctx_ciphertext = par.ctx_at_level(ciphertext_level)?;
-
-        let c0_size: usize;
-        let log_base = value.log_base as usize;
-        if log_base != 0 {
-            if ksk_level != par.max_level() || ciphertext_level != par.max_level() {
-                return Err(Error::DefaultError(
-                    "A decomposition size is specified but the levels are not maximal".to_string(),
-                ));
-            } else {
-                let log_modulus: usize =
-                    par.moduli().first().unwrap().next_power_of_two().ilog2() as usize;
-                c0_size = (log_modulus + log_base - 1) / log_base;
-            }
-        } else {
-            c0_size = ctx_ciphertext.moduli().len();
-        }
+        let c0_size =
+            KeySwitchingKey::c0_size(par, ciphertext_level, ksk_level, value.log_base as usize)?;
 
         if value.c0.len() != c0_size {
             return Err(Error::DefaultError(
                 "Incorrect number of values in c0".to_string(),
             ));
         }
+        config.check_polys(2 * c0_size)?;
 
         let seed = if value.seed.is_empty() {
             if value.c1.len() != c0_size {
@@ -379,7 +630,7 @@ impl BfvTryConvertFrom<&KeySwitchingKeyProto> for KeySwitchingKey {
             Some(unwrapped.unwrap())
         };
 
-        let c1 = if let Some(seed) = seed {
+        let mut c1 = if let Some(seed) = seed {
             Self::generate_c1(ctx_ksk, seed, value.c0.len())
         } else {
             value
@@ -389,11 +640,14 @@ impl BfvTryConvertFrom<&KeySwitchingKeyProto> for KeySwitchingKey {
                 .collect::<Result<Vec<Poly>>>()?
         };
 
-        let c0 = value
+        let mut c0 = value
             .c0
             .iter()
             .map(|c0i| Poly::from_bytes(c0i, ctx_ksk).map_err(Error::MathError))
             .collect::<Result<Vec<Poly>>>()?;
+        c0.iter_mut()
+            .chain(c1.iter_mut())
+            .for_each(|p| config.apply_vt(p));
 
         Ok(Self {
             par: par.clone(),
@@ -409,13 +663,56 @@ impl BfvTryConvertFrom<&KeySwitchingKeyProto> for KeySwitchingKey {
     }
 }
 
+impl Serialize for KeySwitchingKey {
+    fn to_bytes(&self) -> Vec<u8> {
+        KeySwitchingKeyProto::from(self).encode_to_vec()
+    }
+}
+
+impl FheParametrized for KeySwitchingKey {
+    type Parameters = BfvParameters;
+}
+
+impl DeserializeParametrized for KeySwitchingKey {
+    type Error = Error;
+
+    /// Deserializes a key switching key with the limits of the default
+    /// [`DeserializationConfig`], keeping the variable-time computations of
+    /// its public polynomials.
+    fn from_bytes(bytes: &[u8], par: &Arc<Self::Parameters>) -> Result<Self> {
+        Self::from_bytes_with_config(bytes, par, &DeserializationConfig::keys())
+    }
+}
+
+impl KeySwitchingKey {
+    /// Deserializes a key switching key, with the limits and options of
+    /// `config`.
+    pub fn from_bytes_with_config(
+        bytes: &[u8],
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        config.check_bytes(bytes.len())?;
+        let ksk = Message::decode(bytes);
+        if let Ok(ksk) = ksk {
+            KeySwitchingKey::try_convert_from_with_config(&ksk, par, config)
+        } else {
+            Err(Error::DefaultError("Invalid serialization".to_string()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::bfv::{
-        keys::key_switching_key::KeySwitchingKey, traits::TryConvertFrom, BfvParameters, SecretKey,
+        keys::key_switching_key::KeySwitchingKey, traits::TryConvertFrom, BfvParameters,
+        Ciphertext, Encoding, Plaintext, SecretKey,
     };
     use crate::proto::bfv::KeySwitchingKey as KeySwitchingKeyProto;
     use crate::Error;
+    use fhe_traits::{
+        DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, Serialize,
+    };
     extern crate alloc;
 
     use alloc::vec::Vec;
@@ -484,6 +781,7 @@ mod tests {
                 c2.change_representation(Representation::PowerBasis);
 
                 input.change_representation(Representation::Ntt);
+                assert_eq!(ksk.key_switch_ntt(&input)?, (c0.clone(), c1.clone()));
                 p.change_representation(Representation::Ntt);
                 let mut c3 = &input * &p;
                 c3.change_representation(Representation::PowerBasis);
@@ -551,7 +849,42 @@ mod tests {
             let ksk = KeySwitchingKey::new(&sk, &p, 0, 0, &mut rng)?;
             let ksk_proto = KeySwitchingKeyProto::from(&ksk);
             assert_eq!(ksk, KeySwitchingKey::try_convert_from(&ksk_proto, &params)?);
+            assert_eq!(ksk, KeySwitchingKey::from_bytes(&ksk.to_bytes(), &params)?);
         }
         Ok(())
     }
+
+    #[test]
+    fn switch_key() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let from = SecretKey::random(&params, &mut rng);
+        let to = SecretKey::random(&params, &mut rng);
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::poly(), &params)?;
+
+        let ksk = KeySwitchingKey::new_from_keys(&from, &to, &mut rng)?;
+        let ct: Ciphertext = from.try_encrypt(&pt, &mut rng)?;
+        let switched = ct.switch_key(&ksk)?;
+        assert_eq!(to.try_decrypt(&switched)?, pt);
+        assert_ne!(from.try_decrypt(&switched)?, pt);
+
+        // Switching a ciphertext at a lower level, with a key at level 0.
+        let level = 2;
+        let ksk = KeySwitchingKey::new_from_keys_leveled(&from, &to, level, 0, &mut rng)?;
+        let mut ct: Ciphertext = from.try_encrypt(&pt, &mut rng)?;
+        assert!(ct.switch_key(&ksk).is_err());
+        ct.mod_switch_to_level(level)?;
+        let switched = ct.switch_key(&ksk)?;
+        assert_eq!(switched.level(), level);
+        assert_eq!(
+            Vec::<u64>::try_decode(&to.try_decrypt(&switched)?, Encoding::poly())?,
+            values
+        );
+
+        let other = BfvParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&other, &mut rng);
+        assert!(KeySwitchingKey::new_from_keys(&from, &sk, &mut rng).is_err());
+        Ok(())
+    }
 }
@@ -1,14 +1,17 @@
+mod decryptor;
 mod evaluation_key;
 mod galois_key;
 mod key_switching_key;
 mod public_key;
 mod relinearization_key;
+mod ring_switching_key;
 mod secret_key;
 
+pub use decryptor::Decryptor;
 pub use evaluation_key::{EvaluationKey, EvaluationKeyBuilder};
 pub use galois_key::GaloisKey;
-pub use public_key::PublicKey;
+pub use key_switching_key::KeySwitchingKey;
+pub use public_key::{refresh_with_public_key, PublicKey, REFRESH_STATISTICAL_SECURITY};
 pub use relinearization_key::RelinearizationKey;
-pub use secret_key::SecretKey;
-
-pub(crate) use key_switching_key::KeySwitchingKey;
+pub use ring_switching_key::RingSwitchingKey;
+pub use secret_key::{SecretKey, SecretKeyExpanded};
@@ -1,20 +1,25 @@
 //! Public keys for the BFV encryption scheme
 
-use crate::bfv::traits::TryConvertFrom;
-use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext};
+use crate::bfv::{BfvParameters, Ciphertext, DeserializationConfig, Encoding, Plaintext};
 use crate::proto::bfv::{Ciphertext as CiphertextProto, PublicKey as PublicKeyProto};
 use crate::{Error, Result};
-use fhe_math::rq::{Poly, Representation};
-use fhe_traits::{DeserializeParametrized, FheEncrypter, FheParametrized, Serialize};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
+use fhe_math::stream::{Reader, Writer};
+use fhe_traits::{
+    DeserializeParametrized, FheDecrypter, FheEncoder, FheEncrypter, FheParametrized, Serialize,
+};
+use num_bigint::BigUint;
+use num_traits::One;
 use prost::Message;
-use rand::RngCore;
+use rand::{CryptoRng, RngCore};
 extern crate alloc;
+use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 use zeroize::Zeroizing;
 
-use super::SecretKey;
+use super::{SecretKey, SecretKeyExpanded};
 
 /// Public key for the BFV encryption scheme.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -26,8 +31,16 @@ pub struct PublicKey {
 impl PublicKey {
     /// Generate a new [`PublicKey`] from a [`SecretKey`].
     pub fn new<R: RngCore>(sk: &SecretKey, rng: &mut R) -> Self {
+        Self::new_with_material(&sk.public_key_material(), rng)
+    }
+
+    /// Generate a new [`PublicKey`] from the expanded forms of a [`SecretKey`]
+    /// returned by [`SecretKey::public_key_material`].
+    pub fn new_with_material<R: RngCore>(material: &SecretKeyExpanded, rng: &mut R) -> Self {
+        let sk = material.secret_key();
         let zero = Plaintext::zero(Encoding::poly(), &sk.par).unwrap();
-        let mut c: Ciphertext = sk.try_encrypt(&zero, rng).unwrap();
+        let m = Zeroizing::new(zero.to_poly());
+        let mut c = material.encrypt_poly(m.as_ref(), rng).unwrap();
         // The polynomials of a public key should not allow for variable time
         // computation.
         c.iter_mut()
@@ -37,6 +50,60 @@ impl PublicKey {
             c,
         }
     }
+
+    /// Performs structural checks on the public key, e.g., after it has been
+    /// stored or transmitted.
+    ///
+    /// Returns an error if the public key was not generated for the parameters
+    /// `par`, or if its ciphertext is malformed.
+    pub fn validate(&self, par: &Arc<BfvParameters>) -> Result<()> {
+        if &self.par != par || &self.c.par != par {
+            return Err(Error::UnspecifiedInput(
+                "The public key does not match the parameters".to_string(),
+            ));
+        }
+        if self.c.len() != 2 || self.c.level != 0 {
+            return Err(Error::UnspecifiedInput(
+                "The public key should be a ciphertext of size 2 at level 0".to_string(),
+            ));
+        }
+        let ctx = par.ctx_at_level(0)?;
+        for ci in self.c.iter() {
+            if ci.ctx() != ctx {
+                return Err(Error::MathError(fhe_math::Error::InvalidContext));
+            }
+            if ci.representation() != &Representation::Ntt {
+                return Err(Error::MathError(fhe_math::Error::IncorrectRepresentation(
+                    ci.representation().clone(),
+                    Representation::Ntt,
+                )));
+            }
+        }
+        if self.c[1] == Poly::zero(ctx, Representation::Ntt) {
+            return Err(Error::UnspecifiedInput(
+                "The public key has a zero mask".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the public key corresponds to the secret key `sk` by
+    /// encrypting a random canary plaintext and decrypting it.
+    pub fn self_test<R: RngCore>(&self, sk: &SecretKey, rng: &mut R) -> Result<()> {
+        self.validate(&sk.par)?;
+        let mut canary = vec![0u64; self.par.degree()];
+        canary
+            .iter_mut()
+            .for_each(|ci| *ci = rng.next_u64() % self.par.plaintext());
+        let pt = Plaintext::try_encode(&canary, Encoding::poly(), &self.par)?;
+        let ct = self.try_encrypt(&pt, rng)?;
+        if sk.try_decrypt(&ct)? != pt {
+            return Err(Error::UnspecifiedInput(
+                "The public key does not correspond to the secret key".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl FheParametrized for PublicKey {
@@ -47,10 +114,34 @@ impl FheEncrypter<Plaintext, Ciphertext> for PublicKey {
     type Error = Error;
 
     fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
-        let mut ct = self.c.clone();
-        while ct.level != pt.level {
-            ct.mod_switch_to_next_level()?;
+        self.try_encrypt_at_level(pt, pt.level, rng)
+    }
+}
+
+impl PublicKey {
+    /// Encrypts a [`Plaintext`] directly at `level`, i.e. modulo the reduced
+    /// ciphertext modulus of that level, whatever the level of the plaintext.
+    ///
+    /// The public key is switched down to `level` before the encryption, so
+    /// that the ciphertext is computed in the reduced context only.
+    ///
+    /// Unlike a fresh encryption under the [`SecretKey`], the ciphertext is
+    /// never serialized with a seed in place of its last polynomial: this
+    /// polynomial is the product of the public key with the secret encryption
+    /// randomness, so that it cannot be derived from a public seed.
+    pub fn try_encrypt_at_level<R: RngCore>(
+        &self,
+        pt: &Plaintext,
+        level: usize,
+        rng: &mut R,
+    ) -> Result<Ciphertext> {
+        if self.par != pt.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
         }
+        let mut ct = self.c.clone();
+        ct.mod_switch_to_level(level)?;
 
         let ctx = self.par.ctx_at_level(ct.level)?;
         let u = Zeroizing::new(Poly::small(
@@ -72,7 +163,7 @@ impl FheEncrypter<Plaintext, Ciphertext> for PublicKey {
             rng,
         )?);
 
-        let m = Zeroizing::new(pt.to_poly());
+        let m = Zeroizing::new(pt.to_poly_at_level(level)?);
         let mut c0 = u.as_ref() * &ct[0];
         c0 += &e1;
         c0 += &m;
@@ -94,6 +185,116 @@ impl FheEncrypter<Plaintext, Ciphertext> for PublicKey {
     }
 }
 
+/// Statistical security parameter, in bits, of the noise flooding performed
+/// by [`refresh_with_public_key`]: the flooding noise is `2^40` times larger
+/// than the noise bound of the input ciphertext, so that the statistical
+/// distance between refreshed ciphertexts of the same plaintext is at most
+/// `2^-40`.
+pub const REFRESH_STATISTICAL_SECURITY: usize = 40;
+
+/// Re-randomize a ciphertext so that the refreshed ciphertext can be stored
+/// without being linkable to the original one.
+///
+/// The refreshed ciphertext is the sum of `ct`, of a fresh encryption of zero
+/// under the public key, and of a flooding noise sampled uniformly with
+/// `noise_bits + REFRESH_STATISTICAL_SECURITY` bits, where `noise_bits` is an
+/// upper bound on the noise of `ct` in bits (e.g. as returned by
+/// [`SecretKey::measure_noise`], or from the depth of the circuit that
+/// produced `ct`). By the smudging lemma, the refreshed ciphertext hides the
+/// noise of `ct` up to a statistical distance of
+/// `2^-REFRESH_STATISTICAL_SECURITY`, provided `noise_bits` is a valid bound.
+///
+/// The refreshed ciphertext decrypts to the same plaintext, stays at the same
+/// level (modulus switching cannot be undone in BFV, so no level is consumed),
+/// and has no seed, i.e. its mask is stored in full. The flooding consumes
+/// the noise budget: an error is returned if the flooded noise would exceed
+/// a quarter of the scaling factor at the level of `ct`. The ciphertext must
+/// have two parts; relinearize it beforehand if needed.
+pub fn refresh_with_public_key<R: RngCore + CryptoRng>(
+    ct: &Ciphertext,
+    pk: &PublicKey,
+    noise_bits: usize,
+    rng: &mut R,
+) -> Result<Ciphertext> {
+    if ct.par != pk.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    if ct.len() != 2 {
+        return Err(Error::DefaultError(
+            "Only ciphertexts with two parts can be refreshed".to_string(),
+        ));
+    }
+    let ctx = pk.par.ctx_at_level(ct.level)?;
+    let flooding_bits = noise_bits.saturating_add(REFRESH_STATISTICAL_SECURITY);
+    let delta_bits = (ctx.modulus() / pk.par.plaintext()).bits() as usize;
+    if flooding_bits + 3 > delta_bits {
+        return Err(Error::DefaultError(
+            "The noise budget of the ciphertext is too small for noise flooding".to_string(),
+        ));
+    }
+
+    let zero = Plaintext::zero(Encoding::poly_at_level(ct.level), &pk.par)?;
+    let mut refreshed = pk.try_encrypt(&zero, rng)?;
+    refreshed += ct;
+    let flooding = Zeroizing::new(flooding_noise(ctx, flooding_bits, rng)?);
+    refreshed[0] += flooding.as_ref();
+    refreshed.seed = None;
+    Ok(refreshed)
+}
+
+/// Sample a polynomial in NTT representation whose coefficients are uniform
+/// in `[-2^bits, 2^bits)`.
+fn flooding_noise<R: RngCore + CryptoRng>(
+    ctx: &Arc<Context>,
+    bits: usize,
+    rng: &mut R,
+) -> Result<Poly> {
+    let bound = BigUint::one() << bits;
+    let mut bytes = Zeroizing::new(vec![0u8; (bits + 8) / 8]);
+    let coeffs = (0..ctx.degree())
+        .map(|_| {
+            rng.fill_bytes(&mut bytes);
+            let x = BigUint::from_bytes_le(&bytes) % (&bound << 1);
+            if x >= bound {
+                x - &bound
+            } else {
+                ctx.modulus() - (&bound - x)
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut flooding =
+        Poly::try_convert_from(coeffs.as_slice(), ctx, false, Representation::PowerBasis)?;
+    flooding.change_representation(Representation::Ntt);
+    Ok(flooding)
+}
+
+impl PublicKey {
+    /// Writes the public key to `w` in the streaming format (see
+    /// [`fhe_math::stream`]), i.e. its ciphertext.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        self.c.write_to(w)
+    }
+
+    /// Reads a public key from `r`, in the streaming format of
+    /// [`PublicKey::write_to`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        let mut c = Ciphertext::read_from(r, par)?;
+        if c.level != 0 {
+            return Err(Error::SerializationError);
+        }
+        // The polynomials of a public key should not allow for variable time
+        // computation.
+        c.iter_mut()
+            .for_each(|p| p.disallow_variable_time_computations());
+        Ok(Self {
+            par: par.clone(),
+            c,
+        })
+    }
+}
+
 impl From<&PublicKey> for PublicKeyProto {
     fn from(pk: &PublicKey) -> Self {
         PublicKeyProto {
@@ -111,11 +312,26 @@ impl Serialize for PublicKey {
 impl DeserializeParametrized for PublicKey {
     type Error = Error;
 
+    /// Deserializes a public key with the default [`DeserializationConfig`].
     fn from_bytes(bytes: &[u8], par: &Arc<Self::Parameters>) -> Result<Self> {
+        Self::from_bytes_with_config(bytes, par, &DeserializationConfig::default())
+    }
+}
+
+impl PublicKey {
+    /// Deserializes a public key, with the limits of `config`. The polynomials
+    /// of a public key always use constant-time computations, whatever the
+    /// options of `config`.
+    pub fn from_bytes_with_config(
+        bytes: &[u8],
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        config.check_bytes(bytes.len())?;
         let proto: PublicKeyProto =
             Message::decode(bytes).map_err(|_| Error::SerializationError)?;
-        if proto.c.is_some() {
-            let mut c = Ciphertext::try_convert_from(&proto.c.unwrap(), par)?;
+        if let Some(c) = proto.c {
+            let mut c = Ciphertext::try_convert_from_with_config(&c, par, config)?;
             if c.level != 0 {
                 Err(Error::SerializationError)
             } else {
@@ -136,11 +352,16 @@ impl DeserializeParametrized for PublicKey {
 
 #[cfg(test)]
 mod tests {
-    use super::PublicKey;
-    use crate::bfv::{parameters::BfvParameters, Encoding, Plaintext, SecretKey};
+    use super::{refresh_with_public_key, PublicKey, REFRESH_STATISTICAL_SECURITY};
+    use crate::bfv::{parameters::BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
     use crate::Error;
-    use fhe_traits::{DeserializeParametrized, FheDecrypter, FheEncoder, FheEncrypter, Serialize};
+    use fhe_math::rq::{Poly, Representation};
+    use fhe_traits::{
+        DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, Serialize,
+    };
     use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
 
     #[test]
     fn keygen() -> Result<(), Error> {
@@ -185,6 +406,100 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn encrypt_at_level() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+        for level in 0..=params.max_level() {
+            let ct = pk.try_encrypt_at_level(&pt, level, &mut rng)?;
+            assert_eq!(ct.level, level);
+            assert_eq!(ct[0].ctx(), params.ctx_at_level(level)?);
+            let decrypted = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&decrypted, Encoding::simd())?, v);
+        }
+        assert!(pk
+            .try_encrypt_at_level(&pt, params.max_level() + 1, &mut rng)
+            .is_err());
+        let other = BfvParameters::default_arc(1, 16);
+        let pt_other = Plaintext::try_encode(&v, Encoding::simd(), &other)?;
+        assert!(pk.try_encrypt_at_level(&pt_other, 0, &mut rng).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn refresh() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        for level in 0..params.max_level() {
+            let pt = Plaintext::try_encode(
+                &params.plaintext.random_vec(params.degree(), &mut rng),
+                Encoding::poly_at_level(level),
+                &params,
+            )?;
+            let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            assert!(ct.seed.is_some());
+
+            let noise = unsafe { sk.measure_noise(&ct)? };
+            let refreshed = refresh_with_public_key(&ct, &pk, noise, &mut rng)?;
+            assert!(refreshed.seed.is_none());
+            assert_eq!(refreshed.level, level);
+            assert_ne!(refreshed, ct);
+            assert_eq!(sk.try_decrypt(&refreshed)?, pt);
+            // The noise has been flooded.
+            let flooded = unsafe { sk.measure_noise(&refreshed)? };
+            assert!(flooded >= noise + REFRESH_STATISTICAL_SECURITY - 8);
+
+            // The flooding noise cannot exceed the noise budget.
+            assert!(refresh_with_public_key(&ct, &pk, 400, &mut rng).is_err());
+
+            let ct3 = &ct * &ct;
+            assert!(refresh_with_public_key(&ct3, &pk, noise, &mut rng).is_err());
+        }
+
+        let other = BfvParameters::default_arc(1, 16);
+        let other_sk = SecretKey::random(&other, &mut rng);
+        let other_pk = PublicKey::new(&other_sk, &mut rng);
+        let ct: Ciphertext =
+            sk.try_encrypt(&Plaintext::zero(Encoding::poly(), &params)?, &mut rng)?;
+        assert!(refresh_with_public_key(&ct, &other_pk, 0, &mut rng).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        pk.validate(&params)?;
+        pk.self_test(&sk, &mut rng)?;
+
+        let other_params = BfvParameters::default_arc(1, 16);
+        assert!(pk.validate(&other_params).is_err());
+
+        let other_sk = SecretKey::random(&params, &mut rng);
+        assert!(pk.self_test(&other_sk, &mut rng).is_err());
+
+        let mut pk_switched = pk.clone();
+        pk_switched.c.mod_switch_to_next_level()?;
+        assert!(pk_switched.validate(&params).is_err());
+
+        let mut pk_truncated = pk.clone();
+        pk_truncated.c.truncate(1);
+        assert!(pk_truncated.validate(&params).is_err());
+
+        let mut pk_zero = pk;
+        pk_zero.c[1] = Poly::zero(params.ctx_at_level(0)?, Representation::Ntt);
+        assert!(pk_zero.validate(&params).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_serialize() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -199,4 +514,20 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            let mut bytes = Vec::new();
+            pk.write_to(&mut bytes)?;
+            assert_eq!(pk, PublicKey::read_from(&mut &bytes[..], &params)?);
+        }
+        Ok(())
+    }
 }
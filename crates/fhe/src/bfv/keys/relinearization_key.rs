@@ -1,19 +1,21 @@
 //! Relinearization keys for the BFV encryption scheme
 
 extern crate alloc;
-use alloc::sync::Arc;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 use super::key_switching_key::KeySwitchingKey;
-use crate::bfv::{traits::TryConvertFrom, BfvParameters, Ciphertext, SecretKey};
+use super::secret_key::SecretKeyExpanded;
+use crate::bfv::{
+    traits::TryConvertFrom, BfvParameters, Ciphertext, DeserializationConfig, SecretKey,
+};
 use crate::proto::bfv::{
     KeySwitchingKey as KeySwitchingKeyProto, RelinearizationKey as RelinearizationKeyProto,
 };
 use crate::{Error, Result};
-use fhe_math::rq::{
-    switcher::Switcher, traits::TryConvertFrom as TryConvertFromPoly, Poly, Representation,
-};
+use fhe_math::rq::{switcher::Switcher, Poly, Representation};
+use fhe_math::stream::{Reader, Writer};
 use fhe_traits::{DeserializeParametrized, FheParametrized, Serialize};
 use prost::Message;
 use rand::{CryptoRng, RngCore};
@@ -43,12 +45,31 @@ impl RelinearizationKey {
         Self::new_leveled_internal(sk, ciphertext_level, key_level, rng)
     }
 
+    /// Generate a [`RelinearizationKey`] from the expanded forms of a
+    /// [`SecretKey`] returned by [`SecretKey::public_key_material`].
+    pub fn new_with_material<R: RngCore + CryptoRng>(
+        material: &SecretKeyExpanded,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_leveled_with_material(material, 0, 0, rng)
+    }
+
     fn new_leveled_internal<R: RngCore + CryptoRng>(
         sk: &SecretKey,
         ciphertext_level: usize,
         key_level: usize,
         rng: &mut R,
     ) -> Result<Self> {
+        Self::new_leveled_with_material(&sk.public_key_material(), ciphertext_level, key_level, rng)
+    }
+
+    fn new_leveled_with_material<R: RngCore + CryptoRng>(
+        material: &SecretKeyExpanded,
+        ciphertext_level: usize,
+        key_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let sk = material.secret_key();
         let ctx_relin_key = sk.par.ctx_at_level(key_level)?;
         let ctx_ciphertext = sk.par.ctx_at_level(ciphertext_level)?;
 
@@ -58,18 +79,17 @@ impl RelinearizationKey {
             ));
         }
 
-        let mut s = Zeroizing::new(Poly::try_convert_from(
-            sk.coeffs.as_ref(),
-            ctx_ciphertext,
-            false,
-            Representation::PowerBasis,
-        )?);
-        s.change_representation(Representation::Ntt);
-        let mut s2 = Zeroizing::new(s.as_ref() * s.as_ref());
+        let mut s2 = Zeroizing::new(material.s2_ntt(ciphertext_level)?.clone());
         s2.change_representation(Representation::PowerBasis);
         let switcher_up = Switcher::new(ctx_ciphertext, ctx_relin_key)?;
         let s2_switched_up = Zeroizing::new(s2.mod_switch_to(&switcher_up)?);
-        let ksk = KeySwitchingKey::new(sk, &s2_switched_up, ciphertext_level, key_level, rng)?;
+        let ksk = KeySwitchingKey::new_with_material(
+            material,
+            &s2_switched_up,
+            ciphertext_level,
+            key_level,
+            rng,
+        )?;
         Ok(Self { ksk })
     }
 
@@ -110,6 +130,20 @@ impl RelinearizationKey {
     pub(crate) fn relinearizes_poly(&self, c2: &Poly) -> Result<(Poly, Poly)> {
         self.ksk.key_switch(c2)
     }
+
+    /// Writes the relinearization key to `w` in the streaming format (see
+    /// [`fhe_math::stream`]), i.e. its key switching key.
+    pub fn write_to<W: Writer + ?Sized>(&self, w: &mut W) -> Result<()> {
+        self.ksk.write_to(w)
+    }
+
+    /// Reads a relinearization key from `r`, in the streaming format of
+    /// [`RelinearizationKey::write_to`].
+    pub fn read_from<R: Reader + ?Sized>(r: &mut R, par: &Arc<BfvParameters>) -> Result<Self> {
+        Ok(RelinearizationKey {
+            ksk: KeySwitchingKey::read_from(r, par)?,
+        })
+    }
 }
 
 impl From<&RelinearizationKey> for RelinearizationKeyProto {
@@ -122,14 +156,42 @@ impl From<&RelinearizationKey> for RelinearizationKeyProto {
 
 impl TryConvertFrom<&RelinearizationKeyProto> for RelinearizationKey {
     fn try_convert_from(value: &RelinearizationKeyProto, par: &Arc<BfvParameters>) -> Result<Self> {
-        if value.ksk.is_some() {
+        Self::try_convert_from_with_config(value, par, &DeserializationConfig::keys())
+    }
+}
+
+impl RelinearizationKey {
+    /// Converts a protobuf relinearization key, with the limits and options of
+    /// `config`.
+    fn try_convert_from_with_config(
+        value: &RelinearizationKeyProto,
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        if let Some(ksk) = value.ksk.as_ref() {
             Ok(RelinearizationKey {
-                ksk: KeySwitchingKey::try_convert_from(value.ksk.as_ref().unwrap(), par)?,
+                ksk: KeySwitchingKey::try_convert_from_with_config(ksk, par, config)?,
             })
         } else {
             Err(Error::DefaultError("Invalid serialization".to_string()))
         }
     }
+
+    /// Deserializes a relinearization key, with the limits and options of
+    /// `config`.
+    pub fn from_bytes_with_config(
+        bytes: &[u8],
+        par: &Arc<BfvParameters>,
+        config: &DeserializationConfig,
+    ) -> Result<Self> {
+        config.check_bytes(bytes.len())?;
+        let rk = Message::decode(bytes);
+        if let Ok(rk) = rk {
+            RelinearizationKey::try_convert_from_with_config(&rk, par, config)
+        } else {
+            Err(Error::DefaultError("Invalid serialization".to_string()))
+        }
+    }
 }
 
 impl Serialize for RelinearizationKey {
@@ -145,13 +207,11 @@ impl FheParametrized for RelinearizationKey {
 impl DeserializeParametrized for RelinearizationKey {
     type Error = Error;
 
+    /// Deserializes a relinearization key with the limits of the default
+    /// [`DeserializationConfig`], keeping the variable-time computations of
+    /// its public polynomials.
     fn from_bytes(bytes: &[u8], par: &Arc<Self::Parameters>) -> Result<Self> {
-        let rk = Message::decode(bytes);
-        if let Ok(rk) = rk {
-            RelinearizationKey::try_convert_from(&rk, par)
-        } else {
-            Err(Error::DefaultError("Invalid serialization".to_string()))
-        }
+        Self::from_bytes_with_config(bytes, par, &DeserializationConfig::keys())
     }
 }
 
@@ -165,7 +225,7 @@ mod tests {
     use fhe_traits::{FheDecoder, FheDecrypter};
     use rand::thread_rng;
     extern crate alloc;
-    
+
     use alloc::vec;
     use alloc::vec::Vec;
 
@@ -300,4 +360,23 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn stream() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(6, 16),
+            BfvParameters::default_arc(3, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let rk = RelinearizationKey::new(&sk, &mut rng)?;
+            let mut bytes = Vec::new();
+            rk.write_to(&mut bytes)?;
+            assert_eq!(rk, RelinearizationKey::read_from(&mut &bytes[..], &params)?);
+
+            let other = BfvParameters::default_arc(2, 16);
+            assert!(RelinearizationKey::read_from(&mut &bytes[..], &other).is_err());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,273 @@
+//! Ring switching keys for the BFV encryption scheme
+
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::key_switching_key::KeySwitchingKey;
+use crate::bfv::{BfvParameters, Ciphertext, Plaintext, SecretKey, Sparsity};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
+use ndarray::s;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+/// Ring switching key for the BFV encryption scheme.
+///
+/// A ring switching key switches a ciphertext of degree `2n` whose plaintext
+/// only has even coefficients, i.e. belongs to the subring generated by
+/// `Y = X^2`, into a ciphertext of degree `n` encrypting the plaintext of
+/// coefficients `m_0, m_2, m_4, ...`. Writing the secret key as `s(X) =
+/// s_e(X^2) + X s_o(X^2)`, the even part of the phase is a ciphertext under
+/// `(1, s_e, Y s_o)`, so that the ciphertext in the smaller ring is under
+/// `s_e`, and the key is a key switching key from `Y s_o` to `s_e`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RingSwitchingKey {
+    /// The parameters of degree `2n` of the input ciphertexts.
+    pub(crate) par: Arc<BfvParameters>,
+
+    /// The key switching key from `Y s_o` to `s_e`, in the parameters of
+    /// degree `n`.
+    pub(crate) ksk: KeySwitchingKey,
+}
+
+impl RingSwitchingKey {
+    /// Generate a [`RingSwitchingKey`] from a [`SecretKey`] to the parameters
+    /// `to`, whose degree is half of the degree of the secret key, and with
+    /// the same moduli and plaintext modulus.
+    pub fn new<R: RngCore + CryptoRng>(
+        sk: &SecretKey,
+        to: &Arc<BfvParameters>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new_leveled(sk, to, 0, 0, rng)
+    }
+
+    /// Generate a [`RingSwitchingKey`] for ciphertexts at `ciphertext_level`,
+    /// with a key at `key_level`.
+    pub fn new_leveled<R: RngCore + CryptoRng>(
+        sk: &SecretKey,
+        to: &Arc<BfvParameters>,
+        ciphertext_level: usize,
+        key_level: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        check_subring(&sk.par, to)?;
+        let ctx_key = to.ctx_at_level(key_level)?;
+        if ctx_key.moduli().len() == 1 {
+            return Err(Error::DefaultError(
+                "These parameters do not support key switching".to_string(),
+            ));
+        }
+
+        // Y * s_o = -s_{2n-1} + s_1 Y + s_3 Y^2 + ... + s_{2n-3} Y^{n-1}
+        let n = to.degree();
+        let mut y_s_odd = Zeroizing::new(vec![0i64; n]);
+        y_s_odd[0] = -sk.coeffs[2 * n - 1];
+        for i in 1..n {
+            y_s_odd[i] = sk.coeffs[2 * i - 1];
+        }
+        let from = Zeroizing::new(Poly::try_convert_from(
+            y_s_odd.as_slice(),
+            ctx_key,
+            false,
+            Representation::PowerBasis,
+        )?);
+
+        let sk_to = sk.subring_key(to)?;
+        let ksk = KeySwitchingKey::new(&sk_to, &from, ciphertext_level, key_level, rng)?;
+        Ok(Self {
+            par: sk.par.clone(),
+            ksk,
+        })
+    }
+
+    /// Switches a ciphertext of degree `2n` whose plaintext belongs to the
+    /// subring generated by `X^2` into a ciphertext of degree `n` at the same
+    /// level, which decrypts under [`SecretKey::subring_key`].
+    pub fn switches(&self, ct: &Ciphertext) -> Result<Ciphertext> {
+        if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        } else if ct.len() != 2 {
+            return Err(Error::DefaultError(
+                "Only supports ring switching of ciphertexts with 2 parts".to_string(),
+            ));
+        } else if ct.level != self.ksk.ciphertext_level {
+            return Err(Error::DefaultError(
+                "Ciphertext has incorrect level".to_string(),
+            ));
+        }
+
+        let ctx = self.ksk.par.ctx_at_level(ct.level)?;
+        let (c0_even, _) = split_even_odd(&ct[0], ctx)?;
+        let (c1_even, c1_odd) = split_even_odd(&ct[1], ctx)?;
+
+        let (mut c0, mut c1) = self.ksk.key_switch(&c1_odd)?;
+        c0.change_representation(Representation::PowerBasis);
+        c1.change_representation(Representation::PowerBasis);
+        if c0.ctx() != ctx {
+            c0.mod_switch_down_to(ctx)?;
+            c1.mod_switch_down_to(ctx)?;
+        }
+        c0 += &c0_even;
+        c1 += &c1_even;
+        c0.change_representation(Representation::Ntt);
+        c1.change_representation(Representation::Ntt);
+
+        Ciphertext::new(vec![c0, c1], &self.ksk.par)
+    }
+
+    /// Embeds a plaintext of degree `n` into a plaintext of degree `2n` of the
+    /// subring generated by `X^2`, i.e. with the coefficients `m_i` at the
+    /// even indices `2i`, at the same level.
+    ///
+    /// The embedded plaintext is decoded with a polynomial encoding, and its
+    /// encryption is switched back by [`RingSwitchingKey::switches`] into an
+    /// encryption of `pt`, which decodes with the encoding of `pt`.
+    pub fn embeds(&self, pt: &Plaintext) -> Result<Plaintext> {
+        if pt.par != self.ksk.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        let mut value = vec![0u64; self.par.degree()];
+        for (v, pi) in value.iter_mut().step_by(2).zip(pt.value.iter()) {
+            *v = *pi;
+        }
+        let ctx = self.par.ctx_at_level(pt.level)?;
+        let mut poly_ntt =
+            Poly::try_convert_from(value.as_slice(), ctx, false, Representation::PowerBasis)?;
+        poly_ntt.change_representation(Representation::Ntt);
+        Ok(Plaintext {
+            par: self.par.clone(),
+            value: value.into_boxed_slice(),
+            encoding: None,
+            poly_ntt,
+            level: pt.level,
+            sparsity: Sparsity::Dense,
+        })
+    }
+}
+
+impl SecretKey {
+    /// Returns the secret key of degree `n` in the parameters `to` under which
+    /// the ciphertexts switched by a [`RingSwitchingKey`] decrypt, i.e. the
+    /// even coefficients of this secret key of degree `2n`.
+    pub fn subring_key(&self, to: &Arc<BfvParameters>) -> Result<SecretKey> {
+        check_subring(&self.par, to)?;
+        let coeffs = self.coeffs.iter().step_by(2).copied().collect::<Vec<_>>();
+        Ok(SecretKey::new(coeffs, to))
+    }
+}
+
+/// Checks that the parameters `to` define the subring of degree `n` of the
+/// parameters `from` of degree `2n`.
+fn check_subring(from: &BfvParameters, to: &BfvParameters) -> Result<()> {
+    if 2 * to.degree() != from.degree() {
+        Err(Error::DefaultError(
+            "The degree must be halved by ring switching".to_string(),
+        ))
+    } else if to.plaintext() != from.plaintext() || to.moduli() != from.moduli() {
+        Err(Error::DefaultError(
+            "The parameters must have the same plaintext modulus and moduli".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Splits a polynomial of degree `2n` in Ntt representation into its even and
+/// odd parts `p_e` and `p_o`, such that `p(X) = p_e(X^2) + X p_o(X^2)`, in
+/// PowerBasis representation in the context `ctx` of degree `n`.
+fn split_even_odd(p: &Poly, ctx: &Arc<Context>) -> Result<(Poly, Poly)> {
+    let mut p = p.clone();
+    p.change_representation(Representation::PowerBasis);
+    let coefficients = p.coefficients();
+    let even = Poly::try_convert_from(
+        coefficients.slice(s![.., ..;2]).to_owned(),
+        ctx,
+        true,
+        Representation::PowerBasis,
+    )?;
+    let odd = Poly::try_convert_from(
+        coefficients.slice(s![.., 1..;2]).to_owned(),
+        ctx,
+        true,
+        Representation::PowerBasis,
+    )?;
+    Ok((even, odd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingSwitchingKey;
+    use crate::bfv::{
+        BfvParameters, BfvParametersBuilder, Ciphertext, Encoding, Plaintext, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn ring_switching() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let from = BfvParameters::default_arc(3, 32);
+        let to = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(from.plaintext())
+            .set_moduli(from.moduli())
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&from, &mut rng);
+        let sk_to = sk.subring_key(&to)?;
+        for level in 0..from.max_level() {
+            let rsk = RingSwitchingKey::new_leveled(&sk, &to, level, level, &mut rng)?;
+            for encoding in [
+                Encoding::poly_at_level(level),
+                Encoding::simd_at_level(level),
+            ] {
+                let v = to.plaintext.random_vec(to.degree(), &mut rng);
+                let pt = Plaintext::try_encode(&v, encoding.clone(), &to)?;
+                let ct: Ciphertext = sk.try_encrypt(&rsk.embeds(&pt)?, &mut rng)?;
+
+                let switched = rsk.switches(&ct)?;
+                assert_eq!(switched.level(), level);
+                assert_eq!(switched.par, to);
+                let decrypted = sk_to.try_decrypt(&switched)?;
+                assert_eq!(Vec::<u64>::try_decode(&decrypted, encoding)?, v);
+            }
+
+            // The subring is stable by multiplication, so that products of
+            // embedded plaintexts are switched into products in the smaller
+            // ring.
+            let a = to.plaintext.random_vec(to.degree(), &mut rng);
+            let b = to.plaintext.random_vec(to.degree(), &mut rng);
+            let encoding = Encoding::simd_at_level(level);
+            let pt_a = Plaintext::try_encode(&a, encoding.clone(), &to)?;
+            let pt_b = Plaintext::try_encode(&b, encoding.clone(), &to)?;
+            let ct: Ciphertext = sk.try_encrypt(&rsk.embeds(&pt_a)?, &mut rng)?;
+            let product = &ct * &rsk.embeds(&pt_b)?;
+
+            let decrypted = sk_to.try_decrypt(&rsk.switches(&product)?)?;
+            let mut expected = a.clone();
+            to.plaintext.mul_vec(&mut expected, &b);
+            assert_eq!(Vec::<u64>::try_decode(&decrypted, encoding)?, expected);
+        }
+
+        let rsk = RingSwitchingKey::new(&sk, &to, &mut rng)?;
+        assert!(RingSwitchingKey::new(&sk, &from, &mut rng).is_err());
+        assert!(sk.subring_key(&BfvParameters::default_arc(2, 16)).is_err());
+        let ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&[1u64], Encoding::poly_at_level(1), &from)?,
+            &mut rng,
+        )?;
+        assert!(rsk.switches(&ct).is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 //! Secret keys for the BFV encryption scheme
 
-use crate::bfv::{BfvParameters, Ciphertext, Plaintext};
+use crate::bfv::{BfvParameters, Ciphertext, Plaintext, Sparsity};
 use crate::{Error, Result};
 use fhe_math::{
     rq::{traits::TryConvertFrom, Poly, Representation},
@@ -14,20 +14,33 @@ use rand::{Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 extern crate alloc;
 use alloc::borrow::ToOwned;
-use alloc::boxed::Box;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cell::OnceCell;
+#[cfg(any(debug_assertions, feature = "kdm-guard"))]
+use subtle::{Choice, ConstantTimeEq};
 use zeroize::Zeroizing;
 use zeroize_derive::{Zeroize, ZeroizeOnDrop};
 
+/// The coefficients of a secret key, in protected memory with the
+/// `protected-memory` feature.
+#[cfg(feature = "protected-memory")]
+type Coefficients = crate::protected::ProtectedSlice;
+#[cfg(not(feature = "protected-memory"))]
+type Coefficients = alloc::boxed::Box<[i64]>;
+
 /// Secret key for the BFV encryption scheme.
+///
+/// With the `protected-memory` feature, the coefficients are stored in locked
+/// memory pages, between guard pages, and wiped on drop, unless the operating
+/// system refuses to lock them (see [`SecretKey::is_memory_locked`]).
 #[derive(Debug, PartialEq, Eq, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SecretKey {
     #[zeroize(skip)]
     pub(crate) par: Arc<BfvParameters>,
-    pub(crate) coeffs: Box<[i64]>,
+    pub(crate) coeffs: Coefficients,
 }
 
 impl SecretKey {
@@ -41,7 +54,55 @@ impl SecretKey {
     pub(crate) fn new(coeffs: Vec<i64>, par: &Arc<BfvParameters>) -> Self {
         Self {
             par: par.to_owned(),
-            coeffs: coeffs.into_boxed_slice(),
+            coeffs: Coefficients::from(coeffs),
+        }
+    }
+
+    /// Extend the secret key to parameters with a larger modulus chain, while
+    /// keeping the same underlying secret.
+    ///
+    /// The parameters must have the same degree, and their moduli must start
+    /// with the moduli of the current parameters. Public, relinearization and
+    /// evaluation keys for the new parameters are then generated from the
+    /// extended secret key as usual; ciphertexts encrypted under the current
+    /// parameters are not converted.
+    pub fn extend_to(&self, par: &Arc<BfvParameters>) -> Result<SecretKey> {
+        if par.degree() != self.par.degree() {
+            return Err(Error::UnspecifiedInput(
+                "The parameters must have the same degree".to_string(),
+            ));
+        }
+        if !par.moduli().starts_with(self.par.moduli()) {
+            return Err(Error::UnspecifiedInput(
+                "The modulus chain must extend the current modulus chain".to_string(),
+            ));
+        }
+        Ok(Self::new(self.coeffs.to_vec(), par))
+    }
+
+    /// Reports whether the coefficients are stored in locked memory pages.
+    ///
+    /// This is always `false` without the `protected-memory` feature. With
+    /// it, the coefficients fall back to heap memory, still wiped on drop,
+    /// when the operating system refuses to lock the pages, e.g. when the
+    /// limit of locked memory of the process (`RLIMIT_MEMLOCK`) is reached.
+    pub fn is_memory_locked(&self) -> bool {
+        #[cfg(feature = "protected-memory")]
+        return self.coeffs.is_locked();
+        #[cfg(not(feature = "protected-memory"))]
+        false
+    }
+
+    /// Returns a borrow of the secret key which caches the expanded forms of
+    /// the secret polynomial, so that several keys can be generated from it
+    /// without converting the secret key again.
+    pub fn public_key_material(&self) -> SecretKeyExpanded<'_> {
+        let levels = self.par.max_level() + 1;
+        SecretKeyExpanded {
+            sk: self,
+            s: (0..levels).map(|_| OnceCell::new()).collect(),
+            s_ntt: (0..levels).map(|_| OnceCell::new()).collect(),
+            s2_ntt: (0..levels).map(|_| OnceCell::new()).collect(),
         }
     }
 
@@ -91,27 +152,192 @@ impl SecretKey {
         Ok(noise)
     }
 
+    /// Encrypts a [`Plaintext`] directly at `level`, i.e. modulo the reduced
+    /// ciphertext modulus of that level, whatever the level of the plaintext.
+    ///
+    /// This is cheaper than encrypting at the level of the plaintext and
+    /// switching the ciphertext down afterwards, e.g. for values that are only
+    /// added late in a circuit.
+    pub fn try_encrypt_at_level<R: RngCore>(
+        &self,
+        pt: &Plaintext,
+        level: usize,
+        rng: &mut R,
+    ) -> Result<Ciphertext> {
+        if self.par != pt.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        #[cfg(any(debug_assertions, feature = "kdm-guard"))]
+        self.check_key_dependent_message(pt)?;
+        let m = Zeroizing::new(pt.to_poly_at_level(level)?);
+        self.encrypt_poly(m.as_ref(), rng)
+    }
+
+    /// Returns an error if the plaintext encodes the secret key or its
+    /// opposite, with the polynomial or the SIMD encoding, so that the secret
+    /// key is not encrypted under itself by accident: the security of the
+    /// scheme is not established for such key-dependent messages.
+    ///
+    /// The check runs in constant time, in debug builds and with the
+    /// `kdm-guard` feature.
+    #[cfg(any(debug_assertions, feature = "kdm-guard"))]
+    fn check_key_dependent_message(&self, pt: &Plaintext) -> Result<()> {
+        let t = &self.par.plaintext;
+        let s = Zeroizing::new(t.reduce_vec_i64(&self.coeffs));
+        let mut minus_s = s.clone();
+        t.neg_vec(&mut minus_s);
+
+        let mut values = vec![Zeroizing::new(pt.value.to_vec())];
+        if let Some(op) = &self.par.op {
+            // The slots which the value encodes with the SIMD encoding.
+            let mut w = Zeroizing::new(pt.value.to_vec());
+            op.forward(&mut w);
+            let slots = self.par.matrix_reps_index_map.iter().map(|i| w[*i]);
+            values.push(Zeroizing::new(slots.collect()));
+        }
+        let mut is_key_dependent = Choice::from(0);
+        for value in &values {
+            is_key_dependent |= value.ct_eq(&s) | value.ct_eq(&minus_s);
+        }
+        if is_key_dependent.into() {
+            Err(Error::DefaultError(
+                "The plaintext encodes the secret key".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub(crate) fn encrypt_poly<R: RngCore>(&self, p: &Poly, rng: &mut R) -> Result<Ciphertext> {
-        assert_eq!(p.representation(), &Representation::Ntt);
+        self.public_key_material().encrypt_poly(p, rng)
+    }
+}
 
-        let level = self.par.level_of_ctx(p.ctx())?;
+/// Expanded forms of a [`SecretKey`], i.e. its polynomial `s` and its square
+/// `s^2` at every level, computed lazily and shared by the generation of
+/// public keys, relinearization keys and evaluation keys.
+///
+/// The cached polynomials are zeroized on drop, and locked in memory with the
+/// `protected-memory` feature.
+#[derive(Debug)]
+pub struct SecretKeyExpanded<'a> {
+    sk: &'a SecretKey,
+    s: Vec<OnceCell<CachedPoly>>,
+    s_ntt: Vec<OnceCell<CachedPoly>>,
+    s2_ntt: Vec<OnceCell<CachedPoly>>,
+}
 
-        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
-        rng.fill(&mut seed);
+/// A secret polynomial cached by a [`SecretKeyExpanded`].
+#[derive(Debug)]
+struct CachedPoly {
+    poly: Zeroizing<Poly>,
+    // Dropped after the polynomial is zeroized.
+    #[cfg(feature = "protected-memory")]
+    _locked: crate::protected::LockedPages,
+}
 
-        // Let's create a secret key with the ciphertext context
-        let mut s = Zeroizing::new(Poly::try_convert_from(
-            self.coeffs.as_ref(),
-            p.ctx(),
+impl CachedPoly {
+    fn is_locked(&self) -> bool {
+        #[cfg(feature = "protected-memory")]
+        return self._locked.is_locked();
+        #[cfg(not(feature = "protected-memory"))]
+        false
+    }
+
+    fn new(poly: Zeroizing<Poly>) -> Self {
+        #[cfg(feature = "protected-memory")]
+        let _locked = crate::protected::LockedPages::new(
+            poly.coefficients()
+                .as_slice()
+                .expect("The coefficients are contiguous"),
+        );
+        Self {
+            poly,
+            #[cfg(feature = "protected-memory")]
+            _locked,
+        }
+    }
+}
+
+impl SecretKeyExpanded<'_> {
+    /// Returns the underlying secret key.
+    pub(crate) fn secret_key(&self) -> &SecretKey {
+        self.sk
+    }
+
+    /// Reports whether the secret key and the polynomials cached so far are
+    /// stored in locked memory pages (see [`SecretKey::is_memory_locked`]).
+    pub fn is_memory_locked(&self) -> bool {
+        self.sk.is_memory_locked()
+            && [&self.s, &self.s_ntt, &self.s2_ntt]
+                .into_iter()
+                .flatten()
+                .filter_map(OnceCell::get)
+                .all(CachedPoly::is_locked)
+    }
+
+    /// Returns the secret polynomial at `level`, in power basis.
+    pub(crate) fn s(&self, level: usize) -> Result<&Poly> {
+        let cell = self
+            .s
+            .get(level)
+            .ok_or_else(|| Error::DefaultError("No context".to_string()))?;
+        if let Some(s) = cell.get() {
+            return Ok(&s.poly);
+        }
+        let s = Poly::try_convert_from(
+            self.sk.coeffs.as_ref(),
+            self.sk.par.ctx_at_level(level)?,
             false,
             Representation::PowerBasis,
-        )?);
+        )?;
+        Ok(&cell.get_or_init(|| CachedPoly::new(Zeroizing::new(s))).poly)
+    }
+
+    /// Returns the secret polynomial at `level`, in NTT representation.
+    pub(crate) fn s_ntt(&self, level: usize) -> Result<&Poly> {
+        let cell = self
+            .s_ntt
+            .get(level)
+            .ok_or_else(|| Error::DefaultError("No context".to_string()))?;
+        if let Some(s) = cell.get() {
+            return Ok(&s.poly);
+        }
+        let mut s = Zeroizing::new(self.s(level)?.clone());
         s.change_representation(Representation::Ntt);
+        Ok(&cell.get_or_init(|| CachedPoly::new(s)).poly)
+    }
+
+    /// Returns the square of the secret polynomial at `level`, in NTT
+    /// representation.
+    pub(crate) fn s2_ntt(&self, level: usize) -> Result<&Poly> {
+        let cell = self
+            .s2_ntt
+            .get(level)
+            .ok_or_else(|| Error::DefaultError("No context".to_string()))?;
+        if let Some(s2) = cell.get() {
+            return Ok(&s2.poly);
+        }
+        let s = self.s_ntt(level)?;
+        Ok(&cell
+            .get_or_init(|| CachedPoly::new(Zeroizing::new(s * s)))
+            .poly)
+    }
+
+    pub(crate) fn encrypt_poly<R: RngCore>(&self, p: &Poly, rng: &mut R) -> Result<Ciphertext> {
+        assert_eq!(p.representation(), &Representation::Ntt);
+
+        let level = self.sk.par.level_of_ctx(p.ctx())?;
+
+        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+        rng.fill(&mut seed);
 
         let mut a = Poly::random_from_seed(p.ctx(), Representation::Ntt, seed);
-        let a_s = Zeroizing::new(&a * s.as_ref());
+        let a_s = Zeroizing::new(&a * self.s_ntt(level)?);
 
-        let mut b = Poly::small(p.ctx(), Representation::Ntt, self.par.variance, rng)
+        let mut b = Poly::small(p.ctx(), Representation::Ntt, self.sk.par.variance, rng)
             .map_err(Error::MathError)?;
         b -= &a_s;
         b += p;
@@ -123,7 +349,7 @@ impl SecretKey {
         }
 
         Ok(Ciphertext {
-            par: self.par.clone(),
+            par: self.sk.par.clone(),
             seed: Some(seed),
             c: vec![b, a],
             level,
@@ -192,7 +418,7 @@ impl DeserializeParametrized for SecretKey {
         // Return the deserialized SecretKey with the externally provided parameters
         Ok(Self {
             par: par.clone(),
-            coeffs: coeffs.into_boxed_slice(),
+            coeffs: Coefficients::from(coeffs),
         })
     }
 }
@@ -201,6 +427,8 @@ impl FheEncrypter<Plaintext, Ciphertext> for SecretKey {
 
     fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
         assert_eq!(self.par, pt.par);
+        #[cfg(any(debug_assertions, feature = "kdm-guard"))]
+        self.check_key_dependent_message(pt)?;
         let m = Zeroizing::new(pt.to_poly());
         self.encrypt_poly(m.as_ref(), rng)
     }
@@ -223,62 +451,74 @@ impl FheDecrypter<Plaintext, Ciphertext> for SecretKey {
                 Representation::PowerBasis,
             )?);
             s.change_representation(Representation::Ntt);
-            let mut si = s.clone();
-
-            let mut c = Zeroizing::new(ct[0].clone());
-            c.disallow_variable_time_computations();
-
-            // Compute the phase c0 + c1*s + c2*s^2 + ... where the secret power
-            // s^k is computed on-the-fly
-            for i in 1..ct.len() {
-                let mut cis = Zeroizing::new(ct[i].clone());
-                cis.disallow_variable_time_computations();
-                *cis.as_mut() *= si.as_ref();
-                *c.as_mut() += &cis;
-                if i + 1 < ct.len() {
-                    *si.as_mut() *= s.as_ref();
-                }
-            }
-            c.change_representation(Representation::PowerBasis);
-
-            let d = Zeroizing::new(c.scale(&self.par.scalers[ct.level])?);
-
-            // TODO: Can we handle plaintext moduli that are BigUint?
-            let v = Zeroizing::new(
-                Vec::<u64>::from(d.as_ref())
-                    .iter_mut()
-                    .map(|vi| *vi + *self.par.plaintext)
-                    .collect_vec(),
-            );
-            let mut w = v[..self.par.degree()].to_vec();
-            let q = Modulus::new(self.par.moduli[0]).map_err(Error::MathError)?;
-            q.reduce_vec(&mut w);
-            self.par.plaintext.reduce_vec(&mut w);
-
-            let mut poly =
-                Poly::try_convert_from(&w, ct[0].ctx(), false, Representation::PowerBasis)?;
-            poly.change_representation(Representation::Ntt);
-
-            let pt = Plaintext {
-                par: self.par.clone(),
-                value: w.into_boxed_slice(),
-                encoding: None,
-                poly_ntt: poly,
-                level: ct.level,
-            };
-
-            Ok(pt)
+            decrypt_with_secret_poly(&s, ct)
         }
     }
 }
 
+/// Decrypts a ciphertext with the secret polynomial `s` in NTT representation
+/// in the context of the ciphertext.
+pub(crate) fn decrypt_with_secret_poly(s: &Poly, ct: &Ciphertext) -> Result<Plaintext> {
+    let par = &ct.par;
+    let mut si = Zeroizing::new(s.clone());
+
+    let mut c = Zeroizing::new(ct[0].clone());
+    c.disallow_variable_time_computations();
+
+    // Compute the phase c0 + c1*s + c2*s^2 + ... where the secret power
+    // s^k is computed on-the-fly
+    for i in 1..ct.len() {
+        let mut cis = Zeroizing::new(ct[i].clone());
+        cis.disallow_variable_time_computations();
+        *cis.as_mut() *= si.as_ref();
+        *c.as_mut() += &cis;
+        if i + 1 < ct.len() {
+            *si.as_mut() *= s;
+        }
+    }
+    c.change_representation(Representation::PowerBasis);
+
+    let d = Zeroizing::new(c.scale(&par.scalers[ct.level])?);
+
+    // Plaintext moduli larger than 62 bits are handled by `CrtParameters`, which
+    // decrypts each coprime factor separately.
+    let v = Zeroizing::new(
+        Vec::<u64>::from(d.as_ref())
+            .iter_mut()
+            .map(|vi| *vi + *par.plaintext)
+            .collect_vec(),
+    );
+    let mut w = v[..par.degree()].to_vec();
+    let q = Modulus::new(par.moduli[0]).map_err(Error::MathError)?;
+    q.reduce_vec(&mut w);
+    par.plaintext.reduce_vec(&mut w);
+
+    let mut poly = Poly::try_convert_from(&w, ct[0].ctx(), false, Representation::PowerBasis)?;
+    poly.change_representation(Representation::Ntt);
+
+    Ok(Plaintext {
+        par: par.clone(),
+        value: w.into_boxed_slice(),
+        encoding: None,
+        poly_ntt: poly,
+        level: ct.level,
+        sparsity: Sparsity::Dense,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::SecretKey;
-    use crate::bfv::{parameters::BfvParameters, Encoding, Plaintext};
+    use crate::bfv::{
+        parameters::BfvParameters, Ciphertext, Encoding, Multiplicator, Plaintext, PublicKey,
+        RelinearizationKey,
+    };
     use crate::Error;
-    use fhe_traits::{FheDecrypter, FheEncoder, FheEncrypter};
+    use fhe_math::rq::Representation;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
     use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
 
     #[test]
     fn keygen() {
@@ -320,4 +560,117 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn encrypt_at_level() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::poly_at_level(1), &params)?;
+        for level in 0..=params.max_level() {
+            let ct = sk.try_encrypt_at_level(&pt, level, &mut rng)?;
+            assert_eq!(ct.level, level);
+            let decrypted = sk.try_decrypt(&ct)?;
+            assert_eq!(decrypted.level(), level);
+            assert_eq!(Vec::<u64>::try_decode(&decrypted, Encoding::poly())?, v);
+        }
+        assert!(sk
+            .try_encrypt_at_level(&pt, params.max_level() + 1, &mut rng)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "kdm-guard"))]
+    fn key_dependent_message() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let s = params.plaintext.reduce_vec_i64(&sk.coeffs);
+        let mut minus_s = s.clone();
+        params.plaintext.neg_vec(&mut minus_s);
+
+        for value in [&s, &minus_s] {
+            for encoding in [
+                Encoding::poly(),
+                Encoding::simd(),
+                Encoding::poly_at_level(1),
+            ] {
+                let pt = Plaintext::try_encode(value, encoding, &params)?;
+                assert!(FheEncrypter::<_, Ciphertext>::try_encrypt(&sk, &pt, &mut rng).is_err());
+                assert!(sk.try_encrypt_at_level(&pt, pt.level, &mut rng).is_err());
+            }
+        }
+
+        // Another secret key, or any other plaintext, is encrypted.
+        let other = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&s, Encoding::poly(), &params)?;
+        assert!(FheEncrypter::<_, Ciphertext>::try_encrypt(&other, &pt, &mut rng).is_ok());
+        let pt = Plaintext::try_encode(&[0u64], Encoding::poly(), &params)?;
+        assert!(FheEncrypter::<_, Ciphertext>::try_encrypt(&sk, &pt, &mut rng).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn extend_to() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let larger = BfvParameters::default_arc(4, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let extended = sk.extend_to(&larger)?;
+        assert_eq!(extended.par, larger);
+        assert_eq!(extended.coeffs, sk.coeffs);
+
+        // Keys generated for the new parameters are consistent with the
+        // extended secret key.
+        let pk = PublicKey::new(&extended, &mut rng);
+        let rk = RelinearizationKey::new(&extended, &mut rng)?;
+        let values = larger.plaintext.random_vec(larger.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &larger)?;
+        let ct = pk.try_encrypt(&pt, &mut rng)?;
+        let product = Multiplicator::default(&rk)?.multiply(&ct, &ct)?;
+        let mut expected = values.clone();
+        larger.plaintext.mul_vec(&mut expected, &values);
+        assert_eq!(
+            Vec::<u64>::try_decode(&extended.try_decrypt(&product)?, Encoding::simd())?,
+            expected
+        );
+
+        assert!(extended.extend_to(&params).is_err());
+        assert!(sk.extend_to(&BfvParameters::default_arc(4, 32)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn public_key_material() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let material = sk.public_key_material();
+
+        // The expanded forms are computed once and cached.
+        let s = material.s_ntt(1)?;
+        assert_eq!(s.representation(), &Representation::Ntt);
+        assert!(core::ptr::eq(s, material.s_ntt(1)?));
+        assert_eq!(material.s2_ntt(1)?, &(s * s));
+        assert!(material.s_ntt(params.max_level() + 1).is_err());
+        #[cfg(not(feature = "protected-memory"))]
+        assert!(!sk.is_memory_locked() && !material.is_memory_locked());
+
+        let pk = PublicKey::new_with_material(&material, &mut rng);
+        let rk = RelinearizationKey::new_with_material(&material, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct = pk.try_encrypt(&pt, &mut rng)?;
+        let product = multiplicator.multiply(&ct, &ct)?;
+        let mut expected = values.clone();
+        params.plaintext.mul_vec(&mut expected, &values);
+        assert_eq!(
+            Vec::<u64>::try_decode(&sk.try_decrypt(&product)?, Encoding::simd())?,
+            expected
+        );
+        Ok(())
+    }
 }
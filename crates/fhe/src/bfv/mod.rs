@@ -2,22 +2,57 @@
 
 //! The Brakerski-Fan-Vercauteren homomorphic encryption scheme
 
+mod cancellation;
 mod ciphertext;
+mod ciphertext_matrix;
+pub mod circuits;
+mod counting_encoding;
+mod crt;
+mod deserialization;
 mod encoding;
+mod encrypted_vector;
+mod fixed_point;
 mod keys;
-mod ops;
+pub mod ops;
 mod parameters;
 mod plaintext;
 mod plaintext_vec;
 mod rgsw_ciphertext;
+#[cfg(feature = "serde")]
+mod serialization;
 
+#[cfg(feature = "bench-fixtures")]
+pub mod fixtures;
+pub mod seal;
 pub mod traits;
-pub use ciphertext::Ciphertext;
+pub mod typed;
+pub use cancellation::CancellationToken;
+pub use ciphertext::{set_seed_loss_hook, Ciphertext, CompressionState, SeedLoss};
+pub use ciphertext_matrix::CiphertextMatrix;
+pub use counting_encoding::{CollisionPolicy, CountingEncoding};
+pub use crt::{CrtCiphertext, CrtParameters, CrtPlaintext, CrtSecretKey};
+pub use deserialization::DeserializationConfig;
 pub use encoding::Encoding;
-pub(crate) use keys::KeySwitchingKey;
-pub use keys::{EvaluationKey, EvaluationKeyBuilder, PublicKey, RelinearizationKey, SecretKey};
-pub use ops::{dot_product_scalar, Multiplicator};
-pub use parameters::{BfvParameters, BfvParametersBuilder};
-pub use plaintext::Plaintext;
+pub use encrypted_vector::{
+    decrypt_vector, encrypt_vector, encrypt_vector_from_iter, EncryptedVector,
+};
+pub use fixed_point::{FixedPointCiphertext, FixedPointPlaintext};
+pub use keys::{
+    refresh_with_public_key, Decryptor, EvaluationKey, EvaluationKeyBuilder, GaloisKey,
+    KeySwitchingKey, PublicKey, RelinearizationKey, RingSwitchingKey, SecretKey, SecretKeyExpanded,
+    REFRESH_STATISTICAL_SECURITY,
+};
+pub use ops::{
+    align_levels, answer_query, conv1d, conv1d_same, dot_product, dot_product_scalar,
+    dot_product_scalar_with_cancellation, elements_per_plaintext, expand_query, fold_dimension,
+    fold_plaintext_dimension, keyword_equals, matvec, max_fold_dimensions, pack_database,
+    prefix_sums, query_plaintext, reduce_slots_mod, segmented_prefix_sums, selector_plaintext,
+    unpack_element, MulStrategy, Multiplicator, PermutationPlan, MAX_INTERPOLATION_BOUND,
+};
+pub use parameters::{BfvParameters, BfvParametersBuilder, SecretDistribution, SecurityLevel};
+pub(crate) use plaintext::Sparsity;
+pub use plaintext::{Plaintext, ScaledPlaintext};
 pub use plaintext_vec::PlaintextVec;
 pub use rgsw_ciphertext::RGSWCiphertext;
+#[cfg(feature = "serde")]
+pub use serialization::{ParametersId, ParametersRegistry, ParametrizedSeed};
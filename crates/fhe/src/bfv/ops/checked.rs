@@ -0,0 +1,192 @@
+//! Checked free functions mirroring the operators over ciphertexts.
+
+use crate::bfv::{Ciphertext, EvaluationKey, Plaintext, RelinearizationKey};
+use crate::{Error, Result};
+extern crate alloc;
+use alloc::string::ToString;
+
+/// Returns an error if the ciphertexts do not have the same parameters, the
+/// same level and the same number of parts, or if one of them is empty.
+fn check_compatible(lhs: &Ciphertext, rhs: &Ciphertext) -> Result<()> {
+    if lhs.par != rhs.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    if lhs.is_empty() || rhs.is_empty() {
+        return Err(Error::DefaultError("Empty ciphertext".to_string()));
+    }
+    if lhs.level != rhs.level {
+        return Err(Error::DefaultError(
+            "Ciphertexts are not at the same level".to_string(),
+        ));
+    }
+    if lhs.len() != rhs.len() {
+        return Err(Error::DefaultError(
+            "Ciphertexts of different sizes".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds the ciphertexts of an iterator, which must all have the same
+/// parameters, level and number of parts.
+///
+/// Returns an error if the iterator is empty or if the ciphertexts are not
+/// compatible.
+pub fn add_many<'a, I>(cts: I) -> Result<Ciphertext>
+where
+    I: IntoIterator<Item = &'a Ciphertext>,
+{
+    let mut cts = cts.into_iter();
+    let mut sum = cts.next().ok_or(Error::TooFewValues(0, 1))?.clone();
+    for ct in cts {
+        check_compatible(&sum, ct)?;
+        sum += ct;
+    }
+    Ok(sum)
+}
+
+/// Multiplies two ciphertexts of two parts at the same level, without
+/// relinearization, into a ciphertext of three parts.
+///
+/// Returns an error if the ciphertexts are not compatible, or do not have two
+/// parts.
+pub fn mul(lhs: &Ciphertext, rhs: &Ciphertext) -> Result<Ciphertext> {
+    check_compatible(lhs, rhs)?;
+    if lhs.len() != 2 {
+        return Err(Error::DefaultError(
+            "Multiplication can only be performed on ciphertexts of size 2".to_string(),
+        ));
+    }
+    Ok(lhs * rhs)
+}
+
+/// Multiplies two ciphertexts of two parts at the same level, and relinearizes
+/// the product with `rk` into a ciphertext of two parts.
+///
+/// Returns an error if the ciphertexts are not compatible, do not have two
+/// parts, or are not at the level of `rk`.
+pub fn mul2(lhs: &Ciphertext, rhs: &Ciphertext, rk: &RelinearizationKey) -> Result<Ciphertext> {
+    if rk.ksk.par != lhs.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    let mut product = mul(lhs, rhs)?;
+    rk.relinearizes(&mut product)?;
+    Ok(product)
+}
+
+/// Multiplies a ciphertext by a plaintext at the same level.
+///
+/// Returns an error if they do not have the same parameters or level, or if
+/// the ciphertext is empty.
+pub fn mul_plain(ct: &Ciphertext, pt: &Plaintext) -> Result<Ciphertext> {
+    if ct.par != pt.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    if ct.is_empty() {
+        return Err(Error::DefaultError("Empty ciphertext".to_string()));
+    }
+    if ct.level != pt.level {
+        return Err(Error::DefaultError(
+            "The ciphertext and the plaintext are not at the same level".to_string(),
+        ));
+    }
+    Ok(ct * pt)
+}
+
+/// Rotates the columns of a ciphertext by `steps`, as in
+/// [`Ciphertext::rotate`].
+///
+/// Returns an error if the parameters of `ek` differ, or if its column
+/// rotations do not generate this rotation.
+pub fn rotate(ct: &Ciphertext, steps: i64, ek: &EvaluationKey) -> Result<Ciphertext> {
+    if ct.par != ek.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    ct.rotate(steps, ek)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_many, mul, mul2, mul_plain, rotate};
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext, RelinearizationKey,
+        SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn checked_ops() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(1)?
+            .build(&mut rng)?;
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let b = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt_a = Plaintext::try_encode(&a, Encoding::simd(), &params)?;
+        let pt_b = Plaintext::try_encode(&b, Encoding::simd(), &params)?;
+        let ct_a: Ciphertext = sk.try_encrypt(&pt_a, &mut rng)?;
+        let ct_b: Ciphertext = sk.try_encrypt(&pt_b, &mut rng)?;
+        let decrypt = |ct: &Ciphertext| -> Result<Vec<u64>, Error> {
+            Vec::<u64>::try_decode(&sk.try_decrypt(ct)?, Encoding::simd())
+        };
+
+        let mut expected = a.clone();
+        for _ in 0..2 {
+            params.plaintext.add_vec(&mut expected, &b);
+        }
+        assert_eq!(decrypt(&add_many([&ct_a, &ct_b, &ct_b])?)?, expected);
+
+        let mut expected = a.clone();
+        params.plaintext.mul_vec(&mut expected, &b);
+        let product = mul(&ct_a, &ct_b)?;
+        assert_eq!(product.len(), 3);
+        assert_eq!(decrypt(&product)?, expected);
+        let product = mul2(&ct_a, &ct_b, &rk)?;
+        assert_eq!(product.len(), 2);
+        assert_eq!(decrypt(&product)?, expected);
+        assert_eq!(decrypt(&mul_plain(&ct_a, &pt_b)?)?, expected);
+
+        let row_size = params.degree() >> 1;
+        let expected = (0..params.degree())
+            .map(|i| a[(i / row_size) * row_size + (i + 1) % row_size])
+            .collect::<Vec<_>>();
+        assert_eq!(decrypt(&rotate(&ct_a, 1, &ek)?)?, expected);
+
+        // Incompatible inputs are rejected instead of panicking.
+        let mut lower = ct_b.clone();
+        lower.mod_switch_to_next_level()?;
+        let pt_lower = Plaintext::try_encode(&b, Encoding::simd_at_level(1), &params)?;
+        let other = BfvParameters::default_arc(1, 16);
+        let sk_other = SecretKey::random(&other, &mut rng);
+        let ct_other: Ciphertext = sk_other.try_encrypt(
+            &Plaintext::try_encode(&b, Encoding::simd(), &other)?,
+            &mut rng,
+        )?;
+        assert!(add_many([]).is_err());
+        assert!(add_many([&ct_a, &lower]).is_err());
+        assert!(add_many([&ct_a, &ct_other]).is_err());
+        assert!(add_many([&ct_a, &Ciphertext::zero(&params)]).is_err());
+        assert!(mul(&ct_a, &lower).is_err());
+        assert!(mul(&mul(&ct_a, &ct_b)?, &mul(&ct_a, &ct_b)?).is_err());
+        assert!(mul2(&lower, &lower, &rk).is_err());
+        assert!(mul_plain(&ct_a, &pt_lower).is_err());
+        assert!(mul_plain(&Ciphertext::zero(&params), &pt_a).is_err());
+        assert!(rotate(&ct_a, 1, &EvaluationKeyBuilder::new(&sk)?.build(&mut rng)?).is_err());
+        Ok(())
+    }
+}
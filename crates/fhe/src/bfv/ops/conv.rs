@@ -0,0 +1,202 @@
+//! Circular convolution of an encrypted signal with a plaintext kernel.
+
+use crate::bfv::encoding::EncodingEnum;
+use crate::bfv::{Ciphertext, Encoding, EvaluationKey, Plaintext};
+use crate::{Error, Result};
+use fhe_math::rq::Representation;
+use fhe_traits::{FheDecoder, FheEncoder};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Computes the circular convolution of an encrypted signal with a plaintext
+/// kernel.
+///
+/// The strategy is selected from the encoding of the kernel:
+/// - with a polynomial encoding, the signal is multiplied by the kernel
+///   polynomial, i.e. the convolution is negacyclic over the `degree`
+///   coefficients, and matches the linear convolution as long as the signal
+///   and the kernel fit together in `degree` coefficients;
+/// - with a SIMD encoding, each of the two rows of `degree / 2` slots is
+///   convolved with the kernel taps of the same row, using one column rotation
+///   per nonzero tap; the evaluation key must support rotating the columns by
+///   `degree / 2 - j` for every such tap `j > 0`.
+pub fn conv1d(ct: &Ciphertext, kernel: &Plaintext, ek: &EvaluationKey) -> Result<Ciphertext> {
+    convolve(ct, kernel, ek, false)
+}
+
+/// Computes the convolution of an encrypted signal with a plaintext kernel,
+/// cropped to the size of the signal and centered as with a `same` padding.
+///
+/// The output at index `i` is the output of [`conv1d`] at index `i + (k - 1) /
+/// 2`, where `k` is the index of the last nonzero tap of the kernel plus one.
+/// In order to avoid wrapping around, the signal must be followed by at least
+/// `k - 1` zero coefficients (or slots in each row). The evaluation key must
+/// support rotating the columns by `((k - 1) / 2 - j) mod (degree / 2)` for
+/// every nonzero tap `j` of a SIMD kernel.
+pub fn conv1d_same(ct: &Ciphertext, kernel: &Plaintext, ek: &EvaluationKey) -> Result<Ciphertext> {
+    convolve(ct, kernel, ek, true)
+}
+
+fn convolve(
+    ct: &Ciphertext,
+    kernel: &Plaintext,
+    ek: &EvaluationKey,
+    same: bool,
+) -> Result<Ciphertext> {
+    if ct.par != kernel.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    if ct.level != kernel.level {
+        return Err(Error::DefaultError(
+            "The signal and the kernel are at different levels".to_string(),
+        ));
+    }
+    let encoding = kernel
+        .encoding
+        .clone()
+        .ok_or_else(|| Error::UnspecifiedInput("The kernel encoding is unknown".to_string()))?;
+    let taps = Vec::<u64>::try_decode(kernel, encoding.clone())?;
+
+    match encoding.encoding {
+        EncodingEnum::Poly => {
+            let mut out = ct * kernel;
+            let k = taps.iter().rposition(|t| *t != 0).map_or(0, |j| j + 1);
+            let shift = k.saturating_sub(1) / 2;
+            if same && shift > 0 {
+                for c in out.iter_mut() {
+                    c.change_representation(Representation::PowerBasis);
+                    c.multiply_inverse_power_of_x(shift)?;
+                    c.change_representation(Representation::Ntt);
+                }
+            }
+            Ok(out)
+        }
+        EncodingEnum::Simd => {
+            let row_size = ct.par.degree() >> 1;
+            let k = (0..row_size)
+                .rposition(|j| taps[j] != 0 || taps[row_size + j] != 0)
+                .map_or(0, |j| j + 1);
+            let shift = if same { k.saturating_sub(1) / 2 } else { 0 };
+
+            let mut out: Option<Ciphertext> = None;
+            for j in 0..k {
+                if taps[j] == 0 && taps[row_size + j] == 0 {
+                    continue;
+                }
+                // Slot `i` of the rotated signal holds the slot `i + shift - j`
+                // of the signal.
+                let rotation = (row_size + shift - j) % row_size;
+                let rotated = if rotation == 0 {
+                    ct.clone()
+                } else {
+                    ek.rotates_columns_by(ct, rotation)?
+                };
+                let mut tap = vec![taps[j]; ct.par.degree()];
+                tap[row_size..].fill(taps[row_size + j]);
+                let pt = Plaintext::try_encode(&tap, Encoding::simd_at_level(ct.level), &ct.par)?;
+                let term = &rotated * &pt;
+                match out.as_mut() {
+                    Some(acc) => *acc += &term,
+                    None => out = Some(term),
+                }
+            }
+            Ok(out.unwrap_or_else(|| ct * kernel))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{conv1d, conv1d_same};
+    use crate::bfv::{BfvParameters, Encoding, EvaluationKeyBuilder, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    fn circular(signal: &[u64], kernel: &[u64], shift: usize, t: u64) -> Vec<u64> {
+        let n = signal.len();
+        (0..n)
+            .map(|i| {
+                kernel.iter().enumerate().fold(0u64, |acc, (j, kj)| {
+                    let s = signal[(n + i + shift - j) % n];
+                    ((acc as u128 + (*kj as u128) * (s as u128)) % (t as u128)) as u64
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn conv1d_simd() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let row_size = params.degree() >> 1;
+        let t = params.plaintext();
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(1)?
+            .enable_column_rotation(row_size - 1)?
+            .enable_column_rotation(row_size - 2)?
+            .build(&mut rng)?;
+
+        let signal = params.plaintext.random_vec(params.degree(), &mut rng);
+        let mut kernel = params.plaintext.random_vec(params.degree(), &mut rng);
+        for j in 3..row_size {
+            kernel[j] = 0;
+            kernel[row_size + j] = 0;
+        }
+        let pt = Plaintext::try_encode(&signal, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        let pt_kernel = Plaintext::try_encode(&kernel, Encoding::simd(), &params)?;
+
+        for (same, shift) in [(false, 0), (true, 1)] {
+            let ct_out = if same {
+                conv1d_same(&ct, &pt_kernel, &ek)?
+            } else {
+                conv1d(&ct, &pt_kernel, &ek)?
+            };
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&ct_out)?, Encoding::simd())?;
+            let mut expected = circular(&signal[..row_size], &kernel[..3], shift, t);
+            expected.extend(circular(
+                &signal[row_size..],
+                &kernel[row_size..row_size + 3],
+                shift,
+                t,
+            ));
+            assert_eq!(out, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn conv1d_poly() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let t = params.plaintext();
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?.build(&mut rng)?;
+
+        let mut signal = params.plaintext.random_vec(params.degree(), &mut rng);
+        signal[12..].fill(0);
+        let mut kernel = params.plaintext.random_vec(params.degree(), &mut rng);
+        kernel[5..].fill(0);
+        let pt = Plaintext::try_encode(&signal, Encoding::poly(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        let pt_kernel = Plaintext::try_encode(&kernel, Encoding::poly(), &params)?;
+
+        let out = conv1d(&ct, &pt_kernel, &ek)?;
+        let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::poly())?;
+        assert_eq!(out, circular(&signal, &kernel[..5], 0, t));
+
+        let out = conv1d_same(&ct, &pt_kernel, &ek)?;
+        let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::poly())?;
+        let expected = circular(&signal, &kernel[..5], 2, t);
+        assert_eq!(out[..12], expected[..12]);
+        Ok(())
+    }
+}
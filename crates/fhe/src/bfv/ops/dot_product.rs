@@ -5,10 +5,11 @@ use itertools::{izip, Itertools};
 use ndarray::{Array, Array2};
 extern crate alloc;
 use alloc::string::ToString;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use crate::{
-    bfv::{Ciphertext, Plaintext},
+    bfv::{CancellationToken, Ciphertext, Plaintext, RelinearizationKey, Sparsity},
     Error, Result,
 };
 
@@ -59,6 +60,35 @@ where
     I: Iterator<Item = &'a Ciphertext> + Clone,
     J: Iterator<Item = &'a Plaintext> + Clone,
 {
+    dot_product_scalar_inner(ct, pt, None)
+}
+
+/// Compute the dot product between an iterator of [`Ciphertext`] and an
+/// iterator of [`Plaintext`] as [`dot_product_scalar`], checking `token`
+/// between the products. Returns [`Error::Cancelled`] if the token is
+/// cancelled.
+pub fn dot_product_scalar_with_cancellation<'a, I, J>(
+    ct: I,
+    pt: J,
+    token: &CancellationToken,
+) -> Result<Ciphertext>
+where
+    I: Iterator<Item = &'a Ciphertext> + Clone,
+    J: Iterator<Item = &'a Plaintext> + Clone,
+{
+    dot_product_scalar_inner(ct, pt, Some(token))
+}
+
+fn dot_product_scalar_inner<'a, I, J>(
+    ct: I,
+    pt: J,
+    token: Option<&CancellationToken>,
+) -> Result<Ciphertext>
+where
+    I: Iterator<Item = &'a Ciphertext> + Clone,
+    J: Iterator<Item = &'a Plaintext> + Clone,
+{
+    CancellationToken::check(token)?;
     let count = min(ct.clone().count(), pt.clone().count());
     if count == 0 {
         return Err(Error::DefaultError(
@@ -88,16 +118,31 @@ where
 
     if count as u128 > *min_of_max {
         // Too many ciphertexts for the optimized method, instead, we call
-        // `poly_dot_product`.
-        let c = (0..ct_first.len())
-            .map(|i| {
-                poly_dot_product(
-                    ct.clone().map(|cti| unsafe { cti.get_unchecked(i) }),
-                    pt.clone().map(|pti| &pti.poly_ntt),
+        // `poly_dot_product` on chunks of `min_of_max` terms, and check the
+        // token between the chunks.
+        let chunk_size = usize::try_from(*min_of_max).unwrap_or(usize::MAX);
+        let mut c: Vec<Poly> = Vec::with_capacity(ct_first.len());
+        for start in (0..count).step_by(chunk_size) {
+            CancellationToken::check(token)?;
+            for i in 0..ct_first.len() {
+                let chunk = poly_dot_product(
+                    ct.clone()
+                        .skip(start)
+                        .take(chunk_size)
+                        .map(|cti| unsafe { cti.get_unchecked(i) }),
+                    pt.clone()
+                        .skip(start)
+                        .take(chunk_size)
+                        .map(|pti| &pti.poly_ntt),
                 )
-                .map_err(Error::MathError)
-            })
-            .collect::<Result<Vec<Poly>>>()?;
+                .map_err(Error::MathError)?;
+                if let Some(ci) = c.get_mut(i) {
+                    *ci += &chunk;
+                } else {
+                    c.push(chunk);
+                }
+            }
+        }
 
         Ok(Ciphertext {
             par: ct_first.par.clone(),
@@ -108,6 +153,10 @@ where
     } else {
         let mut acc = Array::zeros((ct_first.len(), ctx.moduli().len(), ct_first.par.degree()));
         for (ciphertext, plaintext) in izip!(ct, pt) {
+            CancellationToken::check(token)?;
+            if plaintext.sparsity == Sparsity::Zero {
+                continue;
+            }
             let pt_coefficients = plaintext.poly_ntt.coefficients();
             for (mut acci, ci) in izip!(acc.outer_iter_mut(), ciphertext.iter()) {
                 let ci_coefficients = ci.coefficients();
@@ -157,14 +206,99 @@ where
     }
 }
 
+/// Compute the dot product between two iterators of [`Ciphertext`], and
+/// relinearize the result with `rk`.
+///
+/// The relinearization is lazy: the products of the ciphertexts are summed in
+/// the extended basis of the multiplication, so that the result is scaled
+/// down and relinearized once instead of once per product. The ciphertexts
+/// must have two parts and be at the level of the relinearization key.
+/// Returns an error if the iterator counts are 0, or if the parameters or
+/// the levels don't match.
+pub fn dot_product<'a, 'b, I, J>(lhs: I, rhs: J, rk: &RelinearizationKey) -> Result<Ciphertext>
+where
+    I: Iterator<Item = &'a Ciphertext>,
+    J: Iterator<Item = &'b Ciphertext>,
+{
+    let mut lhs = lhs.peekable();
+    let first = match lhs.peek() {
+        Some(first) => *first,
+        None => {
+            return Err(Error::DefaultError(
+                "At least one iterator is empty".to_string(),
+            ))
+        }
+    };
+    let (par, level) = (first.par.clone(), first.level);
+    let mp = &par.mul_params[level];
+
+    let mut c = vec![Poly::zero(&mp.to, Representation::Ntt); 3];
+    let mut count = 0;
+    for (x, y) in izip!(lhs, rhs) {
+        if x.par != par || y.par != par {
+            return Err(Error::DefaultError("Mismatched parameters".to_string()));
+        }
+        if x.level != level || y.level != level {
+            return Err(Error::DefaultError("Mismatched levels".to_string()));
+        }
+        if x.len() != 2 || y.len() != 2 {
+            return Err(Error::DefaultError(
+                "Only supports ciphertexts with 2 parts".to_string(),
+            ));
+        }
+        let x = x
+            .iter()
+            .map(|xi| xi.scale(&mp.extender).map_err(Error::MathError))
+            .collect::<Result<Vec<Poly>>>()?;
+        let y = y
+            .iter()
+            .map(|yi| yi.scale(&mp.extender).map_err(Error::MathError))
+            .collect::<Result<Vec<Poly>>>()?;
+        c[0] += &(&x[0] * &y[0]);
+        c[1] += &(&x[0] * &y[1]);
+        c[1] += &(&x[1] * &y[0]);
+        c[2] += &(&x[1] * &y[1]);
+        count += 1;
+    }
+    if count == 0 {
+        return Err(Error::DefaultError(
+            "At least one iterator is empty".to_string(),
+        ));
+    }
+
+    let c = c
+        .iter_mut()
+        .map(|ci| {
+            ci.change_representation(Representation::PowerBasis);
+            let mut ci = ci.scale(&mp.down_scaler).map_err(Error::MathError)?;
+            ci.change_representation(Representation::Ntt);
+            Ok(ci)
+        })
+        .collect::<Result<Vec<Poly>>>()?;
+    let mut out = Ciphertext {
+        par,
+        seed: None,
+        c,
+        level,
+    };
+    rk.relinearizes(&mut out)?;
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::dot_product_scalar;
-    use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+    use super::{dot_product, dot_product_scalar};
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, Multiplicator, Plaintext, RelinearizationKey,
+        SecretKey,
+    };
     use crate::Error;
-    use fhe_traits::{FheEncoder, FheEncrypter};
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
     use itertools::{izip, Itertools};
     use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_dot_product_scalar() -> Result<(), Error> {
@@ -198,4 +332,55 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_dot_product() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+        for size in [1, 2, 10] {
+            let values = (0..2 * size)
+                .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+                .collect_vec();
+            let ct = values
+                .iter()
+                .map(|v| {
+                    let pt = Plaintext::try_encode(v, Encoding::simd(), &params).unwrap();
+                    sk.try_encrypt(&pt, &mut rng).unwrap()
+                })
+                .collect_vec();
+
+            let r = dot_product(ct[..size].iter(), ct[size..].iter(), &rk)?;
+            assert_eq!(r.len(), 2);
+
+            let mut expected = vec![0u64; params.degree()];
+            for (x, y) in izip!(&values[..size], &values[size..]) {
+                let mut xy = x.clone();
+                params.plaintext.mul_vec(&mut xy, y);
+                params.plaintext.add_vec(&mut expected, &xy);
+            }
+            let decrypted = Vec::<u64>::try_decode(&sk.try_decrypt(&r)?, Encoding::simd())?;
+            assert_eq!(decrypted, expected);
+
+            let mut eager = multiplicator.multiply(&ct[0], &ct[size])?;
+            for i in 1..size {
+                eager += &multiplicator.multiply(&ct[i], &ct[size + i])?;
+            }
+            assert_eq!(sk.try_decrypt(&eager)?, sk.try_decrypt(&r)?);
+        }
+
+        let ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&[1u64], Encoding::poly(), &params)?,
+            &mut rng,
+        )?;
+        assert!(dot_product([].iter(), [ct.clone()].iter(), &rk).is_err());
+        let mut lower = ct.clone();
+        lower.mod_switch_to_next_level()?;
+        assert!(dot_product([ct.clone()].iter(), [lower].iter(), &rk).is_err());
+        let product = &ct * &ct;
+        assert!(dot_product([ct].iter(), [product].iter(), &rk).is_err());
+        Ok(())
+    }
 }
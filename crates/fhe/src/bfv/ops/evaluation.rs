@@ -0,0 +1,94 @@
+//! Evaluation of an encrypted polynomial at plaintext points.
+
+use crate::bfv::{Ciphertext, Encoding, Plaintext};
+use crate::Result;
+use fhe_traits::FheEncoder;
+extern crate alloc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+impl Ciphertext {
+    /// Evaluates the polynomial encrypted with a polynomial encoding at each
+    /// of the `points` modulo the plaintext modulus.
+    ///
+    /// Returns one ciphertext per point, whose constant coefficient encrypts
+    /// `m(x) = m_0 + m_1 x + ... + m_{n-1} x^{n-1}`, and whose other
+    /// coefficients are arbitrary. Each evaluation is the product by the
+    /// plaintext `1 - x^{n-1} X - ... - x X^{n-1}`, i.e. a row of the
+    /// Vandermonde matrix of the points, so the noise grows as for a product
+    /// by a plaintext with a dense polynomial encoding.
+    ///
+    /// Evaluations at the `2n`-th roots of unity modulo the plaintext modulus
+    /// are obtained more efficiently by decoding with a SIMD encoding.
+    pub fn evaluate_at(&self, points: &[u64]) -> Result<Vec<Ciphertext>> {
+        points
+            .iter()
+            .map(|x| {
+                let pt = self.evaluation_plaintext(*x)?;
+                Ok(self * &pt)
+            })
+            .collect()
+    }
+
+    /// Returns the plaintext whose product with a polynomial `m` has the
+    /// evaluation `m(x)` as constant coefficient, at the level of `self`.
+    ///
+    /// Since `X^{-i} = -X^{n-i}` in the negacyclic ring, this plaintext is
+    /// `1 - sum_{i > 0} x^i X^{n-i}`.
+    fn evaluation_plaintext(&self, x: u64) -> Result<Plaintext> {
+        let t = &self.par.plaintext;
+        let degree = self.par.degree();
+        let x = t.reduce(x);
+        let mut v = vec![0u64; degree];
+        v[0] = 1;
+        let mut xi = 1;
+        for i in 1..degree {
+            xi = t.mul(xi, x);
+            v[degree - i] = t.neg(xi);
+        }
+        Plaintext::try_encode(&v, Encoding::poly_at_level(self.level), &self.par)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn evaluate_at() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(2, 32),
+        ] {
+            let t = &params.plaintext;
+            let sk = SecretKey::random(&params, &mut rng);
+            for level in 0..=params.max_level() {
+                let m = t.random_vec(params.degree(), &mut rng);
+                let pt = Plaintext::try_encode(&m, Encoding::poly_at_level(level), &params)?;
+                let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+                let mut points = t.random_vec(5, &mut rng);
+                points.extend([0, 1, **t + 2]);
+                let evaluations = ct.evaluate_at(&points)?;
+                assert_eq!(evaluations.len(), points.len());
+                for (x, ct_x) in points.iter().zip(&evaluations) {
+                    assert_eq!(ct_x.level(), level);
+                    let expected = m
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, mi| t.add(t.mul(acc, t.reduce(*x)), *mi));
+                    let decrypted =
+                        Vec::<u64>::try_decode(&sk.try_decrypt(ct_x)?, Encoding::poly())?;
+                    assert_eq!(decrypted[0], expected);
+                }
+            }
+        }
+        Ok(())
+    }
+}
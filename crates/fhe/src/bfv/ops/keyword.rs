@@ -0,0 +1,167 @@
+//! Equality of an encrypted byte string with a plaintext keyword.
+//!
+//! The bytes of the string are packed into the first slots of a SIMD-encoded
+//! ciphertext. For a prime plaintext modulus `t`, Fermat's little theorem
+//! gives `x^(t - 1) = 1` for `x != 0` and `0` for `x = 0`, so that raising the
+//! slot-wise difference with the keyword to the power `t - 1` yields a 0/1
+//! mismatch indicator per byte. The inner sum of the indicators counts the
+//! mismatches, and a second exponentiation turns this count into the final
+//! 0/1 indicator.
+
+use crate::bfv::{Ciphertext, Encoding, EvaluationKey, Multiplicator, Plaintext};
+use crate::{Error, Result};
+use fhe_traits::FheEncoder;
+use fhe_util::is_prime;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::vec;
+
+/// Compares the byte string encrypted in `ct` with a plaintext `keyword`.
+///
+/// The slot `i < keyword.len()` of `ct`, in the order of a SIMD encoding,
+/// must encrypt the byte `i` of the string, and the other slots are ignored.
+/// Every slot of the output encrypts 1 if the string is equal to the keyword,
+/// and 0 otherwise.
+///
+/// The plaintext modulus must be a prime larger than 255 and than the length
+/// of the keyword, and the evaluation key must support the inner sum (see
+/// [`EvaluationKey::supports_inner_sum`]). The ciphertext must be at the level
+/// of the `multiplicator`, which must relinearize. The comparison consumes
+/// about `2 * log2(t)` multiplicative levels, and a plaintext modulus of the
+/// form `2^k + 1`, such as 257 or 65537, minimizes the number of
+/// multiplications.
+pub fn keyword_equals(
+    ct: &Ciphertext,
+    keyword: &[u8],
+    ek: &EvaluationKey,
+    multiplicator: &Multiplicator,
+) -> Result<Ciphertext> {
+    let par = &ct.par;
+    let t = par.plaintext();
+    if keyword.is_empty() || keyword.len() > par.degree() {
+        return Err(Error::UnspecifiedInput(
+            "The keyword must contain between 1 and degree bytes".to_string(),
+        ));
+    }
+    if t <= 255 || t <= keyword.len() as u64 || !is_prime(t) {
+        return Err(Error::DefaultError(
+            "The plaintext modulus must be a prime larger than 255 and the keyword length"
+                .to_string(),
+        ));
+    }
+    if !ek.supports_inner_sum() {
+        return Err(Error::DefaultError(
+            "The evaluation key does not support the inner sum".to_string(),
+        ));
+    }
+
+    let encoding = Encoding::simd_at_level(ct.level);
+    let mut values = vec![0u64; par.degree()];
+    values
+        .iter_mut()
+        .zip(keyword)
+        .for_each(|(v, k)| *v = *k as u64);
+    let keyword_pt = Plaintext::try_encode(&values, encoding.clone(), par)?;
+    values.fill(0);
+    values[..keyword.len()].fill(1);
+    let mask = Plaintext::try_encode(&values, encoding.clone(), par)?;
+
+    // The bytes differ by less than `t`, so that the difference is zero
+    // exactly when they are equal, and there are less than `t` mismatches.
+    let mismatches = pow(&(ct - &keyword_pt), t - 1, multiplicator)?;
+    let count = ek.computes_inner_sum(&(&mismatches * &mask))?;
+    let any_mismatch = pow(&count, t - 1, multiplicator)?;
+
+    values.fill(1);
+    let ones = Plaintext::try_encode(&values, encoding, par)?;
+    Ok(&ones - &any_mismatch)
+}
+
+/// Raises the slots of `ct` to the power `exponent >= 1`, by square and
+/// multiply.
+pub(crate) fn pow(
+    ct: &Ciphertext,
+    exponent: u64,
+    multiplicator: &Multiplicator,
+) -> Result<Ciphertext> {
+    let mut out = ct.clone();
+    for i in (0..exponent.ilog2()).rev() {
+        out = multiplicator.multiply(&out, &out)?;
+        if (exponent >> i) & 1 == 1 {
+            out = multiplicator.multiply(&out, ct)?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keyword_equals;
+    use crate::bfv::{
+        BfvParameters, BfvParametersBuilder, Encoding, EvaluationKeyBuilder, Multiplicator,
+        Plaintext, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn keyword_equality() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(257)
+            .set_moduli_sizes(&[62; 10])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+
+        let string = b"fhe.rs!";
+        let mut values = vec![0u64; params.degree()];
+        values
+            .iter_mut()
+            .zip(string)
+            .for_each(|(v, b)| *v = *b as u64);
+        // The slots after the string are ignored.
+        values[string.len()] = 255;
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+        for (keyword, expected) in [
+            (&b"fhe.rs!"[..], 1),
+            (b"fhe.rs?", 0),
+            (b"Fhe.rs!", 0),
+            (b"fhe.rs", 1),
+            (b"fhe.rs!!", 0),
+        ] {
+            let out = keyword_equals(&ct, keyword, &ek, &multiplicator)?;
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::simd())?;
+            assert_eq!(out, vec![expected; params.degree()]);
+        }
+
+        assert!(keyword_equals(&ct, b"", &ek, &multiplicator).is_err());
+        let no_inner_sum = EvaluationKeyBuilder::new(&sk)?.build(&mut rng)?;
+        assert!(keyword_equals(&ct, b"fhe", &no_inner_sum, &multiplicator).is_err());
+
+        let small = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&small, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        let multiplicator = Multiplicator::default(&RelinearizationKey::new(&sk, &mut rng)?)?;
+        let ct = sk.try_encrypt(
+            &Plaintext::try_encode(&values, Encoding::simd(), &small)?,
+            &mut rng,
+        )?;
+        assert!(keyword_equals(&ct, &[0; 17], &ek, &multiplicator).is_err());
+        Ok(())
+    }
+}
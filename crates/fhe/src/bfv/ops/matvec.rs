@@ -0,0 +1,127 @@
+//! Product of a plaintext matrix with an encrypted vector.
+//!
+//! The product uses the diagonal method: with `d = degree / 2` the size of a
+//! row of slots, the product of a `d x d` matrix `M` with a vector `v` is
+//! `sum_i diag_i * rot_i(v)`, where `rot_i` rotates the vector by `i` steps
+//! and the diagonal `diag_i` holds the entries `M[j][(j + i) mod d]`. The
+//! products with the diagonals are summed with [`dot_product_scalar`], which
+//! reduces the sums once.
+
+use super::dot_product_scalar;
+use crate::bfv::{Ciphertext, Encoding, EvaluationKey, Plaintext};
+use crate::{Error, Result};
+use fhe_traits::FheEncoder;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Multiplies the encrypted vector in each row of slots of `ct` by a
+/// plaintext square `matrix` of dimension `degree / 2`.
+///
+/// The slot `j` of each row of the output encrypts the sum over `k` of
+/// `matrix[j][k]` times the slot `k` of the same row of `ct`, in the order of
+/// a SIMD encoding; the entries of the matrix are reduced modulo the
+/// plaintext modulus. The product uses one column rotation per nonzero
+/// diagonal `i > 0` of the matrix, i.e. for each `i` such that
+/// `matrix[j][(j + i) mod (degree / 2)]` is nonzero for some `j`, which must
+/// be generated by the column rotations of `ek` (see [`Ciphertext::rotate`]).
+pub fn matvec(matrix: &[Vec<u64>], ct: &Ciphertext, ek: &EvaluationKey) -> Result<Ciphertext> {
+    let par = &ct.par;
+    let row_size = par.degree() >> 1;
+    if matrix.len() != row_size || matrix.iter().any(|row| row.len() != row_size) {
+        return Err(Error::UnspecifiedInput(
+            "The matrix must be square of dimension degree / 2".to_string(),
+        ));
+    }
+
+    let encoding = Encoding::simd_at_level(ct.level);
+    let mut rotations = Vec::new();
+    let mut diagonals = Vec::new();
+    let mut values = vec![0u64; par.degree()];
+    for i in 0..row_size {
+        for j in 0..row_size {
+            let m = par.plaintext.reduce(matrix[j][(j + i) % row_size]);
+            values[j] = m;
+            values[row_size + j] = m;
+        }
+        // The diagonal 0 is always kept, so that the sum is not empty; it
+        // does not need a rotation.
+        if i > 0 && values.iter().all(|v| *v == 0) {
+            continue;
+        }
+        rotations.push(if i == 0 {
+            ct.clone()
+        } else {
+            ct.rotate(i as i64, ek)?
+        });
+        diagonals.push(Plaintext::try_encode(&values, encoding.clone(), par)?);
+    }
+    dot_product_scalar(rotations.iter(), diagonals.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matvec;
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn matrix_vector_product() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let t = &params.plaintext;
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(4)?
+            .build(&mut rng)?;
+
+        let values = t.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        let random = (0..row_size)
+            .map(|_| t.random_vec(row_size, &mut rng))
+            .collect::<Vec<_>>();
+        // A matrix with the single nonzero diagonal 3.
+        let mut sparse = vec![vec![0u64; row_size]; row_size];
+        (0..row_size).for_each(|j| sparse[j][(j + 3) % row_size] = j as u64 + 1);
+        let zero = vec![vec![0u64; row_size]; row_size];
+        for matrix in [random, sparse, zero] {
+            let out = matvec(&matrix, &ct, &ek)?;
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::simd())?;
+            let mut expected = vec![0u64; params.degree()];
+            for r in 0..2 {
+                for j in 0..row_size {
+                    for k in 0..row_size {
+                        expected[r * row_size + j] = t.add(
+                            expected[r * row_size + j],
+                            t.mul(matrix[j][k], values[r * row_size + k]),
+                        );
+                    }
+                }
+            }
+            assert_eq!(out, expected);
+        }
+
+        assert!(matvec(&vec![vec![0u64; row_size]; row_size - 1], &ct, &ek).is_err());
+        let no_rotation = EvaluationKeyBuilder::new(&sk)?.build(&mut rng)?;
+        let identity = (0..row_size)
+            .map(|j| (0..row_size).map(|k| (j == k) as u64).collect())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matvec(&identity, &ct, &no_rotation)?,
+            &ct * &Plaintext::try_encode(&vec![1u64; params.degree()], Encoding::simd(), &params)?
+        );
+        assert!(matvec(&vec![vec![1u64; row_size]; row_size], &ct, &no_rotation).is_err());
+        Ok(())
+    }
+}
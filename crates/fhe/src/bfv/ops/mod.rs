@@ -1,20 +1,124 @@
 //! Operations over ciphertexts
+//!
+//! Besides the operators implemented on [`Ciphertext`], which panic on
+//! incompatible inputs, this module provides free functions returning a
+//! [`Result`] instead: [`add_many`], [`mul`], [`mul2`], [`mul_plain`],
+//! [`rotate`], and [`dot_product_scalar`]. Their arguments are ordered as the
+//! ciphertexts first, then the plaintexts or other operands, then the keys.
+
+mod checked;
+pub use checked::{add_many, mul, mul2, mul_plain, rotate};
+
+mod conv;
+pub use conv::{conv1d, conv1d_same};
 
 mod dot_product;
-pub use dot_product::dot_product_scalar;
+pub use dot_product::{dot_product, dot_product_scalar, dot_product_scalar_with_cancellation};
+
+mod evaluation;
+
+mod keyword;
+pub use keyword::keyword_equals;
+pub(crate) use keyword::pow;
+
+mod matvec;
+pub use matvec::matvec;
 
 mod mul;
-pub use mul::Multiplicator;
+pub use mul::{MulStrategy, Multiplicator};
+
+mod permutation;
+pub use permutation::PermutationPlan;
+
+mod pir;
+pub use pir::{
+    answer_query, elements_per_plaintext, expand_query, fold_dimension, fold_plaintext_dimension,
+    max_fold_dimensions, pack_database, query_plaintext, selector_plaintext, unpack_element,
+};
 
-use super::{Ciphertext, Plaintext};
+mod reduction;
+pub(crate) use reduction::{constant, evaluate_polynomial, interpolate};
+pub use reduction::{reduce_slots_mod, MAX_INTERPOLATION_BOUND};
+
+mod rotation;
+
+mod scan;
+pub use scan::{prefix_sums, segmented_prefix_sums};
+
+mod shift;
+
+mod transform;
+
+use super::{
+    ciphertext::report_seed_loss, Ciphertext, Plaintext, ScaledPlaintext, SeedLoss, Sparsity,
+};
 use crate::{Error, Result};
 use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use fhe_math::rq::{Poly, Representation};
 use itertools::{izip, Itertools as _};
 extern crate alloc;
+use alloc::string::ToString;
 use alloc::vec;
 use alloc::vec::Vec;
 
+/// Modulo switch the ciphertext at the lowest level (i.e., with the most
+/// moduli) down to the level of the other ciphertext, and returns the common
+/// level.
+///
+/// Modulus switching scales the noise down together with the modulus, and only
+/// adds a small rounding error, so the aligned ciphertexts can be combined
+/// without consuming more of the noise budget than the one of the ciphertext
+/// at the highest level.
+pub fn align_levels(a: &mut Ciphertext, b: &mut Ciphertext) -> Result<usize> {
+    if a.par != b.par {
+        return Err(Error::DefaultError(
+            "Incompatible BFV parameters".to_string(),
+        ));
+    }
+    let level = a.level.max(b.level);
+    a.mod_switch_to_level(level)?;
+    b.mod_switch_to_level(level)?;
+    Ok(level)
+}
+
+impl Ciphertext {
+    /// Returns copies of the two ciphertexts switched to a common level.
+    fn aligned(&self, rhs: &Ciphertext) -> Result<(Ciphertext, Ciphertext)> {
+        let mut a = self.clone();
+        let mut b = rhs.clone();
+        align_levels(&mut a, &mut b)?;
+        if !a.is_empty() && !b.is_empty() && a.len() != b.len() {
+            return Err(Error::DefaultError(
+                "Ciphertexts of different sizes".to_string(),
+            ));
+        }
+        Ok((a, b))
+    }
+
+    /// Adds two ciphertexts, first switching them to a common level with
+    /// [`align_levels`].
+    pub fn checked_add(&self, rhs: &Ciphertext) -> Result<Ciphertext> {
+        let (mut a, b) = self.aligned(rhs)?;
+        a += &b;
+        Ok(a)
+    }
+
+    /// Subtracts two ciphertexts, first switching them to a common level with
+    /// [`align_levels`].
+    pub fn checked_sub(&self, rhs: &Ciphertext) -> Result<Ciphertext> {
+        let (mut a, b) = self.aligned(rhs)?;
+        a -= &b;
+        Ok(a)
+    }
+
+    /// Multiplies two ciphertexts, first switching them to a common level with
+    /// [`align_levels`].
+    pub fn checked_mul(&self, rhs: &Ciphertext) -> Result<Ciphertext> {
+        let (a, b) = self.aligned(rhs)?;
+        Ok(&a * &b)
+    }
+}
+
 impl Add<&Ciphertext> for &Ciphertext {
     type Output = Ciphertext;
 
@@ -35,7 +139,7 @@ impl AddAssign<&Ciphertext> for Ciphertext {
             assert_eq!(self.level, rhs.level);
             assert_eq!(self.len(), rhs.len());
             izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i += c2i);
-            self.seed = None
+            self.drop_seed(SeedLoss::Addition)
         }
     }
 }
@@ -66,7 +170,7 @@ impl AddAssign<&Plaintext> for Ciphertext {
 
         let poly = rhs.to_poly();
         self[0] += &poly;
-        self.seed = None
+        self.drop_seed(SeedLoss::Addition)
     }
 }
 
@@ -90,7 +194,7 @@ impl SubAssign<&Ciphertext> for Ciphertext {
             assert_eq!(self.level, rhs.level);
             assert_eq!(self.len(), rhs.len());
             izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i -= c2i);
-            self.seed = None
+            self.drop_seed(SeedLoss::Addition)
         }
     }
 }
@@ -121,7 +225,65 @@ impl SubAssign<&Plaintext> for Ciphertext {
 
         let poly = rhs.to_poly();
         self.c[0] -= &poly;
-        self.seed = None
+        self.drop_seed(SeedLoss::Addition)
+    }
+}
+
+impl Add<&ScaledPlaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &ScaledPlaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone += rhs;
+        self_clone
+    }
+}
+
+impl Add<&Ciphertext> for &ScaledPlaintext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &Ciphertext) -> Ciphertext {
+        rhs + self
+    }
+}
+
+impl AddAssign<&ScaledPlaintext> for Ciphertext {
+    fn add_assign(&mut self, rhs: &ScaledPlaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert!(!self.is_empty());
+        assert_eq!(self.level, rhs.level);
+
+        self.c[0] += &rhs.poly;
+        self.drop_seed(SeedLoss::Addition)
+    }
+}
+
+impl Sub<&ScaledPlaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: &ScaledPlaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone -= rhs;
+        self_clone
+    }
+}
+
+impl Sub<&Ciphertext> for &ScaledPlaintext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: &Ciphertext) -> Ciphertext {
+        -(rhs - self)
+    }
+}
+
+impl SubAssign<&ScaledPlaintext> for Ciphertext {
+    fn sub_assign(&mut self, rhs: &ScaledPlaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert!(!self.is_empty());
+        assert_eq!(self.level, rhs.level);
+
+        self.c[0] -= &rhs.poly;
+        self.drop_seed(SeedLoss::Addition)
     }
 }
 
@@ -129,6 +291,9 @@ impl Neg for &Ciphertext {
     type Output = Ciphertext;
 
     fn neg(self) -> Ciphertext {
+        if self.seed.is_some() {
+            report_seed_loss(SeedLoss::Negation)
+        }
         let c = self.iter().map(|c1i| -c1i).collect_vec();
         Ciphertext {
             par: self.par.clone(),
@@ -144,7 +309,7 @@ impl Neg for Ciphertext {
 
     fn neg(mut self) -> Ciphertext {
         self.iter_mut().for_each(|c1i| *c1i = -&*c1i);
-        self.seed = None;
+        self.drop_seed(SeedLoss::Negation);
         self
     }
 }
@@ -154,9 +319,19 @@ impl MulAssign<&Plaintext> for Ciphertext {
         assert_eq!(self.par, rhs.par);
         if !self.is_empty() {
             assert_eq!(self.level, rhs.level);
-            self.iter_mut().for_each(|ci| *ci *= &rhs.poly_ntt);
+            match rhs.sparsity {
+                Sparsity::Dense => self.iter_mut().for_each(|ci| *ci *= &rhs.poly_ntt),
+                Sparsity::Zero => self.iter_mut().for_each(|ci| {
+                    *ci = Poly::zero(ci.ctx(), Representation::Ntt);
+                    unsafe { ci.allow_variable_time_computations() }
+                }),
+                Sparsity::Constant(c) => self.iter_mut().for_each(|ci| {
+                    unsafe { ci.allow_variable_time_computations() }
+                    ci.multiply_scalar(c)
+                }),
+            }
         }
-        self.seed = None
+        self.drop_seed(SeedLoss::Multiplication)
     }
 }
 
@@ -279,10 +454,10 @@ impl Mul<&Ciphertext> for &Ciphertext {
 #[cfg(test)]
 mod tests {
     use crate::bfv::{
-        encoding::EncodingEnum, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey,
+        encoding::EncodingEnum, BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey, Sparsity,
     };
     use crate::Error;
-    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncoderVariableTime, FheEncrypter};
     use rand::{rngs::OsRng, thread_rng};
     extern crate alloc;
     use alloc::vec;
@@ -474,6 +649,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn add_sub_cached_plaintext() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let a = params.plaintext.random_vec(params.degree(), &mut rng);
+            let b = params.plaintext.random_vec(params.degree(), &mut rng);
+            let mut sum = a.clone();
+            params.plaintext.add_vec(&mut sum, &b);
+            let mut difference = a.clone();
+            params.plaintext.sub_vec(&mut difference, &b);
+            let mut b_neg = b.clone();
+            params.plaintext.neg_vec(&mut b_neg);
+
+            let sk = SecretKey::random(&params, &mut rng);
+            for encoding in [Encoding::poly(), Encoding::simd()] {
+                let pt_a = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+                let pt_b = Plaintext::try_encode(&b, encoding.clone(), &params)?;
+                let ct_a: Ciphertext = sk.try_encrypt(&pt_a, &mut rng)?;
+                let decrypt = |ct: &Ciphertext| -> Result<Vec<u64>, Error> {
+                    Vec::<u64>::try_decode(&sk.try_decrypt(ct)?, encoding.clone())
+                };
+
+                let pt_b_neg = -&pt_b;
+                assert_eq!(Vec::<u64>::try_decode(&pt_b_neg, encoding.clone())?, b_neg);
+                assert_eq!(decrypt(&(&ct_a + &pt_b_neg))?, difference);
+                assert_eq!(decrypt(&(&ct_a * &pt_b_neg))?, decrypt(&-&(&ct_a * &pt_b))?);
+                let zero = Plaintext::zero(encoding.clone(), &params)?;
+                assert_eq!(-&zero, zero);
+
+                let scaled = pt_b.scaled_for_add();
+                assert_eq!(scaled.level(), pt_b.level());
+                assert_eq!(decrypt(&(&ct_a + &scaled))?, sum);
+                assert_eq!(decrypt(&(&scaled + &ct_a))?, sum);
+                assert_eq!(decrypt(&(&ct_a - &scaled))?, difference);
+                assert_eq!(decrypt(&(&ct_a + &-&scaled))?, difference);
+                let mut ct = ct_a.clone();
+                ct -= &scaled;
+                assert_eq!(ct, &ct_a - &pt_b);
+                ct += &scaled;
+                assert_eq!(ct, ct_a);
+                let mut negated_difference = difference.clone();
+                params.plaintext.neg_vec(&mut negated_difference);
+                assert_eq!(decrypt(&(&scaled - &ct_a))?, negated_difference);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn neg() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -562,6 +788,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mul_zero_and_constant_plaintexts() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let a = params.plaintext.random_vec(params.degree(), &mut rng);
+            let ct: Ciphertext = sk.try_encrypt(
+                &Plaintext::try_encode(&a, Encoding::simd(), &params)?,
+                &mut rng,
+            )?;
+
+            for (mask, sparsity) in [
+                (vec![0u64; params.degree()], Sparsity::Zero),
+                (vec![1u64; params.degree()], Sparsity::Constant(1)),
+                (vec![5u64; params.degree()], Sparsity::Constant(5)),
+                (vec![5u64], Sparsity::Dense),
+            ] {
+                let pt_dense = Plaintext::try_encode(&mask, Encoding::simd(), &params)?;
+                let pt_sparse =
+                    unsafe { Plaintext::try_encode_vt(&mask, Encoding::simd(), &params)? };
+                assert_eq!(pt_dense.sparsity, Sparsity::Dense);
+                assert_eq!(pt_sparse.sparsity, sparsity);
+                assert_eq!(
+                    pt_sparse.poly_ntt.coefficients(),
+                    pt_dense.poly_ntt.coefficients()
+                );
+
+                let ct_sparse = &ct * &pt_sparse;
+                let ct_dense = &ct * &pt_dense;
+                for (ci_sparse, ci_dense) in ct_sparse.iter().zip(ct_dense.iter()) {
+                    assert_eq!(ci_sparse.coefficients(), ci_dense.coefficients());
+                }
+
+                let mut expected = mask.clone();
+                expected.resize(params.degree(), 0);
+                params.plaintext.mul_vec(&mut expected, &a);
+                let pt = sk.try_decrypt(&ct_sparse)?;
+                assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, expected);
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn mul() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -623,4 +896,50 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn align_levels() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let par = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&par, &mut rng);
+        let v1 = par.plaintext.random_vec(par.degree(), &mut rng);
+        let v2 = par.plaintext.random_vec(par.degree(), &mut rng);
+        let pt1 = Plaintext::try_encode(&v1, Encoding::simd(), &par)?;
+        let pt2 = Plaintext::try_encode(&v2, Encoding::simd(), &par)?;
+
+        let mut ct1: Ciphertext = sk.try_encrypt(&pt1, &mut rng)?;
+        let mut ct2: Ciphertext = sk.try_encrypt(&pt2, &mut rng)?;
+        ct2.mod_switch_to_level(2)?;
+        assert_eq!(super::align_levels(&mut ct1, &mut ct2)?, 2);
+        assert_eq!(ct1.level(), 2);
+        assert_eq!(ct2.level(), 2);
+
+        let ct1: Ciphertext = sk.try_encrypt(&pt1, &mut rng)?;
+        for (a, b) in [(&ct1, &ct2), (&ct2, &ct1)] {
+            let mut expected = v1.clone();
+            par.plaintext.add_vec(&mut expected, &v2);
+            let ct = a.checked_add(b)?;
+            assert_eq!(ct.level(), 2);
+            let pt = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, expected);
+
+            let mut expected = v1.clone();
+            par.plaintext.mul_vec(&mut expected, &v2);
+            let pt = sk.try_decrypt(&a.checked_mul(b)?)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, expected);
+        }
+
+        let mut expected = v1.clone();
+        par.plaintext.sub_vec(&mut expected, &v2);
+        let pt = sk.try_decrypt(&ct1.checked_sub(&ct2)?)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, expected);
+
+        let other = BfvParameters::default_arc(6, 32);
+        let ct3: Ciphertext = SecretKey::random(&other, &mut rng).try_encrypt(
+            &Plaintext::try_encode(&[1u64], Encoding::simd(), &other)?,
+            &mut rng,
+        )?;
+        assert!(ct1.checked_add(&ct3).is_err());
+        Ok(())
+    }
 }
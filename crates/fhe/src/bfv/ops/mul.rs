@@ -1,11 +1,11 @@
 extern crate alloc;
-use alloc::sync::Arc;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 
 use fhe_math::{
-    rns::ScalingFactor,
+    rns::{RnsContext, ScalingFactor},
     rq::{scaler::Scaler, Context, Representation},
     zq::primes::generate_prime,
 };
@@ -25,15 +25,69 @@ use crate::{
 /// - Whether relinearization should be used.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Multiplicator {
-    par: Arc<BfvParameters>,
+    pub(crate) par: Arc<BfvParameters>,
     pub(crate) extender_lhs: Scaler,
     pub(crate) extender_rhs: Scaler,
     pub(crate) down_scaler: Scaler,
     pub(crate) base_ctx: Arc<Context>,
     pub(crate) mul_ctx: Arc<Context>,
-    rk: Option<RelinearizationKey>,
+    rk: Option<Arc<RelinearizationKey>>,
     mod_switch: bool,
     level: usize,
+    strategy: MulStrategy,
+}
+
+/// Strategies to multiply ciphertexts in RNS representation, which differ in
+/// the auxiliary basis in which the product is computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulStrategy {
+    /// Extends both operands to an auxiliary basis larger than the square of
+    /// the ciphertext modulus `q`, and scales the product by `t / q`. This is
+    /// the strategy of the `*` operator.
+    Extension,
+    /// Switches the right operand to an auxiliary modulus `p` of about the size
+    /// of `q` before extending both operands, and scales the product by `t /
+    /// p`; this is the second strategy of <https://eprint.iacr.org/2021/204>.
+    RhsSwitching,
+    /// Selects the strategy with the fewest auxiliary moduli for the level of
+    /// the relinearization key, and the extension strategy on a tie. The
+    /// strategy is resolved once, when the multiplicator is constructed, from
+    /// the size of the ciphertext modulus at that level only, and not from
+    /// the noise of the operands: both strategies yield the same noise up to
+    /// a few bits before relinearization, whose noise then dominates, so the
+    /// size of the auxiliary basis, which sets the number of NTTs, is what
+    /// differs.
+    Auto,
+    /// Custom scaling factors and basis, as in [`Multiplicator::new`].
+    Custom,
+}
+
+impl MulStrategy {
+    /// Resolves `Auto` into a concrete strategy for a ciphertext modulus of
+    /// `modulus_size` bits.
+    fn resolve(self, modulus_size: usize) -> Self {
+        match self {
+            MulStrategy::Auto => {
+                if MulStrategy::RhsSwitching.extension_moduli(modulus_size)
+                    < MulStrategy::Extension.extension_moduli(modulus_size)
+                {
+                    MulStrategy::RhsSwitching
+                } else {
+                    MulStrategy::Extension
+                }
+            }
+            strategy => strategy,
+        }
+    }
+
+    /// Number of 62-bit auxiliary moduli for a ciphertext modulus of
+    /// `modulus_size` bits.
+    fn extension_moduli(self, modulus_size: usize) -> usize {
+        match self {
+            MulStrategy::RhsSwitching => modulus_size.div_ceil(62),
+            _ => (modulus_size + 60).div_ceil(62),
+        }
+    }
 }
 
 impl Multiplicator {
@@ -99,43 +153,100 @@ impl Multiplicator {
             rk: None,
             mod_switch: false,
             level,
+            strategy: MulStrategy::Custom,
         })
     }
 
     /// Default multiplication strategy using relinearization.
     pub fn default(rk: &RelinearizationKey) -> Result<Self> {
-        let ctx = rk.ksk.par.ctx_at_level(rk.ksk.ciphertext_level)?;
+        Self::default_shared(&Arc::new(rk.clone()))
+    }
 
-        let modulus_size = rk.ksk.par.moduli_sizes()[..ctx.moduli().len()]
+    /// Default multiplication strategy using relinearization, with a
+    /// relinearization key shared with other multiplicators instead of copied,
+    /// e.g. by one multiplicator per thread.
+    pub fn default_shared(rk: &Arc<RelinearizationKey>) -> Result<Self> {
+        Self::with_strategy(MulStrategy::Extension, rk)
+    }
+
+    /// Multiplication using relinearization with the given strategy, at the
+    /// level of the relinearization key.
+    ///
+    /// With [`MulStrategy::Auto`], the strategy is selected from the moduli of
+    /// the ciphertexts at that level, so that callers do not need to know how
+    /// the strategies differ; [`Multiplicator::new`] remains available to
+    /// experiment with other scaling factors and bases.
+    ///
+    /// Returns an error for [`MulStrategy::Custom`], which has no predefined
+    /// basis.
+    pub fn with_strategy(strategy: MulStrategy, rk: &Arc<RelinearizationKey>) -> Result<Self> {
+        if strategy == MulStrategy::Custom {
+            return Err(Error::DefaultError(
+                "A custom strategy requires explicit scaling factors".to_string(),
+            ));
+        }
+        let par = &rk.ksk.par;
+        let level = rk.ksk.ciphertext_level;
+        let ctx = par.ctx_at_level(level)?;
+        let modulus_size = par.moduli_sizes()[..ctx.moduli().len()]
             .iter()
             .sum::<usize>();
-        let n_moduli = (modulus_size + 60).div_ceil(62);
 
+        let strategy = strategy.resolve(modulus_size);
+        let n_moduli = strategy.extension_moduli(modulus_size);
         let mut extended_basis = Vec::with_capacity(ctx.moduli().len() + n_moduli);
         extended_basis.append(&mut ctx.moduli().to_vec());
         let mut upper_bound = 1 << 62;
         while extended_basis.len() != ctx.moduli().len() + n_moduli {
-            upper_bound = generate_prime(62, 2 * rk.ksk.par.degree() as u64, upper_bound).unwrap();
+            upper_bound = generate_prime(62, 2 * par.degree() as u64, upper_bound).unwrap();
             if !extended_basis.contains(&upper_bound) && !ctx.moduli().contains(&upper_bound) {
                 extended_basis.push(upper_bound)
             }
         }
 
-        let mut multiplicator = Self::new_leveled_internal(
-            ScalingFactor::one(),
-            ScalingFactor::one(),
-            &extended_basis,
-            ScalingFactor::new(&BigUint::from(*rk.ksk.par.plaintext), ctx.modulus()),
-            rk.ksk.ciphertext_level,
-            &rk.ksk.par,
-        )?;
+        let plaintext = BigUint::from(*par.plaintext);
+        let mut multiplicator = match strategy {
+            MulStrategy::RhsSwitching => {
+                let rns = RnsContext::new(&extended_basis[ctx.moduli().len()..])?;
+                Self::new_leveled_internal(
+                    ScalingFactor::one(),
+                    ScalingFactor::new(rns.modulus(), ctx.modulus()),
+                    &extended_basis,
+                    ScalingFactor::new(&plaintext, rns.modulus()),
+                    level,
+                    par,
+                )?
+            }
+            _ => Self::new_leveled_internal(
+                ScalingFactor::one(),
+                ScalingFactor::one(),
+                &extended_basis,
+                ScalingFactor::new(&plaintext, ctx.modulus()),
+                level,
+                par,
+            )?,
+        };
+        multiplicator.strategy = strategy;
 
-        multiplicator.enable_relinearization(rk)?;
+        multiplicator.enable_shared_relinearization(rk)?;
         Ok(multiplicator)
     }
 
+    /// Returns the strategy used by the multiplicator, as resolved when it was
+    /// constructed. Multiplicators constructed with custom scaling factors
+    /// report [`MulStrategy::Custom`].
+    pub fn strategy(&self) -> MulStrategy {
+        self.strategy
+    }
+
     /// Enable relinearization after multiplication.
     pub fn enable_relinearization(&mut self, rk: &RelinearizationKey) -> Result<()> {
+        self.enable_shared_relinearization(&Arc::new(rk.clone()))
+    }
+
+    /// Enable relinearization after multiplication, with a relinearization key
+    /// shared with other multiplicators instead of copied.
+    pub fn enable_shared_relinearization(&mut self, rk: &Arc<RelinearizationKey>) -> Result<()> {
         let rk_ctx = self.par.ctx_at_level(rk.ksk.ciphertext_level)?;
         if rk_ctx != &self.base_ctx {
             return Err(Error::DefaultError(
@@ -253,9 +364,10 @@ mod tests {
     use num_bigint::BigUint;
     use rand::{rngs::OsRng, thread_rng};
     extern crate alloc;
+    use alloc::sync::Arc;
     use alloc::vec::Vec;
 
-    use super::Multiplicator;
+    use super::{MulStrategy, Multiplicator};
 
     #[test]
     fn mul() -> Result<(), Error> {
@@ -410,4 +522,80 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn mul_with_strategy() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let par = BfvParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&par, &mut OsRng);
+        let rk = Arc::new(RelinearizationKey::new(&sk, &mut rng)?);
+        let values = par.plaintext.random_vec(par.degree(), &mut rng);
+        let mut expected = values.clone();
+        par.plaintext.mul_vec(&mut expected, &values);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &par)?;
+        let ct1: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let ct2: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        for strategy in [
+            MulStrategy::Extension,
+            MulStrategy::RhsSwitching,
+            MulStrategy::Auto,
+        ] {
+            let multiplicator = Multiplicator::with_strategy(strategy, &rk)?;
+            assert_ne!(multiplicator.strategy(), MulStrategy::Auto);
+            if strategy != MulStrategy::Auto {
+                assert_eq!(multiplicator.strategy(), strategy);
+            }
+            let ct3 = multiplicator.multiply(&ct1, &ct2)?;
+            let pt = sk.try_decrypt(&ct3)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, expected);
+        }
+
+        // Three moduli of 62 bits need four auxiliary moduli with the extension
+        // strategy, and three when switching the right operand.
+        assert_eq!(MulStrategy::Auto.resolve(186), MulStrategy::RhsSwitching);
+        // On a tie, the extension strategy is selected.
+        assert_eq!(MulStrategy::Auto.resolve(125), MulStrategy::Extension);
+        assert_eq!(
+            Multiplicator::default(&rk)?.strategy(),
+            MulStrategy::Extension
+        );
+        assert!(Multiplicator::with_strategy(MulStrategy::Custom, &rk).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mul_strategies_noise() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for par in [
+            BfvParameters::default_arc(2, 16),
+            BfvParameters::default_arc(3, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&par, &mut OsRng);
+            let rk = Arc::new(RelinearizationKey::new(&sk, &mut rng)?);
+            let extension = Multiplicator::with_strategy(MulStrategy::Extension, &rk)?;
+            let switching = Multiplicator::with_strategy(MulStrategy::RhsSwitching, &rk)?;
+            // Without relinearization, whose noise dominates.
+            let mut extension_no_relin = extension.clone();
+            let mut switching_no_relin = switching.clone();
+            extension_no_relin.rk = None;
+            switching_no_relin.rk = None;
+            for _ in 0..10 {
+                let values = par.plaintext.random_vec(par.degree(), &mut rng);
+                let pt = Plaintext::try_encode(&values, Encoding::simd(), &par)?;
+                let ct1: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+                let ct2: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+                for (m1, m2, bits) in [
+                    (&extension, &switching, 2),
+                    (&extension_no_relin, &switching_no_relin, 3),
+                ] {
+                    let noise1 = unsafe { sk.measure_noise(&m1.multiply(&ct1, &ct2)?)? };
+                    let noise2 = unsafe { sk.measure_noise(&m2.multiply(&ct1, &ct2)?)? };
+                    assert!(noise1.abs_diff(noise2) <= bits);
+                }
+            }
+        }
+        Ok(())
+    }
 }
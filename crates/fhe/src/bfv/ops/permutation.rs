@@ -0,0 +1,307 @@
+//! Arbitrary permutations of the slots of SIMD-encoded ciphertexts.
+//!
+//! The slots of a ciphertext form two rows of `degree / 2` slots, and a slot
+//! is indexed by `row * degree / 2 + column` as in a SIMD encoding. A
+//! permutation of the slots is routed through a Beneš network: each layer of
+//! the network swaps the pairs of slots whose indices differ by one bit, which
+//! is either a column bit, swapped by column rotations by `±2^j`, or the row
+//! bit, swapped by a row rotation. A layer is evaluated as a sum of rotated
+//! ciphertexts multiplied by 0/1 mask plaintexts.
+
+use crate::bfv::{BfvParameters, Ciphertext, Encoding, EvaluationKey, Plaintext};
+use crate::{Error, Result};
+use fhe_traits::FheEncoder;
+extern crate alloc;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The rotation applied to the ciphertext in a term of a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rotation {
+    None,
+    Rows,
+    Columns(usize),
+}
+
+/// A term `mask * rotation(ct)` of a layer, where a mask of `None` selects
+/// all the slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    rotation: Rotation,
+    mask: Option<Vec<u64>>,
+}
+
+/// A permutation of the slots of SIMD-encoded ciphertexts, compiled into a
+/// sequence of rotations and masks which can be applied to many ciphertexts.
+///
+/// The plan is a Beneš network of at most `2 * log2(degree) - 1` layers, from
+/// which the layers that do not move any slot are removed. Each layer costs
+/// up to two rotations and three plaintext multiplications, so that the
+/// noise grows as for a product by a plaintext per layer; a permutation within
+/// the rows only, or which exchanges the rows, needs fewer layers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermutationPlan {
+    par: Arc<BfvParameters>,
+    layers: Vec<Vec<Term>>,
+}
+
+impl PermutationPlan {
+    /// Compiles the permutation which moves slot `i` to slot `permutation[i]`.
+    ///
+    /// Returns an error if `permutation` is not a permutation of the `degree`
+    /// slots, or if the parameters do not support the SIMD encoding.
+    pub fn new(permutation: &[usize], par: &Arc<BfvParameters>) -> Result<Self> {
+        let degree = par.degree();
+        if par.op.is_none() {
+            return Err(Error::DefaultError(
+                "The parameters do not support the SIMD encoding".to_string(),
+            ));
+        }
+        if permutation.len() != degree {
+            return Err(Error::UnspecifiedInput(
+                "There must be one destination per slot".to_string(),
+            ));
+        }
+        let mut src = vec![usize::MAX; degree];
+        for (i, j) in permutation.iter().enumerate() {
+            if *j >= degree || src[*j] != usize::MAX {
+                return Err(Error::UnspecifiedInput(
+                    "The destinations must be a permutation of the slots".to_string(),
+                ));
+            }
+            src[*j] = i;
+        }
+
+        // Route the column bits in the outer layers, so that the row bit is
+        // only swapped once, in the middle layer.
+        let row_size = degree >> 1;
+        let row_bit = row_size.ilog2() as usize;
+        let bits = (0..=row_bit).collect::<Vec<_>>();
+        let layers = route(&src, &bits)
+            .into_iter()
+            .filter(|(_, swap)| swap.iter().any(|s| *s))
+            .map(|(bit, swap)| layer_terms(bit, &swap, row_size))
+            .collect();
+        Ok(Self {
+            par: par.clone(),
+            layers,
+        })
+    }
+
+    /// Returns the column rotations used by this plan, which must be enabled
+    /// in the [`EvaluationKey`], e.g. with
+    /// [`EvaluationKeyBuilder::enable_column_rotation`](crate::bfv::EvaluationKeyBuilder::enable_column_rotation).
+    pub fn column_rotations(&self) -> Vec<usize> {
+        let mut rotations = self
+            .terms()
+            .filter_map(|term| match term.rotation {
+                Rotation::Columns(i) => Some(i),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        rotations.sort_unstable();
+        rotations.dedup();
+        rotations
+    }
+
+    /// Reports whether this plan rotates the rows, which must then be enabled
+    /// in the [`EvaluationKey`].
+    pub fn uses_row_rotation(&self) -> bool {
+        self.terms().any(|term| term.rotation == Rotation::Rows)
+    }
+
+    /// Returns the number of layers multiplying the ciphertext by masks.
+    pub fn depth(&self) -> usize {
+        self.layers
+            .iter()
+            .filter(|layer| layer.iter().any(|term| term.mask.is_some()))
+            .count()
+    }
+
+    /// Applies the permutation to the slots of `ct`, at the level of `ct`.
+    pub fn apply(&self, ct: &Ciphertext, ek: &EvaluationKey) -> Result<Ciphertext> {
+        if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        let mut out = ct.clone();
+        for layer in &self.layers {
+            let mut acc: Option<Ciphertext> = None;
+            for term in layer {
+                let rotated = match term.rotation {
+                    Rotation::None => Cow::Borrowed(&out),
+                    Rotation::Rows => Cow::Owned(ek.rotates_rows(&out)?),
+                    Rotation::Columns(i) => Cow::Owned(ek.rotates_columns_by(&out, i)?),
+                };
+                let term = if let Some(mask) = &term.mask {
+                    let mask =
+                        Plaintext::try_encode(mask, Encoding::simd_at_level(out.level), &out.par)?;
+                    rotated.as_ref() * &mask
+                } else {
+                    rotated.into_owned()
+                };
+                match acc.as_mut() {
+                    Some(acc) => *acc += &term,
+                    None => acc = Some(term),
+                }
+            }
+            if let Some(acc) = acc {
+                out = acc;
+            }
+        }
+        Ok(out)
+    }
+
+    fn terms(&self) -> impl Iterator<Item = &Term> {
+        self.layers.iter().flatten()
+    }
+}
+
+/// Routes the permutation which moves slot `src[j]` to slot `j` through a
+/// Beneš network over the index `bits`, where the first bit is swapped in the
+/// outer layers. Returns the layers as the swapped bit and whether each slot
+/// is swapped with its partner, i.e. receives the slot whose index differs by
+/// that bit.
+fn route(src: &[usize], bits: &[usize]) -> Vec<(usize, Vec<bool>)> {
+    let bit = bits[0];
+    let d = 1 << bit;
+    if bits.len() == 1 {
+        let swap = src.iter().enumerate().map(|(j, i)| *i != j).collect();
+        return vec![(bit, swap)];
+    }
+
+    let mut dst = vec![0; src.len()];
+    src.iter().enumerate().for_each(|(j, i)| dst[*i] = j);
+
+    // Assign each slot to one of the two subnetworks, such that the two slots
+    // of an input pair, and the sources of the two slots of an output pair,
+    // are in different subnetworks, by following the alternating cycles.
+    let mut sub: Vec<Option<usize>> = vec![None; src.len()];
+    for start in 0..src.len() {
+        let mut i = start;
+        while sub[i].is_none() {
+            sub[i] = Some(0);
+            let partner = i ^ d;
+            sub[partner] = Some(1);
+            i = src[dst[partner] ^ d];
+        }
+    }
+    let sub = sub.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+    let position = |i: usize, s: usize| (i & !d) | (s << bit);
+    let mut mid_src = vec![0; src.len()];
+    let mut input = vec![false; src.len()];
+    let mut output = vec![false; src.len()];
+    for (i, s) in sub.iter().enumerate() {
+        input[position(i, *s)] = position(i, *s) != i;
+    }
+    for (j, i) in src.iter().enumerate() {
+        let p = position(j, sub[*i]);
+        output[j] = p != j;
+        mid_src[p] = position(*i, sub[*i]);
+    }
+
+    let mut layers = vec![(bit, input)];
+    layers.extend(route(&mid_src, &bits[1..]));
+    layers.push((bit, output));
+    layers
+}
+
+/// Returns the terms of a layer swapping the slots flagged in `swap` with
+/// their partner for the index `bit`.
+fn layer_terms(bit: usize, swap: &[bool], row_size: usize) -> Vec<Term> {
+    let d = 1 << bit;
+    let mut terms: Vec<Term> = vec![];
+    for (j, s) in swap.iter().enumerate() {
+        let rotation = if !*s {
+            Rotation::None
+        } else if d == row_size {
+            Rotation::Rows
+        } else if j & d == 0 {
+            // Slot `j` of a rotation of the columns by `d` holds slot `j + d`.
+            Rotation::Columns(d)
+        } else {
+            Rotation::Columns(row_size - d)
+        };
+        let index = match terms.iter().position(|t| t.rotation == rotation) {
+            Some(index) => index,
+            None => {
+                terms.push(Term {
+                    rotation,
+                    mask: Some(vec![0; swap.len()]),
+                });
+                terms.len() - 1
+            }
+        };
+        terms[index].mask.as_mut().unwrap()[j] = 1;
+    }
+    for term in terms.iter_mut() {
+        if term.mask.as_ref().unwrap().iter().all(|m| *m == 1) {
+            term.mask = None;
+        }
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PermutationPlan;
+    use crate::bfv::{BfvParameters, Encoding, EvaluationKeyBuilder, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{seq::SliceRandom, thread_rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn permutation_plan() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let degree = params.degree();
+        let row_size = degree >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let mut builder = EvaluationKeyBuilder::new(&sk)?;
+        builder.enable_row_rotation()?;
+        for j in 0..row_size.ilog2() {
+            builder.enable_column_rotation(1 << j)?;
+            builder.enable_column_rotation(row_size - (1 << j))?;
+        }
+        let ek = builder.build(&mut rng)?;
+
+        let values = params.plaintext.random_vec(degree, &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+        let mut random = (0..degree).collect::<Vec<_>>();
+        random.shuffle(&mut rng);
+        let reversed = (0..degree).rev().collect::<Vec<_>>();
+        let rows_swapped = (0..degree).map(|i| i ^ row_size).collect::<Vec<_>>();
+        let identity = (0..degree).collect::<Vec<_>>();
+        for permutation in [random, reversed, rows_swapped.clone(), identity.clone()] {
+            let plan = PermutationPlan::new(&permutation, &params)?;
+            assert!(plan.depth() < 2 * degree.ilog2() as usize);
+            let out =
+                Vec::<u64>::try_decode(&sk.try_decrypt(&plan.apply(&ct, &ek)?)?, Encoding::simd())?;
+            for (i, j) in permutation.iter().enumerate() {
+                assert_eq!(out[*j], values[i]);
+            }
+        }
+
+        // Exchanging the rows is a single row rotation.
+        let plan = PermutationPlan::new(&rows_swapped, &params)?;
+        assert_eq!(plan.depth(), 0);
+        assert!(plan.uses_row_rotation());
+        assert!(plan.column_rotations().is_empty());
+        assert_eq!(PermutationPlan::new(&identity, &params)?.depth(), 0);
+
+        let mut invalid = identity.clone();
+        invalid[0] = 1;
+        assert!(PermutationPlan::new(&invalid, &params).is_err());
+        assert!(PermutationPlan::new(&identity[1..], &params).is_err());
+        Ok(())
+    }
+}
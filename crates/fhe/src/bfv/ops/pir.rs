@@ -0,0 +1,463 @@
+//! Folding of the dimensions of a database, as used by recursive PIR.
+//!
+//! A database of `d_1 × ... × d_k` plaintexts is queried with one selector
+//! ciphertext per dimension. Each selector is obliviously expanded into `d_i`
+//! encryptions of 0 or 1, and the database is folded one dimension at a time:
+//! the first dimension with [`fold_plaintext_dimension`], and the following
+//! ones with [`fold_dimension`].
+//!
+//! The toolkit also covers a complete PIR protocol in the style of SealPIR: the
+//! server packs a database of byte strings into plaintexts with
+//! [`pack_database`], the client encrypts the output of [`query_plaintext`],
+//! a single ciphertext selecting one index in every dimension, the server
+//! answers with [`answer_query`], and the client decrypts the answer and
+//! extracts the element with [`unpack_element`].
+
+use crate::bfv::parameters::ceil_log2;
+use crate::bfv::{
+    dot_product_scalar, BfvParameters, Ciphertext, CiphertextMatrix, Encoding, EvaluationKey,
+    Multiplicator, Plaintext,
+};
+use crate::{Error, Result};
+use fhe_traits::{FheDecoder, FheEncoder};
+use fhe_util::{transcode_from_bytes, transcode_to_bytes};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Returns the plaintext to encrypt in order to select `index` in a dimension
+/// of `dimension_size` hyper-rows.
+///
+/// The plaintext encodes the inverse of `2^ceil(log2(dimension_size))` in
+/// the coefficient `index`, so that the oblivious expansion of its encryption
+/// yields an encryption of 1 at position `index`, and encryptions of 0
+/// elsewhere. This requires an odd plaintext modulus.
+pub fn selector_plaintext(
+    index: usize,
+    dimension_size: usize,
+    par: &Arc<BfvParameters>,
+) -> Result<Plaintext> {
+    if index >= dimension_size || dimension_size > par.degree() {
+        return Err(Error::UnspecifiedInput(
+            "The index must be smaller than the dimension size, which must be at most the degree"
+                .to_string(),
+        ));
+    }
+    let t = par.plaintext();
+    if t % 2 == 0 {
+        return Err(Error::UnspecifiedInput(
+            "The plaintext modulus must be odd".to_string(),
+        ));
+    }
+    let level = dimension_size.next_power_of_two().ilog2() as u64;
+    let mut value = vec![0u64; par.degree()];
+    value[index] = par.plaintext.pow(t.div_ceil(2), level);
+    Plaintext::try_encode(&value, Encoding::poly(), par)
+}
+
+/// Expands a selector into `dimension_size` encryptions of 0 or 1.
+fn expand_selector(
+    selector: &Ciphertext,
+    dimension_size: usize,
+    ek: &EvaluationKey,
+) -> Result<Vec<Ciphertext>> {
+    if dimension_size == 0 {
+        return Err(Error::UnspecifiedInput(
+            "The dimension must not be empty".to_string(),
+        ));
+    }
+    ek.expands(selector, dimension_size)
+}
+
+/// Folds the first dimension of a plaintext database.
+///
+/// The database dimension contains `d` hyper-rows of `m` plaintexts each,
+/// given in row-major order, and the selector encrypts the output of
+/// [`selector_plaintext`] for an index `i < d`. The output is an encryption of
+/// the `m` plaintexts of the hyper-row `i`. The evaluation key must support
+/// the expansion to `ceil(log2(d))` levels.
+pub fn fold_plaintext_dimension(
+    hyper_rows: &[Plaintext],
+    dimension_size: usize,
+    selector: &Ciphertext,
+    ek: &EvaluationKey,
+) -> Result<Vec<Ciphertext>> {
+    if dimension_size == 0 || hyper_rows.len() % dimension_size != 0 {
+        return Err(Error::UnspecifiedInput(
+            "The number of plaintexts is not a multiple of the dimension size".to_string(),
+        ));
+    }
+    let selectors = expand_selector(selector, dimension_size, ek)?;
+    fold_plaintext_rows(hyper_rows, &selectors)
+}
+
+/// Folds `hyper_rows`, given in row-major order, with one expanded selector
+/// per hyper-row.
+fn fold_plaintext_rows(
+    hyper_rows: &[Plaintext],
+    selectors: &[Ciphertext],
+) -> Result<Vec<Ciphertext>> {
+    let m = hyper_rows.len() / selectors.len();
+    (0..m)
+        .map(|j| dot_product_scalar(selectors.iter(), hyper_rows.iter().skip(j).step_by(m)))
+        .collect()
+}
+
+/// Folds one dimension of an encrypted database, e.g. the output of the
+/// folding of the previous dimension.
+///
+/// The database dimension contains `d` hyper-rows of `m` ciphertexts each,
+/// given in row-major order, and the selector encrypts the output of
+/// [`selector_plaintext`] for an index `i < d`. The output is an encryption of
+/// the `m` values of the hyper-row `i`, packed into `m` ciphertexts. The
+/// evaluation key must support the expansion to `ceil(log2(d))` levels, and
+/// each folding consumes one multiplicative level; see
+/// [`max_fold_dimensions`] for the number of dimensions supported by the
+/// parameters.
+pub fn fold_dimension(
+    hyper_rows: &[Ciphertext],
+    dimension_size: usize,
+    selector: &Ciphertext,
+    ek: &EvaluationKey,
+    multiplicator: &Multiplicator,
+) -> Result<Vec<Ciphertext>> {
+    if dimension_size == 0 || hyper_rows.len() % dimension_size != 0 {
+        return Err(Error::UnspecifiedInput(
+            "The number of ciphertexts is not a multiple of the dimension size".to_string(),
+        ));
+    }
+    let m = hyper_rows.len() / dimension_size;
+    let selectors = expand_selector(selector, dimension_size, ek)?;
+    CiphertextMatrix::new(hyper_rows.to_vec(), dimension_size, m)?
+        .fold_rows(&selectors, multiplicator)
+}
+
+/// Returns the number of dimensions of size `dimension_size` that can be
+/// folded, the first one with [`fold_plaintext_dimension`] and the following
+/// ones with [`fold_dimension`], while guaranteeing a correct decryption
+/// according to worst-case noise bounds.
+///
+/// The bounds are those of [`BfvParameters::max_mul_depth`]. Each level of the
+/// oblivious expansion doubles the noise of the selector and adds the noise of
+/// a key switching; folding a dimension multiplies the noise by the plaintext
+/// (respectively ciphertext) multiplication growth, and the sum over the
+/// hyper-rows adds `log2(dimension_size)` bits. Since these bounds are
+/// worst-case, the actual number of dimensions is usually larger.
+pub fn max_fold_dimensions(par: &Arc<BfvParameters>, dimension_size: usize) -> Result<usize> {
+    let Some(bounds) = par.noise_bounds(0)? else {
+        return Ok(0);
+    };
+    let log_d = ceil_log2(dimension_size as u128);
+    let mut selector = bounds.fresh;
+    for _ in 0..log_d {
+        selector = selector.max(bounds.relinearization) + 1;
+    }
+
+    let mut noise = selector + ceil_log2(par.plaintext() as u128 * par.degree() as u128) + log_d;
+    let mut dimensions = 0;
+    while noise < bounds.budget {
+        dimensions += 1;
+        noise = (noise.max(selector) + bounds.growth).max(bounds.relinearization) + 1 + log_d;
+    }
+    Ok(dimensions)
+}
+
+/// Returns the number of bits stored in each coefficient of the plaintexts
+/// packed by [`pack_database`], i.e. `floor(log2(t))` for the plaintext
+/// modulus `t`.
+fn bits_per_coefficient(par: &BfvParameters) -> usize {
+    par.plaintext().ilog2() as usize
+}
+
+/// Returns the number of elements of `element_size` bytes packed in each
+/// plaintext by [`pack_database`].
+pub fn elements_per_plaintext(par: &BfvParameters, element_size: usize) -> usize {
+    if element_size == 0 {
+        return 0;
+    }
+    bits_per_coefficient(par) * par.degree() / (element_size * 8)
+}
+
+/// Packs a database of elements of the same size into plaintexts with the
+/// `Poly` encoding, [`elements_per_plaintext`] elements per plaintext, in
+/// order. Each coefficient stores `floor(log2(t))` bits of the elements.
+///
+/// Returns an error if the database is empty, if the elements do not have the
+/// same size, or if an element does not fit in a plaintext. The number of
+/// plaintexts is not padded: a database queried with [`answer_query`] must be
+/// completed with zero plaintexts to the product of the dimensions.
+pub fn pack_database<E: AsRef<[u8]>>(
+    elements: &[E],
+    par: &Arc<BfvParameters>,
+) -> Result<Vec<Plaintext>> {
+    let Some(first) = elements.first() else {
+        return Err(Error::UnspecifiedInput("The database is empty".to_string()));
+    };
+    let element_size = first.as_ref().len();
+    if elements.iter().any(|e| e.as_ref().len() != element_size) {
+        return Err(Error::UnspecifiedInput(
+            "The elements must have the same size".to_string(),
+        ));
+    }
+    let per_plaintext = elements_per_plaintext(par, element_size);
+    if per_plaintext == 0 {
+        return Err(Error::UnspecifiedInput(
+            "The elements must be non-empty and fit in a plaintext".to_string(),
+        ));
+    }
+    let nbits = bits_per_coefficient(par);
+    elements
+        .chunks(per_plaintext)
+        .map(|chunk| {
+            let bytes = chunk
+                .iter()
+                .flat_map(|e| e.as_ref())
+                .copied()
+                .collect::<Vec<u8>>();
+            let mut value = transcode_from_bytes(&bytes, nbits);
+            value.resize(par.degree(), 0);
+            Plaintext::try_encode(&value, Encoding::poly(), par)
+        })
+        .collect()
+}
+
+/// Extracts the element of index `index` in the database, of `element_size`
+/// bytes, from the (decrypted) plaintext of [`pack_database`] containing it.
+pub fn unpack_element(pt: &Plaintext, index: usize, element_size: usize) -> Result<Vec<u8>> {
+    let per_plaintext = elements_per_plaintext(&pt.par, element_size);
+    if per_plaintext == 0 {
+        return Err(Error::UnspecifiedInput(
+            "The elements must be non-empty and fit in a plaintext".to_string(),
+        ));
+    }
+    let value = Vec::<u64>::try_decode(pt, Encoding::poly_at_level(pt.level))?;
+    let bytes = transcode_to_bytes(&value, bits_per_coefficient(&pt.par));
+    let offset = (index % per_plaintext) * element_size;
+    Ok(bytes[offset..offset + element_size].to_vec())
+}
+
+/// Returns an error if the dimensions are empty or contain an empty
+/// dimension.
+fn check_dimensions(dimensions: &[usize]) -> Result<()> {
+    if dimensions.is_empty() || dimensions.contains(&0) {
+        return Err(Error::UnspecifiedInput(
+            "The dimensions must be non-empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the plaintext to encrypt in order to query the plaintext of index
+/// `index` in a database of `dimensions[0] × ... × dimensions[k - 1]`
+/// plaintexts, given in row-major order.
+///
+/// Unlike [`selector_plaintext`], a single ciphertext selects one index in
+/// every dimension: the plaintext encodes the inverse of `2^ceil(log2(s))`,
+/// for the sum `s` of the dimensions, in the coefficient of the coordinate of
+/// the index in each dimension, the dimensions being laid out one after the
+/// other. This requires an odd plaintext modulus, and `s` must be at most the
+/// degree.
+pub fn query_plaintext(
+    index: usize,
+    dimensions: &[usize],
+    par: &Arc<BfvParameters>,
+) -> Result<Plaintext> {
+    check_dimensions(dimensions)?;
+    let size = dimensions.iter().sum::<usize>();
+    if index >= dimensions.iter().product::<usize>() || size > par.degree() {
+        return Err(Error::UnspecifiedInput(
+            "The index must be smaller than the database size, and the sum of the dimensions at most the degree"
+                .to_string(),
+        ));
+    }
+    let t = par.plaintext();
+    if t % 2 == 0 {
+        return Err(Error::UnspecifiedInput(
+            "The plaintext modulus must be odd".to_string(),
+        ));
+    }
+    let level = size.next_power_of_two().ilog2() as u64;
+    let inverse = par.plaintext.pow(t.div_ceil(2), level);
+    let mut value = vec![0u64; par.degree()];
+    let mut remainder = index;
+    let mut offset = size;
+    for d in dimensions.iter().rev() {
+        offset -= d;
+        value[offset + remainder % d] = inverse;
+        remainder /= d;
+    }
+    Plaintext::try_encode(&value, Encoding::poly(), par)
+}
+
+/// Expands an encryption of the output of [`query_plaintext`] into one vector
+/// of selectors per dimension, each of them containing encryptions of 0 or 1.
+/// The evaluation key must support the expansion to `ceil(log2(s))` levels,
+/// for the sum `s` of the dimensions.
+pub fn expand_query(
+    query: &Ciphertext,
+    dimensions: &[usize],
+    ek: &EvaluationKey,
+) -> Result<Vec<Vec<Ciphertext>>> {
+    check_dimensions(dimensions)?;
+    let mut expanded = ek.expands(query, dimensions.iter().sum())?.into_iter();
+    Ok(dimensions
+        .iter()
+        .map(|d| expanded.by_ref().take(*d).collect())
+        .collect())
+}
+
+/// Answers a query, i.e. an encryption of the output of [`query_plaintext`],
+/// over a database of `dimensions[0] × ... × dimensions[k - 1]` plaintexts
+/// given in row-major order. The output is an encryption of the queried
+/// plaintext.
+///
+/// The query is expanded with [`expand_query`], the first dimension is folded
+/// with products by the plaintexts as in [`fold_plaintext_dimension`], and the
+/// following ones with the `multiplicator` as in [`fold_dimension`]; it is
+/// only needed, and an error is returned if it is missing, when there are
+/// several dimensions.
+pub fn answer_query(
+    database: &[Plaintext],
+    dimensions: &[usize],
+    query: &Ciphertext,
+    ek: &EvaluationKey,
+    multiplicator: Option<&Multiplicator>,
+) -> Result<Ciphertext> {
+    check_dimensions(dimensions)?;
+    if database.len() != dimensions.iter().product::<usize>() {
+        return Err(Error::UnspecifiedInput(
+            "The number of plaintexts does not match the dimensions".to_string(),
+        ));
+    }
+    let selectors = expand_query(query, dimensions, ek)?;
+    let mut folded = fold_plaintext_rows(database, &selectors[0])?;
+    for (d, selectors) in dimensions.iter().zip(&selectors).skip(1) {
+        let multiplicator = multiplicator.ok_or_else(|| {
+            Error::UnspecifiedInput(
+                "A multiplicator is needed to fold several dimensions".to_string(),
+            )
+        })?;
+        let cols = folded.len() / d;
+        folded = CiphertextMatrix::new(folded, *d, cols)?.fold_rows(selectors, multiplicator)?;
+    }
+    Ok(folded.remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        answer_query, elements_per_plaintext, fold_dimension, fold_plaintext_dimension,
+        max_fold_dimensions, pack_database, query_plaintext, selector_plaintext, unpack_element,
+    };
+    use crate::bfv::{
+        BfvParameters, Encoding, EvaluationKeyBuilder, Multiplicator, Plaintext,
+        RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, RngCore};
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn fold_two_dimensions() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_expansion(2)?
+            .build(&mut rng)?;
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+        assert!(max_fold_dimensions(&params, 4)? >= 2);
+
+        // A database of 4 × 3 plaintexts.
+        let (d1, d2) = (4, 3);
+        let values = (0..d1 * d2)
+            .map(|_| params.plaintext.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+        let database = values
+            .iter()
+            .map(|v| Plaintext::try_encode(v, Encoding::simd(), &params))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let (i, j) = (2, 1);
+        let query1 = sk.try_encrypt(&selector_plaintext(i, d1, &params)?, &mut rng)?;
+        let query2 = sk.try_encrypt(&selector_plaintext(j, d2, &params)?, &mut rng)?;
+
+        let folded = fold_plaintext_dimension(&database, d1, &query1, &ek)?;
+        assert_eq!(folded.len(), d2);
+        let answer = fold_dimension(&folded, d2, &query2, &ek, &multiplicator)?;
+        assert_eq!(answer.len(), 1);
+
+        let pt = sk.try_decrypt(&answer[0])?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&pt, Encoding::simd())?,
+            values[i * d2 + j]
+        );
+
+        assert!(selector_plaintext(4, 4, &params).is_err());
+        assert!(fold_dimension(&folded, 2, &query2, &ek, &multiplicator).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn max_fold_dimensions_bounds() -> Result<(), Error> {
+        let params = BfvParameters::default_arc(6, 16);
+        let small = BfvParameters::default_arc(2, 16);
+        assert!(max_fold_dimensions(&params, 16)? <= max_fold_dimensions(&params, 2)?);
+        assert!(max_fold_dimensions(&small, 4)? < max_fold_dimensions(&params, 4)?);
+        Ok(())
+    }
+
+    #[test]
+    fn packed_database_query() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_expansion(4)?
+            .build(&mut rng)?;
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+
+        // 37 elements of 5 bytes, packed 4 per plaintext with t = 1153, and
+        // completed to 4 × 3 plaintexts.
+        let element_size = 5;
+        assert_eq!(elements_per_plaintext(&params, element_size), 4);
+        let elements = (0..37)
+            .map(|_| {
+                let mut element = vec![0u8; element_size];
+                rng.fill_bytes(&mut element);
+                element
+            })
+            .collect::<Vec<_>>();
+        let mut database = pack_database(&elements, &params)?;
+        assert_eq!(database.len(), 10);
+        database.resize(12, Plaintext::zero(Encoding::poly(), &params)?);
+
+        for dimensions in [vec![12], vec![4, 3], vec![2, 3, 2]] {
+            for index in [0, 17, 36] {
+                let pt = query_plaintext(index / 4, &dimensions, &params)?;
+                let query = sk.try_encrypt(&pt, &mut rng)?;
+                let answer =
+                    answer_query(&database, &dimensions, &query, &ek, Some(&multiplicator))?;
+                let pt = sk.try_decrypt(&answer)?;
+                assert_eq!(unpack_element(&pt, index, element_size)?, elements[index]);
+            }
+        }
+
+        let pt = query_plaintext(5, &[4, 3], &params)?;
+        let query = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(answer_query(&database, &[4, 3], &query, &ek, None).is_err());
+        assert!(answer_query(&database, &[4, 2], &query, &ek, Some(&multiplicator)).is_err());
+        assert!(query_plaintext(12, &[4, 3], &params).is_err());
+        assert!(query_plaintext(0, &[9, 9], &params).is_err());
+        assert!(pack_database(&[vec![0u8; 5], vec![0u8; 4]], &params).is_err());
+        assert!(pack_database(&[vec![0u8; 21]], &params).is_err());
+        Ok(())
+    }
+}
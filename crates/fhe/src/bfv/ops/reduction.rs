@@ -0,0 +1,275 @@
+//! Reduction of the slots of SIMD-encoded ciphertexts by a smaller modulus.
+//!
+//! For a prime plaintext modulus `t`, any function of the slots is a
+//! polynomial of degree less than `t`. The reduction of the slots smaller
+//! than a public bound `B <= t` modulo `m` is the polynomial of degree less
+//! than `B` interpolating `x mod m` at the points `0..B`, which is evaluated
+//! with the baby-step giant-step algorithm: the baby steps are the powers
+//! `x^i` for `i <= k` with `k` about `sqrt(B)`, the giant steps are the
+//! powers `x^(k 2^j)`, and the polynomial is split recursively into chunks
+//! of `k` coefficients multiplied by the giant steps.
+
+use crate::bfv::{Ciphertext, Encoding, Multiplicator, Plaintext};
+use crate::{Error, Result};
+use fhe_math::zq::Modulus;
+use fhe_traits::FheEncoder;
+use fhe_util::is_prime;
+extern crate alloc;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The largest number of points interpolated by [`reduce_slots_mod`] and
+/// [`lt_const`](crate::bfv::circuits::lt_const), i.e. the largest `bound` of
+/// the former and the largest plaintext modulus of the latter.
+///
+/// The interpolation costs about `bound^2` operations modulo the plaintext
+/// modulus and `bound` words of memory before any homomorphic operation, and
+/// the evaluation a multiplicative depth of `ceil(log2(bound))`, beyond the
+/// budget of practical parameters for larger bounds.
+pub const MAX_INTERPOLATION_BOUND: u64 = 1 << 14;
+
+/// Reduces the slots of `ct` modulo `m`.
+///
+/// The slots must encrypt values smaller than `bound`, at most the plaintext
+/// modulus `t`, which must be prime: with `bound = t`, the slots may encrypt
+/// any value, e.g. counters accumulated modulo `t`. Each slot of the output
+/// encrypts the value of the same slot modulo `m`.
+///
+/// The ciphertext must be at the level of the `multiplicator`, which must
+/// relinearize. The reduction evaluates a polynomial of degree `bound - 1`,
+/// with about `2 sqrt(bound)` ciphertext multiplications and a
+/// multiplicative depth of `ceil(log2(bound))`, followed by a product by a
+/// constant; a smaller bound therefore saves both multiplications and levels.
+///
+/// Returns an error if `m < bound` and `bound` is larger than
+/// [`MAX_INTERPOLATION_BOUND`].
+pub fn reduce_slots_mod(
+    ct: &Ciphertext,
+    m: u64,
+    bound: u64,
+    multiplicator: &Multiplicator,
+) -> Result<Ciphertext> {
+    let t = ct.par.plaintext();
+    if m == 0 || bound == 0 || bound > t {
+        return Err(Error::UnspecifiedInput(
+            "The modulus must be non-zero, and the bound between 1 and the plaintext modulus"
+                .to_string(),
+        ));
+    }
+    if !is_prime(t) {
+        return Err(Error::DefaultError(
+            "The plaintext modulus must be prime".to_string(),
+        ));
+    }
+    if m >= bound {
+        return Ok(ct.clone());
+    }
+    let coefficients = interpolate(bound, &ct.par.plaintext, |x| x % m)?;
+    evaluate_polynomial(ct, &coefficients, multiplicator)
+}
+
+/// Returns the coefficients of the polynomial of degree less than `bound`
+/// which interpolates `f(x)` at the points `0..bound`, modulo the prime
+/// `t >= bound`.
+///
+/// Returns an error if `bound` is larger than [`MAX_INTERPOLATION_BOUND`].
+pub(crate) fn interpolate<F: Fn(u64) -> u64>(bound: u64, t: &Modulus, f: F) -> Result<Vec<u64>> {
+    if bound > MAX_INTERPOLATION_BOUND {
+        return Err(Error::UnspecifiedInput(format!(
+            "The interpolation of {bound} points exceeds the maximum of {MAX_INTERPOLATION_BOUND}"
+        )));
+    }
+    let b = bound as usize;
+
+    // The coefficients of prod_{j < bound} (X - j), of degree `bound`.
+    let mut master = vec![0u64; b + 1];
+    master[0] = 1;
+    for j in 0..b {
+        for k in (0..=j + 1).rev() {
+            let previous = if k > 0 { master[k - 1] } else { 0 };
+            master[k] = t.sub(previous, t.mul(master[k], j as u64));
+        }
+    }
+
+    // Lagrange interpolation, where the basis polynomial of the point `i` is
+    // `q_i(X) / q_i(i)` with `q_i(X) = prod_{j != i} (X - j)`.
+    let mut coefficients = vec![0u64; b];
+    let mut q = vec![0u64; b];
+    for i in 0..bound {
+        let y = t.reduce(f(i));
+        if y == 0 {
+            continue;
+        }
+        q[b - 1] = master[b];
+        for k in (1..b).rev() {
+            q[k - 1] = t.add(master[k], t.mul(i, q[k]));
+        }
+        let denominator = q.iter().rev().fold(0, |acc, qk| t.add(t.mul(acc, i), *qk));
+        // The points are distinct modulo the prime `t`, so that the
+        // denominator is invertible.
+        let weight = t.mul(y, t.inv(denominator).unwrap());
+        for (c, qk) in coefficients.iter_mut().zip(&q) {
+            *c = t.add(*c, t.mul(weight, *qk));
+        }
+    }
+    Ok(coefficients)
+}
+
+/// Evaluates the polynomial with `coefficients`, from the constant one, at
+/// the slots of `ct`, with the baby-step giant-step algorithm.
+pub(crate) fn evaluate_polynomial(
+    ct: &Ciphertext,
+    coefficients: &[u64],
+    multiplicator: &Multiplicator,
+) -> Result<Ciphertext> {
+    let degree = coefficients.len().saturating_sub(1).max(1);
+    let k = 1usize << degree.ilog2().div_ceil(2);
+
+    // The baby steps x^1, ..., x^k, where x^i is the product of the largest
+    // power of two smaller than i and of the rest, with depth ceil(log2(i)).
+    let mut baby: Vec<Ciphertext> = vec![ct.clone()];
+    for i in 2..=k {
+        let high = 1 << (i - 1).ilog2();
+        let power = multiplicator.multiply(&baby[high - 1], &baby[i - high - 1])?;
+        baby.push(power);
+    }
+
+    // The giant steps x^(k 2^j), while k 2^j is smaller than the degree.
+    let mut giant = vec![baby[k - 1].clone()];
+    while k << giant.len() <= degree {
+        let last = giant.last().unwrap();
+        giant.push(multiplicator.multiply(last, last)?);
+    }
+
+    let out = evaluate_chunks(coefficients, &baby, &giant, multiplicator)?;
+    match out {
+        Some(out) => Ok(out),
+        None => Ok(&baby[0] * &constant(ct, 0)?),
+    }
+}
+
+/// Evaluates the polynomial with `coefficients` from the baby and giant
+/// steps, or returns `None` if the polynomial is zero.
+fn evaluate_chunks(
+    coefficients: &[u64],
+    baby: &[Ciphertext],
+    giant: &[Ciphertext],
+    multiplicator: &Multiplicator,
+) -> Result<Option<Ciphertext>> {
+    let k = baby.len();
+    if coefficients.len() <= k + 1 {
+        // A linear combination of the baby steps, with a constant term.
+        let ct = &baby[0];
+        let mut out: Option<Ciphertext> = None;
+        for (c, power) in coefficients.iter().skip(1).zip(baby) {
+            if *c != 0 {
+                let term = power * &constant(ct, *c)?;
+                match out.as_mut() {
+                    Some(out) => *out += &term,
+                    None => out = Some(term),
+                }
+            }
+        }
+        if let Some(c0) = coefficients.first().filter(|c0| **c0 != 0) {
+            let c0 = constant(ct, *c0)?;
+            out = Some(match out {
+                Some(out) => &out + &c0,
+                None => &(ct * &constant(ct, 0)?) + &c0,
+            });
+        }
+        return Ok(out);
+    }
+
+    // Split the polynomial at the largest giant step k 2^j smaller than its
+    // degree, as low(x) + x^(k 2^j) high(x).
+    let j = ((coefficients.len() - 1) / k).ilog2() as usize;
+    let split = k << j;
+    let low = evaluate_chunks(&coefficients[..split], baby, giant, multiplicator)?;
+    let high = evaluate_chunks(&coefficients[split..], baby, giant, multiplicator)?;
+    Ok(match (low, high) {
+        (low, None) => low,
+        (None, Some(high)) => Some(multiplicator.multiply(&high, &giant[j])?),
+        (Some(low), Some(high)) => Some(&low + &multiplicator.multiply(&high, &giant[j])?),
+    })
+}
+
+/// Returns the plaintext encoding the constant `c` at the level of `ct`.
+pub(crate) fn constant(ct: &Ciphertext, c: u64) -> Result<Plaintext> {
+    Plaintext::try_encode(&[c], Encoding::poly_at_level(ct.level), &ct.par)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, reduce_slots_mod, MAX_INTERPOLATION_BOUND};
+    use crate::bfv::{
+        BfvParametersBuilder, Encoding, Multiplicator, Plaintext, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_math::zq::Modulus;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn interpolation() -> Result<(), Error> {
+        let t = Modulus::new(257)?;
+        for (m, bound) in [(2, 257), (3, 100), (10, 11), (2, 3)] {
+            let coefficients = interpolate(bound, &t, |x| x % m)?;
+            for x in 0..bound {
+                let y = coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, c| t.add(t.mul(acc, x), *c));
+                assert_eq!(y, x % m);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reduce_slots() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(257)
+            .set_moduli_sizes(&[62; 8])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+
+        for (m, bound) in [(2, 257), (5, 40), (7, 7), (2, 2)] {
+            let values = (0..params.degree())
+                .map(|_| rng.gen_range(0..bound))
+                .collect::<Vec<_>>();
+            let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+            let ct = sk.try_encrypt(&pt, &mut rng)?;
+            let out = reduce_slots_mod(&ct, m, bound, &multiplicator)?;
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::simd())?;
+            assert_eq!(out, values.iter().map(|v| v % m).collect::<Vec<_>>());
+        }
+
+        let pt = Plaintext::try_encode(&[1u64], Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(reduce_slots_mod(&ct, 0, 10, &multiplicator).is_err());
+        assert!(reduce_slots_mod(&ct, 2, 258, &multiplicator).is_err());
+        assert!(interpolate(MAX_INTERPOLATION_BOUND + 1, &Modulus::new(65537)?, |x| x).is_err());
+
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1024)
+            .set_moduli_sizes(&[62; 2])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let multiplicator = Multiplicator::default(&RelinearizationKey::new(&sk, &mut rng)?)?;
+        let pt = Plaintext::try_encode(&[1u64], Encoding::poly(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(reduce_slots_mod(&ct, 2, 4, &multiplicator).is_err());
+        Ok(())
+    }
+}
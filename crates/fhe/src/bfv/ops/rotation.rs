@@ -0,0 +1,84 @@
+//! Rotation of the columns by an arbitrary number of steps.
+
+use crate::bfv::{Ciphertext, EvaluationKey};
+use crate::Result;
+
+impl Ciphertext {
+    /// Rotates the columns of the plaintext by `steps`, so that slot `i` of
+    /// each row holds the slot `i + steps` of the same row, modulo
+    /// `degree / 2`; a negative number of steps rotates in the other
+    /// direction.
+    ///
+    /// The rotation does not need to be enabled in the evaluation key: it is
+    /// composed of the column rotations supported by `ek` with the fewest key
+    /// switchings, each of which adds noise. An evaluation key built with
+    /// [`EvaluationKeyBuilder::enable_baby_step_giant_step_rotations`](crate::bfv::EvaluationKeyBuilder::enable_baby_step_giant_step_rotations)
+    /// rotates by any number of steps with at most two key switchings.
+    ///
+    /// Returns an error if the column rotations of `ek` do not generate this
+    /// rotation.
+    pub fn rotate(&self, steps: i64, ek: &EvaluationKey) -> Result<Ciphertext> {
+        let row_size = self.par.degree() >> 1;
+        let i = steps.rem_euclid(row_size as i64) as usize;
+        let path = ek.column_rotation_path(i)?;
+        let mut out = self.clone();
+        for step in path {
+            out = ek.rotates_columns_by(&out, step)?;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn rotate() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 32);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        let bsgs = EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(4)?
+            .build(&mut rng)?;
+        let single = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(1)?
+            .build(&mut rng)?;
+        for ek in [&bsgs, &single] {
+            for steps in [-20i64, -1, 0, 1, 5, 15, 16, 33] {
+                let rotated = ct.rotate(steps, ek)?;
+                let out = Vec::<u64>::try_decode(&sk.try_decrypt(&rotated)?, Encoding::simd())?;
+                for row in [0, row_size] {
+                    for i in 0..row_size {
+                        let j = (i as i64 + steps).rem_euclid(row_size as i64) as usize;
+                        assert_eq!(out[row + i], values[row + j]);
+                    }
+                }
+            }
+        }
+        assert_eq!(bsgs.column_rotation_path(15)?.len(), 2);
+        assert_eq!(single.column_rotation_path(15)?, Vec::from([1; 15]));
+
+        let even = EvaluationKeyBuilder::new(&sk)?
+            .enable_column_rotation(2)?
+            .build(&mut rng)?;
+        assert!(ct.rotate(2, &even).is_ok());
+        assert!(ct.rotate(1, &even).is_err());
+        assert!(EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(row_size)
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Prefix sums over the slots of SIMD-encoded ciphertexts.
+//!
+//! The slots of a ciphertext form two rows of `degree / 2` slots, and the
+//! prefix sums are computed independently in each row, using `log2` column
+//! rotations and additions. Slot `i` of a rotation by `degree / 2 - k` holds
+//! the slot `i - k` of the same row, so the evaluation key must support
+//! rotating the columns by `degree / 2 - 2^j` for the powers of two `2^j`
+//! used by the scan.
+
+use crate::bfv::{Ciphertext, Encoding, EvaluationKey, Plaintext};
+use crate::{Error, Result};
+use fhe_traits::FheEncoder;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// Computes the inclusive prefix sums of the first `len` slots of each row.
+///
+/// The slots `len..degree / 2` of each row must encrypt zero, and `len` must
+/// be at most `degree / 4`: the rotations then bring in zero slots instead of
+/// wrapping around, and no plaintext multiplication is needed. Slot `i <
+/// len` of each row of the output encrypts the sum of the slots `0..=i` of the
+/// same row, and the other slots are unspecified. The evaluation key must
+/// support rotating the columns by `degree / 2 - 2^j` for every `2^j < len`.
+pub fn prefix_sums(ct: &Ciphertext, len: usize, ek: &EvaluationKey) -> Result<Ciphertext> {
+    let row_size = ct.par.degree() >> 1;
+    if len > row_size >> 1 {
+        return Err(Error::UnspecifiedInput(
+            "The length must be at most a quarter of the degree".to_string(),
+        ));
+    }
+    let mut out = ct.clone();
+    let mut k = 1;
+    while k < len {
+        out += &ek.rotates_columns_by(&out, row_size - k)?;
+        k <<= 1;
+    }
+    Ok(out)
+}
+
+/// Computes the inclusive prefix sums of each segment of slots.
+///
+/// The slots are split into segments by the public `segment_starts`, which
+/// contains `degree` flags in the order of the slots of a SIMD encoding, and
+/// where a segment starts at each set flag and at the first slot of each row.
+/// Slot `i` of the output encrypts the sum of the slots of its segment up to
+/// `i`. Each step of the scan multiplies the ciphertext by a mask plaintext,
+/// and the number of steps is the base-2 logarithm of the length of the
+/// longest segment, rounded up. The evaluation key must support rotating the
+/// columns by `degree / 2 - 2^j` for every `2^j` smaller than this length.
+pub fn segmented_prefix_sums(
+    ct: &Ciphertext,
+    segment_starts: &[bool],
+    ek: &EvaluationKey,
+) -> Result<Ciphertext> {
+    let degree = ct.par.degree();
+    if segment_starts.len() != degree {
+        return Err(Error::UnspecifiedInput(
+            "There must be one segment flag per slot".to_string(),
+        ));
+    }
+    let row_size = degree >> 1;
+
+    // The offset of each slot in its segment.
+    let mut offsets = Vec::with_capacity(degree);
+    for (i, start) in segment_starts.iter().enumerate() {
+        if *start || i % row_size == 0 {
+            offsets.push(0)
+        } else {
+            offsets.push(offsets[i - 1] + 1)
+        }
+    }
+    let longest = offsets.iter().max().map_or(0, |o| o + 1);
+
+    let mut out = ct.clone();
+    let mut k = 1;
+    while k < longest {
+        // Only the slots with at least `k` previous slots in their segment
+        // receive the slot `k` positions before.
+        let mask = offsets
+            .iter()
+            .map(|offset| (*offset >= k) as u64)
+            .collect::<Vec<_>>();
+        let mask = Plaintext::try_encode(&mask, Encoding::simd_at_level(ct.level), &ct.par)?;
+        let rotated = ek.rotates_columns_by(&out, row_size - k)?;
+        out += &(&rotated * &mask);
+        k <<= 1;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prefix_sums, segmented_prefix_sums};
+    use crate::bfv::{BfvParameters, Encoding, EvaluationKeyBuilder, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn prefix_sums_rows() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 32);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let mut builder = EvaluationKeyBuilder::new(&sk)?;
+        for j in 0..row_size.ilog2() {
+            builder.enable_column_rotation(row_size - (1 << j))?;
+        }
+        let ek = builder.build(&mut rng)?;
+
+        let len = row_size / 2;
+        let mut values = params.plaintext.random_vec(params.degree(), &mut rng);
+        values[len..row_size].fill(0);
+        values[row_size + len..].fill(0);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+        for l in [0, 1, 5, len] {
+            let out = prefix_sums(&ct, l, &ek)?;
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::simd())?;
+            for row in [0, row_size] {
+                let mut sum = 0;
+                for i in 0..l {
+                    sum = params.plaintext.add(sum, values[row + i]);
+                    assert_eq!(out[row + i], sum);
+                }
+            }
+        }
+        assert!(prefix_sums(&ct, len + 1, &ek).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn segmented_prefix_sums_rows() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 32);
+        let row_size = params.degree() >> 1;
+        let sk = SecretKey::random(&params, &mut rng);
+        let mut builder = EvaluationKeyBuilder::new(&sk)?;
+        for j in 0..row_size.ilog2() {
+            builder.enable_column_rotation(row_size - (1 << j))?;
+        }
+        let ek = builder.build(&mut rng)?;
+
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct = sk.try_encrypt(&pt, &mut rng)?;
+
+        let mut starts = (0..params.degree())
+            .map(|_| rng.gen_bool(0.3))
+            .collect::<Vec<_>>();
+        for segment_starts in [starts.clone(), vec![false; params.degree()]] {
+            let out = segmented_prefix_sums(&ct, &segment_starts, &ek)?;
+            let out = Vec::<u64>::try_decode(&sk.try_decrypt(&out)?, Encoding::simd())?;
+            let mut sum = 0;
+            for i in 0..params.degree() {
+                if segment_starts[i] || i % row_size == 0 {
+                    sum = 0;
+                }
+                sum = params.plaintext.add(sum, values[i]);
+                assert_eq!(out[i], sum);
+            }
+        }
+
+        starts.pop();
+        assert!(segmented_prefix_sums(&ct, &starts, &ek).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+//! Negacyclic shifts of the coefficients of the plaintext.
+
+use crate::bfv::{Ciphertext, SeedLoss};
+use crate::Result;
+use fhe_math::rq::Representation;
+
+impl Ciphertext {
+    /// Multiplies the plaintext by the monomial `x^k`, which shifts the
+    /// coefficients of a plaintext with the `Poly` encoding by `k` positions;
+    /// a negative `k` shifts in the other direction. No key is needed, and no
+    /// noise is added.
+    ///
+    /// Since `x^degree = -1` in the plaintext ring, the shift is negacyclic:
+    /// the coefficient `i` moves to position `i + k` when `0 <= i + k <
+    /// degree`, and the coefficients which wrap around are negated modulo the
+    /// plaintext modulus, e.g. the coefficient `degree - 1` moves to position 0
+    /// with its sign flipped when `k = 1`. Shifting by `degree` negates the
+    /// plaintext, and shifting by `2 * degree` leaves it unchanged.
+    pub fn shift_coefficients(&self, k: i64) -> Result<Ciphertext> {
+        let period = 2 * self.par.degree() as i64;
+        // Multiplying by x^k is multiplying by x^(-power) for power = -k.
+        let power = (-k).rem_euclid(period) as usize;
+        let mut out = self.clone();
+        out.drop_seed(SeedLoss::Multiplication);
+        for ci in out.iter_mut() {
+            ci.change_representation(Representation::PowerBasis);
+            ci.multiply_inverse_power_of_x(power)?;
+            ci.change_representation(Representation::Ntt);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn shift_coefficients() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(1, 16);
+        let degree = params.degree() as i64;
+        let sk = SecretKey::random(&params, &mut rng);
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        for k in [-degree - 3, -1, 0, 1, 5, degree - 1, degree, 2 * degree + 3] {
+            let shifted = ct.shift_coefficients(k)?;
+            let pt = sk.try_decrypt(&shifted)?;
+            let decoded = Vec::<u64>::try_decode(&pt, Encoding::poly())?;
+            let expected = (0..degree)
+                .map(|i| {
+                    // The coefficient at position i comes from position i - k,
+                    // negated once per wrap around.
+                    let source = i - k;
+                    let value = values[source.rem_euclid(degree) as usize];
+                    if source.div_euclid(degree) % 2 == 0 {
+                        value
+                    } else {
+                        params.plaintext.neg(value)
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(decoded, expected);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Homomorphic conversions between the SIMD and `Poly` encodings.
+//!
+//! The slot `i` of a SIMD-encoded plaintext is the evaluation of its
+//! polynomial at a root `w_i` of `x^degree + 1` modulo the plaintext modulus.
+//! Converting between the encodings is therefore a linear map over the slots:
+//! the evaluation matrix `E[i][k] = w_i^k` maps the coefficients to the slots,
+//! and its inverse is `E^-1[k][i] = w_i^-k / degree`. The map is evaluated
+//! with the diagonal method over the two rows of slots, as in
+//! [`matvec`](super::matvec), with the baby-step giant-step method for the
+//! column rotations and one row rotation.
+
+use crate::bfv::{BfvParameters, Ciphertext, Encoding, EvaluationKey, Plaintext};
+use crate::{Error, Result};
+use fhe_traits::FheEncoder;
+use itertools::izip;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Returns the roots `w_i` such that the slot `i` of a SIMD-encoded plaintext
+/// is its polynomial evaluated at `w_i`.
+fn slot_roots(par: &BfvParameters) -> Result<Vec<u64>> {
+    let Some(op) = &par.op else {
+        return Err(Error::DefaultError(
+            "The parameters do not support the SIMD encoding".to_string(),
+        ));
+    };
+    // The evaluations of the polynomial x.
+    let mut x = vec![0u64; par.degree()];
+    x[1] = 1;
+    op.forward(&mut x);
+    Ok(par.matrix_reps_index_map.iter().map(|i| x[*i]).collect())
+}
+
+/// Applies a linear map to the slots of `ct`: the slot `i` of the output
+/// encrypts the sum over `k` of `entry(i, k)` times the slot `k` of `ct`.
+///
+/// The diagonals are evaluated with the baby-step giant-step method: the
+/// diagonal `giant + b` is the product of the rotation of `ct` by `b` and of
+/// the diagonal rotated by `-giant`, and the products are summed before being
+/// rotated by `giant`. The products are accumulated as the diagonals are
+/// encoded, so that only the baby-step rotations are kept in memory.
+fn transform_slots<F>(ct: &Ciphertext, ek: &EvaluationKey, entry: F) -> Result<Ciphertext>
+where
+    F: Fn(usize, usize) -> u64,
+{
+    let par = &ct.par;
+    let row_size = par.degree() >> 1;
+    let encoding = Encoding::simd_at_level(ct.level);
+    let swapped = ek.rotates_rows(ct)?;
+    let sources = [ct, &swapped];
+    let baby_steps = baby_steps(row_size);
+    let mut rotations: [Vec<Option<Ciphertext>>; 2] =
+        [vec![None; baby_steps], vec![None; baby_steps]];
+    let mut values = vec![0u64; par.degree()];
+    let mut out: Option<Ciphertext> = None;
+    for giant in (0..row_size).step_by(baby_steps) {
+        let mut inner: Option<Ciphertext> = None;
+        for (s, (source, rotations)) in izip!(sources, rotations.iter_mut()).enumerate() {
+            for (b, rotation) in rotations.iter_mut().enumerate() {
+                let i = giant + b;
+                // The slot (r, j) of the rotated ciphertext holds the slot
+                // (r xor s, j + i) of `ct`, and the diagonal is rotated by
+                // `-giant`.
+                for r in 0..2 {
+                    for j in 0..row_size {
+                        let jd = (j + row_size - giant) % row_size;
+                        let k = (r ^ s) * row_size + (jd + i) % row_size;
+                        values[r * row_size + j] = entry(r * row_size + jd, k);
+                    }
+                }
+                // The first diagonal is always kept so that the output is
+                // defined.
+                if (s, i) != (0, 0) && values.iter().all(|v| *v == 0) {
+                    continue;
+                }
+                if rotation.is_none() {
+                    *rotation = Some(if b == 0 {
+                        source.clone()
+                    } else {
+                        source.rotate(b as i64, ek)?
+                    });
+                }
+                let diagonal = Plaintext::try_encode(&values, encoding.clone(), par)?;
+                let product = rotation.as_ref().unwrap() * &diagonal;
+                match &mut inner {
+                    Some(inner) => *inner += &product,
+                    None => inner = Some(product),
+                }
+            }
+        }
+        if let Some(inner) = inner {
+            let rotated = if giant == 0 {
+                inner
+            } else {
+                inner.rotate(giant as i64, ek)?
+            };
+            match &mut out {
+                Some(out) => *out += &rotated,
+                None => out = Some(rotated),
+            }
+        }
+    }
+    Ok(out.unwrap())
+}
+
+/// Returns the number of baby steps used by [`transform_slots`], i.e. the
+/// smallest power of two whose square is at least `row_size`.
+fn baby_steps(row_size: usize) -> usize {
+    1 << row_size.trailing_zeros().div_ceil(2)
+}
+
+impl Ciphertext {
+    /// Converts an encryption of SIMD-encoded values into an encryption of
+    /// the same values with the `Poly` encoding: the coefficient `k` of the
+    /// plaintext of the output is the slot `k` of the plaintext of `self`.
+    ///
+    /// The evaluation key must enable the row rotation and column rotations
+    /// generating all the rotations (see [`Ciphertext::rotate`]), e.g. with
+    /// [`EvaluationKeyBuilder::enable_baby_step_giant_step_rotations`](crate::bfv::EvaluationKeyBuilder::enable_baby_step_giant_step_rotations).
+    ///
+    /// The conversion costs `degree` products by a plaintext and
+    /// `O(sqrt(degree))` rotations: `2 * (n - 1)` baby steps and
+    /// `degree / (2 * n) - 1` giant steps, where `n` is the smallest power of
+    /// two whose square is at least `degree / 2`. Each rotation is a single
+    /// key switching when the baby steps of the evaluation key are `n`. The
+    /// output is a sum of `degree` products by a plaintext, so its noise is up
+    /// to `degree` times the noise of one such product (i.e. about
+    /// `log2(degree)` more bits), plus the key switching noise of the
+    /// rotations.
+    pub fn slot_to_coeff(&self, ek: &EvaluationKey) -> Result<Ciphertext> {
+        let roots = slot_roots(&self.par)?;
+        let t = &self.par.plaintext;
+        transform_slots(self, ek, |i, k| t.pow(roots[i], k as u64))
+    }
+
+    /// Converts an encryption of values with the `Poly` encoding into an
+    /// encryption of the same values with the SIMD encoding: the slot `k` of
+    /// the plaintext of the output is the coefficient `k` of the plaintext of
+    /// `self`. This is the inverse of [`Ciphertext::slot_to_coeff`], with the
+    /// same requirements and cost.
+    pub fn coeff_to_slot(&self, ek: &EvaluationKey) -> Result<Ciphertext> {
+        let t = &self.par.plaintext;
+        let inverse_degree = t.inv(self.par.degree() as u64).ok_or_else(|| {
+            Error::DefaultError("The degree is not invertible modulo t".to_string())
+        })?;
+        // The roots satisfy w^(2 * degree) = 1.
+        let inverse_roots = slot_roots(&self.par)?
+            .iter()
+            .map(|w| t.pow(*w, 2 * self.par.degree() as u64 - 1))
+            .collect::<Vec<_>>();
+        transform_slots(self, ek, |k, i| {
+            t.mul(t.pow(inverse_roots[i], k as u64), inverse_degree)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{
+        BfvParameters, BfvParametersBuilder, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext,
+        SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn encoding_conversions() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(4)?
+            .enable_row_rotation()?
+            .build(&mut rng)?;
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let coeffs = ct.slot_to_coeff(&ek)?;
+        let pt = sk.try_decrypt(&coeffs)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::poly())?, values);
+        let slots = coeffs.coeff_to_slot(&ek)?;
+        let pt = sk.try_decrypt(&slots)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, values);
+
+        let pt = Plaintext::try_encode(&values, Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let pt = sk.try_decrypt(&ct.coeff_to_slot(&ek)?)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, values);
+
+        let no_row_rotation = EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(4)?
+            .build(&mut rng)?;
+        assert!(ct.slot_to_coeff(&no_row_rotation).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn encoding_conversions_large_degree() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(2048)
+            .set_plaintext_modulus(40961)
+            .set_moduli_sizes(&[62, 62, 62])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_baby_step_giant_step_rotations(32)?
+            .enable_row_rotation()?
+            .build(&mut rng)?;
+        let values = params.plaintext.random_vec(params.degree(), &mut rng);
+
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let coeffs = ct.slot_to_coeff(&ek)?;
+        let pt = sk.try_decrypt(&coeffs)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::poly())?, values);
+        let slots = coeffs.coeff_to_slot(&ek)?;
+        let pt = sk.try_decrypt(&slots)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, values);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! Create parameters for the BFV encryption scheme
 
+use crate::bfv::Encoding;
 use crate::proto::bfv::Parameters;
 use crate::{Error, ParametersError, Result};
 use alloc::fmt::Debug;
@@ -16,12 +17,18 @@ use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 use prost::Message;
 extern crate alloc;
+#[cfg(feature = "serde")]
+use crate::bfv::ParametersId;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
+#[cfg(feature = "serde")]
+use alloc::string::String;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use sha2::{Digest, Sha256};
 
 /// Parameters for the BFV encryption scheme.
 #[derive(PartialEq, Eq)]
@@ -54,7 +61,8 @@ pub struct BfvParameters {
     /// Scaling polynomial for the plaintext
     pub(crate) delta: Box<[Poly]>,
 
-    /// Q modulo the plaintext modulus
+    /// Q modulo the plaintext modulus, for the modulus Q of each level; the
+    /// plaintexts encoded at a level are scaled accordingly.
     pub(crate) q_mod_t: Box<[u64]>,
 
     /// Down scaler for the plaintext
@@ -94,6 +102,13 @@ impl FheParameters for BfvParameters {}
 unsafe impl Send for BfvParameters {}
 
 impl BfvParameters {
+    /// Minimum polynomial degree supported by the parameters.
+    ///
+    /// With the `small-degree` feature and the native NTT, degrees down to 4
+    /// are supported to make tests and examples faster; otherwise the degree
+    /// must be at least 8.
+    pub const MIN_DEGREE: usize = fhe_math::ntt::MIN_SIZE;
+
     /// Returns the underlying polynomial degree
     pub const fn degree(&self) -> usize {
         self.polynomial_degree
@@ -114,6 +129,13 @@ impl BfvParameters {
         self.plaintext_modulus
     }
 
+    /// Returns the standard deviation of the coefficients of the LWE errors,
+    /// i.e. the square root of the variance of their centered binomial
+    /// distribution.
+    pub fn noise_std_dev(&self) -> f64 {
+        libm::sqrt(self.variance as f64)
+    }
+
     /// Returns the maximum level allowed by these parameters.
     pub fn max_level(&self) -> usize {
         self.moduli.len() - 1
@@ -188,45 +210,336 @@ impl BfvParameters {
         params
     }
 
+    /// Returns an estimate of the classical security level (in bits) provided
+    /// by these parameters according to the tables of the
+    /// <https://homomorphicencryption.org> standard for ternary secrets, or
+    /// `None` if the ciphertext modulus is too large to reach 128 bits of
+    /// security.
+    pub fn security_level(&self) -> Option<usize> {
+        let log_q = self.moduli_sizes.iter().sum::<usize>();
+        [
+            SecurityLevel::Tc256,
+            SecurityLevel::Tc192,
+            SecurityLevel::Tc128,
+        ]
+        .into_iter()
+        .find(|level| log_q <= level.max_log_q(self.polynomial_degree, SecretDistribution::Ternary))
+        .map(SecurityLevel::bits)
+    }
+
+    /// Checks that the ciphertext modulus is small enough for the security
+    /// `level` with secrets of the given `distribution`, according to the
+    /// tables of the <https://homomorphicencryption.org> standard.
+    ///
+    /// Returns [`ParametersError::InsecureParameters`] otherwise.
+    pub fn validate_security(
+        &self,
+        level: SecurityLevel,
+        distribution: SecretDistribution,
+    ) -> Result<()> {
+        let log_q = self.moduli_sizes.iter().sum::<usize>();
+        let max = level.max_log_q(self.polynomial_degree, distribution);
+        if log_q > max {
+            return Err(Error::ParametersError(
+                ParametersError::InsecureParameters {
+                    log_q,
+                    max,
+                    security: level.bits(),
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the classical security (in bits) of these parameters estimated
+    /// with the primal uSVP attack, as in
+    /// [`BfvParametersBuilder::estimated_security_bits`].
+    pub fn estimated_security_bits(&self) -> usize {
+        let log_q = self.moduli.iter().map(|qi| libm::log2(*qi as f64)).sum();
+        estimate_security_bits(self.polynomial_degree, log_q, self.noise_std_dev())
+    }
+
+    /// Returns parameters of degree `degree` and plaintext modulus
+    /// `plaintext_modulus` providing the security `level` for ternary
+    /// secrets, with the largest ciphertext modulus allowed by the tables of
+    /// the <https://homomorphicencryption.org> standard, split in moduli of
+    /// at most 62 bits of about the same size.
+    ///
+    /// Returns an error if the degree is not a power of two between 1024 and
+    /// 32768, or if the plaintext modulus is invalid for these moduli.
+    pub fn standard(
+        level: SecurityLevel,
+        degree: usize,
+        plaintext_modulus: u64,
+    ) -> Result<Arc<Self>> {
+        BfvParametersBuilder::standard(level, degree)?
+            .set_plaintext_modulus(plaintext_modulus)
+            .build_arc()
+    }
+
+    /// Returns the number of sequential multiplications (with relinearization
+    /// and without modulus switching) that are guaranteed to decrypt correctly
+    /// for fresh ciphertexts encrypted under the public key with the given
+    /// encoding, according to worst-case noise bounds.
+    ///
+    /// The invariant noise of a fresh ciphertext is at most `B (2 n B + 1) +
+    /// t`, where `B = 2 * variance` bounds the coefficients of the errors and
+    /// of the secret key; a multiplication multiplies the noise by at most `2 t
+    /// n (n B + 1)` and relinearization adds at most `l n B max(q_i)` for `l`
+    /// moduli. Decryption is correct as long as the noise is smaller than `q /
+    /// (2 t)`. Since these bounds are worst-case, the actual depth is usually
+    /// larger.
+    pub fn max_mul_depth(&self, encoding: &Encoding) -> Result<usize> {
+        let Some(bounds) = self.noise_bounds(encoding.level)? else {
+            return Ok(0);
+        };
+        let mut noise = bounds.fresh;
+        let mut depth = 0;
+        loop {
+            let next = (noise + bounds.growth).max(bounds.relinearization) + 1;
+            if next >= bounds.budget {
+                break;
+            }
+            noise = next;
+            depth += 1;
+        }
+        Ok(depth)
+    }
+
+    /// Returns the worst-case noise bounds at a level, as described in
+    /// [`BfvParameters::max_mul_depth`], or `None` if the plaintext modulus
+    /// does not leave any noise budget.
+    pub(crate) fn noise_bounds(&self, level: usize) -> Result<Option<NoiseBounds>> {
+        let ctx = self.ctx_at_level(level)?;
+        let n = self.polynomial_degree as u128;
+        let t = self.plaintext_modulus as u128;
+        let b = 2 * self.variance as u128;
+
+        let log_q = ctx.modulus().bits() as usize - 1;
+        let Some(budget) = log_q.checked_sub(ceil_log2(t) + 1) else {
+            return Ok(None);
+        };
+        let fresh = ceil_log2(b * (2 * n * b + 1) + t);
+        let growth = ceil_log2(2 * t * n * (n * b + 1));
+        let relinearization = if let [modulus] = ctx.moduli() {
+            // With a single modulus, the key switching decomposes in base
+            // 2^log_base.
+            let log_modulus = modulus.next_power_of_two().ilog2() as usize;
+            let log_base = log_modulus / 2;
+            ceil_log2((log_modulus.div_ceil(log_base) as u128) * n * b) + log_base
+        } else {
+            let max_log_qi = ctx.moduli().iter().map(|qi| ceil_log2(*qi as u128)).max();
+            ceil_log2(ctx.moduli().len() as u128 * n * b) + max_log_qi.unwrap_or(0)
+        };
+        Ok(Some(NoiseBounds {
+            budget,
+            fresh,
+            growth,
+            relinearization,
+        }))
+    }
+
+    /// Returns default parameters for tests.
     #[cfg(test)]
     pub fn default_arc(num_moduli: usize, degree: usize) -> Arc<Self> {
-        if !degree.is_power_of_two() || degree < 8 {
+        if !degree.is_power_of_two() || degree < Self::MIN_DEGREE {
             panic!("Invalid degree");
         }
         BfvParametersBuilder::new()
             .set_degree(degree)
             .set_plaintext_modulus(1153)
             .set_moduli_sizes(&vec![62usize; num_moduli])
+            .allow_insecure()
             .build_arc()
             .unwrap()
     }
 }
 
+/// Worst-case noise bounds, in bits.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NoiseBounds {
+    /// Noise above which decryption may fail.
+    pub(crate) budget: usize,
+    /// Noise of a fresh ciphertext.
+    pub(crate) fresh: usize,
+    /// Noise growth of a multiplication.
+    pub(crate) growth: usize,
+    /// Noise added by a key switching.
+    pub(crate) relinearization: usize,
+}
+
+/// Returns the number of bits of `x - 1`, i.e. `ceil(log2(x))` for `x > 0`.
+pub(crate) fn ceil_log2(x: u128) -> usize {
+    (128 - x.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// Estimates the classical security (in bits) of RLWE of degree `degree`
+/// with a ciphertext modulus of `log_q` bits, errors of standard deviation
+/// `sigma` and ternary secrets, against the primal uSVP attack.
+///
+/// The attack succeeds with block size `beta` when the condition of
+/// [ADPS16](https://eprint.iacr.org/2015/1092) holds for the number of samples
+/// maximizing the right-hand side, after scaling the secret coordinates to the
+/// error standard deviation; its cost is that of BKZ with sieving,
+/// `0.292 * beta + 16.4 + log2(8 * d)` in dimension `d`, which is the cost
+/// model of the <https://homomorphicencryption.org> standard.
+fn estimate_security_bits(degree: usize, log_q: f64, sigma: f64) -> usize {
+    use core::f64::consts::{E, PI};
+
+    let n = degree as f64;
+    let ln_q = log_q * core::f64::consts::LN_2;
+    // Ternary secrets are a (conservative) lower bound on the secret size.
+    let ln_nu = libm::log(sigma / libm::sqrt(2.0 / 3.0));
+    // The right-hand side of the condition is `c - d * ln_delta - a / d`.
+    let a = (n + 1.0) * ln_q - n * ln_nu;
+    let max_d = 3.0 * n + 1.0;
+    let cost = |beta: f64, d: f64| 0.292 * beta + 16.4 + libm::log2(8.0 * d);
+    for beta in 40..=(3 * degree + 1) {
+        let beta = beta as f64;
+        let ln_delta = libm::log(libm::pow(PI * beta, 1.0 / beta) * beta / (2.0 * PI * E))
+            / (2.0 * (beta - 1.0));
+        let d = libm::sqrt(a / ln_delta).clamp(n + 2.0, max_d);
+        let rhs = (2.0 * beta - d - 1.0) * ln_delta + ln_q - a / d;
+        if libm::log(sigma) + libm::log(beta) / 2.0 <= rhs {
+            return cost(beta, d) as usize;
+        }
+    }
+    cost(max_d, max_d) as usize
+}
+
+/// Security levels of the <https://homomorphicencryption.org> standard
+/// against classical attacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SecurityLevel {
+    /// 128 bits of security.
+    Tc128,
+    /// 192 bits of security.
+    Tc192,
+    /// 256 bits of security.
+    Tc256,
+}
+
+/// Distributions of the secret key coefficients of the tables of the
+/// <https://homomorphicencryption.org> standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretDistribution {
+    /// Coefficients uniformly sampled in {-1, 0, 1}.
+    Ternary,
+    /// Coefficients sampled from the error distribution, as
+    /// [`SecretKey::random`](crate::bfv::SecretKey::random) does.
+    Gaussian,
+}
+
+impl SecurityLevel {
+    /// Returns the security level in bits.
+    pub const fn bits(self) -> usize {
+        match self {
+            SecurityLevel::Tc128 => 128,
+            SecurityLevel::Tc192 => 192,
+            SecurityLevel::Tc256 => 256,
+        }
+    }
+
+    /// Returns the maximum number of bits of the ciphertext modulus providing
+    /// this security level for a degree and a secret distribution; degrees
+    /// larger than 32768 use the bound of 32768, and degrees smaller than
+    /// 1024 do not provide any security.
+    pub fn max_log_q(self, degree: usize, distribution: SecretDistribution) -> usize {
+        // Maximum log2(q) for 128, 192 and 256 bits of security, for ternary
+        // and for gaussian secrets.
+        const BOUNDS: [(usize, [usize; 3], [usize; 3]); 6] = [
+            (1024, [27, 19, 14], [29, 21, 16]),
+            (2048, [54, 37, 29], [56, 39, 31]),
+            (4096, [109, 75, 58], [111, 77, 60]),
+            (8192, [218, 152, 118], [220, 154, 120]),
+            (16384, [438, 305, 237], [440, 307, 239]),
+            (32768, [881, 611, 476], [883, 613, 478]),
+        ];
+        let index = match self {
+            SecurityLevel::Tc128 => 0,
+            SecurityLevel::Tc192 => 1,
+            SecurityLevel::Tc256 => 2,
+        };
+        BOUNDS
+            .iter()
+            .rev()
+            .find(|(n, _, _)| *n <= degree)
+            .map_or(0, |(_, ternary, gaussian)| match distribution {
+                SecretDistribution::Ternary => ternary[index],
+                SecretDistribution::Gaussian => gaussian[index],
+            })
+    }
+}
+
 /// Builder for parameters for the Bfv encryption scheme.
 #[derive(Debug)]
 pub struct BfvParametersBuilder {
     degree: usize,
     plaintext: u64,
     variance: usize,
+    noise_std_dev: Option<f64>,
     ciphertext_moduli: Vec<u64>,
     ciphertext_moduli_sizes: Vec<usize>,
+    ciphertext_moduli_chain: Vec<Vec<u64>>,
+    security: Option<(SecurityLevel, SecretDistribution)>,
+    min_security_bits: Option<usize>,
 }
 
 impl BfvParametersBuilder {
-    /// Creates a new instance of the builder
+    /// Creates a new instance of the builder.
+    ///
+    /// By default, [`BfvParametersBuilder::build`] requires the parameters to
+    /// provide 128 bits of security for ternary secrets; see
+    /// [`BfvParametersBuilder::set_security_level`] and
+    /// [`BfvParametersBuilder::allow_insecure`].
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
             degree: Default::default(),
             plaintext: Default::default(),
             variance: 10,
+            noise_std_dev: None,
             ciphertext_moduli: Default::default(),
             ciphertext_moduli_sizes: Default::default(),
+            ciphertext_moduli_chain: Default::default(),
+            security: Some((SecurityLevel::Tc128, SecretDistribution::Ternary)),
+            min_security_bits: None,
         }
     }
 
+    /// Creates a builder for parameters of degree `degree` providing the
+    /// security `level` for ternary secrets, as in [`BfvParameters::standard`];
+    /// the plaintext modulus must still be set.
+    ///
+    /// Returns an error if the degree is not a power of two between 1024 and
+    /// 32768.
+    pub fn standard(level: SecurityLevel, degree: usize) -> Result<Self> {
+        if degree < 1024 {
+            return Err(Error::ParametersError(ParametersError::DegreeTooSmall {
+                min: 1024,
+                got: degree,
+            }));
+        }
+        if !degree.is_power_of_two() || degree > 32768 {
+            return Err(Error::ParametersError(ParametersError::InvalidDegree(
+                degree,
+            )));
+        }
+        let max_log_q = level.max_log_q(degree, SecretDistribution::Ternary);
+        let num_moduli = max_log_q.div_ceil(62);
+        let sizes = (0..num_moduli)
+            .map(|i| max_log_q / num_moduli + usize::from(i < max_log_q % num_moduli))
+            .collect_vec();
+        let mut builder = Self::new();
+        builder
+            .set_degree(degree)
+            .set_moduli_sizes(&sizes)
+            .set_security_level(level, SecretDistribution::Ternary);
+        Ok(builder)
+    }
+
     /// Sets the polynomial degree. Returns an error if the degree is not
-    /// a power of two larger or equal to 8.
+    /// a power of two larger or equal to [`BfvParameters::MIN_DEGREE`].
     pub fn set_degree(&mut self, degree: usize) -> &mut Self {
         self.degree = degree;
         self
@@ -240,30 +553,165 @@ impl BfvParametersBuilder {
     }
 
     /// Sets the sizes of the ciphertext moduli.
-    /// Only one of `set_moduli_sizes` and `set_moduli`
-    /// can be specified.
+    /// Only one of `set_moduli_sizes`, `set_moduli`, and
+    /// `set_moduli_chain_explicit_levels` can be specified.
     pub fn set_moduli_sizes(&mut self, sizes: &[usize]) -> &mut Self {
         sizes.clone_into(&mut self.ciphertext_moduli_sizes);
         self
     }
 
     /// Sets the ciphertext moduli to use.
-    /// Only one of `set_moduli_sizes` and `set_moduli`
-    /// can be specified.
+    /// Only one of `set_moduli_sizes`, `set_moduli`, and
+    /// `set_moduli_chain_explicit_levels` can be specified.
     pub fn set_moduli(&mut self, moduli: &[u64]) -> &mut Self {
         moduli.clone_into(&mut self.ciphertext_moduli);
         self
     }
 
+    /// Sets the ciphertext moduli at each level, starting from level 0, to
+    /// choose which modulus is dropped when switching to the next level.
+    ///
+    /// The first level contains all the ciphertext moduli, and each following
+    /// level must contain the moduli of the previous level but one, in any
+    /// order. After the last specified level, the moduli are dropped in the
+    /// reverse order of the last level. The moduli are stored so that each
+    /// level drops the last one, and [`BfvParameters::moduli`] returns them
+    /// in that order. Only one of `set_moduli_sizes`, `set_moduli`, and
+    /// `set_moduli_chain_explicit_levels` can be specified.
+    pub fn set_moduli_chain_explicit_levels(&mut self, levels: &[&[u64]]) -> &mut Self {
+        self.ciphertext_moduli_chain = levels.iter().map(|level| level.to_vec()).collect();
+        self
+    }
+
     /// Sets the error variance. Returns an error if the variance is not between
     /// one and sixteen.
+    ///
+    /// This overrides a previous call to
+    /// [`BfvParametersBuilder::set_noise_std_dev`].
     pub fn set_variance(&mut self, variance: usize) -> &mut Self {
         self.variance = variance;
+        self.noise_std_dev = None;
+        self
+    }
+
+    /// Sets the standard deviation `sigma` of the LWE error distribution, i.e.
+    /// of the coefficients of the errors of the encryptions and keys.
+    ///
+    /// The errors are sampled from a centered binomial distribution, whose
+    /// variance is an integer: `sigma^2` is rounded to the nearest integer,
+    /// which must be between one and sixteen, and the standard deviation of
+    /// the built parameters is [`BfvParameters::noise_std_dev`]. For instance,
+    /// the standard deviation of about 3.2 of the
+    /// <https://homomorphicencryption.org> standard is the default variance
+    /// of 10, i.e. a standard deviation of 3.16. The standard deviation is
+    /// also the one of the security estimate
+    /// [`BfvParametersBuilder::estimated_security_bits`].
+    ///
+    /// This overrides a previous call to [`BfvParametersBuilder::set_variance`].
+    pub fn set_noise_std_dev(&mut self, sigma: f64) -> &mut Self {
+        self.noise_std_dev = Some(sigma);
+        self
+    }
+
+    /// Returns the variance of the error distribution, from the standard
+    /// deviation if it is set.
+    fn resolved_variance(&self) -> Result<usize> {
+        let variance = match self.noise_std_dev {
+            Some(sigma) => {
+                let variance = libm::round(sigma * sigma);
+                if !sigma.is_finite() || sigma <= 0.0 || !(1.0..=16.0).contains(&variance) {
+                    return Err(Error::ParametersError(ParametersError::InvalidNoiseStdDev(
+                        sigma.to_string(),
+                    )));
+                }
+                variance as usize
+            }
+            None => self.variance,
+        };
+        if !(1..=16).contains(&variance) {
+            return Err(Error::ParametersError(ParametersError::InvalidVariance(
+                variance,
+            )));
+        }
+        Ok(variance)
+    }
+
+    /// Requires the parameters to provide the security `level` for secrets of
+    /// the given `distribution`: [`BfvParametersBuilder::build`] then fails
+    /// with [`ParametersError::InsecureParameters`] if the ciphertext modulus
+    /// is too large, as checked by [`BfvParameters::validate_security`]. By
+    /// default, 128 bits of security for ternary secrets are required.
+    pub fn set_security_level(
+        &mut self,
+        level: SecurityLevel,
+        distribution: SecretDistribution,
+    ) -> &mut Self {
+        self.security = Some((level, distribution));
+        self
+    }
+
+    /// Requires the security estimated by
+    /// [`BfvParametersBuilder::estimated_security_bits`] to be at least `bits`:
+    /// [`BfvParametersBuilder::build`] then fails with
+    /// [`ParametersError::EstimatedSecurityTooLow`] otherwise. By default, the
+    /// estimated security is not checked.
+    pub fn set_min_security_bits(&mut self, bits: usize) -> &mut Self {
+        self.min_security_bits = Some(bits);
         self
     }
 
+    /// Removes the security requirements, including the default one and those
+    /// set by [`BfvParametersBuilder::set_security_level`],
+    /// [`BfvParametersBuilder::set_min_security_bits`] or
+    /// [`BfvParametersBuilder::standard`], e.g. for tests with small degrees.
+    ///
+    /// The built parameters may then provide no security at all.
+    pub fn allow_insecure(&mut self) -> &mut Self {
+        self.security = None;
+        self.min_security_bits = None;
+        self
+    }
+
+    /// Returns a conservative estimate of the classical security (in bits) of
+    /// the parameters being built, to sanity-check custom choices of moduli.
+    ///
+    /// The estimate approximates the cost of the primal uSVP attack of the
+    /// [lattice estimator](https://github.com/malb/lattice-estimator) for
+    /// ternary secrets, and is within a few bits of the tables of the
+    /// <https://homomorphicencryption.org> standard. When only the sizes of the
+    /// moduli are set, the ciphertext modulus is assumed to have the sum of
+    /// these sizes as number of bits.
+    ///
+    /// Returns an error if the degree is too small, if the ciphertext moduli
+    /// are not specified, or if the error distribution is invalid.
+    pub fn estimated_security_bits(&self) -> Result<usize> {
+        if self.degree < BfvParameters::MIN_DEGREE {
+            return Err(Error::ParametersError(ParametersError::DegreeTooSmall {
+                min: BfvParameters::MIN_DEGREE,
+                got: self.degree,
+            }));
+        }
+        let log_q = if !self.ciphertext_moduli.is_empty() {
+            self.ciphertext_moduli
+                .iter()
+                .map(|qi| libm::log2(*qi as f64))
+                .sum()
+        } else if !self.ciphertext_moduli_sizes.is_empty() {
+            self.ciphertext_moduli_sizes.iter().sum::<usize>() as f64
+        } else if let Some(first) = self.ciphertext_moduli_chain.first() {
+            first.iter().map(|qi| libm::log2(*qi as f64)).sum()
+        } else {
+            return Err(Error::ParametersError(ParametersError::TooFewSpecified(
+                "One of `ciphertext_moduli`, `ciphertext_moduli_sizes`, and `ciphertext_moduli_chain` must be specified"
+                    .to_string(),
+            )));
+        };
+        let sigma = libm::sqrt(self.resolved_variance()? as f64);
+        Ok(estimate_security_bits(self.degree, log_q, sigma))
+    }
+
     /// Generate ciphertext moduli with the specified sizes
-    fn generate_moduli(moduli_sizes: &[usize], degree: usize) -> Result<Vec<u64>> {
+    pub(crate) fn generate_moduli(moduli_sizes: &[usize], degree: usize) -> Result<Vec<u64>> {
         let mut moduli = vec![];
         for size in moduli_sizes {
             if *size > 62 || *size < 10 {
@@ -292,6 +740,44 @@ impl BfvParametersBuilder {
         Ok(moduli)
     }
 
+    /// Orders the moduli of a chain so that each level drops the last modulus.
+    fn moduli_from_chain(levels: &[Vec<u64>]) -> Result<Vec<u64>> {
+        let invalid = |reason: &str| {
+            Error::ParametersError(ParametersError::InvalidModuliChain(reason.to_string()))
+        };
+        let first = &levels[0];
+        if first.is_empty() {
+            return Err(invalid("the first level is empty"));
+        }
+        let mut distinct = first.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() != first.len() {
+            return Err(invalid("the moduli are not distinct"));
+        }
+
+        let mut dropped = Vec::with_capacity(levels.len() - 1);
+        for (previous, level) in levels.iter().tuple_windows() {
+            let removed = previous
+                .iter()
+                .filter(|qi| !level.contains(qi))
+                .collect_vec();
+            if level.len() + 1 != previous.len()
+                || removed.len() != 1
+                || level.iter().any(|qi| !previous.contains(qi))
+            {
+                return Err(invalid(
+                    "each level must drop exactly one modulus of the previous level",
+                ));
+            }
+            dropped.push(*removed[0]);
+        }
+
+        let mut moduli = levels[levels.len() - 1].clone();
+        moduli.extend(dropped.iter().rev());
+        Ok(moduli)
+    }
+
     /// Build a new `BfvParameters` inside an `Arc`.
     pub fn build_arc(&self) -> Result<Arc<BfvParameters>> {
         self.build().map(Arc::new)
@@ -300,28 +786,44 @@ impl BfvParametersBuilder {
     /// Build a new `BfvParameters`.
     pub fn build(&self) -> Result<BfvParameters> {
         // Check that the degree is a power of 2 (and large enough).
-        if self.degree < 8 || !self.degree.is_power_of_two() {
+        if self.degree < BfvParameters::MIN_DEGREE {
+            return Err(Error::ParametersError(ParametersError::DegreeTooSmall {
+                min: BfvParameters::MIN_DEGREE,
+                got: self.degree,
+            }));
+        }
+        if !self.degree.is_power_of_two() {
             return Err(Error::ParametersError(ParametersError::InvalidDegree(
                 self.degree,
             )));
         }
 
+        let variance = self.resolved_variance()?;
+
         // This checks that the plaintext modulus is valid.
         // TODO: Check bound on the plaintext modulus.
         let plaintext_modulus = Modulus::new(self.plaintext).map_err(|e| {
             Error::ParametersError(ParametersError::InvalidPlaintext(e.to_string()))
         })?;
 
-        // Check that one of `ciphertext_moduli` and `ciphertext_moduli_sizes` is
-        // specified.
-        if !self.ciphertext_moduli.is_empty() && !self.ciphertext_moduli_sizes.is_empty() {
+        // Check that one of `ciphertext_moduli`, `ciphertext_moduli_sizes`, and
+        // `ciphertext_moduli_chain` is specified.
+        let specified = [
+            !self.ciphertext_moduli.is_empty(),
+            !self.ciphertext_moduli_sizes.is_empty(),
+            !self.ciphertext_moduli_chain.is_empty(),
+        ]
+        .iter()
+        .filter(|s| **s)
+        .count();
+        if specified > 1 {
             return Err(Error::ParametersError(ParametersError::TooManySpecified(
-                "Only one of `ciphertext_moduli` and `ciphertext_moduli_sizes` can be specified"
+                "Only one of `ciphertext_moduli`, `ciphertext_moduli_sizes`, and `ciphertext_moduli_chain` can be specified"
                     .to_string(),
             )));
-        } else if self.ciphertext_moduli.is_empty() && self.ciphertext_moduli_sizes.is_empty() {
+        } else if specified == 0 {
             return Err(Error::ParametersError(ParametersError::TooFewSpecified(
-                "One of `ciphertext_moduli` and `ciphertext_moduli_sizes` must be specified"
+                "One of `ciphertext_moduli`, `ciphertext_moduli_sizes`, and `ciphertext_moduli_chain` must be specified"
                     .to_string(),
             )));
         }
@@ -330,6 +832,8 @@ impl BfvParametersBuilder {
         let mut moduli = self.ciphertext_moduli.clone();
         if !self.ciphertext_moduli_sizes.is_empty() {
             moduli = Self::generate_moduli(&self.ciphertext_moduli_sizes, self.degree)?
+        } else if !self.ciphertext_moduli_chain.is_empty() {
+            moduli = Self::moduli_from_chain(&self.ciphertext_moduli_chain)?
         }
 
         // Recomputes the moduli sizes
@@ -355,7 +859,12 @@ impl BfvParametersBuilder {
         let mut delta_rests = vec![];
         for m in &moduli {
             let q = Modulus::new(*m)?;
-            delta_rests.push(q.inv(q.neg(*plaintext_modulus)).unwrap())
+            delta_rests.push(q.inv(q.neg(*plaintext_modulus)).ok_or_else(|| {
+                Error::ParametersError(ParametersError::InvalidPlaintext(
+                    "The plaintext modulus must be invertible modulo the ciphertext moduli"
+                        .to_string(),
+                ))
+            })?)
         }
 
         let mut ctx = Vec::with_capacity(moduli.len());
@@ -418,12 +927,12 @@ impl BfvParametersBuilder {
             pos &= m - 1;
         }
 
-        Ok(BfvParameters {
+        let par = BfvParameters {
             polynomial_degree: self.degree,
             plaintext_modulus: self.plaintext,
             moduli: moduli.into(),
             moduli_sizes: moduli_sizes.into(),
-            variance: self.variance,
+            variance,
             ctx,
             op: op.map(Arc::new),
             delta: delta.into(),
@@ -432,7 +941,19 @@ impl BfvParametersBuilder {
             plaintext: plaintext_modulus,
             mul_params: mul_params.into(),
             matrix_reps_index_map: matrix_reps_index_map.into(),
-        })
+        };
+        if let Some((level, distribution)) = self.security {
+            par.validate_security(level, distribution)?;
+        }
+        if let Some(min) = self.min_security_bits {
+            let estimated = par.estimated_security_bits();
+            if estimated < min {
+                return Err(Error::ParametersError(
+                    ParametersError::EstimatedSecurityTooLow { estimated, min },
+                ));
+            }
+        }
+        Ok(par)
     }
 }
 
@@ -448,6 +969,95 @@ impl Serialize for BfvParameters {
     }
 }
 
+/// Human-readable description of the parameters.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ParametersConfig {
+    degree: usize,
+    plaintext_modulus: u64,
+    moduli: Vec<u64>,
+    variance: usize,
+    #[serde(default)]
+    security_level: Option<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl BfvParameters {
+    /// Returns a human-readable (JSON) description of the parameters, which
+    /// includes the degree, the plaintext modulus, the ciphertext moduli, the
+    /// error variance, and the estimated security level.
+    pub fn to_config_string(&self) -> String {
+        serde_json::to_string_pretty(&self.config()).unwrap()
+    }
+
+    /// Parses parameters from their human-readable description.
+    ///
+    /// Returns an error if the description is malformed, if the parameters are
+    /// invalid, or if the security level in the description does not match the
+    /// security level of the parameters.
+    pub fn from_config_str(config: &str) -> Result<Self> {
+        let config: ParametersConfig =
+            serde_json::from_str(config).map_err(|_| Error::SerializationError)?;
+        Self::from_config(config)
+    }
+
+    /// Returns the identifier of the parameters, with which the other types
+    /// reference their parameters when serialized with serde.
+    pub fn id(&self) -> ParametersId {
+        ParametersId(Sha256::digest(self.to_bytes()).into())
+    }
+
+    fn config(&self) -> ParametersConfig {
+        ParametersConfig {
+            degree: self.polynomial_degree,
+            plaintext_modulus: self.plaintext_modulus,
+            moduli: self.moduli.to_vec(),
+            variance: self.variance,
+            security_level: self.security_level(),
+        }
+    }
+
+    fn from_config(config: ParametersConfig) -> Result<Self> {
+        // As for the binary serialization, the security is not validated.
+        let par = BfvParametersBuilder::new()
+            .set_degree(config.degree)
+            .set_plaintext_modulus(config.plaintext_modulus)
+            .set_moduli(&config.moduli)
+            .set_variance(config.variance)
+            .allow_insecure()
+            .build()?;
+        if config.security_level.is_some() && config.security_level != par.security_level() {
+            return Err(Error::UnspecifiedInput(
+                "The security level does not match the parameters".to_string(),
+            ));
+        }
+        Ok(par)
+    }
+}
+
+/// The parameters are serialized as their human-readable description.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BfvParameters {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.config().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BfvParameters {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let config = ParametersConfig::deserialize(deserializer)?;
+        Self::from_config(config).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserialized parameters are rebuilt without validating their security; use
+/// [`BfvParameters::validate_security`] to check it.
 impl Deserialize for BfvParameters {
     fn try_deserialize(bytes: &[u8]) -> Result<Self> {
         let params: Parameters = Message::decode(bytes).map_err(|_| Error::SerializationError)?;
@@ -456,6 +1066,7 @@ impl Deserialize for BfvParameters {
             .set_plaintext_modulus(params.plaintext)
             .set_moduli(&params.moduli)
             .set_variance(params.variance as usize)
+            .allow_insecure()
             .build()
     }
     type Error = Error;
@@ -488,9 +1099,22 @@ impl MultiplicationParameters {
 
 #[cfg(test)]
 mod tests {
-    use super::{BfvParameters, BfvParametersBuilder};
-    use crate::Error;
-    use fhe_traits::{Deserialize, Serialize};
+    use super::{
+        ceil_log2, BfvParameters, BfvParametersBuilder, SecretDistribution, SecurityLevel,
+    };
+    use crate::bfv::{
+        Ciphertext, Encoding, EvaluationKeyBuilder, Multiplicator, Plaintext, PublicKey,
+        RelinearizationKey, SecretKey,
+    };
+    use crate::{Error, ParametersError};
+    use fhe_traits::{
+        Deserialize, DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter,
+        Serialize,
+    };
+    use rand::{rngs::ThreadRng, thread_rng};
+    extern crate alloc;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
 
     // TODO: To fix when errors handling is fixed.
     // #[test]
@@ -590,12 +1214,56 @@ mod tests {
         assert_eq!(params.degree(), 16);
     }
 
+    #[test]
+    fn min_degree() -> Result<(), Error> {
+        assert_eq!(
+            BfvParametersBuilder::new()
+                .set_degree(BfvParameters::MIN_DEGREE / 2)
+                .set_plaintext_modulus(1153)
+                .set_moduli_sizes(&[62])
+                .build(),
+            Err(Error::ParametersError(ParametersError::DegreeTooSmall {
+                min: BfvParameters::MIN_DEGREE,
+                got: BfvParameters::MIN_DEGREE / 2
+            }))
+        );
+        assert_eq!(
+            BfvParametersBuilder::new()
+                .set_degree(3 * BfvParameters::MIN_DEGREE)
+                .set_plaintext_modulus(1153)
+                .set_moduli_sizes(&[62])
+                .build(),
+            Err(Error::ParametersError(ParametersError::InvalidDegree(
+                3 * BfvParameters::MIN_DEGREE
+            )))
+        );
+
+        let mut rng = thread_rng();
+        let params = BfvParametersBuilder::new()
+            .set_degree(BfvParameters::MIN_DEGREE)
+            .set_plaintext_modulus(17)
+            .set_moduli_sizes(&[62, 62])
+            .allow_insecure()
+            .build_arc()?;
+        let sk = SecretKey::random(&params, &mut rng);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        for encoding in [Encoding::poly(), Encoding::simd()] {
+            let pt = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+            let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let ct = Ciphertext::from_bytes(&ct.to_bytes(), &params)?;
+            let pt = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt, encoding)?, a);
+        }
+        Ok(())
+    }
+
     #[test]
     fn ciphertext_moduli() -> Result<(), Error> {
         let params = BfvParametersBuilder::new()
             .set_degree(16)
             .set_plaintext_modulus(2)
             .set_moduli_sizes(&[62, 62, 62, 61, 60, 11])
+            .allow_insecure()
             .build()?;
         assert_eq!(
             params.moduli.to_vec(),
@@ -620,12 +1288,79 @@ mod tests {
                 1152921504606845473,
                 2017,
             ])
+            .allow_insecure()
             .build()?;
         assert_eq!(params.moduli_sizes.to_vec(), &[62, 62, 62, 61, 60, 11]);
 
         Ok(())
     }
 
+    #[test]
+    fn moduli_chain_explicit_levels() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let q = BfvParameters::default_arc(3, 16).moduli().to_vec();
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1153)
+            .set_moduli_chain_explicit_levels(&[&q, &[q[2], q[0]], &[q[2]]])
+            .allow_insecure()
+            .build_arc()?;
+        assert_eq!(params.moduli(), &[q[2], q[0], q[1]]);
+        assert_eq!(params.ctx_at_level(1)?.moduli(), &[q[2], q[0]]);
+        assert_eq!(params.ctx_at_level(2)?.moduli(), &[q[2]]);
+        assert_eq!(BfvParameters::try_deserialize(&params.to_bytes())?, *params);
+
+        let sk = SecretKey::random(&params, &mut rng);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&a, Encoding::simd(), &params)?;
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        for level in 1..=params.max_level() {
+            ct.mod_switch_to_next_level()?;
+            assert_eq!(ct[0].ctx(), params.ctx_at_level(level)?);
+            let pt = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, a);
+        }
+
+        // The remaining levels drop the moduli of the last level from its end.
+        let params = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1153)
+            .set_moduli_chain_explicit_levels(&[&q, &[q[1], q[2]]])
+            .allow_insecure()
+            .build()?;
+        assert_eq!(params.moduli(), &[q[1], q[2], q[0]]);
+
+        for chain in [
+            &[&q[..], &[q[0]]] as &[&[u64]],
+            &[&q[..], &[q[0], q[1], q[2]]],
+            &[&[q[0], q[1], q[1]], &[q[0], q[1]]],
+            &[&q[..], &[q[0], q[0]]],
+            &[&[]],
+        ] {
+            assert!(matches!(
+                BfvParametersBuilder::new()
+                    .set_degree(16)
+                    .set_plaintext_modulus(1153)
+                    .set_moduli_chain_explicit_levels(chain)
+                    .build(),
+                Err(Error::ParametersError(ParametersError::InvalidModuliChain(
+                    _
+                )))
+            ));
+        }
+        assert!(matches!(
+            BfvParametersBuilder::new()
+                .set_degree(16)
+                .set_plaintext_modulus(1153)
+                .set_moduli(&q)
+                .set_moduli_chain_explicit_levels(&[&q])
+                .build(),
+            Err(Error::ParametersError(ParametersError::TooManySpecified(_)))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn serialize() -> Result<(), Error> {
         let params = BfvParametersBuilder::new()
@@ -633,9 +1368,286 @@ mod tests {
             .set_plaintext_modulus(2)
             .set_moduli_sizes(&[62, 62, 62, 61, 60, 11])
             .set_variance(4)
+            .allow_insecure()
             .build()?;
         let bytes = params.to_bytes();
         assert_eq!(BfvParameters::try_deserialize(&bytes)?, params);
         Ok(())
     }
+
+    #[test]
+    fn security_level() {
+        for params in BfvParameters::default_parameters_128(20) {
+            assert_eq!(params.security_level(), Some(128));
+        }
+        assert_eq!(BfvParameters::default_arc(1, 16).security_level(), None);
+    }
+
+    #[test]
+    fn standard() -> Result<(), Error> {
+        for (level, degree) in [
+            (SecurityLevel::Tc128, 1024),
+            (SecurityLevel::Tc192, 4096),
+            (SecurityLevel::Tc256, 8192),
+        ] {
+            let params = BfvParameters::standard(level, degree, 12289)?;
+            assert_eq!(params.degree(), degree);
+            assert_eq!(
+                params.moduli_sizes().iter().sum::<usize>(),
+                level.max_log_q(degree, SecretDistribution::Ternary)
+            );
+            assert!(params.moduli_sizes().iter().all(|size| *size <= 62));
+            assert_eq!(params.security_level(), Some(level.bits()));
+            params.validate_security(level, SecretDistribution::Gaussian)?;
+        }
+        assert!(BfvParameters::standard(SecurityLevel::Tc128, 512, 12289).is_err());
+        assert!(BfvParameters::standard(SecurityLevel::Tc128, 65536, 12289).is_err());
+
+        // The security is checked by default, unless explicitly allowed.
+        let mut builder = BfvParametersBuilder::new();
+        builder
+            .set_degree(2048)
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[62]);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            Error::ParametersError(ParametersError::InsecureParameters {
+                log_q: 62,
+                max: 54,
+                security: 128
+            })
+        );
+        let params = builder.allow_insecure().build_arc()?;
+        assert!(params
+            .validate_security(SecurityLevel::Tc128, SecretDistribution::Gaussian)
+            .is_err());
+        assert!(BfvParametersBuilder::standard(SecurityLevel::Tc256, 2048)?
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[30])
+            .build()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn estimated_security_bits() -> Result<(), Error> {
+        // The estimates are close to the tables of the standard.
+        for level in [
+            SecurityLevel::Tc128,
+            SecurityLevel::Tc192,
+            SecurityLevel::Tc256,
+        ] {
+            for degree in [1024, 4096, 32768] {
+                let builder = BfvParametersBuilder::standard(level, degree)?;
+                let estimated = builder.estimated_security_bits()?;
+                assert!(estimated.abs_diff(level.bits()) <= 10);
+            }
+        }
+
+        // Larger moduli and smaller variances decrease the security.
+        let mut builder = BfvParametersBuilder::new();
+        builder.set_degree(2048).set_moduli_sizes(&[50]);
+        let estimated = builder.estimated_security_bits()?;
+        assert!(builder.set_moduli_sizes(&[62]).estimated_security_bits()? < estimated);
+        let estimated = builder.estimated_security_bits()?;
+        assert!(builder.set_variance(1).estimated_security_bits()? < estimated);
+
+        // The builder checks the estimate against the minimum when requested,
+        // even without a security level.
+        builder
+            .set_variance(10)
+            .set_moduli_sizes(&[62, 62])
+            .set_plaintext_modulus(1153)
+            .allow_insecure()
+            .set_min_security_bits(128);
+        let estimated = builder.estimated_security_bits()?;
+        assert!(estimated < 128);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            Error::ParametersError(ParametersError::EstimatedSecurityTooLow {
+                estimated,
+                min: 128
+            })
+        );
+        let params = builder.allow_insecure().build()?;
+        assert!(params.estimated_security_bits() >= estimated);
+        assert!(builder
+            .set_moduli_sizes(&[40])
+            .set_min_security_bits(128)
+            .build()
+            .is_ok());
+
+        assert!(BfvParametersBuilder::new()
+            .set_degree(2048)
+            .estimated_security_bits()
+            .is_err());
+        assert!(BfvParametersBuilder::new()
+            .set_degree(2)
+            .set_moduli_sizes(&[20])
+            .estimated_security_bits()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn noise_std_dev() -> Result<(), Error> {
+        let mut builder = BfvParametersBuilder::new();
+        builder
+            .set_degree(2048)
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[54]);
+        let default = builder.build()?;
+        assert_eq!(default.noise_std_dev(), libm::sqrt(10.0));
+
+        // The standard deviation of the homomorphicencryption.org standard
+        // rounds to the default variance.
+        let params = builder.set_noise_std_dev(3.2).build()?;
+        assert_eq!(params, default);
+        let estimated = builder.estimated_security_bits()?;
+        let params = builder.set_noise_std_dev(2.0).build()?;
+        assert_eq!(params.noise_std_dev(), 2.0);
+        assert_eq!(params.variance, 4);
+        assert_eq!(
+            params.estimated_security_bits(),
+            builder.estimated_security_bits()?
+        );
+        assert!(builder.estimated_security_bits()? < estimated);
+
+        for sigma in [0.0, -3.2, 0.5, 4.2, f64::NAN, f64::INFINITY] {
+            builder.set_noise_std_dev(sigma);
+            assert_eq!(
+                builder.build().unwrap_err(),
+                Error::ParametersError(ParametersError::InvalidNoiseStdDev(sigma.to_string()))
+            );
+            assert!(builder.estimated_security_bits().is_err());
+        }
+
+        // The last of the variance and the standard deviation is used.
+        assert_eq!(builder.set_variance(10).build()?, default);
+        assert!(builder.set_variance(17).estimated_security_bits().is_err());
+        assert_eq!(builder.set_noise_std_dev(3.2).build()?, default);
+        Ok(())
+    }
+
+    #[test]
+    fn max_mul_depth() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let depth = params.max_mul_depth(&Encoding::simd())?;
+        assert!(depth > 0);
+        for level in 1..params.max_level() {
+            assert!(params.max_mul_depth(&Encoding::simd_at_level(level))? <= depth);
+        }
+        assert!(params
+            .max_mul_depth(&Encoding::simd_at_level(params.max_level() + 1))
+            .is_err());
+        assert!(BfvParameters::default_arc(1, 16).max_mul_depth(&Encoding::poly())? < depth);
+
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let multiplicator = Multiplicator::default(&rk)?;
+        let mut values = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&values, Encoding::simd(), &params)?;
+        let mut ct: Ciphertext = pk.try_encrypt(&pt, &mut rng)?;
+        for _ in 0..depth {
+            ct = multiplicator.multiply(&ct, &ct)?;
+            let copy = values.clone();
+            params.plaintext.mul_vec(&mut values, &copy);
+        }
+        let pt = sk.try_decrypt(&ct)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt, Encoding::simd())?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn measured_noise_within_bounds() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let mut parameters = (1..=6)
+            .map(|num_moduli| BfvParameters::default_arc(num_moduli, 16))
+            .collect::<Vec<_>>();
+        parameters.extend(
+            BfvParameters::default_parameters_128(20)
+                .into_iter()
+                .filter(|params| params.degree() <= 4096),
+        );
+        for params in parameters {
+            let Some(bounds) = params.noise_bounds(0)? else {
+                continue;
+            };
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            let encrypt = |rng: &mut ThreadRng| -> Result<Ciphertext, Error> {
+                let pt = Plaintext::random(Encoding::simd(), &params, rng)?;
+                pk.try_encrypt(&pt, rng)
+            };
+            // The measured noise, in bits, must not exceed the estimate.
+            let check = |ct: &Ciphertext, estimate: usize| -> Result<(), Error> {
+                let noise = unsafe { sk.measure_noise(ct)? };
+                assert!(noise <= estimate, "{noise} > {estimate} for {params:?}");
+                Ok(())
+            };
+
+            let ct1 = encrypt(&mut rng)?;
+            let ct2 = encrypt(&mut rng)?;
+            check(&ct1, bounds.fresh)?;
+            check(&(&ct1 + &ct2), bounds.fresh + 1)?;
+            check(&(&ct1 - &ct2), bounds.fresh + 1)?;
+
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
+            let plaintext_growth = ceil_log2(params.plaintext() as u128 * params.degree() as u128);
+            check(&(&ct1 * &pt), bounds.fresh + plaintext_growth)?;
+
+            let product = &ct1 * &ct2;
+            check(&product, bounds.fresh + bounds.growth)?;
+
+            // Key switching needs several moduli.
+            if params.moduli().len() == 1 {
+                continue;
+            }
+            let rk = RelinearizationKey::new(&sk, &mut rng)?;
+            let ek = EvaluationKeyBuilder::new(&sk)?
+                .enable_column_rotation(1)?
+                .enable_row_rotation()?
+                .build(&mut rng)?;
+            let switched = bounds.fresh.max(bounds.relinearization) + 1;
+            check(&ek.rotates_columns_by(&ct1, 1)?, switched)?;
+            check(&ek.rotates_rows(&ct1)?, switched)?;
+
+            let relinearized = (bounds.fresh + bounds.growth).max(bounds.relinearization) + 1;
+            if relinearized < bounds.budget {
+                let mut relinearized_product = product;
+                rk.relinearizes(&mut relinearized_product)?;
+                check(&relinearized_product, relinearized)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_string() -> Result<(), Error> {
+        for params in [
+            BfvParameters::default_arc(6, 16),
+            BfvParameters::default_parameters_128(20)[2].clone(),
+        ] {
+            let config = params.to_config_string();
+            assert_eq!(&BfvParameters::from_config_str(&config)?, params.as_ref());
+        }
+
+        let config = r#"{"degree": 2048, "plaintext_modulus": 1032193,
+            "moduli": [18014398492704769], "variance": 10}"#;
+        let params = BfvParameters::from_config_str(config)?;
+        assert_eq!(params.degree(), 2048);
+        assert_eq!(params.security_level(), Some(128));
+
+        let config = r#"{"degree": 2048, "plaintext_modulus": 1032193,
+            "moduli": [18014398492704769], "variance": 10, "security_level": 192}"#;
+        assert!(BfvParameters::from_config_str(config).is_err());
+        assert_eq!(
+            BfvParameters::from_config_str("degree = 2048"),
+            Err(Error::SerializationError)
+        );
+        Ok(())
+    }
 }
@@ -1,16 +1,25 @@
 //! Plaintext type in the BFV encryption scheme.
+use crate::proto::bfv::Plaintext as PlaintextProto;
 use crate::{
     bfv::{BfvParameters, Encoding, PlaintextVec},
     Error, Result,
 };
 use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
-use fhe_traits::{FheDecoder, FheEncoder, FheParametrized, FhePlaintext};
+use fhe_traits::{
+    DeserializeParametrized, FheDecoder, FheEncoder, FheEncoderVariableTime, FheParametrized,
+    FhePlaintext, Serialize,
+};
+use prost::Message;
+use rand::{CryptoRng, RngCore};
 extern crate alloc;
 use alloc::boxed::Box;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::ops::Neg;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use zeroize::{Zeroize as _, Zeroizing};
 use zeroize_derive::{Zeroize, ZeroizeOnDrop};
 
@@ -32,6 +41,44 @@ pub struct Plaintext {
     /// The level of the plaintext
     #[zeroize(skip)]
     pub(crate) level: usize,
+    /// The structure of the polynomial, if known.
+    #[zeroize(skip)]
+    pub(crate) sparsity: Sparsity,
+}
+
+/// The structure of the polynomial of a plaintext.
+///
+/// The structure is only detected for plaintexts encoded in variable time,
+/// since the multiplication by a plaintext with a known structure does not run
+/// in constant time; it is [`Sparsity::Dense`] otherwise.
+///
+/// Only the zero and constant polynomials are special-cased. A SIMD mask or
+/// selector with a few nonzero slots is a dense polynomial, and the product
+/// by any other polynomial, e.g. a monomial, is already a pointwise product
+/// in the Ntt representation of the ciphertext: a negacyclic shift of the
+/// coefficients would first require inverse and forward NTTs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sparsity {
+    /// No structure is known, and the polynomial is used in Ntt representation.
+    Dense,
+    /// All the coefficients are zero.
+    Zero,
+    /// All the coefficients but the constant one are zero.
+    Constant(u64),
+}
+
+impl Sparsity {
+    /// Detects the structure of the coefficients of a polynomial in variable
+    /// time.
+    pub(crate) fn detect_vt(value: &[u64]) -> Self {
+        if value.iter().skip(1).any(|v| *v != 0) {
+            Sparsity::Dense
+        } else if value.first().is_some_and(|v| *v != 0) {
+            Sparsity::Constant(value[0])
+        } else {
+            Sparsity::Zero
+        }
+    }
 }
 
 impl FheParametrized for Plaintext {
@@ -44,16 +91,22 @@ impl FhePlaintext for Plaintext {
 
 impl Plaintext {
     pub(crate) fn to_poly(&self) -> Poly {
+        self.to_poly_at_level(self.level).unwrap()
+    }
+
+    /// Returns the plaintext scaled by `delta` in the context of `level`,
+    /// which may differ from the level of the plaintext since the encoded
+    /// value does not depend on the level.
+    pub(crate) fn to_poly_at_level(&self, level: usize) -> Result<Poly> {
+        let ctx = self.par.ctx_at_level(level)?;
         let mut m_v = Zeroizing::new(self.value.clone());
         self.par
             .plaintext
-            .scalar_mul_vec(&mut m_v, self.par.q_mod_t[self.level]);
-        let ctx = self.par.ctx_at_level(self.level).unwrap();
-        let mut m =
-            Poly::try_convert_from(m_v.as_ref(), ctx, false, Representation::PowerBasis).unwrap();
+            .scalar_mul_vec(&mut m_v, self.par.q_mod_t[level]);
+        let mut m = Poly::try_convert_from(m_v.as_ref(), ctx, false, Representation::PowerBasis)?;
         m.change_representation(Representation::Ntt);
-        m *= &self.par.delta[self.level];
-        m
+        m *= &self.par.delta[level];
+        Ok(m)
     }
 
     /// Generate a zero plaintext.
@@ -68,25 +121,184 @@ impl Plaintext {
             encoding: Some(encoding),
             poly_ntt,
             level,
+            sparsity: Sparsity::Zero,
         })
     }
 
+    /// Generate a plaintext encoding `degree` values sampled uniformly modulo
+    /// the plaintext modulus, e.g. for tests.
+    pub fn random<R: RngCore + CryptoRng>(
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let values = Zeroizing::new(par.plaintext.random_vec(par.degree(), rng));
+        Plaintext::try_encode(values.as_ref() as &[u64], encoding, par)
+    }
+
     /// Returns the level of this plaintext.
     pub fn level(&self) -> usize {
         self.par.level_of_ctx(self.poly_ntt.ctx()).unwrap()
     }
+
+    /// Returns the plaintext scaled by `delta` as added to or subtracted from
+    /// a ciphertext, so that repeated additions and subtractions of the same
+    /// plaintext are only additions of polynomials.
+    pub fn scaled_for_add(&self) -> ScaledPlaintext {
+        ScaledPlaintext {
+            par: self.par.clone(),
+            poly: self.to_poly(),
+            level: self.level,
+        }
+    }
+
+    /// Recodes the plaintext for the parameters `par`, which must have the
+    /// same plaintext modulus, in one plaintext or more, as in
+    /// [`PlaintextVec::recode`].
+    pub fn recode(&self, par: &Arc<BfvParameters>) -> Result<PlaintextVec> {
+        PlaintextVec(vec![self.clone()]).recode(par)
+    }
+
+    /// Encodes at most `degree` values produced by an iterator, in constant
+    /// time, without collecting them first, e.g. to encode values read from a
+    /// stream.
+    ///
+    /// The output is the same as encoding the collected values with
+    /// [`Plaintext::try_encode`]. Returns an error if the iterator has more
+    /// than `degree` values, or if the parameters do not support the encoding.
+    pub fn try_encode_from_iter<I: ExactSizeIterator<Item = u64>>(
+        values: I,
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        if values.len() > par.degree() {
+            return Err(Error::TooManyValues(values.len(), par.degree()));
+        }
+        if values.len() == 0 {
+            return Plaintext::zero(encoding, par);
+        }
+        if encoding.encoding == EncodingEnum::Simd && par.op.is_none() {
+            return Err(Error::EncodingNotSupported(EncodingEnum::Simd.to_string()));
+        }
+        let ctx = par.ctx_at_level(encoding.level)?;
+        PlaintextVec::encode_row(values, &encoding, ctx, par)
+    }
+
+    /// Encodes many rows of at most `degree` values each, one plaintext per
+    /// row, in constant time.
+    ///
+    /// The output is the same as encoding each row with
+    /// [`Plaintext::try_encode`], but the encoding and the level are checked
+    /// once, and the rows are encoded in parallel when the `rayon` feature is
+    /// enabled.
+    pub fn try_encode_rows(
+        rows: &[&[u64]],
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Vec<Self>> {
+        if let Some(row) = rows.iter().find(|row| row.len() > par.degree()) {
+            return Err(Error::TooManyValues(row.len(), par.degree()));
+        }
+        if encoding.encoding == EncodingEnum::Simd && par.op.is_none() {
+            return Err(Error::EncodingNotSupported(EncodingEnum::Simd.to_string()));
+        }
+        let ctx = par.ctx_at_level(encoding.level)?;
+
+        let encode_row = |row: &&[u64]| {
+            if row.is_empty() {
+                Plaintext::zero(encoding.clone(), par)
+            } else {
+                PlaintextVec::encode_row(row.iter().copied(), &encoding, ctx, par)
+            }
+        };
+        #[cfg(feature = "rayon")]
+        let plaintexts = rows.par_iter().map(encode_row).collect();
+        #[cfg(not(feature = "rayon"))]
+        let plaintexts = rows.iter().map(encode_row).collect();
+        plaintexts
+    }
 }
 
 unsafe impl Send for Plaintext {}
 
+impl Neg for &Plaintext {
+    type Output = Plaintext;
+
+    /// Returns the plaintext encoding the opposite values modulo the plaintext
+    /// modulus, with the same encoding and level, e.g. to be cached in
+    /// circuits subtracting the same plaintext many times.
+    fn neg(self) -> Plaintext {
+        let mut value = self.value.clone();
+        self.par.plaintext.neg_vec(&mut value);
+        let mut poly_ntt = Poly::try_convert_from(
+            value.as_ref(),
+            self.poly_ntt.ctx(),
+            self.poly_ntt.allows_variable_time_computations(),
+            Representation::PowerBasis,
+        )
+        .unwrap();
+        poly_ntt.change_representation(Representation::Ntt);
+        let sparsity = match self.sparsity {
+            Sparsity::Constant(c) => Sparsity::Constant(self.par.plaintext.neg(c)),
+            sparsity => sparsity,
+        };
+        Plaintext {
+            par: self.par.clone(),
+            value,
+            encoding: self.encoding.clone(),
+            poly_ntt,
+            level: self.level,
+            sparsity,
+        }
+    }
+}
+
+/// A plaintext scaled by `delta` in NTT representation, as returned by
+/// [`Plaintext::scaled_for_add`].
+///
+/// Adding a [`Plaintext`] to a ciphertext, or subtracting it, scales the
+/// plaintext by `delta` every time; a scaled plaintext is added to or
+/// subtracted from ciphertexts at its level with a single addition of
+/// polynomials.
+#[derive(Debug, Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct ScaledPlaintext {
+    /// The parameters of the underlying BFV encryption scheme.
+    #[zeroize(skip)]
+    pub(crate) par: Arc<BfvParameters>,
+    /// The plaintext scaled by `delta`, in NTT representation.
+    pub(crate) poly: Poly,
+    /// The level of the plaintext.
+    #[zeroize(skip)]
+    pub(crate) level: usize,
+}
+
+impl ScaledPlaintext {
+    /// Returns the level of this plaintext.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
+impl Neg for &ScaledPlaintext {
+    type Output = ScaledPlaintext;
+
+    fn neg(self) -> ScaledPlaintext {
+        ScaledPlaintext {
+            par: self.par.clone(),
+            poly: -&self.poly,
+            level: self.level,
+        }
+    }
+}
+
 // Implement the equality manually; we want to say that two plaintexts are equal
 // even if one of them doesn't store its encoding information.
 impl PartialEq for Plaintext {
     fn eq(&self, other: &Self) -> bool {
         let mut eq = self.par == other.par;
         eq &= self.value == other.value;
-        if self.encoding.is_some() && other.encoding.is_some() {
-            eq &= self.encoding.as_ref().unwrap() == other.encoding.as_ref().unwrap()
+        if let (Some(enc), Some(other_enc)) = (self.encoding.as_ref(), other.encoding.as_ref()) {
+            eq &= enc == other_enc
         }
         eq
     }
@@ -148,11 +360,7 @@ where
 impl<'a> FheEncoder<&'a [u64]> for Plaintext {
     type Error = Error;
     fn try_encode(value: &'a [u64], encoding: Encoding, par: &Arc<BfvParameters>) -> Result<Self> {
-        if value.len() > par.degree() {
-            return Err(Error::TooManyValues(value.len(), par.degree()));
-        }
-        let v = PlaintextVec::try_encode(value, encoding, par)?;
-        Ok(v[0].clone())
+        Plaintext::try_encode_from_iter(value.iter().copied(), encoding, par)
     }
 }
 
@@ -164,6 +372,77 @@ impl<'a> FheEncoder<&'a [i64]> for Plaintext {
     }
 }
 
+// Variable time encoding, for public values only.
+//
+// The variable time encoding is significantly faster than the constant time
+// one, which makes a difference when encoding large public datasets, e.g., the
+// database in PIR or the set of the server in PSI. It must never be used on
+// secret values: the running time of the encoding depends on the values being
+// encoded, and the resulting plaintext allows for variable time computations
+// with the ciphertexts it interacts with. The encoding also detects the zero
+// and constant polynomials, e.g. all-zero or all-equal SIMD masks, so that
+// the products by the resulting plaintexts zero or scale the ciphertexts
+// instead of multiplying them pointwise.
+
+impl<'a, const N: usize, T> FheEncoderVariableTime<&'a [T; N]> for Plaintext
+where
+    Plaintext: FheEncoderVariableTime<&'a [T], Error = Error>,
+{
+    type Error = Error;
+
+    unsafe fn try_encode_vt(
+        value: &'a [T; N],
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        Plaintext::try_encode_vt(value.as_ref(), encoding, par)
+    }
+}
+
+impl<'a, T> FheEncoderVariableTime<&'a Vec<T>> for Plaintext
+where
+    Plaintext: FheEncoderVariableTime<&'a [T], Error = Error>,
+{
+    type Error = Error;
+
+    unsafe fn try_encode_vt(
+        value: &'a Vec<T>,
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        Plaintext::try_encode_vt(value.as_ref(), encoding, par)
+    }
+}
+
+impl<'a> FheEncoderVariableTime<&'a [u64]> for Plaintext {
+    type Error = Error;
+
+    unsafe fn try_encode_vt(
+        value: &'a [u64],
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        if value.len() > par.degree() {
+            return Err(Error::TooManyValues(value.len(), par.degree()));
+        }
+        let v = PlaintextVec::try_encode_vt(value, encoding, par)?;
+        Ok(v[0].clone())
+    }
+}
+
+impl<'a> FheEncoderVariableTime<&'a [i64]> for Plaintext {
+    type Error = Error;
+
+    unsafe fn try_encode_vt(
+        value: &'a [i64],
+        encoding: Encoding,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Self> {
+        let w = par.plaintext.reduce_vec_i64_vt(value);
+        Plaintext::try_encode_vt(w.as_ref() as &[u64], encoding, par)
+    }
+}
+
 impl FheDecoder<Plaintext> for Vec<u64> {
     fn try_decode<O>(pt: &Plaintext, encoding: O) -> Result<Vec<u64>>
     where
@@ -224,13 +503,70 @@ impl FheDecoder<Plaintext> for Vec<i64> {
     type Error = Error;
 }
 
+impl Serialize for Plaintext {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut proto = PlaintextProto {
+            value: self.value.to_vec(),
+            encoding: match self.encoding.as_ref().map(|e| &e.encoding) {
+                None => 0,
+                Some(EncodingEnum::Poly) => 1,
+                Some(EncodingEnum::Simd) => 2,
+            },
+            level: self.level as u32,
+        };
+        let bytes = proto.encode_to_vec();
+        proto.value.zeroize();
+        bytes
+    }
+}
+
+impl DeserializeParametrized for Plaintext {
+    fn from_bytes(bytes: &[u8], par: &Arc<BfvParameters>) -> Result<Self> {
+        let mut proto: PlaintextProto =
+            Message::decode(bytes).map_err(|_| Error::SerializationError)?;
+        let level = proto.level as usize;
+        let encoding = match proto.encoding {
+            0 => None,
+            1 => Some(Encoding::poly_at_level(level)),
+            2 => Some(Encoding::simd_at_level(level)),
+            _ => return Err(Error::SerializationError),
+        };
+        if proto.value.len() != par.degree()
+            || proto.value.iter().any(|v| *v >= par.plaintext())
+            || level > par.max_level()
+        {
+            proto.value.zeroize();
+            return Err(Error::SerializationError);
+        }
+        let mut poly_ntt = Poly::try_convert_from(
+            proto.value.as_slice(),
+            par.ctx_at_level(level)?,
+            false,
+            Representation::PowerBasis,
+        )?;
+        poly_ntt.change_representation(Representation::Ntt);
+        Ok(Self {
+            par: par.clone(),
+            value: core::mem::take(&mut proto.value).into_boxed_slice(),
+            encoding,
+            poly_ntt,
+            level,
+            sparsity: Sparsity::Dense,
+        })
+    }
+
+    type Error = Error;
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Encoding, Plaintext};
     use crate::bfv::parameters::{BfvParameters, BfvParametersBuilder};
     use crate::Error;
     use fhe_math::rq::{Poly, Representation};
-    use fhe_traits::{FheDecoder, FheEncoder};
+    use fhe_traits::{
+        DeserializeParametrized, FheDecoder, FheEncoder, FheEncoderVariableTime, Serialize,
+    };
     use rand::thread_rng;
     use zeroize::Zeroize;
     extern crate alloc;
@@ -262,6 +598,7 @@ mod tests {
             .set_degree(16)
             .set_plaintext_modulus(2)
             .set_moduli(&[4611686018326724609])
+            .allow_insecure()
             .build_arc()?;
 
         let a = params.plaintext.random_vec(params.degree(), &mut rng);
@@ -300,6 +637,95 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn try_encode_rows() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let b = params.plaintext.random_vec(5, &mut rng);
+        let rows: [&[u64]; 3] = [&a, &b, &[]];
+
+        for encoding in [Encoding::poly(), Encoding::simd_at_level(1)] {
+            let plaintexts = Plaintext::try_encode_rows(&rows, encoding.clone(), &params)?;
+            assert_eq!(plaintexts.len(), rows.len());
+            for (row, pt) in rows.iter().zip(&plaintexts) {
+                let expected = Plaintext::try_encode(*row, encoding.clone(), &params)?;
+                assert_eq!(pt, &expected);
+                assert_eq!(pt.poly_ntt.coefficients(), expected.poly_ntt.coefficients());
+                assert_eq!(pt.level(), encoding.level);
+            }
+        }
+
+        assert!(Plaintext::try_encode_rows(&[], Encoding::poly(), &params)?.is_empty());
+        let too_long = [0u64; 17];
+        assert_eq!(
+            Plaintext::try_encode_rows(&[&a, &too_long], Encoding::poly(), &params)
+                .expect_err("Should fail because a row is too long"),
+            Error::TooManyValues(17, 16)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn try_encode_from_iter() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+
+        for encoding in [Encoding::poly(), Encoding::simd_at_level(1)] {
+            for len in [0, 1, 5, params.degree()] {
+                let pt = Plaintext::try_encode_from_iter(
+                    a[..len].iter().copied(),
+                    encoding.clone(),
+                    &params,
+                )?;
+                let expected = Plaintext::try_encode(&a[..len], encoding.clone(), &params)?;
+                assert_eq!(pt, expected);
+                assert_eq!(pt.poly_ntt, expected.poly_ntt);
+                assert_eq!(pt.level(), encoding.level);
+            }
+        }
+
+        // The values are encoded as they are produced.
+        let pt =
+            Plaintext::try_encode_from_iter((0..16u32).map(u64::from), Encoding::poly(), &params)?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&pt, Encoding::poly())?,
+            (0..16).collect::<Vec<u64>>()
+        );
+
+        assert_eq!(
+            Plaintext::try_encode_from_iter((0..17u32).map(u64::from), Encoding::poly(), &params)
+                .expect_err("Should fail because there are too many values"),
+            Error::TooManyValues(17, 16)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn try_encode_vt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(1, 16);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let a_signed = unsafe { params.plaintext.center_vec_vt(&a) };
+
+        for encoding in [Encoding::poly(), Encoding::simd()] {
+            let plaintext = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+            let plaintext_vt = unsafe { Plaintext::try_encode_vt(&a, encoding.clone(), &params)? };
+            assert_eq!(plaintext, plaintext_vt);
+            assert_eq!(Vec::<u64>::try_decode(&plaintext_vt, encoding.clone())?, a);
+
+            let plaintext_vt =
+                unsafe { Plaintext::try_encode_vt(&a_signed, encoding.clone(), &params)? };
+            assert_eq!(plaintext, plaintext_vt);
+        }
+
+        let plaintext = unsafe { Plaintext::try_encode_vt(&[0u64; 17], Encoding::poly(), &params) };
+        assert!(plaintext.is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn partial_eq() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -370,6 +796,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn random() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        for encoding in [Encoding::poly(), Encoding::simd_at_level(1)] {
+            let pt = Plaintext::random(encoding.clone(), &params, &mut rng)?;
+            assert_eq!(pt.level(), encoding.level);
+            let values = Vec::<u64>::try_decode(&pt, encoding.clone())?;
+            assert_eq!(values.len(), params.degree());
+            assert!(values.iter().all(|v| *v < params.plaintext()));
+            assert_ne!(pt, Plaintext::random(encoding, &params, &mut rng)?);
+        }
+        Ok(())
+    }
+
     #[test]
     fn zeroize() -> Result<(), Error> {
         let mut rng = thread_rng();
@@ -400,4 +841,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn serialize() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        for encoding in [Encoding::poly(), Encoding::simd_at_level(2)] {
+            let pt = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+            let deserialized = Plaintext::from_bytes(&pt.to_bytes(), &params)?;
+            assert_eq!(deserialized, pt);
+            assert_eq!(deserialized.encoding, Some(encoding));
+            assert_eq!(deserialized.level(), pt.level());
+            assert_eq!(deserialized.poly_ntt, pt.poly_ntt);
+        }
+
+        let other = BfvParameters::default_arc(6, 32);
+        let pt = Plaintext::try_encode(&a, Encoding::poly(), &params)?;
+        assert_eq!(
+            Plaintext::from_bytes(&pt.to_bytes(), &other),
+            Err(Error::SerializationError)
+        );
+        Ok(())
+    }
 }
@@ -5,12 +5,14 @@ use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
-use fhe_traits::{FheEncoder, FheEncoderVariableTime, FheParametrized, FhePlaintext};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
+use fhe_traits::{FheDecoder, FheEncoder, FheEncoderVariableTime, FheParametrized, FhePlaintext};
+use itertools::{izip, repeat_n, Itertools};
+use zeroize::Zeroizing;
 use zeroize_derive::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
-    bfv::{BfvParameters, Encoding, Plaintext},
+    bfv::{BfvParameters, Encoding, Plaintext, Sparsity},
     Error, Result,
 };
 
@@ -19,7 +21,7 @@ use super::encoding::EncodingEnum;
 /// A wrapper around a vector of plaintext which implements the [`FhePlaintext`]
 /// trait, and therefore can be encoded to / decoded from.
 #[derive(Zeroize, ZeroizeOnDrop)]
-pub struct PlaintextVec(Vec<Plaintext>);
+pub struct PlaintextVec(pub(crate) Vec<Plaintext>);
 
 impl Deref for PlaintextVec {
     type Target = [Plaintext];
@@ -72,9 +74,31 @@ impl FheEncoderVariableTime<&[u64]> for PlaintextVec {
                         }
                     };
 
-                    let mut poly =
-                        Poly::try_convert_from(&v, ctx, true, Representation::PowerBasis)?;
-                    poly.change_representation(Representation::Ntt);
+                    // The Ntt representation of a constant polynomial is constant.
+                    let sparsity = Sparsity::detect_vt(&v);
+                    let poly = match sparsity {
+                        Sparsity::Dense => {
+                            let mut poly =
+                                Poly::try_convert_from(&v, ctx, true, Representation::PowerBasis)?;
+                            poly.change_representation(Representation::Ntt);
+                            poly
+                        }
+                        Sparsity::Zero | Sparsity::Constant(_) => {
+                            let coefficients = ctx
+                                .moduli_operators()
+                                .iter()
+                                .flat_map(|qi| repeat_n(qi.reduce(v[0]), par.degree()))
+                                .collect_vec();
+                            let mut poly = Poly::try_convert_from(
+                                coefficients,
+                                ctx,
+                                true,
+                                Representation::Ntt,
+                            )?;
+                            unsafe { poly.allow_variable_time_computations() }
+                            poly
+                        }
+                    };
 
                     Ok(Plaintext {
                         par: par.clone(),
@@ -82,6 +106,7 @@ impl FheEncoderVariableTime<&[u64]> for PlaintextVec {
                         encoding: Some(encoding.clone()),
                         poly_ntt: poly,
                         level: encoding.level,
+                        sparsity,
                     })
                 })
                 .collect::<Result<Vec<Plaintext>>>()?,
@@ -105,40 +130,97 @@ impl FheEncoder<&[u64]> for PlaintextVec {
             (0..num_plaintexts)
                 .map(|i| {
                     let slice = &value[i * par.degree()..min(value.len(), (i + 1) * par.degree())];
-                    let mut v = vec![0u64; par.degree()];
-                    match encoding.encoding {
-                        EncodingEnum::Poly => v[..slice.len()].copy_from_slice(slice),
-                        EncodingEnum::Simd => {
-                            for i in 0..slice.len() {
-                                v[par.matrix_reps_index_map[i]] = slice[i];
-                            }
-                            par.op
-                                .as_ref()
-                                .ok_or(Error::DefaultError("No Ntt operator".to_string()))?
-                                .backward(&mut v);
-                        }
-                    };
-
-                    let mut poly =
-                        Poly::try_convert_from(&v, ctx, false, Representation::PowerBasis)?;
-                    poly.change_representation(Representation::Ntt);
-
-                    Ok(Plaintext {
-                        par: par.clone(),
-                        value: v.into(),
-                        encoding: Some(encoding.clone()),
-                        poly_ntt: poly,
-                        level: encoding.level,
-                    })
+                    PlaintextVec::encode_row(slice.iter().copied(), &encoding, ctx, par)
                 })
                 .collect::<Result<Vec<Plaintext>>>()?,
         ))
     }
 }
 
+impl PlaintextVec {
+    /// Recodes the plaintexts for the parameters `par`, which must have the
+    /// same plaintext modulus, e.g. to migrate stored plaintexts to parameters
+    /// with another degree or other moduli.
+    ///
+    /// The decoded values of the plaintexts are concatenated, and encoded with
+    /// the same encoding at level 0 of `par`, in as many plaintexts as needed:
+    /// the values are split when the degree of `par` is smaller, and the last
+    /// plaintext is padded with zeros. Returns an error if the encoding of a
+    /// plaintext is unknown or differs from the encoding of the others, or if
+    /// `par` does not support the encoding.
+    pub fn recode(&self, par: &Arc<BfvParameters>) -> Result<PlaintextVec> {
+        let first = self.0.first().ok_or(Error::TooFewValues(0, 1))?;
+        if first.par.plaintext() != par.plaintext() {
+            return Err(Error::DefaultError(
+                "The plaintext moduli differ".to_string(),
+            ));
+        }
+        let encoding = first
+            .encoding
+            .as_ref()
+            .ok_or(Error::UnspecifiedInput("No encoding specified".to_string()))?
+            .encoding
+            .clone();
+        let mut values = Zeroizing::new(Vec::with_capacity(self.0.len() * first.par.degree()));
+        for pt in self.0.iter() {
+            match &pt.encoding {
+                Some(e) if e.encoding == encoding => {
+                    values.extend_from_slice(&Zeroizing::new(Vec::<u64>::try_decode(pt, None)?))
+                }
+                _ => {
+                    return Err(Error::UnspecifiedInput(
+                        "The plaintexts must have the same encoding".to_string(),
+                    ))
+                }
+            }
+        }
+        PlaintextVec::try_encode(
+            values.as_ref() as &[u64],
+            Encoding { encoding, level: 0 },
+            par,
+        )
+    }
+
+    /// Encodes at most `degree` values into a plaintext, in constant time. The
+    /// encoding must be supported by the parameters, and `ctx` must be the
+    /// context at the level of the encoding.
+    pub(crate) fn encode_row<I: Iterator<Item = u64>>(
+        values: I,
+        encoding: &Encoding,
+        ctx: &Arc<Context>,
+        par: &Arc<BfvParameters>,
+    ) -> Result<Plaintext> {
+        let mut v = vec![0u64; par.degree()];
+        match encoding.encoding {
+            EncodingEnum::Poly => izip!(v.iter_mut(), values).for_each(|(vi, value)| *vi = value),
+            EncodingEnum::Simd => {
+                for (i, value) in values.take(par.degree()).enumerate() {
+                    v[par.matrix_reps_index_map[i]] = value;
+                }
+                par.op
+                    .as_ref()
+                    .ok_or(Error::DefaultError("No Ntt operator".to_string()))?
+                    .backward(&mut v);
+            }
+        };
+
+        let mut poly = Poly::try_convert_from(&v, ctx, false, Representation::PowerBasis)?;
+        poly.change_representation(Representation::Ntt);
+
+        Ok(Plaintext {
+            par: par.clone(),
+            value: v.into(),
+            encoding: Some(encoding.clone()),
+            poly_ntt: poly,
+            level: encoding.level,
+            sparsity: Sparsity::Dense,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::bfv::{BfvParameters, Encoding, PlaintextVec};
+    use crate::bfv::{BfvParameters, BfvParametersBuilder, Encoding, Plaintext, PlaintextVec};
     use crate::Error;
     use fhe_traits::{FheDecoder, FheEncoder, FheEncoderVariableTime};
     use rand::thread_rng;
@@ -174,4 +256,57 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn recode() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let small = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[50])
+            .allow_insecure()
+            .build_arc()?;
+        let large = BfvParametersBuilder::new()
+            .set_degree(32)
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[50, 50])
+            .allow_insecure()
+            .build_arc()?;
+
+        for encoding in [Encoding::poly(), Encoding::simd_at_level(0)] {
+            let a = small.plaintext.random_vec(2 * small.degree(), &mut rng);
+            let plaintexts = PlaintextVec::try_encode(&a, encoding.clone(), &small)?;
+
+            // Concatenation of two plaintexts into one.
+            let recoded = plaintexts.recode(&large)?;
+            assert_eq!(recoded.len(), 1);
+            assert_eq!(Vec::<u64>::try_decode(&recoded[0], encoding.clone())?, a);
+
+            // Splitting of one plaintext into two.
+            let split = recoded[0].recode(&small)?;
+            assert_eq!(split.len(), 2);
+            assert!(split.iter().zip(plaintexts.iter()).all(|(p, q)| p == q));
+
+            // Padding with zeros.
+            let b = small.plaintext.random_vec(small.degree() / 2, &mut rng);
+            let pt = Plaintext::try_encode(&b, encoding.clone(), &small)?;
+            let recoded = pt.recode(&large)?;
+            let mut expected = b.clone();
+            expected.resize(large.degree(), 0);
+            assert_eq!(
+                Vec::<u64>::try_decode(&recoded[0], encoding.clone())?,
+                expected
+            );
+        }
+
+        let other = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(2)
+            .set_moduli_sizes(&[50])
+            .allow_insecure()
+            .build_arc()?;
+        let pt = Plaintext::try_encode(&[1u64], Encoding::poly(), &small)?;
+        assert!(pt.recode(&other).is_err());
+        Ok(())
+    }
 }
@@ -195,8 +195,7 @@ mod tests {
             BfvParameters::default_arc(5, 16),
         ] {
             let sk = SecretKey::random(&params, &mut rng);
-            let v = params.plaintext.random_vec(params.degree(), &mut rng);
-            let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+            let pt = Plaintext::random(Encoding::simd(), &params, &mut rng)?;
             let ct: RGSWCiphertext = sk.try_encrypt(&pt, &mut rng)?;
 
             let bytes = ct.to_bytes();
@@ -0,0 +1,1288 @@
+//! Interoperability with the binary formats of Microsoft SEAL.
+//!
+//! A [`SealContext`] describes the SEAL encryption parameters matching some
+//! [`BfvParameters`]: the BFV scheme with the same degree and plaintext
+//! modulus, and the coefficient modulus made of the ciphertext moduli of the
+//! parameters followed, when keys are switched, by the special modulus of the
+//! key level. It reads and writes the uncompressed SEAL 4 serializations of
+//! ciphertexts, secret keys and Galois keys, i.e. those saved with
+//! `compr_mode_type::none` from non-seeded objects, with the headers of the
+//! [`SealVersion`] of the context.
+//!
+//! SEAL switches keys with the special modulus, whereas the Galois keys of
+//! this library decompose the ciphertext modulus without a special modulus,
+//! so the two kinds of Galois keys are not interchangeable: SEAL Galois keys
+//! are generated from a [`SecretKey`] with [`SealContext::write_galois_keys`],
+//! and are read as [`SealGaloisKeys`], which apply SEAL's key switching.
+
+use crate::bfv::{BfvParameters, Ciphertext, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation, SubstitutionExponent};
+use fhe_math::stream::{read_u64, write_u64, Reader, Writer};
+use fhe_math::zq::Modulus;
+use hashbrown::HashMap;
+use itertools::{izip, Itertools};
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+/// The magic number starting the SEAL serialization headers.
+const MAGIC: u16 = 0xA15E;
+/// The size of the SEAL serialization headers.
+const HEADER_SIZE: u64 = 16;
+/// The major version of SEAL whose serializations are read and written.
+const VERSION_MAJOR: u8 = 4;
+/// The identifier of the BFV scheme in SEAL.
+const SCHEME_BFV: u64 = 1;
+/// The largest bit size of a SEAL coefficient modulus.
+const MAX_MODULUS_BITS: u32 = 60;
+
+/// The size of the members of a ciphertext before its coefficients: the
+/// parameters identifier, the NTT flag, the size, the degree, the number of
+/// moduli, the scale and the correction factor.
+const CIPHERTEXT_METADATA_SIZE: u64 = 32 + 1 + 3 * 8 + 8 + 8;
+
+/// The version of SEAL written in the serialization headers.
+///
+/// SEAL rejects the serializations whose header has a different minor
+/// version than its own (except those of SEAL 3.4 and above), so the version
+/// must be the one of the SEAL library reading the serializations. All the
+/// SEAL 4 serializations are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SealVersion {
+    /// SEAL 4.0.
+    V4_0,
+    /// SEAL 4.1.
+    V4_1,
+}
+
+impl SealVersion {
+    fn minor(&self) -> u8 {
+        match self {
+            SealVersion::V4_0 => 0,
+            SealVersion::V4_1 => 1,
+        }
+    }
+}
+
+/// The SEAL encryption parameters matching some [`BfvParameters`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct SealContext {
+    par: Arc<BfvParameters>,
+    /// The version of SEAL written in the serialization headers.
+    version: SealVersion,
+    /// The context of the key level: the ciphertext moduli, followed by the
+    /// special modulus when there is one.
+    key_ctx: Arc<Context>,
+    /// The SEAL NTT tables of the moduli of the key level.
+    ntt: Vec<SealNtt>,
+    /// Whether the key level has a special modulus.
+    has_special_modulus: bool,
+}
+
+impl SealContext {
+    /// Creates the SEAL context matching `par`, with the special modulus
+    /// `special_modulus` of the key level, i.e. the last modulus of the SEAL
+    /// coefficient modulus; without a special modulus, the SEAL coefficient
+    /// modulus must have a single modulus.
+    ///
+    /// Returns an error if SEAL does not support these parameters: the moduli
+    /// must be distinct primes of at most 60 bits, congruent to 1 modulo twice
+    /// the degree.
+    ///
+    /// The serializations are written with the headers of SEAL 4.1; see
+    /// [`SealContext::new_with_version`] for other versions.
+    pub fn new(par: &Arc<BfvParameters>, special_modulus: Option<u64>) -> Result<Arc<Self>> {
+        Self::new_with_version(par, special_modulus, SealVersion::V4_1)
+    }
+
+    /// Creates the SEAL context matching `par`, as [`SealContext::new`],
+    /// writing the serializations with the headers of `version`.
+    pub fn new_with_version(
+        par: &Arc<BfvParameters>,
+        special_modulus: Option<u64>,
+        version: SealVersion,
+    ) -> Result<Arc<Self>> {
+        let mut moduli = par.moduli().to_vec();
+        if let Some(special_modulus) = special_modulus {
+            if moduli.contains(&special_modulus) {
+                return Err(Error::UnspecifiedInput(
+                    "The special modulus must differ from the ciphertext moduli".to_string(),
+                ));
+            }
+            moduli.push(special_modulus);
+        } else if moduli.len() > 1 {
+            return Err(Error::UnspecifiedInput(
+                "A special modulus is required with several ciphertext moduli".to_string(),
+            ));
+        }
+        if moduli.iter().any(|qi| qi.ilog2() >= MAX_MODULUS_BITS)
+            || par.plaintext().ilog2() >= MAX_MODULUS_BITS
+        {
+            return Err(Error::UnspecifiedInput(
+                "SEAL moduli have at most 60 bits".to_string(),
+            ));
+        }
+        let key_ctx = Arc::new(Context::new(&moduli, par.degree())?);
+        let ntt = key_ctx
+            .moduli_operators()
+            .iter()
+            .map(|qi| SealNtt::new(qi, par.degree()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Arc::new(Self {
+            par: par.clone(),
+            version,
+            key_ctx,
+            ntt,
+            has_special_modulus: special_modulus.is_some(),
+        }))
+    }
+
+    /// Returns the BFV parameters of this context.
+    pub fn parameters(&self) -> &Arc<BfvParameters> {
+        &self.par
+    }
+
+    /// Returns the version of SEAL written in the serialization headers.
+    pub fn version(&self) -> SealVersion {
+        self.version
+    }
+
+    /// Returns the SEAL identifier of the parameters of the ciphertexts at
+    /// `level`, i.e. the BLAKE2b hash of the scheme, the degree, the moduli of
+    /// the level and the plaintext modulus.
+    pub fn parms_id(&self, level: usize) -> Result<[u8; 32]> {
+        Ok(self.parms_id_of(self.par.ctx_at_level(level)?.moduli()))
+    }
+
+    /// Returns the SEAL identifier of the parameters of the key level.
+    pub fn key_parms_id(&self) -> [u8; 32] {
+        self.parms_id_of(self.key_ctx.moduli())
+    }
+
+    /// Returns the identifier computed by `EncryptionParameters::compute_parms_id`
+    /// in SEAL: the BLAKE2b hash of the words of the scheme, the degree, each
+    /// modulus of `moduli` and the plaintext modulus. SEAL uses the number of
+    /// moduli to size the hashed buffer, but does not hash it.
+    fn parms_id_of(&self, moduli: &[u64]) -> [u8; 32] {
+        let words = [SCHEME_BFV, self.par.degree() as u64]
+            .into_iter()
+            .chain(moduli.iter().copied())
+            .chain([self.par.plaintext()]);
+        let mut data = Vec::with_capacity(8 * (moduli.len() + 3));
+        words.for_each(|word| data.extend_from_slice(&word.to_le_bytes()));
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&blake2b(&data, 32));
+        id
+    }
+
+    /// Returns the number of moduli of the key level.
+    fn key_moduli_len(&self) -> usize {
+        self.key_ctx.moduli().len()
+    }
+
+    /// Writes `ct` to `w` as a SEAL ciphertext, in coefficient representation.
+    ///
+    /// Returns an error if the ciphertext has fewer than two polynomials.
+    pub fn write_ciphertext<W: Writer + ?Sized>(&self, ct: &Ciphertext, w: &mut W) -> Result<()> {
+        if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        if ct.len() < 2 {
+            return Err(Error::TooFewValues(ct.len(), 2));
+        }
+        let num_moduli = ct[0].ctx().moduli().len();
+        let count = (ct.len() * num_moduli * self.par.degree()) as u64;
+        self.write_header(
+            w,
+            HEADER_SIZE + CIPHERTEXT_METADATA_SIZE + array_size(count),
+        )?;
+        w.write_all(&self.parms_id(ct.level)?)?;
+        w.write_all(&[0])?;
+        write_u64(w, ct.len() as u64)?;
+        write_u64(w, self.par.degree() as u64)?;
+        write_u64(w, num_moduli as u64)?;
+        write_u64(w, 1f64.to_bits())?;
+        write_u64(w, 1)?;
+        self.write_array_header(w, count)?;
+        for ci in ct.iter() {
+            let mut ci = ci.clone();
+            ci.change_representation(Representation::PowerBasis);
+            for coefficient in ci.coefficients().iter() {
+                write_u64(w, *coefficient)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a SEAL ciphertext from `r`.
+    ///
+    /// Returns an error if the ciphertext is not a BFV ciphertext in
+    /// coefficient representation for the parameters of one of the levels, or
+    /// if it is compressed or seeded.
+    pub fn read_ciphertext<R: Reader + ?Sized>(&self, r: &mut R) -> Result<Ciphertext> {
+        let size = read_header(r)?;
+        let parms_id = read_parms_id(r)?;
+        let level = (0..=self.par.max_level())
+            .find(|level| self.parms_id(*level).is_ok_and(|id| id == parms_id))
+            .ok_or_else(|| Error::DefaultError("Unknown SEAL parameters".to_string()))?;
+        let ctx = self.par.ctx_at_level(level)?;
+        let (is_ntt_form, num_polys) = self.read_ciphertext_metadata(r, ctx.moduli().len())?;
+        if is_ntt_form {
+            return Err(Error::DefaultError(
+                "BFV ciphertexts must be in coefficient representation".to_string(),
+            ));
+        }
+        if !(2..=16).contains(&num_polys) {
+            return Err(Error::DefaultError(
+                "Invalid number of polynomials".to_string(),
+            ));
+        }
+        let count = (num_polys * ctx.moduli().len() * self.par.degree()) as u64;
+        if size != HEADER_SIZE + CIPHERTEXT_METADATA_SIZE + array_size(count) {
+            return Err(Error::DefaultError("Invalid SEAL object size".to_string()));
+        }
+        let c = read_array(r, count)?
+            .chunks(ctx.moduli().len() * self.par.degree())
+            .map(|coefficients| {
+                let mut ci = poly_from_coefficients(coefficients, ctx)?;
+                ci.change_representation(Representation::Ntt);
+                Ok(ci)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ciphertext::new(c, &self.par)
+    }
+
+    /// Reads the members of a ciphertext after the parameters identifier,
+    /// checks that it has `num_moduli` moduli and returns whether it is in NTT
+    /// representation and its number of polynomials.
+    fn read_ciphertext_metadata<R: Reader + ?Sized>(
+        &self,
+        r: &mut R,
+        num_moduli: usize,
+    ) -> Result<(bool, usize)> {
+        let mut is_ntt_form = [0u8];
+        r.read_exact(&mut is_ntt_form)?;
+        let num_polys = read_u64(r)? as usize;
+        let degree = read_u64(r)? as usize;
+        let coeff_modulus_size = read_u64(r)? as usize;
+        let _scale = read_u64(r)?;
+        let correction_factor = read_u64(r)?;
+        if is_ntt_form[0] > 1
+            || degree != self.par.degree()
+            || coeff_modulus_size != num_moduli
+            || correction_factor != 1
+        {
+            return Err(Error::DefaultError(
+                "Invalid SEAL ciphertext metadata".to_string(),
+            ));
+        }
+        Ok((is_ntt_form[0] == 1, num_polys))
+    }
+
+    /// Writes `sk` to `w` as a SEAL secret key, in SEAL's NTT representation
+    /// at the key level.
+    pub fn write_secret_key<W: Writer + ?Sized>(&self, sk: &SecretKey, w: &mut W) -> Result<()> {
+        if sk.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        let s = self.secret_ntt(sk)?;
+        let count = s.len() as u64;
+        self.write_header(w, HEADER_SIZE + 32 + 8 + 8 + array_size(count))?;
+        w.write_all(&self.key_parms_id())?;
+        write_u64(w, count)?;
+        write_u64(w, 1f64.to_bits())?;
+        self.write_array_header(w, count)?;
+        for si in s.iter() {
+            write_u64(w, *si)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a SEAL secret key from `r`.
+    ///
+    /// Returns an error if the secret key is not at the key level, or if its
+    /// residues modulo the moduli of the key level do not represent the same
+    /// small polynomial.
+    pub fn read_secret_key<R: Reader + ?Sized>(&self, r: &mut R) -> Result<SecretKey> {
+        let size = read_header(r)?;
+        if read_parms_id(r)? != self.key_parms_id() {
+            return Err(Error::DefaultError("Unknown SEAL parameters".to_string()));
+        }
+        let count = (self.key_moduli_len() * self.par.degree()) as u64;
+        let coeff_count = read_u64(r)?;
+        let _scale = read_u64(r)?;
+        if coeff_count != count || size != HEADER_SIZE + 32 + 8 + 8 + array_size(count) {
+            return Err(Error::DefaultError(
+                "Invalid SEAL secret key metadata".to_string(),
+            ));
+        }
+        let mut s = Zeroizing::new(read_array(r, count)?);
+        for (si, ntt) in izip!(s.chunks_mut(self.par.degree()), &self.ntt) {
+            if si.iter().any(|c| *c >= *ntt.q) {
+                return Err(Error::DefaultError(
+                    "A coefficient is not reduced modulo its modulus".to_string(),
+                ));
+            }
+            ntt.backward(si);
+        }
+
+        let q0 = &self.ntt[0].q;
+        let coeffs = Zeroizing::new(
+            s[..self.par.degree()]
+                .iter()
+                .map(|c| {
+                    if *c > **q0 >> 1 {
+                        -((**q0 - *c) as i64)
+                    } else {
+                        *c as i64
+                    }
+                })
+                .collect_vec(),
+        );
+        let expected = Zeroizing::new(Poly::try_convert_from(
+            coeffs.as_ref() as &[i64],
+            &self.key_ctx,
+            false,
+            Representation::PowerBasis,
+        )?);
+        if expected.coefficients().as_slice() != Some(&s[..]) {
+            return Err(Error::DefaultError(
+                "Inconsistent SEAL secret key".to_string(),
+            ));
+        }
+        Ok(SecretKey::new(coeffs.to_vec(), &self.par))
+    }
+
+    /// Returns the coefficients of the secret key in SEAL's NTT representation
+    /// at the key level.
+    fn secret_ntt(&self, sk: &SecretKey) -> Result<Zeroizing<Vec<u64>>> {
+        let s = Zeroizing::new(Poly::try_convert_from(
+            sk.coeffs.as_ref(),
+            &self.key_ctx,
+            false,
+            Representation::PowerBasis,
+        )?);
+        Ok(self.to_ntt(&s))
+    }
+
+    /// Returns the coefficients of a polynomial in the key context, in
+    /// PowerBasis representation, converted to SEAL's NTT representation.
+    fn to_ntt(&self, p: &Poly) -> Zeroizing<Vec<u64>> {
+        let mut coefficients = Zeroizing::new(p.coefficients().iter().copied().collect_vec());
+        izip!(coefficients.chunks_mut(self.par.degree()), &self.ntt)
+            .for_each(|(ci, ntt)| ntt.forward(ci));
+        coefficients
+    }
+
+    /// Generates SEAL Galois keys for the Galois `exponents` from `sk`, and
+    /// writes them to `w`.
+    ///
+    /// The rotation of the columns by `i`, as in
+    /// [`EvaluationKey::rotates_columns_by`](crate::bfv::EvaluationKey::rotates_columns_by),
+    /// has exponent `3^i` modulo twice the degree, and the rotation of the rows
+    /// has exponent `2 * degree - 1`, as in SEAL. Returns an error if the
+    /// context has no special modulus, or if an exponent is even.
+    pub fn write_galois_keys<W: Writer + ?Sized, R: RngCore + CryptoRng>(
+        &self,
+        sk: &SecretKey,
+        exponents: &[usize],
+        rng: &mut R,
+        w: &mut W,
+    ) -> Result<()> {
+        if sk.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        if !self.has_special_modulus {
+            return Err(Error::DefaultError(
+                "Galois keys require a special modulus".to_string(),
+            ));
+        }
+        let degree = self.par.degree();
+        let exponents = exponents
+            .iter()
+            .map(|e| e % (2 * degree))
+            .sorted()
+            .dedup()
+            .collect_vec();
+        if exponents.iter().any(|e| e % 2 == 0) {
+            return Err(Error::UnspecifiedInput(
+                "The Galois exponents must be odd".to_string(),
+            ));
+        }
+
+        let decomposition_size = self.key_moduli_len() - 1;
+        let key_size = self.key_size();
+        self.write_header(
+            w,
+            HEADER_SIZE
+                + 32
+                + 8
+                + 8 * degree as u64
+                + (exponents.len() * decomposition_size) as u64 * key_size,
+        )?;
+        w.write_all(&self.key_parms_id())?;
+        write_u64(w, degree as u64)?;
+
+        let s = Zeroizing::new(Poly::try_convert_from(
+            sk.coeffs.as_ref(),
+            &self.key_ctx,
+            false,
+            Representation::PowerBasis,
+        )?);
+        let s_ntt = self.to_ntt(&s);
+        let p = self.key_ctx.moduli().last().unwrap();
+        for index in 0..degree {
+            let exponent = 2 * index + 1;
+            if exponents.binary_search(&exponent).is_err() {
+                write_u64(w, 0)?;
+                continue;
+            }
+            write_u64(w, decomposition_size as u64)?;
+            let substitution = SubstitutionExponent::new(&self.key_ctx, exponent)?;
+            let s_sub = Zeroizing::new(s.substitute(&substitution)?);
+            let s_sub_ntt = self.to_ntt(&s_sub);
+            for j in 0..decomposition_size {
+                // An encryption of zero in NTT representation, to which the
+                // special modulus times the substituted secret key is added
+                // in the residue j only.
+                let e = Zeroizing::new(Poly::small(
+                    &self.key_ctx,
+                    Representation::PowerBasis,
+                    self.par.variance,
+                    rng,
+                )?);
+                let e = self.to_ntt(&e);
+                let a = self
+                    .ntt
+                    .iter()
+                    .flat_map(|ntt| ntt.q.random_vec(degree, rng))
+                    .collect_vec();
+                let mut c0 = Zeroizing::new(vec![0u64; a.len()]);
+                for (i, ntt) in self.ntt.iter().enumerate() {
+                    let range = i * degree..(i + 1) * degree;
+                    let factor = ntt.q.reduce(*p);
+                    for (c0k, ak, sk, ek, s_subk) in izip!(
+                        &mut c0[range.clone()],
+                        &a[range.clone()],
+                        &s_ntt[range.clone()],
+                        &e[range.clone()],
+                        &s_sub_ntt[range]
+                    ) {
+                        *c0k = ntt.q.neg(ntt.q.add(ntt.q.mul(*ak, *sk), *ek));
+                        if i == j {
+                            *c0k = ntt.q.add(*c0k, ntt.q.mul(factor, *s_subk));
+                        }
+                    }
+                }
+                self.write_key_ciphertext(w, &c0, &a)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the size of the serialization of a key switching ciphertext.
+    fn key_size(&self) -> u64 {
+        let count = (2 * self.key_moduli_len() * self.par.degree()) as u64;
+        HEADER_SIZE + CIPHERTEXT_METADATA_SIZE + array_size(count)
+    }
+
+    /// Writes a ciphertext of two polynomials in SEAL's NTT representation at
+    /// the key level, as the components of SEAL Galois keys.
+    fn write_key_ciphertext<W: Writer + ?Sized>(
+        &self,
+        w: &mut W,
+        c0: &[u64],
+        c1: &[u64],
+    ) -> Result<()> {
+        self.write_header(w, self.key_size())?;
+        w.write_all(&self.key_parms_id())?;
+        w.write_all(&[1])?;
+        write_u64(w, 2)?;
+        write_u64(w, self.par.degree() as u64)?;
+        write_u64(w, self.key_moduli_len() as u64)?;
+        write_u64(w, 1f64.to_bits())?;
+        write_u64(w, 1)?;
+        self.write_array_header(w, (c0.len() + c1.len()) as u64)?;
+        for c in c0.iter().chain(c1) {
+            write_u64(w, *c)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a SEAL serialization header, for an object of `size` bytes
+    /// including the header.
+    fn write_header<W: Writer + ?Sized>(&self, w: &mut W, size: u64) -> Result<()> {
+        w.write_all(&MAGIC.to_le_bytes())?;
+        w.write_all(&[
+            HEADER_SIZE as u8,
+            VERSION_MAJOR,
+            self.version.minor(),
+            0,
+            0,
+            0,
+        ])?;
+        write_u64(w, size)?;
+        Ok(())
+    }
+
+    /// Writes the header and the size of an array of `count` words, before
+    /// its elements.
+    fn write_array_header<W: Writer + ?Sized>(&self, w: &mut W, count: u64) -> Result<()> {
+        self.write_header(w, array_size(count))?;
+        Ok(write_u64(w, count)?)
+    }
+
+    /// Reads SEAL Galois keys from `r`.
+    ///
+    /// Returns an error if the Galois keys are not at the key level, or if
+    /// they are compressed or seeded.
+    pub fn read_galois_keys<R: Reader + ?Sized>(
+        self: &Arc<Self>,
+        r: &mut R,
+    ) -> Result<SealGaloisKeys> {
+        if !self.has_special_modulus {
+            return Err(Error::DefaultError(
+                "Galois keys require a special modulus".to_string(),
+            ));
+        }
+        let size = read_header(r)?;
+        if read_parms_id(r)? != self.key_parms_id() {
+            return Err(Error::DefaultError("Unknown SEAL parameters".to_string()));
+        }
+        let degree = self.par.degree();
+        let num_indices = read_u64(r)? as usize;
+        if num_indices > degree {
+            return Err(Error::DefaultError(
+                "Invalid number of Galois keys".to_string(),
+            ));
+        }
+        let decomposition_size = self.key_moduli_len() - 1;
+        let count = (2 * self.key_moduli_len() * degree) as u64;
+        let mut keys = HashMap::new();
+        let mut expected_size = HEADER_SIZE + 32 + 8 + 8 * num_indices as u64;
+        for index in 0..num_indices {
+            match read_u64(r)? as usize {
+                0 => continue,
+                n if n == decomposition_size => {}
+                _ => {
+                    return Err(Error::DefaultError(
+                        "Invalid Galois key decomposition size".to_string(),
+                    ))
+                }
+            }
+            let key = (0..decomposition_size)
+                .map(|_| {
+                    let size = read_header(r)?;
+                    if size != self.key_size() || read_parms_id(r)? != self.key_parms_id() {
+                        return Err(Error::DefaultError("Invalid SEAL Galois key".to_string()));
+                    }
+                    let (is_ntt_form, num_polys) =
+                        self.read_ciphertext_metadata(r, self.key_moduli_len())?;
+                    if !is_ntt_form || num_polys != 2 {
+                        return Err(Error::DefaultError("Invalid SEAL Galois key".to_string()));
+                    }
+                    let c = read_array(r, count)?;
+                    let reduced = izip!(c.chunks(degree), self.ntt.iter().cycle())
+                        .all(|(residues, ntt)| residues.iter().all(|c| *c < *ntt.q));
+                    if !reduced {
+                        return Err(Error::DefaultError(
+                            "A coefficient is not reduced modulo its modulus".to_string(),
+                        ));
+                    }
+                    let (c0, c1) = c.split_at(c.len() / 2);
+                    Ok([c0.to_vec(), c1.to_vec()])
+                })
+                .collect::<Result<Vec<_>>>()?;
+            expected_size += decomposition_size as u64 * self.key_size();
+            keys.insert(2 * index + 1, key);
+        }
+        if size != expected_size {
+            return Err(Error::DefaultError("Invalid SEAL object size".to_string()));
+        }
+        Ok(SealGaloisKeys {
+            ctx: self.clone(),
+            keys,
+        })
+    }
+}
+
+/// SEAL Galois keys, read with [`SealContext::read_galois_keys`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct SealGaloisKeys {
+    ctx: Arc<SealContext>,
+    /// Map from the Galois exponents to the key switching ciphertexts, one per
+    /// ciphertext modulus, in SEAL's NTT representation at the key level.
+    keys: HashMap<usize, Vec<[Vec<u64>; 2]>>,
+}
+
+impl SealGaloisKeys {
+    /// Returns the Galois exponents of these keys, in increasing order.
+    pub fn exponents(&self) -> Vec<usize> {
+        self.keys.keys().copied().sorted().collect()
+    }
+
+    /// Substitutes `x` by `x^exponent` in the plaintext of `ct`, with SEAL's
+    /// key switching.
+    ///
+    /// Returns an error if there is no key for this exponent, or if the
+    /// ciphertext does not have two polynomials.
+    pub fn apply_galois(&self, ct: &Ciphertext, exponent: usize) -> Result<Ciphertext> {
+        let ctx = &self.ctx;
+        if ct.par != ctx.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+        if ct.len() != 2 {
+            return Err(Error::DefaultError(
+                "The ciphertext must have two polynomials".to_string(),
+            ));
+        }
+        let exponent = exponent % (2 * ctx.par.degree());
+        let key = self.keys.get(&exponent).ok_or_else(|| {
+            Error::DefaultError(format!("No Galois key for the exponent {exponent}"))
+        })?;
+
+        let ct_ctx = ct[0].ctx();
+        let substitution = SubstitutionExponent::new(ct_ctx, exponent)?;
+        let mut c0 = ct[0].substitute(&substitution)?;
+        let mut c1 = ct[1].substitute(&substitution)?;
+        c0.change_representation(Representation::PowerBasis);
+        c1.change_representation(Representation::PowerBasis);
+        let (ks0, ks1) = self.switch_key(&c1, key)?;
+
+        let mut c1 = poly_from_coefficients(&ks1, ct_ctx)?;
+        c0 += &poly_from_coefficients(&ks0, ct_ctx)?;
+        c0.change_representation(Representation::Ntt);
+        c1.change_representation(Representation::Ntt);
+        Ciphertext::new(vec![c0, c1], &ctx.par)
+    }
+
+    /// Switches the key of the polynomial `target` in PowerBasis
+    /// representation, as SEAL: each residue of the target is multiplied by
+    /// the key switching ciphertext of its modulus, modulo the moduli of the
+    /// target and the special modulus, and the products are accumulated and
+    /// divided by the special modulus with rounding. Returns the coefficients
+    /// of the two resulting polynomials, modulo the moduli of the target.
+    fn switch_key(&self, target: &Poly, key: &[[Vec<u64>; 2]]) -> Result<(Vec<u64>, Vec<u64>)> {
+        let ctx = &self.ctx;
+        let degree = ctx.par.degree();
+        let decomposition_size = target.ctx().moduli().len();
+        let special = ctx.key_moduli_len() - 1;
+        // The indices of the moduli of the key level involved, i.e. those of
+        // the target followed by the special modulus.
+        let key_indices = (0..decomposition_size).chain([special]).collect_vec();
+
+        let target = target.coefficients();
+        let mut products = vec![vec![vec![0u64; degree]; key_indices.len()]; 2];
+        for (j, target_j) in target.outer_iter().enumerate() {
+            for (i, key_index) in key_indices.iter().enumerate() {
+                let ntt = &ctx.ntt[*key_index];
+                let mut t = target_j.iter().map(|c| ntt.q.reduce(*c)).collect_vec();
+                ntt.forward(&mut t);
+                let range = key_index * degree..(key_index + 1) * degree;
+                for (product, key_k) in izip!(products.iter_mut(), &key[j]) {
+                    for (acc, ti, ki) in izip!(&mut product[i], &t, &key_k[range.clone()]) {
+                        *acc = ntt.q.add(*acc, ntt.q.mul(*ti, *ki));
+                    }
+                }
+            }
+        }
+
+        let p_ntt = &ctx.ntt[special];
+        let p = *p_ntt.q;
+        let p_half = p >> 1;
+        let mut out = Vec::with_capacity(2);
+        for mut product in products {
+            let mut last = product.pop().unwrap();
+            p_ntt.backward(&mut last);
+            last.iter_mut().for_each(|c| *c = p_ntt.q.add(*c, p_half));
+            let mut coefficients = Vec::with_capacity(decomposition_size * degree);
+            for (i, mut product_i) in product.into_iter().enumerate() {
+                let ntt = &ctx.ntt[i];
+                let qi = &ntt.q;
+                let p_inv = qi
+                    .inv(qi.reduce(p))
+                    .ok_or_else(|| Error::DefaultError("Non-invertible modulus".to_string()))?;
+                let p_half_i = qi.reduce(p_half);
+                ntt.backward(&mut product_i);
+                for (c, l) in izip!(product_i.iter_mut(), &last) {
+                    let rounded = qi.sub(qi.reduce(*l), p_half_i);
+                    *c = qi.mul(qi.sub(*c, rounded), p_inv);
+                }
+                coefficients.append(&mut product_i);
+            }
+            out.push(coefficients);
+        }
+        let ks1 = out.pop().unwrap();
+        let ks0 = out.pop().unwrap();
+        Ok((ks0, ks1))
+    }
+}
+
+/// The negacyclic NTT of SEAL for one modulus, with the minimal primitive
+/// root of unity and the output in bit-reversed order.
+#[derive(Debug, PartialEq, Eq)]
+struct SealNtt {
+    q: Modulus,
+    /// The powers of the root in bit-reversed order.
+    root_powers: Vec<u64>,
+    /// The powers of the inverse root in the order of the inverse transform.
+    inv_root_powers: Vec<u64>,
+    degree_inv: u64,
+}
+
+impl SealNtt {
+    fn new(q: &Modulus, degree: usize) -> Result<Self> {
+        let root = minimal_primitive_root(q, 2 * degree as u64)?;
+        let inv_root = q.inv(root).unwrap();
+        let log_degree = degree.ilog2();
+        let bit_reverse = |i: usize| {
+            if log_degree == 0 {
+                0
+            } else {
+                i.reverse_bits() >> (usize::BITS - log_degree)
+            }
+        };
+
+        let mut root_powers = vec![1u64; degree];
+        let mut inv_root_powers = vec![1u64; degree];
+        let (mut power, mut inv_power) = (root, inv_root);
+        for i in 1..degree {
+            root_powers[bit_reverse(i)] = power;
+            inv_root_powers[bit_reverse(i - 1) + 1] = inv_power;
+            power = q.mul(power, root);
+            inv_power = q.mul(inv_power, inv_root);
+        }
+        Ok(Self {
+            q: q.clone(),
+            root_powers,
+            inv_root_powers,
+            degree_inv: q.inv(degree as u64).unwrap(),
+        })
+    }
+
+    /// Forward transform, with the Cooley-Tukey butterflies of SEAL.
+    fn forward(&self, a: &mut [u64]) {
+        let q = &self.q;
+        let n = a.len();
+        let mut gap = n >> 1;
+        let mut m = 1;
+        let mut root_index = 0;
+        while m < n {
+            for i in 0..m {
+                root_index += 1;
+                let r = self.root_powers[root_index];
+                let offset = 2 * i * gap;
+                for j in offset..offset + gap {
+                    let u = a[j];
+                    let v = q.mul(a[j + gap], r);
+                    a[j] = q.add(u, v);
+                    a[j + gap] = q.sub(u, v);
+                }
+            }
+            m <<= 1;
+            gap >>= 1;
+        }
+    }
+
+    /// Backward transform, with the Gentleman-Sande butterflies of SEAL.
+    fn backward(&self, a: &mut [u64]) {
+        let q = &self.q;
+        let n = a.len();
+        let mut gap = 1;
+        let mut m = n >> 1;
+        let mut root_index = 0;
+        while m >= 1 {
+            for i in 0..m {
+                root_index += 1;
+                let r = self.inv_root_powers[root_index];
+                let offset = 2 * i * gap;
+                for j in offset..offset + gap {
+                    let (u, v) = (a[j], a[j + gap]);
+                    a[j] = q.add(u, v);
+                    a[j + gap] = q.mul(q.sub(u, v), r);
+                }
+            }
+            m >>= 1;
+            gap <<= 1;
+        }
+        a.iter_mut().for_each(|c| *c = q.mul(*c, self.degree_inv));
+    }
+}
+
+/// Returns the smallest primitive `m`-th root of unity modulo `q`, for a power
+/// of two `m`, as SEAL.
+fn minimal_primitive_root(q: &Modulus, m: u64) -> Result<u64> {
+    if (**q - 1) % m != 0 {
+        return Err(Error::UnspecifiedInput(
+            "The moduli must be congruent to 1 modulo twice the degree".to_string(),
+        ));
+    }
+    // A root of unity of order m is primitive when its power m / 2 is -1.
+    let root = (2..**q)
+        .map(|x| q.pow(x, (**q - 1) / m))
+        .find(|root| q.pow(*root, m >> 1) == **q - 1)
+        .ok_or_else(|| Error::UnspecifiedInput("The moduli must be prime".to_string()))?;
+    let root_squared = q.mul(root, root);
+    let mut power = root;
+    let mut minimal = root;
+    for _ in 0..m >> 1 {
+        minimal = minimal.min(power);
+        power = q.mul(power, root_squared);
+    }
+    Ok(minimal)
+}
+
+/// Returns the polynomial in PowerBasis representation with the residues
+/// `coefficients` modulo the moduli of `ctx`, or an error if a coefficient is
+/// not reduced.
+fn poly_from_coefficients(coefficients: &[u64], ctx: &Arc<Context>) -> Result<Poly> {
+    let reduced = izip!(coefficients.chunks(ctx.degree()), ctx.moduli())
+        .all(|(residues, qi)| residues.iter().all(|c| c < qi));
+    if !reduced {
+        return Err(Error::DefaultError(
+            "A coefficient is not reduced modulo its modulus".to_string(),
+        ));
+    }
+    Ok(Poly::try_convert_from(
+        coefficients,
+        ctx,
+        false,
+        Representation::PowerBasis,
+    )?)
+}
+
+/// Reads a SEAL serialization header, and returns the size of the object
+/// including the header.
+fn read_header<R: Reader + ?Sized>(r: &mut R) -> Result<u64> {
+    let mut header = [0u8; 8];
+    r.read_exact(&mut header)?;
+    let size = read_u64(r)?;
+    if u16::from_le_bytes([header[0], header[1]]) != MAGIC
+        || header[2] as u64 != HEADER_SIZE
+        || header[3] != VERSION_MAJOR
+    {
+        return Err(Error::DefaultError(
+            "Invalid SEAL serialization header".to_string(),
+        ));
+    }
+    if header[5] != 0 {
+        return Err(Error::DefaultError(
+            "Compressed SEAL serializations are not supported".to_string(),
+        ));
+    }
+    Ok(size)
+}
+
+fn read_parms_id<R: Reader + ?Sized>(r: &mut R) -> Result<[u8; 32]> {
+    let mut parms_id = [0u8; 32];
+    r.read_exact(&mut parms_id)?;
+    Ok(parms_id)
+}
+
+/// Returns the size of the serialization of an array of `count` words.
+fn array_size(count: u64) -> u64 {
+    HEADER_SIZE + 8 + 8 * count
+}
+
+/// Reads an array of `count` words.
+fn read_array<R: Reader + ?Sized>(r: &mut R, count: u64) -> Result<Vec<u64>> {
+    let size = read_header(r)?;
+    if read_u64(r)? != count || size != array_size(count) {
+        return Err(Error::DefaultError(
+            "Unexpected number of coefficients; seeded SEAL serializations are not supported"
+                .to_string(),
+        ));
+    }
+    (0..count).map(|_| Ok(read_u64(r)?)).collect()
+}
+
+/// The BLAKE2b hash of `data`, of `length` bytes, as used by SEAL for the
+/// parameters identifiers.
+fn blake2b(data: &[u8], length: usize) -> Vec<u8> {
+    const IV: [u64; 8] = [
+        0x6a09e667f3bcc908,
+        0xbb67ae8584caa73b,
+        0x3c6ef372fe94f82b,
+        0xa54ff53a5f1d36f1,
+        0x510e527fade682d1,
+        0x9b05688c2b3e6c1f,
+        0x1f83d9abfb41bd6b,
+        0x5be0cd19137e2179,
+    ];
+    const SIGMA: [[usize; 16]; 10] = [
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+        [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+        [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+        [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+        [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+        [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+        [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+        [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+        [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    ];
+
+    let compress = |h: &mut [u64; 8], block: &[u8], counter: u128, last: bool| {
+        let m: [u64; 16] = core::array::from_fn(|i| {
+            u64::from_le_bytes(block[8 * i..8 * i + 8].try_into().unwrap())
+        });
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= counter as u64;
+        v[13] ^= (counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+        let mut g = |a: usize, b: usize, c: usize, d: usize, x: u64, y: u64| {
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(24);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(63);
+        };
+        for round in 0..12 {
+            let s = &SIGMA[round % 10];
+            g(0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+        for i in 0..8 {
+            h[i] ^= v[i] ^ v[i + 8];
+        }
+    };
+
+    let mut h = IV;
+    h[0] ^= 0x01010000 ^ length as u64;
+    let blocks = data.len().div_ceil(128).max(1);
+    for i in 0..blocks {
+        let chunk = &data[(128 * i).min(data.len())..(128 * (i + 1)).min(data.len())];
+        let mut block = [0u8; 128];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let counter = (128 * i + chunk.len()) as u128;
+        compress(&mut h, &block, counter, i == blocks - 1);
+    }
+    h.iter()
+        .flat_map(|hi| hi.to_le_bytes())
+        .take(length)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blake2b, minimal_primitive_root, SealContext, SealNtt, SealVersion};
+    use crate::bfv::{
+        BfvParameters, BfvParametersBuilder, Ciphertext, Encoding, Plaintext, SecretKey,
+    };
+    use crate::Error;
+    use alloc::sync::Arc;
+    use fhe_math::zq::{primes::generate_prime, Modulus};
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use itertools::{izip, Itertools};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    // Serializations written by SEAL 4.0.0 with `tests/data/seal/generate.cpp`.
+    const SEAL_SECRET_KEY: &[u8] = include_bytes!("../../tests/data/seal/secret_key.bin");
+    const SEAL_CIPHERTEXT: &[u8] = include_bytes!("../../tests/data/seal/ciphertext.bin");
+    const SEAL_GALOIS_KEYS: &[u8] = include_bytes!("../../tests/data/seal/galois_keys.bin");
+    const SEAL_ROTATED: &[u8] = include_bytes!("../../tests/data/seal/rotated.bin");
+
+    fn seal_parameters() -> Result<(Arc<BfvParameters>, u64), Error> {
+        let par = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1153)
+            .set_moduli_sizes(&[50, 50, 50])
+            .allow_insecure()
+            .build_arc()?;
+        let mut special = 1 << 60;
+        loop {
+            special = generate_prime(60, 32, special).unwrap();
+            if !par.moduli().contains(&special) {
+                return Ok((par, special));
+            }
+        }
+    }
+
+    /// Returns the context of the parameters of the SEAL serializations.
+    fn seal_vectors_context() -> Result<Arc<SealContext>, Error> {
+        let par = BfvParametersBuilder::new()
+            .set_degree(16)
+            .set_plaintext_modulus(1153)
+            .set_moduli(&[1125899906842177, 1125899906842273])
+            .allow_insecure()
+            .build_arc()?;
+        SealContext::new_with_version(&par, Some(1152921504606845473), SealVersion::V4_0)
+    }
+
+    /// Returns the SEAL Galois keys `bytes` with the coefficients of the key
+    /// switching ciphertexts, which are random, set to zero.
+    fn mask_galois_keys(ctx: &SealContext, bytes: &[u8]) -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        let key_size = ctx.key_size() as usize;
+        let coefficients_size = 16 * ctx.key_moduli_len() * ctx.par.degree();
+        let read_u64 = |bytes: &[u8], offset: usize| {
+            u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap()) as usize
+        };
+        let mut offset = 56;
+        for _ in 0..read_u64(&bytes, 48) {
+            let num_keys = read_u64(&bytes, offset);
+            offset += 8;
+            for _ in 0..num_keys {
+                bytes[offset + key_size - coefficients_size..offset + key_size].fill(0);
+                offset += key_size;
+            }
+        }
+        assert_eq!(offset, bytes.len());
+        bytes
+    }
+
+    #[test]
+    fn seal_vectors() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let ctx = seal_vectors_context()?;
+        // The identifier of the parameters of the first level in SEAL, i.e.
+        // `context.first_parms_id()`.
+        let parms_id = [
+            0xf7ad72e631afe59du64,
+            0x83e3e3b25fdc2704,
+            0x89eef5801d52e4f8,
+            0x348fe2371f4e54e7,
+        ];
+        assert_eq!(
+            ctx.parms_id(0)?.to_vec(),
+            parms_id.iter().flat_map(|w| w.to_le_bytes()).collect_vec()
+        );
+
+        let sk = ctx.read_secret_key(&mut &SEAL_SECRET_KEY[..])?;
+        let mut bytes = Vec::new();
+        ctx.write_secret_key(&sk, &mut bytes)?;
+        assert_eq!(bytes, SEAL_SECRET_KEY);
+
+        let ct = ctx.read_ciphertext(&mut &SEAL_CIPHERTEXT[..])?;
+        let pt = sk.try_decrypt(&ct)?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&pt, Encoding::poly())?,
+            (0..16).collect_vec()
+        );
+        bytes.clear();
+        ctx.write_ciphertext(&ct, &mut bytes)?;
+        assert_eq!(bytes, SEAL_CIPHERTEXT);
+
+        // SEAL's key switching is reproduced exactly.
+        let gk = ctx.read_galois_keys(&mut &SEAL_GALOIS_KEYS[..])?;
+        assert_eq!(gk.exponents(), [3, 31]);
+        let rotated = gk.apply_galois(&ct, 3)?;
+        bytes.clear();
+        ctx.write_ciphertext(&rotated, &mut bytes)?;
+        assert_eq!(bytes, SEAL_ROTATED);
+
+        // The exported Galois keys only differ from SEAL's by their random
+        // coefficients.
+        bytes.clear();
+        ctx.write_galois_keys(&sk, &[3, 31], &mut rng, &mut bytes)?;
+        assert_eq!(
+            mask_galois_keys(&ctx, &bytes),
+            mask_galois_keys(&ctx, SEAL_GALOIS_KEYS)
+        );
+        let rotated = ctx
+            .read_galois_keys(&mut &bytes[..])?
+            .apply_galois(&ct, 3)?;
+        assert_eq!(
+            sk.try_decrypt(&rotated)?,
+            sk.try_decrypt(&gk.apply_galois(&ct, 3)?)?
+        );
+
+        // By default, the headers are those of SEAL 4.1: only the minor
+        // versions of the object and of its array differ.
+        let ctx = SealContext::new(ctx.parameters(), Some(1152921504606845473))?;
+        assert_eq!(ctx.read_secret_key(&mut &SEAL_SECRET_KEY[..])?, sk);
+        bytes.clear();
+        ctx.write_secret_key(&sk, &mut bytes)?;
+        let differences = izip!(&bytes, SEAL_SECRET_KEY)
+            .positions(|(a, b)| a != b)
+            .collect_vec();
+        assert_eq!(differences, [4, 68]);
+        assert!(differences.iter().all(|i| bytes[*i] == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn blake2b_vectors() {
+        assert_eq!(
+            blake2b(b"abc", 64),
+            hex("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
+        );
+        assert_eq!(
+            blake2b(b"", 32),
+            hex("0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8")
+        );
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ntt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let q = Modulus::new(generate_prime(50, 32, 1 << 50).unwrap())?;
+        let ntt = SealNtt::new(&q, 16)?;
+        let root = minimal_primitive_root(&q, 32)?;
+        assert_eq!(ntt.root_powers[8], root);
+
+        let a = q.random_vec(16, &mut rng);
+        let mut b = a.clone();
+        ntt.forward(&mut b);
+        // The i-th output is the evaluation at root^(2 bitrev(i) + 1).
+        for (i, bi) in b.iter().enumerate() {
+            let x = q.pow(
+                root,
+                2 * ((i.reverse_bits() >> (usize::BITS - 4)) as u64) + 1,
+            );
+            let y = a.iter().rev().fold(0, |acc, c| q.add(q.mul(acc, x), *c));
+            assert_eq!(*bi, y);
+        }
+        ntt.backward(&mut b);
+        assert_eq!(a, b);
+        Ok(())
+    }
+
+    #[test]
+    fn ciphertext() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let (par, special) = seal_parameters()?;
+        let ctx = SealContext::new(&par, Some(special))?;
+        let sk = SecretKey::random(&par, &mut rng);
+        let v = par.plaintext.random_vec(par.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &par)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let mut ct_low = ct.clone();
+        ct_low.mod_switch_to_last_level()?;
+
+        for ct in [ct, &ct_low * &ct_low] {
+            let mut bytes = Vec::new();
+            ctx.write_ciphertext(&ct, &mut bytes)?;
+            assert_eq!(&bytes[..2], &[0x5e, 0xa1]);
+            assert_eq!(
+                u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+                bytes.len() as u64
+            );
+            assert_eq!(&bytes[16..48], &ctx.parms_id(ct.level())?);
+            assert_eq!(ctx.read_ciphertext(&mut &bytes[..])?, ct);
+
+            // Compressed serializations are rejected.
+            let mut compressed = bytes.clone();
+            compressed[5] = 2;
+            assert!(ctx.read_ciphertext(&mut &compressed[..]).is_err());
+            assert!(ctx.read_ciphertext(&mut &bytes[..bytes.len() - 1]).is_err());
+        }
+
+        let other = SealContext::new(&BfvParameters::default_arc(1, 16), None);
+        assert!(other.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn secret_key() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let (par, special) = seal_parameters()?;
+        let ctx = SealContext::new(&par, Some(special))?;
+        assert_ne!(ctx.key_parms_id(), ctx.parms_id(0)?);
+        let sk = SecretKey::random(&par, &mut rng);
+
+        let mut bytes = Vec::new();
+        ctx.write_secret_key(&sk, &mut bytes)?;
+        assert_eq!(&bytes[16..48], &ctx.key_parms_id());
+        assert_eq!(ctx.read_secret_key(&mut &bytes[..])?, sk);
+
+        // The residues must represent the same polynomial.
+        let last = bytes.len() - 8;
+        bytes[last] ^= 1;
+        assert!(ctx.read_secret_key(&mut &bytes[..]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn galois_keys() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let (par, special) = seal_parameters()?;
+        let ctx = SealContext::new(&par, Some(special))?;
+        let sk = SecretKey::random(&par, &mut rng);
+        let row_size = par.degree() >> 1;
+
+        let mut bytes = Vec::new();
+        ctx.write_galois_keys(&sk, &[3, 2 * par.degree() - 1], &mut rng, &mut bytes)?;
+        assert_eq!(
+            u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            bytes.len() as u64
+        );
+        let gk = ctx.read_galois_keys(&mut &bytes[..])?;
+        assert_eq!(gk.exponents(), [3, 2 * par.degree() - 1]);
+
+        let v = par.plaintext.random_vec(par.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &par)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let mut ct_low = ct.clone();
+        ct_low.mod_switch_to_next_level()?;
+        for ct in [ct, ct_low] {
+            let rotated = gk.apply_galois(&ct, 3)?;
+            let w = Vec::<u64>::try_decode(&sk.try_decrypt(&rotated)?, Encoding::simd())?;
+            for j in 0..row_size {
+                assert_eq!(w[j], v[(j + 1) % row_size]);
+                assert_eq!(w[row_size + j], v[row_size + (j + 1) % row_size]);
+            }
+
+            let swapped = gk.apply_galois(&ct, 2 * par.degree() - 1)?;
+            let w = Vec::<u64>::try_decode(&sk.try_decrypt(&swapped)?, Encoding::simd())?;
+            assert_eq!(w[..row_size], v[row_size..]);
+            assert_eq!(w[row_size..], v[..row_size]);
+
+            assert!(gk.apply_galois(&ct, 5).is_err());
+        }
+
+        assert!(ctx
+            .write_galois_keys(&sk, &[2], &mut rng, &mut Vec::new())
+            .is_err());
+        let ctx = SealContext::new(
+            &BfvParametersBuilder::new()
+                .set_degree(16)
+                .set_plaintext_modulus(1153)
+                .set_moduli_sizes(&[50])
+                .allow_insecure()
+                .build_arc()?,
+            None,
+        )?;
+        let sk = SecretKey::random(ctx.parameters(), &mut rng);
+        assert!(ctx
+            .write_galois_keys(&sk, &[3], &mut rng, &mut Vec::new())
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! Serde support for the BFV types, with the `serde` feature.
+//!
+//! The [`BfvParameters`] are serialized as their human-readable description.
+//! The other types are serialized as the [`ParametersId`] of their parameters
+//! followed by their protobuf serialization, and are deserialized with the
+//! seed of a [`ParametersRegistry`], which resolves the identifier to the
+//! registered `Arc<BfvParameters>`.
+
+use crate::bfv::{
+    BfvParameters, Ciphertext, EvaluationKey, KeySwitchingKey, Plaintext, PublicKey,
+    RelinearizationKey, SecretKey,
+};
+use crate::Error;
+use core::marker::PhantomData;
+use fhe_traits::{DeserializeParametrized, Serialize as _};
+use hashbrown::HashMap;
+use serde::de::{self, DeserializeSeed, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+extern crate alloc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// The identifier of BFV parameters, i.e. the SHA-256 hash of their protobuf
+/// serialization, as returned by [`BfvParameters::id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ParametersId(pub(crate) [u8; 32]);
+
+impl ParametersId {
+    /// Returns the bytes of the identifier.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// The serde representation of a type depending on BFV parameters.
+#[derive(Serialize, Deserialize)]
+struct Parametrized {
+    parameters: ParametersId,
+    bytes: Vec<u8>,
+}
+
+/// A set of BFV parameters indexed by their [`ParametersId`], from which the
+/// types depending on parameters are deserialized.
+///
+/// ```
+/// # use fhe::bfv::{BfvParameters, Ciphertext, Encoding, ParametersRegistry, Plaintext, SecretKey};
+/// # use fhe_traits::{FheEncoder, FheEncrypter};
+/// # use serde::de::DeserializeSeed;
+/// let par = BfvParameters::default_parameters_128(20)[0].clone();
+/// let mut registry = ParametersRegistry::new();
+/// registry.register(&par);
+///
+/// # let mut rng = rand::thread_rng();
+/// # let sk = SecretKey::random(&par, &mut rng);
+/// # let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &par).unwrap();
+/// let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng).unwrap();
+/// let json = serde_json::to_string(&ct).unwrap();
+/// let mut deserializer = serde_json::Deserializer::from_str(&json);
+/// let deserialized: Ciphertext = registry.seed().deserialize(&mut deserializer).unwrap();
+/// assert_eq!(deserialized, ct);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParametersRegistry {
+    parameters: HashMap<ParametersId, Arc<BfvParameters>>,
+}
+
+impl ParametersRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers parameters, and returns their identifier.
+    pub fn register(&mut self, par: &Arc<BfvParameters>) -> ParametersId {
+        let id = par.id();
+        self.parameters.insert(id, par.clone());
+        id
+    }
+
+    /// Returns the parameters registered with the identifier `id`, if any.
+    pub fn get(&self, id: &ParametersId) -> Option<&Arc<BfvParameters>> {
+        self.parameters.get(id)
+    }
+
+    /// Returns a seed deserializing a `T` whose parameters are registered.
+    pub fn seed<T>(&self) -> ParametrizedSeed<'_, T> {
+        ParametrizedSeed {
+            registry: self,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A [`DeserializeSeed`] for a type depending on BFV parameters, returned by
+/// [`ParametersRegistry::seed`].
+///
+/// The deserialization fails if the parameters of the value are not
+/// registered, or if the value is invalid for these parameters.
+#[derive(Debug)]
+pub struct ParametrizedSeed<'a, T> {
+    registry: &'a ParametersRegistry,
+    _phantom: PhantomData<T>,
+}
+
+impl<'de, T> DeserializeSeed<'de> for ParametrizedSeed<'_, T>
+where
+    T: DeserializeParametrized<Parameters = BfvParameters, Error = Error>,
+{
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        let mut value = Parametrized::deserialize(deserializer)?;
+        let out = match self.registry.get(&value.parameters) {
+            Some(par) => T::from_bytes(&value.bytes, par).map_err(de::Error::custom),
+            None => Err(de::Error::custom("Unknown BFV parameters")),
+        };
+        value.bytes.zeroize();
+        out
+    }
+}
+
+macro_rules! impl_serialize {
+    ($($ty:ty => $($par:ident).+),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let mut value = Parametrized {
+                        parameters: self.$($par).+.id(),
+                        bytes: self.to_bytes(),
+                    };
+                    let out = value.serialize(serializer);
+                    value.bytes.zeroize();
+                    out
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize!(
+    Ciphertext => par,
+    Plaintext => par,
+    SecretKey => par,
+    PublicKey => par,
+    EvaluationKey => par,
+    RelinearizationKey => ksk.par,
+    KeySwitchingKey => par,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::ParametersRegistry;
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKeyBuilder, Plaintext, PublicKey,
+        RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{DeserializeParametrized, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    use serde::de::DeserializeSeed;
+
+    fn round_trip<T>(value: &T, registry: &ParametersRegistry) -> Result<T, serde_json::Error>
+    where
+        T: serde::Serialize + DeserializeParametrized<Parameters = BfvParameters, Error = Error>,
+    {
+        let json = serde_json::to_string(value)?;
+        registry
+            .seed::<T>()
+            .deserialize(&mut serde_json::Deserializer::from_str(&json))
+    }
+
+    #[test]
+    fn serde() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(6, 16);
+        let json = serde_json::to_string(params.as_ref()).unwrap();
+        assert_eq!(
+            &serde_json::from_str::<BfvParameters>(&json).unwrap(),
+            params.as_ref()
+        );
+
+        let mut registry = ParametersRegistry::new();
+        let id = registry.register(&params);
+        assert_eq!(id, params.id());
+        assert_eq!(registry.get(&id), Some(&params));
+
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_inner_sum()?
+            .build(&mut rng)?;
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::simd(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        assert_eq!(round_trip(&sk, &registry).unwrap(), sk);
+        assert_eq!(round_trip(&pk, &registry).unwrap(), pk);
+        assert_eq!(round_trip(&rk, &registry).unwrap(), rk);
+        assert_eq!(round_trip(&ek, &registry).unwrap(), ek);
+        assert_eq!(round_trip(&pt, &registry).unwrap(), pt);
+        assert_eq!(round_trip(&ct, &registry).unwrap(), ct);
+
+        let other = BfvParameters::default_arc(6, 32);
+        assert_ne!(other.id(), params.id());
+        let ct: Ciphertext = SecretKey::random(&other, &mut rng).try_encrypt(
+            &Plaintext::try_encode(&[1u64], Encoding::poly(), &other)?,
+            &mut rng,
+        )?;
+        assert!(round_trip(&ct, &registry).is_err());
+        Ok(())
+    }
+}
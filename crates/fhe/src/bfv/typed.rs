@@ -0,0 +1,388 @@
+//! Objects tagged with their parameters at the type level.
+//!
+//! The objects of the BFV scheme carry their parameters at runtime, and mixing
+//! objects defined with different parameters is only detected when they
+//! interact. Applications that opt in can instead define a zero-sized token
+//! for each of their parameter sets by implementing [`ParametersToken`], and
+//! wrap their objects in [`Typed`]: combining objects tagged with different
+//! tokens is then a compile error. The parameters of an object are checked
+//! once, when it is tagged with [`Typed::new`].
+//!
+//! ```
+//! # use fhe::bfv::{BfvParameters, BfvParametersBuilder, Encoding, Plaintext, SecretKey};
+//! # use fhe::bfv::typed::{ParametersToken, Typed};
+//! # use fhe_traits::FheDecoder;
+//! # use std::sync::{Arc, OnceLock};
+//! struct Small;
+//!
+//! impl ParametersToken for Small {
+//!     fn parameters() -> Arc<BfvParameters> {
+//!         static PARAMETERS: OnceLock<Arc<BfvParameters>> = OnceLock::new();
+//!         PARAMETERS
+//!             .get_or_init(|| {
+//!                 BfvParametersBuilder::new()
+//!                     .set_degree(2048)
+//!                     .set_plaintext_modulus(1153)
+//!                     .set_moduli_sizes(&[54])
+//!                     .build_arc()
+//!                     .unwrap()
+//!             })
+//!             .clone()
+//!     }
+//! }
+//!
+//! # fn main() -> fhe::Result<()> {
+//! let mut rng = rand::thread_rng();
+//! let sk = Typed::<SecretKey, Small>::random(&mut rng);
+//! let pt = Typed::<Plaintext, Small>::try_encode(&[1u64, 2, 3], Encoding::poly())?;
+//! let ct = sk.try_encrypt(&pt, &mut rng)?;
+//! let ct = &(&ct + &ct) * &pt;
+//! let decrypted = sk.try_decrypt(&ct)?;
+//! assert_eq!(
+//!     Vec::<u64>::try_decode(&decrypted, Encoding::poly())?[..5],
+//!     [2, 8, 20, 24, 18]
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bfv::{
+    BfvParameters, Ciphertext, Encoding, EvaluationKey, Multiplicator, Plaintext, PublicKey,
+    RelinearizationKey, SecretKey,
+};
+use crate::{Error, Result};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Deref, Mul, MulAssign, Neg, Sub, SubAssign};
+use fhe_traits::{FheDecrypter, FheEncoder, FheEncrypter};
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+
+/// A zero-sized token identifying a parameter set at the type level.
+pub trait ParametersToken: 'static {
+    /// Returns the parameters identified by this token. All the calls must
+    /// return the same parameters.
+    fn parameters() -> Arc<BfvParameters>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The objects of the BFV scheme which can be tagged with a
+/// [`ParametersToken`].
+pub trait Parametrized: sealed::Sealed {
+    /// Returns the parameters of the object.
+    fn parameters(&self) -> &Arc<BfvParameters>;
+}
+
+macro_rules! impl_parametrized {
+    ($($t:ty => |$x:ident| $par:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Parametrized for $t {
+                fn parameters(&self) -> &Arc<BfvParameters> {
+                    let $x = self;
+                    &$par
+                }
+            }
+        )*
+    };
+}
+
+impl_parametrized!(
+    Ciphertext => |x| x.par,
+    Plaintext => |x| x.par,
+    SecretKey => |x| x.par,
+    PublicKey => |x| x.par,
+    RelinearizationKey => |x| x.ksk.par,
+    EvaluationKey => |x| x.par,
+    Multiplicator => |x| x.par,
+);
+
+/// An object whose parameters are those identified by the token `P`.
+///
+/// A [`Typed`] object dereferences to the underlying object, so that all the
+/// methods which do not combine objects remain available.
+///
+/// Objects tagged with different tokens cannot be combined:
+///
+/// ```compile_fail
+/// # use fhe::bfv::{typed::Typed, Ciphertext};
+/// fn add<A, B>(a: &Typed<Ciphertext, A>, b: &Typed<Ciphertext, B>) {
+///     let _ = a + b;
+/// }
+/// ```
+pub struct Typed<T, P> {
+    value: T,
+    token: PhantomData<fn() -> P>,
+}
+
+impl<T: Parametrized, P: ParametersToken> Typed<T, P> {
+    /// Tags an object with the token `P`. Returns an error if the parameters of
+    /// the object are not those identified by `P`.
+    pub fn new(value: T) -> Result<Self> {
+        if value.parameters() != &P::parameters() {
+            return Err(Error::DefaultError(
+                "The parameters do not match the token".to_string(),
+            ));
+        }
+        Ok(Self::new_unchecked(value))
+    }
+}
+
+impl<T, P> Typed<T, P> {
+    fn new_unchecked(value: T) -> Self {
+        Self {
+            value,
+            token: PhantomData,
+        }
+    }
+
+    /// Returns the underlying object.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, P> Deref for Typed<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Clone, P> Clone for Typed<T, P> {
+    fn clone(&self) -> Self {
+        Self::new_unchecked(self.value.clone())
+    }
+}
+
+impl<T: Debug, P> Debug for Typed<T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: PartialEq, P> PartialEq for Typed<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, P> Eq for Typed<T, P> {}
+
+impl<P: ParametersToken> Typed<SecretKey, P> {
+    /// Generates a random secret key.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::new_unchecked(SecretKey::random(&P::parameters(), rng))
+    }
+
+    /// Generates a public key.
+    pub fn public_key<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Typed<PublicKey, P> {
+        Typed::new_unchecked(PublicKey::new(&self.value, rng))
+    }
+
+    /// Generates a relinearization key.
+    pub fn relinearization_key<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<Typed<RelinearizationKey, P>> {
+        RelinearizationKey::new(&self.value, rng).map(Typed::new_unchecked)
+    }
+
+    /// Encrypts a plaintext.
+    pub fn try_encrypt<R: RngCore + CryptoRng>(
+        &self,
+        pt: &Typed<Plaintext, P>,
+        rng: &mut R,
+    ) -> Result<Typed<Ciphertext, P>> {
+        self.value
+            .try_encrypt(&pt.value, rng)
+            .map(Typed::new_unchecked)
+    }
+
+    /// Decrypts a ciphertext.
+    pub fn try_decrypt(&self, ct: &Typed<Ciphertext, P>) -> Result<Typed<Plaintext, P>> {
+        self.value.try_decrypt(&ct.value).map(Typed::new_unchecked)
+    }
+}
+
+impl<P: ParametersToken> Typed<PublicKey, P> {
+    /// Encrypts a plaintext.
+    pub fn try_encrypt<R: RngCore + CryptoRng>(
+        &self,
+        pt: &Typed<Plaintext, P>,
+        rng: &mut R,
+    ) -> Result<Typed<Ciphertext, P>> {
+        self.value
+            .try_encrypt(&pt.value, rng)
+            .map(Typed::new_unchecked)
+    }
+}
+
+impl<P: ParametersToken> Typed<Plaintext, P> {
+    /// Encodes a value.
+    pub fn try_encode<'a, V>(value: &'a V, encoding: Encoding) -> Result<Self>
+    where
+        V: ?Sized,
+        Plaintext: FheEncoder<&'a V, Error = Error>,
+    {
+        Plaintext::try_encode(value, encoding, &P::parameters()).map(Self::new_unchecked)
+    }
+}
+
+impl<P: ParametersToken> Typed<RelinearizationKey, P> {
+    /// Relinearizes a ciphertext.
+    pub fn relinearizes(&self, ct: &mut Typed<Ciphertext, P>) -> Result<()> {
+        self.value.relinearizes(&mut ct.value)
+    }
+}
+
+impl<P: ParametersToken> Typed<Multiplicator, P> {
+    /// Multiplies two ciphertexts.
+    pub fn multiply(
+        &self,
+        lhs: &Typed<Ciphertext, P>,
+        rhs: &Typed<Ciphertext, P>,
+    ) -> Result<Typed<Ciphertext, P>> {
+        self.value
+            .multiply(&lhs.value, &rhs.value)
+            .map(Typed::new_unchecked)
+    }
+}
+
+impl<P: ParametersToken> Typed<EvaluationKey, P> {
+    /// Rotates the rows of a ciphertext.
+    pub fn rotates_rows(&self, ct: &Typed<Ciphertext, P>) -> Result<Typed<Ciphertext, P>> {
+        self.value.rotates_rows(&ct.value).map(Typed::new_unchecked)
+    }
+
+    /// Rotates the columns of a ciphertext by `i`.
+    pub fn rotates_columns_by(
+        &self,
+        ct: &Typed<Ciphertext, P>,
+        i: usize,
+    ) -> Result<Typed<Ciphertext, P>> {
+        self.value
+            .rotates_columns_by(&ct.value, i)
+            .map(Typed::new_unchecked)
+    }
+
+    /// Computes the inner sum of a ciphertext.
+    pub fn computes_inner_sum(&self, ct: &Typed<Ciphertext, P>) -> Result<Typed<Ciphertext, P>> {
+        self.value
+            .computes_inner_sum(&ct.value)
+            .map(Typed::new_unchecked)
+    }
+}
+
+macro_rules! impl_typed_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $rhs:ty) => {
+        impl<P> $assign_trait<&Typed<$rhs, P>> for Typed<Ciphertext, P> {
+            fn $assign_method(&mut self, rhs: &Typed<$rhs, P>) {
+                self.value.$assign_method(&rhs.value)
+            }
+        }
+
+        impl<P> $trait<&Typed<$rhs, P>> for &Typed<Ciphertext, P> {
+            type Output = Typed<Ciphertext, P>;
+
+            fn $method(self, rhs: &Typed<$rhs, P>) -> Typed<Ciphertext, P> {
+                Typed::new_unchecked((&self.value).$method(&rhs.value))
+            }
+        }
+    };
+}
+
+impl_typed_op!(Add, add, AddAssign, add_assign, Ciphertext);
+impl_typed_op!(Add, add, AddAssign, add_assign, Plaintext);
+impl_typed_op!(Sub, sub, SubAssign, sub_assign, Ciphertext);
+impl_typed_op!(Sub, sub, SubAssign, sub_assign, Plaintext);
+impl_typed_op!(Mul, mul, MulAssign, mul_assign, Plaintext);
+
+impl<P> Mul<&Typed<Ciphertext, P>> for &Typed<Ciphertext, P> {
+    type Output = Typed<Ciphertext, P>;
+
+    fn mul(self, rhs: &Typed<Ciphertext, P>) -> Typed<Ciphertext, P> {
+        Typed::new_unchecked(&self.value * &rhs.value)
+    }
+}
+
+impl<P> Neg for &Typed<Ciphertext, P> {
+    type Output = Typed<Ciphertext, P>;
+
+    fn neg(self) -> Typed<Ciphertext, P> {
+        Typed::new_unchecked(-&self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParametersToken, Typed};
+    use crate::bfv::{BfvParameters, Ciphertext, Encoding, Multiplicator, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    struct Token;
+
+    impl ParametersToken for Token {
+        fn parameters() -> Arc<BfvParameters> {
+            // The default parameters are deterministic, so that all the calls
+            // return equal parameters.
+            BfvParameters::default_arc(2, 16)
+        }
+    }
+
+    #[test]
+    fn typed() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = Token::parameters();
+        let sk = Typed::<SecretKey, Token>::random(&mut rng);
+        let pk = sk.public_key(&mut rng);
+        let rk = sk.relinearization_key(&mut rng)?;
+        let multiplicator = Typed::<_, Token>::new(Multiplicator::default(&rk)?)?;
+
+        let a = params.plaintext.random_vec(params.degree(), &mut rng);
+        let b = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt_a = Typed::<Plaintext, Token>::try_encode(&a, Encoding::simd())?;
+        let pt_b = Typed::<Plaintext, Token>::try_encode(&b, Encoding::simd())?;
+        let ct_a = sk.try_encrypt(&pt_a, &mut rng)?;
+        let ct_b = pk.try_encrypt(&pt_b, &mut rng)?;
+
+        let mut expected = a.clone();
+        params.plaintext.add_vec(&mut expected, &b);
+        let pt = sk.try_decrypt(&(&ct_a + &ct_b))?;
+        assert_eq!(Vec::<u64>::try_decode(&*pt, Encoding::simd())?, expected);
+
+        let mut expected = a.clone();
+        params.plaintext.mul_vec(&mut expected, &b);
+        let mut ct = &ct_a * &ct_b;
+        rk.relinearizes(&mut ct)?;
+        assert_eq!(ct.len(), 2);
+        for ct in [ct, multiplicator.multiply(&ct_a, &ct_b)?, &ct_a * &pt_b] {
+            let pt = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&*pt, Encoding::simd())?, expected);
+        }
+
+        // Tagging checks the parameters.
+        let other = BfvParameters::default_arc(1, 16);
+        let sk_other = SecretKey::random(&other, &mut rng);
+        let ct_other: Ciphertext = sk_other.try_encrypt(
+            &Plaintext::try_encode(&a, Encoding::simd(), &other)?,
+            &mut rng,
+        )?;
+        assert!(Typed::<_, Token>::new(ct_other).is_err());
+        assert!(Typed::<_, Token>::new(sk_other).is_err());
+        assert_eq!(Typed::<_, Token>::new(ct_a.clone().into_inner())?, ct_a);
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+//! Ciphertext type in the BGV encryption scheme.
+
+extern crate alloc;
+
+use crate::bfv::BfvParameters;
+use crate::proto::bgv::Ciphertext as CiphertextProto;
+use crate::{Error, Result};
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
+use fhe_traits::{
+    DeserializeParametrized, DeserializeWithContext, FheCiphertext, FheParametrized, Serialize,
+};
+use itertools::izip;
+use ndarray::Array2;
+use prost::Message;
+
+/// A ciphertext encrypting a plaintext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext {
+    /// The parameters of the underlying BFV encryption scheme.
+    pub(crate) par: Arc<BfvParameters>,
+
+    /// The ciphertext elements.
+    pub(crate) c: Vec<Poly>,
+
+    /// The ciphertext level
+    pub(crate) level: usize,
+
+    /// The factor by which the plaintext is multiplied modulo the plaintext
+    /// modulus, which is modified by the modulus switching.
+    pub(crate) correction_factor: u64,
+}
+
+impl Deref for Ciphertext {
+    type Target = [Poly];
+
+    fn deref(&self) -> &Self::Target {
+        &self.c
+    }
+}
+
+impl DerefMut for Ciphertext {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.c
+    }
+}
+
+impl Ciphertext {
+    /// Returns the level of this ciphertext.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the factor by which the plaintext is multiplied modulo the
+    /// plaintext modulus in this ciphertext.
+    ///
+    /// The factor of a fresh ciphertext is `1`; switching to the next level
+    /// multiplies it by the inverse of the dropped modulus, and multiplying two
+    /// ciphertexts multiplies their factors. It is removed at decryption.
+    pub fn correction_factor(&self) -> u64 {
+        self.correction_factor
+    }
+
+    /// Switches the ciphertext to the next level, by dividing it by the last
+    /// modulus of its context while keeping the message modulo the plaintext
+    /// modulus, which also divides the noise by this modulus.
+    ///
+    /// Returns an error if the ciphertext is at the maximum level.
+    pub fn mod_switch_to_next_level(&mut self) -> Result<()> {
+        if self.level == self.par.max_level() {
+            return Err(Error::DefaultError(
+                "Cannot switch a ciphertext at the maximum level".to_string(),
+            ));
+        }
+        let t = &self.par.plaintext;
+        let q_last = *self.c[0].ctx().moduli().last().unwrap();
+        let q_last_inv_t = t.inv(t.reduce(q_last)).ok_or_else(|| {
+            Error::DefaultError(
+                "The ciphertext moduli must be invertible modulo the plaintext modulus".to_string(),
+            )
+        })?;
+        for ci in self.c.iter_mut() {
+            *ci = mod_switch_poly(&self.par, ci, self.level, q_last_inv_t)?;
+        }
+        self.correction_factor = t.mul(self.correction_factor, q_last_inv_t);
+        self.level += 1;
+        Ok(())
+    }
+
+    /// Switches the ciphertext down to the given level.
+    ///
+    /// Returns an error if the level is lower than the level of the ciphertext
+    /// or higher than the maximum level.
+    pub fn mod_switch_to_level(&mut self, level: usize) -> Result<()> {
+        if level < self.level || level > self.par.max_level() {
+            return Err(Error::DefaultError("Invalid level".to_string()));
+        }
+        while self.level < level {
+            self.mod_switch_to_next_level()?;
+        }
+        Ok(())
+    }
+}
+
+/// Switches a polynomial in Ntt representation at `level` to the next level,
+/// as `(p - δ) / q_last`, where `δ` is the smallest polynomial equal to `p`
+/// modulo the last modulus `q_last` and to zero modulo the plaintext modulus.
+fn mod_switch_poly(par: &BfvParameters, p: &Poly, level: usize, q_last_inv_t: u64) -> Result<Poly> {
+    let next_ctx = par.ctx_at_level(level + 1)?;
+    let t = &par.plaintext;
+    let moduli = p.ctx().moduli_operators();
+    let (q_last, moduli) = moduli.split_last().unwrap();
+    let q_last_inv = moduli
+        .iter()
+        .map(|qi| qi.inv(qi.reduce(**q_last)).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut p = p.clone();
+    p.change_representation(Representation::PowerBasis);
+    let coefficients = p.coefficients();
+    let last_row = coefficients.row(moduli.len());
+
+    let mut out = Array2::zeros((moduli.len(), coefficients.ncols()));
+    for (j, c_last) in last_row.iter().enumerate() {
+        // δ = r + q_last * k, where r is the centered representative of the
+        // coefficient modulo q_last, and k = -r / q_last modulo t is centered.
+        let r = if *c_last > **q_last >> 1 {
+            *c_last as i64 - **q_last as i64
+        } else {
+            *c_last as i64
+        };
+        let r_t = if r < 0 {
+            t.neg(t.reduce(r.unsigned_abs()))
+        } else {
+            t.reduce(r as u64)
+        };
+        let k = t.neg(t.mul(r_t, q_last_inv_t));
+        let k = if k > **t >> 1 {
+            k as i64 - **t as i64
+        } else {
+            k as i64
+        };
+        let delta = r as i128 + (**q_last as i128) * (k as i128);
+
+        for (i, qi, qi_inv) in izip!(0..moduli.len(), moduli, &q_last_inv) {
+            let delta_i = qi.reduce_u128(delta.unsigned_abs());
+            let delta_i = if delta < 0 { qi.neg(delta_i) } else { delta_i };
+            out[(i, j)] = qi.mul(qi.sub(coefficients[(i, j)], delta_i), *qi_inv);
+        }
+    }
+
+    let mut out = Poly::try_convert_from(out, next_ctx, true, Representation::PowerBasis)?;
+    out.change_representation(Representation::Ntt);
+    Ok(out)
+}
+
+impl FheParametrized for Ciphertext {
+    type Parameters = BfvParameters;
+}
+
+impl FheCiphertext for Ciphertext {}
+
+impl Serialize for Ciphertext {
+    fn to_bytes(&self) -> Vec<u8> {
+        CiphertextProto::from(self).encode_to_vec()
+    }
+}
+
+impl DeserializeParametrized for Ciphertext {
+    fn from_bytes(bytes: &[u8], par: &Arc<BfvParameters>) -> Result<Self> {
+        if let Ok(ctp) = Message::decode(bytes) {
+            Ciphertext::try_from_proto(&ctp, par)
+        } else {
+            Err(Error::SerializationError)
+        }
+    }
+
+    type Error = Error;
+}
+
+/// Conversions from and to protobuf.
+impl From<&Ciphertext> for CiphertextProto {
+    fn from(ct: &Ciphertext) -> Self {
+        CiphertextProto {
+            c: ct.c.iter().map(|ci| ci.to_bytes()).collect(),
+            level: ct.level as u32,
+            correction_factor: ct.correction_factor,
+        }
+    }
+}
+
+impl Ciphertext {
+    fn try_from_proto(value: &CiphertextProto, par: &Arc<BfvParameters>) -> Result<Self> {
+        if value.c.len() < 2 {
+            return Err(Error::DefaultError("Not enough polynomials".to_string()));
+        }
+        if value.level as usize > par.max_level() {
+            return Err(Error::DefaultError("Invalid level".to_string()));
+        }
+        let t = &par.plaintext;
+        if value.correction_factor >= **t || t.inv(value.correction_factor).is_none() {
+            return Err(Error::DefaultError("Invalid correction factor".to_string()));
+        }
+
+        let ctx = par.ctx_at_level(value.level as usize)?;
+        let c = value
+            .c
+            .iter()
+            .map(|ci| Poly::from_bytes(ci, ctx))
+            .collect::<fhe_math::Result<Vec<_>>>()?;
+        if c.iter()
+            .any(|ci| ci.representation() != &Representation::Ntt)
+        {
+            return Err(Error::DefaultError("Incorrect representation".to_string()));
+        }
+
+        Ok(Ciphertext {
+            par: par.clone(),
+            c,
+            level: value.level as usize,
+            correction_factor: value.correction_factor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{BfvParameters, Encoding, Plaintext};
+    use crate::bgv::{Ciphertext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{
+        DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, Serialize,
+    };
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn serialize() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::poly(), &params)?;
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        for _ in 0..=params.max_level() {
+            let bytes = ct.to_bytes();
+            assert_eq!(Ciphertext::from_bytes(&bytes, &params)?, ct);
+            if ct.level() < params.max_level() {
+                ct.mod_switch_to_next_level()?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mod_switch() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let v = params.plaintext.random_vec(params.degree(), &mut rng);
+        let pt = Plaintext::try_encode(&v, Encoding::simd(), &params)?;
+
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert_eq!(ct.correction_factor(), 1);
+        for level in 1..=params.max_level() {
+            ct.mod_switch_to_next_level()?;
+            assert_eq!(ct.level(), level);
+            assert_eq!(ct[0].ctx(), params.ctx_at_level(level)?);
+            let pt2 = sk.try_decrypt(&ct)?;
+            assert_eq!(Vec::<u64>::try_decode(&pt2, Encoding::simd())?, v);
+        }
+        assert!(ct.mod_switch_to_next_level().is_err());
+
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        ct.mod_switch_to_level(params.max_level())?;
+        assert_eq!(ct.level(), params.max_level());
+        assert!(ct.mod_switch_to_level(0).is_err());
+        let pt2 = sk.try_decrypt(&ct)?;
+        assert_eq!(Vec::<u64>::try_decode(&pt2, Encoding::simd())?, v);
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+//! Keys for the BGV encryption scheme
+
+mod public_key;
+mod relinearization_key;
+mod secret_key;
+
+pub use public_key::PublicKey;
+pub use relinearization_key::RelinearizationKey;
+pub use secret_key::SecretKey;
+
+use crate::Result;
+use alloc::sync::Arc;
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly};
+use ndarray::s;
+extern crate alloc;
+
+/// Drops the last moduli of a polynomial defined modulo the moduli of level 0,
+/// so that it is defined in the context `ctx` of a higher level, without
+/// changing its representation.
+pub(crate) fn drop_last_moduli(p: &Poly, ctx: &Arc<Context>) -> Result<Poly> {
+    let coefficients = p.coefficients();
+    Ok(Poly::try_convert_from(
+        coefficients.slice(s![..ctx.moduli().len(), ..]).to_owned(),
+        ctx,
+        false,
+        p.representation().clone(),
+    )?)
+}
@@ -0,0 +1,135 @@
+//! Public keys for the BGV encryption scheme
+
+use crate::bfv::{BfvParameters, Plaintext};
+use crate::bgv::Ciphertext;
+use crate::{Error, Result};
+use fhe_math::rq::{Poly, Representation};
+use fhe_traits::{FheEncrypter, FheParametrized};
+use rand::RngCore;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use zeroize::Zeroizing;
+
+use super::{drop_last_moduli, SecretKey};
+
+/// Public key for the BGV encryption scheme.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PublicKey {
+    pub(crate) par: Arc<BfvParameters>,
+    pub(crate) c: Ciphertext,
+}
+
+impl PublicKey {
+    /// Generate a new [`PublicKey`] from a [`SecretKey`].
+    pub fn new<R: RngCore>(sk: &SecretKey, rng: &mut R) -> Self {
+        let zero = Zeroizing::new(Poly::zero(&sk.par.ctx[0], Representation::Ntt));
+        let mut c = sk.encrypt_poly(zero.as_ref(), rng).unwrap();
+        // The polynomials of a public key should not allow for variable time
+        // computation.
+        c.iter_mut()
+            .for_each(|p| p.disallow_variable_time_computations());
+        Self {
+            par: sk.par.clone(),
+            c,
+        }
+    }
+}
+
+impl FheParametrized for PublicKey {
+    type Parameters = BfvParameters;
+}
+
+impl FheEncrypter<Plaintext, Ciphertext> for PublicKey {
+    type Error = Error;
+
+    fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
+        if self.par != pt.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+
+        // The public key remains an encryption of zero modulo the moduli of
+        // the plaintext level.
+        let ctx = self.par.ctx_at_level(pt.level)?;
+        let b = drop_last_moduli(&self.c[0], ctx)?;
+        let a = drop_last_moduli(&self.c[1], ctx)?;
+        let t = self.par.plaintext();
+
+        let u = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+        let mut e1 = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+        let mut e2 = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+        e1.multiply_scalar(t);
+        e2.multiply_scalar(t);
+
+        let mut c0 = u.as_ref() * &b;
+        c0 += &e1;
+        c0 += &pt.poly_ntt;
+        let mut c1 = u.as_ref() * &a;
+        c1 += &e2;
+
+        // It is now safe to enable variable time computations.
+        unsafe {
+            c0.allow_variable_time_computations();
+            c1.allow_variable_time_computations()
+        }
+
+        Ok(Ciphertext {
+            par: self.par.clone(),
+            c: vec![c0, c1],
+            level: pt.level,
+            correction_factor: 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PublicKey;
+    use crate::bfv::{BfvParameters, Encoding, Plaintext};
+    use crate::bgv::SecretKey;
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn encrypt_decrypt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            for level in 0..=params.max_level() {
+                let v = params.plaintext.random_vec(params.degree(), &mut rng);
+                let pt = Plaintext::try_encode(&v, Encoding::simd_at_level(level), &params)?;
+                let ct = pk.try_encrypt(&pt, &mut rng)?;
+                assert_eq!(ct.level(), level);
+
+                let pt2 = sk.try_decrypt(&ct)?;
+                assert_eq!(Vec::<u64>::try_decode(&pt2, Encoding::simd())?, v);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! Relinearization keys for the BGV encryption scheme
+
+use crate::bfv::BfvParameters;
+use crate::bgv::{Ciphertext, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
+use fhe_traits::FheParametrized;
+use itertools::izip;
+use ndarray::Array2;
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+use super::drop_last_moduli;
+
+/// Relinearization key for the BGV encryption scheme.
+///
+/// The key is defined modulo the ciphertext moduli of level 0, and encrypts
+/// `δ_i * s^2` under `s` for each modulus `q_i`, where `δ_i` is `1` modulo
+/// `q_i` and `0` modulo the other moduli, with a noise multiple of the
+/// plaintext modulus. Since these relations also hold modulo the moduli of any
+/// level, the key relinearizes ciphertexts at every level.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RelinearizationKey {
+    pub(crate) par: Arc<BfvParameters>,
+    pub(crate) c0: Box<[Poly]>,
+    pub(crate) c1: Box<[Poly]>,
+}
+
+impl RelinearizationKey {
+    /// Generate a [`RelinearizationKey`] from a [`SecretKey`].
+    pub fn new<R: RngCore + CryptoRng>(sk: &SecretKey, rng: &mut R) -> Result<Self> {
+        let ctx = &sk.par.ctx[0];
+        let s = sk.s_ntt(ctx)?;
+        let mut s2 = Zeroizing::new(s.as_ref() * s.as_ref());
+        s2.change_representation(Representation::PowerBasis);
+        let coefficients = s2.coefficients();
+
+        let mut c0 = Vec::with_capacity(ctx.moduli().len());
+        let mut c1 = Vec::with_capacity(ctx.moduli().len());
+        for i in 0..ctx.moduli().len() {
+            // δ_i * s^2 is s^2 modulo q_i, and 0 modulo the others.
+            let mut gadget = Array2::zeros((ctx.moduli().len(), ctx.degree()));
+            gadget.row_mut(i).assign(&coefficients.row(i));
+            let mut gadget = Zeroizing::new(Poly::try_convert_from(
+                gadget,
+                ctx,
+                false,
+                Representation::PowerBasis,
+            )?);
+            gadget.change_representation(Representation::Ntt);
+
+            let mut ct = sk.encrypt_poly(gadget.as_ref(), rng)?;
+            let mut a = ct.c.pop().unwrap();
+            let mut b = ct.c.pop().unwrap();
+            a.change_representation(Representation::NttShoup);
+            b.change_representation(Representation::NttShoup);
+            c0.push(b);
+            c1.push(a);
+        }
+
+        Ok(Self {
+            par: sk.par.clone(),
+            c0: c0.into_boxed_slice(),
+            c1: c1.into_boxed_slice(),
+        })
+    }
+
+    /// Relinearize an "extended" ciphertext (c0, c1, c2) into a [`Ciphertext`]
+    /// (c0', c1') encrypting the same plaintext, at the same level.
+    ///
+    /// The noise added by the relinearization is about the largest modulus
+    /// times the plaintext modulus, so that the ciphertext is usually switched
+    /// to the next level afterwards.
+    pub fn relinearizes(&self, ct: &mut Ciphertext) -> Result<()> {
+        if ct.len() != 3 {
+            return Err(Error::DefaultError(
+                "Only supports relinearization of ciphertext with 3 parts".to_string(),
+            ));
+        } else if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+
+        let ctx = self.par.ctx_at_level(ct.level)?;
+        let mut c2 = ct.c.pop().unwrap();
+        c2.change_representation(Representation::PowerBasis);
+
+        let mut c0 = Poly::zero(ctx, Representation::Ntt);
+        let mut c1 = Poly::zero(ctx, Representation::Ntt);
+        for (c2_i_coefficients, key_c0_i, key_c1_i) in izip!(
+            c2.coefficients().outer_iter(),
+            self.c0.iter(),
+            self.c1.iter()
+        ) {
+            let restricted;
+            let (key_c0_i, key_c1_i) = if ct.level == 0 {
+                (key_c0_i, key_c1_i)
+            } else {
+                restricted = (
+                    drop_last_moduli(key_c0_i, ctx)?,
+                    drop_last_moduli(key_c1_i, ctx)?,
+                );
+                (&restricted.0, &restricted.1)
+            };
+            let mut c2_i = unsafe {
+                Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
+                    c2_i_coefficients.as_slice().unwrap(),
+                    ctx,
+                )
+            };
+            c0 += &(&c2_i * key_c0_i);
+            c2_i *= key_c1_i;
+            c1 += &c2_i;
+        }
+        unsafe {
+            c0.allow_variable_time_computations();
+            c1.allow_variable_time_computations();
+        }
+
+        ct[0] += &c0;
+        ct[1] += &c1;
+        Ok(())
+    }
+}
+
+impl FheParametrized for RelinearizationKey {
+    type Parameters = BfvParameters;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RelinearizationKey;
+    use crate::bfv::{BfvParameters, Encoding, Plaintext};
+    use crate::bgv::{Ciphertext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn relinearization() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(4, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        for level in 0..params.max_level() {
+            let a = params.plaintext.random_vec(params.degree(), &mut rng);
+            let b = params.plaintext.random_vec(params.degree(), &mut rng);
+            let mut expected = a.clone();
+            params.plaintext.mul_vec(&mut expected, &b);
+
+            let encoding = Encoding::simd_at_level(level);
+            let ct_a: Ciphertext = sk.try_encrypt(
+                &Plaintext::try_encode(&a, encoding.clone(), &params)?,
+                &mut rng,
+            )?;
+            let ct_b: Ciphertext =
+                sk.try_encrypt(&Plaintext::try_encode(&b, encoding, &params)?, &mut rng)?;
+
+            let mut ct = &ct_a * &ct_b;
+            let decrypted = sk.try_decrypt(&ct)?;
+            rk.relinearizes(&mut ct)?;
+            assert_eq!(ct.len(), 2);
+            assert_eq!(ct.level(), level);
+            assert_eq!(sk.try_decrypt(&ct)?, decrypted);
+            assert_eq!(
+                Vec::<u64>::try_decode(&sk.try_decrypt(&ct)?, Encoding::simd())?,
+                expected
+            );
+        }
+        Ok(())
+    }
+}
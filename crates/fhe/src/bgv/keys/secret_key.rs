@@ -0,0 +1,209 @@
+//! Secret keys for the BGV encryption scheme
+
+use crate::bfv::{BfvParameters, Plaintext, Sparsity};
+use crate::bgv::Ciphertext;
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
+use fhe_traits::{FheDecrypter, FheEncrypter, FheParametrized};
+use fhe_util::sample_vec_cbd;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+use zeroize_derive::{Zeroize, ZeroizeOnDrop};
+
+/// Secret key for the BGV encryption scheme.
+#[derive(Debug, PartialEq, Eq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SecretKey {
+    #[zeroize(skip)]
+    pub(crate) par: Arc<BfvParameters>,
+    pub(crate) coeffs: Box<[i64]>,
+}
+
+impl SecretKey {
+    /// Generate a random [`SecretKey`].
+    pub fn random<R: RngCore>(par: &Arc<BfvParameters>, rng: &mut R) -> Self {
+        let s_coefficients = sample_vec_cbd(par.degree(), par.variance, rng).unwrap();
+        Self {
+            par: par.clone(),
+            coeffs: s_coefficients.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the secret key in Ntt representation in the context `ctx`.
+    pub(crate) fn s_ntt(&self, ctx: &Arc<Context>) -> Result<Zeroizing<Poly>> {
+        let mut s = Zeroizing::new(Poly::try_convert_from(
+            self.coeffs.as_ref(),
+            ctx,
+            false,
+            Representation::PowerBasis,
+        )?);
+        s.change_representation(Representation::Ntt);
+        Ok(s)
+    }
+
+    /// Encrypts a polynomial in Ntt representation, at the level of its
+    /// context, as `(-a*s + t*e + p, a)`.
+    pub(crate) fn encrypt_poly<R: RngCore>(&self, p: &Poly, rng: &mut R) -> Result<Ciphertext> {
+        assert_eq!(p.representation(), &Representation::Ntt);
+
+        let level = self.par.level_of_ctx(p.ctx())?;
+
+        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+        rng.fill(&mut seed);
+
+        let mut a = Poly::random_from_seed(p.ctx(), Representation::Ntt, seed);
+        let a_s = Zeroizing::new(&a * self.s_ntt(p.ctx())?.as_ref());
+
+        let mut b = Poly::small(p.ctx(), Representation::Ntt, self.par.variance, rng)
+            .map_err(Error::MathError)?;
+        b.multiply_scalar(self.par.plaintext());
+        b -= &a_s;
+        b += p;
+
+        // It is now safe to enable variable time computations.
+        unsafe {
+            a.allow_variable_time_computations();
+            b.allow_variable_time_computations()
+        }
+
+        Ok(Ciphertext {
+            par: self.par.clone(),
+            c: vec![b, a],
+            level,
+            correction_factor: 1,
+        })
+    }
+}
+
+impl FheParametrized for SecretKey {
+    type Parameters = BfvParameters;
+}
+
+impl FheEncrypter<Plaintext, Ciphertext> for SecretKey {
+    type Error = Error;
+
+    fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
+        assert_eq!(self.par, pt.par);
+        self.encrypt_poly(&pt.poly_ntt, rng)
+    }
+}
+
+impl FheDecrypter<Plaintext, Ciphertext> for SecretKey {
+    type Error = Error;
+
+    fn try_decrypt(&self, ct: &Ciphertext) -> Result<Plaintext> {
+        if self.par != ct.par {
+            return Err(Error::DefaultError(
+                "Incompatible BFV parameters".to_string(),
+            ));
+        }
+
+        // Compute the phase c0 + c1*s + c2*s^2 + ... where the secret power
+        // s^k is computed on-the-fly
+        let s = self.s_ntt(ct[0].ctx())?;
+        let mut si = s.clone();
+        let mut c = Zeroizing::new(ct[0].clone());
+        c.disallow_variable_time_computations();
+        for i in 1..ct.len() {
+            let mut cis = Zeroizing::new(ct[i].clone());
+            cis.disallow_variable_time_computations();
+            *cis.as_mut() *= si.as_ref();
+            *c.as_mut() += &cis;
+            if i + 1 < ct.len() {
+                *si.as_mut() *= s.as_ref();
+            }
+        }
+        c.change_representation(Representation::PowerBasis);
+
+        // The message is the centered phase modulo the plaintext modulus,
+        // divided by the correction factor.
+        let q = c.ctx().modulus();
+        let q_half = q >> 1;
+        let t = BigUint::from(self.par.plaintext());
+        let mut w = Zeroizing::new(
+            Vec::<BigUint>::from(c.as_ref())
+                .iter()
+                .map(|ci| {
+                    let v = if ci > &q_half {
+                        (&t - (q - ci) % &t) % &t
+                    } else {
+                        ci % &t
+                    };
+                    v.to_u64().unwrap()
+                })
+                .collect::<Vec<_>>(),
+        );
+        let factor_inv = self
+            .par
+            .plaintext
+            .inv(ct.correction_factor)
+            .ok_or_else(|| Error::DefaultError("Invalid correction factor".to_string()))?;
+        self.par.plaintext.scalar_mul_vec(&mut w, factor_inv);
+
+        let mut poly =
+            Poly::try_convert_from(w.as_slice(), ct[0].ctx(), false, Representation::PowerBasis)?;
+        poly.change_representation(Representation::Ntt);
+
+        Ok(Plaintext {
+            par: self.par.clone(),
+            value: w.as_slice().into(),
+            encoding: None,
+            poly_ntt: poly,
+            level: ct.level,
+            sparsity: Sparsity::Dense,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretKey;
+    use crate::bfv::{BfvParameters, Encoding, Plaintext};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn keygen() {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(1, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        assert_eq!(sk.par, params);
+        sk.coeffs.iter().for_each(|ci| {
+            // Check that this is a small polynomial
+            assert!((*ci).abs() <= 2 * sk.par.variance as i64)
+        })
+    }
+
+    #[test]
+    fn encrypt_decrypt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 16),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            for level in 0..=params.max_level() {
+                let v = params.plaintext.random_vec(params.degree(), &mut rng);
+                let pt = Plaintext::try_encode(&v, Encoding::poly_at_level(level), &params)?;
+                let ct = sk.try_encrypt(&pt, &mut rng)?;
+                assert_eq!(ct.level(), level);
+
+                let pt2 = sk.try_decrypt(&ct)?;
+                assert_eq!(pt2.level(), level);
+                assert_eq!(Vec::<u64>::try_decode(&pt2, Encoding::poly())?, v);
+            }
+        }
+        Ok(())
+    }
+}
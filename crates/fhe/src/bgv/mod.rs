@@ -0,0 +1,19 @@
+#![warn(missing_docs, unused_imports)]
+
+//! The Brakerski-Gentry-Vaikuntanathan homomorphic encryption scheme
+//!
+//! The scheme shares its parameters and plaintexts with the BFV scheme: a
+//! [`BfvParameters`](crate::bfv::BfvParameters) defines the plaintext modulus
+//! and the chain of ciphertext moduli, and the values are encoded in a
+//! [`Plaintext`](crate::bfv::Plaintext). The message is encrypted in the least
+//! significant bits of the ciphertext instead of the most significant ones, so
+//! that switching to the next modulus of the chain with
+//! [`Ciphertext::mod_switch_to_next_level`] also divides the noise by this
+//! modulus.
+
+mod ciphertext;
+mod keys;
+mod ops;
+
+pub use ciphertext::Ciphertext;
+pub use keys::{PublicKey, RelinearizationKey, SecretKey};
@@ -0,0 +1,273 @@
+//! Operations over ciphertexts
+
+use super::Ciphertext;
+use crate::bfv::Plaintext;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use itertools::izip;
+extern crate alloc;
+use alloc::borrow::Cow;
+use alloc::vec;
+
+impl Ciphertext {
+    /// Returns `rhs` with the same correction factor as `self`, which is
+    /// obtained by multiplying `rhs` by the ratio of the factors.
+    fn align_correction_factor<'a>(&self, rhs: &'a Ciphertext) -> Cow<'a, Ciphertext> {
+        if self.correction_factor == rhs.correction_factor {
+            Cow::Borrowed(rhs)
+        } else {
+            let t = &self.par.plaintext;
+            let ratio = t.mul(
+                self.correction_factor,
+                t.inv(rhs.correction_factor).unwrap(),
+            );
+            let mut rhs = rhs.clone();
+            rhs.iter_mut().for_each(|ci| ci.multiply_scalar(ratio));
+            rhs.correction_factor = self.correction_factor;
+            Cow::Owned(rhs)
+        }
+    }
+}
+
+impl Add<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &Ciphertext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone += rhs;
+        self_clone
+    }
+}
+
+impl AddAssign<&Ciphertext> for Ciphertext {
+    /// Adds two ciphertexts at the same level. If their correction factors
+    /// differ, `rhs` is first multiplied by the ratio of the factors, which
+    /// multiplies its noise by up to the plaintext modulus.
+    fn add_assign(&mut self, rhs: &Ciphertext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert_eq!(self.len(), rhs.len());
+        let rhs = self.align_correction_factor(rhs);
+        izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i += c2i);
+    }
+}
+
+impl Add<&Plaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &Plaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone += rhs;
+        self_clone
+    }
+}
+
+impl AddAssign<&Plaintext> for Ciphertext {
+    fn add_assign(&mut self, rhs: &Plaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        let mut m = rhs.poly_ntt.clone();
+        m.multiply_scalar(self.correction_factor);
+        self.c[0] += &m;
+    }
+}
+
+impl Sub<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: &Ciphertext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone -= rhs;
+        self_clone
+    }
+}
+
+impl SubAssign<&Ciphertext> for Ciphertext {
+    fn sub_assign(&mut self, rhs: &Ciphertext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert_eq!(self.len(), rhs.len());
+        let rhs = self.align_correction_factor(rhs);
+        izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i -= c2i);
+    }
+}
+
+impl Neg for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(self) -> Ciphertext {
+        let c = self.iter().map(|c1i| -c1i).collect();
+        Ciphertext {
+            par: self.par.clone(),
+            c,
+            level: self.level,
+            correction_factor: self.correction_factor,
+        }
+    }
+}
+
+impl Neg for Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(mut self) -> Ciphertext {
+        self.iter_mut().for_each(|c1i| *c1i = -&*c1i);
+        self
+    }
+}
+
+impl MulAssign<&Plaintext> for Ciphertext {
+    fn mul_assign(&mut self, rhs: &Plaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        self.iter_mut().for_each(|ci| *ci *= &rhs.poly_ntt);
+    }
+}
+
+impl Mul<&Plaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn mul(self, rhs: &Plaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone *= rhs;
+        self_clone
+    }
+}
+
+impl Mul<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    /// Multiplies two ciphertexts of two parts into a ciphertext of three
+    /// parts, whose correction factor is the product of the factors. The
+    /// product is usually relinearized with a
+    /// [`RelinearizationKey`](crate::bgv::RelinearizationKey) and switched to
+    /// the next level with [`Ciphertext::mod_switch_to_next_level`].
+    fn mul(self, rhs: &Ciphertext) -> Ciphertext {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert!(
+            self.len() == 2 && rhs.len() == 2,
+            "Only supports the multiplication of ciphertexts with 2 parts"
+        );
+
+        let mut c1 = &self[0] * &rhs[1];
+        c1 += &(&self[1] * &rhs[0]);
+        Ciphertext {
+            par: self.par.clone(),
+            c: vec![&self[0] * &rhs[0], c1, &self[1] * &rhs[1]],
+            level: self.level,
+            correction_factor: self
+                .par
+                .plaintext
+                .mul(self.correction_factor, rhs.correction_factor),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfv::{BfvParameters, Encoding, Plaintext};
+    use crate::bgv::{Ciphertext, PublicKey, RelinearizationKey, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn add_sub_neg() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(3, 16);
+        let t = &params.plaintext;
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        for level in 0..=params.max_level() {
+            let a = t.random_vec(params.degree(), &mut rng);
+            let b = t.random_vec(params.degree(), &mut rng);
+            let encoding = Encoding::simd_at_level(level);
+            let pt_a = Plaintext::try_encode(&a, encoding.clone(), &params)?;
+            let pt_b = Plaintext::try_encode(&b, encoding, &params)?;
+            let ct_a: Ciphertext = sk.try_encrypt(&pt_a, &mut rng)?;
+            let ct_b: Ciphertext = pk.try_encrypt(&pt_b, &mut rng)?;
+
+            let mut sum = a.clone();
+            t.add_vec(&mut sum, &b);
+            let mut diff = a.clone();
+            t.sub_vec(&mut diff, &b);
+            let mut neg = a.clone();
+            t.neg_vec(&mut neg);
+
+            let decrypt =
+                |ct: &Ciphertext| Vec::<u64>::try_decode(&sk.try_decrypt(ct)?, Encoding::simd());
+            assert_eq!(decrypt(&(&ct_a + &ct_b))?, sum);
+            assert_eq!(decrypt(&(&ct_a + &pt_b))?, sum);
+            assert_eq!(decrypt(&(&ct_a - &ct_b))?, diff);
+            assert_eq!(decrypt(&(-&ct_a))?, neg);
+            assert_eq!(decrypt(&(-ct_a.clone()))?, neg);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mul_relinearize_mod_switch() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(4, 16);
+        let t = &params.plaintext;
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+
+        // Computes a * b * c * d with a multiplicative depth of 3, where the
+        // fresh operands are encrypted at the level of the product.
+        let values = (0..4)
+            .map(|_| t.random_vec(params.degree(), &mut rng))
+            .collect::<Vec<_>>();
+        let mut ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&values[0], Encoding::simd(), &params)?,
+            &mut rng,
+        )?;
+        let mut expected = values[0].clone();
+        for (level, v) in values.iter().enumerate().skip(1) {
+            let pt = Plaintext::try_encode(v, Encoding::simd_at_level(level - 1), &params)?;
+            let other: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            ct = &ct * &other;
+            rk.relinearizes(&mut ct)?;
+            ct.mod_switch_to_next_level()?;
+            assert_eq!(ct.level(), level);
+            t.mul_vec(&mut expected, v);
+
+            let decrypted = Vec::<u64>::try_decode(&sk.try_decrypt(&ct)?, Encoding::simd())?;
+            assert_eq!(decrypted, expected);
+        }
+
+        // Fresh ciphertexts have a different correction factor.
+        let pt = Plaintext::try_encode(&values[0], Encoding::simd_at_level(3), &params)?;
+        let other: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert_ne!(ct.correction_factor(), other.correction_factor());
+        t.add_vec(&mut expected, &values[0]);
+        let decrypted =
+            Vec::<u64>::try_decode(&sk.try_decrypt(&(&ct + &other))?, Encoding::simd())?;
+        assert_eq!(decrypted, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn mul_plaintext() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(3, 16);
+        let t = &params.plaintext;
+        let sk = SecretKey::random(&params, &mut rng);
+        let a = t.random_vec(params.degree(), &mut rng);
+        let b = t.random_vec(params.degree(), &mut rng);
+        let mut ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&a, Encoding::simd(), &params)?,
+            &mut rng,
+        )?;
+        ct.mod_switch_to_next_level()?;
+        let pt = Plaintext::try_encode(&b, Encoding::simd_at_level(1), &params)?;
+
+        let product = &ct * &pt;
+        let mut expected = a.clone();
+        t.mul_vec(&mut expected, &b);
+        let decrypted = Vec::<u64>::try_decode(&sk.try_decrypt(&product)?, Encoding::simd())?;
+        assert_eq!(decrypted, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! Ciphertext type in the CKKS encryption scheme.
+
+extern crate alloc;
+
+use crate::ckks::parameters::{select_rows, CkksParameters};
+use crate::proto::ckks::Ciphertext as CiphertextProto;
+use crate::{Error, Result};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use fhe_math::rq::{Poly, Representation};
+use fhe_traits::{
+    DeserializeParametrized, DeserializeWithContext, FheCiphertext, FheParametrized, Serialize,
+};
+use itertools::Itertools;
+use prost::Message;
+
+/// A ciphertext encrypting a plaintext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ciphertext {
+    /// The parameters of the underlying CKKS encryption scheme.
+    pub(crate) par: Arc<CkksParameters>,
+
+    /// The ciphertext elements.
+    pub(crate) c: Vec<Poly>,
+
+    /// The ciphertext level
+    pub(crate) level: usize,
+
+    /// The scaling factor of the encrypted values
+    pub(crate) scale: f64,
+}
+
+impl Deref for Ciphertext {
+    type Target = [Poly];
+
+    fn deref(&self) -> &Self::Target {
+        &self.c
+    }
+}
+
+impl DerefMut for Ciphertext {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.c
+    }
+}
+
+impl Ciphertext {
+    /// Returns the level of this ciphertext.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the scaling factor of the values encrypted in this ciphertext.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Divides the ciphertext by its last modulus, which divides the scale by
+    /// this modulus and moves the ciphertext to the next level.
+    ///
+    /// A product of two ciphertexts is usually rescaled once, so that its
+    /// scale returns close to the scale of its operands. Returns an error if
+    /// the ciphertext is at the maximum level.
+    pub fn rescale(&mut self) -> Result<()> {
+        if self.level == self.par.max_level() {
+            return Err(Error::DefaultError(
+                "Cannot rescale a ciphertext at the maximum level".to_string(),
+            ));
+        }
+        let q_last = *self.c[0].ctx().moduli().last().unwrap();
+        for ci in self.c.iter_mut() {
+            ci.change_representation(Representation::PowerBasis);
+            ci.mod_switch_down_next()?;
+            ci.change_representation(Representation::Ntt);
+        }
+        self.level += 1;
+        self.scale /= q_last as f64;
+        Ok(())
+    }
+
+    /// Drops the last moduli of the ciphertext to move it to the given level
+    /// without changing its scale, e.g., to add it to a rescaled ciphertext.
+    ///
+    /// Returns an error if the level is lower than the current level of the
+    /// ciphertext or larger than the maximum level of the parameters.
+    pub fn mod_drop_to_level(&mut self, level: usize) -> Result<()> {
+        if level < self.level || level > self.par.max_level() {
+            return Err(Error::DefaultError(format!(
+                "Cannot drop from level {} to level {}",
+                self.level, level
+            )));
+        }
+        if level == self.level {
+            return Ok(());
+        }
+        let ctx = self.par.ctx_at_level(level)?;
+        let rows = (0..ctx.moduli().len()).collect_vec();
+        for ci in self.c.iter_mut() {
+            *ci = select_rows(ci, &rows, ctx)?;
+        }
+        self.level = level;
+        Ok(())
+    }
+}
+
+impl FheCiphertext for Ciphertext {}
+
+impl FheParametrized for Ciphertext {
+    type Parameters = CkksParameters;
+}
+
+impl Serialize for Ciphertext {
+    fn to_bytes(&self) -> Vec<u8> {
+        CiphertextProto::from(self).encode_to_vec()
+    }
+}
+
+impl DeserializeParametrized for Ciphertext {
+    fn from_bytes(bytes: &[u8], par: &Arc<CkksParameters>) -> Result<Self> {
+        if let Ok(ctp) = Message::decode(bytes) {
+            Ciphertext::try_from_proto(&ctp, par)
+        } else {
+            Err(Error::SerializationError)
+        }
+    }
+
+    type Error = Error;
+}
+
+/// Conversions from and to protobuf.
+impl From<&Ciphertext> for CiphertextProto {
+    fn from(ct: &Ciphertext) -> Self {
+        CiphertextProto {
+            c: ct.c.iter().map(|ci| ci.to_bytes()).collect(),
+            level: ct.level as u32,
+            scale: ct.scale,
+        }
+    }
+}
+
+impl Ciphertext {
+    fn try_from_proto(value: &CiphertextProto, par: &Arc<CkksParameters>) -> Result<Self> {
+        if value.c.len() < 2 {
+            return Err(Error::DefaultError("Not enough polynomials".to_string()));
+        }
+        if value.level as usize > par.max_level() {
+            return Err(Error::DefaultError("Invalid level".to_string()));
+        }
+        if !value.scale.is_finite() || value.scale <= 0.0 {
+            return Err(Error::DefaultError("Invalid scale".to_string()));
+        }
+
+        let ctx = par.ctx_at_level(value.level as usize)?;
+        let c = value
+            .c
+            .iter()
+            .map(|ci| Poly::from_bytes(ci, ctx))
+            .collect::<fhe_math::Result<Vec<_>>>()?;
+        if c.iter()
+            .any(|ci| ci.representation() != &Representation::Ntt)
+        {
+            return Err(Error::DefaultError("Incorrect representation".to_string()));
+        }
+
+        Ok(Ciphertext {
+            par: par.clone(),
+            c,
+            level: value.level as usize,
+            scale: value.scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ckks::{Ciphertext, CkksParameters, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{
+        DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter, Serialize,
+    };
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn serialize() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        for level in 0..=params.max_level() {
+            let pt =
+                Plaintext::try_encode(&[1.5, -2.0] as &[f64], Encoding::at_level(level), &params)?;
+            let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let bytes = ct.to_bytes();
+            assert_eq!(Ciphertext::from_bytes(&bytes, &params)?, ct);
+        }
+        assert!(Ciphertext::from_bytes(&[1, 2, 3], &params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rescale_and_mod_drop() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let values = (0..params.slots())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>();
+
+        // Rescaling divides the scale by the last modulus.
+        let scale = (1u64 << 55) as f64;
+        let pt = Plaintext::try_encode(&values, Encoding::new().with_scale(scale), &params)?;
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        ct.rescale()?;
+        assert_eq!(ct.level(), 1);
+        assert_eq!(ct.scale(), scale / params.moduli()[2] as f64);
+        let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&ct)?, None)?;
+        for (v, d) in values.iter().zip(decoded.iter()) {
+            assert!((v - d).abs() < 1e-2);
+        }
+
+        let pt = Plaintext::try_encode(&values, Encoding::new(), &params)?;
+        let mut ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        assert!(ct.mod_drop_to_level(3).is_err());
+        ct.mod_drop_to_level(2)?;
+        assert_eq!(ct.level(), 2);
+        assert_eq!(ct.scale(), params.scale());
+        assert!(ct.mod_drop_to_level(1).is_err());
+        let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&ct)?, None)?;
+        for (v, d) in values.iter().zip(decoded.iter()) {
+            assert!((v - d).abs() < 1e-6);
+        }
+        assert!(ct.rescale().is_err());
+        Ok(())
+    }
+}
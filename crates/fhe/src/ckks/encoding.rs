@@ -0,0 +1,296 @@
+//! Encoding of real vectors into plaintext polynomials.
+
+use core::f64::consts::PI;
+use core::ops::{Add, Mul, Sub};
+use fhe_traits::FhePlaintextEncoding;
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An encoding for the CKKS plaintexts, which specifies the level and the
+/// scaling factor of the plaintext.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Encoding {
+    pub(crate) level: usize,
+    pub(crate) scale: Option<f64>,
+}
+
+impl Encoding {
+    /// An encoding at level 0 with the default scale of the parameters.
+    pub fn new() -> Self {
+        Self {
+            level: 0,
+            scale: None,
+        }
+    }
+
+    /// An encoding at the specified level with the default scale of the
+    /// parameters.
+    pub fn at_level(level: usize) -> Self {
+        Self { level, scale: None }
+    }
+
+    /// Replaces the scale of the encoding, e.g., to match the scale of a
+    /// ciphertext after rescaling.
+    pub fn with_scale(self, scale: f64) -> Self {
+        Self {
+            level: self.level,
+            scale: Some(scale),
+        }
+    }
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FhePlaintextEncoding for Encoding {}
+
+/// A complex number, just enough for the canonical embedding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    const ZERO: Self = Self { re: 0.0, im: 0.0 };
+
+    fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+/// Maps the slots to the coefficients of the plaintext polynomials and back.
+///
+/// The slot `j` holds the evaluation of the polynomial at `ζ^(5^j)`, where `ζ`
+/// is the primitive `2n`-th root of unity `exp(iπ/n)`, so that the
+/// substitution `x -> x^(5^r)` rotates the slots by `r`. The evaluations at all
+/// the odd powers of `ζ` are computed with a complex FFT of size `n`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SlotEncoder {
+    degree: usize,
+    /// The powers `ζ^k` for `0 <= k < 2n`.
+    roots: Box<[Complex]>,
+    /// The index `(5^j mod 2n - 1) / 2` of the slot `j` in the FFT output.
+    slots: Box<[usize]>,
+}
+
+impl SlotEncoder {
+    pub(crate) fn new(degree: usize) -> Self {
+        let roots = (0..2 * degree)
+            .map(|k| {
+                let angle = PI * k as f64 / degree as f64;
+                Complex {
+                    re: libm::cos(angle),
+                    im: libm::sin(angle),
+                }
+            })
+            .collect();
+        let mut power = 1;
+        let slots = (0..degree / 2)
+            .map(|_| {
+                let index = (power - 1) / 2;
+                power = (power * 5) % (2 * degree);
+                index
+            })
+            .collect();
+        Self {
+            degree,
+            roots,
+            slots,
+        }
+    }
+
+    /// Computes in place `a_t = sum_k a_k ζ^(2kt)`, or its inverse.
+    fn fft(&self, a: &mut [Complex], inverse: bool) {
+        let n = self.degree;
+        let shift = n.leading_zeros() + 1;
+        for i in 0..n {
+            let j = i.reverse_bits() >> shift;
+            if i < j {
+                a.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= n {
+            let step = 2 * n / len;
+            for chunk in a.chunks_mut(len) {
+                let (lo, hi) = chunk.split_at_mut(len / 2);
+                for (j, (u, v)) in lo.iter_mut().zip(hi.iter_mut()).enumerate() {
+                    let mut w = self.roots[j * step];
+                    if inverse {
+                        w = w.conj();
+                    }
+                    let t = *v * w;
+                    *v = *u - t;
+                    *u = *u + t;
+                }
+            }
+            len <<= 1;
+        }
+        if inverse {
+            let inv_n = 1.0 / n as f64;
+            a.iter_mut().for_each(|ai| {
+                ai.re *= inv_n;
+                ai.im *= inv_n;
+            });
+        }
+    }
+
+    /// Returns the rounded coefficients of the real polynomial whose slots
+    /// are the values multiplied by the scale, the remaining slots being 0.
+    ///
+    /// Returns None if a coefficient does not fit in 62 bits.
+    pub(crate) fn encode(&self, values: &[f64], scale: f64) -> Option<Vec<i64>> {
+        let n = self.degree;
+        let mut a = vec![Complex::ZERO; n];
+        for (value, index) in values.iter().zip(self.slots.iter()) {
+            // The slots at the conjugate roots hold the conjugate values.
+            let v = Complex {
+                re: value * scale,
+                im: 0.0,
+            };
+            a[*index] = v;
+            a[n - 1 - *index] = v.conj();
+        }
+        self.fft(&mut a, true);
+
+        let bound = (1u64 << 62) as f64;
+        a.iter()
+            .enumerate()
+            .map(|(k, ak)| {
+                let coefficient = libm::round((*ak * self.roots[(2 * n - k) % (2 * n)]).re);
+                (coefficient.abs() < bound).then_some(coefficient as i64)
+            })
+            .collect()
+    }
+
+    /// Returns the real parts of the slots of the polynomial with the
+    /// specified (real) coefficients.
+    pub(crate) fn decode(&self, coefficients: &[f64]) -> Vec<f64> {
+        let mut a = coefficients
+            .iter()
+            .zip(self.roots.iter())
+            .map(|(c, root)| Complex {
+                re: c * root.re,
+                im: c * root.im,
+            })
+            .collect::<Vec<_>>();
+        self.fft(&mut a, false);
+        self.slots.iter().map(|index| a[*index].re).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Complex, SlotEncoder};
+    extern crate alloc;
+    use alloc::vec::Vec;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn fft() {
+        let mut rng = thread_rng();
+        for degree in [8, 16, 128] {
+            let encoder = SlotEncoder::new(degree);
+            let a = (0..degree)
+                .map(|_| Complex {
+                    re: rng.gen_range(-1.0..1.0),
+                    im: rng.gen_range(-1.0..1.0),
+                })
+                .collect::<Vec<_>>();
+
+            let mut b = a.clone();
+            encoder.fft(&mut b, false);
+            for (t, bt) in b.iter().enumerate() {
+                let mut expected = Complex::ZERO;
+                for (k, ak) in a.iter().enumerate() {
+                    expected = expected + *ak * encoder.roots[(2 * k * t) % (2 * degree)];
+                }
+                assert!((bt.re - expected.re).abs() < 1e-9);
+                assert!((bt.im - expected.im).abs() < 1e-9);
+            }
+
+            encoder.fft(&mut b, true);
+            for (ai, bi) in a.iter().zip(b.iter()) {
+                assert!((ai.re - bi.re).abs() < 1e-12);
+                assert!((ai.im - bi.im).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode() {
+        let mut rng = thread_rng();
+        for degree in [8, 16, 128] {
+            let encoder = SlotEncoder::new(degree);
+            let values = (0..degree / 2)
+                .map(|_| rng.gen_range(-10.0..10.0))
+                .collect::<Vec<f64>>();
+            let scale = (1u64 << 30) as f64;
+            let coefficients = encoder.encode(&values, scale).unwrap();
+            let decoded = encoder.decode(
+                &coefficients
+                    .iter()
+                    .map(|c| *c as f64 / scale)
+                    .collect::<Vec<_>>(),
+            );
+            for (v, d) in values.iter().zip(decoded.iter()) {
+                assert!((v - d).abs() < 1e-6);
+            }
+
+            // Fewer values are padded with zeros.
+            let coefficients = encoder.encode(&values[..1], scale).unwrap();
+            let decoded = encoder.decode(
+                &coefficients
+                    .iter()
+                    .map(|c| *c as f64 / scale)
+                    .collect::<Vec<_>>(),
+            );
+            assert!((values[0] - decoded[0]).abs() < 1e-6);
+            assert!(decoded[1..].iter().all(|d| d.abs() < 1e-6));
+
+            assert!(encoder.encode(&[1.0], 1e30).is_none());
+        }
+    }
+}
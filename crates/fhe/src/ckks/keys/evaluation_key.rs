@@ -0,0 +1,166 @@
+//! Evaluation keys for the CKKS encryption scheme
+
+use crate::ckks::{Ciphertext, CkksParameters, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation, SubstitutionExponent};
+use fhe_traits::FheParametrized;
+use hashbrown::HashMap;
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use zeroize::Zeroizing;
+
+use super::key_switching_key::KeySwitchingKey;
+
+/// Evaluation key for the CKKS encryption scheme, which rotates the slots of
+/// the ciphertexts by a set of steps.
+///
+/// The slot `j` of a plaintext holds the evaluation of its polynomial at
+/// `ζ^(5^j)`, so that substituting `x` by `x^(5^r)` rotates the slots by `r`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvaluationKey {
+    pub(crate) par: Arc<CkksParameters>,
+    /// Key switching keys from `s(x^exponent)` to `s`, indexed by exponent.
+    pub(crate) gk: HashMap<usize, KeySwitchingKey>,
+}
+
+impl EvaluationKey {
+    /// Generate an [`EvaluationKey`] from a [`SecretKey`], which rotates the
+    /// slots by each of the `steps`. Negative steps rotate to the right.
+    pub fn new<R: RngCore + CryptoRng>(
+        sk: &SecretKey,
+        steps: &[isize],
+        rng: &mut R,
+    ) -> Result<Self> {
+        let ctx = &sk.par.key_ctx[0];
+        let mut gk = HashMap::new();
+        for step in steps {
+            let exponent = Self::exponent(&sk.par, *step);
+            if exponent == 1 || gk.contains_key(&exponent) {
+                continue;
+            }
+            let s = Zeroizing::new(Poly::try_convert_from(
+                sk.coeffs.as_ref(),
+                ctx,
+                false,
+                Representation::PowerBasis,
+            )?);
+            let mut s_sub =
+                Zeroizing::new(s.substitute(&SubstitutionExponent::new(ctx, exponent)?)?);
+            s_sub.change_representation(Representation::Ntt);
+            gk.insert(exponent, KeySwitchingKey::new(sk, &s_sub, rng)?);
+        }
+        Ok(Self {
+            par: sk.par.clone(),
+            gk,
+        })
+    }
+
+    /// Returns the Galois exponent `5^step mod 2n` of a rotation.
+    fn exponent(par: &CkksParameters, step: isize) -> usize {
+        // The order of 5 modulo 2n is n / 2, i.e., the number of slots.
+        let step = step.rem_euclid(par.slots() as isize) as usize;
+        let modulus = 2 * par.degree();
+        (0..step).fold(1, |e, _| (e * 5) % modulus)
+    }
+
+    /// Returns whether the key rotates the slots by `step`.
+    pub fn supports_rotation_by(&self, step: isize) -> bool {
+        let exponent = Self::exponent(&self.par, step);
+        exponent == 1 || self.gk.contains_key(&exponent)
+    }
+
+    /// Rotates the slots of a ciphertext by `step`, so that the slot `j` of
+    /// the output holds the slot `j + step` of the input.
+    ///
+    /// Returns an error if the key does not support this rotation.
+    pub fn rotates_slots_by(&self, ct: &Ciphertext, step: isize) -> Result<Ciphertext> {
+        if ct.par != self.par {
+            return Err(Error::DefaultError(
+                "Incompatible CKKS parameters".to_string(),
+            ));
+        }
+        if ct.len() != 2 {
+            return Err(Error::DefaultError(
+                "Only supports rotation of ciphertexts with 2 parts".to_string(),
+            ));
+        }
+        let exponent = Self::exponent(&self.par, step);
+        if exponent == 1 {
+            return Ok(ct.clone());
+        }
+        let ksk = self
+            .gk
+            .get(&exponent)
+            .ok_or_else(|| Error::DefaultError(format!("No key to rotate the slots by {step}")))?;
+
+        let sub_exponent = SubstitutionExponent::new(ct[0].ctx(), exponent)?;
+        let mut c0 = ct[0].substitute(&sub_exponent)?;
+        let mut c1 = ct[1].substitute(&sub_exponent)?;
+        c1.change_representation(Representation::PowerBasis);
+        let (d0, d1) = ksk.key_switch(&c1)?;
+        c0 += &d0;
+
+        Ok(Ciphertext {
+            par: self.par.clone(),
+            c: vec![c0, d1],
+            level: ct.level,
+            scale: ct.scale,
+        })
+    }
+}
+
+impl FheParametrized for EvaluationKey {
+    type Parameters = CkksParameters;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EvaluationKey;
+    use crate::ckks::{Ciphertext, CkksParameters, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn rotation() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            CkksParameters::default_arc(1, 16),
+            CkksParameters::default_arc(3, 32),
+        ] {
+            let slots = params.slots() as isize;
+            let sk = SecretKey::random(&params, &mut rng);
+            let steps = [1, 3, -1, slots - 2];
+            let ek = EvaluationKey::new(&sk, &steps, &mut rng)?;
+            assert!(ek.supports_rotation_by(0));
+            assert!(ek.supports_rotation_by(slots + 1));
+            assert!(!ek.supports_rotation_by(2));
+
+            for level in 0..=params.max_level() {
+                let values = (0..params.slots())
+                    .map(|_| rng.gen_range(-1.0..1.0))
+                    .collect::<Vec<f64>>();
+                let pt = Plaintext::try_encode(&values, Encoding::at_level(level), &params)?;
+                let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+                for step in steps.iter().chain([0].iter()) {
+                    let rotated = ek.rotates_slots_by(&ct, *step)?;
+                    assert_eq!(rotated.level(), level);
+                    let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&rotated)?, None)?;
+                    for (j, d) in decoded.iter().enumerate() {
+                        let expected = values[(j as isize + step).rem_euclid(slots) as usize];
+                        assert!((expected - d).abs() < 1e-6);
+                    }
+                }
+                assert!(ek.rotates_slots_by(&ct, 2).is_err());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Key-switching keys for the CKKS encryption scheme
+
+use crate::ckks::parameters::select_rows;
+use crate::ckks::{CkksParameters, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
+use itertools::{izip, Itertools};
+use ndarray::Array2;
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+/// Key switching key for the CKKS encryption scheme.
+///
+/// The key is defined modulo the ciphertext moduli of level 0 and the special
+/// modulus `P`, and encrypts `P * δ_i * s'` under `s` for each ciphertext
+/// modulus `q_i`, where `δ_i` is `1` modulo `q_i` and `0` modulo the other
+/// ciphertext moduli. Since these relations also hold modulo the moduli of any
+/// level and `P`, the key switches ciphertexts at every level.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct KeySwitchingKey {
+    pub(crate) par: Arc<CkksParameters>,
+    pub(crate) c0: Box<[Poly]>,
+    pub(crate) c1: Box<[Poly]>,
+}
+
+impl KeySwitchingKey {
+    /// Generate a [`KeySwitchingKey`] from `from` to the secret key `sk`,
+    /// where `from` is in Ntt representation in the key context of level 0.
+    pub(crate) fn new<R: RngCore + CryptoRng>(
+        sk: &SecretKey,
+        from: &Poly,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let ctx = &sk.par.key_ctx[0];
+        if from.ctx() != ctx || from.representation() != &Representation::Ntt {
+            return Err(Error::DefaultError(
+                "The polynomial to switch from is not in the key context".to_string(),
+            ));
+        }
+        let s = sk.s_ntt(ctx)?;
+        let special_modulus = sk.par.special_modulus();
+        let coefficients = from.coefficients();
+
+        let mut c0 = Vec::with_capacity(sk.par.moduli().len());
+        let mut c1 = Vec::with_capacity(sk.par.moduli().len());
+        for (i, qi) in ctx.moduli_operators()[..sk.par.moduli().len()]
+            .iter()
+            .enumerate()
+        {
+            // P * δ_i * from is P * from modulo q_i, and 0 modulo the others.
+            let mut gadget = Array2::zeros((ctx.moduli().len(), coefficients.ncols()));
+            let p_mod_qi = qi.reduce(special_modulus);
+            izip!(gadget.row_mut(i).iter_mut(), coefficients.row(i).iter())
+                .for_each(|(g, f)| *g = qi.mul(*f, p_mod_qi));
+            let gadget = Zeroizing::new(Poly::try_convert_from(
+                gadget,
+                ctx,
+                false,
+                Representation::Ntt,
+            )?);
+
+            let mut a = Poly::random(ctx, Representation::Ntt, rng);
+            let a_s = Zeroizing::new(&a * s.as_ref());
+            let mut b = Poly::small(ctx, Representation::Ntt, sk.par.variance, rng)?;
+            b -= &a_s;
+            b += &gadget;
+
+            // It is now safe to enable variable time computations.
+            unsafe {
+                a.allow_variable_time_computations();
+                b.allow_variable_time_computations();
+            }
+            a.change_representation(Representation::NttShoup);
+            b.change_representation(Representation::NttShoup);
+            c0.push(b);
+            c1.push(a);
+        }
+
+        Ok(Self {
+            par: sk.par.clone(),
+            c0: c0.into_boxed_slice(),
+            c1: c1.into_boxed_slice(),
+        })
+    }
+
+    /// Key switch a polynomial in PowerBasis representation at any level.
+    ///
+    /// Returns `(c0, c1)` in Ntt representation at the level of `p`, such that
+    /// `c0 + c1 * s` is approximately `p * s'`.
+    pub(crate) fn key_switch(&self, p: &Poly) -> Result<(Poly, Poly)> {
+        if p.representation() != &Representation::PowerBasis {
+            return Err(Error::DefaultError("Incorrect representation".to_string()));
+        }
+        let level = self.par.level_of_ctx(p.ctx())?;
+        let ctx = &self.par.ctx[level];
+        let key_ctx = &self.par.key_ctx[level];
+
+        // The rows of the key modulo the moduli of the level and P.
+        let num_moduli = ctx.moduli().len();
+        let rows = (0..num_moduli)
+            .chain([self.par.moduli().len()])
+            .collect_vec();
+
+        let mut c0 = Poly::zero(key_ctx, Representation::Ntt);
+        let mut c1 = Poly::zero(key_ctx, Representation::Ntt);
+        for (p_i_coefficients, c0_i, c1_i) in izip!(
+            p.coefficients().outer_iter(),
+            self.c0.iter(),
+            self.c1.iter()
+        ) {
+            let restricted;
+            let (c0_i, c1_i) = if level == 0 {
+                (c0_i, c1_i)
+            } else {
+                restricted = (
+                    select_rows(c0_i, &rows, key_ctx)?,
+                    select_rows(c1_i, &rows, key_ctx)?,
+                );
+                (&restricted.0, &restricted.1)
+            };
+            let mut p_i = unsafe {
+                Poly::create_constant_ntt_polynomial_with_lazy_coefficients_and_variable_time(
+                    p_i_coefficients.as_slice().unwrap(),
+                    key_ctx,
+                )
+            };
+            c0 += &(&p_i * c0_i);
+            p_i *= c1_i;
+            c1 += &p_i;
+        }
+
+        // Divide by P, and move the result to the context of the level.
+        let rows = (0..num_moduli).collect_vec();
+        let mut out = [c0, c1];
+        for ci in out.iter_mut() {
+            ci.change_representation(Representation::PowerBasis);
+            ci.mod_switch_down_next()?;
+            *ci = select_rows(ci, &rows, ctx)?;
+            ci.change_representation(Representation::Ntt);
+            unsafe { ci.allow_variable_time_computations() }
+        }
+        let [c0, c1] = out;
+        Ok((c0, c1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeySwitchingKey;
+    use crate::ckks::{CkksParameters, SecretKey};
+    use crate::Error;
+    use fhe_math::rq::{Poly, Representation};
+    use num_bigint::BigUint;
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn key_switch() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let other = SecretKey::random(&params, &mut rng);
+        let from = other.s_ntt(&params.key_ctx[0])?;
+        let ksk = KeySwitchingKey::new(&sk, &from, &mut rng)?;
+
+        for level in 0..=params.max_level() {
+            let ctx = &params.ctx[level];
+            let input = Poly::random(ctx, Representation::PowerBasis, &mut rng);
+            let (c0, c1) = ksk.key_switch(&input)?;
+            assert_eq!(c0.ctx(), ctx);
+
+            // c0 + c1 * s - input * s' is small.
+            let mut input_ntt = input.clone();
+            input_ntt.change_representation(Representation::Ntt);
+            let mut diff = &c0 + &(&c1 * sk.s_ntt(ctx)?.as_ref());
+            diff -= &(&input_ntt * other.s_ntt(ctx)?.as_ref());
+            diff.change_representation(Representation::PowerBasis);
+
+            let q = ctx.modulus();
+            let bound = BigUint::from(1u64 << 20);
+            Vec::<BigUint>::from(&diff).iter().for_each(|c| {
+                assert!(c < &bound || (q - c) < bound);
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Keys for the CKKS encryption scheme
+
+mod evaluation_key;
+mod key_switching_key;
+mod public_key;
+mod relinearization_key;
+mod secret_key;
+
+pub use evaluation_key::EvaluationKey;
+pub use public_key::PublicKey;
+pub use relinearization_key::RelinearizationKey;
+pub use secret_key::SecretKey;
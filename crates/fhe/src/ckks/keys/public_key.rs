@@ -0,0 +1,137 @@
+//! Public keys for the CKKS encryption scheme
+
+use crate::ckks::parameters::select_rows;
+use crate::ckks::{Ciphertext, CkksParameters, Plaintext};
+use crate::{Error, Result};
+use fhe_math::rq::{Poly, Representation};
+use fhe_traits::{FheEncrypter, FheParametrized};
+use itertools::Itertools;
+use rand::RngCore;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use zeroize::Zeroizing;
+
+use super::SecretKey;
+
+/// Public key for the CKKS encryption scheme.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PublicKey {
+    pub(crate) par: Arc<CkksParameters>,
+    pub(crate) c: Ciphertext,
+}
+
+impl PublicKey {
+    /// Generate a new [`PublicKey`] from a [`SecretKey`].
+    pub fn new<R: RngCore>(sk: &SecretKey, rng: &mut R) -> Self {
+        let zero = Zeroizing::new(Poly::zero(&sk.par.ctx[0], Representation::Ntt));
+        let mut c = sk.encrypt_poly(zero.as_ref(), 1.0, rng).unwrap();
+        // The polynomials of a public key should not allow for variable time
+        // computation.
+        c.iter_mut()
+            .for_each(|p| p.disallow_variable_time_computations());
+        Self {
+            par: sk.par.clone(),
+            c,
+        }
+    }
+}
+
+impl FheParametrized for PublicKey {
+    type Parameters = CkksParameters;
+}
+
+impl FheEncrypter<Plaintext, Ciphertext> for PublicKey {
+    type Error = Error;
+
+    fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
+        if self.par != pt.par {
+            return Err(Error::DefaultError(
+                "Incompatible CKKS parameters".to_string(),
+            ));
+        }
+
+        // The public key remains an encryption of zero modulo a divisor of its
+        // modulus, so it is restricted to the moduli of the plaintext level.
+        let ctx = self.par.ctx_at_level(pt.level)?;
+        let rows = (0..ctx.moduli().len()).collect_vec();
+        let b = select_rows(&self.c[0], &rows, ctx)?;
+        let a = select_rows(&self.c[1], &rows, ctx)?;
+
+        let u = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+        let e1 = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+        let e2 = Zeroizing::new(Poly::small(
+            ctx,
+            Representation::Ntt,
+            self.par.variance,
+            rng,
+        )?);
+
+        let mut c0 = u.as_ref() * &b;
+        c0 += &e1;
+        c0 += &pt.poly_ntt;
+        let mut c1 = u.as_ref() * &a;
+        c1 += &e2;
+
+        // It is now safe to enable variable time computations.
+        unsafe {
+            c0.allow_variable_time_computations();
+            c1.allow_variable_time_computations()
+        }
+
+        Ok(Ciphertext {
+            par: self.par.clone(),
+            c: vec![c0, c1],
+            level: pt.level,
+            scale: pt.scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PublicKey;
+    use crate::ckks::{CkksParameters, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn encrypt_decrypt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            CkksParameters::default_arc(1, 16),
+            CkksParameters::default_arc(3, 32),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            let pk = PublicKey::new(&sk, &mut rng);
+            for level in 0..=params.max_level() {
+                let values = (0..params.slots())
+                    .map(|_| rng.gen_range(-10.0..10.0))
+                    .collect::<Vec<f64>>();
+                let pt = Plaintext::try_encode(&values, Encoding::at_level(level), &params)?;
+                let ct = pk.try_encrypt(&pt, &mut rng)?;
+                assert_eq!(ct.level(), level);
+
+                let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&ct)?, None)?;
+                for (v, d) in values.iter().zip(decoded.iter()) {
+                    assert!((v - d).abs() < 1e-6);
+                }
+            }
+        }
+        Ok(())
+    }
+}
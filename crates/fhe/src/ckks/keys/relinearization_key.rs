@@ -0,0 +1,97 @@
+//! Relinearization keys for the CKKS encryption scheme
+
+use crate::ckks::{Ciphertext, CkksParameters, SecretKey};
+use crate::{Error, Result};
+use fhe_math::rq::Representation;
+use fhe_traits::FheParametrized;
+use rand::{CryptoRng, RngCore};
+extern crate alloc;
+use alloc::string::ToString;
+use zeroize::Zeroizing;
+
+use super::key_switching_key::KeySwitchingKey;
+
+/// Relinearization key for the CKKS encryption scheme.
+/// A relinearization key is a special type of key switching key,
+/// which switch from `s^2` to `s` where `s` is the secret key.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RelinearizationKey {
+    pub(crate) ksk: KeySwitchingKey,
+}
+
+impl RelinearizationKey {
+    /// Generate a [`RelinearizationKey`] from a [`SecretKey`].
+    pub fn new<R: RngCore + CryptoRng>(sk: &SecretKey, rng: &mut R) -> Result<Self> {
+        let s = sk.s_ntt(&sk.par.key_ctx[0])?;
+        let s2 = Zeroizing::new(s.as_ref() * s.as_ref());
+        let ksk = KeySwitchingKey::new(sk, &s2, rng)?;
+        Ok(Self { ksk })
+    }
+
+    /// Relinearize an "extended" ciphertext (c0, c1, c2) into a [`Ciphertext`]
+    /// (c0', c1') encrypting the same values, at the same level and scale.
+    pub fn relinearizes(&self, ct: &mut Ciphertext) -> Result<()> {
+        if ct.len() != 3 {
+            Err(Error::DefaultError(
+                "Only supports relinearization of ciphertext with 3 parts".to_string(),
+            ))
+        } else if ct.par != self.ksk.par {
+            Err(Error::DefaultError(
+                "Incompatible CKKS parameters".to_string(),
+            ))
+        } else {
+            let mut c2 = ct.c.pop().unwrap();
+            c2.change_representation(Representation::PowerBasis);
+            let (c0, c1) = self.ksk.key_switch(&c2)?;
+            ct.c[0] += &c0;
+            ct.c[1] += &c1;
+            Ok(())
+        }
+    }
+}
+
+impl FheParametrized for RelinearizationKey {
+    type Parameters = CkksParameters;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RelinearizationKey;
+    use crate::ckks::{Ciphertext, CkksParameters, Encoding, Plaintext, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn relinearization() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+
+        // At the maximum level, the modulus is too small for the product.
+        for level in 0..params.max_level() {
+            let values = (0..params.slots())
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect::<Vec<f64>>();
+            let pt = Plaintext::try_encode(&values, Encoding::at_level(level), &params)?;
+            let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            let mut product = &ct * &ct;
+            assert_eq!(product.len(), 3);
+            let expected = Vec::<f64>::try_decode(&sk.try_decrypt(&product)?, None)?;
+
+            rk.relinearizes(&mut product)?;
+            assert_eq!(product.len(), 2);
+            assert_eq!(product.level(), level);
+            let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&product)?, None)?;
+            for (v, (e, d)) in values.iter().zip(expected.iter().zip(decoded.iter())) {
+                assert!((v * v - e).abs() < 1e-6);
+                assert!((e - d).abs() < 1e-6);
+            }
+            assert!(rk.relinearizes(&mut product).is_err());
+        }
+        Ok(())
+    }
+}
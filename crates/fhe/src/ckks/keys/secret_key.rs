@@ -0,0 +1,182 @@
+//! Secret keys for the CKKS encryption scheme
+
+use crate::ckks::{Ciphertext, CkksParameters, Plaintext};
+use crate::{Error, Result};
+use fhe_math::rq::{traits::TryConvertFrom, Context, Poly, Representation};
+use fhe_traits::{FheDecrypter, FheEncrypter, FheParametrized};
+use fhe_util::sample_vec_cbd;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use zeroize::Zeroizing;
+use zeroize_derive::{Zeroize, ZeroizeOnDrop};
+
+/// Secret key for the CKKS encryption scheme.
+#[derive(Debug, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SecretKey {
+    #[zeroize(skip)]
+    pub(crate) par: Arc<CkksParameters>,
+    pub(crate) coeffs: Box<[i64]>,
+}
+
+impl SecretKey {
+    /// Generate a random [`SecretKey`].
+    pub fn random<R: RngCore>(par: &Arc<CkksParameters>, rng: &mut R) -> Self {
+        let s_coefficients = sample_vec_cbd(par.degree(), par.variance, rng).unwrap();
+        Self {
+            par: par.clone(),
+            coeffs: s_coefficients.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the secret key in Ntt representation in the context `ctx`.
+    pub(crate) fn s_ntt(&self, ctx: &Arc<Context>) -> Result<Zeroizing<Poly>> {
+        let mut s = Zeroizing::new(Poly::try_convert_from(
+            self.coeffs.as_ref(),
+            ctx,
+            false,
+            Representation::PowerBasis,
+        )?);
+        s.change_representation(Representation::Ntt);
+        Ok(s)
+    }
+
+    /// Encrypts a polynomial in Ntt representation, at the level of its
+    /// context.
+    pub(crate) fn encrypt_poly<R: RngCore>(
+        &self,
+        p: &Poly,
+        scale: f64,
+        rng: &mut R,
+    ) -> Result<Ciphertext> {
+        assert_eq!(p.representation(), &Representation::Ntt);
+
+        let level = self.par.level_of_ctx(p.ctx())?;
+
+        let mut seed = <ChaCha8Rng as SeedableRng>::Seed::default();
+        rng.fill(&mut seed);
+
+        let mut a = Poly::random_from_seed(p.ctx(), Representation::Ntt, seed);
+        let a_s = Zeroizing::new(&a * self.s_ntt(p.ctx())?.as_ref());
+
+        let mut b = Poly::small(p.ctx(), Representation::Ntt, self.par.variance, rng)
+            .map_err(Error::MathError)?;
+        b -= &a_s;
+        b += p;
+
+        // It is now safe to enable variable time computations.
+        unsafe {
+            a.allow_variable_time_computations();
+            b.allow_variable_time_computations()
+        }
+
+        Ok(Ciphertext {
+            par: self.par.clone(),
+            c: vec![b, a],
+            level,
+            scale,
+        })
+    }
+}
+
+impl FheParametrized for SecretKey {
+    type Parameters = CkksParameters;
+}
+
+impl FheEncrypter<Plaintext, Ciphertext> for SecretKey {
+    type Error = Error;
+
+    fn try_encrypt<R: RngCore>(&self, pt: &Plaintext, rng: &mut R) -> Result<Ciphertext> {
+        assert_eq!(self.par, pt.par);
+        self.encrypt_poly(&pt.poly_ntt, pt.scale, rng)
+    }
+}
+
+impl FheDecrypter<Plaintext, Ciphertext> for SecretKey {
+    type Error = Error;
+
+    fn try_decrypt(&self, ct: &Ciphertext) -> Result<Plaintext> {
+        if self.par != ct.par {
+            return Err(Error::DefaultError(
+                "Incompatible CKKS parameters".to_string(),
+            ));
+        }
+
+        // Compute the phase c0 + c1*s + c2*s^2 + ... where the secret power
+        // s^k is computed on-the-fly
+        let s = self.s_ntt(ct[0].ctx())?;
+        let mut si = s.clone();
+        let mut c = Zeroizing::new(ct[0].clone());
+        c.disallow_variable_time_computations();
+        for i in 1..ct.len() {
+            let mut cis = Zeroizing::new(ct[i].clone());
+            cis.disallow_variable_time_computations();
+            *cis.as_mut() *= si.as_ref();
+            *c.as_mut() += &cis;
+            if i + 1 < ct.len() {
+                *si.as_mut() *= s.as_ref();
+            }
+        }
+
+        Ok(Plaintext {
+            par: self.par.clone(),
+            poly_ntt: c.as_ref().clone(),
+            level: ct.level,
+            scale: ct.scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretKey;
+    use crate::ckks::{CkksParameters, Encoding, Plaintext};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn keygen() {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(1, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        assert_eq!(sk.par, params);
+        sk.coeffs.iter().for_each(|ci| {
+            // Check that this is a small polynomial
+            assert!((*ci).abs() <= 2 * sk.par.variance as i64)
+        })
+    }
+
+    #[test]
+    fn encrypt_decrypt() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        for params in [
+            CkksParameters::default_arc(1, 16),
+            CkksParameters::default_arc(3, 32),
+        ] {
+            let sk = SecretKey::random(&params, &mut rng);
+            for level in 0..=params.max_level() {
+                let values = (0..params.slots())
+                    .map(|_| rng.gen_range(-10.0..10.0))
+                    .collect::<Vec<f64>>();
+                let pt = Plaintext::try_encode(&values, Encoding::at_level(level), &params)?;
+                let ct = sk.try_encrypt(&pt, &mut rng)?;
+                assert_eq!(ct.level(), level);
+                assert_eq!(ct.scale(), params.scale());
+
+                let pt2 = sk.try_decrypt(&ct)?;
+                let decoded = Vec::<f64>::try_decode(&pt2, None)?;
+                for (v, d) in values.iter().zip(decoded.iter()) {
+                    assert!((v - d).abs() < 1e-6);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+#![warn(missing_docs, unused_imports)]
+
+//! The Cheon-Kim-Kim-Song homomorphic encryption scheme, for approximate
+//! arithmetic over vectors of real numbers.
+//!
+//! The values are multiplied by a scaling factor and rounded when encoded, so
+//! that the decrypted values are only approximately equal to the result of
+//! the computation. The product of two ciphertexts has the product of their
+//! scales, and is rescaled to the next level with [`Ciphertext::rescale`].
+
+mod ciphertext;
+mod encoding;
+mod keys;
+mod ops;
+mod parameters;
+mod plaintext;
+
+pub use ciphertext::Ciphertext;
+pub use encoding::Encoding;
+pub use keys::{EvaluationKey, PublicKey, RelinearizationKey, SecretKey};
+pub use parameters::{CkksParameters, CkksParametersBuilder};
+pub use plaintext::Plaintext;
@@ -0,0 +1,244 @@
+//! Operations over ciphertexts
+
+use super::{Ciphertext, Plaintext};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use itertools::izip;
+extern crate alloc;
+use alloc::vec;
+
+impl Add<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &Ciphertext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone += rhs;
+        self_clone
+    }
+}
+
+impl AddAssign<&Ciphertext> for Ciphertext {
+    fn add_assign(&mut self, rhs: &Ciphertext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert_eq!(self.scale, rhs.scale, "Incompatible scales");
+        assert_eq!(self.len(), rhs.len());
+        izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i += c2i);
+    }
+}
+
+impl Add<&Plaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &Plaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone += rhs;
+        self_clone
+    }
+}
+
+impl AddAssign<&Plaintext> for Ciphertext {
+    fn add_assign(&mut self, rhs: &Plaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert_eq!(self.scale, rhs.scale, "Incompatible scales");
+        self.c[0] += &rhs.poly_ntt;
+    }
+}
+
+impl Sub<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: &Ciphertext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone -= rhs;
+        self_clone
+    }
+}
+
+impl SubAssign<&Ciphertext> for Ciphertext {
+    fn sub_assign(&mut self, rhs: &Ciphertext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert_eq!(self.scale, rhs.scale, "Incompatible scales");
+        assert_eq!(self.len(), rhs.len());
+        izip!(self.iter_mut(), rhs.iter()).for_each(|(c1i, c2i)| *c1i -= c2i);
+    }
+}
+
+impl Neg for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(self) -> Ciphertext {
+        let c = self.iter().map(|c1i| -c1i).collect();
+        Ciphertext {
+            par: self.par.clone(),
+            c,
+            level: self.level,
+            scale: self.scale,
+        }
+    }
+}
+
+impl Neg for Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(mut self) -> Ciphertext {
+        self.iter_mut().for_each(|c1i| *c1i = -&*c1i);
+        self
+    }
+}
+
+impl MulAssign<&Plaintext> for Ciphertext {
+    fn mul_assign(&mut self, rhs: &Plaintext) {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        self.iter_mut().for_each(|ci| *ci *= &rhs.poly_ntt);
+        self.scale *= rhs.scale;
+    }
+}
+
+impl Mul<&Plaintext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn mul(self, rhs: &Plaintext) -> Ciphertext {
+        let mut self_clone = self.clone();
+        self_clone *= rhs;
+        self_clone
+    }
+}
+
+impl Mul<&Ciphertext> for &Ciphertext {
+    type Output = Ciphertext;
+
+    /// Multiplies two ciphertexts of two parts into a ciphertext of three
+    /// parts, whose scale is the product of the scales. The product is usually
+    /// relinearized with a
+    /// [`RelinearizationKey`](crate::ckks::RelinearizationKey) and rescaled
+    /// with [`Ciphertext::rescale`].
+    fn mul(self, rhs: &Ciphertext) -> Ciphertext {
+        assert_eq!(self.par, rhs.par);
+        assert_eq!(self.level, rhs.level);
+        assert!(
+            self.len() == 2 && rhs.len() == 2,
+            "Only supports the multiplication of ciphertexts with 2 parts"
+        );
+
+        let mut c1 = &self[0] * &rhs[1];
+        c1 += &(&self[1] * &rhs[0]);
+        Ciphertext {
+            par: self.par.clone(),
+            c: vec![&self[0] * &rhs[0], c1, &self[1] * &rhs[1]],
+            level: self.level,
+            scale: self.scale * rhs.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ckks::{
+        Ciphertext, CkksParameters, Encoding, Plaintext, PublicKey, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, Rng};
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    fn random_values(params: &CkksParameters) -> Vec<f64> {
+        let mut rng = thread_rng();
+        (0..params.slots())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64], tolerance: f64) {
+        assert_eq!(a.len(), b.len());
+        for (ai, bi) in a.iter().zip(b.iter()) {
+            assert!((ai - bi).abs() < tolerance, "{ai} != {bi}");
+        }
+    }
+
+    #[test]
+    fn add_sub_neg() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 32);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        for level in 0..=params.max_level() {
+            let a = random_values(&params);
+            let b = random_values(&params);
+            let pt_a = Plaintext::try_encode(&a, Encoding::at_level(level), &params)?;
+            let pt_b = Plaintext::try_encode(&b, Encoding::at_level(level), &params)?;
+            let ct_a: Ciphertext = sk.try_encrypt(&pt_a, &mut rng)?;
+            let ct_b: Ciphertext = pk.try_encrypt(&pt_b, &mut rng)?;
+
+            let sum = a.iter().zip(&b).map(|(x, y)| x + y).collect::<Vec<_>>();
+            let diff = a.iter().zip(&b).map(|(x, y)| x - y).collect::<Vec<_>>();
+            let neg = a.iter().map(|x| -x).collect::<Vec<_>>();
+
+            let decrypt = |ct: &Ciphertext| Vec::<f64>::try_decode(&sk.try_decrypt(ct)?, None);
+            assert_close(&decrypt(&(&ct_a + &ct_b))?, &sum, 1e-6);
+            assert_close(&decrypt(&(&ct_a + &pt_b))?, &sum, 1e-6);
+            assert_close(&decrypt(&(&ct_a - &ct_b))?, &diff, 1e-6);
+            assert_close(&decrypt(&(-&ct_a))?, &neg, 1e-6);
+            assert_close(&decrypt(&(-ct_a.clone()))?, &neg, 1e-6);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mul_relinearize_rescale() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(4, 32);
+        let sk = SecretKey::random(&params, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+
+        // Computes a * b * c * d with a multiplicative depth of 3.
+        let values = (0..4).map(|_| random_values(&params)).collect::<Vec<_>>();
+        let mut ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&values[0], Encoding::new(), &params)?,
+            &mut rng,
+        )?;
+        let mut expected = values[0].clone();
+        for (level, v) in values.iter().enumerate().skip(1) {
+            let pt = Plaintext::try_encode(
+                v,
+                Encoding::at_level(level - 1).with_scale(ct.scale()),
+                &params,
+            )?;
+            let other: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+            ct = &ct * &other;
+            rk.relinearizes(&mut ct)?;
+            ct.rescale()?;
+            assert_eq!(ct.level(), level);
+            expected = expected.iter().zip(v).map(|(x, y)| x * y).collect();
+
+            let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&ct)?, None)?;
+            assert_close(&decoded, &expected, 1e-4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mul_plaintext() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 32);
+        let sk = SecretKey::random(&params, &mut rng);
+        let a = random_values(&params);
+        let b = random_values(&params);
+        let ct: Ciphertext = sk.try_encrypt(
+            &Plaintext::try_encode(&a, Encoding::new(), &params)?,
+            &mut rng,
+        )?;
+        let pt = Plaintext::try_encode(&b, Encoding::new(), &params)?;
+
+        let mut product = &ct * &pt;
+        assert_eq!(product.scale(), params.scale() * params.scale());
+        product.rescale()?;
+        let expected = a.iter().zip(&b).map(|(x, y)| x * y).collect::<Vec<_>>();
+        let decoded = Vec::<f64>::try_decode(&sk.try_decrypt(&product)?, None)?;
+        assert_close(&decoded, &expected, 1e-4);
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+//! Create parameters for the CKKS encryption scheme
+
+use crate::bfv::BfvParametersBuilder;
+use crate::ckks::encoding::SlotEncoder;
+use crate::{Error, ParametersError, Result};
+use alloc::fmt::Debug;
+use fhe_math::{
+    rq::{traits::TryConvertFrom, Context, Poly},
+    zq::primes::generate_prime,
+};
+use fhe_traits::FheParameters;
+use itertools::Itertools;
+use ndarray::Array2;
+extern crate alloc;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Parameters for the CKKS encryption scheme.
+///
+/// The ciphertext moduli `q_0, ..., q_L` define `L + 1` levels: a ciphertext
+/// at level `l` is defined modulo the first `L + 1 - l` moduli, and rescaling
+/// a ciphertext divides it by its last modulus and moves it to the next
+/// level. The special modulus `P` is only used by the key switching keys,
+/// which are defined modulo `q_0 * ... * q_L * P`, and should be at least as
+/// large as the largest ciphertext modulus.
+#[derive(PartialEq)]
+pub struct CkksParameters {
+    /// Number of coefficients in a polynomial.
+    polynomial_degree: usize,
+
+    /// Vector of coprime moduli q_i for the ciphertext.
+    moduli: Box<[u64]>,
+
+    /// Vector of the sizes of the coprime moduli q_i for the ciphertext.
+    moduli_sizes: Box<[usize]>,
+
+    /// Special modulus of the key switching keys.
+    special_modulus: u64,
+
+    /// Default scaling factor of the plaintexts.
+    scale: f64,
+
+    /// Error variance
+    pub(crate) variance: usize,
+
+    /// Context for the ciphertext polynomials at each level.
+    pub(crate) ctx: Vec<Arc<Context>>,
+
+    /// Context for the key switching polynomials at each level, with the
+    /// special modulus last.
+    pub(crate) key_ctx: Vec<Arc<Context>>,
+
+    /// Encoder between the slots and the plaintext polynomials.
+    pub(crate) encoder: SlotEncoder,
+}
+
+impl Debug for CkksParameters {
+    fn fmt(&self, f: &mut alloc::fmt::Formatter<'_>) -> alloc::fmt::Result {
+        f.debug_struct("CkksParameters")
+            .field("polynomial_degree", &self.polynomial_degree)
+            .field("moduli", &self.moduli)
+            .field("special_modulus", &self.special_modulus)
+            .field("scale", &self.scale)
+            .finish()
+    }
+}
+
+impl FheParameters for CkksParameters {}
+
+unsafe impl Send for CkksParameters {}
+
+impl CkksParameters {
+    /// Minimum polynomial degree supported by the parameters.
+    pub const MIN_DEGREE: usize = fhe_math::ntt::MIN_SIZE;
+
+    /// Returns the underlying polynomial degree
+    pub const fn degree(&self) -> usize {
+        self.polynomial_degree
+    }
+
+    /// Returns the number of slots of a plaintext, i.e., half the degree.
+    pub const fn slots(&self) -> usize {
+        self.polynomial_degree / 2
+    }
+
+    /// Returns a reference to the ciphertext moduli
+    pub fn moduli(&self) -> &[u64] {
+        &self.moduli
+    }
+
+    /// Returns a reference to the sizes of the ciphertext moduli
+    pub fn moduli_sizes(&self) -> &[usize] {
+        &self.moduli_sizes
+    }
+
+    /// Returns the special modulus of the key switching keys
+    pub const fn special_modulus(&self) -> u64 {
+        self.special_modulus
+    }
+
+    /// Returns the default scaling factor of the plaintexts
+    pub const fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the maximum level allowed by these parameters.
+    pub fn max_level(&self) -> usize {
+        self.moduli.len() - 1
+    }
+
+    /// Returns the context corresponding to the level.
+    pub(crate) fn ctx_at_level(&self, level: usize) -> Result<&Arc<Context>> {
+        self.ctx
+            .get(level)
+            .ok_or_else(|| Error::DefaultError("No context".to_string()))
+    }
+
+    /// Returns the level of a given context
+    pub(crate) fn level_of_ctx(&self, ctx: &Arc<Context>) -> Result<usize> {
+        self.ctx[0].niterations_to(ctx).map_err(Error::MathError)
+    }
+
+    /// Returns default parameters for tests.
+    #[cfg(test)]
+    pub fn default_arc(num_moduli: usize, degree: usize) -> Arc<Self> {
+        let mut sizes = alloc::vec![40usize; num_moduli];
+        sizes[0] = 60;
+        CkksParametersBuilder::new()
+            .set_degree(degree)
+            .set_moduli_sizes(&sizes)
+            .set_special_modulus_size(60)
+            .set_scale((1u64 << 40) as f64)
+            .build_arc()
+            .unwrap()
+    }
+}
+
+/// Restricts a polynomial to the moduli at indices `rows`, which define the
+/// context `ctx`, without changing its representation.
+pub(crate) fn select_rows(p: &Poly, rows: &[usize], ctx: &Arc<Context>) -> Result<Poly> {
+    let coefficients = p.coefficients();
+    let mut a = Array2::zeros((rows.len(), coefficients.ncols()));
+    for (mut row, i) in a.outer_iter_mut().zip(rows) {
+        row.assign(&coefficients.row(*i));
+    }
+    Ok(Poly::try_convert_from(
+        a,
+        ctx,
+        false,
+        p.representation().clone(),
+    )?)
+}
+
+/// Builder for parameters for the CKKS encryption scheme.
+#[derive(Debug)]
+pub struct CkksParametersBuilder {
+    degree: usize,
+    variance: usize,
+    scale: f64,
+    ciphertext_moduli: Vec<u64>,
+    ciphertext_moduli_sizes: Vec<usize>,
+    special_modulus: u64,
+    special_modulus_size: usize,
+}
+
+impl CkksParametersBuilder {
+    /// Creates a new instance of the builder
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            degree: Default::default(),
+            variance: 10,
+            scale: Default::default(),
+            ciphertext_moduli: Default::default(),
+            ciphertext_moduli_sizes: Default::default(),
+            special_modulus: Default::default(),
+            special_modulus_size: Default::default(),
+        }
+    }
+
+    /// Sets the polynomial degree. Returns an error if the degree is not
+    /// a power of two larger or equal to [`CkksParameters::MIN_DEGREE`].
+    pub fn set_degree(&mut self, degree: usize) -> &mut Self {
+        self.degree = degree;
+        self
+    }
+
+    /// Sets the sizes of the ciphertext moduli.
+    /// Only one of `set_moduli_sizes` and `set_moduli` can be specified.
+    pub fn set_moduli_sizes(&mut self, sizes: &[usize]) -> &mut Self {
+        sizes.clone_into(&mut self.ciphertext_moduli_sizes);
+        self
+    }
+
+    /// Sets the ciphertext moduli to use.
+    /// Only one of `set_moduli_sizes` and `set_moduli` can be specified.
+    pub fn set_moduli(&mut self, moduli: &[u64]) -> &mut Self {
+        moduli.clone_into(&mut self.ciphertext_moduli);
+        self
+    }
+
+    /// Sets the size of the special modulus of the key switching keys.
+    /// Only one of `set_special_modulus_size` and `set_special_modulus` can be
+    /// specified.
+    pub fn set_special_modulus_size(&mut self, size: usize) -> &mut Self {
+        self.special_modulus_size = size;
+        self
+    }
+
+    /// Sets the special modulus of the key switching keys.
+    /// Only one of `set_special_modulus_size` and `set_special_modulus` can be
+    /// specified.
+    pub fn set_special_modulus(&mut self, modulus: u64) -> &mut Self {
+        self.special_modulus = modulus;
+        self
+    }
+
+    /// Sets the default scaling factor of the plaintexts. When not set, the
+    /// scale is the last ciphertext modulus, so that rescaling a product
+    /// approximately restores the scale.
+    pub fn set_scale(&mut self, scale: f64) -> &mut Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the error variance. Returns an error if the variance is not between
+    /// one and sixteen.
+    pub fn set_variance(&mut self, variance: usize) -> &mut Self {
+        self.variance = variance;
+        self
+    }
+
+    /// Generate a special modulus of the specified size, distinct from the
+    /// ciphertext moduli.
+    fn generate_special_modulus(size: usize, degree: usize, moduli: &[u64]) -> Result<u64> {
+        if !(10..=62).contains(&size) {
+            return Err(Error::ParametersError(ParametersError::InvalidModulusSize(
+                size, 10, 62,
+            )));
+        }
+        let mut upper_bound = 1 << size;
+        while let Some(prime) = generate_prime(size, 2 * degree as u64, upper_bound) {
+            if !moduli.contains(&prime) {
+                return Ok(prime);
+            }
+            upper_bound = prime;
+        }
+        Err(Error::ParametersError(ParametersError::NotEnoughPrimes(
+            size, degree,
+        )))
+    }
+
+    /// Build a new `CkksParameters` inside an `Arc`.
+    pub fn build_arc(&self) -> Result<Arc<CkksParameters>> {
+        self.build().map(Arc::new)
+    }
+
+    /// Build a new `CkksParameters`.
+    pub fn build(&self) -> Result<CkksParameters> {
+        // Check that the degree is a power of 2 (and large enough).
+        if self.degree < CkksParameters::MIN_DEGREE {
+            return Err(Error::ParametersError(ParametersError::DegreeTooSmall {
+                min: CkksParameters::MIN_DEGREE,
+                got: self.degree,
+            }));
+        }
+        if !self.degree.is_power_of_two() {
+            return Err(Error::ParametersError(ParametersError::InvalidDegree(
+                self.degree,
+            )));
+        }
+
+        if !(1..=16).contains(&self.variance) {
+            return Err(Error::ParametersError(ParametersError::InvalidVariance(
+                self.variance,
+            )));
+        }
+
+        if self.ciphertext_moduli.is_empty() == self.ciphertext_moduli_sizes.is_empty() {
+            return Err(Error::ParametersError(ParametersError::TooManySpecified(
+                "Exactly one of `ciphertext_moduli` and `ciphertext_moduli_sizes` must be specified"
+                    .to_string(),
+            )));
+        }
+        if (self.special_modulus == 0) == (self.special_modulus_size == 0) {
+            return Err(Error::ParametersError(ParametersError::TooManySpecified(
+                "Exactly one of `special_modulus` and `special_modulus_size` must be specified"
+                    .to_string(),
+            )));
+        }
+
+        // Get or generate the moduli.
+        let moduli = if self.ciphertext_moduli.is_empty() {
+            BfvParametersBuilder::generate_moduli(&self.ciphertext_moduli_sizes, self.degree)?
+        } else {
+            self.ciphertext_moduli.clone()
+        };
+        let special_modulus = if self.special_modulus == 0 {
+            Self::generate_special_modulus(self.special_modulus_size, self.degree, &moduli)?
+        } else {
+            self.special_modulus
+        };
+        let mut distinct = moduli.clone();
+        distinct.push(special_modulus);
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() != moduli.len() + 1 {
+            return Err(Error::ParametersError(ParametersError::InvalidModuliChain(
+                "the moduli are not distinct".to_string(),
+            )));
+        }
+
+        let scale = if self.scale == 0.0 {
+            moduli[moduli.len() - 1] as f64
+        } else {
+            self.scale
+        };
+        if !scale.is_finite() || scale < 1.0 {
+            return Err(Error::ParametersError(ParametersError::InvalidPlaintext(
+                "The scale must be a finite number larger or equal to 1".to_string(),
+            )));
+        }
+
+        let moduli_sizes = moduli
+            .iter()
+            .map(|m| 64 - m.leading_zeros() as usize)
+            .collect_vec();
+
+        // The ciphertext contexts are nested, so that rescaling a polynomial
+        // yields the context of the next level.
+        let ctx_0 = Context::new_arc(&moduli, self.degree)?;
+        let ctx = core::iter::once(&ctx_0)
+            .chain(ctx_0.sub_contexts())
+            .cloned()
+            .collect_vec();
+        let key_ctx = (0..moduli.len())
+            .map(|i| {
+                let mut key_moduli = moduli[..moduli.len() - i].to_owned();
+                key_moduli.push(special_modulus);
+                Context::new_arc(&key_moduli, self.degree)
+            })
+            .collect::<fhe_math::Result<Vec<_>>>()?;
+
+        Ok(CkksParameters {
+            polynomial_degree: self.degree,
+            moduli: moduli.into_boxed_slice(),
+            moduli_sizes: moduli_sizes.into_boxed_slice(),
+            special_modulus,
+            scale,
+            variance: self.variance,
+            ctx,
+            key_ctx,
+            encoder: SlotEncoder::new(self.degree),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CkksParameters, CkksParametersBuilder};
+    use crate::Error;
+
+    #[test]
+    fn default() {
+        let params = CkksParameters::default_arc(3, 16);
+        assert_eq!(params.degree(), 16);
+        assert_eq!(params.slots(), 8);
+        assert_eq!(params.moduli().len(), 3);
+        assert_eq!(params.moduli_sizes(), &[60, 40, 40]);
+        assert_eq!(params.max_level(), 2);
+        assert_eq!(params.scale(), (1u64 << 40) as f64);
+        assert!(!params.moduli().contains(&params.special_modulus()));
+        for (level, ctx) in params.ctx.iter().enumerate() {
+            assert_eq!(ctx.moduli(), &params.moduli()[..3 - level]);
+            assert_eq!(params.level_of_ctx(ctx).unwrap(), level);
+            assert_eq!(params.key_ctx[level].moduli()[..3 - level], *ctx.moduli());
+            assert_eq!(
+                params.key_ctx[level].moduli()[3 - level],
+                params.special_modulus()
+            );
+        }
+    }
+
+    #[test]
+    fn builder() -> Result<(), Error> {
+        let params = CkksParametersBuilder::new()
+            .set_degree(16)
+            .set_moduli_sizes(&[50, 30])
+            .set_special_modulus_size(50)
+            .build()?;
+        assert_eq!(params.scale(), params.moduli()[1] as f64);
+
+        let same = CkksParametersBuilder::new()
+            .set_degree(16)
+            .set_moduli(params.moduli())
+            .set_special_modulus(params.special_modulus())
+            .set_scale(params.scale())
+            .build()?;
+        assert_eq!(same, params);
+
+        assert!(CkksParametersBuilder::new()
+            .set_degree(16)
+            .set_moduli_sizes(&[50, 30])
+            .build()
+            .is_err());
+        assert!(CkksParametersBuilder::new()
+            .set_degree(16)
+            .set_moduli_sizes(&[50, 30])
+            .set_special_modulus(params.moduli()[0])
+            .build()
+            .is_err());
+        assert!(CkksParametersBuilder::new()
+            .set_degree(12)
+            .set_moduli_sizes(&[50, 30])
+            .set_special_modulus_size(50)
+            .build()
+            .is_err());
+        assert!(CkksParametersBuilder::new()
+            .set_degree(16)
+            .set_moduli_sizes(&[50, 30])
+            .set_special_modulus_size(50)
+            .set_scale(-1.0)
+            .build()
+            .is_err());
+        Ok(())
+    }
+}
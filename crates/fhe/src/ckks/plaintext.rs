@@ -0,0 +1,192 @@
+//! Plaintext type in the CKKS encryption scheme.
+use crate::{
+    ckks::{CkksParameters, Encoding},
+    Error, Result,
+};
+use fhe_math::rq::{traits::TryConvertFrom, Poly, Representation};
+use fhe_traits::{FheDecoder, FheEncoder, FheParametrized, FhePlaintext};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+use zeroize_derive::{Zeroize, ZeroizeOnDrop};
+
+/// A plaintext object, that encodes a vector of real values multiplied by a
+/// scaling factor.
+#[derive(Debug, Clone, PartialEq, Zeroize, ZeroizeOnDrop)]
+pub struct Plaintext {
+    /// The parameters of the underlying CKKS encryption scheme.
+    #[zeroize(skip)]
+    pub(crate) par: Arc<CkksParameters>,
+    /// The plaintext as a polynomial.
+    pub(crate) poly_ntt: Poly,
+    /// The level of the plaintext
+    #[zeroize(skip)]
+    pub(crate) level: usize,
+    /// The scaling factor of the plaintext
+    #[zeroize(skip)]
+    pub(crate) scale: f64,
+}
+
+impl FheParametrized for Plaintext {
+    type Parameters = CkksParameters;
+}
+
+impl FhePlaintext for Plaintext {
+    type Encoding = Encoding;
+}
+
+impl Plaintext {
+    /// Returns the level of this plaintext.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the scaling factor of this plaintext.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Decodes a polynomial in power basis, whose coefficients are the
+    /// encoded values multiplied by `scale`.
+    pub(crate) fn decode_poly(par: &CkksParameters, p: &Poly, scale: f64) -> Result<Vec<f64>> {
+        if p.representation() != &Representation::PowerBasis {
+            return Err(Error::MathError(fhe_math::Error::IncorrectRepresentation(
+                p.representation().clone(),
+                Representation::PowerBasis,
+            )));
+        }
+        let q = p.ctx().modulus();
+        let q_half = q >> 1;
+        let coefficients = Vec::<BigUint>::from(p)
+            .iter()
+            .map(|c| {
+                let c = if c > &q_half {
+                    -(q - c).to_f64().unwrap_or(f64::INFINITY)
+                } else {
+                    c.to_f64().unwrap_or(f64::INFINITY)
+                };
+                c / scale
+            })
+            .collect::<Vec<_>>();
+        Ok(par.encoder.decode(&coefficients))
+    }
+}
+
+impl<'a> FheEncoder<&'a [f64]> for Plaintext {
+    type Error = Error;
+
+    fn try_encode(value: &'a [f64], encoding: Encoding, par: &Arc<CkksParameters>) -> Result<Self> {
+        if value.len() > par.slots() {
+            return Err(Error::TooManyValues(value.len(), par.slots()));
+        }
+        let scale = encoding.scale.unwrap_or(par.scale());
+        if !scale.is_finite() || scale < 1.0 {
+            return Err(Error::UnspecifiedInput(
+                "The scale must be a finite number larger or equal to 1".to_string(),
+            ));
+        }
+        let ctx = par.ctx_at_level(encoding.level)?;
+
+        let coefficients = Zeroizing::new(par.encoder.encode(value, scale).ok_or_else(|| {
+            Error::UnspecifiedInput("The values are too large for the scale".to_string())
+        })?);
+        let mut poly_ntt = Poly::try_convert_from(
+            coefficients.as_slice(),
+            ctx,
+            false,
+            Representation::PowerBasis,
+        )?;
+        poly_ntt.change_representation(Representation::Ntt);
+
+        Ok(Self {
+            par: par.clone(),
+            poly_ntt,
+            level: encoding.level,
+            scale,
+        })
+    }
+}
+
+impl<'a> FheEncoder<&'a Vec<f64>> for Plaintext {
+    type Error = Error;
+
+    fn try_encode(
+        value: &'a Vec<f64>,
+        encoding: Encoding,
+        par: &Arc<CkksParameters>,
+    ) -> Result<Self> {
+        Plaintext::try_encode(value.as_slice(), encoding, par)
+    }
+}
+
+impl FheDecoder<Plaintext> for Vec<f64> {
+    type Error = Error;
+
+    /// Decodes the slots of the plaintext. The level and the scale are those
+    /// of the plaintext, and an encoding is only used to check them.
+    fn try_decode<O>(pt: &Plaintext, encoding: O) -> Result<Vec<f64>>
+    where
+        O: Into<Option<Encoding>>,
+    {
+        if let Some(encoding) = encoding.into() {
+            if encoding.level != pt.level || encoding.scale.is_some_and(|s| s != pt.scale) {
+                return Err(Error::UnspecifiedInput(
+                    "The encoding does not match the level and scale of the plaintext".to_string(),
+                ));
+            }
+        }
+        let mut p = Zeroizing::new(pt.poly_ntt.clone());
+        p.change_representation(Representation::PowerBasis);
+        Plaintext::decode_poly(&pt.par, &p, pt.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Plaintext;
+    use crate::ckks::{CkksParameters, Encoding};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheEncoder};
+    extern crate alloc;
+    use alloc::vec::Vec;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn encode_decode() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = CkksParameters::default_arc(3, 16);
+        let values = (0..params.slots())
+            .map(|_| rng.gen_range(-100.0..100.0))
+            .collect::<Vec<f64>>();
+
+        for level in 0..=params.max_level() {
+            let pt = Plaintext::try_encode(&values, Encoding::at_level(level), &params)?;
+            assert_eq!(pt.level(), level);
+            assert_eq!(pt.scale(), params.scale());
+            let decoded = Vec::<f64>::try_decode(&pt, None)?;
+            for (v, d) in values.iter().zip(decoded.iter()) {
+                assert!((v - d).abs() < 1e-6);
+            }
+            assert!(Vec::<f64>::try_decode(&pt, Encoding::at_level(level)).is_ok());
+            assert!(Vec::<f64>::try_decode(&pt, Encoding::at_level(level + 1)).is_err());
+        }
+
+        let pt = Plaintext::try_encode(&values, Encoding::new().with_scale(1024.0), &params)?;
+        assert_eq!(pt.scale(), 1024.0);
+        let decoded = Vec::<f64>::try_decode(&pt, None)?;
+        for (v, d) in values.iter().zip(decoded.iter()) {
+            assert!((v - d).abs() < 1e-1);
+        }
+
+        let too_many = alloc::vec![0.0; params.slots() + 1];
+        assert!(Plaintext::try_encode(&too_many, Encoding::new(), &params).is_err());
+        assert!(Plaintext::try_encode(&values, Encoding::at_level(3), &params).is_err());
+        assert!(Plaintext::try_encode(&values, Encoding::new().with_scale(0.5), &params).is_err());
+        assert!(Plaintext::try_encode(&values, Encoding::new().with_scale(1e30), &params).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Sources of entropy for targets without an operating system random number
+//! generator.
+//!
+//! All the key generation and encryption APIs of this library are generic over
+//! a cryptographically secure random number generator. On targets without
+//! `std`, an [`EntropyRng`] can be seeded from any [`EntropySource`]:
+//! - [`GetRandom`] uses the `getrandom` crate (requires the `getrandom`
+//!   feature);
+//! - [`RdRand`] uses the `RDRAND` instruction on `x86_64` (requires the
+//!   `rdrand` feature);
+//! - [`CallbackEntropy`] calls a user-provided function, e.g. to read a
+//!   hardware random number generator.
+//!
+//! The [`default_rng`] function is only defined when one of the `getrandom` or
+//! `rdrand` features is enabled, so that using it without any configured
+//! source of entropy is a compile-time error.
+
+extern crate alloc;
+
+use crate::Result;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+#[cfg(any(feature = "getrandom", all(feature = "rdrand", target_arch = "x86_64")))]
+use {crate::Error, alloc::string::ToString};
+
+/// A source of entropy used to seed the random number generators.
+pub trait EntropySource {
+    /// Fill `dest` with random bytes, or return an error if the source failed.
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<()>;
+}
+
+/// An entropy source using the `getrandom` crate.
+#[cfg(feature = "getrandom")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GetRandom;
+
+#[cfg(feature = "getrandom")]
+impl EntropySource for GetRandom {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<()> {
+        getrandom::getrandom(dest).map_err(|e| Error::EntropyError(e.to_string()))
+    }
+}
+
+/// An entropy source using the `RDRAND` instruction of `x86_64` processors.
+#[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+#[derive(Debug, Clone, Copy)]
+pub struct RdRand(());
+
+#[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+impl RdRand {
+    /// Number of attempts before reporting a failure of the instruction, as
+    /// recommended by Intel.
+    const RETRIES: usize = 10;
+
+    /// Returns an entropy source using `RDRAND`, or an error if the processor
+    /// does not support the instruction.
+    pub fn new() -> Result<Self> {
+        // CPUID leaf 1 reports the support of RDRAND in bit 30 of ecx.
+        #[allow(unused_unsafe)]
+        let ecx = unsafe { core::arch::x86_64::__cpuid(1) }.ecx;
+        if ecx & (1 << 30) == 0 {
+            return Err(Error::EntropyError(
+                "RDRAND is not supported by this processor".to_string(),
+            ));
+        }
+        Ok(Self(()))
+    }
+
+    #[target_feature(enable = "rdrand")]
+    unsafe fn next(&self) -> Option<u64> {
+        let mut value = 0u64;
+        for _ in 0..Self::RETRIES {
+            if core::arch::x86_64::_rdrand64_step(&mut value) == 1 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+impl EntropySource for RdRand {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<()> {
+        for chunk in dest.chunks_mut(8) {
+            // Safety: the support of RDRAND is checked in `RdRand::new`.
+            let value = unsafe { self.next() }
+                .ok_or_else(|| Error::EntropyError("RDRAND failed".to_string()))?;
+            chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+/// An entropy source calling a user-provided function.
+pub struct CallbackEntropy<F: FnMut(&mut [u8]) -> Result<()>>(F);
+
+impl<F: FnMut(&mut [u8]) -> Result<()>> CallbackEntropy<F> {
+    /// Returns an entropy source calling `f` to fill buffers with random bytes.
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F: FnMut(&mut [u8]) -> Result<()>> EntropySource for CallbackEntropy<F> {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<()> {
+        (self.0)(dest)
+    }
+}
+
+/// A cryptographically secure random number generator seeded from an
+/// [`EntropySource`], to be used with the key generation and encryption APIs.
+#[derive(Debug, Clone)]
+pub struct EntropyRng(ChaCha20Rng);
+
+impl EntropyRng {
+    /// Returns a random number generator seeded from `source`.
+    pub fn from_source<E: EntropySource>(source: &mut E) -> Result<Self> {
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        source.fill_entropy(&mut seed)?;
+        Ok(Self(ChaCha20Rng::from_seed(seed)))
+    }
+}
+
+impl RngCore for EntropyRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for EntropyRng {}
+
+/// Returns a random number generator seeded from the configured source of
+/// entropy, i.e. `getrandom` if the `getrandom` feature is enabled, and
+/// `RDRAND` otherwise.
+#[cfg(any(feature = "getrandom", all(feature = "rdrand", target_arch = "x86_64")))]
+pub fn default_rng() -> Result<EntropyRng> {
+    #[cfg(feature = "getrandom")]
+    let mut source = GetRandom;
+    #[cfg(not(feature = "getrandom"))]
+    let mut source = RdRand::new()?;
+    EntropyRng::from_source(&mut source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CallbackEntropy, EntropyRng, EntropySource};
+    use crate::bfv::{BfvParameters, Encoding, Plaintext, PublicKey, SecretKey};
+    use crate::Error;
+    use fhe_traits::{FheDecoder, FheDecrypter, FheEncoder, FheEncrypter};
+    use rand::{thread_rng, RngCore};
+    extern crate alloc;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn callback() -> Result<(), Error> {
+        let mut counter = 0u8;
+        let mut source = CallbackEntropy::new(|dest: &mut [u8]| {
+            dest.iter_mut().for_each(|b| {
+                *b = counter;
+                counter = counter.wrapping_add(1);
+            });
+            Ok(())
+        });
+        let mut buf = [0u8; 4];
+        source.fill_entropy(&mut buf)?;
+        assert_eq!(buf, [0, 1, 2, 3]);
+
+        let mut failing =
+            CallbackEntropy::new(|_: &mut [u8]| Err(Error::EntropyError("failed".to_string())));
+        assert!(EntropyRng::from_source(&mut failing).is_err());
+
+        let mut rng = EntropyRng::from_source(&mut source)?;
+        let params = BfvParameters::default_arc(1, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let values = params
+            .plaintext
+            .random_vec(params.degree(), &mut thread_rng());
+        let pt = Plaintext::try_encode(&values, Encoding::poly(), &params)?;
+        let ct = pk.try_encrypt(&pt, &mut rng)?;
+        let decrypted = sk.try_decrypt(&ct)?;
+        assert_eq!(
+            Vec::<u64>::try_decode(&decrypted, Encoding::poly())?,
+            values
+        );
+
+        // Identical seeds yield identical generators.
+        let mut zeros = CallbackEntropy::new(|dest: &mut [u8]| {
+            dest.fill(0);
+            Ok(())
+        });
+        let mut rng1 = EntropyRng::from_source(&mut zeros)?;
+        let mut rng2 = EntropyRng::from_source(&mut zeros)?;
+        assert_eq!(rng1.next_u64(), rng2.next_u64());
+        Ok(())
+    }
+
+    #[cfg(any(feature = "getrandom", all(feature = "rdrand", target_arch = "x86_64")))]
+    #[test]
+    fn default_rng() -> Result<(), Error> {
+        let mut rng1 = super::default_rng()?;
+        let mut rng2 = super::default_rng()?;
+        assert_ne!(rng1.next_u64(), rng2.next_u64());
+        Ok(())
+    }
+}
@@ -41,6 +41,15 @@ pub enum Error {
     #[error("{0}")]
     ParametersError(ParametersError),
 
+    /// Indicates that the source of entropy failed.
+    #[error("Entropy error: {0}")]
+    EntropyError(String),
+
+    /// Indicates that the operation was cancelled with a
+    /// [`CancellationToken`](crate::bfv::CancellationToken).
+    #[error("The operation was cancelled")]
+    Cancelled,
+
     /// Indicates a default error
     /// TODO: To delete eventually
     #[error("{0}")]
@@ -57,9 +66,21 @@ impl From<fhe_math::Error> for Error {
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum ParametersError {
     /// Indicates that the degree is invalid.
-    #[error("Invalid degree: {0} is not a power of 2 larger than 8")]
+    #[error(
+        "Invalid degree: {0} is not a power of 2 larger or equal to {}",
+        crate::bfv::BfvParameters::MIN_DEGREE
+    )]
     InvalidDegree(usize),
 
+    /// Indicates that the degree is smaller than the minimum degree.
+    #[error("Degree too small: {got} is smaller than the minimum degree {min}")]
+    DegreeTooSmall {
+        /// The minimum degree.
+        min: usize,
+        /// The degree that was provided.
+        got: usize,
+    },
+
     /// Indicates that the moduli sizes are invalid.
     #[error("Invalid modulus size: {0}, expected an integer between {1} and {2}")]
     InvalidModulusSize(usize, usize, usize),
@@ -79,12 +100,47 @@ pub enum ParametersError {
     /// Indicates that too few parameters were specified.
     #[error("{0}")]
     TooFewSpecified(String),
+
+    /// Indicates that the error variance is invalid.
+    #[error("Invalid variance: {0}, expected an integer between 1 and 16")]
+    InvalidVariance(usize),
+
+    /// Indicates that the standard deviation of the error is invalid.
+    #[error("Invalid noise standard deviation: {0}, expected a value whose square rounds to an integer between 1 and 16")]
+    InvalidNoiseStdDev(String),
+
+    /// Indicates that the moduli chain is invalid.
+    #[error("Invalid moduli chain: {0}")]
+    InvalidModuliChain(String),
+
+    /// Indicates that the ciphertext modulus is too large for the requested
+    /// security level.
+    #[error("Insecure parameters: the ciphertext modulus has {log_q} bits, more than the {max} bits allowed for {security} bits of security")]
+    InsecureParameters {
+        /// The number of bits of the ciphertext modulus.
+        log_q: usize,
+        /// The maximum number of bits of the ciphertext modulus.
+        max: usize,
+        /// The requested security level, in bits.
+        security: usize,
+    },
+
+    /// Indicates that the estimated security of the parameters is below the
+    /// required minimum.
+    #[error("Insecure parameters: estimated security of {estimated} bits, less than the required {min} bits")]
+    EstimatedSecurityTooLow {
+        /// The estimated security, in bits.
+        estimated: usize,
+        /// The required security, in bits.
+        min: usize,
+    },
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Error, ParametersError};
+    use crate::{bfv::BfvParameters, Error, ParametersError};
     extern crate alloc;
+    use alloc::format;
     use alloc::string::ToString;
 
     #[test]
@@ -94,6 +150,7 @@ mod tests {
             fhe_math::Error::InvalidContext.to_string()
         );
         assert_eq!(Error::SerializationError.to_string(), "Serialization error");
+        assert_eq!(Error::Cancelled.to_string(), "The operation was cancelled");
         assert_eq!(
             Error::TooManyValues(20, 17).to_string(),
             "Too many values provided: 20 exceeds limit 17"
@@ -124,7 +181,14 @@ mod tests {
     fn parameters_error_strings() {
         assert_eq!(
             ParametersError::InvalidDegree(10).to_string(),
-            "Invalid degree: 10 is not a power of 2 larger than 8"
+            format!(
+                "Invalid degree: 10 is not a power of 2 larger or equal to {}",
+                BfvParameters::MIN_DEGREE
+            )
+        );
+        assert_eq!(
+            ParametersError::DegreeTooSmall { min: 8, got: 4 }.to_string(),
+            "Degree too small: 4 is smaller than the minimum degree 8"
         );
         assert_eq!(
             ParametersError::InvalidModulusSize(1, 2, 3).to_string(),
@@ -146,5 +210,17 @@ mod tests {
             ParametersError::TooFewSpecified("test".to_string()).to_string(),
             "test"
         );
+        assert_eq!(
+            ParametersError::InvalidVariance(17).to_string(),
+            "Invalid variance: 17, expected an integer between 1 and 16"
+        );
+        assert_eq!(
+            ParametersError::InvalidNoiseStdDev("4.2".to_string()).to_string(),
+            "Invalid noise standard deviation: 4.2, expected a value whose square rounds to an integer between 1 and 16"
+        );
+        assert_eq!(
+            ParametersError::InvalidModuliChain("test".to_string()).to_string(),
+            "Invalid moduli chain: test"
+        );
     }
 }
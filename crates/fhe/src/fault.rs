@@ -0,0 +1,224 @@
+//! Fault injection for robustness testing.
+//!
+//! This module is only available in the tests of this crate and with the
+//! `fault-injection` feature, which requires `std`. It corrupts objects at
+//! defined points, i.e. serialized bytes, ciphertext coefficients, and
+//! ciphertext seeds, and runs the public APIs on the corrupted objects while
+//! catching panics, so that tests can assert that corrupted inputs are
+//! rejected with an error instead of a panic.
+//!
+//! Note that corruptions which yield well-formed objects, e.g. a seed of the
+//! right length or a coefficient which is reduced, cannot be detected and
+//! result in silently wrong values: the BFV objects are not authenticated.
+
+extern crate std;
+
+use crate::bfv::Ciphertext;
+use crate::proto::bfv::Ciphertext as CiphertextProto;
+use crate::{Error, Result};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use prost::Message;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+extern crate alloc;
+
+/// The outcome of running a public API on a corrupted input.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome<T> {
+    /// The API accepted the input.
+    Accepted(T),
+    /// The API returned an error.
+    Rejected,
+    /// The API panicked, with the given message.
+    Panicked(String),
+}
+
+impl<T> Outcome<T> {
+    /// Reports whether the API panicked.
+    pub fn panicked(&self) -> bool {
+        matches!(self, Outcome::Panicked(_))
+    }
+}
+
+/// Runs `f`, catching panics.
+pub fn run<T, E, F>(f: F) -> Outcome<T>
+where
+    F: FnOnce() -> core::result::Result<T, E>,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Outcome::Accepted(value),
+        Ok(Err(_)) => Outcome::Rejected,
+        Err(payload) => Outcome::Panicked(
+            payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Returns all the strict prefixes of `bytes`, i.e. the truncated
+/// serializations.
+pub fn truncations(bytes: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    (0..bytes.len()).map(|len| bytes[..len].to_vec())
+}
+
+/// Returns copies of `bytes` in which one bit is flipped, for every `step`-th
+/// bit.
+pub fn bit_flips(bytes: &[u8], step: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    (0..bytes.len() * 8).step_by(step.max(1)).map(|bit| {
+        let mut corrupted = bytes.to_vec();
+        corrupted[bit / 8] ^= 1 << (bit % 8);
+        corrupted
+    })
+}
+
+/// Replaces the seed of a serialized ciphertext, which may have any length.
+pub fn with_seed(bytes: &[u8], seed: &[u8]) -> Result<Vec<u8>> {
+    let mut proto = CiphertextProto::decode(bytes).map_err(|_| Error::SerializationError)?;
+    proto.seed = seed.to_vec();
+    Ok(proto.encode_to_vec())
+}
+
+/// Returns the raw parts of a ciphertext, in the layout of
+/// [`Ciphertext::to_raw_parts`], in which the `index`-th coefficient of the
+/// `part`-th polynomial is set to `value`, which is not reduced.
+pub fn with_coefficient(
+    ct: &Ciphertext,
+    part: usize,
+    index: usize,
+    value: u64,
+) -> Result<Vec<Vec<u64>>> {
+    let mut parts = ct.to_raw_parts();
+    let coefficient = parts
+        .get_mut(part)
+        .and_then(|p| p.get_mut(index))
+        .ok_or_else(|| Error::UnspecifiedInput("Invalid coefficient".to_string()))?;
+    *coefficient = value;
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bit_flips, run, truncations, with_coefficient, with_seed, Outcome};
+    use crate::bfv::{
+        BfvParameters, Ciphertext, Encoding, EvaluationKey, EvaluationKeyBuilder, Plaintext,
+        PublicKey, RelinearizationKey, SecretKey,
+    };
+    use crate::Error;
+    use fhe_traits::{
+        Deserialize, DeserializeParametrized, FheDecoder, FheDecrypter, FheEncoder, FheEncrypter,
+        Serialize,
+    };
+    use rand::thread_rng;
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    /// Checks that the corruptions of `bytes` never make `f` panic, and that
+    /// the truncations are rejected, except the empty serialization when
+    /// `empty_is_valid`.
+    fn check<T, F>(bytes: &[u8], empty_is_valid: bool, f: F)
+    where
+        F: Fn(&[u8]) -> Result<T, Error>,
+    {
+        for (len, truncated) in truncations(bytes).enumerate() {
+            let outcome = run(|| f(&truncated));
+            if len == 0 && empty_is_valid {
+                assert!(matches!(outcome, Outcome::Accepted(_)));
+            } else {
+                assert!(
+                    matches!(outcome, Outcome::Rejected),
+                    "truncation to {len} bytes was not rejected"
+                );
+            }
+        }
+        for corrupted in bit_flips(bytes, 7) {
+            assert!(!run(|| f(&corrupted)).panicked());
+        }
+    }
+
+    #[test]
+    fn corrupted_serializations() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let rk = RelinearizationKey::new(&sk, &mut rng)?;
+        let ek = EvaluationKeyBuilder::new(&sk)?
+            .enable_row_rotation()?
+            .build(&mut rng)?;
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        check(&params.to_bytes(), false, |b| {
+            BfvParameters::try_deserialize(b)
+        });
+        check(&sk.to_bytes(), false, |b| SecretKey::from_bytes(b, &params));
+        check(&pk.to_bytes(), false, |b| PublicKey::from_bytes(b, &params));
+        check(&rk.to_bytes(), false, |b| {
+            RelinearizationKey::from_bytes(b, &params)
+        });
+        // The empty serialization is that of an evaluation key without any
+        // Galois key.
+        check(&ek.to_bytes(), true, |b| {
+            EvaluationKey::from_bytes(b, &params)
+        });
+        check(&ct.to_bytes(), false, |b| {
+            Ciphertext::from_bytes(b, &params)
+        });
+        check(&ct.to_bytes_expanded(), false, |b| {
+            Ciphertext::from_bytes(b, &params)
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn mismatched_seeds() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+        let bytes = ct.to_bytes();
+
+        for len in [0, 1, 31, 33, 64] {
+            let corrupted = with_seed(&bytes, &alloc::vec![7u8; len])?;
+            assert_eq!(
+                run(|| Ciphertext::from_bytes(&corrupted, &params)),
+                Outcome::Rejected
+            );
+        }
+
+        // A seed of the right length yields a well-formed ciphertext which
+        // decrypts to a wrong value.
+        let corrupted = with_seed(&bytes, &[7u8; 32])?;
+        let ct = Ciphertext::from_bytes(&corrupted, &params)?;
+        let decrypted = sk.try_decrypt(&ct)?;
+        assert_ne!(
+            Vec::<u64>::try_decode(&decrypted, Encoding::poly())?[..3],
+            [1, 2, 3]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted_coefficients() -> Result<(), Error> {
+        let mut rng = thread_rng();
+        let params = BfvParameters::default_arc(2, 16);
+        let sk = SecretKey::random(&params, &mut rng);
+        let pt = Plaintext::try_encode(&[1u64, 2, 3], Encoding::poly(), &params)?;
+        let ct: Ciphertext = sk.try_encrypt(&pt, &mut rng)?;
+
+        for value in [params.moduli()[0], u64::MAX] {
+            let parts = with_coefficient(&ct, 1, 3, value)?;
+            assert_eq!(
+                run(|| Ciphertext::from_raw_parts(parts, 0, &params)),
+                Outcome::Rejected
+            );
+        }
+        assert!(with_coefficient(&ct, 2, 0, 0).is_err());
+        Ok(())
+    }
+}
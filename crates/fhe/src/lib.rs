@@ -7,11 +7,24 @@
 mod errors;
 
 pub mod bfv;
+pub mod bgv;
+pub mod ckks;
+pub mod entropy;
+#[cfg(any(test, feature = "fault-injection"))]
+pub mod fault;
 pub mod mbfv;
+#[cfg(all(unix, any(test, feature = "protected-memory")))]
+pub(crate) mod protected;
 pub mod proto;
+
+#[cfg(all(feature = "protected-memory", not(unix)))]
+compile_error!("The `protected-memory` feature is only available on Unix targets");
+
 pub use errors::{Error, ParametersError, Result};
 
 // Test the source code included in the README.
+#[cfg(doctest)]
 #[macro_use]
 extern crate doc_comment;
+#[cfg(doctest)]
 doctest!("../README.md");
@@ -0,0 +1,192 @@
+extern crate alloc;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, PublicKey};
+use crate::errors::Result;
+use crate::Error;
+use fhe_traits::{FheEncoder, FheEncrypter};
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use super::Aggregate;
+
+/// A party's encrypted share of an additively secret-shared input.
+///
+/// An input `x` is split into shares `x_1 + ... + x_k = x` modulo the plaintext
+/// modulus with [`InputShare::share`], e.g. by the owner of the input, who
+/// sends one share to each party. Each party encodes and encrypts its share
+/// under the (collective) public key with [`InputShare::new`], and the server
+/// uses the [`Aggregate`] impl to sum the shares into a [`Ciphertext`] of `x`.
+/// The encodings are linear, so that the sum decrypts to `x` with any
+/// encoding, and no party nor the server learns `x`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InputShare {
+    pub(crate) ct: Ciphertext,
+}
+
+impl InputShare {
+    /// Splits `values` into `num_shares` additive shares modulo the plaintext
+    /// modulus.
+    ///
+    /// All the shares but the last are sampled uniformly with `rng`, so that
+    /// any `num_shares - 1` shares are independent of the values. Returns an
+    /// error if there are no shares, more values than the degree, or values
+    /// which are not reduced modulo the plaintext modulus.
+    pub fn share<R: RngCore + CryptoRng>(
+        values: &[u64],
+        num_shares: usize,
+        par: &Arc<BfvParameters>,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<u64>>> {
+        if num_shares == 0 {
+            return Err(Error::TooFewValues(0, 1));
+        }
+        if values.len() > par.degree() {
+            return Err(Error::TooManyValues(values.len(), par.degree()));
+        }
+        if values.iter().any(|v| *v >= par.plaintext()) {
+            return Err(Error::UnspecifiedInput(
+                "The values must be reduced modulo the plaintext modulus".to_string(),
+            ));
+        }
+
+        let mut shares = Vec::with_capacity(num_shares);
+        let mut last = Zeroizing::new(values.to_vec());
+        for _ in 1..num_shares {
+            let share = par.plaintext.random_vec(values.len(), rng);
+            par.plaintext.sub_vec(&mut last, &share);
+            shares.push(share);
+        }
+        shares.push(last.to_vec());
+        Ok(shares)
+    }
+
+    /// Encodes a share with `encoding` and encrypts it with the public key.
+    pub fn new<R: RngCore + CryptoRng>(
+        share: &[u64],
+        encoding: Encoding,
+        pk: &PublicKey,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let pt = Plaintext::try_encode(share, encoding, &pk.par)?;
+        let ct = pk.try_encrypt(&pt, rng)?;
+        Ok(Self { ct })
+    }
+}
+
+impl Aggregate<InputShare> for Ciphertext {
+    fn from_shares<T>(iter: T) -> Result<Self>
+    where
+        T: IntoIterator<Item = InputShare>,
+    {
+        let mut shares = iter.into_iter();
+        let mut ct = shares.next().ok_or(Error::TooFewValues(0, 1))?.ct;
+        for sh in shares {
+            if sh.ct.par != ct.par || sh.ct.level != ct.level {
+                return Err(Error::UnspecifiedInput(
+                    "The input shares must have the same parameters and level".to_string(),
+                ));
+            }
+            ct += &sh.ct;
+        }
+        Ok(ct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    use fhe_traits::FheDecoder;
+    use rand::thread_rng;
+
+    use crate::{
+        bfv::{BfvParameters, Ciphertext, Encoding, Plaintext, PublicKey, SecretKey},
+        mbfv::{Aggregate, AggregateIter, CommonRandomPoly, DecryptionShare, PublicKeyShare},
+    };
+
+    use super::InputShare;
+
+    const NUM_PARTIES: usize = 5;
+
+    #[test]
+    fn shares_sum_to_values() {
+        let mut rng = thread_rng();
+        let par = BfvParameters::default_arc(1, 16);
+        let values = par.plaintext.random_vec(10, &mut rng);
+        for num_shares in 1..=NUM_PARTIES {
+            let shares = InputShare::share(&values, num_shares, &par, &mut rng).unwrap();
+            assert_eq!(shares.len(), num_shares);
+            let mut sum = alloc::vec![0u64; values.len()];
+            for share in &shares {
+                assert_eq!(share.len(), values.len());
+                par.plaintext.add_vec(&mut sum, share);
+            }
+            assert_eq!(sum, values);
+        }
+
+        assert!(InputShare::share(&values, 0, &par, &mut rng).is_err());
+        assert!(InputShare::share(&[par.plaintext()], 2, &par, &mut rng).is_err());
+        let too_many = alloc::vec![0u64; par.degree() + 1];
+        assert!(InputShare::share(&too_many, 2, &par, &mut rng).is_err());
+    }
+
+    #[test]
+    fn encrypt_shares_aggregate_decrypt() {
+        let mut rng = thread_rng();
+        for par in [
+            BfvParameters::default_arc(1, 16),
+            BfvParameters::default_arc(6, 32),
+        ] {
+            for encoding in [Encoding::poly(), Encoding::simd()] {
+                let crp = CommonRandomPoly::new(&par, &mut rng).unwrap();
+                let sk_shares = (0..NUM_PARTIES)
+                    .map(|_| SecretKey::random(&par, &mut rng))
+                    .collect::<Vec<_>>();
+                let public_key: PublicKey = sk_shares
+                    .iter()
+                    .map(|sk| PublicKeyShare::new(sk, crp.clone(), &mut rng))
+                    .aggregate()
+                    .unwrap();
+
+                // The owner of the input shares it among the parties, which each
+                // encrypt their share.
+                let values = par.plaintext.random_vec(par.degree(), &mut rng);
+                let shares = InputShare::share(&values, NUM_PARTIES, &par, &mut rng).unwrap();
+                let ct: Ciphertext = shares
+                    .iter()
+                    .map(|share| InputShare::new(share, encoding.clone(), &public_key, &mut rng))
+                    .aggregate()
+                    .unwrap();
+
+                // The parties decrypt the input collectively.
+                let ct = Arc::new(ct);
+                let pt: Plaintext = sk_shares
+                    .iter()
+                    .map(|sk| DecryptionShare::new(sk, &ct, &mut rng))
+                    .aggregate()
+                    .unwrap();
+                assert_eq!(Vec::<u64>::try_decode(&pt, encoding).unwrap(), values);
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_levels() {
+        let mut rng = thread_rng();
+        let par = BfvParameters::default_arc(6, 32);
+        let sk = SecretKey::random(&par, &mut rng);
+        let pk = PublicKey::new(&sk, &mut rng);
+        let shares = [
+            InputShare::new(&[1, 2], Encoding::poly(), &pk, &mut rng).unwrap(),
+            InputShare::new(&[3, 4], Encoding::poly_at_level(1), &pk, &mut rng).unwrap(),
+        ];
+        assert!(Ciphertext::from_shares(shares).is_err());
+        assert!(Ciphertext::from_shares(Vec::<InputShare>::new()).is_err());
+    }
+}
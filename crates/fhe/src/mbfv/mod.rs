@@ -3,6 +3,7 @@
 
 mod aggregate;
 mod crp;
+mod input_share;
 mod public_key_gen;
 mod public_key_switch;
 mod relin_key_gen;
@@ -11,6 +12,7 @@ mod secret_key_switch;
 
 pub use aggregate::{Aggregate, AggregateIter};
 pub use crp::CommonRandomPoly;
+pub use input_share::InputShare;
 pub use public_key_gen::PublicKeyShare;
 pub use public_key_switch::PublicKeySwitchShare;
 pub use relin_key_gen::{RelinKeyGenerator, RelinKeyShare};
@@ -12,7 +12,7 @@ use itertools::Itertools;
 use rand::{CryptoRng, RngCore};
 use zeroize::Zeroizing;
 
-use crate::bfv::{BfvParameters, Ciphertext, Plaintext, SecretKey};
+use crate::bfv::{BfvParameters, Ciphertext, Plaintext, SecretKey, Sparsity};
 use crate::{Error, Result};
 
 use super::Aggregate;
@@ -174,6 +174,7 @@ impl Aggregate<DecryptionShare> for Plaintext {
             encoding: None,
             poly_ntt: poly,
             level: ct.level,
+            sparsity: Sparsity::Dense,
         };
 
         Ok(pt)
@@ -183,7 +184,7 @@ impl Aggregate<DecryptionShare> for Plaintext {
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    
+
     use alloc::sync::Arc;
     use alloc::vec;
     use alloc::vec::Vec;
@@ -0,0 +1,303 @@
+//! Protected memory for secret key material.
+//!
+//! With the `protected-memory` feature, which is only available on Unix
+//! targets, the coefficients of a [`SecretKey`](crate::bfv::SecretKey) are
+//! stored in their own pages, which are locked in memory so that they are
+//! never swapped to disk, excluded from core dumps where supported, surrounded
+//! by inaccessible guard pages, and wiped on drop. The polynomials cached by a
+//! [`SecretKeyExpanded`](crate::bfv::SecretKeyExpanded) are locked in memory
+//! while they are cached.
+//!
+//! When the operating system refuses to allocate or lock protected memory,
+//! e.g. when the limit of locked memory of the process is reached, the
+//! coefficients fall back to heap memory, which is still wiped on drop, and
+//! the pages are left unlocked. Whether the memory is locked can be queried
+//! with [`SecretKey::is_memory_locked`](crate::bfv::SecretKey::is_memory_locked)
+//! and [`SecretKeyExpanded::is_memory_locked`](crate::bfv::SecretKeyExpanded::is_memory_locked).
+
+use core::fmt;
+use core::mem::{size_of, size_of_val};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+use zeroize::Zeroize;
+
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Excludes the pages of `len` bytes at `addr` from the core dumps.
+fn exclude_from_core_dumps(addr: *mut libc::c_void, len: usize) {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe {
+        libc::madvise(addr, len, libc::MADV_DONTDUMP);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let _ = (addr, len);
+}
+
+/// A slice of `i64` stored in locked pages between two guard pages, or on the
+/// heap if the pages could not be locked, and wiped on drop.
+pub(crate) struct ProtectedSlice {
+    storage: Storage,
+    len: usize,
+}
+
+enum Storage {
+    Locked {
+        region: NonNull<u8>,
+        page: usize,
+        data_len: usize,
+    },
+    Heap(Box<[i64]>),
+}
+
+// The protected slice owns its pages, as a `Box<[i64]>` owns its allocation.
+unsafe impl Send for ProtectedSlice {}
+unsafe impl Sync for ProtectedSlice {}
+
+impl ProtectedSlice {
+    /// Copies `values` into protected memory, or on the heap if the
+    /// operating system refuses to allocate or lock the pages.
+    pub(crate) fn new(values: &[i64]) -> Self {
+        let page = page_size();
+        let data_len = size_of_val(values).div_ceil(page).max(1) * page;
+        let region_len = data_len + 2 * page;
+        unsafe {
+            let region = libc::mmap(
+                ptr::null_mut(),
+                region_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if region == libc::MAP_FAILED {
+                return Self::on_heap(values);
+            }
+            let region = region as *mut u8;
+            let data = region.add(page);
+            if libc::mprotect(region as *mut _, page, libc::PROT_NONE) != 0
+                || libc::mprotect(data.add(data_len) as *mut _, page, libc::PROT_NONE) != 0
+                || libc::mlock(data as *const _, data_len) != 0
+            {
+                libc::munmap(region as *mut _, region_len);
+                return Self::on_heap(values);
+            }
+            exclude_from_core_dumps(data as *mut _, data_len);
+
+            let mut out = Self {
+                storage: Storage::Locked {
+                    region: NonNull::new_unchecked(region),
+                    page,
+                    data_len,
+                },
+                len: values.len(),
+            };
+            out.copy_from_slice(values);
+            out
+        }
+    }
+
+    /// Copies `values` on the heap, as a fallback when the pages cannot be
+    /// locked.
+    fn on_heap(values: &[i64]) -> Self {
+        Self {
+            storage: Storage::Heap(values.into()),
+            len: values.len(),
+        }
+    }
+
+    /// Reports whether the values are stored in locked pages.
+    pub(crate) fn is_locked(&self) -> bool {
+        matches!(self.storage, Storage::Locked { .. })
+    }
+}
+
+impl Drop for ProtectedSlice {
+    fn drop(&mut self) {
+        self.zeroize();
+        if let Storage::Locked {
+            region,
+            page,
+            data_len,
+        } = self.storage
+        {
+            unsafe {
+                let data = region.as_ptr().add(page);
+                libc::munlock(data as *const _, data_len);
+                libc::munmap(region.as_ptr() as *mut _, data_len + 2 * page);
+            }
+        }
+    }
+}
+
+impl Deref for ProtectedSlice {
+    type Target = [i64];
+
+    fn deref(&self) -> &[i64] {
+        match &self.storage {
+            // The values end at the trailing guard page so that an overflow
+            // faults.
+            Storage::Locked {
+                region,
+                page,
+                data_len,
+            } => unsafe {
+                let ptr = region
+                    .as_ptr()
+                    .add(page + data_len - self.len * size_of::<i64>());
+                slice::from_raw_parts(ptr as *const i64, self.len)
+            },
+            Storage::Heap(values) => values,
+        }
+    }
+}
+
+impl DerefMut for ProtectedSlice {
+    fn deref_mut(&mut self) -> &mut [i64] {
+        match &mut self.storage {
+            Storage::Locked {
+                region,
+                page,
+                data_len,
+            } => unsafe {
+                let ptr = region
+                    .as_ptr()
+                    .add(*page + *data_len - self.len * size_of::<i64>());
+                slice::from_raw_parts_mut(ptr as *mut i64, self.len)
+            },
+            Storage::Heap(values) => values,
+        }
+    }
+}
+
+impl AsRef<[i64]> for ProtectedSlice {
+    fn as_ref(&self) -> &[i64] {
+        self
+    }
+}
+
+impl From<Vec<i64>> for ProtectedSlice {
+    fn from(mut values: Vec<i64>) -> Self {
+        let out = Self::new(&values);
+        values.zeroize();
+        out
+    }
+}
+
+impl Clone for ProtectedSlice {
+    fn clone(&self) -> Self {
+        Self::new(self)
+    }
+}
+
+impl PartialEq for ProtectedSlice {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for ProtectedSlice {}
+
+impl fmt::Debug for ProtectedSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProtectedSlice")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Zeroize for ProtectedSlice {
+    fn zeroize(&mut self) {
+        self.deref_mut().zeroize()
+    }
+}
+
+/// Locks the pages of a buffer in memory until dropped.
+///
+/// The pages may be shared with other allocations, which are then unlocked
+/// as well on drop, since the locks of a page are not counted.
+#[derive(Debug)]
+pub(crate) struct LockedPages {
+    start: usize,
+    len: usize,
+    locked: bool,
+}
+
+impl LockedPages {
+    /// Locks the pages of `buffer` in memory. The pages are left unlocked if
+    /// the operating system refuses to lock them.
+    pub(crate) fn new<T>(buffer: &[T]) -> Self {
+        let page = page_size();
+        let start = buffer.as_ptr() as usize & !(page - 1);
+        let end = (buffer.as_ptr() as usize + size_of_val(buffer)).next_multiple_of(page);
+        let len = end - start;
+        let locked = unsafe { libc::mlock(start as *const _, len) == 0 };
+        exclude_from_core_dumps(start as *mut _, len);
+        Self { start, len, locked }
+    }
+
+    /// Reports whether the pages are locked in memory.
+    // The module is also compiled for the tests without the feature.
+    #[cfg_attr(not(feature = "protected-memory"), allow(dead_code))]
+    pub(crate) fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Drop for LockedPages {
+    fn drop(&mut self) {
+        if self.locked {
+            unsafe {
+                libc::munlock(self.start as *const _, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LockedPages, ProtectedSlice};
+    extern crate alloc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use zeroize::Zeroize;
+
+    #[test]
+    fn protected_slice() {
+        for len in [0, 1, 511, 512, 513, 4096] {
+            let values = (0..len as i64).map(|i| i - 7).collect::<Vec<_>>();
+            let mut protected = ProtectedSlice::from(values.clone());
+            assert_eq!(*protected, values);
+            assert_eq!(protected.clone(), protected);
+
+            protected.zeroize();
+            assert_eq!(*protected, vec![0; len]);
+        }
+    }
+
+    #[test]
+    fn protected_slice_on_heap() {
+        let values = (0..513i64).map(|i| i - 7).collect::<Vec<_>>();
+        let mut protected = ProtectedSlice::on_heap(&values);
+        assert!(!protected.is_locked());
+        assert_eq!(*protected, values);
+        assert_eq!(protected, ProtectedSlice::new(&values));
+
+        protected.zeroize();
+        assert_eq!(*protected, vec![0; 513]);
+    }
+
+    #[test]
+    fn locked_pages() {
+        let values = vec![3u64; 1000];
+        let locked = LockedPages::new(&values);
+        drop(locked);
+        assert_eq!(values, vec![3u64; 1000]);
+    }
+}
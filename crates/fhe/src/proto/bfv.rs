@@ -57,6 +57,8 @@ pub struct EvaluationKey {
     pub ciphertext_level: u32,
     #[prost(uint32, tag = "4")]
     pub evaluation_key_level: u32,
+    #[prost(bytes = "vec", tag = "5")]
+    pub seed: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -76,3 +78,13 @@ pub struct PublicKey {
     #[prost(message, optional, tag = "1")]
     pub c: ::core::option::Option<Ciphertext>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Plaintext {
+    #[prost(uint64, repeated, tag = "1")]
+    pub value: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint32, tag = "2")]
+    pub encoding: u32,
+    #[prost(uint32, tag = "3")]
+    pub level: u32,
+}
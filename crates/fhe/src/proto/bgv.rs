@@ -0,0 +1,12 @@
+#![allow(missing_docs)]
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Ciphertext {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub c: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(uint32, tag = "2")]
+    pub level: u32,
+    #[prost(uint64, tag = "3")]
+    pub correction_factor: u64,
+}
@@ -2,3 +2,9 @@
 
 /// Protobuf for the BFV encryption scheme.
 pub mod bfv;
+
+/// Protobuf for the BGV encryption scheme.
+pub mod bgv;
+
+/// Protobuf for the CKKS encryption scheme.
+pub mod ckks;
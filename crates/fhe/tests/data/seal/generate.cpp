@@ -0,0 +1,58 @@
+// Generates the SEAL serializations of this directory with Microsoft SEAL
+// 4.0.0, built without compression libraries:
+//
+//     g++ -std=c++17 -O2 generate.cpp -lseal-4.0 -o generate && ./generate
+//
+// The parameters have a degree of 16, the plaintext modulus 1153 and two
+// ciphertext moduli of 50 bits followed by a special modulus of 60 bits. The
+// secret key and the Galois keys for the exponents 3 and 31 are random; the
+// ciphertext encrypts the polynomial 0 + 1 x + ... + 15 x^15, and
+// `rotated.bin` is the ciphertext after the Galois automorphism of exponent 3.
+
+#include "seal/seal.h"
+#include <fstream>
+#include <functional>
+#include <iostream>
+using namespace seal;
+using namespace std;
+
+static void save(const string &name, const function<void(ostream &)> &f)
+{
+    ofstream out(name, ios::binary);
+    f(out);
+}
+
+int main()
+{
+    EncryptionParameters parms(scheme_type::bfv);
+    parms.set_poly_modulus_degree(16);
+    parms.set_coeff_modulus(CoeffModulus::Create(16, { 50, 50, 60 }));
+    parms.set_plain_modulus(1153);
+    SEALContext context(parms, true, sec_level_type::none);
+    for (auto &q : parms.coeff_modulus())
+    {
+        cout << "modulus " << q.value() << endl;
+    }
+
+    KeyGenerator keygen(context);
+    SecretKey sk = keygen.secret_key();
+    GaloisKeys gk;
+    keygen.create_galois_keys(vector<uint32_t>{ 3, 31 }, gk);
+
+    Plaintext pt(16);
+    for (size_t i = 0; i < 16; i++)
+    {
+        pt[i] = i;
+    }
+    Encryptor encryptor(context, sk);
+    Ciphertext ct;
+    encryptor.encrypt_symmetric(pt, ct);
+    Evaluator evaluator(context);
+    Ciphertext rotated;
+    evaluator.apply_galois(ct, 3, gk, rotated);
+
+    save("secret_key.bin", [&](ostream &s) { sk.save(s, compr_mode_type::none); });
+    save("ciphertext.bin", [&](ostream &s) { ct.save(s, compr_mode_type::none); });
+    save("galois_keys.bin", [&](ostream &s) { gk.save(s, compr_mode_type::none); });
+    save("rotated.bin", [&](ostream &s) { rotated.save(s, compr_mode_type::none); });
+}